@@ -17,7 +17,7 @@ pub fn large_file(c: &mut Criterion) {
             let sources = SourceMap::new([("large.qs".into(), INPUT.into())], None);
             let (_, reports) = compile(
                 &store,
-                &[std],
+                &[(std, None)],
                 sources,
                 PackageType::Exe,
                 RuntimeCapabilityFlags::all(),
@@ -3,19 +3,28 @@
 
 use clap::{crate_version, ArgGroup, Parser, ValueEnum};
 use log::info;
-use miette::{Context, IntoDiagnostic, Report};
-use qsc::compile::compile;
-use qsc_codegen::qir_base;
-use qsc_data_structures::language_features::LanguageFeatures;
+use miette::{Context, Diagnostic, IntoDiagnostic, Report};
+use qsc::{compile::compile_with_options, lint::Registry, CompileOptions};
+use qsc_codegen::{circuit, defer, gate_set::GateSet, qasm, qir_adaptive, qir_base};
+use qsc_data_structures::{
+    language_features::LanguageFeatures,
+    line_column::{Encoding, Position},
+    lints::Severity,
+};
 use qsc_frontend::{
-    compile::{PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName},
+    compile::{
+        artifact, CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap,
+        SourceName,
+    },
     error::WithSource,
 };
 use qsc_hir::hir::{Package, PackageId};
 use qsc_passes::PackageType;
 use qsc_project::{FileSystem, Manifest, StdFs};
 use std::{
-    concat, fs,
+    concat,
+    fmt::Write,
+    fs,
     io::{self, Read},
     path::{Path, PathBuf},
     process::ExitCode,
@@ -25,6 +34,7 @@ use std::{
 #[derive(Debug, Parser)]
 #[command(version = concat!(crate_version!(), " (", env!("QSHARP_GIT_HASH"), ")"), arg_required_else_help(false))]
 #[clap(group(ArgGroup::new("input").args(["entry", "sources"]).required(false).multiple(true)))]
+#[allow(clippy::struct_excessive_bools)]
 struct Cli {
     /// Disable automatic inclusion of the standard library.
     #[arg(long)]
@@ -57,12 +67,94 @@ struct Cli {
     /// Language features to compile with
     #[arg(short, long)]
     features: Vec<String>,
+
+    /// Symbols to define for `@Config` attributes
+    #[arg(long = "define", value_name = "SYMBOL")]
+    defines: Vec<String>,
+
+    /// Warning codes to not report
+    #[arg(long = "allow", value_name = "CODE")]
+    allow: Vec<String>,
+
+    /// Warning codes to report as errors
+    #[arg(long = "deny", value_name = "CODE")]
+    deny: Vec<String>,
+
+    /// Run the recommended lint rules
+    #[arg(long)]
+    lint: bool,
+
+    /// Inline calls to callables with at most this many statements and expressions
+    #[arg(long, value_name = "SIZE", default_value_t = 0)]
+    inline_threshold: usize,
+
+    /// Unroll `for` loops over constant ranges with at most this many iterations
+    #[arg(long, value_name = "ITERATIONS", default_value_t = 0)]
+    unroll_budget: usize,
+
+    /// Fold constant expressions and propagate immutable constants
+    #[arg(long)]
+    const_fold: bool,
+
+    /// The QIR profile of the target to emit QIR for
+    #[arg(long, value_enum, default_value_t = Profile::Base)]
+    profile: Profile,
+
+    /// Cancel inverse gates, fuse rotations, and remove gates that cannot affect a measurement in
+    /// emitted OpenQASM and circuits
+    #[arg(long)]
+    optimize: bool,
+
+    /// A JSON file with the native gates of the target and decompositions of other gates into
+    /// them, used to rewrite the gates in emitted OpenQASM
+    #[arg(long, value_name = "FILE")]
+    gate_set: Option<PathBuf>,
+
+    /// Move measurements to the end of emitted base profile QIR, replacing branches on their
+    /// results with controlled gates
+    #[arg(long)]
+    defer_measurements: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum Emit {
     Hir,
+    /// The FIR that the evaluator runs, lowered from the HIR after passes.
+    Fir,
+    /// The items of the package with their signatures, attributes, and documentation, as
+    /// versioned JSON for tools outside the compiler.
+    Api,
+    /// The capabilities that each callable needs from the target, with the source locations of
+    /// the code that needs them.
+    Capabilities,
     Qir,
+    /// OpenQASM 3 generated from the program without running it, which needs its loops unrolled.
+    Qasm,
+    /// A circuit diagram of the operations the program applies when run once, as text and SVG.
+    Circuit,
+    /// The compiled standard library, which a build of the compiler can load instead of compiling
+    /// the standard library itself.
+    Std,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Profile {
+    /// Quantum operations and measurements with no classical control flow.
+    Base,
+    /// Branches on measurement results and integer computations with them.
+    Adaptive,
+}
+
+impl Profile {
+    fn capabilities(self) -> RuntimeCapabilityFlags {
+        match self {
+            Profile::Base => RuntimeCapabilityFlags::empty(),
+            Profile::Adaptive => {
+                RuntimeCapabilityFlags::ForwardBranching
+                    | RuntimeCapabilityFlags::IntegerComputations
+            }
+        }
+    }
 }
 
 fn main() -> miette::Result<ExitCode> {
@@ -71,15 +163,10 @@ fn main() -> miette::Result<ExitCode> {
     let mut store = PackageStore::new(qsc::compile::core());
     let mut dependencies = Vec::new();
 
-    let (package_type, capabilities) = if cli.emit.contains(&Emit::Qir) {
-        (PackageType::Exe, RuntimeCapabilityFlags::empty())
-    } else {
-        (PackageType::Lib, RuntimeCapabilityFlags::all())
-    };
+    let (package_type, capabilities) = package_type_and_capabilities(&cli);
 
-    if !cli.nostdlib {
-        dependencies.push(store.insert(qsc::compile::std(&store, capabilities)));
-    }
+    let std_id = (!cli.nostdlib).then(|| store.insert(qsc::compile::std(&store, capabilities)));
+    dependencies.extend(std_id.map(|id| (id, None)));
 
     let mut features = LanguageFeatures::from_iter(cli.features);
 
@@ -106,26 +193,71 @@ fn main() -> miette::Result<ExitCode> {
 
     let entry = cli.entry.unwrap_or_default();
     let sources = SourceMap::new(sources, Some(entry.into()));
-    let (unit, errors) = compile(
+    let warnings = cli
+        .allow
+        .into_iter()
+        .map(|code| (code, Severity::Allow))
+        .chain(cli.deny.into_iter().map(|code| (code, Severity::Deny)))
+        .collect();
+    let options = CompileOptions {
+        defines: cli.defines.into_iter().map(Into::into).collect(),
+        warnings,
+        lint_rules: if cli.lint {
+            Registry::recommended()
+        } else {
+            Registry::default()
+        },
+        inline_threshold: cli.inline_threshold,
+        unroll_budget: cli.unroll_budget,
+        const_fold: cli.const_fold,
+        ..CompileOptions::default()
+    };
+    let (unit, errors) = compile_with_options(
         &store,
         &dependencies,
         sources,
         package_type,
         capabilities,
         features,
+        &options,
     );
     let package_id = store.insert(unit);
     let unit = store.get(package_id).expect("package should be in store");
 
+    for warning in &unit.warnings {
+        let warning = WithSource::from_map(&unit.sources, warning.clone());
+        eprintln!("{:?}", Report::new(warning));
+    }
+
     let out_dir = cli.out_dir.as_ref().map_or(".".as_ref(), PathBuf::as_path);
     for emit in &cli.emit {
         match emit {
             Emit::Hir => emit_hir(&unit.package, out_dir)?,
-            Emit::Qir => {
-                if errors.is_empty() {
-                    emit_qir(out_dir, &store, package_id)?;
+            Emit::Std => {
+                if let Some(std_id) = std_id {
+                    let std = store.get(std_id).expect("package should be in store");
+                    emit_std(std, capabilities, out_dir)?;
                 }
             }
+            _ if !errors.is_empty() => {}
+            Emit::Fir => emit_fir(&unit.package, out_dir)?,
+            Emit::Api => emit_api(&unit.package, out_dir)?,
+            Emit::Capabilities => emit_capabilities(&store, unit, out_dir)?,
+            Emit::Qir => emit_qir(
+                out_dir,
+                &store,
+                package_id,
+                cli.profile,
+                cli.defer_measurements,
+            )?,
+            Emit::Qasm => emit_qasm(
+                out_dir,
+                &store,
+                package_id,
+                cli.gate_set.as_deref(),
+                cli.optimize,
+            )?,
+            Emit::Circuit => emit_circuit(out_dir, &store, package_id, cli.optimize)?,
         }
     }
 
@@ -140,6 +272,23 @@ fn main() -> miette::Result<ExitCode> {
     }
 }
 
+fn package_type_and_capabilities(cli: &Cli) -> (PackageType, RuntimeCapabilityFlags) {
+    // The adaptive profile generator checks each use of a value that depends on a measurement
+    // against the capabilities of the profile itself, and deferring measurements removes the
+    // branches on them, so in both cases the program is compiled without limits.
+    if cli.emit.contains(&Emit::Qir) {
+        let capabilities = match cli.profile {
+            Profile::Base if !cli.defer_measurements => RuntimeCapabilityFlags::empty(),
+            Profile::Base | Profile::Adaptive => RuntimeCapabilityFlags::all(),
+        };
+        (PackageType::Exe, capabilities)
+    } else if cli.emit.contains(&Emit::Qasm) || cli.emit.contains(&Emit::Circuit) {
+        (PackageType::Exe, RuntimeCapabilityFlags::all())
+    } else {
+        (PackageType::Lib, RuntimeCapabilityFlags::all())
+    }
+}
+
 fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceContents)> {
     let path = path.as_ref();
     if path.as_os_str() == "-" {
@@ -165,27 +314,184 @@ fn emit_hir(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
         "Writing HIR output file to: {}",
         path.to_str().unwrap_or_default()
     );
-    fs::write(&path, package.to_string())
+    fs::write(&path, qsc_hir::pretty::package_to_string(package))
         .into_diagnostic()
         .with_context(|| format!("could not emit HIR file `{}`", path.display()))
 }
 
-fn emit_qir(out_dir: &Path, store: &PackageStore, package_id: PackageId) -> Result<(), Report> {
-    let path = out_dir.join("qir.ll");
-    let result = qir_base::generate_qir(store, package_id);
-    match result {
-        Ok(qir) => {
-            info!(
-                "Writing QIR output file to: {}",
-                path.to_str().unwrap_or_default()
+fn emit_api(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
+    let path = dir.as_ref().join("api.json");
+    info!(
+        "Writing API output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, qsc::api::export_package(package))
+        .into_diagnostic()
+        .with_context(|| format!("could not emit API file `{}`", path.display()))
+}
+
+fn emit_capabilities(
+    store: &PackageStore,
+    unit: &CompileUnit,
+    dir: impl AsRef<Path>,
+) -> miette::Result<()> {
+    let path = dir.as_ref().join("capabilities.txt");
+    info!(
+        "Writing capabilities output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    let mut report = String::new();
+    for callable in qsc::callable_capabilities(store.core(), &unit.package) {
+        writeln!(
+            report,
+            "{}: {}",
+            callable.name,
+            capability_names(callable.capabilities)
+        )
+        .expect("writing to string should succeed");
+        for error in &callable.uses {
+            let offset = error
+                .labels()
+                .and_then(|mut labels| labels.next())
+                .map_or(0, |label| label.offset());
+            let offset = u32::try_from(offset).expect("offset should fit in u32");
+            let source = unit
+                .sources
+                .find_by_offset(offset)
+                .expect("source should exist for offset");
+            let position = Position::from_utf8_byte_offset(
+                Encoding::Utf8,
+                &source.contents,
+                offset - source.offset,
             );
-            fs::write(&path, qir)
-                .into_diagnostic()
-                .with_context(|| format!("could not emit QIR file `{}`", path.display()))
-        }
-        Err((error, _)) => {
-            let unit = store.get(package_id).expect("package should be in store");
-            Err(Report::new(WithSource::from_map(&unit.sources, error)))
+            writeln!(
+                report,
+                "    {}:{}:{}: {error} ({})",
+                source.name,
+                position.line + 1,
+                position.column + 1,
+                capability_names(error.capability())
+            )
+            .expect("writing to string should succeed");
         }
     }
+    fs::write(&path, report)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit capabilities file `{}`", path.display()))
+}
+
+fn capability_names(capabilities: RuntimeCapabilityFlags) -> String {
+    if capabilities.is_empty() {
+        "none".to_string()
+    } else {
+        let names: Vec<_> = capabilities.iter_names().map(|(name, _)| name).collect();
+        names.join(" | ")
+    }
+}
+
+fn emit_fir(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
+    let path = dir.as_ref().join("fir.txt");
+    info!(
+        "Writing FIR output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    let fir = qsc_eval::lower::Lowerer::new().lower_package(package);
+    fs::write(&path, qsc_fir::pretty::package_to_string(&fir))
+        .into_diagnostic()
+        .with_context(|| format!("could not emit FIR file `{}`", path.display()))
+}
+
+fn emit_qasm(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+    gate_set: Option<&Path>,
+    optimize: bool,
+) -> Result<(), Report> {
+    let gate_set = gate_set
+        .map(|path| {
+            let json = fs::read_to_string(path)
+                .into_diagnostic()
+                .with_context(|| format!("could not read gate set file `{}`", path.display()))?;
+            GateSet::from_json(&json)
+                .map_err(Report::new)
+                .with_context(|| format!("could not load gate set file `{}`", path.display()))
+        })
+        .transpose()?;
+    let path = out_dir.join("program.qasm");
+    let unit = store.get(package_id).expect("package should be in store");
+    let qasm = qasm::generate_qasm(store, package_id, gate_set.as_ref(), optimize)
+        .map_err(|error| Report::new(WithSource::from_map(&unit.sources, error)))?;
+    info!(
+        "Writing OpenQASM output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, qasm)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit OpenQASM file `{}`", path.display()))
+}
+
+fn emit_std(
+    std: &CompileUnit,
+    capabilities: RuntimeCapabilityFlags,
+    dir: impl AsRef<Path>,
+) -> miette::Result<()> {
+    let path = dir.as_ref().join("std.qsclib");
+    info!(
+        "Writing standard library artifact to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, artifact::save(std, capabilities))
+        .into_diagnostic()
+        .with_context(|| format!("could not emit standard library `{}`", path.display()))
+}
+
+fn emit_circuit(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+    optimize: bool,
+) -> Result<(), Report> {
+    let unit = store.get(package_id).expect("package should be in store");
+    let circuit = circuit::from_trace(store, package_id, None, optimize)
+        .map_err(|(error, _)| Report::new(WithSource::from_map(&unit.sources, error)))?;
+    for (path, contents) in [
+        (out_dir.join("circuit.txt"), circuit.to_ascii()),
+        (out_dir.join("circuit.svg"), circuit.to_svg()),
+    ] {
+        info!(
+            "Writing circuit output file to: {}",
+            path.to_str().unwrap_or_default()
+        );
+        fs::write(&path, contents)
+            .into_diagnostic()
+            .with_context(|| format!("could not emit circuit file `{}`", path.display()))?;
+    }
+    Ok(())
+}
+
+fn emit_qir(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+    profile: Profile,
+    defer_measurements: bool,
+) -> Result<(), Report> {
+    let path = out_dir.join("qir.ll");
+    let unit = store.get(package_id).expect("package should be in store");
+    let qir = match profile {
+        Profile::Base if defer_measurements => defer::generate_qir(store, package_id)
+            .map_err(|error| Report::new(WithSource::from_map(&unit.sources, error)))?,
+        Profile::Base => qir_base::generate_qir(store, package_id)
+            .map_err(|(error, _)| Report::new(WithSource::from_map(&unit.sources, error)))?,
+        Profile::Adaptive => qir_adaptive::generate_qir(store, package_id, profile.capabilities())
+            .map_err(|error| Report::new(WithSource::from_map(&unit.sources, error)))?,
+    };
+    info!(
+        "Writing QIR output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, qir)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit QIR file `{}`", path.display()))
 }
@@ -0,0 +1,31 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use clap::{crate_version, Parser};
+use miette::{IntoDiagnostic, Result};
+use std::{
+    io::{self, BufReader},
+    net::TcpListener,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "qsdap", version = concat!(crate_version!(), " (", env!("QSHARP_GIT_HASH"), ")"))]
+#[command(author, about, next_line_help = true)]
+struct Cli {
+    /// Listen for a single client on the given TCP port instead of using standard input and output.
+    #[arg(long)]
+    port: Option<u16>,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.port {
+        Some(port) => {
+            let listener = TcpListener::bind(("127.0.0.1", port)).into_diagnostic()?;
+            let (stream, _) = listener.accept().into_diagnostic()?;
+            let reader = BufReader::new(stream.try_clone().into_diagnostic()?);
+            qsc::dap::serve(reader, stream).into_diagnostic()
+        }
+        None => qsc::dap::serve(io::stdin().lock(), io::stdout().lock()).into_diagnostic(),
+    }
+}
@@ -3,13 +3,10 @@
 
 use clap::{crate_version, Parser};
 use miette::{Context, IntoDiagnostic, Report, Result};
-use num_bigint::BigUint;
-use num_complex::Complex64;
 use qsc::interpret::{self, InterpretResult, Interpreter};
 use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_eval::{
-    output::{self, Receiver},
-    state::format_state_id,
+    output::{self, Receiver, StateDump},
     val::Value,
 };
 use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName};
@@ -55,15 +52,15 @@ struct Cli {
 struct TerminalReceiver;
 
 impl Receiver for TerminalReceiver {
-    fn state(
-        &mut self,
-        states: Vec<(BigUint, Complex64)>,
-        qubit_count: usize,
-    ) -> Result<(), output::Error> {
+    fn state_dump(&mut self, mut dump: StateDump) -> Result<(), output::Error> {
         println!("DumpMachine:");
-        for (qubit, amplitude) in states {
-            let id = format_state_id(&qubit, qubit_count);
-            println!("{id}: [{}, {}]", amplitude.re, amplitude.im);
+        while let Some(chunk) = dump.next_chunk() {
+            for entry in chunk {
+                println!(
+                    "{}: [{}, {}]",
+                    entry.label, entry.amplitude.re, entry.amplitude.im
+                );
+            }
         }
 
         Ok(())
@@ -4,11 +4,16 @@
 use miette::{Diagnostic, Report};
 use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_frontend::{
-    compile::{CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap},
+    compile::{
+        loader::{self, SourceLoader},
+        CompileOptions, CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap,
+    },
     error::WithSource,
+    resolve::Cache,
 };
 use qsc_hir::hir::PackageId;
-use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType, PassContext};
+use std::sync::Arc;
 use thiserror::Error;
 
 pub type Error = WithSource<ErrorKind>;
@@ -32,26 +37,129 @@ pub enum ErrorKind {
 #[must_use]
 pub fn compile(
     store: &PackageStore,
-    dependencies: &[PackageId],
+    dependencies: &[(PackageId, Option<Arc<str>>)],
     sources: SourceMap,
     package_type: PackageType,
     capabilities: RuntimeCapabilityFlags,
     language_features: LanguageFeatures,
 ) -> (CompileUnit, Vec<Error>) {
-    let mut unit = qsc_frontend::compile::compile(
+    compile_with_options(
         store,
         dependencies,
         sources,
+        package_type,
         capabilities,
         language_features,
+        &CompileOptions::default(),
+    )
+}
+
+/// Compiles the sources like [`compile`], with the given options.
+#[must_use]
+pub fn compile_with_options(
+    store: &PackageStore,
+    dependencies: &[(PackageId, Option<Arc<str>>)],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    options: &CompileOptions,
+) -> (CompileUnit, Vec<Error>) {
+    let unit = qsc_frontend::compile::compile_with_options(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        language_features,
+        options,
+    );
+    run_passes(store, unit, package_type, capabilities, options)
+}
+
+/// Compiles the sources like [`compile_with_options`], loading the entry source and the sources it
+/// references with the loader instead of taking every source up front.
+///
+/// # Errors
+///
+/// Returns an error if the entry source or a referenced source cannot be loaded.
+#[allow(clippy::too_many_arguments)]
+pub fn compile_with_loader(
+    store: &PackageStore,
+    dependencies: &[(PackageId, Option<Arc<str>>)],
+    loader: &mut impl SourceLoader,
+    entry: &str,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    options: &CompileOptions,
+) -> Result<(CompileUnit, Vec<Error>), loader::Error> {
+    let sources = loader::load(loader, entry, None)?;
+    Ok(compile_with_options(
+        store,
+        dependencies,
+        sources,
+        package_type,
+        capabilities,
+        language_features,
+        options,
+    ))
+}
+
+/// Compiles the sources like [`compile_with_options`], reusing the name resolution of each
+/// namespace that did not change since the last compilation with the cache.
+#[allow(clippy::too_many_arguments)]
+#[must_use]
+pub fn compile_with_cache(
+    store: &PackageStore,
+    dependencies: &[(PackageId, Option<Arc<str>>)],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    options: &CompileOptions,
+    cache: &mut Cache,
+) -> (CompileUnit, Vec<Error>) {
+    let unit = qsc_frontend::compile::compile_with_cache(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        language_features,
+        options,
+        cache,
     );
+    run_passes(store, unit, package_type, capabilities, options)
+}
+
+fn run_passes(
+    store: &PackageStore,
+    mut unit: CompileUnit,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    options: &CompileOptions,
+) -> (CompileUnit, Vec<Error>) {
     let mut errors = Vec::new();
     for error in unit.errors.drain(..) {
         errors.push(WithSource::from_map(&unit.sources, error.into()));
     }
 
+    if errors.is_empty() && options.cancellation.is_cancelled() {
+        let error = qsc_frontend::compile::Error::cancelled();
+        errors.push(WithSource::from_map(&unit.sources, error.into()));
+    }
+
     if errors.is_empty() {
-        for error in run_default_passes(store.core(), &mut unit, package_type, capabilities) {
+        let pass_errors = PassContext::new(capabilities)
+            .with_inline_threshold(options.inline_threshold)
+            .with_unroll_budget(options.unroll_budget)
+            .with_const_fold(options.const_fold)
+            .run_default_passes(
+                &mut unit.package,
+                &mut unit.assigner,
+                store.core(),
+                package_type,
+            );
+        for error in pass_errors {
             errors.push(WithSource::from_map(&unit.sources, error.into()));
         }
     }
@@ -80,13 +188,19 @@ pub fn core() -> CompileUnit {
     }
 }
 
-/// Compiles the standard library.
+/// Compiles the standard library. With the `prebuilt-std` feature, the standard library saved at build
+/// time is loaded instead when it matches the capabilities.
 ///
 /// # Panics
 ///
 /// Panics if the standard library does not compile without errors.
 #[must_use]
 pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> CompileUnit {
+    #[cfg(feature = "prebuilt-std")]
+    if let Some(unit) = prebuilt_std(capabilities) {
+        return unit;
+    }
+
     let mut unit = qsc_frontend::compile::std(store, capabilities);
     let pass_errors = run_default_passes(store.core(), &mut unit, PackageType::Lib, capabilities);
     if pass_errors.is_empty() {
@@ -100,3 +214,11 @@ pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> Compil
         panic!("could not compile standard library")
     }
 }
+
+/// Loads the standard library that was saved when this crate was built, from the file named by the
+/// `QSC_STD_ARTIFACT` environment variable, if it was saved with the given capabilities.
+#[cfg(feature = "prebuilt-std")]
+fn prebuilt_std(capabilities: RuntimeCapabilityFlags) -> Option<CompileUnit> {
+    qsc_frontend::compile::artifact::load(include_bytes!(env!("QSC_STD_ARTIFACT")), capabilities)
+        .ok()
+}
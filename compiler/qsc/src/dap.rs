@@ -0,0 +1,539 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A Debug Adapter Protocol server that exposes the stepping, breakpoint, stack, and variable APIs
+//! of the [`Debugger`] to any DAP client, over any byte stream such as stdio or a socket.
+//!
+//! The server debugs a single Q# file, given by the `program` argument of the `launch` request,
+//! on a single thread.
+
+#[cfg(test)]
+mod tests;
+
+use crate::interpret::{Debugger, HitCondition, StepAction, StepResult, Value};
+use crate::line_column::{Encoding, Position, Range};
+use miette::Report;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::output::{self, GenericReceiver, Receiver, StateDump};
+use qsc_fir::fir::StmtId;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use rustc_hash::FxHashMap;
+use serde_json::{json, Value as Json};
+use std::io::{self, BufRead, Write};
+
+/// The ID of the only thread reported to clients.
+const THREAD_ID: i64 = 1;
+
+/// The reference of the only variable scope, which holds the locals of the current frame.
+const LOCALS_REFERENCE: i64 = 1;
+
+/// The exception breakpoint filter that pauses before runtime failures.
+const FAIL_FILTER: &str = "fail";
+
+/// Serves DAP requests read from `reader`, writing responses and events to `writer`, until the
+/// client disconnects or the input ends.
+/// # Errors
+/// Returns an error if reading or writing a message fails, or a message is malformed.
+pub fn serve(mut reader: impl BufRead, writer: impl Write) -> io::Result<()> {
+    let mut server = Server::new(writer);
+    while let Some(request) = read_message(&mut reader)? {
+        if !server.handle(&request)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a message framed by a `Content-Length` header, or returns `None` at the end of input.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Json>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(length) = line.strip_prefix("Content-Length:") {
+            content_length = Some(
+                length
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            );
+        }
+    }
+
+    let mut content = vec![0; content_length.expect("content length should be set")];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes messages to the client, numbering them in order.
+struct Output<W: Write> {
+    writer: W,
+    seq: i64,
+}
+
+impl<W: Write> Output<W> {
+    fn send(&mut self, mut message: Json) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = self.seq.into();
+        let content = message.to_string();
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{content}",
+            content.len()
+        )?;
+        self.writer.flush()
+    }
+
+    fn respond(&mut self, request: &Json, body: Json) -> io::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+        });
+        response["body"] = body;
+        self.send(response)
+    }
+
+    fn fail(&mut self, request: &Json, message: &str) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }))
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        let mut message = json!({ "type": "event", "event": event });
+        message["body"] = body;
+        self.send(message)
+    }
+
+    fn output(&mut self, category: &str, output: &str) -> io::Result<()> {
+        self.event("output", json!({ "category": category, "output": output }))
+    }
+}
+
+/// Sends program output to the client as `output` events.
+struct OutputReceiver<'a, W: Write> {
+    output: &'a mut Output<W>,
+}
+
+impl<W: Write> OutputReceiver<'_, W> {
+    fn write(
+        &mut self,
+        write: impl FnOnce(&mut GenericReceiver) -> Result<(), output::Error>,
+    ) -> Result<(), output::Error> {
+        let mut buffer = Vec::new();
+        write(&mut GenericReceiver::new(&mut buffer))?;
+        self.output
+            .output("stdout", &String::from_utf8_lossy(&buffer))
+            .map_err(|_| output::Error)
+    }
+}
+
+impl<W: Write> Receiver for OutputReceiver<'_, W> {
+    fn state_dump(&mut self, dump: StateDump) -> Result<(), output::Error> {
+        self.write(|receiver| receiver.state_dump(dump))
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), output::Error> {
+        self.write(|receiver| receiver.message(msg))
+    }
+}
+
+struct Server<W: Write> {
+    output: Output<W>,
+    debugger: Option<Debugger>,
+    /// Whether line numbers sent to and received from the client start at one.
+    lines_start_at_1: bool,
+    /// Whether column numbers sent to and received from the client start at one.
+    columns_start_at_1: bool,
+    stop_on_entry: bool,
+    /// The breakpoints requested by the client, by source path.
+    source_breakpoints: FxHashMap<String, Vec<Json>>,
+    /// The statements on which execution pauses.
+    breakpoints: Vec<StmtId>,
+}
+
+impl<W: Write> Server<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            output: Output { writer, seq: 0 },
+            debugger: None,
+            lines_start_at_1: true,
+            columns_start_at_1: true,
+            stop_on_entry: false,
+            source_breakpoints: FxHashMap::default(),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    /// Handles a request, returning false if the server should stop.
+    fn handle(&mut self, request: &Json) -> io::Result<bool> {
+        let args = &request["arguments"];
+        let command = request["command"].as_str().unwrap_or_default();
+        if self.debugger.is_none() && !matches!(command, "initialize" | "launch" | "disconnect") {
+            self.output.fail(request, "no program has been launched")?;
+            return Ok(true);
+        }
+
+        match command {
+            "initialize" => {
+                self.lines_start_at_1 = args["linesStartAt1"].as_bool().unwrap_or(true);
+                self.columns_start_at_1 = args["columnsStartAt1"].as_bool().unwrap_or(true);
+                self.output.respond(
+                    request,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsHitConditionalBreakpoints": true,
+                        "supportsLogPoints": true,
+                        "exceptionBreakpointFilters": [{
+                            "filter": FAIL_FILTER,
+                            "label": "Runtime failures",
+                            "default": false,
+                        }],
+                    }),
+                )?;
+            }
+            "launch" => self.launch(request)?,
+            "setBreakpoints" => self.set_breakpoints(request)?,
+            "setExceptionBreakpoints" => {
+                let break_on_fail = args["filters"]
+                    .as_array()
+                    .is_some_and(|filters| filters.iter().any(|f| f == FAIL_FILTER));
+                self.debugger_mut().set_break_on_fail(break_on_fail);
+                self.output.respond(request, json!({}))?;
+            }
+            "configurationDone" => {
+                self.output.respond(request, json!({}))?;
+                if self.stop_on_entry {
+                    self.stopped("entry", None)?;
+                } else {
+                    self.resume(StepAction::Continue)?;
+                }
+            }
+            "threads" => self.output.respond(
+                request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            )?,
+            "stackTrace" => self.stack_trace(request)?,
+            "scopes" => self.output.respond(
+                request,
+                json!({ "scopes": [{
+                    "name": "Locals",
+                    "variablesReference": LOCALS_REFERENCE,
+                    "expensive": false,
+                }] }),
+            )?,
+            "variables" => self.variables(request)?,
+            "continue" => {
+                self.output
+                    .respond(request, json!({ "allThreadsContinued": true }))?;
+                self.resume(StepAction::Continue)?;
+            }
+            "next" => {
+                self.output.respond(request, json!({}))?;
+                self.resume(StepAction::Next)?;
+            }
+            "stepIn" => {
+                self.output.respond(request, json!({}))?;
+                self.resume(StepAction::In)?;
+            }
+            "stepOut" => {
+                self.output.respond(request, json!({}))?;
+                self.resume(StepAction::Out)?;
+            }
+            "disconnect" => {
+                self.output.respond(request, json!({}))?;
+                return Ok(false);
+            }
+            _ => self
+                .output
+                .fail(request, &format!("unsupported request `{command}`"))?,
+        }
+        Ok(true)
+    }
+
+    fn stack_trace(&mut self, request: &Json) -> io::Result<()> {
+        let frames = self
+            .debugger_mut()
+            .get_stack_frames()
+            .into_iter()
+            .rev()
+            .enumerate()
+            .map(|(id, frame)| {
+                let name = match (frame.functor.as_str(), frame.args) {
+                    ("", args) => format!("{}{}", frame.name, args.unwrap_or_default()),
+                    (functor, args) => {
+                        format!("{functor} {}{}", frame.name, args.unwrap_or_default())
+                    }
+                };
+                let mut frame_json = json!({
+                    "id": id,
+                    "name": name,
+                    "source": { "path": frame.path },
+                });
+                self.add_range(&mut frame_json, frame.range);
+                frame_json
+            })
+            .collect::<Vec<_>>();
+        let total = frames.len();
+        self.output.respond(
+            request,
+            json!({ "stackFrames": frames, "totalFrames": total }),
+        )
+    }
+
+    fn variables(&mut self, request: &Json) -> io::Result<()> {
+        let args = &request["arguments"];
+        let variables = if args["variablesReference"] == LOCALS_REFERENCE {
+            self.debugger_mut()
+                .get_locals()
+                .into_iter()
+                .map(|local| {
+                    json!({
+                        "name": &*local.name,
+                        "value": local.value.to_string(),
+                        "type": local.type_name,
+                        "variablesReference": 0,
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.output
+            .respond(request, json!({ "variables": variables }))
+    }
+
+    fn debugger_mut(&mut self) -> &mut Debugger {
+        self.debugger.as_mut().expect("debugger should be launched")
+    }
+
+    fn launch(&mut self, request: &Json) -> io::Result<()> {
+        let args = &request["arguments"];
+        let Some(program) = args["program"].as_str() else {
+            return self.output.fail(request, "missing `program` argument");
+        };
+        let contents = match std::fs::read_to_string(program) {
+            Ok(contents) => contents,
+            Err(e) => {
+                return self
+                    .output
+                    .fail(request, &format!("could not read `{program}`: {e}"))
+            }
+        };
+
+        let sources = SourceMap::new([(program.into(), contents.into())], None);
+        let debugger = Debugger::new(
+            sources,
+            RuntimeCapabilityFlags::all(),
+            Encoding::Utf16,
+            LanguageFeatures::default(),
+        )
+        .and_then(|mut debugger| debugger.set_entry().map(|()| debugger));
+        match debugger {
+            Ok(debugger) => {
+                self.debugger = Some(debugger);
+                self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+                self.output.respond(request, json!({}))?;
+                self.output.event("initialized", json!({}))
+            }
+            Err(errors) => {
+                let message = errors
+                    .into_iter()
+                    .map(|error| format!("{:?}", Report::new(error)))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.output.fail(request, &message)
+            }
+        }
+    }
+
+    fn set_breakpoints(&mut self, request: &Json) -> io::Result<()> {
+        let args = &request["arguments"];
+        let Some(path) = args["source"]["path"].as_str() else {
+            return self.output.fail(request, "missing source path");
+        };
+        let requested = args["breakpoints"].as_array().cloned().unwrap_or_default();
+        self.source_breakpoints.insert(path.to_string(), requested);
+
+        // Breakpoint state is shared by all sources, so it is rebuilt from every source.
+        let debugger = self.debugger.as_mut().expect("debugger should be launched");
+        debugger.clear_logpoints();
+        debugger.clear_hit_conditions();
+        self.breakpoints.clear();
+        let mut results = Vec::new();
+        for (source, requested) in &self.source_breakpoints {
+            for breakpoint in requested {
+                let line = breakpoint["line"].as_u64().unwrap_or_default();
+                let column = breakpoint["column"].as_u64();
+                let (line, column) = (
+                    from_client(line, self.lines_start_at_1),
+                    column.map(|column| from_client(column, self.columns_start_at_1)),
+                );
+                let candidates = resolve(debugger, source, line, column);
+                let condition = breakpoint["hitCondition"]
+                    .as_str()
+                    .map(str::parse::<HitCondition>)
+                    .transpose();
+                let result = match (candidates.first(), condition) {
+                    (None, _) => {
+                        json!({ "verified": false, "message": "no statement on this line" })
+                    }
+                    (Some(_), Err(message)) => json!({ "verified": false, "message": message }),
+                    (Some(&(_, range)), Ok(condition)) => {
+                        for &(id, _) in &candidates {
+                            if let Some(template) = breakpoint["logMessage"].as_str() {
+                                debugger.set_logpoint(id, template);
+                            } else {
+                                self.breakpoints.push(id);
+                            }
+                            if let Some(condition) = condition {
+                                debugger.set_hit_condition(id, condition);
+                            }
+                        }
+                        let mut result = json!({ "verified": true });
+                        add_range(
+                            &mut result,
+                            range,
+                            self.lines_start_at_1,
+                            self.columns_start_at_1,
+                        );
+                        result
+                    }
+                };
+                if source == path {
+                    results.push(result);
+                }
+            }
+        }
+        self.output
+            .respond(request, json!({ "breakpoints": results }))
+    }
+
+    /// Resumes execution and reports where it stopped.
+    fn resume(&mut self, action: StepAction) -> io::Result<()> {
+        let debugger = self.debugger.as_mut().expect("debugger should be launched");
+        let mut receiver = OutputReceiver {
+            output: &mut self.output,
+        };
+        match debugger.eval_step(&mut receiver, &self.breakpoints, action) {
+            Ok(StepResult::BreakpointHit(_)) => self.stopped("breakpoint", None),
+            Ok(StepResult::Next | StepResult::StepIn | StepResult::StepOut) => {
+                self.stopped("step", None)
+            }
+            Ok(StepResult::ExceptionHit(error)) => {
+                self.stopped("exception", Some(&error.to_string()))
+            }
+            Ok(StepResult::Yield) => self.stopped("pause", None),
+            Ok(StepResult::Return(value)) => {
+                if value != Value::unit() {
+                    self.output.output("console", &format!("{value}\n"))?;
+                }
+                self.exited(0)
+            }
+            Err(errors) => {
+                for error in errors {
+                    let message = match error.stack_trace() {
+                        Some(trace) => trace.clone(),
+                        None => format!("{:?}\n", Report::new(error)),
+                    };
+                    self.output.output("stderr", &message)?;
+                }
+                self.exited(1)
+            }
+        }
+    }
+
+    fn stopped(&mut self, reason: &str, description: Option<&str>) -> io::Result<()> {
+        let mut body = json!({
+            "reason": reason,
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+        if let Some(description) = description {
+            body["description"] = description.into();
+            body["text"] = description.into();
+        }
+        self.output.event("stopped", body)
+    }
+
+    fn exited(&mut self, exit_code: i64) -> io::Result<()> {
+        self.output
+            .event("exited", json!({ "exitCode": exit_code }))?;
+        self.output.event("terminated", json!({}))
+    }
+
+    fn add_range(&self, message: &mut Json, range: Range) {
+        add_range(
+            message,
+            range,
+            self.lines_start_at_1,
+            self.columns_start_at_1,
+        );
+    }
+}
+
+/// Resolves the statements for a breakpoint on the given zero-based line and, optionally, column,
+/// with the range that is highlighted when it is hit.
+fn resolve(
+    debugger: &Debugger,
+    path: &str,
+    line: u32,
+    column: Option<u32>,
+) -> Vec<(StmtId, Range)> {
+    let candidates = match column {
+        Some(column) => debugger.resolve_breakpoints(
+            debugger.source_package_id(),
+            path,
+            Position { line, column },
+        ),
+        None => debugger
+            .get_breakpoints(path)
+            .into_iter()
+            .filter(|bp| bp.range.start.line == line)
+            .collect(),
+    };
+    // Keep only the first candidate, together with the statements generated from it.
+    let Some(first) = candidates.first().map(|bp| bp.range) else {
+        return Vec::new();
+    };
+    candidates
+        .into_iter()
+        .filter(|bp| bp.range == first)
+        .map(|bp| (bp.id.into(), bp.range))
+        .collect()
+}
+
+fn from_client(value: u64, starts_at_1: bool) -> u32 {
+    let value = u32::try_from(value).unwrap_or(u32::MAX);
+    if starts_at_1 {
+        value.saturating_sub(1)
+    } else {
+        value
+    }
+}
+
+fn add_range(message: &mut Json, range: Range, lines_start_at_1: bool, columns_start_at_1: bool) {
+    let line_offset = u32::from(lines_start_at_1);
+    let column_offset = u32::from(columns_start_at_1);
+    message["line"] = (range.start.line + line_offset).into();
+    message["column"] = (range.start.column + column_offset).into();
+    message["endLine"] = (range.end.line + line_offset).into();
+    message["endColumn"] = (range.end.column + column_offset).into();
+}
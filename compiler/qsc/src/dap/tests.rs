@@ -0,0 +1,196 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{read_message, serve};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use serde_json::{json, Value as Json};
+use std::io::Cursor;
+
+const PROGRAM: &str = indoc! {r#"
+    namespace Test {
+        function Add(a : Int, b : Int) : Int {
+            let sum = a + b;
+            sum
+        }
+        @EntryPoint()
+        operation Main() : Int {
+            Message("start");
+            let x = Add(1, 2);
+            x * 2
+        }
+    }
+"#};
+
+/// Runs the server on the given requests for a program file named after the test, and renders the
+/// messages it sends, leaving out their sequence numbers.
+fn check(name: &str, source: &str, requests: &[Json], expect: &Expect) {
+    let path = std::env::temp_dir().join(format!("qsdap_{name}.qs"));
+    std::fs::write(&path, source).expect("program should be written");
+    let path = path.to_string_lossy().to_string();
+
+    let mut input = Vec::new();
+    let mut requests = requests.to_vec();
+    requests.insert(0, json!({ "command": "initialize", "arguments": {} }));
+    requests.insert(
+        1,
+        json!({ "command": "launch", "arguments": { "program": path } }),
+    );
+    for (seq, mut request) in requests.into_iter().enumerate() {
+        request["seq"] = (seq + 1).into();
+        request["type"] = "request".into();
+        if request["arguments"]["source"].is_object() {
+            request["arguments"]["source"]["path"] = path.clone().into();
+        }
+        let content = request.to_string();
+        input.extend(format!("Content-Length: {}\r\n\r\n{content}", content.len()).bytes());
+    }
+
+    let mut output = Vec::new();
+    serve(Cursor::new(input), &mut output).expect("server should succeed");
+
+    let mut output = Cursor::new(output);
+    let mut actual = String::new();
+    while let Some(mut message) = read_message(&mut output).expect("message should be valid") {
+        let message = message
+            .as_object_mut()
+            .expect("message should be an object");
+        message.remove("seq");
+        message.remove("request_seq");
+        let line = Json::from(message.clone())
+            .to_string()
+            .replace(&path, "<path>");
+        actual.push_str(&line);
+        actual.push('\n');
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn run_to_completion() {
+    check(
+        "run_to_completion",
+        PROGRAM,
+        &[json!({ "command": "configurationDone" })],
+        &expect![[r#"
+            {"body":{"exceptionBreakpointFilters":[{"default":false,"filter":"fail","label":"Runtime failures"}],"supportsConfigurationDoneRequest":true,"supportsHitConditionalBreakpoints":true,"supportsLogPoints":true},"command":"initialize","success":true,"type":"response"}
+            {"body":{},"command":"launch","success":true,"type":"response"}
+            {"body":{},"event":"initialized","type":"event"}
+            {"body":{},"command":"configurationDone","success":true,"type":"response"}
+            {"body":{"category":"stdout","output":"start\n"},"event":"output","type":"event"}
+            {"body":{"category":"console","output":"6\n"},"event":"output","type":"event"}
+            {"body":{"exitCode":0},"event":"exited","type":"event"}
+            {"body":{},"event":"terminated","type":"event"}
+        "#]],
+    );
+}
+
+#[test]
+fn breakpoint_stack_and_variables() {
+    check(
+        "breakpoint_stack_and_variables",
+        PROGRAM,
+        &[
+            json!({ "command": "setBreakpoints", "arguments": {
+                "source": {},
+                "breakpoints": [{ "line": 4 }, { "line": 12 }],
+            } }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+            json!({ "command": "variables", "arguments": { "variablesReference": 1 } }),
+            json!({ "command": "disconnect" }),
+        ],
+        &expect![[r#"
+            {"body":{"exceptionBreakpointFilters":[{"default":false,"filter":"fail","label":"Runtime failures"}],"supportsConfigurationDoneRequest":true,"supportsHitConditionalBreakpoints":true,"supportsLogPoints":true},"command":"initialize","success":true,"type":"response"}
+            {"body":{},"command":"launch","success":true,"type":"response"}
+            {"body":{},"event":"initialized","type":"event"}
+            {"body":{"breakpoints":[{"column":9,"endColumn":12,"endLine":4,"line":4,"verified":true},{"message":"no statement on this line","verified":false}]},"command":"setBreakpoints","success":true,"type":"response"}
+            {"body":{},"command":"configurationDone","success":true,"type":"response"}
+            {"body":{"category":"stdout","output":"start\n"},"event":"output","type":"event"}
+            {"body":{"allThreadsStopped":true,"reason":"breakpoint","threadId":1},"event":"stopped","type":"event"}
+            {"body":{"stackFrames":[{"column":9,"endColumn":12,"endLine":4,"id":0,"line":4,"name":"Add(1, 2)","source":{"path":"<path>"}},{"column":24,"endColumn":25,"endLine":9,"id":1,"line":9,"name":"Main()","source":{"path":"<path>"}}],"totalFrames":2},"command":"stackTrace","success":true,"type":"response"}
+            {"body":{"variables":[{"name":"a","type":"Int","value":"1","variablesReference":0},{"name":"b","type":"Int","value":"2","variablesReference":0},{"name":"sum","type":"Int","value":"3","variablesReference":0}]},"command":"variables","success":true,"type":"response"}
+            {"body":{},"command":"disconnect","success":true,"type":"response"}
+        "#]],
+    );
+}
+
+#[test]
+fn logpoint_and_step() {
+    check(
+        "logpoint_and_step",
+        PROGRAM,
+        &[
+            json!({ "command": "setBreakpoints", "arguments": {
+                "source": {},
+                "breakpoints": [{ "line": 3, "logMessage": "a is {a}" }, { "line": 9 }],
+            } }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "next", "arguments": { "threadId": 1 } }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        ],
+        &expect![[r#"
+            {"body":{"exceptionBreakpointFilters":[{"default":false,"filter":"fail","label":"Runtime failures"}],"supportsConfigurationDoneRequest":true,"supportsHitConditionalBreakpoints":true,"supportsLogPoints":true},"command":"initialize","success":true,"type":"response"}
+            {"body":{},"command":"launch","success":true,"type":"response"}
+            {"body":{},"event":"initialized","type":"event"}
+            {"body":{"breakpoints":[{"column":9,"endColumn":25,"endLine":3,"line":3,"verified":true},{"column":9,"endColumn":27,"endLine":9,"line":9,"verified":true}]},"command":"setBreakpoints","success":true,"type":"response"}
+            {"body":{},"command":"configurationDone","success":true,"type":"response"}
+            {"body":{"category":"stdout","output":"start\n"},"event":"output","type":"event"}
+            {"body":{"allThreadsStopped":true,"reason":"breakpoint","threadId":1},"event":"stopped","type":"event"}
+            {"body":{},"command":"next","success":true,"type":"response"}
+            {"body":{"category":"stdout","output":"a is 1\n"},"event":"output","type":"event"}
+            {"body":{"allThreadsStopped":true,"reason":"step","threadId":1},"event":"stopped","type":"event"}
+            {"body":{"allThreadsContinued":true},"command":"continue","success":true,"type":"response"}
+            {"body":{"category":"console","output":"6\n"},"event":"output","type":"event"}
+            {"body":{"exitCode":0},"event":"exited","type":"event"}
+            {"body":{},"event":"terminated","type":"event"}
+        "#]],
+    );
+}
+
+#[test]
+fn exception_breakpoint_on_fail() {
+    check(
+        "exception_breakpoint_on_fail",
+        indoc! {r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    fail "boom";
+                }
+            }
+        "#},
+        &[
+            json!({ "command": "setExceptionBreakpoints", "arguments": { "filters": ["fail"] } }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        ],
+        &expect![[r#"
+            {"body":{"exceptionBreakpointFilters":[{"default":false,"filter":"fail","label":"Runtime failures"}],"supportsConfigurationDoneRequest":true,"supportsHitConditionalBreakpoints":true,"supportsLogPoints":true},"command":"initialize","success":true,"type":"response"}
+            {"body":{},"command":"launch","success":true,"type":"response"}
+            {"body":{},"event":"initialized","type":"event"}
+            {"body":{},"command":"setExceptionBreakpoints","success":true,"type":"response"}
+            {"body":{},"command":"configurationDone","success":true,"type":"response"}
+            {"body":{"allThreadsStopped":true,"description":"program failed: boom","reason":"exception","text":"program failed: boom","threadId":1},"event":"stopped","type":"event"}
+            {"body":{"allThreadsContinued":true},"command":"continue","success":true,"type":"response"}
+            {"body":{"category":"stderr","output":"Error: program failed: boom\nCall stack:\n    at Test.Main in <path>\n"},"event":"output","type":"event"}
+            {"body":{"exitCode":1},"event":"exited","type":"event"}
+            {"body":{},"event":"terminated","type":"event"}
+        "#]],
+    );
+}
+
+#[test]
+fn request_before_launch_fails() {
+    let content = json!({ "seq": 1, "type": "request", "command": "threads" }).to_string();
+    let input = format!("Content-Length: {}\r\n\r\n{content}", content.len());
+    let mut output = Vec::new();
+    serve(Cursor::new(input), &mut output).expect("server should succeed");
+    let message = read_message(&mut Cursor::new(output))
+        .expect("message should be valid")
+        .expect("response should be sent");
+    assert_eq!(message["success"], false);
+    assert_eq!(message["message"], "no program has been launched");
+}
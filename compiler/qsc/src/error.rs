@@ -1,9 +1,20 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use miette::Diagnostic;
-use qsc_frontend::compile::PackageStore;
-use std::fmt::{self, Debug, Display, Formatter};
+#[cfg(test)]
+mod tests;
+
+use miette::{Diagnostic, SourceCode, SourceSpan};
+use qsc_data_structures::{
+    line_column::{Encoding, Position},
+    span::Span,
+};
+use qsc_frontend::compile::{PackageStore, Source};
+use serde::Serialize;
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    iter,
+};
 use thiserror::Error;
 
 pub use qsc_frontend::error::WithSource;
@@ -12,17 +23,27 @@ pub use qsc_frontend::error::WithSource;
 pub struct WithStack<E> {
     error: E,
     stack_trace: Option<String>,
+    frames: Vec<ReportFrame>,
 }
 
 impl<E> WithStack<E> {
-    pub(super) fn new(error: E, stack_trace: Option<String>) -> Self {
-        WithStack { error, stack_trace }
+    pub(super) fn new(error: E, stack_trace: Option<String>, frames: Vec<ReportFrame>) -> Self {
+        WithStack {
+            error,
+            stack_trace,
+            frames,
+        }
     }
 
     pub(super) fn stack_trace(&self) -> &Option<String> {
         &self.stack_trace
     }
 
+    /// The frames of the call stack at the time of the error, innermost first.
+    pub fn frames(&self) -> &[ReportFrame] {
+        &self.frames
+    }
+
     pub fn error(&self) -> &E {
         &self.error
     }
@@ -73,6 +94,7 @@ pub(super) fn from_eval(
     error: qsc_eval::Error,
     store: &PackageStore,
     stack_trace: Option<String>,
+    frames: Vec<ReportFrame>,
 ) -> WithStack<WithSource<qsc_eval::Error>> {
     let span = error.span();
 
@@ -81,5 +103,118 @@ pub(super) fn from_eval(
         .expect("expected to find package id in store")
         .sources;
 
-    WithStack::new(WithSource::from_map(sources, error), stack_trace)
+    WithStack::new(WithSource::from_map(sources, error), stack_trace, frames)
+}
+
+/// A machine-readable description of an error, for hosts that need structured diagnostics rather
+/// than rendered text. Lines and columns are one-based, and columns count UTF-8 bytes.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub code: Option<String>,
+    pub message: String,
+    /// The messages of the underlying errors that caused this one, outermost first.
+    pub causes: Vec<String>,
+    pub help: Option<String>,
+    pub labels: Vec<ReportLabel>,
+    /// The frames of the call stack for runtime errors, innermost first.
+    pub frames: Vec<ReportFrame>,
+    pub related: Vec<ErrorReport>,
+}
+
+/// A labeled span of an [`ErrorReport`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportLabel {
+    pub message: Option<String>,
+    /// The location of the span, or `None` if the error has no source to resolve it against.
+    pub location: Option<ReportLocation>,
+}
+
+/// A stack frame of an [`ErrorReport`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportFrame {
+    /// The qualified name of the callable, including its functors.
+    pub name: String,
+    /// The location being evaluated in the callable when the error occurred.
+    pub location: Option<ReportLocation>,
+}
+
+/// A source range resolved to a file, line and column.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportLocation {
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl ErrorReport {
+    /// Describes a diagnostic, resolving its labels against its source code, if any.
+    #[must_use]
+    pub fn from_diagnostic(diagnostic: &dyn Diagnostic) -> Self {
+        let source_code = diagnostic.source_code();
+        let labels = diagnostic
+            .labels()
+            .into_iter()
+            .flatten()
+            .map(|label| ReportLabel {
+                message: label.label().map(ToString::to_string),
+                location: source_code.and_then(|code| resolve_label(code, label.inner())),
+            })
+            .collect();
+        let causes = iter::successors(diagnostic.source(), |error| error.source())
+            .map(ToString::to_string)
+            .collect();
+        Self {
+            code: diagnostic.code().map(|code| code.to_string()),
+            message: diagnostic.to_string(),
+            causes,
+            help: diagnostic.help().map(|help| help.to_string()),
+            labels,
+            frames: Vec::new(),
+            related: diagnostic
+                .related()
+                .into_iter()
+                .flatten()
+                .map(Self::from_diagnostic)
+                .collect(),
+        }
+    }
+}
+
+impl ReportLocation {
+    /// Resolves a span of `source`, whose offsets are relative to the source map.
+    pub(super) fn from_source(source: &Source, span: Span) -> Self {
+        let span = span - source.offset;
+        let start = Position::from_utf8_byte_offset(Encoding::Utf8, &source.contents, span.lo);
+        let end = Position::from_utf8_byte_offset(Encoding::Utf8, &source.contents, span.hi);
+        Self {
+            source: source.name.to_string(),
+            line: start.line + 1,
+            column: start.column + 1,
+            end_line: end.line + 1,
+            end_column: end.column + 1,
+        }
+    }
+}
+
+fn resolve_label(code: &dyn SourceCode, span: &SourceSpan) -> Option<ReportLocation> {
+    let start = code.read_span(&SourceSpan::new(span.offset().into(), 0.into()), 0, 0);
+    let end = code.read_span(
+        &SourceSpan::new((span.offset() + span.len()).into(), 0.into()),
+        0,
+        0,
+    );
+    let (start, end) = (start.ok()?, end.ok()?);
+    Some(ReportLocation {
+        source: start.name().unwrap_or_default().to_string(),
+        line: u32::try_from(start.line() + 1).ok()?,
+        column: u32::try_from(start.column() + 1).ok()?,
+        end_line: u32::try_from(end.line() + 1).ok()?,
+        end_column: u32::try_from(end.column() + 1).ok()?,
+    })
 }
@@ -0,0 +1,132 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::interpret::{Error, Interpreter};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::output::CursorReceiver;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+use std::io::Cursor;
+
+fn interpreter(source: &str) -> Result<Interpreter, Vec<Error>> {
+    Interpreter::new(
+        true,
+        SourceMap::new([("test.qs".into(), source.into())], None),
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+}
+
+fn to_json(errors: &[Error]) -> String {
+    let reports = errors.iter().map(Error::report).collect::<Vec<_>>();
+    serde_json::to_string_pretty(&reports).expect("reports should serialize")
+}
+
+#[test]
+fn compile_error_report_resolves_spans() {
+    let errors = interpreter(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                Foo()
+            }
+        }
+    "})
+    .err()
+    .expect("compilation should fail");
+    expect![[r#"
+        [
+          {
+            "code": "Qsc.Resolve.NotFound",
+            "message": "name error",
+            "causes": [
+              "`Foo` not found"
+            ],
+            "help": null,
+            "labels": [
+              {
+                "message": null,
+                "location": {
+                  "source": "test.qs",
+                  "line": 4,
+                  "column": 9,
+                  "endLine": 4,
+                  "endColumn": 12
+                }
+              }
+            ],
+            "frames": [],
+            "related": []
+          }
+        ]"#]]
+    .assert_eq(&to_json(&errors));
+}
+
+#[test]
+fn runtime_error_report_includes_frames() {
+    let mut interpreter = interpreter(indoc! {"
+        namespace Test {
+            function Divide(n : Int) : Int {
+                1 / n
+            }
+
+            @EntryPoint()
+            operation Main() : Int {
+                Divide(0)
+            }
+        }
+    "})
+    .expect("compilation should succeed");
+    let mut cursor = Cursor::new(Vec::new());
+    let errors = interpreter
+        .eval_entry(&mut CursorReceiver::new(&mut cursor))
+        .expect_err("evaluation should fail");
+    expect![[r#"
+        [
+          {
+            "code": "Qsc.Eval.DivZero",
+            "message": "division by zero",
+            "causes": [],
+            "help": null,
+            "labels": [
+              {
+                "message": "cannot divide by zero",
+                "location": {
+                  "source": "test.qs",
+                  "line": 3,
+                  "column": 13,
+                  "endLine": 3,
+                  "endColumn": 14
+                }
+              }
+            ],
+            "frames": [
+              {
+                "name": "Test.Divide",
+                "location": {
+                  "source": "test.qs",
+                  "line": 3,
+                  "column": 13,
+                  "endLine": 3,
+                  "endColumn": 14
+                }
+              },
+              {
+                "name": "Test.Main",
+                "location": {
+                  "source": "test.qs",
+                  "line": 8,
+                  "column": 16,
+                  "endLine": 8,
+                  "endColumn": 17
+                }
+              }
+            ],
+            "related": []
+          }
+        ]"#]]
+    .assert_eq(&to_json(&errors));
+}
@@ -7,10 +7,13 @@ use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_frontend::{
     compile::{OpenPackageStore, PackageStore, RuntimeCapabilityFlags, SourceMap},
     error::WithSource,
-    incremental::Increment,
+    incremental::{session_items, Increment},
 };
 use qsc_hir::hir::PackageId;
 use qsc_passes::{PackageType, PassContext};
+use serde::{Deserialize, Serialize};
+
+pub use qsc_frontend::incremental::{SessionItem, SessionItemKind};
 
 /// An incremental Q# compiler.
 pub struct Compiler {
@@ -24,6 +27,36 @@ pub struct Compiler {
     passes: PassContext,
     /// The frontend incremental compiler.
     frontend: qsc_frontend::incremental::Compiler,
+    /// The last fragments or expression that were compiled, which `update()` adds to the history.
+    pending: Option<Entry>,
+    /// The fragments and expressions that the compilation was updated with, in order.
+    history: Vec<Entry>,
+}
+
+/// The fragments and expressions that an incremental compiler was updated with, in order. Since
+/// compiling the same entries again rebuilds the same global scope, IDs, and packages, a
+/// checkpoint can be serialized to save a session and restored into a new compiler to resume it,
+/// or restored into several compilers to fork it.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    entries: Vec<Entry>,
+}
+
+impl Checkpoint {
+    /// The source names of the fragments in the checkpoint.
+    pub fn fragment_names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Fragments { name, .. } => Some(name.as_str()),
+            Entry::Expr { .. } => None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum Entry {
+    Fragments { name: String, contents: String },
+    Expr { contents: String },
 }
 
 /// An incremental compiler error.
@@ -46,7 +79,7 @@ impl Compiler {
         if include_std {
             let std = std(&store, capabilities);
             let id = store.insert(std);
-            dependencies.push(id);
+            dependencies.push((id, None));
         }
 
         let (unit, errors) = compile(
@@ -62,7 +95,7 @@ impl Compiler {
         }
 
         let source_package_id = store.insert(unit);
-        dependencies.push(source_package_id);
+        dependencies.push((source_package_id, None));
 
         let frontend = qsc_frontend::incremental::Compiler::new(
             &store,
@@ -77,6 +110,8 @@ impl Compiler {
             source_package_id,
             frontend,
             passes: PassContext::new(capabilities),
+            pending: None,
+            history: Vec::new(),
         })
     }
 
@@ -115,6 +150,10 @@ impl Compiler {
     where
         F: FnMut(Errors) -> Result<(), Errors>,
     {
+        self.pending = Some(Entry::Fragments {
+            name: source_name.to_string(),
+            contents: source_contents.to_string(),
+        });
         let (core, unit) = self.store.get_open_mut();
 
         let mut errors = false;
@@ -150,6 +189,9 @@ impl Compiler {
     /// It is then the caller's responsibility to merge
     /// these packages into the current `CompileUnit` using the `update()` method.
     pub fn compile_expr(&mut self, expr: &str) -> Result<Increment, Errors> {
+        self.pending = Some(Entry::Expr {
+            contents: expr.to_string(),
+        });
         let (core, unit) = self.store.get_open_mut();
 
         let mut increment = self
@@ -173,10 +215,60 @@ impl Compiler {
 
     /// Updates the current compilation with the AST and HIR packages,
     /// and any associated context, returned from a previous incremental compilation.
+    ///
+    /// The increment should be the one returned from the latest compilation, which is
+    /// what the compiler records for checkpoints.
     pub fn update(&mut self, new: Increment) {
         let (_, unit) = self.store.get_open_mut();
 
         self.frontend.update(unit, new);
+        self.history.extend(self.pending.take());
+    }
+
+    /// Captures the fragments and expressions that the compilation was updated with so far.
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            entries: self.history.clone(),
+        }
+    }
+
+    /// Rebuilds the compilation of a checkpoint, which should be taken from a compiler created
+    /// with the same sources and options as this one before it compiled anything. Each entry is
+    /// compiled and the compilation is updated with it in turn, after passing the increment to
+    /// `inspect` so that the caller can process it like the original increment.
+    ///
+    /// # Errors
+    /// If compiling an entry fails, its errors are returned and the entries after it
+    /// are not compiled.
+    pub fn restore(
+        &mut self,
+        checkpoint: &Checkpoint,
+        mut inspect: impl FnMut(&Increment),
+    ) -> Result<(), Errors> {
+        for entry in &checkpoint.entries {
+            let increment = match entry {
+                Entry::Fragments { name, contents } => {
+                    self.compile_fragments_fail_fast(name, contents)?
+                }
+                Entry::Expr { contents } => self.compile_expr(contents)?,
+            };
+            inspect(&increment);
+            self.update(increment);
+        }
+        Ok(())
+    }
+
+    /// Lists the callables, types, and opens that the fragments compiled so far defined and that are
+    /// still in effect, in the order they were defined.
+    #[must_use]
+    pub fn items(&self) -> Vec<SessionItem> {
+        let unit = self
+            .store
+            .package_store()
+            .get(self.store.open_package_id())
+            .expect("open package should be in store");
+        session_items(unit)
     }
 
     /// Returns a reference to the underlying package store.
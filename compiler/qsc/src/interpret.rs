@@ -2,6 +2,8 @@
 // Licensed under the MIT License.
 
 mod debug;
+mod entry_args;
+mod unitary;
 
 #[cfg(test)]
 mod tests;
@@ -10,25 +12,27 @@ mod tests;
 mod debugger_tests;
 
 pub use qsc_eval::{
-    debug::Frame,
+    debug::{Frame, FrameArg, HitCondition},
     output::{self, GenericReceiver},
     val::Result,
+    val::ResultBits,
     val::Value,
     StepAction, StepResult,
 };
+pub use unitary::{OperationDifference, UnitaryMatrix, MAX_UNITARY_QUBITS};
 
 use crate::{
-    error::{self, WithStack},
-    incremental::Compiler,
+    error::{self, ErrorReport, WithStack},
+    incremental::{Checkpoint, Compiler, SessionItem},
 };
-use debug::format_call_stack;
+use debug::{format_call_stack, report_frames};
 use miette::Diagnostic;
 use num_bigint::BigUint;
 use num_complex::Complex;
 use qsc_codegen::qir_base::BaseProfSim;
 use qsc_data_structures::{
     language_features::LanguageFeatures,
-    line_column::{Encoding, Range},
+    line_column::{Encoding, Position, Range},
     span::Span,
 };
 use qsc_eval::{
@@ -36,7 +40,7 @@ use qsc_eval::{
     debug::{map_fir_package_to_hir, map_hir_package_to_fir},
     output::Receiver,
     val::{self},
-    Env, EvalId, State, VariableInfo,
+    Env, EvalId, NextNode, State, VariableInfo,
 };
 use qsc_fir::fir::{self, Global, PackageStoreLookup};
 use qsc_fir::{
@@ -44,11 +48,13 @@ use qsc_fir::{
     visit::{self, Visitor},
 };
 use qsc_frontend::{
-    compile::{CompileUnit, PackageStore, RuntimeCapabilityFlags, Source, SourceMap},
+    compile::{PackageStore, RuntimeCapabilityFlags, Source, SourceMap},
     error::WithSource,
 };
+use qsc_hir::hir;
 use qsc_passes::PackageType;
 use rustc_hash::FxHashSet;
+use std::num::{NonZeroU32, NonZeroU64};
 use thiserror::Error;
 
 impl Error {
@@ -59,6 +65,19 @@ impl Error {
             _ => &None,
         }
     }
+
+    /// Describes the error in a machine-readable form, including the call stack of runtime errors.
+    #[must_use]
+    pub fn report(&self) -> ErrorReport {
+        match self {
+            Error::Eval(err) => {
+                let mut report = ErrorReport::from_diagnostic(err);
+                report.frames = err.frames().to_vec();
+                report
+            }
+            _ => ErrorReport::from_diagnostic(self),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -75,9 +94,26 @@ pub enum Error {
     #[error("entry point not found")]
     #[diagnostic(code("Qsc.Interpret.NoEntryPoint"))]
     NoEntryPoint,
+    #[error("expected {0} entry point arguments, found {1}")]
+    #[diagnostic(code("Qsc.Interpret.EntryArgCount"))]
+    EntryArgCount(usize, usize),
+    #[error("invalid argument for entry point parameter `{0}`")]
+    #[diagnostic(help("expected a value of type {1}"))]
+    #[diagnostic(code("Qsc.Interpret.EntryArgType"))]
+    EntryArgType(String, String),
+    #[error("entry point arguments should be a JSON array or object")]
+    #[diagnostic(code("Qsc.Interpret.EntryArgJson"))]
+    EntryArgJson,
     #[error("unsupported runtime capabilities for code generation")]
     #[diagnostic(code("Qsc.Interpret.UnsupportedRuntimeCapabilities"))]
     UnsupportedRuntimeCapabilities,
+    #[error("unitary matrix of {0} qubits is too large")]
+    #[diagnostic(help("the unitary matrix can be computed for at most {1} qubits"))]
+    #[diagnostic(code("Qsc.Interpret.UnitaryTooLarge"))]
+    UnitaryTooLarge(usize, usize),
+    #[error("the state of the qubits was not dumped")]
+    #[diagnostic(code("Qsc.Interpret.NoStateDump"))]
+    NoStateDump,
 }
 
 /// A Q# interpreter.
@@ -165,6 +201,13 @@ impl Interpreter {
     pub fn set_classical_seed(&mut self, seed: Option<u64>) {
         self.classical_seed = seed;
     }
+
+    /// Returns the number of bytes of heap memory retained by the variables of the session.
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        self.env.heap_size()
+    }
+
     /// Executes the entry expression until the end of execution.
     /// # Errors
     /// Returns a vector of errors if evaluating the entry point fails.
@@ -175,7 +218,9 @@ impl Interpreter {
         let expr = self.get_entry_expr()?;
         eval(
             self.source_package,
+            self.user_packages(),
             self.classical_seed,
+            None,
             expr.into(),
             self.compiler.package_store(),
             &self.fir_store,
@@ -193,12 +238,11 @@ impl Interpreter {
         receiver: &mut impl Receiver,
     ) -> std::result::Result<Value, Vec<Error>> {
         let expr = self.get_entry_expr()?;
-        if self.quantum_seed.is_some() {
-            sim.set_seed(self.quantum_seed);
-        }
         eval(
             self.source_package,
+            self.user_packages(),
             self.classical_seed,
+            self.quantum_seed,
             expr.into(),
             self.compiler.package_store(),
             &self.fir_store,
@@ -208,6 +252,50 @@ impl Interpreter {
         )
     }
 
+    /// Runs the entry point with the given arguments on a new instance of the environment and
+    /// simulator. Each argument is checked against the type of its parameter.
+    /// # Errors
+    /// Returns a vector of errors if the arguments do not match the entry point parameters or if
+    /// evaluating the entry point fails.
+    pub fn run_entry_with_args(
+        &mut self,
+        receiver: &mut impl Receiver,
+        args: &[Value],
+    ) -> InterpretResult {
+        let expr = entry_args::entry_call(self.source_package(), args)?;
+        self.run(receiver, &expr)?
+    }
+
+    /// Runs the entry point like [`Self::run_entry_with_args`], reading the arguments from JSON.
+    /// The JSON is either an array with one element per parameter or an object with one field per
+    /// parameter name.
+    /// # Errors
+    /// Returns a vector of errors if the arguments do not match the entry point parameters or if
+    /// evaluating the entry point fails.
+    pub fn run_entry_with_json_args(
+        &mut self,
+        receiver: &mut impl Receiver,
+        json: &str,
+    ) -> InterpretResult {
+        let args = entry_args::json_args(self.source_package(), json)?;
+        self.run_entry_with_args(receiver, &args)
+    }
+
+    fn source_package(&self) -> &hir::Package {
+        &self
+            .compiler
+            .package_store()
+            .get(self.compiler.source_package_id())
+            .expect("source package should be in the package store")
+            .package
+    }
+
+    /// The packages of user code: the source package, and the package of the fragments and
+    /// expressions evaluated after it.
+    fn user_packages(&self) -> Vec<PackageId> {
+        vec![self.source_package, self.package]
+    }
+
     fn get_entry_expr(&self) -> std::result::Result<ExprId, Vec<Error>> {
         let unit = self.fir_store.get(self.source_package);
         if let Some(entry) = unit.entry {
@@ -247,7 +335,9 @@ impl Interpreter {
         for stmt_id in stmts {
             result = eval(
                 self.package,
+                self.user_packages(),
                 self.classical_seed,
+                None,
                 stmt_id.into(),
                 self.compiler.package_store(),
                 &self.fir_store,
@@ -260,6 +350,46 @@ impl Interpreter {
         Ok(result)
     }
 
+    /// Lists the callables, types, and opens that the evaluated fragments defined and that are still
+    /// in effect, with the lines they were defined on.
+    #[must_use]
+    pub fn session_items(&self) -> Vec<SessionItem> {
+        self.compiler.items()
+    }
+
+    /// Captures the fragments and expressions compiled so far, from which the compilation of the
+    /// session can be rebuilt.
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.compiler.checkpoint()
+    }
+
+    /// Rebuilds the compilation of a checkpoint, which should be taken from an interpreter created
+    /// with the same sources and options as this one before it evaluated anything. The callables
+    /// and types of the checkpoint become available, but its statements are not evaluated again,
+    /// so the variables they bound are not restored.
+    ///
+    /// # Errors
+    /// If compiling the checkpoint fails, the compiler errors are returned.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> std::result::Result<(), Vec<Error>> {
+        let fir_package = self.fir_store.get_mut(self.package);
+        let lowerer = &mut self.lowerer;
+        self.compiler
+            .restore(checkpoint, |increment| {
+                lowerer.lower_and_update_package(fir_package, &increment.hir);
+            })
+            .map_err(into_errors)?;
+
+        // Continue numbering lines after the last one in the checkpoint, so that their labels
+        // stay unique.
+        self.lines = checkpoint
+            .fragment_names()
+            .filter_map(|name| name.strip_prefix("line_")?.parse::<u32>().ok())
+            .map(|line| line + 1)
+            .fold(self.lines, u32::max);
+        Ok(())
+    }
+
     /// Runs the given entry expression on a new instance of the environment and simulator,
     /// but using the current compilation.
     pub fn run(
@@ -300,13 +430,12 @@ impl Interpreter {
         expr: &str,
     ) -> std::result::Result<InterpretResult, Vec<Error>> {
         let stmt_id = self.compile_expr_to_stmt(expr)?;
-        if self.quantum_seed.is_some() {
-            sim.set_seed(self.quantum_seed);
-        }
 
         Ok(eval(
             self.package,
+            self.user_packages(),
             self.classical_seed,
+            self.quantum_seed,
             stmt_id.into(),
             self.compiler.package_store(),
             &self.fir_store,
@@ -374,10 +503,13 @@ impl Debugger {
             language_features,
         )?;
         let source_package_id = interpreter.source_package;
+        let mut state = State::new(source_package_id, None);
+        state.set_user_packages(interpreter.user_packages());
+        state.set_capture_args(true);
         Ok(Self {
             interpreter,
             position_encoding,
-            state: State::new(source_package_id, None),
+            state,
         })
     }
 
@@ -392,6 +524,36 @@ impl Debugger {
         Ok(())
     }
 
+    /// Sets whether execution pauses with `StepResult::ExceptionHit` when a `fail` expression or
+    /// an intrinsic is about to produce an error, so that the stack can be inspected before it
+    /// unwinds.
+    pub fn set_break_on_fail(&mut self, break_on_fail: bool) {
+        self.state.set_break_on_fail(break_on_fail);
+    }
+
+    /// Sets a logpoint on the given statement, which renders the message template against the
+    /// current locals and sends it to the receiver each time the statement executes, without
+    /// pausing. Placeholders of the form `{name}` are replaced with the value of the local `name`.
+    pub fn set_logpoint(&mut self, stmt: StmtId, template: &str) {
+        self.state.set_logpoint(stmt, template);
+    }
+
+    /// Removes all logpoints.
+    pub fn clear_logpoints(&mut self) {
+        self.state.clear_logpoints();
+    }
+
+    /// Sets the hit condition of the breakpoint on the given statement, so that execution only
+    /// pauses there when the number of hits meets the condition.
+    pub fn set_hit_condition(&mut self, stmt: StmtId, condition: HitCondition) {
+        self.state.set_hit_condition(stmt, condition);
+    }
+
+    /// Removes all breakpoint hit conditions and resets the hit counts.
+    pub fn clear_hit_conditions(&mut self) {
+        self.state.clear_hit_conditions();
+    }
+
     /// Resumes execution with specified `StepAction`.
     /// # Errors
     /// Returns a vector of errors if evaluating the entry point fails.
@@ -420,6 +582,50 @@ impl Debugger {
             })
     }
 
+    /// Sets the number of statements after which evaluation yields, or `None` to never yield. See
+    /// [`Debugger::eval_step_async`].
+    pub fn set_yield_interval(&mut self, interval: Option<NonZeroU32>) {
+        self.state.set_yield_interval(interval);
+    }
+
+    /// Sets the number of statements between progress heartbeats sent to the receiver, or `None`
+    /// to never send them.
+    pub fn set_progress_interval(&mut self, interval: Option<NonZeroU64>) {
+        self.state.set_progress_interval(interval);
+    }
+
+    /// Resumes execution with specified `StepAction` like [`Debugger::eval_step`], but hands
+    /// control back to the executor every time the number of statements set with
+    /// [`Debugger::set_yield_interval`] has been evaluated. Dropping the returned future cancels
+    /// execution at the next yield point.
+    /// # Errors
+    /// Returns a vector of errors if evaluating the entry point fails.
+    pub async fn eval_step_async(
+        &mut self,
+        receiver: &mut impl Receiver,
+        breakpoints: &[StmtId],
+        step: StepAction,
+    ) -> std::result::Result<StepResult, Vec<Error>> {
+        self.state
+            .eval_async(
+                &self.interpreter.fir_store,
+                &mut self.interpreter.env,
+                &mut self.interpreter.sim,
+                receiver,
+                breakpoints,
+                step,
+            )
+            .await
+            .map_err(|(error, call_stack)| {
+                eval_error(
+                    self.interpreter.compiler.package_store(),
+                    &self.interpreter.fir_store,
+                    call_stack,
+                    error,
+                )
+            })
+    }
+
     #[must_use]
     pub fn get_stack_frames(&self) -> Vec<StackFrame> {
         let frames = self.state.get_stack_frames();
@@ -434,7 +640,7 @@ impl Debugger {
                 let functor = format!("{}", frame.functor);
                 let name = match callable {
                     Global::Callable(decl) => decl.name.name.to_string(),
-                    Global::Udt => "udt".into(),
+                    Global::Udt(udt) => udt.name.to_string(),
                 };
 
                 let hir_package = self
@@ -448,9 +654,14 @@ impl Debugger {
                     .find_by_offset(frame.span.lo)
                     .expect("frame should have a source");
                 let path = source.name.to_string();
+                let args = frame.arg.as_ref().map(|arg| match arg {
+                    FrameArg::Tuple(_) | FrameArg::Value(Value::Tuple(_)) => arg.to_string(),
+                    _ => format!("({arg})"),
+                });
                 StackFrame {
                     name,
                     functor,
+                    args,
                     path,
                     range: Range::from_span(
                         self.position_encoding,
@@ -467,55 +678,125 @@ impl Debugger {
         self.interpreter.sim.capture_quantum_state()
     }
 
+    /// Renders the FIR of the statement or expression that is evaluated next, including the IDs
+    /// and spans of all of its nodes, or `None` if evaluation has finished.
+    #[must_use]
+    pub fn disassemble(&self) -> Option<String> {
+        Some(match self.state.next_node()? {
+            NextNode::Stmt(package, stmt) => {
+                qsc_fir::pretty::stmt_to_string(self.interpreter.fir_store.get(package), stmt)
+            }
+            NextNode::Expr(package, expr) => {
+                qsc_fir::pretty::expr_to_string(self.interpreter.fir_store.get(package), expr)
+            }
+        })
+    }
+
     #[must_use]
     pub fn get_breakpoints(&self, path: &str) -> Vec<BreakpointSpan> {
-        let unit = self.source_package();
-
-        if let Some(source) = unit.sources.find_by_name(path) {
-            let package = self
-                .interpreter
-                .fir_store
-                .get(self.interpreter.source_package);
-            let mut collector = BreakpointCollector::new(
-                &unit.sources,
-                source.offset,
-                package,
-                self.position_encoding,
-            );
-            collector.visit_package(package);
-            let mut spans: Vec<_> = collector
-                .statements
-                .iter()
-                .map(|bps| BreakpointSpan {
-                    id: bps.id,
-                    range: bps.range,
-                })
-                .collect();
-
-            // Sort by start position (line first, column next)
-            spans.sort_by_key(|s| (s.range.start.line, s.range.start.column));
-            spans
-        } else {
-            Vec::new()
-        }
+        let package = map_fir_package_to_hir(self.interpreter.source_package);
+        let Some((source, stmts)) = self.breakpoint_stmts(package, path) else {
+            return Vec::new();
+        };
+        let mut spans: Vec<_> = stmts
+            .into_iter()
+            .map(|(id, span)| BreakpointSpan {
+                id,
+                range: Range::from_span(self.position_encoding, &source.contents, &span),
+            })
+            .collect();
+
+        // Sort by start position (line first, column next)
+        spans.sort_by_key(|s| (s.range.start.line, s.range.start.column));
+        spans
     }
 
+    /// The ID of the package containing the debugged sources.
     #[must_use]
-    pub fn get_locals(&self) -> Vec<VariableInfo> {
-        self.interpreter
-            .env
-            .get_variables_in_top_frame()
+    pub fn source_package_id(&self) -> hir::PackageId {
+        map_fir_package_to_hir(self.interpreter.source_package)
+    }
+
+    /// Resolves the statements on which a breakpoint can be set at the given position in a source
+    /// file of the given package, along with the range that is highlighted when the breakpoint is
+    /// hit. Candidates are ordered innermost first. A statement in a generated specialization has
+    /// the same range as the statement it was generated from, so it is returned alongside it.
+    #[must_use]
+    pub fn resolve_breakpoints(
+        &self,
+        package: hir::PackageId,
+        path: &str,
+        position: Position,
+    ) -> Vec<BreakpointSpan> {
+        self.source_by_name(package, path)
+            .map(|source| {
+                let offset = position.to_utf8_byte_offset(self.position_encoding, &source.contents);
+                self.resolve_breakpoints_at_offset(package, path, offset)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Resolves the statements on which a breakpoint can be set at the given byte offset in a
+    /// source file of the given package, as described in [`Debugger::resolve_breakpoints`].
+    #[must_use]
+    pub fn resolve_breakpoints_at_offset(
+        &self,
+        package: hir::PackageId,
+        path: &str,
+        offset: u32,
+    ) -> Vec<BreakpointSpan> {
+        let Some((source, stmts)) = self.breakpoint_stmts(package, path) else {
+            return Vec::new();
+        };
+        let mut candidates: Vec<_> = stmts
             .into_iter()
-            .filter(|v| !v.name.starts_with('@'))
+            .filter(|(_, span)| span.lo <= offset && offset < span.hi)
+            .collect();
+        candidates.sort_by_key(|(id, span)| (span.hi - span.lo, *id));
+        candidates
+            .into_iter()
+            .map(|(id, span)| BreakpointSpan {
+                id,
+                range: Range::from_span(self.position_encoding, &source.contents, &span),
+            })
             .collect()
     }
 
-    fn source_package(&self) -> &CompileUnit {
+    fn source_by_name(&self, package: hir::PackageId, path: &str) -> Option<&Source> {
         self.interpreter
             .compiler
             .package_store()
-            .get(map_fir_package_to_hir(self.interpreter.source_package))
-            .expect("Could not load package")
+            .get(package)?
+            .sources
+            .find_by_name(path)
+    }
+
+    /// Collects the statements of the given package that lie in the named source file, with their
+    /// spans relative to the start of the file.
+    fn breakpoint_stmts(
+        &self,
+        package: hir::PackageId,
+        path: &str,
+    ) -> Option<(&Source, FxHashSet<(u32, Span)>)> {
+        let unit = self.interpreter.compiler.package_store().get(package)?;
+        let source = unit.sources.find_by_name(path)?;
+        let fir_package = self
+            .interpreter
+            .fir_store
+            .get(map_hir_package_to_fir(package));
+        let mut collector = BreakpointCollector::new(&unit.sources, source.offset, fir_package);
+        collector.visit_package(fir_package);
+        Some((source, collector.statements))
+    }
+
+    #[must_use]
+    pub fn get_locals(&self) -> Vec<VariableInfo> {
+        self.interpreter
+            .env
+            .get_variables_in_top_frame()
+            .into_iter()
+            .filter(|v| !v.name.starts_with('@'))
+            .collect()
     }
 }
 
@@ -523,7 +804,9 @@ impl Debugger {
 #[allow(clippy::too_many_arguments)]
 fn eval(
     package: PackageId,
+    user_packages: Vec<PackageId>,
     classical_seed: Option<u64>,
+    quantum_seed: Option<u64>,
     id: EvalId,
     package_store: &PackageStore,
     fir_store: &fir::PackageStore,
@@ -531,8 +814,18 @@ fn eval(
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     receiver: &mut impl Receiver,
 ) -> InterpretResult {
-    qsc_eval::eval(package, classical_seed, id, fir_store, env, sim, receiver)
-        .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
+    qsc_eval::eval(
+        package,
+        user_packages,
+        classical_seed,
+        quantum_seed,
+        id,
+        fir_store,
+        env,
+        sim,
+        receiver,
+    )
+    .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
 }
 
 /// Represents a stack frame for debugging.
@@ -541,6 +834,8 @@ pub struct StackFrame {
     pub name: String,
     /// The functor of the callable.
     pub functor: String,
+    /// The arguments of the call, as a parenthesized list, if they were captured.
+    pub args: Option<String>,
     /// The path of the source file.
     pub path: String,
     /// The source range of the call site.
@@ -556,26 +851,19 @@ pub struct BreakpointSpan {
 }
 
 struct BreakpointCollector<'a> {
-    statements: FxHashSet<BreakpointSpan>,
+    statements: FxHashSet<(u32, Span)>,
     sources: &'a SourceMap,
     offset: u32,
     package: &'a Package,
-    position_encoding: Encoding,
 }
 
 impl<'a> BreakpointCollector<'a> {
-    fn new(
-        sources: &'a SourceMap,
-        offset: u32,
-        package: &'a Package,
-        position_encoding: Encoding,
-    ) -> Self {
+    fn new(sources: &'a SourceMap, offset: u32, package: &'a Package) -> Self {
         Self {
             statements: FxHashSet::default(),
             sources,
             offset,
             package,
-            position_encoding,
         }
     }
 
@@ -590,11 +878,7 @@ impl<'a> BreakpointCollector<'a> {
         if source.offset == self.offset {
             let span = stmt.span - source.offset;
             if span != Span::default() {
-                let bps = BreakpointSpan {
-                    id: stmt.id.into(),
-                    range: Range::from_span(self.position_encoding, &source.contents, &span),
-                };
-                self.statements.insert(bps);
+                self.statements.insert((stmt.id.into(), span));
             }
         }
     }
@@ -646,6 +930,7 @@ fn eval_error(
     call_stack: Vec<Frame>,
     error: qsc_eval::Error,
 ) -> Vec<Error> {
+    let frames = report_frames(package_store, fir_store, &call_stack);
     let stack_trace = if call_stack.is_empty() {
         None
     } else {
@@ -657,7 +942,7 @@ fn eval_error(
         ))
     };
 
-    vec![error::from_eval(error, package_store, stack_trace).into()]
+    vec![error::from_eval(error, package_store, stack_trace, frames).into()]
 }
 
 fn into_errors(errors: Vec<crate::compile::Error>) -> Vec<Error> {
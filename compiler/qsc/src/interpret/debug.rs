@@ -4,11 +4,13 @@
 #[cfg(test)]
 mod tests;
 
+use crate::error::{ReportFrame, ReportLocation};
 use qsc_eval::debug::{map_fir_package_to_hir, Frame};
 use qsc_fir::fir::{Global, PackageStoreLookup, StoreItemId};
 use qsc_frontend::compile::PackageStore;
 use qsc_hir::hir;
 use qsc_hir::hir::{Item, ItemKind};
+use std::fmt::Write;
 
 #[must_use]
 pub(crate) fn format_call_stack(
@@ -25,23 +27,8 @@ pub(crate) fn format_call_stack(
     frames.reverse();
 
     for frame in frames {
-        let Some(Global::Callable(call)) = globals.get_global(frame.id) else {
-            panic!("missing global");
-        };
-
         trace.push_str("    at ");
-        if frame.functor.adjoint {
-            trace.push_str("Adjoint ");
-        }
-        if frame.functor.controlled > 0 {
-            trace.push_str(&format!("Controlled({}) ", frame.functor.controlled));
-        }
-        if let Some(item) = get_item_parent(store, frame.id) {
-            if let Some(ns) = get_ns_name(&item) {
-                trace.push_str(&format!("{ns}."));
-            }
-        }
-        trace.push_str(&format!("{}", call.name.name));
+        trace.push_str(&frame_name(store, globals, &frame));
 
         let name = get_item_file_name(store, frame.id);
         trace.push_str(&format!(
@@ -54,6 +41,49 @@ pub(crate) fn format_call_stack(
     trace
 }
 
+/// Describes the frames of the call stack for an error report, innermost first.
+#[must_use]
+pub(crate) fn report_frames(
+    store: &PackageStore,
+    globals: &impl PackageStoreLookup,
+    frames: &[Frame],
+) -> Vec<ReportFrame> {
+    frames
+        .iter()
+        .rev()
+        .map(|frame| ReportFrame {
+            name: frame_name(store, globals, frame),
+            location: store
+                .get(map_fir_package_to_hir(frame.id.package))
+                .and_then(|unit| unit.sources.find_by_offset(frame.span.lo))
+                .map(|source| ReportLocation::from_source(source, frame.span)),
+        })
+        .collect()
+}
+
+/// The qualified name of the callable of the frame, prefixed by its functors.
+fn frame_name(store: &PackageStore, globals: &impl PackageStoreLookup, frame: &Frame) -> String {
+    let Some(Global::Callable(call)) = globals.get_global(frame.id) else {
+        panic!("missing global");
+    };
+
+    let mut name = String::new();
+    if frame.functor.adjoint {
+        name.push_str("Adjoint ");
+    }
+    if frame.functor.controlled > 0 {
+        write!(name, "Controlled({}) ", frame.functor.controlled)
+            .expect("writing to string should succeed");
+    }
+    if let Some(item) = get_item_parent(store, frame.id) {
+        if let Some(ns) = get_ns_name(&item) {
+            write!(name, "{ns}.").expect("writing to string should succeed");
+        }
+    }
+    name.push_str(&call.name.name);
+    name
+}
+
 #[must_use]
 fn get_item_parent(store: &PackageStore, id: StoreItemId) -> Option<Item> {
     let package = map_fir_package_to_hir(id.package);
@@ -222,4 +222,601 @@ mod given_debugger {
             Ok(())
         }
     }
+
+    #[cfg(test)]
+    mod break_on_fail {
+        use super::*;
+
+        static FAILING_SOURCE: &str = r#"
+            namespace Test {
+                @EntryPoint()
+                operation A() : Int {
+                    let x = B(3);
+                    x
+                }
+                operation B(n : Int) : Int {
+                    if n > 2 {
+                        fail "n is too large";
+                    }
+                    n
+                }
+            }"#;
+
+        fn failing_debugger() -> Result<Debugger, Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), FAILING_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            Ok(debugger)
+        }
+
+        #[test]
+        fn pauses_before_fail_with_stack_intact() -> Result<(), Vec<crate::interpret::Error>> {
+            let mut debugger = failing_debugger()?;
+            debugger.set_break_on_fail(true);
+            let result = step(&mut debugger, &[], StepAction::Continue);
+            match result.0 {
+                Ok(StepResult::ExceptionHit(qsc_eval::Error::UserFail(message, _))) => {
+                    assert_eq!(message, "n is too large");
+                }
+                Ok(v) => panic!("Expected ExceptionHit, got {v:?}"),
+                Err(e) => panic!("Expected ExceptionHit, got {e:?}"),
+            }
+            let names = debugger
+                .get_stack_frames()
+                .into_iter()
+                .map(|frame| frame.name)
+                .collect::<Vec<_>>();
+            assert_eq!(names, vec!["A", "B"]);
+
+            let result = step(&mut debugger, &[], StepAction::Continue);
+            match result.0 {
+                Err(errors) => assert_eq!(errors[0].to_string(), "runtime error"),
+                Ok(v) => panic!("Expected error, got {v:?}"),
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn stack_frames_show_captured_arguments() -> Result<(), Vec<crate::interpret::Error>> {
+            let source = r#"
+                namespace Test {
+                    @EntryPoint()
+                    operation A() : Unit {
+                        use qs = Qubit[20];
+                        C(qs, 2);
+                    }
+                    operation C(qs : Qubit[], n : Int) : Unit {
+                        B(qs[3], 0.5);
+                    }
+                    operation B(q : Qubit, x : Double) : Unit {
+                        fail "stop";
+                    }
+                }"#;
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            debugger.set_break_on_fail(true);
+            let result = step(&mut debugger, &[], StepAction::Continue);
+            assert!(
+                matches!(result.0, Ok(StepResult::ExceptionHit(_))),
+                "Expected ExceptionHit, got {:?}",
+                result.0
+            );
+            let frames = debugger
+                .get_stack_frames()
+                .into_iter()
+                .map(|frame| format!("{}{}", frame.name, frame.args.unwrap_or_default()))
+                .collect::<Vec<_>>();
+            assert_eq!(frames, vec!["A()", "C(Qubit[20], 2)", "B(Qubit3, 0.5)"]);
+            Ok(())
+        }
+
+        #[test]
+        fn disabled_reports_error() -> Result<(), Vec<crate::interpret::Error>> {
+            let mut debugger = failing_debugger()?;
+            let result = step(&mut debugger, &[], StepAction::Continue);
+            match result.0 {
+                Err(errors) => assert_eq!(errors[0].to_string(), "runtime error"),
+                Ok(v) => panic!("Expected error, got {v:?}"),
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod logpoint {
+        use super::*;
+
+        static LOOP_SOURCE: &str = r#"
+            namespace Test {
+                @EntryPoint()
+                operation A() : Int {
+                    mutable total = 0;
+                    for i in 0..2 {
+                        set total += i;
+                    }
+                    total
+                }
+            }"#;
+
+        #[test]
+        fn renders_template_each_time_without_pausing() -> Result<(), Vec<crate::interpret::Error>>
+        {
+            let sources = SourceMap::new([("test".into(), LOOP_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            let stmt = debugger
+                .get_breakpoints("test")
+                .into_iter()
+                .find(|bp| bp.range.start.line == 6)
+                .expect("statement should have a breakpoint span");
+            debugger.set_logpoint(
+                stmt.id.into(),
+                "i = {i}, total = {total}, {{unknown}} = {x}",
+            );
+            let result = step(&mut debugger, &[], StepAction::Continue);
+            match result.0 {
+                Ok(StepResult::Return(value)) => assert_eq!(value.to_string(), "3"),
+                Ok(v) => panic!("Expected Return, got {v:?}"),
+                Err(e) => panic!("Expected Return, got {e:?}"),
+            }
+            assert_eq!(
+                result.1,
+                "i = 0, total = 0, {unknown} = <x: not found>\n\
+                 i = 1, total = 0, {unknown} = <x: not found>\n\
+                 i = 2, total = 1, {unknown} = <x: not found>"
+            );
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod hit_condition {
+        use super::*;
+        use crate::interpret::HitCondition;
+
+        static LOOP_SOURCE: &str = r#"
+            namespace Test {
+                @EntryPoint()
+                operation A() : Int {
+                    mutable total = 0;
+                    for i in 1..10 {
+                        set total += i;
+                    }
+                    total
+                }
+            }"#;
+
+        fn hits(condition: HitCondition) -> Result<Vec<i64>, Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), LOOP_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            let stmt = debugger
+                .get_breakpoints("test")
+                .into_iter()
+                .find(|bp| bp.range.start.line == 6)
+                .expect("statement should have a breakpoint span")
+                .id
+                .into();
+            debugger.set_hit_condition(stmt, condition);
+            let mut hits = Vec::new();
+            loop {
+                match step(&mut debugger, &[stmt], StepAction::Continue).0? {
+                    StepResult::BreakpointHit(_) => {
+                        let i = debugger
+                            .get_locals()
+                            .into_iter()
+                            .find(|local| &*local.name == "i")
+                            .expect("loop variable should be in scope");
+                        hits.push(i.value.unwrap_int());
+                    }
+                    StepResult::Return(value) => {
+                        assert_eq!(value.to_string(), "55");
+                        return Ok(hits);
+                    }
+                    v => panic!("Expected BP or Return, got {v:?}"),
+                }
+            }
+        }
+
+        #[test]
+        fn equal_breaks_on_nth_hit() -> Result<(), Vec<crate::interpret::Error>> {
+            assert_eq!(hits(HitCondition::Equal(7))?, vec![7]);
+            Ok(())
+        }
+
+        #[test]
+        fn multiple_breaks_on_every_nth_hit() -> Result<(), Vec<crate::interpret::Error>> {
+            assert_eq!(hits(HitCondition::Multiple(4))?, vec![4, 8]);
+            Ok(())
+        }
+
+        #[test]
+        fn after_breaks_on_hits_after_n() -> Result<(), Vec<crate::interpret::Error>> {
+            assert_eq!(hits(HitCondition::After(8))?, vec![9, 10]);
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod resolve_breakpoints {
+        use super::*;
+        use qsc_data_structures::line_column::Position;
+
+        static ADJOINT_SOURCE: &str = r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Foo(q); Adjoint Foo(q);
+                }
+                operation Foo(q : Qubit) : Unit is Adj {
+                    H(q);
+                    S(q);
+                }
+            }"#;
+
+        fn adjoint_debugger() -> Result<Debugger, Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), ADJOINT_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            Ok(debugger)
+        }
+
+        #[test]
+        fn position_resolves_to_statements_in_generated_specializations(
+        ) -> Result<(), Vec<crate::interpret::Error>> {
+            let mut debugger = adjoint_debugger()?;
+            let package = debugger.source_package_id();
+            let candidates = debugger.resolve_breakpoints(
+                package,
+                "test",
+                Position {
+                    line: 9,
+                    column: 21,
+                },
+            );
+            assert_eq!(candidates.len(), 2);
+            assert!(candidates
+                .iter()
+                .all(|candidate| candidate.range == candidates[0].range));
+            assert_eq!(candidates[0].range.start.line, 9);
+            assert_eq!(candidates[0].range.start.column, 20);
+            assert_eq!(candidates[0].range.end.column, 25);
+
+            // The body and the generated adjoint each hit their own statement.
+            let ids = candidates
+                .iter()
+                .map(|candidate| candidate.id.into())
+                .collect::<Vec<StmtId>>();
+            let mut hits = Vec::new();
+            loop {
+                match step(&mut debugger, &ids, StepAction::Continue).0? {
+                    StepResult::BreakpointHit(id) => hits.push(id),
+                    StepResult::Return(_) => break,
+                    v => panic!("Expected BP or Return, got {v:?}"),
+                }
+            }
+            assert_eq!(hits.len(), 2);
+            assert!(ids.contains(&hits[0]) && ids.contains(&hits[1]) && hits[0] != hits[1]);
+            Ok(())
+        }
+
+        #[test]
+        fn loop_variable_resolves_to_statements_in_generated_adjoint(
+        ) -> Result<(), Vec<crate::interpret::Error>> {
+            let source = r#"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main() : Unit {
+                        use qs = Qubit[2];
+                        Foo(qs); Adjoint Foo(qs);
+                    }
+                    operation Foo(qs : Qubit[]) : Unit is Adj {
+                        for q in qs {
+                            H(q);
+                        }
+                    }
+                }"#;
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            let package = debugger.source_package_id();
+            let offset = u32::try_from(source.find("for q").expect("source should contain loop"))
+                .expect("offset should fit in u32")
+                + 4;
+            let candidates = debugger.resolve_breakpoints_at_offset(package, "test", offset);
+
+            // The binding of the loop variable in the body and in the reversed loop of the
+            // generated adjoint both have the span of the loop variable.
+            let ids = candidates
+                .iter()
+                .filter(|candidate| candidate.range == candidates[0].range)
+                .map(|candidate| candidate.id.into())
+                .collect::<Vec<StmtId>>();
+            assert_eq!(ids.len(), 2);
+            assert_eq!(
+                candidates[0].range.end.column - candidates[0].range.start.column,
+                1
+            );
+
+            let mut hits = Vec::new();
+            loop {
+                match step(&mut debugger, &ids, StepAction::Continue).0? {
+                    StepResult::BreakpointHit(id) => hits.push(id),
+                    StepResult::Return(_) => break,
+                    v => panic!("Expected BP or Return, got {v:?}"),
+                }
+            }
+            assert_eq!(hits.len(), 4);
+            assert!(ids.iter().all(|id| hits.contains(id)));
+            Ok(())
+        }
+
+        #[test]
+        fn offset_resolves_to_innermost_statement_first() -> Result<(), Vec<crate::interpret::Error>>
+        {
+            let source = r#"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main() : Int {
+                        let x = {
+                            let y = 1;
+                            y + 1
+                        };
+                        x
+                    }
+                }"#;
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            let package = debugger.source_package_id();
+            let offset =
+                u32::try_from(source.find("let y").expect("source should contain `let y`"))
+                    .expect("offset should fit in u32");
+            let lines = debugger
+                .resolve_breakpoints_at_offset(package, "test", offset)
+                .into_iter()
+                .map(|candidate| candidate.range.start.line)
+                .collect::<Vec<_>>();
+            assert_eq!(lines, vec![5, 4]);
+            assert!(debugger
+                .resolve_breakpoints_at_offset(package, "test", 0)
+                .is_empty());
+            assert!(debugger
+                .resolve_breakpoints_at_offset(package, "missing", offset)
+                .is_empty());
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod disassemble {
+        use super::*;
+        use expect_test::expect;
+
+        /// Replaces node IDs, which depend on the size of the standard library, with `_`.
+        fn disassemble(debugger: &Debugger) -> String {
+            debugger
+                .disassemble()
+                .expect("node should be next")
+                .lines()
+                .map(|line| {
+                    let (indent, node) = line.split_at(line.len() - line.trim_start().len());
+                    let mut words = node.splitn(3, ' ');
+                    let kind = words.next().unwrap_or_default();
+                    let _id = words.next();
+                    format!("{indent}{kind} _ {}", words.next().unwrap_or_default())
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        #[test]
+        fn renders_next_node_at_breakpoint() -> Result<(), Vec<crate::interpret::Error>> {
+            let source = r#"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main() : Int {
+                        let x = 1 + 2;
+                        x
+                    }
+                }"#;
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            let ids = get_breakpoint_ids(&debugger, "test");
+            expect_bp(&mut debugger, &ids, ids[0]);
+            expect![[r#"
+                Expr _ [145-150] [Type Int]: BinOp (Add)
+                    Expr _ [145-146] [Type Int]: Lit: Int(1)
+                    Expr _ [149-150] [Type Int]: Lit: Int(2)"#]]
+            .assert_eq(&disassemble(&debugger));
+            expect_next(&mut debugger);
+            expect!["Expr _ [176-177] [Type Int]: Var: Local 2"].assert_eq(&disassemble(&debugger));
+            expect_return(debugger, "3");
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod yielding {
+        use super::*;
+        use std::future::Future;
+        use std::num::NonZeroU32;
+        use std::pin::pin;
+        use std::task::{Context, Poll, Waker};
+
+        fn stepping_debugger(interval: u32) -> Result<Debugger, Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), STEPPING_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            debugger.set_yield_interval(NonZeroU32::new(interval));
+            Ok(debugger)
+        }
+
+        /// Steps until a result other than `Yield`, returning it and the number of yields.
+        fn step_past_yields(
+            debugger: &mut Debugger,
+            breakpoints: &[StmtId],
+            action: StepAction,
+        ) -> (StepResult, usize) {
+            let mut yields = 0;
+            loop {
+                match step(debugger, breakpoints, action).0 {
+                    Ok(StepResult::Yield) => yields += 1,
+                    Ok(result) => return (result, yields),
+                    Err(e) => panic!("Expected result, got {e:?}"),
+                }
+            }
+        }
+
+        #[test]
+        fn stepping_is_unaffected_by_yields() -> Result<(), Vec<crate::interpret::Error>> {
+            let mut debugger = stepping_debugger(1)?;
+            let ids = get_breakpoint_ids(&debugger, "test");
+            let (result, _) = step_past_yields(&mut debugger, &ids, StepAction::Next);
+            assert!(matches!(result, StepResult::BreakpointHit(id) if id == ids[0]));
+            // Stepping over the call to `B` yields inside it, but still stops in `A`.
+            let (result, yields) = step_past_yields(&mut debugger, &[], StepAction::Next);
+            assert!(matches!(result, StepResult::Next), "got {result:?}");
+            assert!(yields > 0);
+            let (result, _) = step_past_yields(&mut debugger, &[], StepAction::Next);
+            assert!(matches!(result, StepResult::Next), "got {result:?}");
+            let (result, _) = step_past_yields(&mut debugger, &[], StepAction::Next);
+            assert!(matches!(result, StepResult::Return(value) if value.to_string() == "42"));
+            Ok(())
+        }
+
+        #[test]
+        fn async_evaluation_returns_control_to_executor() -> Result<(), Vec<crate::interpret::Error>>
+        {
+            let mut debugger = stepping_debugger(2)?;
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let mut future =
+                pin!(debugger.eval_step_async(&mut receiver, &[], StepAction::Continue));
+            let mut context = Context::from_waker(Waker::noop());
+            let mut pending = 0;
+            let result = loop {
+                match future.as_mut().poll(&mut context) {
+                    Poll::Ready(result) => break result?,
+                    Poll::Pending => pending += 1,
+                }
+            };
+            assert!(matches!(result, StepResult::Return(value) if value.to_string() == "42"));
+            assert!(pending > 0);
+            Ok(())
+        }
+    }
+
+    mod progress {
+        use super::*;
+        use qsc_eval::output::{self, Progress, Receiver};
+        use std::num::NonZeroU64;
+
+        #[derive(Default)]
+        struct ProgressReceiver(Vec<Progress>);
+
+        impl Receiver for ProgressReceiver {
+            fn state_dump(&mut self, _dump: output::StateDump) -> Result<(), output::Error> {
+                Ok(())
+            }
+
+            fn message(&mut self, _msg: &str) -> Result<(), output::Error> {
+                Ok(())
+            }
+
+            fn progress(&mut self, progress: &Progress) -> Result<(), output::Error> {
+                self.0.push(progress.clone());
+                Ok(())
+            }
+        }
+
+        fn run(
+            interval: Option<NonZeroU64>,
+        ) -> Result<Vec<Progress>, Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), STEPPING_SOURCE.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )?;
+            debugger.set_entry()?;
+            debugger.set_progress_interval(interval);
+            let mut receiver = ProgressReceiver::default();
+            let result = debugger.eval_step(&mut receiver, &[], StepAction::Continue)?;
+            assert!(matches!(result, StepResult::Return(value) if value.to_string() == "42"));
+            Ok(receiver.0)
+        }
+
+        #[test]
+        fn heartbeats_are_sent_at_interval() -> Result<(), Vec<crate::interpret::Error>> {
+            let heartbeats = run(NonZeroU64::new(2))?;
+            let summary = heartbeats
+                .iter()
+                .map(|p| {
+                    assert!(p.elapsed.is_some());
+                    format!(
+                        "{} {}",
+                        p.statements,
+                        p.callable.as_deref().unwrap_or("<top>")
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(summary, ["2 B", "4 B", "6 C", "8 B", "10 A"]);
+            Ok(())
+        }
+
+        #[test]
+        fn heartbeats_can_be_disabled() -> Result<(), Vec<crate::interpret::Error>> {
+            assert!(run(None)?.is_empty());
+            Ok(())
+        }
+    }
 }
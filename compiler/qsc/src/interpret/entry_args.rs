@@ -0,0 +1,194 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::Error;
+use num_bigint::BigInt;
+use qsc_eval::val::{self, Value};
+use qsc_fir::fir::Pauli;
+use qsc_hir::{
+    hir::{Attr, CallableDecl, ItemKind, Package, PatKind},
+    ty::{Prim, Ty},
+};
+use std::rc::Rc;
+
+/// Builds the expression that calls the package's entry point with the given arguments, checking
+/// each argument against the type of its parameter.
+pub(super) fn entry_call(package: &Package, args: &[Value]) -> Result<String, Vec<Error>> {
+    let (name, decl) = entry_point(package).ok_or_else(|| vec![Error::NoEntryPoint])?;
+    let params = decl.params();
+    if params.len() != args.len() {
+        return Err(vec![Error::EntryArgCount(params.len(), args.len())]);
+    }
+
+    let mut sources = Vec::new();
+    let mut errors = Vec::new();
+    for (ix, (param, arg)) in params.iter().zip(args).enumerate() {
+        match to_source(arg, &param.ty) {
+            Some(source) => sources.push(source),
+            None => errors.push(Error::EntryArgType(
+                param_name(&param.kind, ix),
+                param.ty.display(),
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(format!("{name}({})", sources.join(", ")))
+    } else {
+        Err(errors)
+    }
+}
+
+/// Reads the arguments for the package's entry point from JSON, which is either an array with one
+/// element per parameter or an object with one field per parameter name. Each argument is read as
+/// the type of its parameter.
+pub(super) fn json_args(package: &Package, json: &str) -> Result<Vec<Value>, Vec<Error>> {
+    let (_, decl) = entry_point(package).ok_or_else(|| vec![Error::NoEntryPoint])?;
+    let params = decl.params();
+    let json: serde_json::Value =
+        serde_json::from_str(json).map_err(|_| vec![Error::EntryArgJson])?;
+    let jsons: Vec<_> = match &json {
+        serde_json::Value::Array(items) if items.len() == params.len() => items.iter().collect(),
+        serde_json::Value::Array(items) => {
+            return Err(vec![Error::EntryArgCount(params.len(), items.len())])
+        }
+        serde_json::Value::Object(fields) => params
+            .iter()
+            .enumerate()
+            .map(|(ix, param)| {
+                fields
+                    .get(&param_name(&param.kind, ix))
+                    .unwrap_or(&serde_json::Value::Null)
+            })
+            .collect(),
+        _ => return Err(vec![Error::EntryArgJson]),
+    };
+
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for (ix, (param, json)) in params.iter().zip(jsons).enumerate() {
+        match from_json(json, &param.ty) {
+            Some(value) => values.push(value),
+            None => errors.push(Error::EntryArgType(
+                param_name(&param.kind, ix),
+                param.ty.display(),
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Finds the entry point callable and its fully qualified name.
+fn entry_point(package: &Package) -> Option<(String, &CallableDecl)> {
+    let item = package
+        .items
+        .values()
+        .find(|item| item.attrs.contains(&Attr::EntryPoint))?;
+    let ItemKind::Callable(decl) = &item.kind else {
+        return None;
+    };
+
+    let namespace = package
+        .items
+        .values()
+        .find_map(|parent| match &parent.kind {
+            ItemKind::Namespace(name, items) if items.contains(&item.id) => Some(name),
+            _ => None,
+        });
+    let name = match namespace {
+        Some(namespace) => format!("{}.{}", namespace.name, decl.name.name),
+        None => decl.name.name.to_string(),
+    };
+    Some((name, decl))
+}
+
+fn param_name(kind: &PatKind, ix: usize) -> String {
+    match kind {
+        PatKind::Bind(name) => name.name.to_string(),
+        _ => ix.to_string(),
+    }
+}
+
+/// Writes the value as a Q# expression, if it has the given type.
+fn to_source(value: &Value, ty: &Ty) -> Option<String> {
+    match (ty, value) {
+        (Ty::Prim(Prim::BigInt), Value::BigInt(v)) => Some(format!("{v}L")),
+        (Ty::Prim(Prim::Bool), Value::Bool(v)) => Some(v.to_string()),
+        (Ty::Prim(Prim::Double), &Value::Double(v)) if v.is_finite() => Some(format!("{v:?}")),
+        (Ty::Prim(Prim::Int), Value::Int(v)) => Some(v.to_string()),
+        (Ty::Prim(Prim::Pauli), Value::Pauli(_))
+        | (Ty::Prim(Prim::Range), Value::Range(Some(_), _, Some(_)))
+        | (Ty::Prim(Prim::Result), Value::Result(val::Result::Val(_))) => Some(value.to_string()),
+        (Ty::Prim(Prim::String), Value::String(v)) => Some(format!("{v:?}")),
+        (Ty::Array(item), Value::Array(values)) => {
+            let values = values
+                .iter()
+                .map(|value| to_source(value, item))
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("[{}]", values.join(", ")))
+        }
+        (Ty::Tuple(items), Value::Tuple(values)) if items.len() == values.len() => {
+            let values = values
+                .iter()
+                .zip(items)
+                .map(|(value, item)| to_source(value, item))
+                .collect::<Option<Vec<_>>>()?;
+            match values.as_slice() {
+                [value] => Some(format!("({value},)")),
+                _ => Some(format!("({})", values.join(", "))),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Reads the value from JSON as the given type. A `Result` is written as `"Zero"` or `"One"`, a
+/// `Pauli` as its name, a `BigInt` as a number or a string of digits, and a tuple as an array.
+fn from_json(json: &serde_json::Value, ty: &Ty) -> Option<Value> {
+    match ty {
+        Ty::Prim(Prim::BigInt) => match json {
+            serde_json::Value::String(digits) => digits.parse().ok().map(Value::BigInt),
+            _ => json.as_i64().map(|v| Value::BigInt(BigInt::from(v))),
+        },
+        Ty::Prim(Prim::Bool) => json.as_bool().map(Value::Bool),
+        Ty::Prim(Prim::Double) => json.as_f64().map(Value::Double),
+        Ty::Prim(Prim::Int) => json.as_i64().map(Value::Int),
+        Ty::Prim(Prim::Pauli) => match json.as_str()? {
+            "PauliI" => Some(Value::Pauli(Pauli::I)),
+            "PauliX" => Some(Value::Pauli(Pauli::X)),
+            "PauliY" => Some(Value::Pauli(Pauli::Y)),
+            "PauliZ" => Some(Value::Pauli(Pauli::Z)),
+            _ => None,
+        },
+        Ty::Prim(Prim::Result) => match json.as_str()? {
+            "Zero" => Some(Value::Result(val::Result::Val(false))),
+            "One" => Some(Value::Result(val::Result::Val(true))),
+            _ => None,
+        },
+        Ty::Prim(Prim::String) => json.as_str().map(|v| Value::String(v.into())),
+        Ty::Array(item) => {
+            let values = json
+                .as_array()?
+                .iter()
+                .map(|json| from_json(json, item))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Value::Array(Rc::new(values)))
+        }
+        Ty::Tuple(items) if items.is_empty() && json.is_null() => Some(Value::unit()),
+        Ty::Tuple(items) => {
+            let jsons = json.as_array().filter(|jsons| jsons.len() == items.len())?;
+            let values = jsons
+                .iter()
+                .zip(items)
+                .map(|(json, item)| from_json(json, item))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Value::Tuple(values.into()))
+        }
+        _ => None,
+    }
+}
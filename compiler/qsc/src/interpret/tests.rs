@@ -4,11 +4,17 @@
 #![allow(clippy::needless_raw_string_hashes)]
 
 mod given_interpreter {
-    use crate::interpret::{Error, InterpretResult, Interpreter};
+    use crate::{
+        incremental::SessionItemKind,
+        interpret::{Error, InterpretResult, Interpreter},
+    };
     use expect_test::Expect;
     use miette::Diagnostic;
     use qsc_data_structures::language_features::LanguageFeatures;
-    use qsc_eval::{output::CursorReceiver, val::Value};
+    use qsc_eval::{
+        output::{CaptureReceiver, CursorReceiver, OutputEvent},
+        val::Value,
+    };
     use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
     use qsc_passes::PackageType;
     use std::{fmt::Write, io::Cursor, iter, str::from_utf8};
@@ -101,6 +107,21 @@ mod given_interpreter {
             is_only_value(&result, &output, &Value::Int(7));
         }
 
+        #[test]
+        fn heap_size_counts_shared_values_once() {
+            let mut interpreter = get_interpreter();
+            let empty = interpreter.heap_size();
+            line(&mut interpreter, "let a = [0, size = 1000];")
+                .0
+                .expect("line should succeed");
+            let with_array = interpreter.heap_size();
+            assert!(with_array - empty >= 1000 * std::mem::size_of::<Value>());
+            line(&mut interpreter, "let b = a;")
+                .0
+                .expect("line should succeed");
+            assert!(interpreter.heap_size() - with_array < 1000);
+        }
+
         #[test]
         fn let_bindings_can_be_shadowed() {
             let mut interpreter = get_interpreter();
@@ -417,6 +438,122 @@ mod given_interpreter {
             );
         }
 
+        #[test]
+        fn match_evaluates_first_matching_arm() {
+            let mut interpreter = get_interpreter();
+            line(
+                &mut interpreter,
+                r#"function Describe(n : Int) : String { match n { 0 => "zero", 1 => "one", other => $"many ({other})" } }"#,
+            )
+            .0
+            .expect("line should succeed");
+            let (result, output) = line(&mut interpreter, "(Describe(1), Describe(5))");
+            is_only_value(
+                &result,
+                &output,
+                &Value::Tuple(
+                    vec![
+                        Value::String("one".into()),
+                        Value::String("many (5)".into()),
+                    ]
+                    .into(),
+                ),
+            );
+        }
+
+        #[test]
+        fn session_items_list_live_definitions() {
+            let mut interpreter = get_interpreter();
+            for fragments in [
+                "open Microsoft.Quantum.Math;",
+                "namespace Foo { newtype Pair = (First : Int, Second : Int); operation A(q : Qubit) : Unit is Adj {} }",
+                "function B(x : Int) : Int { x }",
+                "function B(x : Int, y : Int) : Int { x + y }",
+            ] {
+                line(&mut interpreter, fragments)
+                    .0
+                    .expect("line should succeed");
+            }
+
+            let items = interpreter.session_items();
+            let items = items
+                .iter()
+                .map(|item| {
+                    (
+                        item.kind,
+                        item.name.as_ref(),
+                        item.signature.as_str(),
+                        item.source.as_ref(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                items,
+                vec![
+                    (
+                        SessionItemKind::Open,
+                        "Microsoft.Quantum.Math",
+                        "open Microsoft.Quantum.Math",
+                        "line_0",
+                    ),
+                    (
+                        SessionItemKind::Ty,
+                        "Foo.Pair",
+                        "newtype Pair = (First : Int, Second : Int)",
+                        "line_1",
+                    ),
+                    (
+                        SessionItemKind::Callable,
+                        "Foo.A",
+                        "operation A(q : Qubit) : Unit is Adj",
+                        "line_1",
+                    ),
+                    (
+                        SessionItemKind::Callable,
+                        "B",
+                        "function B(x : Int, y : Int) : Int",
+                        "line_3",
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn restored_checkpoint_keeps_items() {
+            let mut interpreter = get_interpreter();
+            for fragments in [
+                "function Double(x : Int) : Int { 2 * x }",
+                "Double(1)",
+                "function Double(x : Int) : Int { x + x + 1 }",
+            ] {
+                line(&mut interpreter, fragments)
+                    .0
+                    .expect("line should succeed");
+            }
+
+            let json = serde_json::to_string(&interpreter.checkpoint())
+                .expect("checkpoint should serialize");
+            let checkpoint = serde_json::from_str(&json).expect("checkpoint should deserialize");
+            let mut restored = get_interpreter();
+            restored
+                .restore(&checkpoint)
+                .expect("restore should succeed");
+            assert_eq!(restored.checkpoint(), checkpoint);
+
+            line(&mut restored, "function Triple(x : Int) : Int { 3 * x }")
+                .0
+                .expect("line should succeed");
+            assert_eq!(
+                restored
+                    .session_items()
+                    .last()
+                    .map(|item| item.source.as_ref()),
+                Some("line_3")
+            );
+            let (result, output) = line(&mut restored, "Double(Triple(1))");
+            is_only_value(&result, &output, &Value::Int(7));
+        }
+
         #[test]
         fn runtime_error_across_lines() {
             let mut interpreter = get_interpreter();
@@ -977,6 +1114,123 @@ mod given_interpreter {
             }
         }
 
+        #[test]
+        fn run_quantum_seed_is_independent_of_classical_seed() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    operation Foo() : (Result[], Int) {
+                        use qs = Qubit[16];
+                        ApplyToEach(H, qs);
+                        (Microsoft.Quantum.Measurement.MResetEachZ(qs), Microsoft.Quantum.Random.DrawRandomInt(0, 1000000))
+                    }
+                "},
+            );
+            is_only_value(&result, &output, &Value::unit());
+
+            let mut run_with_seeds = |quantum_seed, classical_seed| {
+                interpreter.set_quantum_seed(Some(quantum_seed));
+                interpreter.set_classical_seed(Some(classical_seed));
+                let (result, _) = run(&mut interpreter, "Foo()");
+                let value = result
+                    .expect("compilation should succeed")
+                    .expect("run should succeed");
+                let [results, int] = &*value.unwrap_tuple() else {
+                    panic!("value should be a tuple of two elements");
+                };
+                (results.clone(), int.clone())
+            };
+
+            let (results_a, int_a) = run_with_seeds(42, 1);
+            let (results_b, int_b) = run_with_seeds(42, 2);
+            let (results_c, int_c) = run_with_seeds(7, 1);
+            assert_eq!(results_a, results_b);
+            assert_ne!(int_a, int_b);
+            assert_ne!(results_a, results_c);
+            assert_eq!(int_a, int_c);
+        }
+
+        #[test]
+        fn run_with_qubit_loss_reports_warnings() {
+            use qsc_eval::{
+                backend::{
+                    loss::{LossModel, LostMeasurement, QubitLoss},
+                    SparseSim,
+                },
+                output::{self, Receiver},
+                PackageSpan,
+            };
+
+            /// Records messages, and warnings along with the code that they are located at.
+            struct WarningReceiver(Vec<String>, &'static str);
+            impl Receiver for WarningReceiver {
+                fn state_dump(&mut self, _dump: output::StateDump) -> Result<(), output::Error> {
+                    Ok(())
+                }
+
+                fn message(&mut self, msg: &str) -> Result<(), output::Error> {
+                    self.0.push(msg.to_string());
+                    Ok(())
+                }
+
+                fn warning(&mut self, msg: &str, span: PackageSpan) -> Result<(), output::Error> {
+                    let call = &self.1[span.span.lo as usize..span.span.hi as usize];
+                    self.0.push(format!("warning at `{call}`: {msg}"));
+                    Ok(())
+                }
+            }
+
+            let mut interpreter = get_interpreter();
+            let mut sim = QubitLoss::new(
+                SparseSim::new(),
+                LossModel {
+                    loss_probability: 1.0,
+                    lost_measurement: LostMeasurement::One,
+                    warn: true,
+                    ..LossModel::default()
+                },
+            );
+            let expr = "{ use q = Qubit(); H(q); X(q); Message($\"{M(q)}\"); Reset(q); }";
+            let mut receiver = WarningReceiver(Vec::new(), expr);
+            let value = interpreter
+                .run_with_sim(&mut sim, &mut receiver, expr)
+                .expect("compilation should succeed")
+                .expect("run should succeed");
+            assert_eq!(value, Value::unit());
+            assert_eq!(
+                receiver.0,
+                [
+                    "warning at `H(q)`: qubit 0 was lost during `H`",
+                    "warning at `X(q)`: `X` was not applied because qubit 0 is unavailable",
+                    "One",
+                ]
+            );
+        }
+
+        #[test]
+        fn run_with_deterministic_measurement_fails_on_superposition() {
+            use qsc_eval::backend::{deterministic::DeterministicMeasurement, SparseSim};
+
+            let mut interpreter = get_interpreter();
+            let mut sim = DeterministicMeasurement::new(SparseSim::new());
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let result = interpreter.run_with_sim(
+                &mut sim,
+                &mut receiver,
+                "{ use q = Qubit(); H(q); let r = M(q); Reset(q); }",
+            );
+            is_only_error(
+                &result.expect("compilation should succeed"),
+                &receiver.dump(),
+                &expect![[r#"
+                    runtime error: backend failed: measurement of qubit 0 is not deterministic, the probability of `One` is 0.500000
+                      failed during this operation [<entry>] [M(q)]
+                "#]],
+            );
+        }
+
         #[test]
         fn run_parse_error() {
             let mut interpreter = get_interpreter();
@@ -1168,6 +1422,232 @@ mod given_interpreter {
             is_unit_with_output_eval_entry(&result, &output, "hello there...");
         }
 
+        #[test]
+        fn entry_point_runs_with_host_args() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main(n : Int, bits : Result[], label : String) : (String, Int) {
+                    (label, n + Length(bits))
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let result = interpreter.run_entry_with_json_args(
+                &mut receiver,
+                r#"{ "n": 2, "bits": ["Zero", "One"], "label": "run \"a\"" }"#,
+            );
+            is_only_value(
+                &result,
+                &receiver.dump(),
+                &Value::Tuple(vec![Value::String("run \"a\"".into()), Value::Int(4)].into()),
+            );
+        }
+
+        #[test]
+        fn entry_point_rejects_host_args_of_wrong_type() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main(n : Int, flag : Bool) : Int { n }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let result =
+                interpreter.run_entry_with_args(&mut receiver, &[Value::Int(1), Value::Int(0)]);
+            is_only_error(
+                &result,
+                &receiver.dump(),
+                &expect![[r#"
+                    invalid argument for entry point parameter `flag`
+                "#]],
+            );
+
+            let result = interpreter.run_entry_with_json_args(&mut receiver, "[1]");
+            is_only_error(
+                &result,
+                &receiver.dump(),
+                &expect![[r#"
+                    expected 2 entry point arguments, found 1
+                "#]],
+            );
+        }
+
+        #[test]
+        fn backend_failure_in_source_called_from_expression_is_located_in_source() {
+            use qsc_eval::backend::{deterministic::DeterministicMeasurement, SparseSim};
+
+            let source = indoc! { r#"
+            namespace Test {
+                operation Measure(q : Qubit) : Result {
+                    M(q)
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            // The expression is in the interactive package, which comes after the source package.
+            let mut sim = DeterministicMeasurement::new(SparseSim::new());
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let result = interpreter.run_with_sim(
+                &mut sim,
+                &mut receiver,
+                "{ use q = Qubit(); H(q); let r = Test.Measure(q); Reset(q); }",
+            );
+            is_only_error(
+                &result.expect("compilation should succeed"),
+                &receiver.dump(),
+                &expect![[r#"
+                    runtime error: backend failed: measurement of qubit 0 is not deterministic, the probability of `One` is 0.500000
+                      failed during this operation [test] [M(q)]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn entry_output_is_captured_as_events() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    Message("hello there...");
+                    use q = Qubit();
+                    X(q);
+                    Microsoft.Quantum.Diagnostics.DumpMachine();
+                    Microsoft.Quantum.Measurement.MResetZ(q)
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            let mut receiver = CaptureReceiver::new();
+            interpreter
+                .eval_entry(&mut receiver)
+                .expect("entry should evaluate");
+            let events = receiver.take_events();
+            let [OutputEvent::Message(message), OutputEvent::StateDump {
+                qubit_count,
+                amplitudes,
+            }, OutputEvent::Result(result)] = &events[..]
+            else {
+                panic!("unexpected events: {events:?}");
+            };
+            assert_eq!(message, "hello there...");
+            assert_eq!(*qubit_count, 1);
+            assert_eq!(amplitudes.len(), 1);
+            assert_eq!(amplitudes[0].label, "|1⟩");
+            assert_eq!(*result, Value::RESULT_ONE);
+        }
+
+        #[test]
+        fn messages_are_located_at_their_call() {
+            use qsc_eval::{
+                debug::Frame,
+                output::{self, Receiver},
+                PackageSpan,
+            };
+
+            struct LocatingReceiver(Vec<(String, String, bool)>, &'static str);
+            impl Receiver for LocatingReceiver {
+                fn state_dump(&mut self, _dump: output::StateDump) -> Result<(), output::Error> {
+                    Ok(())
+                }
+
+                fn message(&mut self, _msg: &str) -> Result<(), output::Error> {
+                    panic!("message should be located");
+                }
+
+                fn message_at(
+                    &mut self,
+                    msg: &str,
+                    span: PackageSpan,
+                    caller: Option<&Frame>,
+                ) -> Result<(), output::Error> {
+                    let call = &self.1[span.span.lo as usize..span.span.hi as usize];
+                    self.0
+                        .push((msg.to_string(), call.to_string(), caller.is_some()));
+                    Ok(())
+                }
+            }
+
+            let source = indoc! { r#"
+            namespace Test {
+                function Greet(name : String) : Unit {
+                    Message($"hello {name}");
+                }
+                @EntryPoint()
+                operation Main() : Unit {
+                    Message("start");
+                    Greet("there");
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            let mut receiver = LocatingReceiver(Vec::new(), source);
+            interpreter
+                .eval_entry(&mut receiver)
+                .expect("entry should evaluate");
+            assert_eq!(
+                receiver.0,
+                [
+                    ("start".into(), "Message(\"start\")".into(), true),
+                    (
+                        "hello there".into(),
+                        "Message($\"hello {name}\")".into(),
+                        true
+                    ),
+                ]
+            );
+        }
+
         #[test]
         fn stdlib_members_can_be_accessed_from_sources() {
             let source = indoc! { r#"
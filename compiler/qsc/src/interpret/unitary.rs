@@ -0,0 +1,209 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::{Error, Interpreter};
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_eval::{
+    backend::SparseSim,
+    output::{self, Receiver, StateDump},
+    state::format_state_id,
+};
+
+/// The most qubits that a unitary matrix can be computed for. Computing the matrix simulates twice
+/// as many qubits, and the matrix of `n` qubits has `4ⁿ` entries.
+pub const MAX_UNITARY_QUBITS: usize = 10;
+
+/// Amplitudes with a magnitude below this tolerance are treated as zero when choosing the global
+/// phase.
+const PHASE_TOLERANCE: f64 = 1e-10;
+
+/// The unitary matrix of an operation, where the first qubit corresponds to the most significant
+/// bit of the row and column indices.
+///
+/// The global phase is chosen so that the first nonzero entry of the first column is real and
+/// positive, which makes matrices of operations that differ only by a global phase equal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitaryMatrix {
+    pub qubit_count: usize,
+    /// The rows of the matrix.
+    pub rows: Vec<Vec<Complex64>>,
+}
+
+impl UnitaryMatrix {
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the column of the matrix for the given basis state.
+    #[must_use]
+    pub fn column(&self, basis_state: usize) -> Vec<Complex64> {
+        self.rows.iter().map(|row| row[basis_state]).collect()
+    }
+}
+
+/// The first computational basis input on which two operations differ, as found by
+/// [`Interpreter::check_equivalence`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OperationDifference {
+    /// The index of the basis input, where the first qubit is the most significant bit.
+    pub basis_state: usize,
+    /// The label of the basis input, such as `|01⟩`.
+    pub label: String,
+    /// The output state of the first operation for this input.
+    pub expected: Vec<Complex64>,
+    /// The output state of the second operation for this input, aligned to the global phase of
+    /// the first operation.
+    pub actual: Vec<Complex64>,
+}
+
+/// Collects the dumped state as a dense vector.
+struct StateReceiver {
+    dimension: usize,
+    state: Option<Vec<Complex64>>,
+}
+
+impl Receiver for StateReceiver {
+    fn state_dump(&mut self, mut dump: StateDump) -> Result<(), output::Error> {
+        let mut dense = vec![Complex64::default(); self.dimension];
+        while let Some(chunk) = dump.next_chunk() {
+            for entry in chunk {
+                let index = usize::try_from(&entry.id).map_err(|_| output::Error)?;
+                *dense.get_mut(index).ok_or(output::Error)? = entry.amplitude;
+            }
+        }
+        self.state = Some(dense);
+        Ok(())
+    }
+
+    fn message(&mut self, _msg: &str) -> Result<(), output::Error> {
+        Ok(())
+    }
+}
+
+impl Interpreter {
+    /// Computes the unitary matrix of `operation`, an expression of type `Qubit[] => Unit`, acting
+    /// on `qubit_count` qubits.
+    ///
+    /// Rather than simulating the operation once for each computational basis input, it is applied
+    /// to the target half of the maximally entangled state `Σₖ |k⟩|k⟩`, whose amplitudes then hold
+    /// every column of the matrix. This needs twice as many simulated qubits as the operation acts
+    /// on. The operation must not measure or otherwise act non-unitarily on its qubits, or the
+    /// result is not meaningful.
+    /// # Errors
+    /// Returns an error if `qubit_count` is more than [`MAX_UNITARY_QUBITS`], or if the operation
+    /// does not compile or fails at runtime.
+    pub fn unitary_matrix(
+        &mut self,
+        operation: &str,
+        qubit_count: usize,
+    ) -> std::result::Result<UnitaryMatrix, Vec<Error>> {
+        if qubit_count > MAX_UNITARY_QUBITS {
+            return Err(vec![Error::UnitaryTooLarge(
+                qubit_count,
+                MAX_UNITARY_QUBITS,
+            )]);
+        }
+        let dimension = 1 << qubit_count;
+        let expr = format!(
+            "{{
+                use qubits = Qubit[2 * {qubit_count}];
+                let (inputs, targets) = (qubits[...{qubit_count} - 1], qubits[{qubit_count}...]);
+                for i in 0..{qubit_count} - 1 {{
+                    Microsoft.Quantum.Intrinsic.H(inputs[i]);
+                    Microsoft.Quantum.Intrinsic.CNOT(inputs[i], targets[i]);
+                }}
+                ({operation})(targets);
+                Microsoft.Quantum.Diagnostics.DumpMachine();
+                Microsoft.Quantum.Intrinsic.ResetAll(qubits);
+            }}"
+        );
+        let mut receiver = StateReceiver {
+            dimension: dimension * dimension,
+            state: None,
+        };
+        self.run_with_sim(&mut SparseSim::new(), &mut receiver, &expr)??;
+        let state = receiver.state.ok_or_else(|| vec![Error::NoStateDump])?;
+
+        // The amplitude of |k⟩|j⟩ is the entry in row j and column k, scaled down by the
+        // normalization of the entangled state.
+        #[allow(clippy::cast_precision_loss)]
+        let scale = (dimension as f64).sqrt();
+        let phase = (0..dimension)
+            .map(|row| state[row])
+            .find(|c| c.norm() > PHASE_TOLERANCE)
+            .map_or(Complex64::new(1.0, 0.0), |c| c.conj() / c.norm());
+        let rows = (0..dimension)
+            .map(|row| {
+                (0..dimension)
+                    .map(|col| state[col * dimension + row] * phase * scale)
+                    .collect()
+            })
+            .collect();
+        Ok(UnitaryMatrix { qubit_count, rows })
+    }
+
+    /// Checks whether the operations `expected` and `actual`, expressions of type
+    /// `Qubit[] => Unit`, are equivalent up to global phase when acting on `qubit_count` qubits.
+    ///
+    /// Entries of the unitary matrices may differ by at most `tolerance` after aligning the global
+    /// phases, so a tolerance of zero requires the matrices to match exactly. Returns the first
+    /// basis input on which the operations differ, or `None` if they are equivalent.
+    /// # Errors
+    /// Returns an error if `qubit_count` is more than [`MAX_UNITARY_QUBITS`], or if either
+    /// operation does not compile or fails at runtime.
+    pub fn check_equivalence(
+        &mut self,
+        expected: &str,
+        actual: &str,
+        qubit_count: usize,
+        tolerance: f64,
+    ) -> std::result::Result<Option<OperationDifference>, Vec<Error>> {
+        let expected = self.unitary_matrix(expected, qubit_count)?;
+        let actual = self.unitary_matrix(actual, qubit_count)?;
+
+        // Align the global phases on the largest entry of the expected matrix, which is the least
+        // sensitive to numerical error.
+        let (row, col) = (0..expected.dimension())
+            .flat_map(|row| (0..expected.dimension()).map(move |col| (row, col)))
+            .max_by(|&(r0, c0), &(r1, c1)| {
+                expected.rows[r0][c0]
+                    .norm()
+                    .total_cmp(&expected.rows[r1][c1].norm())
+            })
+            .expect("matrix should not be empty");
+        let reference = actual.rows[row][col];
+        let phase = if reference.norm() > PHASE_TOLERANCE {
+            expected.rows[row][col] / reference
+                * (reference.norm() / expected.rows[row][col].norm())
+        } else {
+            Complex64::new(1.0, 0.0)
+        };
+
+        for basis_state in 0..expected.dimension() {
+            let expected_column = expected.column(basis_state);
+            let actual_column = actual
+                .column(basis_state)
+                .into_iter()
+                .map(|c| c * phase)
+                .collect::<Vec<_>>();
+            if expected_column
+                .iter()
+                .zip(&actual_column)
+                .any(|(e, a)| (e - a).norm() > tolerance)
+            {
+                return Ok(Some(OperationDifference {
+                    basis_state,
+                    label: format_state_id(&BigUint::from(basis_state), qubit_count),
+                    expected: expected_column,
+                    actual: actual_column,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
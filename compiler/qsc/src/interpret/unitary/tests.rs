@@ -0,0 +1,171 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::interpret::{Error, Interpreter, MAX_UNITARY_QUBITS};
+use indoc::indoc;
+use num_complex::Complex64;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+use std::f64::consts::FRAC_1_SQRT_2;
+
+fn get_interpreter(source: &str) -> Interpreter {
+    Interpreter::new(
+        true,
+        SourceMap::new([("test".into(), source.into())], None),
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created")
+}
+
+fn assert_matrix_eq(actual: &[Vec<Complex64>], expected: &[Vec<Complex64>]) {
+    assert_eq!(actual.len(), expected.len(), "dimensions should match");
+    for (row, (actual_row, expected_row)) in actual.iter().zip(expected).enumerate() {
+        for (col, (a, e)) in actual_row.iter().zip(expected_row).enumerate() {
+            assert!(
+                (a - e).norm() < 1e-10,
+                "entry ({row}, {col}) should be {e} but was {a}"
+            );
+        }
+    }
+}
+
+fn real(rows: &[&[f64]]) -> Vec<Vec<Complex64>> {
+    rows.iter()
+        .map(|row| row.iter().map(|&re| Complex64::new(re, 0.0)).collect())
+        .collect()
+}
+
+#[test]
+fn hadamard_matrix() {
+    let mut interpreter = get_interpreter("");
+    let unitary = interpreter
+        .unitary_matrix("qs => H(qs[0])", 1)
+        .expect("unitary extraction should succeed");
+    assert_eq!(unitary.qubit_count, 1);
+    assert_matrix_eq(
+        &unitary.rows,
+        &real(&[
+            &[FRAC_1_SQRT_2, FRAC_1_SQRT_2],
+            &[FRAC_1_SQRT_2, -FRAC_1_SQRT_2],
+        ]),
+    );
+}
+
+#[test]
+fn first_qubit_is_most_significant() {
+    let mut interpreter = get_interpreter("");
+    let unitary = interpreter
+        .unitary_matrix("qs => CNOT(qs[0], qs[1])", 2)
+        .expect("unitary extraction should succeed");
+    assert_matrix_eq(
+        &unitary.rows,
+        &real(&[
+            &[1.0, 0.0, 0.0, 0.0],
+            &[0.0, 1.0, 0.0, 0.0],
+            &[0.0, 0.0, 0.0, 1.0],
+            &[0.0, 0.0, 1.0, 0.0],
+        ]),
+    );
+}
+
+#[test]
+fn global_phase_is_normalized() {
+    let mut interpreter = get_interpreter(indoc! {"
+        namespace Test {
+            operation PhaseShift(qs : Qubit[]) : Unit {
+                Rz(Microsoft.Quantum.Math.PI() / 2.0, qs[0]);
+            }
+        }
+    "});
+    let unitary = interpreter
+        .unitary_matrix("Test.PhaseShift", 1)
+        .expect("unitary extraction should succeed");
+    assert_matrix_eq(
+        &unitary.rows,
+        &[
+            vec![Complex64::new(1.0, 0.0), Complex64::default()],
+            vec![Complex64::default(), Complex64::new(0.0, 1.0)],
+        ],
+    );
+    assert_eq!(unitary.column(1)[1], unitary.rows[1][1]);
+}
+
+#[test]
+fn runtime_failure_is_an_error() {
+    let mut interpreter = get_interpreter("");
+    let errors = interpreter
+        .unitary_matrix("qs => fail \"unsupported\"", 1)
+        .expect_err("unitary extraction should fail");
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn too_many_qubits_is_an_error() {
+    let mut interpreter = get_interpreter("");
+    for qubit_count in [MAX_UNITARY_QUBITS + 1, usize::MAX] {
+        let errors = interpreter
+            .unitary_matrix("qs => ()", qubit_count)
+            .expect_err("unitary extraction should fail");
+        assert!(
+            matches!(
+                errors[..],
+                [Error::UnitaryTooLarge(count, MAX_UNITARY_QUBITS)] if count == qubit_count
+            ),
+            "{errors:?}"
+        );
+    }
+}
+
+#[test]
+fn equivalent_operations_up_to_global_phase() {
+    let mut interpreter = get_interpreter("");
+    let difference = interpreter
+        .check_equivalence(
+            "qs => Z(qs[0])",
+            "qs => Rz(Microsoft.Quantum.Math.PI(), qs[0])",
+            1,
+            1e-10,
+        )
+        .expect("equivalence check should succeed");
+    assert_eq!(difference, None);
+}
+
+#[test]
+fn optimized_rewrite_is_equivalent() {
+    let mut interpreter = get_interpreter(indoc! {"
+        namespace Test {
+            operation Original(qs : Qubit[]) : Unit {
+                CNOT(qs[0], qs[1]);
+                CNOT(qs[1], qs[0]);
+                CNOT(qs[0], qs[1]);
+            }
+        }
+    "});
+    let difference = interpreter
+        .check_equivalence("Test.Original", "qs => SWAP(qs[0], qs[1])", 2, 0.0)
+        .expect("equivalence check should succeed");
+    assert_eq!(difference, None);
+}
+
+#[test]
+fn first_differing_basis_state_is_reported() {
+    let mut interpreter = get_interpreter("");
+    let difference = interpreter
+        .check_equivalence(
+            "qs => CNOT(qs[0], qs[1])",
+            "qs => CZ(qs[0], qs[1])",
+            2,
+            1e-10,
+        )
+        .expect("equivalence check should succeed")
+        .expect("operations should differ");
+    assert_eq!(difference.basis_state, 2);
+    assert_eq!(difference.label, "|10⟩");
+    assert_matrix_eq(
+        &[difference.expected, difference.actual],
+        &real(&[&[0.0, 0.0, 0.0, 1.0], &[0.0, 0.0, 1.0, 0.0]]),
+    );
+}
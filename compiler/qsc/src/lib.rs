@@ -2,18 +2,43 @@
 // Licensed under the MIT License.
 
 pub mod compile;
+pub mod dap;
 pub mod error;
 pub mod incremental;
 pub mod interpret;
 pub mod location;
+pub mod packages;
+pub mod snapshot;
 pub mod target;
 
 pub use qsc_frontend::compile::{
-    CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName,
+    loader::{MemoryLoader, SourceLoader},
+    preprocess::{Config, TrackedName},
+    CompileOptions, CompileUnit, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceContents,
+    SourceMap, SourceName,
 };
 
 pub mod resolve {
-    pub use qsc_frontend::resolve::{Local, LocalKind, Locals, Res};
+    pub use qsc_frontend::resolve::{
+        Cache, Explanation, Explanations, GlobalScope, Local, LocalKind, Locals, Member,
+        MemberKind, Origin, References, Res, PRELUDE,
+    };
+}
+
+pub mod definition {
+    pub use qsc_frontend::definition::{at_offset, Definition};
+}
+
+pub mod rename {
+    pub use qsc_frontend::rename::{item, local, namespace, Edit, Error};
+}
+
+pub mod formatter {
+    pub use qsc_parse::formatter::{apply, format, TextEdit};
+}
+
+pub mod lint {
+    pub use qsc_frontend::lint::{Context, Lint, Registry, Rule};
 }
 
 pub mod fir {
@@ -28,13 +53,17 @@ pub mod ast {
     pub use qsc_ast::{ast::*, *};
 }
 
+pub mod codegen {
+    pub use qsc_codegen::{circuit, qasm};
+}
+
 pub mod project {
     pub use qsc_project::{DirEntry, EntryType, FileSystem, Manifest, ManifestDescriptor};
 }
 
-pub use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+pub use qsc_data_structures::{language_features::LanguageFeatures, lints::Lints, span::Span};
 
-pub use qsc_passes::{PackageType, PassContext};
+pub use qsc_passes::{callable_capabilities, CallableCapabilities, PackageType, PassContext};
 
 pub mod line_column {
     pub use qsc_data_structures::line_column::{Encoding, Position, Range};
@@ -42,7 +71,10 @@ pub mod line_column {
 
 pub use qsc_eval::{
     backend::{Backend, SparseSim},
-    state::{fmt_basis_state_label, fmt_complex, format_state_id, get_latex, get_phase},
+    state::{
+        bloch_vector, entanglement_entropy, fmt_basis_state_label, fmt_complex, format_state_id,
+        get_latex, get_phase, probability_distribution, BasisStateProbability, BlochVector,
+    },
 };
 
-pub use qsc_doc_gen::{display, generate_docs};
+pub use qsc_doc_gen::{api, display, generate_docs};
@@ -0,0 +1,171 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Compiles a project made of several packages that depend on each other, building the package
+//! store in dependency order.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compile::{self, compile_with_options};
+use miette::Diagnostic;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{CompileOptions, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+use rustc_hash::FxHashMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("package `{0}` is not in the project")]
+    #[diagnostic(code("Qsc.Packages.UnknownPackage"))]
+    UnknownPackage(String),
+
+    #[error("package `{0}` depends on package `{1}`, which is not in the project")]
+    #[diagnostic(code("Qsc.Packages.UnknownDependency"))]
+    UnknownDependency(String, String),
+
+    #[error("package `{0}` depends on itself")]
+    #[diagnostic(help("dependencies between packages cannot form a cycle"))]
+    #[diagnostic(code("Qsc.Packages.DependencyCycle"))]
+    DependencyCycle(String),
+}
+
+/// A package in a project.
+pub struct PackageDescription {
+    pub sources: SourceMap,
+    pub capabilities: RuntimeCapabilityFlags,
+    pub language_features: LanguageFeatures,
+    /// The packages that this package depends on. Each is a pair of the alias that the package's
+    /// sources use for the dependency, like `Alias` in `Alias::Namespace.Name`, and the name of the
+    /// dependency in the project.
+    pub dependencies: Vec<(Arc<str>, Arc<str>)>,
+}
+
+/// A project made of named packages, one of which is the root that the others are compiled for.
+pub struct ProjectDescription {
+    pub packages: FxHashMap<Arc<str>, PackageDescription>,
+    /// The name of the root package.
+    pub root: Arc<str>,
+    /// The type of the root package. The other packages are libraries.
+    pub package_type: PackageType,
+    /// Whether every package depends on the standard library, which is compiled with the
+    /// capabilities of the root package.
+    pub include_std: bool,
+}
+
+/// A compiled project.
+pub struct Project {
+    /// The store with every package that the root package depends on, and the root package.
+    pub store: PackageStore,
+    /// The ID of each compiled package by its name in the project.
+    pub packages: FxHashMap<Arc<str>, PackageId>,
+    /// The ID of the root package.
+    pub root: PackageId,
+}
+
+/// Compiles the packages that the root package depends on, directly or indirectly, and then the
+/// root package. Each package is compiled after its dependencies so that it can refer to their
+/// items by the aliases it gives them.
+///
+/// # Errors
+///
+/// Returns an error if a package depends on a package that is not in the project, or if the
+/// dependencies form a cycle. Compilation errors in the packages are returned with the project.
+pub fn compile_project(
+    mut project: ProjectDescription,
+    options: &CompileOptions,
+) -> Result<(Project, Vec<compile::Error>), Error> {
+    let order = build_order(&project)?;
+    let root_capabilities = project.packages[&project.root].capabilities;
+
+    let mut store = PackageStore::new(compile::core());
+    let std_id = project
+        .include_std
+        .then(|| store.insert(compile::std(&store, root_capabilities)));
+
+    let mut packages = FxHashMap::default();
+    let mut errors = Vec::new();
+    for name in order {
+        let package = project
+            .packages
+            .remove(&name)
+            .expect("package in build order should be in project");
+        let mut dependencies: Vec<_> = std_id.into_iter().map(|id| (id, None)).collect();
+        dependencies.extend(
+            package
+                .dependencies
+                .iter()
+                .map(|(alias, dependency)| (packages[dependency], Some(Arc::clone(alias)))),
+        );
+        let package_type = if name == project.root {
+            project.package_type
+        } else {
+            PackageType::Lib
+        };
+
+        let (unit, mut unit_errors) = compile_with_options(
+            &store,
+            &dependencies,
+            package.sources,
+            package_type,
+            package.capabilities,
+            package.language_features,
+            options,
+        );
+        errors.append(&mut unit_errors);
+        packages.insert(name, store.insert(unit));
+    }
+
+    let root = packages[&project.root];
+    Ok((
+        Project {
+            store,
+            packages,
+            root,
+        },
+        errors,
+    ))
+}
+
+/// Orders the packages that the root depends on so that each comes after its dependencies, ending
+/// with the root.
+fn build_order(project: &ProjectDescription) -> Result<Vec<Arc<str>>, Error> {
+    if !project.packages.contains_key(&project.root) {
+        return Err(Error::UnknownPackage(project.root.to_string()));
+    }
+
+    let mut order = Vec::new();
+    let mut visiting = Vec::new();
+    visit(project, &project.root, &mut visiting, &mut order)?;
+    Ok(order)
+}
+
+fn visit(
+    project: &ProjectDescription,
+    name: &Arc<str>,
+    visiting: &mut Vec<Arc<str>>,
+    order: &mut Vec<Arc<str>>,
+) -> Result<(), Error> {
+    if order.contains(name) {
+        return Ok(());
+    } else if visiting.contains(name) {
+        return Err(Error::DependencyCycle(name.to_string()));
+    }
+
+    visiting.push(Arc::clone(name));
+    for (_, dependency) in &project.packages[name].dependencies {
+        if !project.packages.contains_key(dependency) {
+            return Err(Error::UnknownDependency(
+                name.to_string(),
+                dependency.to_string(),
+            ));
+        }
+        visit(project, dependency, visiting, order)?;
+    }
+    visiting.pop();
+    order.push(Arc::clone(name));
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{compile_project, Error, PackageDescription, ProjectDescription};
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{CompileOptions, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+use std::sync::Arc;
+
+fn package(source: &str, dependencies: &[(&str, &str)]) -> PackageDescription {
+    PackageDescription {
+        sources: SourceMap::new([("test.qs".into(), source.into())], None),
+        capabilities: RuntimeCapabilityFlags::all(),
+        language_features: LanguageFeatures::default(),
+        dependencies: dependencies
+            .iter()
+            .map(|&(alias, name)| (Arc::from(alias), Arc::from(name)))
+            .collect(),
+    }
+}
+
+fn project(packages: Vec<(&str, PackageDescription)>) -> ProjectDescription {
+    ProjectDescription {
+        packages: packages
+            .into_iter()
+            .map(|(name, package)| (Arc::from(name), package))
+            .collect(),
+        root: "app".into(),
+        package_type: PackageType::Lib,
+        include_std: false,
+    }
+}
+
+#[test]
+fn dependencies_are_compiled_before_dependents() {
+    let project = project(vec![
+        (
+            "app",
+            package(
+                "namespace App { function Main() : Int { Math::Math.Square(Util::Util.Three()) } }",
+                &[("Math", "math"), ("Util", "util")],
+            ),
+        ),
+        (
+            "math",
+            package(
+                "namespace Math { function Square(x : Int) : Int { Util::Util.Times(x, x) } }",
+                &[("Util", "util")],
+            ),
+        ),
+        (
+            "util",
+            package(
+                "namespace Util { function Times(x : Int, y : Int) : Int { x * y } function Three() : Int { 3 } }",
+                &[],
+            ),
+        ),
+    ]);
+
+    let (project, errors) =
+        compile_project(project, &CompileOptions::default()).expect("project should be valid");
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert_eq!(project.packages.len(), 3);
+    assert_eq!(project.packages["app"], project.root);
+    assert!(project.store.get(project.packages["util"]).is_some());
+}
+
+#[test]
+fn unreachable_packages_are_not_compiled() {
+    let project = project(vec![
+        ("app", package("namespace App {}", &[])),
+        ("unused", package("namespace Unused { invalid }", &[])),
+    ]);
+
+    let (project, errors) =
+        compile_project(project, &CompileOptions::default()).expect("project should be valid");
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert!(!project.packages.contains_key("unused"));
+}
+
+#[test]
+fn dependency_cycle_is_an_error() {
+    let project = project(vec![
+        ("app", package("namespace App {}", &[("A", "a")])),
+        ("a", package("namespace A {}", &[("B", "b")])),
+        ("b", package("namespace B {}", &[("A", "a")])),
+    ]);
+
+    let error = compile_project(project, &CompileOptions::default())
+        .err()
+        .expect("project should have a cycle");
+    assert!(matches!(error, Error::DependencyCycle(name) if name == "a"));
+}
+
+#[test]
+fn unknown_dependency_is_an_error() {
+    let project = project(vec![("app", package("namespace App {}", &[("A", "a")]))]);
+
+    let error = compile_project(project, &CompileOptions::default())
+        .err()
+        .expect("project should have an unknown dependency");
+    assert!(
+        matches!(error, Error::UnknownDependency(package, dependency) if package == "app" && dependency == "a")
+    );
+}
@@ -0,0 +1,178 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Saves a compiled package store, with the FIR lowered from it, as bytes and loads it back, so
+//! that a tool can reuse the compiled dependency graph of an earlier process instead of compiling
+//! it again.
+//!
+//! A snapshot starts with a header that is read on its own, so that a snapshot written by another
+//! version of the compiler or for other capabilities is rejected before the packages are read.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_fir::fir;
+use qsc_frontend::compile::{
+    preprocess::TrackedName, CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
+use qsc_hir::{assigner::Assigner as HirAssigner, hir};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The version of the snapshot format. A snapshot saved with a different version cannot be loaded.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The version of the compiler that saves and loads snapshots.
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("package store snapshot could not be read")]
+    #[diagnostic(code("Qsc.Snapshot.Format"))]
+    Format,
+
+    #[error("package store snapshot has format version {0}, expected version {1}")]
+    #[diagnostic(help("save the snapshot again with this version of the compiler"))]
+    #[diagnostic(code("Qsc.Snapshot.Version"))]
+    Version(u32, u32),
+
+    #[error("package store snapshot was saved by compiler version {0}, expected version {1}")]
+    #[diagnostic(help("save the snapshot again with this version of the compiler"))]
+    #[diagnostic(code("Qsc.Snapshot.CompilerVersion"))]
+    CompilerVersion(String, String),
+
+    #[error("package store snapshot was compiled for different target capabilities")]
+    #[diagnostic(code("Qsc.Snapshot.Capabilities"))]
+    Capabilities,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Header {
+    version: u32,
+    compiler: String,
+    capabilities: u32,
+}
+
+/// The saved packages. They are written from borrowed parts, so that saving doesn't copy them.
+#[derive(Serialize)]
+struct SavedPackages<'a> {
+    /// The units in the order of their IDs, starting with the core library.
+    units: Vec<SavedUnit<'a>>,
+    fir_store: &'a fir::PackageStore,
+}
+
+#[derive(Serialize)]
+struct SavedUnit<'a> {
+    package: &'a hir::Package,
+    assigner: &'a HirAssigner,
+    sources: Vec<(&'a str, &'a str)>,
+    entry: Option<&'a str>,
+    dropped_names: &'a [TrackedName],
+}
+
+/// The loaded packages, with the same fields as [`SavedPackages`].
+#[derive(Deserialize)]
+struct Packages {
+    units: Vec<Unit>,
+    fir_store: fir::PackageStore,
+}
+
+/// A loaded unit, with the same fields as [`SavedUnit`].
+#[derive(Deserialize)]
+struct Unit {
+    package: hir::Package,
+    assigner: HirAssigner,
+    sources: Vec<(Arc<str>, Arc<str>)>,
+    entry: Option<Arc<str>>,
+    dropped_names: Vec<TrackedName>,
+}
+
+impl From<Unit> for CompileUnit {
+    fn from(unit: Unit) -> Self {
+        CompileUnit {
+            package: unit.package,
+            assigner: unit.assigner,
+            sources: SourceMap::new(unit.sources, unit.entry),
+            dropped_names: unit.dropped_names,
+            ..CompileUnit::default()
+        }
+    }
+}
+
+/// Saves the HIR of every unit in the store with its sources, the FIR lowered from the store, and
+/// the capabilities that the store was compiled for. The ASTs of the units are not saved.
+///
+/// # Panics
+///
+/// Panics if the store cannot be written, which only happens if a value in it is not
+/// serializable.
+#[must_use]
+pub fn save(
+    store: &PackageStore,
+    fir_store: &fir::PackageStore,
+    capabilities: RuntimeCapabilityFlags,
+) -> Vec<u8> {
+    let header = Header {
+        version: FORMAT_VERSION,
+        compiler: COMPILER_VERSION.to_string(),
+        capabilities: capabilities.bits(),
+    };
+    let units = store
+        .iter()
+        .map(|(_, unit)| SavedUnit {
+            package: &unit.package,
+            assigner: &unit.assigner,
+            sources: unit
+                .sources
+                .iter()
+                .map(|source| (&*source.name, &*source.contents))
+                .collect(),
+            entry: unit.sources.entry().map(|source| &*source.contents),
+            dropped_names: &unit.dropped_names,
+        })
+        .collect();
+
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&header, &mut bytes).expect("header should be serializable");
+    ciborium::into_writer(&SavedPackages { units, fir_store }, &mut bytes)
+        .expect("package store should be serializable");
+    bytes
+}
+
+/// Loads a package store and its FIR saved by [`save`]. Each unit gets the same ID it had in the
+/// saved store. The loaded units have empty ASTs, so tools that need the syntax of a unit, like
+/// finding references in its sources, should compile it instead.
+///
+/// # Errors
+///
+/// Returns an error if the bytes are not a snapshot of this format version saved by this version
+/// of the compiler, or if the snapshot was compiled for capabilities other than the given
+/// capabilities.
+pub fn load(
+    bytes: &[u8],
+    capabilities: RuntimeCapabilityFlags,
+) -> Result<(PackageStore, fir::PackageStore), Error> {
+    let mut reader = bytes;
+    let header: Header = ciborium::from_reader(&mut reader).map_err(|_| Error::Format)?;
+    if header.version != FORMAT_VERSION {
+        return Err(Error::Version(header.version, FORMAT_VERSION));
+    } else if header.compiler != COMPILER_VERSION {
+        return Err(Error::CompilerVersion(
+            header.compiler,
+            COMPILER_VERSION.to_string(),
+        ));
+    } else if header.capabilities != capabilities.bits() {
+        return Err(Error::Capabilities);
+    }
+
+    let packages: Packages = ciborium::from_reader(&mut reader).map_err(|_| Error::Format)?;
+    let mut units = packages.units.into_iter();
+    let core = units.next().ok_or(Error::Format)?;
+    let mut store = PackageStore::new(core.into());
+    for unit in units {
+        store.insert(unit.into());
+    }
+    Ok((store, packages.fir_store))
+}
@@ -0,0 +1,99 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{load, save, Error, Header, FORMAT_VERSION};
+use crate::compile;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::{debug::map_hir_package_to_fir, lower::Lowerer};
+use qsc_fir::fir;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn compile_store() -> (PackageStore, fir::PackageStore) {
+    let mut store = PackageStore::new(compile::core());
+    let sources = SourceMap::new(
+        [(
+            "test.qs".into(),
+            "namespace Test { function Square(x : Int) : Int { x * x } }".into(),
+        )],
+        None,
+    );
+    let (unit, errors) = compile::compile(
+        &store,
+        &[],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(errors.is_empty(), "{errors:#?}");
+    store.insert(unit);
+
+    let mut lowerer = Lowerer::new();
+    let mut fir_store = fir::PackageStore::new();
+    for (id, unit) in &store {
+        fir_store.insert(
+            map_hir_package_to_fir(id),
+            lowerer.lower_package(&unit.package),
+        );
+    }
+    (store, fir_store)
+}
+
+#[test]
+fn saved_snapshot_loads_same_packages() {
+    let (store, fir_store) = compile_store();
+    let bytes = save(&store, &fir_store, RuntimeCapabilityFlags::all());
+    let (loaded, loaded_fir) =
+        load(&bytes, RuntimeCapabilityFlags::all()).expect("snapshot should load");
+
+    assert_eq!(loaded.iter().count(), store.iter().count());
+    for ((id, unit), (loaded_id, loaded_unit)) in store.iter().zip(loaded.iter()) {
+        assert_eq!(loaded_id, id);
+        assert_eq!(loaded_unit.package.to_string(), unit.package.to_string());
+        assert_eq!(
+            loaded_unit.sources.iter().count(),
+            unit.sources.iter().count()
+        );
+        let fir_id = map_hir_package_to_fir(id);
+        assert_eq!(
+            loaded_fir.get(fir_id).to_string(),
+            fir_store.get(fir_id).to_string()
+        );
+    }
+}
+
+#[test]
+fn snapshot_for_other_capabilities_is_error() {
+    let (store, fir_store) = compile_store();
+    let bytes = save(&store, &fir_store, RuntimeCapabilityFlags::all());
+    assert!(matches!(
+        load(&bytes, RuntimeCapabilityFlags::empty()),
+        Err(Error::Capabilities)
+    ));
+}
+
+#[test]
+fn snapshot_from_other_compiler_version_is_error() {
+    let header = Header {
+        version: FORMAT_VERSION,
+        compiler: "0.0.0-other".to_string(),
+        capabilities: RuntimeCapabilityFlags::all().bits(),
+    };
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&header, &mut bytes).expect("header should be serializable");
+    assert!(matches!(
+        load(&bytes, RuntimeCapabilityFlags::all()),
+        Err(Error::CompilerVersion(..))
+    ));
+}
+
+#[test]
+fn truncated_snapshot_is_error() {
+    let (store, fir_store) = compile_store();
+    let bytes = save(&store, &fir_store, RuntimeCapabilityFlags::all());
+    assert!(matches!(
+        load(&bytes[..bytes.len() / 2], RuntimeCapabilityFlags::all()),
+        Err(Error::Format)
+    ));
+}
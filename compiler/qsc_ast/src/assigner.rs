@@ -3,8 +3,8 @@
 
 use crate::{
     ast::{
-        Attr, Block, CallableDecl, Expr, FunctorExpr, Ident, Item, Namespace, NodeId, Package, Pat,
-        Path, QubitInit, SpecDecl, Stmt, Ty, TyDef, Visibility,
+        Attr, Block, CallableDecl, Expr, FieldAssign, FunctorExpr, Ident, Item, MatchArm,
+        Namespace, NodeId, Package, Pat, Path, QubitInit, SpecDecl, Stmt, Ty, TyDef, Visibility,
     },
     mut_visit::{self, MutVisitor},
 };
@@ -111,6 +111,17 @@ impl MutVisitor for Assigner {
         mut_visit::walk_pat(self, pat);
     }
 
+    fn visit_field_assign(&mut self, assign: &mut FieldAssign) {
+        self.assign(&mut assign.id);
+        mut_visit::walk_field_assign(self, assign);
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        self.assign(&mut arm.id);
+        self.assign(&mut arm.pat.id);
+        mut_visit::walk_match_arm(self, arm);
+    }
+
     fn visit_qubit_init(&mut self, init: &mut QubitInit) {
         self.assign(&mut init.id);
         mut_visit::walk_qubit_init(self, init);
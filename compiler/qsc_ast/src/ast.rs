@@ -12,7 +12,7 @@ use std::{
     cmp::Ordering,
     fmt::{self, Display, Formatter, Write},
     hash::{Hash, Hasher},
-    rc::Rc,
+    sync::Arc,
 };
 
 fn set_indentation<'a, 'b>(
@@ -162,13 +162,27 @@ pub struct Namespace {
     /// The span.
     pub span: Span,
     /// The documentation.
-    pub doc: Rc<str>,
-    /// The namespace name.
+    pub doc: Arc<str>,
+    /// The namespace name, as written in the declaration.
     pub name: Box<Ident>,
+    /// The fully-qualified name of the namespace that this namespace is declared in, if any.
+    pub parent: Option<Arc<str>>,
     /// The items in the namespace.
     pub items: Box<[Box<Item>]>,
 }
 
+impl Namespace {
+    /// The fully-qualified name of the namespace, which includes the name of the namespace that it
+    /// is declared in.
+    #[must_use]
+    pub fn full_name(&self) -> Arc<str> {
+        match &self.parent {
+            Some(parent) => format!("{parent}.{}", self.name.name).into(),
+            None => Arc::clone(&self.name.name),
+        }
+    }
+}
+
 impl Display for Namespace {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut indent = set_indentation(indented(f), 0);
@@ -179,6 +193,9 @@ impl Display for Namespace {
         )?;
         indent = set_indentation(indent, 1);
 
+        if let Some(parent) = &self.parent {
+            write!(indent, "\nparent: {parent}")?;
+        }
         if !self.doc.is_empty() {
             write!(indent, "\ndoc:")?;
             indent = set_indentation(indent, 2);
@@ -202,7 +219,7 @@ pub struct Item {
     /// The span.
     pub span: Span,
     /// The documentation.
-    pub doc: Rc<str>,
+    pub doc: Arc<str>,
     /// The attributes.
     pub attrs: Box<[Box<Attr>]>,
     /// The visibility.
@@ -258,6 +275,8 @@ pub enum ItemKind {
     /// Default item when nothing has been parsed.
     #[default]
     Err,
+    /// An `export` item for a global item with an optional alias.
+    Export(Box<Path>, Option<Box<Ident>>),
     /// An `open` item for a namespace with an optional alias.
     Open(Box<Ident>, Option<Box<Ident>>),
     /// A `newtype` declaration.
@@ -269,6 +288,10 @@ impl Display for ItemKind {
         match &self {
             ItemKind::Callable(decl) => write!(f, "{decl}")?,
             ItemKind::Err => write!(f, "Err")?,
+            ItemKind::Export(path, alias) => match alias {
+                Some(a) => write!(f, "Export ({path}) ({a})")?,
+                None => write!(f, "Export ({path})")?,
+            },
             ItemKind::Open(name, alias) => match alias {
                 Some(a) => write!(f, "Open ({name}) ({a})")?,
                 None => write!(f, "Open ({name})")?,
@@ -402,9 +425,12 @@ pub struct CallableDecl {
     /// The name of the callable.
     pub name: Box<Ident>,
     /// The generic parameters to the callable.
-    pub generics: Box<[Box<Ident>]>,
+    pub generics: Box<[Box<TypeParameter>]>,
     /// The input to the callable.
     pub input: Box<Pat>,
+    /// The default value of each parameter, in the same order as [`CallableDecl::params`]. Empty
+    /// if no parameter has a default value.
+    pub defaults: Box<[Option<Box<Expr>>]>,
     /// The return type of the callable.
     pub output: Box<Ty>,
     /// The functors supported by the callable.
@@ -413,6 +439,22 @@ pub struct CallableDecl {
     pub body: Box<CallableBody>,
 }
 
+impl CallableDecl {
+    /// The parameters of the callable: the items of the input tuple, or the whole input if it is
+    /// not a tuple.
+    #[must_use]
+    pub fn params(&self) -> Vec<&Pat> {
+        let mut input = &*self.input;
+        while let PatKind::Paren(inner) = &*input.kind {
+            input = inner;
+        }
+        match &*input.kind {
+            PatKind::Tuple(items) => items.iter().map(AsRef::as_ref).collect(),
+            _ => vec![input],
+        }
+    }
+}
+
 impl Display for CallableDecl {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut indent = set_indentation(indented(f), 0);
@@ -432,6 +474,17 @@ impl Display for CallableDecl {
             indent = set_indentation(indent, 1);
         }
         write!(indent, "\ninput: {}", self.input)?;
+        if !self.defaults.is_empty() {
+            write!(indent, "\ndefaults:")?;
+            indent = set_indentation(indent, 2);
+            for default in &*self.defaults {
+                match default {
+                    Some(value) => write!(indent, "\n{value}")?,
+                    None => write!(indent, "\n<no default>")?,
+                }
+            }
+            indent = set_indentation(indent, 1);
+        }
         write!(indent, "\noutput: {}", self.output)?;
         if let Some(f) = &self.functors {
             write!(indent, "\nfunctors: {}", f.as_ref())?;
@@ -797,6 +850,10 @@ pub enum ExprKind {
     Lambda(CallableKind, Box<Pat>, Box<Expr>),
     /// A literal.
     Lit(Box<Lit>),
+    /// A match expression: `match a { b => c, ... }`.
+    Match(Box<Expr>, Box<[Box<MatchArm>]>),
+    /// A named argument in a call: `b = c` in `a(b = c)`.
+    NamedArg(Box<Ident>, Box<Expr>),
     /// Parentheses: `(a)`.
     Paren(Box<Expr>),
     /// A path: `a` or `a.b`.
@@ -807,6 +864,8 @@ pub enum ExprKind {
     Repeat(Box<Block>, Box<Expr>, Option<Box<Block>>),
     /// A return: `return a`.
     Return(Box<Expr>),
+    /// A struct constructor with named fields: `new A { B = c, D = e }`.
+    Struct(Box<Path>, Box<[Box<FieldAssign>]>),
     /// A ternary operator.
     TernOp(TernOp, Box<Expr>, Box<Expr>, Box<Expr>),
     /// A tuple: `(a, b, c)`.
@@ -842,11 +901,14 @@ impl Display for ExprKind {
             ExprKind::Interpolate(components) => display_interpolate(indent, components)?,
             ExprKind::Lambda(kind, param, expr) => display_lambda(indent, *kind, param, expr)?,
             ExprKind::Lit(lit) => write!(indent, "Lit: {lit}")?,
+            ExprKind::Match(expr, arms) => display_match(indent, expr, arms)?,
+            ExprKind::NamedArg(name, value) => display_named_arg(indent, name, value)?,
             ExprKind::Paren(e) => write!(indent, "Paren: {e}")?,
             ExprKind::Path(p) => write!(indent, "Path: {p}")?,
             ExprKind::Range(start, step, end) => display_range(indent, start, step, end)?,
             ExprKind::Repeat(repeat, until, fixup) => display_repeat(indent, repeat, until, fixup)?,
             ExprKind::Return(e) => write!(indent, "Return: {e}")?,
+            ExprKind::Struct(name, fields) => display_struct(indent, name, fields)?,
             ExprKind::TernOp(op, expr1, expr2, expr3) => {
                 display_tern_op(indent, *op, expr1, expr2, expr3)?;
             }
@@ -1058,6 +1120,41 @@ fn display_repeat(
     Ok(())
 }
 
+fn display_match(
+    mut indent: Indented<Formatter>,
+    expr: &Expr,
+    arms: &[Box<MatchArm>],
+) -> fmt::Result {
+    write!(indent, "Match:")?;
+    indent = set_indentation(indent, 1);
+    write!(indent, "\n{expr}")?;
+    for arm in arms {
+        write!(indent, "\n{arm}")?;
+    }
+    Ok(())
+}
+
+fn display_named_arg(mut indent: Indented<Formatter>, name: &Ident, value: &Expr) -> fmt::Result {
+    write!(indent, "NamedArg:")?;
+    indent = set_indentation(indent, 1);
+    write!(indent, "\n{name}")?;
+    write!(indent, "\n{value}")?;
+    Ok(())
+}
+
+fn display_struct(
+    mut indent: Indented<Formatter>,
+    name: &Path,
+    fields: &[Box<FieldAssign>],
+) -> fmt::Result {
+    write!(indent, "Struct ({name}):")?;
+    indent = set_indentation(indent, 1);
+    for field in fields {
+        write!(indent, "\n{field}")?;
+    }
+    Ok(())
+}
+
 fn display_tern_op(
     mut indent: Indented<Formatter>,
     op: TernOp,
@@ -1101,13 +1198,120 @@ fn display_while(mut indent: Indented<Formatter>, cond: &Expr, block: &Block) ->
     Ok(())
 }
 
+/// A field assignment in a struct constructor: `B = c`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldAssign {
+    /// The node ID.
+    pub id: NodeId,
+    /// The span.
+    pub span: Span,
+    /// The name of the field.
+    pub field: Box<Ident>,
+    /// The value assigned to the field.
+    pub value: Box<Expr>,
+}
+
+impl Display for FieldAssign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FieldAssign {} {} ({}): {}",
+            self.id, self.span, self.field, self.value
+        )
+    }
+}
+
+impl WithSpan for FieldAssign {
+    fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
+    }
+}
+
+/// An arm of a match expression: `a => b`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchArm {
+    /// The node ID.
+    pub id: NodeId,
+    /// The span.
+    pub span: Span,
+    /// The pattern compared with the matched value.
+    pub pat: Box<MatchPat>,
+    /// The expression evaluated when the pattern matches.
+    pub body: Box<Expr>,
+}
+
+impl Display for MatchArm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut indent = set_indentation(indented(f), 0);
+        write!(indent, "MatchArm {} {}:", self.id, self.span)?;
+        indent = set_indentation(indent, 1);
+        write!(indent, "\n{}", self.pat)?;
+        write!(indent, "\n{}", self.body)?;
+        Ok(())
+    }
+}
+
+impl WithSpan for MatchArm {
+    fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
+    }
+}
+
+/// A pattern in a match arm.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchPat {
+    /// The node ID.
+    pub id: NodeId,
+    /// The span.
+    pub span: Span,
+    /// The pattern kind.
+    pub kind: Box<MatchPatKind>,
+}
+
+impl Display for MatchPat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "MatchPat {} {}: {}", self.id, self.span, self.kind)
+    }
+}
+
+impl WithSpan for MatchPat {
+    fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
+    }
+}
+
+/// A match pattern kind.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum MatchPatKind {
+    /// A binding of the matched value, which matches any value.
+    Bind(Box<Ident>),
+    /// A discarded value, `_`, which matches any value.
+    Discard,
+    /// A literal, which matches an equal value.
+    Lit(Box<Lit>),
+    /// An invalid pattern.
+    #[default]
+    Err,
+}
+
+impl Display for MatchPatKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchPatKind::Bind(name) => write!(f, "Bind: {name}"),
+            MatchPatKind::Discard => write!(f, "Discard"),
+            MatchPatKind::Lit(lit) => write!(f, "Lit: {lit}"),
+            MatchPatKind::Err => write!(f, "Err"),
+        }
+    }
+}
+
 /// An interpolated string component.
 #[derive(Clone, Debug, PartialEq)]
 pub enum StringComponent {
     /// An expression.
     Expr(Box<Expr>),
     /// A string literal.
-    Lit(Rc<str>),
+    Lit(Arc<str>),
 }
 
 /// A pattern.
@@ -1272,6 +1476,8 @@ pub struct Path {
     pub id: NodeId,
     /// The span.
     pub span: Span,
+    /// The package alias, for a path qualified by the package that declares the item.
+    pub package: Option<Box<Ident>>,
     /// The namespace.
     pub namespace: Option<Box<Ident>>,
     /// The declaration name.
@@ -1280,7 +1486,13 @@ pub struct Path {
 
 impl Display for Path {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        if let Some(ns) = &self.namespace {
+        if let Some(package) = &self.package {
+            write!(f, "Path {} {} ({})::", self.id, self.span, package)?;
+            if let Some(ns) = &self.namespace {
+                write!(f, "({ns}) ")?;
+            }
+            write!(f, "({})", self.name)?;
+        } else if let Some(ns) = &self.namespace {
             write!(f, "Path {} {} ({}) ({})", self.id, self.span, ns, self.name)?;
         } else {
             write!(f, "Path {} {} ({})", self.id, self.span, self.name)?;
@@ -1295,6 +1507,43 @@ impl WithSpan for Path {
     }
 }
 
+/// A type parameter of a callable, with the classes its arguments must belong to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TypeParameter {
+    /// The span, including the constraints.
+    pub span: Span,
+    /// The name of the parameter.
+    pub ty: Ident,
+    /// The class constraints on the parameter, like `Eq` in `'T: Eq`.
+    pub constraints: Box<[Box<Ident>]>,
+}
+
+impl WithSpan for TypeParameter {
+    fn with_span(self, span: Span) -> Self {
+        Self {
+            span,
+            ty: self.ty.with_span(span),
+            ..self
+        }
+    }
+}
+
+impl Display for TypeParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.constraints.is_empty() {
+            return write!(f, "{}", self.ty);
+        }
+
+        let mut indent = set_indentation(indented(f), 0);
+        write!(indent, "TypeParameter {}: {}", self.span, self.ty)?;
+        indent = set_indentation(indent, 1);
+        for constraint in &*self.constraints {
+            write!(indent, "\n{constraint}")?;
+        }
+        Ok(())
+    }
+}
+
 /// An identifier.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Ident {
@@ -1303,7 +1552,7 @@ pub struct Ident {
     /// The span.
     pub span: Span,
     /// The identifier name.
-    pub name: Rc<str>,
+    pub name: Arc<str>,
 }
 
 impl Default for Ident {
@@ -1381,7 +1630,7 @@ pub enum Lit {
     /// A measurement result literal.
     Result(Result),
     /// A string literal.
-    String(Rc<str>),
+    String(Arc<str>),
 }
 
 impl Display for Lit {
@@ -2,10 +2,10 @@
 // Licensed under the MIT License.
 
 use crate::ast::{
-    Attr, Block, CallableBody, CallableDecl, Expr, ExprKind, FunctorExpr, FunctorExprKind, Ident,
-    Item, ItemKind, Namespace, Package, Pat, PatKind, Path, QubitInit, QubitInitKind, SpecBody,
-    SpecDecl, Stmt, StmtKind, StringComponent, TopLevelNode, Ty, TyDef, TyDefKind, TyKind,
-    Visibility,
+    Attr, Block, CallableBody, CallableDecl, Expr, ExprKind, FieldAssign, FunctorExpr,
+    FunctorExprKind, Ident, Item, ItemKind, MatchArm, MatchPatKind, Namespace, Package, Pat,
+    PatKind, Path, QubitInit, QubitInitKind, SpecBody, SpecDecl, Stmt, StmtKind, StringComponent,
+    TopLevelNode, Ty, TyDef, TyDefKind, TyKind, Visibility,
 };
 use qsc_data_structures::span::Span;
 
@@ -60,6 +60,14 @@ pub trait MutVisitor: Sized {
         walk_expr(self, expr);
     }
 
+    fn visit_field_assign(&mut self, assign: &mut FieldAssign) {
+        walk_field_assign(self, assign);
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        walk_match_arm(self, arm);
+    }
+
     fn visit_pat(&mut self, pat: &mut Pat) {
         walk_pat(self, pat);
     }
@@ -103,6 +111,12 @@ pub fn walk_item(vis: &mut impl MutVisitor, item: &mut Item) {
     match &mut *item.kind {
         ItemKind::Callable(decl) => vis.visit_callable_decl(decl),
         ItemKind::Err => {}
+        ItemKind::Export(path, alias) => {
+            vis.visit_path(path);
+            if let Some(alias) = alias {
+                vis.visit_ident(alias);
+            }
+        }
         ItemKind::Open(ns, alias) => {
             vis.visit_ident(ns);
             alias.iter_mut().for_each(|a| vis.visit_ident(a));
@@ -137,8 +151,16 @@ pub fn walk_ty_def(vis: &mut impl MutVisitor, def: &mut TyDef) {
 pub fn walk_callable_decl(vis: &mut impl MutVisitor, decl: &mut CallableDecl) {
     vis.visit_span(&mut decl.span);
     vis.visit_ident(&mut decl.name);
-    decl.generics.iter_mut().for_each(|p| vis.visit_ident(p));
+    decl.generics.iter_mut().for_each(|p| {
+        vis.visit_span(&mut p.span);
+        vis.visit_ident(&mut p.ty);
+        p.constraints.iter_mut().for_each(|c| vis.visit_ident(c));
+    });
     vis.visit_pat(&mut decl.input);
+    decl.defaults
+        .iter_mut()
+        .flatten()
+        .for_each(|d| vis.visit_expr(d));
     vis.visit_ty(&mut decl.output);
     decl.functors
         .iter_mut()
@@ -277,6 +299,14 @@ pub fn walk_expr(vis: &mut impl MutVisitor, expr: &mut Expr) {
             vis.visit_pat(pat);
             vis.visit_expr(expr);
         }
+        ExprKind::Match(expr, arms) => {
+            vis.visit_expr(expr);
+            arms.iter_mut().for_each(|a| vis.visit_match_arm(a));
+        }
+        ExprKind::NamedArg(name, value) => {
+            vis.visit_ident(name);
+            vis.visit_expr(value);
+        }
         ExprKind::Paren(expr) | ExprKind::Return(expr) | ExprKind::UnOp(_, expr) => {
             vis.visit_expr(expr);
         }
@@ -291,6 +321,10 @@ pub fn walk_expr(vis: &mut impl MutVisitor, expr: &mut Expr) {
             vis.visit_expr(until);
             fixup.iter_mut().for_each(|f| vis.visit_block(f));
         }
+        ExprKind::Struct(path, fields) => {
+            vis.visit_path(path);
+            fields.iter_mut().for_each(|f| vis.visit_field_assign(f));
+        }
         ExprKind::TernOp(_, e1, e2, e3) => {
             vis.visit_expr(e1);
             vis.visit_expr(e2);
@@ -305,6 +339,21 @@ pub fn walk_expr(vis: &mut impl MutVisitor, expr: &mut Expr) {
     }
 }
 
+pub fn walk_field_assign(vis: &mut impl MutVisitor, assign: &mut FieldAssign) {
+    vis.visit_span(&mut assign.span);
+    vis.visit_ident(&mut assign.field);
+    vis.visit_expr(&mut assign.value);
+}
+
+pub fn walk_match_arm(vis: &mut impl MutVisitor, arm: &mut MatchArm) {
+    vis.visit_span(&mut arm.span);
+    vis.visit_span(&mut arm.pat.span);
+    if let MatchPatKind::Bind(name) = &mut *arm.pat.kind {
+        vis.visit_ident(name);
+    }
+    vis.visit_expr(&mut arm.body);
+}
+
 pub fn walk_pat(vis: &mut impl MutVisitor, pat: &mut Pat) {
     vis.visit_span(&mut pat.span);
 
@@ -333,6 +382,7 @@ pub fn walk_qubit_init(vis: &mut impl MutVisitor, init: &mut QubitInit) {
 
 pub fn walk_path(vis: &mut impl MutVisitor, path: &mut Path) {
     vis.visit_span(&mut path.span);
+    path.package.iter_mut().for_each(|p| vis.visit_ident(p));
     path.namespace.iter_mut().for_each(|n| vis.visit_ident(n));
     vis.visit_ident(&mut path.name);
 }
@@ -2,10 +2,10 @@
 // Licensed under the MIT License.
 
 use crate::ast::{
-    Attr, Block, CallableBody, CallableDecl, Expr, ExprKind, FunctorExpr, FunctorExprKind, Ident,
-    Item, ItemKind, Namespace, Package, Pat, PatKind, Path, QubitInit, QubitInitKind, SpecBody,
-    SpecDecl, Stmt, StmtKind, StringComponent, TopLevelNode, Ty, TyDef, TyDefKind, TyKind,
-    Visibility,
+    Attr, Block, CallableBody, CallableDecl, Expr, ExprKind, FieldAssign, FunctorExpr,
+    FunctorExprKind, Ident, Item, ItemKind, MatchArm, MatchPatKind, Namespace, Package, Pat,
+    PatKind, Path, QubitInit, QubitInitKind, SpecBody, SpecDecl, Stmt, StmtKind, StringComponent,
+    TopLevelNode, Ty, TyDef, TyDefKind, TyKind, Visibility,
 };
 
 pub trait Visitor<'a>: Sized {
@@ -59,6 +59,14 @@ pub trait Visitor<'a>: Sized {
         walk_expr(self, expr);
     }
 
+    fn visit_field_assign(&mut self, assign: &'a FieldAssign) {
+        walk_field_assign(self, assign);
+    }
+
+    fn visit_match_arm(&mut self, arm: &'a MatchArm) {
+        walk_match_arm(self, arm);
+    }
+
     fn visit_pat(&mut self, pat: &'a Pat) {
         walk_pat(self, pat);
     }
@@ -93,6 +101,12 @@ pub fn walk_item<'a>(vis: &mut impl Visitor<'a>, item: &'a Item) {
     match &*item.kind {
         ItemKind::Err => {}
         ItemKind::Callable(decl) => vis.visit_callable_decl(decl),
+        ItemKind::Export(path, alias) => {
+            vis.visit_path(path);
+            if let Some(alias) = alias {
+                vis.visit_ident(alias);
+            }
+        }
         ItemKind::Open(ns, alias) => {
             vis.visit_ident(ns);
             alias.iter().for_each(|a| vis.visit_ident(a));
@@ -123,8 +137,15 @@ pub fn walk_ty_def<'a>(vis: &mut impl Visitor<'a>, def: &'a TyDef) {
 
 pub fn walk_callable_decl<'a>(vis: &mut impl Visitor<'a>, decl: &'a CallableDecl) {
     vis.visit_ident(&decl.name);
-    decl.generics.iter().for_each(|p| vis.visit_ident(p));
+    decl.generics.iter().for_each(|p| {
+        vis.visit_ident(&p.ty);
+        p.constraints.iter().for_each(|c| vis.visit_ident(c));
+    });
     vis.visit_pat(&decl.input);
+    decl.defaults
+        .iter()
+        .flatten()
+        .for_each(|d| vis.visit_expr(d));
     vis.visit_ty(&decl.output);
     decl.functors.iter().for_each(|f| vis.visit_functor_expr(f));
     match &*decl.body {
@@ -249,6 +270,14 @@ pub fn walk_expr<'a>(vis: &mut impl Visitor<'a>, expr: &'a Expr) {
             vis.visit_pat(pat);
             vis.visit_expr(expr);
         }
+        ExprKind::Match(expr, arms) => {
+            vis.visit_expr(expr);
+            arms.iter().for_each(|a| vis.visit_match_arm(a));
+        }
+        ExprKind::NamedArg(name, value) => {
+            vis.visit_ident(name);
+            vis.visit_expr(value);
+        }
         ExprKind::Paren(expr) | ExprKind::Return(expr) | ExprKind::UnOp(_, expr) => {
             vis.visit_expr(expr);
         }
@@ -263,6 +292,10 @@ pub fn walk_expr<'a>(vis: &mut impl Visitor<'a>, expr: &'a Expr) {
             vis.visit_expr(until);
             fixup.iter().for_each(|f| vis.visit_block(f));
         }
+        ExprKind::Struct(path, fields) => {
+            vis.visit_path(path);
+            fields.iter().for_each(|f| vis.visit_field_assign(f));
+        }
         ExprKind::TernOp(_, e1, e2, e3) => {
             vis.visit_expr(e1);
             vis.visit_expr(e2);
@@ -277,6 +310,18 @@ pub fn walk_expr<'a>(vis: &mut impl Visitor<'a>, expr: &'a Expr) {
     }
 }
 
+pub fn walk_field_assign<'a>(vis: &mut impl Visitor<'a>, assign: &'a FieldAssign) {
+    vis.visit_ident(&assign.field);
+    vis.visit_expr(&assign.value);
+}
+
+pub fn walk_match_arm<'a>(vis: &mut impl Visitor<'a>, arm: &'a MatchArm) {
+    if let MatchPatKind::Bind(name) = &*arm.pat.kind {
+        vis.visit_ident(name);
+    }
+    vis.visit_expr(&arm.body);
+}
+
 pub fn walk_pat<'a>(vis: &mut impl Visitor<'a>, pat: &'a Pat) {
     match &*pat.kind {
         PatKind::Bind(name, ty) => {
@@ -300,6 +345,7 @@ pub fn walk_qubit_init<'a>(vis: &mut impl Visitor<'a>, init: &'a QubitInit) {
 }
 
 pub fn walk_path<'a>(vis: &mut impl Visitor<'a>, path: &'a Path) {
+    path.package.iter().for_each(|p| vis.visit_ident(p));
     path.namespace.iter().for_each(|n| vis.visit_ident(n));
     vis.visit_ident(&path.name);
 }
@@ -0,0 +1,730 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Circuit diagrams of programs, built from either their structure or a trace of running them.
+//!
+//! A [`Circuit`] is a list of wires, one for each qubit, and the operations applied to them in
+//! order, which a renderer lays out in columns. [`from_program`] walks the program without running
+//! it, the way OpenQASM is generated, so a branch on a measurement result becomes operations with
+//! a condition. [`from_trace`] runs the program once and records the operations it applies, so it
+//! handles any program but only shows the branches that were taken. Either way, a qubit is labeled
+//! with the name of the variable in the entry package that it was first bound to.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    peephole,
+    qasm::{self, walk_program, Instr},
+    qir_base::lower_store,
+};
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use qsc_eval::{
+    backend::{Backend, SparseSim},
+    debug::{map_hir_package_to_fir, Frame},
+    eval_push_expr,
+    output::{GenericReceiver, StateDump},
+    partial_eval,
+    val::{Qubit, Value},
+    Env, State, StepAction, StepResult,
+};
+use qsc_fir::fir::Pauli;
+use qsc_frontend::compile;
+use qsc_hir::hir;
+use rustc_hash::FxHashMap;
+use std::{fmt::Write, rc::Rc};
+
+/// The vertical distance between wires in an SVG diagram.
+const SVG_WIRE_SPACING: usize = 40;
+/// The width of a character of text in an SVG diagram.
+const SVG_CHAR_WIDTH: usize = 9;
+/// The space around the text of a gate, and between the columns of gates, in an SVG diagram.
+const SVG_PADDING: usize = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Circuit {
+    pub wires: Vec<Wire>,
+    pub operations: Vec<Operation>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wire {
+    /// The name of the variable the qubit was first bound to, or its index in the circuit.
+    pub label: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
+    /// The name of the gate, such as `H`, `Rx`, or `X` for a controlled X.
+    pub gate: String,
+    pub params: Vec<f64>,
+    pub controls: Vec<usize>,
+    pub targets: Vec<usize>,
+    pub kind: OperationKind,
+    /// The measurement results that must all have the given values for the operation to apply.
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    Unitary,
+    /// A measurement that writes the result with the given index.
+    Measurement(usize),
+    Reset,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Condition {
+    pub result: usize,
+    pub value: bool,
+}
+
+impl Circuit {
+    /// Renders the circuit as text, with a row for each wire and the operations in columns.
+    #[must_use]
+    pub fn to_ascii(&self) -> String {
+        let label_width = self
+            .wires
+            .iter()
+            .map(|wire| wire.label.chars().count())
+            .max()
+            .unwrap_or_default();
+        // Each wire has a row, and is followed by a row for the vertical lines to the next wire.
+        let mut rows = Vec::new();
+        for wire in &self.wires {
+            rows.push(format!("{:<label_width$} ─", wire.label));
+            rows.push(" ".repeat(label_width + 2));
+        }
+        for column in self.columns() {
+            let width = column
+                .iter()
+                .map(|op| op.label().chars().count())
+                .max()
+                .unwrap_or_default()
+                + 2;
+            for (i, wire_rows) in rows.chunks_mut(2).enumerate() {
+                let mut text = String::new();
+                let mut crossed = false;
+                for op in &column {
+                    let (low, high) = op.span();
+                    if op.targets.contains(&i) {
+                        text = op.label();
+                    } else if op.controls.contains(&i) {
+                        text = "●".to_string();
+                    } else if low < i && i < high {
+                        text = "┼".to_string();
+                    }
+                    crossed |= low <= i && i < high;
+                }
+                let len = text.chars().count();
+                let left = (width - len).div_ceil(2);
+                let right = width - len - left;
+                write!(
+                    wire_rows[0],
+                    "{}{text}{}",
+                    "─".repeat(left),
+                    "─".repeat(right)
+                )
+                .expect("writing to string should succeed");
+                let bar = if crossed { "│" } else { " " };
+                let center = width / 2;
+                write!(
+                    wire_rows[1],
+                    "{}{bar}{}",
+                    " ".repeat(center),
+                    " ".repeat(width - center - 1)
+                )
+                .expect("writing to string should succeed");
+            }
+        }
+        rows.pop();
+        let mut ascii = String::new();
+        for row in rows {
+            ascii.push_str(row.trim_end());
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    /// Renders the circuit as an SVG image, with a horizontal line for each wire and the
+    /// operations in columns.
+    #[must_use]
+    pub fn to_svg(&self) -> String {
+        let columns = self.columns();
+        let widths: Vec<_> = columns
+            .iter()
+            .map(|column| {
+                let len = column
+                    .iter()
+                    .map(|op| op.label().chars().count())
+                    .max()
+                    .unwrap_or_default();
+                len * SVG_CHAR_WIDTH + 3 * SVG_PADDING
+            })
+            .collect();
+        let label_width = self
+            .wires
+            .iter()
+            .map(|wire| wire.label.chars().count())
+            .max()
+            .unwrap_or_default();
+        let left = label_width * SVG_CHAR_WIDTH + 2 * SVG_PADDING;
+        let width = left + widths.iter().sum::<usize>() + SVG_PADDING;
+        let height = (self.wires.len() + 1) * SVG_WIRE_SPACING;
+        let y = |wire: usize| (wire + 1) * SVG_WIRE_SPACING;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" font-family=\"monospace\" font-size=\"14\">\n"
+        );
+        for (i, wire) in self.wires.iter().enumerate() {
+            let y = y(i);
+            writeln!(
+                svg,
+                "<text x=\"{SVG_PADDING}\" y=\"{y}\" dominant-baseline=\"middle\">{}</text>",
+                escape(&wire.label)
+            )
+            .expect("writing to string should succeed");
+            writeln!(
+                svg,
+                "<line x1=\"{left}\" y1=\"{y}\" x2=\"{width}\" y2=\"{y}\" stroke=\"black\"/>"
+            )
+            .expect("writing to string should succeed");
+        }
+        let mut x = left;
+        for (column, column_width) in columns.iter().zip(widths) {
+            let center = x + column_width / 2;
+            for op in column {
+                let (low, high) = op.span();
+                if low < high {
+                    writeln!(
+                        svg,
+                        "<line x1=\"{center}\" y1=\"{}\" x2=\"{center}\" y2=\"{}\" stroke=\"black\"/>",
+                        y(low),
+                        y(high)
+                    )
+                    .expect("writing to string should succeed");
+                }
+                for &control in &op.controls {
+                    writeln!(
+                        svg,
+                        "<circle cx=\"{center}\" cy=\"{}\" r=\"4\" fill=\"black\"/>",
+                        y(control)
+                    )
+                    .expect("writing to string should succeed");
+                }
+                let label = op.label();
+                let box_width = label.chars().count() * SVG_CHAR_WIDTH + 2 * SVG_PADDING;
+                let box_height = SVG_WIRE_SPACING - 2 * SVG_PADDING;
+                for &target in &op.targets {
+                    let y = y(target);
+                    writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{box_width}\" height=\"{box_height}\" fill=\"white\" stroke=\"black\"/>",
+                        center - box_width / 2,
+                        y - box_height / 2
+                    )
+                    .expect("writing to string should succeed");
+                    writeln!(
+                        svg,
+                        "<text x=\"{center}\" y=\"{y}\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>",
+                        escape(&label)
+                    )
+                    .expect("writing to string should succeed");
+                }
+            }
+            x += column_width;
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Lays out the operations in columns, placing each one in the first column after every
+    /// earlier operation on the wires between its lowest and highest, so that the vertical lines
+    /// of operations in the same column never overlap, and after the measurements of the results
+    /// in its conditions.
+    fn columns(&self) -> Vec<Vec<&Operation>> {
+        let mut next = vec![0; self.wires.len()];
+        let mut measured = FxHashMap::default();
+        let mut columns: Vec<Vec<&Operation>> = Vec::new();
+        for op in &self.operations {
+            let (low, high) = op.span();
+            let column = next[low..=high]
+                .iter()
+                .copied()
+                .chain(op.conditions.iter().map(|c| measured[&c.result] + 1))
+                .max()
+                .unwrap_or_default();
+            next[low..=high].fill(column + 1);
+            if let OperationKind::Measurement(result) = op.kind {
+                measured.insert(result, column);
+            }
+            if column == columns.len() {
+                columns.push(Vec::new());
+            }
+            columns[column].push(op);
+        }
+        columns
+    }
+}
+
+impl Operation {
+    /// The operation for a gate with the given OpenQASM name, where the leading qubits of a
+    /// controlled gate are its controls.
+    fn gate(name: &str, params: Vec<f64>, qubits: &[usize]) -> Self {
+        let (gate, controls) = match name {
+            "ccx" => ("X", 2),
+            "cx" => ("X", 1),
+            "cy" => ("Y", 1),
+            "cz" => ("Z", 1),
+            "rx" => ("Rx", 0),
+            "rxx" => ("Rxx", 0),
+            "ry" => ("Ry", 0),
+            "ryy" => ("Ryy", 0),
+            "rz" => ("Rz", 0),
+            "rzz" => ("Rzz", 0),
+            "sdg" => ("S†", 0),
+            "tdg" => ("T†", 0),
+            "h" => ("H", 0),
+            "s" => ("S", 0),
+            "t" => ("T", 0),
+            "x" => ("X", 0),
+            "y" => ("Y", 0),
+            "z" => ("Z", 0),
+            "swap" => ("SWAP", 0),
+            name => (name, 0),
+        };
+        Self {
+            gate: gate.to_string(),
+            params,
+            controls: qubits[..controls].to_vec(),
+            targets: qubits[controls..].to_vec(),
+            kind: OperationKind::Unitary,
+            conditions: Vec::new(),
+        }
+    }
+
+    fn measurement(qubit: usize, result: usize) -> Self {
+        Self {
+            gate: "M".to_string(),
+            params: Vec::new(),
+            controls: Vec::new(),
+            targets: vec![qubit],
+            kind: OperationKind::Measurement(result),
+            conditions: Vec::new(),
+        }
+    }
+
+    fn reset(qubit: usize) -> Self {
+        Self {
+            gate: "|0⟩".to_string(),
+            params: Vec::new(),
+            controls: Vec::new(),
+            targets: vec![qubit],
+            kind: OperationKind::Reset,
+            conditions: Vec::new(),
+        }
+    }
+
+    /// The text in the box of each target, with the parameters and conditions of the operation.
+    #[must_use]
+    pub fn label(&self) -> String {
+        let mut label = self.gate.clone();
+        if !self.params.is_empty() {
+            let params: Vec<_> = self.params.iter().map(|&param| number(param)).collect();
+            write!(label, "({})", params.join(", ")).expect("writing to string should succeed");
+        }
+        if !self.conditions.is_empty() {
+            let conditions: Vec<_> = self
+                .conditions
+                .iter()
+                .map(|c| format!("c{}={}", c.result, u8::from(c.value)))
+                .collect();
+            write!(label, "[{}]", conditions.join(",")).expect("writing to string should succeed");
+        }
+        label
+    }
+
+    /// The lowest and highest wires the operation acts on.
+    fn span(&self) -> (usize, usize) {
+        let wires = self.controls.iter().chain(&self.targets);
+        let low = wires.clone().min().copied().unwrap_or_default();
+        let high = wires.max().copied().unwrap_or_default();
+        (low, high)
+    }
+}
+
+/// Builds the circuit for the entry expression of the given package from its structure, lowering
+/// every package in the store to FIR and partially evaluating the given package first. With
+/// `optimize`, inverse gates cancel, rotations fuse, and gates that cannot affect a measurement
+/// are removed.
+///
+/// # Errors
+///
+/// Returns the first construct that cannot be resolved without running the program, as for
+/// OpenQASM generation.
+pub fn from_program(
+    store: &compile::PackageStore,
+    package: hir::PackageId,
+    optimize: bool,
+) -> Result<Circuit, qasm::Error> {
+    let mut fir_store = lower_store(store);
+    let package = map_hir_package_to_fir(package);
+    partial_eval::partially_evaluate(&mut fir_store, package, partial_eval::DEFAULT_STEP_BUDGET);
+    let mut program = walk_program(&fir_store, package)?;
+    if optimize {
+        peephole::optimize(&mut program.instrs);
+    }
+    let mut operations = Vec::new();
+    add_instrs(&program.instrs, &[], &mut operations);
+    Ok(Circuit {
+        wires: wires(&program.labels),
+        operations,
+    })
+}
+
+fn add_instrs(instrs: &[Instr], conditions: &[Condition], operations: &mut Vec<Operation>) {
+    for instr in instrs {
+        let mut operation = match instr {
+            Instr::Gate(gate, params, qubits) => Operation::gate(gate, params.clone(), qubits),
+            &Instr::Measure(qubit, bit) => Operation::measurement(qubit, bit),
+            &Instr::Reset(qubit) => Operation::reset(qubit),
+            &Instr::If(bit, value, ref then, ref otherwise) => {
+                let mut inner = conditions.to_vec();
+                inner.push(Condition { result: bit, value });
+                add_instrs(then, &inner, operations);
+                inner.last_mut().expect("condition should be pushed").value = !value;
+                add_instrs(otherwise, &inner, operations);
+                continue;
+            }
+        };
+        operation.conditions = conditions.to_vec();
+        operations.push(operation);
+    }
+}
+
+/// Builds the circuit for the entry expression of the given package by running it once on the
+/// sparse simulator, with the given seed for both its classical and quantum randomness. With
+/// `optimize`, the recorded operations are optimized as for [`from_program`].
+///
+/// # Errors
+///
+/// Returns the error that stopped the program and the call stack where it happened.
+///
+/// # Panics
+///
+/// Panics if the package has no entry expression.
+pub fn from_trace(
+    store: &compile::PackageStore,
+    package: hir::PackageId,
+    seed: Option<u64>,
+    optimize: bool,
+) -> Result<Circuit, (qsc_eval::Error, Vec<Frame>)> {
+    let fir_store = lower_store(store);
+    let package = map_hir_package_to_fir(package);
+    let entry = fir_store
+        .get(package)
+        .entry
+        .expect("package should have entry");
+
+    let mut tracer = CircuitTrace::new(SparseSim::new());
+    tracer.set_seed(seed);
+    let mut state = State::new(package, seed);
+    eval_push_expr(&mut state, entry);
+    let mut env = Env::default();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    // Step into every statement, so that the qubits bound by each one in the entry package can be
+    // labeled before a later statement rebinds the variables.
+    while !matches!(
+        state.eval(
+            &fir_store,
+            &mut env,
+            &mut tracer,
+            &mut out,
+            &[],
+            StepAction::In
+        )?,
+        StepResult::Return(_)
+    ) {
+        let in_package = state
+            .get_stack_frames()
+            .last()
+            .map_or(true, |frame| frame.id.package == package);
+        if in_package {
+            for var in env.get_variables_in_top_frame() {
+                if !var.name.starts_with('@') {
+                    tracer.label(&var.name, &var.value);
+                }
+            }
+        }
+    }
+
+    if optimize {
+        peephole::optimize(&mut tracer.instrs);
+    }
+    let mut operations = Vec::new();
+    add_instrs(&tracer.instrs, &[], &mut operations);
+    Ok(Circuit {
+        wires: wires(&tracer.labels),
+        operations,
+    })
+}
+
+fn wires(labels: &[Option<Rc<str>>]) -> Vec<Wire> {
+    labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| Wire {
+            label: label
+                .as_ref()
+                .map_or_else(|| format!("q{i}"), ToString::to_string),
+        })
+        .collect()
+}
+
+/// A backend that records the operations applied to another backend as instructions, with a wire
+/// for each qubit ID that the other backend allocates.
+struct CircuitTrace<B: Backend> {
+    inner: B,
+    /// The wire of each qubit ID allocated so far.
+    wires: FxHashMap<usize, usize>,
+    labels: Vec<Option<Rc<str>>>,
+    instrs: Vec<Instr>,
+    next_result: usize,
+}
+
+impl<B: Backend> CircuitTrace<B> {
+    fn new(inner: B) -> Self {
+        Self {
+            inner,
+            wires: FxHashMap::default(),
+            labels: Vec::new(),
+            instrs: Vec::new(),
+            next_result: 0,
+        }
+    }
+
+    /// Records a gate with its OpenQASM name.
+    fn gate(&mut self, name: &'static str, params: Vec<f64>, qubits: &[usize]) {
+        let qubits = qubits.iter().map(|q| self.wires[q]).collect();
+        self.instrs.push(Instr::Gate(name, params, qubits));
+    }
+
+    fn measurement(&mut self, q: usize) {
+        let result = self.next_result;
+        self.next_result += 1;
+        self.instrs.push(Instr::Measure(self.wires[&q], result));
+    }
+
+    /// Labels each unlabeled wire of a qubit in the value with the name of the variable it is
+    /// bound to, or with the name and its index if it is in an array.
+    fn label(&mut self, name: &Rc<str>, value: &Value) {
+        match value {
+            &Value::Qubit(Qubit(q)) => {
+                if let Some(&wire) = self.wires.get(&q) {
+                    self.labels[wire].get_or_insert_with(|| name.clone());
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if let &Value::Qubit(Qubit(q)) = item {
+                        if let Some(&wire) = self.wires.get(&q) {
+                            self.labels[wire].get_or_insert_with(|| format!("{name}[{i}]").into());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<B: Backend> Backend for CircuitTrace<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.gate("ccx", Vec::new(), &[ctl0, ctl1, q]);
+        self.inner.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.gate("cx", Vec::new(), &[ctl, q]);
+        self.inner.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.gate("cy", Vec::new(), &[ctl, q]);
+        self.inner.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.gate("cz", Vec::new(), &[ctl, q]);
+        self.inner.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.gate("h", Vec::new(), &[q]);
+        self.inner.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.measurement(q);
+        self.inner.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.measurement(q);
+        self.instrs.push(Instr::Reset(self.wires[&q]));
+        self.inner.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.instrs.push(Instr::Reset(self.wires[&q]));
+        self.inner.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.gate("rx", vec![theta], &[q]);
+        self.inner.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("rxx", vec![theta], &[q0, q1]);
+        self.inner.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.gate("ry", vec![theta], &[q]);
+        self.inner.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("ryy", vec![theta], &[q0, q1]);
+        self.inner.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.gate("rz", vec![theta], &[q]);
+        self.inner.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("rzz", vec![theta], &[q0, q1]);
+        self.inner.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.gate("sdg", Vec::new(), &[q]);
+        self.inner.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.gate("s", Vec::new(), &[q]);
+        self.inner.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.gate("swap", Vec::new(), &[q0, q1]);
+        self.inner.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.gate("tdg", Vec::new(), &[q]);
+        self.inner.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.gate("t", Vec::new(), &[q]);
+        self.inner.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.gate("x", Vec::new(), &[q]);
+        self.inner.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.gate("y", Vec::new(), &[q]);
+        self.inner.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.gate("z", Vec::new(), &[q]);
+        self.inner.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let q = self.inner.qubit_allocate();
+        if !self.wires.contains_key(&q) {
+            self.wires.insert(q, self.labels.len());
+            self.labels.push(None);
+        }
+        q
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.inner.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        self.inner.dump_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.inner.qubit_is_zero(q)
+    }
+
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        self.inner.pauli_expectation(paulis, qubits)
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        self.inner.basis_state_amplitude(qubits, basis_state)
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        self.gate("U", Vec::new(), qubits);
+        self.inner.apply_unitary(matrix, qubits)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        self.inner.take_warnings()
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.inner.take_error()
+    }
+}
+
+/// Formats a gate parameter with at most four decimal places.
+fn number(value: f64) -> String {
+    let text = format!("{value:.4}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,150 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+
+use super::{from_program, from_trace};
+
+fn compile_program(program: &str) -> (PackageStore, PackageId) {
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::all());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let std = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let package = store.insert(unit);
+    (store, package)
+}
+
+#[test]
+fn program_wires_are_labeled_with_variable_names() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] {
+                use (control, target) = (Qubit(), Qubit());
+                H(control);
+                CNOT(control, target);
+                [M(control), M(target)]
+            }
+        }
+    "});
+    let circuit = from_program(&store, package, false).expect("circuit should be built");
+    expect![[r#"
+        control ──H──●──M─
+                     │
+        target  ─────X──M─
+    "#]]
+    .assert_eq(&circuit.to_ascii());
+}
+
+#[test]
+fn program_branch_on_measurement_is_condition() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                use r = Qubit();
+                H(q);
+                if M(q) == One {
+                    X(r);
+                }
+                M(r)
+            }
+        }
+    "});
+    let circuit = from_program(&store, package, false).expect("circuit should be built");
+    expect![[r#"
+        q ──H──M─────────────
+
+        r ────────X[c0=1]──M─
+    "#]]
+    .assert_eq(&circuit.to_ascii());
+}
+
+#[test]
+fn trace_of_loop_labels_array_elements() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            @EntryPoint()
+            operation Main() : Result[] {
+                use qs = Qubit[3];
+                H(qs[0]);
+                for i in 1..2 {
+                    CNOT(qs[0], qs[i]);
+                }
+                MResetEachZ(qs)
+            }
+        }
+    "});
+    let circuit = from_trace(&store, package, Some(0), false).expect("circuit should be built");
+    expect![[r#"
+        qs[0] ──H──●──●──M──|0⟩─
+                   │  │
+        qs[1] ─────X──┼──M──|0⟩─
+                      │
+        qs[2] ────────X──M──|0⟩─
+    "#]]
+    .assert_eq(&circuit.to_ascii());
+}
+
+#[test]
+fn trace_renders_as_svg() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                Rx(0.5, q);
+                MResetZ(q)
+            }
+        }
+    "});
+    let circuit = from_trace(&store, package, Some(0), false).expect("circuit should be built");
+    expect![[r#"
+        <svg xmlns="http://www.w3.org/2000/svg" width="228" height="80" font-family="monospace" font-size="14">
+        <text x="10" y="40" dominant-baseline="middle">q</text>
+        <line x1="29" y1="40" x2="228" y2="40" stroke="black"/>
+        <rect x="34" y="30" width="83" height="20" fill="white" stroke="black"/>
+        <text x="75" y="40" text-anchor="middle" dominant-baseline="middle">Rx(0.5)</text>
+        <rect x="127" y="30" width="29" height="20" fill="white" stroke="black"/>
+        <text x="141" y="40" text-anchor="middle" dominant-baseline="middle">M</text>
+        <rect x="166" y="30" width="47" height="20" fill="white" stroke="black"/>
+        <text x="189" y="40" text-anchor="middle" dominant-baseline="middle">|0⟩</text>
+        </svg>
+    "#]]
+    .assert_eq(&circuit.to_svg());
+}
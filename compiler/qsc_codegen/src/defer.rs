@@ -0,0 +1,274 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Deferral of measurements to the end of a program, so that a program with branches on
+//! measurement results can still run on a target without mid-circuit measurement.
+//!
+//! A measurement can move past a later gate on the measured qubit only if the gate commutes with
+//! it, that is, if the gate uses the qubit as a control or is diagonal on it. A branch on the
+//! measurement result then becomes the gates in the branch, each controlled by the measured qubit
+//! instead of the bit. A reset after a measurement moves the rest of the program onto a fresh
+//! qubit, since the measured qubit is still needed as a control. The program is written as base
+//! profile QIR with every measurement at the end, in the order they appear in the program.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    qasm::{self, walk_program, Instr, Output},
+    qir_base::{lower_store, BaseProfSim},
+};
+use miette::Diagnostic;
+use qsc_eval::{backend::Backend, debug::map_hir_package_to_fir, partial_eval};
+use qsc_fir::fir::{PackageId, PackageStore};
+use qsc_frontend::compile;
+use qsc_hir::hir;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::iter;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("cannot defer a measurement past the gate `{0}` on the measured qubit")]
+    #[diagnostic(help(
+        "after a measurement, a qubit can only be used as a control or by gates that are diagonal on it, unless it is reset first"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.Defer.GateAfterMeasurement"))]
+    GateAfterMeasurement(&'static str),
+
+    #[error(
+        "cannot replace the gate `{0}` in a branch on a measurement result with a controlled gate"
+    )]
+    #[diagnostic(help(
+        "a branch on a measurement result can only apply `X`, `Y`, `Z` and `CNOT` when measurements are deferred"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.Defer.UncontrollableGate"))]
+    UncontrollableGate(&'static str),
+
+    #[error("cannot defer a measurement or reset in a branch on a measurement result")]
+    #[diagnostic(code("Qsc.Codegen.Defer.MeasurementInBranch"))]
+    MeasurementInBranch,
+
+    #[error("cannot defer a branch in a branch on a measurement result")]
+    #[diagnostic(code("Qsc.Codegen.Defer.NestedBranch"))]
+    NestedBranch,
+
+    #[error("cannot write a result literal as base profile output")]
+    #[diagnostic(help("the entry expression should only return measured results"))]
+    #[diagnostic(code("Qsc.Codegen.Defer.ResultLiteralOutput"))]
+    ResultLiteralOutput,
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Qasm(qasm::Error),
+}
+
+/// Generates base profile QIR for the entry expression of the given package with every
+/// measurement deferred to the end, lowering every package in the store to FIR and partially
+/// evaluating the given package first.
+///
+/// # Errors
+///
+/// Returns the first construct that cannot be resolved without running the program, or the
+/// first instruction that keeps a measurement from being deferred.
+pub fn generate_qir(
+    store: &compile::PackageStore,
+    package: hir::PackageId,
+) -> Result<String, Error> {
+    let mut fir_store = lower_store(store);
+    let package = map_hir_package_to_fir(package);
+    partial_eval::partially_evaluate(&mut fir_store, package, partial_eval::DEFAULT_STEP_BUDGET);
+    generate_qir_from_fir(&fir_store, package)
+}
+
+/// Generates base profile QIR for the entry expression of the given package in a store that has
+/// already been lowered to FIR, with every measurement deferred as for [`generate_qir`].
+///
+/// # Errors
+///
+/// Returns the first construct that cannot be resolved without running the program, or the
+/// first instruction that keeps a measurement from being deferred.
+///
+/// # Panics
+///
+/// Panics if the package has no entry expression.
+pub fn generate_qir_from_fir(store: &PackageStore, package: PackageId) -> Result<String, Error> {
+    let program = walk_program(store, package).map_err(Error::Qasm)?;
+    if program
+        .outputs
+        .iter()
+        .any(|output| matches!(output, Output::Lit(_)))
+    {
+        return Err(Error::ResultLiteralOutput);
+    }
+
+    let mut deferral = Deferral::new(program.qubits);
+    for instr in program.instrs {
+        deferral.instr(instr)?;
+    }
+
+    let mut sim = BaseProfSim::new();
+    for (gate, params, qubits) in &deferral.gates {
+        apply(&mut sim, gate, params, qubits);
+    }
+    // The measurements keep their order, so the simulator gives each result the same ID as its
+    // bit in the program.
+    for &qubit in &deferral.measurements {
+        sim.m(qubit);
+    }
+    Ok(sim.finish(&program.value))
+}
+
+/// The gates of a program with its measurements moved to the end.
+struct Deferral {
+    /// The qubit that each qubit in the program currently acts on, which changes after a reset.
+    wires: Vec<usize>,
+    next_wire: usize,
+    /// The qubit that each bit was measured from.
+    bits: FxHashMap<usize, usize>,
+    measured: FxHashSet<usize>,
+    gates: Vec<(&'static str, Vec<f64>, Vec<usize>)>,
+    /// The measured qubits, in the order of their bits.
+    measurements: Vec<usize>,
+}
+
+impl Deferral {
+    fn new(qubits: usize) -> Self {
+        Self {
+            wires: (0..qubits).collect(),
+            next_wire: qubits,
+            bits: FxHashMap::default(),
+            measured: FxHashSet::default(),
+            gates: Vec::new(),
+            measurements: Vec::new(),
+        }
+    }
+
+    fn instr(&mut self, instr: Instr) -> Result<(), Error> {
+        match instr {
+            Instr::Gate(gate, params, qubits) => {
+                let qubits = qubits.into_iter().map(|q| self.wires[q]).collect();
+                self.gate(gate, params, qubits)
+            }
+            Instr::Measure(qubit, bit) => {
+                let wire = self.wires[qubit];
+                self.bits.insert(bit, wire);
+                self.measured.insert(wire);
+                self.measurements.push(wire);
+                Ok(())
+            }
+            Instr::Reset(qubit) => {
+                self.wires[qubit] = self.next_wire;
+                self.next_wire += 1;
+                Ok(())
+            }
+            Instr::If(bit, value, then, otherwise) => {
+                let control = self.bits[&bit];
+                self.branch(control, value, then)?;
+                self.branch(control, !value, otherwise)
+            }
+        }
+    }
+
+    fn gate(
+        &mut self,
+        gate: &'static str,
+        params: Vec<f64>,
+        qubits: Vec<usize>,
+    ) -> Result<(), Error> {
+        if self.blocks_measurement(gate, &qubits) {
+            return Err(Error::GateAfterMeasurement(gate));
+        }
+        self.gates.push((gate, params, qubits));
+        Ok(())
+    }
+
+    /// Whether the gate acts on a measured qubit in a way that does not commute with measuring it.
+    fn blocks_measurement(&self, gate: &str, qubits: &[usize]) -> bool {
+        qubits.iter().enumerate().any(|(position, q)| {
+            self.measured.contains(q) && !commutes_with_measurement(gate, position)
+        })
+    }
+
+    /// Adds the instructions in a branch as gates controlled by the measured qubit, applying them
+    /// when the qubit is in the state of the value.
+    fn branch(&mut self, control: usize, value: bool, instrs: Vec<Instr>) -> Result<(), Error> {
+        if instrs.is_empty() {
+            return Ok(());
+        }
+        // Flipping the control before and after the gates leaves it as it was, so together they
+        // still commute with its measurement.
+        if !value {
+            self.gates.push(("x", Vec::new(), vec![control]));
+        }
+        for instr in instrs {
+            match instr {
+                Instr::Gate(gate, params, qubits) => {
+                    let controlled = controlled(gate).ok_or(Error::UncontrollableGate(gate))?;
+                    let qubits: Vec<_> = iter::once(control)
+                        .chain(qubits.into_iter().map(|q| self.wires[q]))
+                        .collect();
+                    if qubits[1..].contains(&control)
+                        || self.blocks_measurement(controlled, &qubits)
+                    {
+                        return Err(Error::GateAfterMeasurement(gate));
+                    }
+                    self.gates.push((controlled, params, qubits));
+                }
+                Instr::Measure(..) | Instr::Reset(_) => return Err(Error::MeasurementInBranch),
+                Instr::If(..) => return Err(Error::NestedBranch),
+            }
+        }
+        if !value {
+            self.gates.push(("x", Vec::new(), vec![control]));
+        }
+        Ok(())
+    }
+}
+
+/// Whether a measurement of the qubit at the position commutes with the gate, because the gate
+/// uses it as a control or is diagonal on it.
+fn commutes_with_measurement(gate: &str, position: usize) -> bool {
+    match gate {
+        "cx" | "cy" => position == 0,
+        "ccx" => position < 2,
+        "cz" | "rz" | "rzz" | "s" | "sdg" | "t" | "tdg" | "z" => true,
+        _ => false,
+    }
+}
+
+/// The gate with one more control, if the standard gates have it.
+fn controlled(gate: &str) -> Option<&'static str> {
+    match gate {
+        "cx" => Some("ccx"),
+        "x" => Some("cx"),
+        "y" => Some("cy"),
+        "z" => Some("cz"),
+        _ => None,
+    }
+}
+
+fn apply(sim: &mut BaseProfSim, gate: &str, params: &[f64], qubits: &[usize]) {
+    match (gate, params, qubits) {
+        ("ccx", [], &[ctl0, ctl1, q]) => sim.ccx(ctl0, ctl1, q),
+        ("cx", [], &[ctl, q]) => sim.cx(ctl, q),
+        ("cy", [], &[ctl, q]) => sim.cy(ctl, q),
+        ("cz", [], &[ctl, q]) => sim.cz(ctl, q),
+        ("rx", &[theta], &[q]) => sim.rx(theta, q),
+        ("rxx", &[theta], &[q0, q1]) => sim.rxx(theta, q0, q1),
+        ("ry", &[theta], &[q]) => sim.ry(theta, q),
+        ("ryy", &[theta], &[q0, q1]) => sim.ryy(theta, q0, q1),
+        ("rz", &[theta], &[q]) => sim.rz(theta, q),
+        ("rzz", &[theta], &[q0, q1]) => sim.rzz(theta, q0, q1),
+        ("h", [], &[q]) => sim.h(q),
+        ("s", [], &[q]) => sim.s(q),
+        ("sdg", [], &[q]) => sim.sadj(q),
+        ("t", [], &[q]) => sim.t(q),
+        ("tdg", [], &[q]) => sim.tadj(q),
+        ("x", [], &[q]) => sim.x(q),
+        ("y", [], &[q]) => sim.y(q),
+        ("z", [], &[q]) => sim.z(q),
+        ("swap", [], &[q0, q1]) => sim.swap(q0, q1),
+        _ => panic!("gate `{gate}` should be a standard gate with matching arguments"),
+    }
+}
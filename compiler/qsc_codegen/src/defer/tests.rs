@@ -0,0 +1,181 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+
+use super::{generate_qir, Error};
+use crate::test_utils::entry_definition;
+
+fn generate(program: &str) -> Result<String, Error> {
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::all());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let std = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let package = store.insert(unit);
+    generate_qir(&store, package)
+}
+
+/// Checks the definition of the entry point, leaving out the declarations and attributes.
+fn check_entry(program: &str, expect: &Expect) {
+    let qir = generate(program).expect("QIR should be generated");
+    expect.assert_eq(entry_definition(&qir));
+}
+
+#[test]
+fn branch_on_result_becomes_controlled_gates() {
+    check_entry(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    if M(q0) == One {
+                        X(q1);
+                    } else {
+                        Z(q1);
+                    }
+                    M(q1)
+                }
+            }
+        "},
+        &expect![[r#"
+            define void @ENTRYPOINT__main() #0 {
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 1 to %Qubit*))
+              call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__cz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 1 to %Qubit*))
+              call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*)) #1
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+              ret void
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn reset_after_measurement_moves_to_fresh_qubit() {
+    check_entry(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use (q, t) = (Qubit(), Qubit());
+                    H(q);
+                    let r = M(q);
+                    Reset(q);
+                    H(q);
+                    if r == One {
+                        X(t);
+                    }
+                    [r, M(q), M(t)]
+                }
+            }
+        "},
+        &expect![[r#"
+            define void @ENTRYPOINT__main() #0 {
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+              call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 2 to %Qubit*))
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*)) #1
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 2 to %Qubit*), %Result* inttoptr (i64 2 to %Result*)) #1
+              call void @__quantum__rt__array_record_output(i64 3, i8* null)
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 2 to %Result*), i8* null)
+              ret void
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn gate_on_measured_qubit_is_error() {
+    let error = generate(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                let r = M(q);
+                H(q);
+                r
+            }
+        }
+    "})
+    .expect_err("QIR should not be generated");
+    assert!(
+        matches!(error, Error::GateAfterMeasurement("h")),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn correcting_measured_qubit_in_branch_is_error() {
+    let error = generate(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+        }
+    "})
+    .expect_err("QIR should not be generated");
+    assert!(
+        matches!(error, Error::GateAfterMeasurement("x")),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn uncontrollable_gate_in_branch_is_error() {
+    let error = generate(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                use (q0, q1) = (Qubit(), Qubit());
+                if M(q0) == One {
+                    H(q1);
+                }
+            }
+        }
+    "})
+    .expect_err("QIR should not be generated");
+    assert!(matches!(error, Error::UncontrollableGate("h")), "{error:?}");
+}
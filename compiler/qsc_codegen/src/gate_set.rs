@@ -0,0 +1,403 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The native gate set of a target, and the rewriting of the gates in a program into it.
+//!
+//! A target is described by a JSON file with the gates it runs natively, and a decomposition of
+//! each other gate into a sequence of gates:
+//!
+//! ```json
+//! {
+//!     "native": ["rz", "sx", "x", "cx"],
+//!     "decompositions": {
+//!         "h": [
+//!             { "gate": "rz", "params": ["pi / 2"], "qubits": [0] },
+//!             { "gate": "sx", "qubits": [0] },
+//!             { "gate": "rz", "params": ["pi / 2"], "qubits": [0] }
+//!         ],
+//!         "rx": [
+//!             { "gate": "h", "qubits": [0] },
+//!             { "gate": "rz", "params": ["theta"], "qubits": [0] },
+//!             { "gate": "h", "qubits": [0] }
+//!         ]
+//!     }
+//! }
+//! ```
+//!
+//! Each gate in a decomposition acts on qubits of the decomposed gate by their position in it. Its
+//! angles are numbers or arithmetic expressions of numbers, `pi`, and the angles of the decomposed
+//! gate, named `theta`, `phi` and `lambda` in order. A decomposition can use gates that are not
+//! native but have decompositions themselves, as long as no gate is defined in terms of itself.
+//! Gates are named as in the OpenQASM standard gate library, and every name in the file must be
+//! one of [`GATES`].
+
+#[cfg(test)]
+mod tests;
+
+use crate::qasm::Instr;
+use miette::Diagnostic;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use std::{f64::consts::PI, iter::Peekable, str::Chars};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("gate set could not be read: {0}")]
+    #[diagnostic(code("Qsc.Codegen.GateSet.Format"))]
+    Format(String),
+
+    #[error("gate set has unknown gate `{0}`")]
+    #[diagnostic(help("gates are named as in the OpenQASM standard gate library"))]
+    #[diagnostic(code("Qsc.Codegen.GateSet.UnknownGate"))]
+    UnknownGate(String),
+
+    #[error("decomposition of `{0}` applies `{1}` with the wrong number of angles or qubits")]
+    #[diagnostic(code("Qsc.Codegen.GateSet.Arity"))]
+    Arity(String, String),
+
+    #[error("decomposition of `{0}` has invalid angle `{1}`")]
+    #[diagnostic(help(
+        "an angle is an arithmetic expression of numbers, `pi`, and the angles `theta`, `phi` and `lambda` of the decomposed gate"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.GateSet.Angle"))]
+    Angle(String, String),
+
+    #[error("decomposition of `{0}` depends on itself")]
+    #[diagnostic(code("Qsc.Codegen.GateSet.Cycle"))]
+    Cycle(String),
+
+    #[error("gate `{0}` is not native to the target and has no decomposition")]
+    #[diagnostic(code("Qsc.Codegen.GateSet.Unmapped"))]
+    Unmapped(&'static str),
+}
+
+/// The gates that a gate set can name, with the number of angles and qubits each takes.
+pub const GATES: &[(&str, usize, usize)] = &[
+    ("ccx", 0, 3),
+    ("cx", 0, 2),
+    ("cy", 0, 2),
+    ("cz", 0, 2),
+    ("rx", 1, 1),
+    ("rxx", 1, 2),
+    ("ry", 1, 1),
+    ("ryy", 1, 2),
+    ("rz", 1, 1),
+    ("rzz", 1, 2),
+    ("h", 0, 1),
+    ("s", 0, 1),
+    ("sdg", 0, 1),
+    ("t", 0, 1),
+    ("tdg", 0, 1),
+    ("x", 0, 1),
+    ("y", 0, 1),
+    ("z", 0, 1),
+    ("swap", 0, 2),
+    ("sx", 0, 1),
+    ("p", 1, 1),
+    ("u3", 3, 1),
+];
+
+/// The native gates of a target, and the decomposition of each other gate into native gates.
+#[derive(Debug)]
+pub struct GateSet {
+    native: Vec<&'static str>,
+    decompositions: FxHashMap<&'static str, Vec<Step>>,
+}
+
+/// A gate in a decomposition.
+#[derive(Clone, Debug)]
+struct Step {
+    gate: &'static str,
+    params: Vec<Angle>,
+    /// The positions of the qubits in the decomposed gate.
+    qubits: Vec<usize>,
+}
+
+/// An angle in a decomposition.
+#[derive(Clone, Debug)]
+enum Angle {
+    Num(f64),
+    /// The angle of the decomposed gate at the index.
+    Param(usize),
+    Neg(Box<Angle>),
+    Bin(char, Box<Angle>, Box<Angle>),
+}
+
+#[derive(Deserialize)]
+struct GateSetFile {
+    native: Vec<String>,
+    #[serde(default)]
+    decompositions: FxHashMap<String, Vec<StepFile>>,
+}
+
+#[derive(Deserialize)]
+struct StepFile {
+    gate: String,
+    #[serde(default)]
+    params: Vec<AngleFile>,
+    qubits: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AngleFile {
+    Num(f64),
+    Expr(String),
+}
+
+impl GateSet {
+    /// Reads a gate set from its JSON description, expanding each decomposition until it only
+    /// uses native gates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the description is not valid, names an unknown gate, applies a gate to
+    /// the wrong number of angles or qubits, or defines a gate in terms of itself.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let file: GateSetFile =
+            serde_json::from_str(json).map_err(|error| Error::Format(error.to_string()))?;
+        let native = file
+            .native
+            .iter()
+            .map(|name| gate_arity(name).map(|(name, ..)| name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut steps = FxHashMap::default();
+        for (name, file_steps) in &file.decompositions {
+            let (name, params, qubits) = gate_arity(name)?;
+            let mut decomposition = Vec::with_capacity(file_steps.len());
+            for step in file_steps {
+                let (gate, step_params, step_qubits) = gate_arity(&step.gate)?;
+                if step.params.len() != step_params
+                    || step.qubits.len() != step_qubits
+                    || step.qubits.iter().any(|&q| q >= qubits)
+                {
+                    return Err(Error::Arity(name.to_string(), gate.to_string()));
+                }
+                let angles = step
+                    .params
+                    .iter()
+                    .map(|param| match param {
+                        AngleFile::Num(value) => Ok(Angle::Num(*value)),
+                        AngleFile::Expr(expr) => parse_angle(expr, params)
+                            .ok_or_else(|| Error::Angle(name.to_string(), expr.clone())),
+                    })
+                    .collect::<Result<_, _>>()?;
+                decomposition.push(Step {
+                    gate,
+                    params: angles,
+                    qubits: step.qubits.clone(),
+                });
+            }
+            steps.insert(name, decomposition);
+        }
+
+        let mut decompositions = FxHashMap::default();
+        for &name in steps.keys() {
+            if !native.contains(&name) {
+                let expanded = expand(name, &native, &steps, &mut Vec::new())?;
+                decompositions.insert(name, expanded);
+            }
+        }
+        Ok(Self {
+            native,
+            decompositions,
+        })
+    }
+
+    /// Rewrites each gate in the instructions that is not native into its decomposition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a gate is neither native nor has a decomposition.
+    pub(crate) fn map(&self, instrs: &mut Vec<Instr>) -> Result<(), Error> {
+        let mut mapped = Vec::with_capacity(instrs.len());
+        for instr in instrs.drain(..) {
+            match instr {
+                Instr::Gate(gate, params, qubits) if !self.native.contains(&gate) => {
+                    let steps = self.decompositions.get(gate).ok_or(Error::Unmapped(gate))?;
+                    for step in steps {
+                        mapped.push(Instr::Gate(
+                            step.gate,
+                            step.params
+                                .iter()
+                                .map(|angle| angle.eval(&params))
+                                .collect(),
+                            step.qubits.iter().map(|&q| qubits[q]).collect(),
+                        ));
+                    }
+                }
+                Instr::If(bit, value, mut then, mut otherwise) => {
+                    self.map(&mut then)?;
+                    self.map(&mut otherwise)?;
+                    mapped.push(Instr::If(bit, value, then, otherwise));
+                }
+                instr => mapped.push(instr),
+            }
+        }
+        *instrs = mapped;
+        Ok(())
+    }
+}
+
+/// The gate with the name, with the number of angles and qubits it takes.
+fn gate_arity(name: &str) -> Result<(&'static str, usize, usize), Error> {
+    GATES
+        .iter()
+        .find(|&&(gate, ..)| gate == name)
+        .copied()
+        .ok_or_else(|| Error::UnknownGate(name.to_string()))
+}
+
+/// Expands the decomposition of the gate until it only uses native gates, with the gates whose
+/// decompositions are being expanded on the stack.
+fn expand(
+    name: &'static str,
+    native: &[&'static str],
+    steps: &FxHashMap<&'static str, Vec<Step>>,
+    stack: &mut Vec<&'static str>,
+) -> Result<Vec<Step>, Error> {
+    if stack.contains(&name) {
+        return Err(Error::Cycle(name.to_string()));
+    }
+    stack.push(name);
+    let mut expanded = Vec::new();
+    for step in &steps[name] {
+        if native.contains(&step.gate) {
+            expanded.push(step.clone());
+        } else if steps.contains_key(step.gate) {
+            for inner in expand(step.gate, native, steps, stack)? {
+                expanded.push(Step {
+                    gate: inner.gate,
+                    params: inner
+                        .params
+                        .iter()
+                        .map(|angle| angle.substitute(&step.params))
+                        .collect(),
+                    qubits: inner.qubits.iter().map(|&q| step.qubits[q]).collect(),
+                });
+            }
+        } else {
+            return Err(Error::Unmapped(step.gate));
+        }
+    }
+    stack.pop();
+    Ok(expanded)
+}
+
+impl Angle {
+    fn eval(&self, params: &[f64]) -> f64 {
+        match self {
+            &Angle::Num(value) => value,
+            &Angle::Param(i) => params[i],
+            Angle::Neg(angle) => -angle.eval(params),
+            Angle::Bin(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(params), rhs.eval(params));
+                match op {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    '/' => lhs / rhs,
+                    _ => unreachable!("operator should be arithmetic"),
+                }
+            }
+        }
+    }
+
+    /// Replaces each angle of the decomposed gate with the angle at its index.
+    fn substitute(&self, params: &[Angle]) -> Angle {
+        match self {
+            Angle::Num(_) => self.clone(),
+            &Angle::Param(i) => params[i].clone(),
+            Angle::Neg(angle) => Angle::Neg(Box::new(angle.substitute(params))),
+            Angle::Bin(op, lhs, rhs) => Angle::Bin(
+                *op,
+                Box::new(lhs.substitute(params)),
+                Box::new(rhs.substitute(params)),
+            ),
+        }
+    }
+}
+
+/// Parses an angle of a gate that takes the given number of angles.
+fn parse_angle(expr: &str, params: usize) -> Option<Angle> {
+    let mut chars = expr.chars().peekable();
+    let angle = parse_sum(&mut chars, params)?;
+    skip_spaces(&mut chars);
+    chars.peek().is_none().then_some(angle)
+}
+
+fn parse_sum(chars: &mut Peekable<Chars>, params: usize) -> Option<Angle> {
+    let mut lhs = parse_product(chars, params)?;
+    loop {
+        skip_spaces(chars);
+        match chars.peek() {
+            Some(&op @ ('+' | '-')) => {
+                chars.next();
+                let rhs = parse_product(chars, params)?;
+                lhs = Angle::Bin(op, Box::new(lhs), Box::new(rhs));
+            }
+            _ => return Some(lhs),
+        }
+    }
+}
+
+fn parse_product(chars: &mut Peekable<Chars>, params: usize) -> Option<Angle> {
+    let mut lhs = parse_factor(chars, params)?;
+    loop {
+        skip_spaces(chars);
+        match chars.peek() {
+            Some(&op @ ('*' | '/')) => {
+                chars.next();
+                let rhs = parse_factor(chars, params)?;
+                lhs = Angle::Bin(op, Box::new(lhs), Box::new(rhs));
+            }
+            _ => return Some(lhs),
+        }
+    }
+}
+
+fn parse_factor(chars: &mut Peekable<Chars>, params: usize) -> Option<Angle> {
+    skip_spaces(chars);
+    match *chars.peek()? {
+        '-' => {
+            chars.next();
+            Some(Angle::Neg(Box::new(parse_factor(chars, params)?)))
+        }
+        '(' => {
+            chars.next();
+            let angle = parse_sum(chars, params)?;
+            skip_spaces(chars);
+            (chars.next()? == ')').then_some(angle)
+        }
+        c if c.is_ascii_digit() || c == '.' => {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            number.parse().ok().map(Angle::Num)
+        }
+        c if c.is_ascii_alphabetic() => {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                name.push(c);
+                chars.next();
+            }
+            let param = match name.as_str() {
+                "pi" => return Some(Angle::Num(PI)),
+                "theta" => 0,
+                "phi" => 1,
+                "lambda" => 2,
+                _ => return None,
+            };
+            (param < params).then_some(Angle::Param(param))
+        }
+        _ => None,
+    }
+}
+
+fn skip_spaces(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
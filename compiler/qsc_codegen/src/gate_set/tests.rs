@@ -0,0 +1,163 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{Error, GateSet};
+use crate::qasm::{self, generate_qasm};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+
+const IBM: &str = r#"{
+    "native": ["rz", "sx", "x", "cx"],
+    "decompositions": {
+        "h": [
+            { "gate": "rz", "params": ["pi / 2"], "qubits": [0] },
+            { "gate": "sx", "qubits": [0] },
+            { "gate": "rz", "params": ["pi / 2"], "qubits": [0] }
+        ],
+        "t": [{ "gate": "rz", "params": ["pi / 4"], "qubits": [0] }],
+        "rx": [
+            { "gate": "h", "qubits": [0] },
+            { "gate": "rz", "params": ["theta"], "qubits": [0] },
+            { "gate": "h", "qubits": [0] }
+        ]
+    }
+}"#;
+
+fn generate(program: &str, gate_set: &GateSet) -> Result<String, qasm::Error> {
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::all());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let std = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let package = store.insert(unit);
+    generate_qasm(&store, package, Some(gate_set), false)
+}
+
+fn check(program: &str, gate_set: &str, expect: &Expect) {
+    let gate_set = GateSet::from_json(gate_set).expect("gate set should load");
+    let qasm = generate(program, &gate_set).expect("OpenQASM should be generated");
+    expect.assert_eq(&qasm);
+}
+
+#[test]
+fn gates_are_decomposed_into_native_gates() {
+    check(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    T(q0);
+                    CNOT(q0, q1);
+                    Rx(0.5, q1);
+                    [M(q0), M(q1)]
+                }
+            }
+        "},
+        IBM,
+        &expect![[r#"
+            OPENQASM 3.0;
+            include "stdgates.inc";
+
+            qubit[2] q;
+            bit[2] c;
+            output bit[2] result;
+
+            rz(1.5707963267948966) q[0];
+            sx q[0];
+            rz(1.5707963267948966) q[0];
+            rz(0.7853981633974483) q[0];
+            cx q[0], q[1];
+            rz(1.5707963267948966) q[1];
+            sx q[1];
+            rz(1.5707963267948966) q[1];
+            rz(0.5) q[1];
+            rz(1.5707963267948966) q[1];
+            sx q[1];
+            rz(1.5707963267948966) q[1];
+            c[0] = measure q[0];
+            c[1] = measure q[1];
+            result[0] = c[0];
+            result[1] = c[1];
+        "#]],
+    );
+}
+
+#[test]
+fn gate_without_decomposition_is_error() {
+    let gate_set = GateSet::from_json(IBM).expect("gate set should load");
+    let result = generate(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    Y(q);
+                    M(q)
+                }
+            }
+        "},
+        &gate_set,
+    );
+    assert!(matches!(
+        result,
+        Err(qasm::Error::GateSet(Error::Unmapped("y")))
+    ));
+}
+
+#[test]
+fn decomposition_cycle_is_error() {
+    let result = GateSet::from_json(
+        r#"{
+            "native": ["rz"],
+            "decompositions": {
+                "h": [{ "gate": "y", "qubits": [0] }],
+                "y": [{ "gate": "h", "qubits": [0] }]
+            }
+        }"#,
+    );
+    assert!(matches!(result, Err(Error::Cycle(_))));
+}
+
+#[test]
+fn decomposition_with_wrong_arity_is_error() {
+    let result = GateSet::from_json(
+        r#"{
+            "native": ["rz", "cx"],
+            "decompositions": {
+                "h": [{ "gate": "cx", "qubits": [0, 1] }]
+            }
+        }"#,
+    );
+    assert!(matches!(result, Err(Error::Arity(gate, step)) if gate == "h" && step == "cx"));
+}
@@ -1,4 +1,13 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod circuit;
+pub mod defer;
+pub mod gate_set;
+mod peephole;
+pub mod qasm;
+pub mod qir_adaptive;
 pub mod qir_base;
+#[cfg(test)]
+mod test_utils;
+mod walk;
@@ -0,0 +1,194 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A peephole optimizer for the instructions extracted from a program.
+//!
+//! A gate followed directly by its inverse on the same qubits cancels with it, and a rotation
+//! followed directly by a rotation about the same axis on the same qubits fuses into one, which
+//! is dropped if its angle is a multiple of 2π. Cancelling a pair can make the gates around it
+//! adjacent, so they are checked in turn. Then, walking backwards from the end, a gate is dropped
+//! if none of its qubits can still affect a measurement, either directly or through a later gate
+//! with a qubit that can.
+
+#[cfg(test)]
+mod tests;
+
+use crate::qasm::Instr;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::f64::consts::TAU;
+
+/// The tolerance for a fused rotation angle to be considered a multiple of 2π.
+const ANGLE_TOLERANCE: f64 = 1e-10;
+
+/// Optimizes the instructions in place.
+pub(crate) fn optimize(instrs: &mut Vec<Instr>) {
+    cancel(instrs);
+    remove_unmeasured(instrs, &mut FxHashSet::default());
+}
+
+/// How a gate combines with the gate before it on the same qubits.
+enum Combined {
+    /// The gate was fused into the gate before it.
+    Fused,
+    /// The gates cancel out.
+    Identity,
+}
+
+fn cancel(instrs: &mut Vec<Instr>) {
+    let mut kept: Vec<Option<Instr>> = Vec::new();
+    // The indices in `kept` of the instructions that act on each qubit, with the latest last.
+    let mut on_qubit: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+    for mut instr in instrs.drain(..) {
+        if let Instr::If(_, _, then, otherwise) = &mut instr {
+            cancel(then);
+            cancel(otherwise);
+        }
+        let mut qubits = Vec::new();
+        instr_qubits(&instr, &mut qubits);
+
+        if let Some(i) = previous(&on_qubit, &qubits) {
+            match kept[i].as_mut().and_then(|prev| combine(prev, &instr)) {
+                Some(Combined::Fused) => continue,
+                Some(Combined::Identity) => {
+                    kept[i] = None;
+                    for q in &qubits {
+                        on_qubit
+                            .get_mut(q)
+                            .expect("qubit should have instructions")
+                            .pop();
+                    }
+                    continue;
+                }
+                None => {}
+            }
+        }
+
+        for q in qubits {
+            on_qubit.entry(q).or_default().push(kept.len());
+        }
+        kept.push(Some(instr));
+    }
+    instrs.extend(kept.into_iter().flatten());
+}
+
+/// The index of the latest instruction, if it is the same for every qubit.
+fn previous(on_qubit: &FxHashMap<usize, Vec<usize>>, qubits: &[usize]) -> Option<usize> {
+    let mut latest = qubits
+        .iter()
+        .map(|q| on_qubit.get(q).and_then(|indices| indices.last()));
+    let first = latest.next()??;
+    latest.all(|i| i == Some(first)).then_some(*first)
+}
+
+fn combine(prev: &mut Instr, next: &Instr) -> Option<Combined> {
+    let (
+        Instr::Gate(prev_gate, prev_params, prev_qubits),
+        Instr::Gate(next_gate, next_params, next_qubits),
+    ) = (prev, next)
+    else {
+        return None;
+    };
+
+    let symmetric = matches!(*next_gate, "cz" | "swap" | "rxx" | "ryy" | "rzz");
+    let same_qubits = prev_qubits == next_qubits
+        || (symmetric && prev_qubits.iter().rev().eq(next_qubits.iter()));
+    if !same_qubits {
+        return None;
+    }
+
+    if inverse(prev_gate) == Some(*next_gate) {
+        Some(Combined::Identity)
+    } else if *prev_gate == *next_gate
+        && matches!(*next_gate, "p" | "rx" | "ry" | "rz" | "rxx" | "ryy" | "rzz")
+    {
+        prev_params[0] += next_params[0];
+        let turns = prev_params[0] / TAU;
+        if (turns - turns.round()).abs() * TAU < ANGLE_TOLERANCE {
+            Some(Combined::Identity)
+        } else {
+            Some(Combined::Fused)
+        }
+    } else {
+        None
+    }
+}
+
+/// The gate that undoes the given gate, if it has no parameters.
+fn inverse(gate: &str) -> Option<&'static str> {
+    match gate {
+        "ccx" => Some("ccx"),
+        "cx" => Some("cx"),
+        "cy" => Some("cy"),
+        "cz" => Some("cz"),
+        "h" => Some("h"),
+        "s" => Some("sdg"),
+        "sdg" => Some("s"),
+        "swap" => Some("swap"),
+        "t" => Some("tdg"),
+        "tdg" => Some("t"),
+        "x" => Some("x"),
+        "y" => Some("y"),
+        "z" => Some("z"),
+        _ => None,
+    }
+}
+
+/// Adds the qubits the instruction acts on, including those in branches.
+fn instr_qubits(instr: &Instr, qubits: &mut Vec<usize>) {
+    match instr {
+        Instr::Gate(_, _, gate_qubits) => {
+            for &q in gate_qubits {
+                if !qubits.contains(&q) {
+                    qubits.push(q);
+                }
+            }
+        }
+        &Instr::Measure(q, _) | &Instr::Reset(q) => {
+            if !qubits.contains(&q) {
+                qubits.push(q);
+            }
+        }
+        Instr::If(_, _, then, otherwise) => {
+            for instr in then.iter().chain(otherwise) {
+                instr_qubits(instr, qubits);
+            }
+        }
+    }
+}
+
+/// Removes the instructions that cannot affect a measurement, walking backwards with the set of
+/// qubits whose state can still affect one.
+fn remove_unmeasured(instrs: &mut Vec<Instr>, live: &mut FxHashSet<usize>) {
+    let mut kept = Vec::new();
+    for mut instr in instrs.drain(..).rev() {
+        match &mut instr {
+            Instr::Gate(_, _, qubits) => {
+                if !qubits.iter().any(|q| live.contains(q)) {
+                    continue;
+                }
+                live.extend(qubits.iter().copied());
+            }
+            &mut Instr::Measure(q, _) => {
+                live.insert(q);
+            }
+            // The state of the qubit before a reset cannot be measured after it.
+            &mut Instr::Reset(q) => {
+                if !live.remove(&q) {
+                    continue;
+                }
+            }
+            Instr::If(_, _, then, otherwise) => {
+                let mut otherwise_live = live.clone();
+                remove_unmeasured(then, live);
+                remove_unmeasured(otherwise, &mut otherwise_live);
+                live.extend(otherwise_live);
+                if then.is_empty() && otherwise.is_empty() {
+                    continue;
+                }
+            }
+        }
+        kept.push(instr);
+    }
+    kept.reverse();
+    *instrs = kept;
+}
@@ -0,0 +1,148 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use crate::qasm::generate_qasm;
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+
+fn check_optimized(program: &str, expect: &Expect) {
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::all());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let std = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let package = store.insert(unit);
+    let qasm = generate_qasm(&store, package, None, true).expect("OpenQASM should be generated");
+    expect.assert_eq(&qasm);
+}
+
+#[test]
+fn inverse_gates_cancel_until_none_are_adjacent() {
+    check_optimized(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    H(q0);
+                    CNOT(q0, q1);
+                    X(q1);
+                    X(q1);
+                    CNOT(q0, q1);
+                    S(q0);
+                    Adjoint S(q0);
+                    [M(q0), M(q1)]
+                }
+            }
+        "},
+        &expect![[r#"
+            OPENQASM 3.0;
+            include "stdgates.inc";
+
+            qubit[2] q;
+            bit[2] c;
+            output bit[2] result;
+
+            c[0] = measure q[0];
+            c[1] = measure q[1];
+            result[0] = c[0];
+            result[1] = c[1];
+        "#]],
+    );
+}
+
+#[test]
+fn rotations_about_same_axis_fuse() {
+    check_optimized(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    Rx(0.25, q);
+                    Rx(0.5, q);
+                    Rz(1.0, q);
+                    Rz(-1.0, q);
+                    Ry(1.0, q);
+                    M(q)
+                }
+            }
+        "},
+        &expect![[r#"
+            OPENQASM 3.0;
+            include "stdgates.inc";
+
+            qubit[1] q;
+            bit[1] c;
+            output bit[1] result;
+
+            rx(0.75) q[0];
+            ry(1.0) q[0];
+            c[0] = measure q[0];
+            result[0] = c[0];
+        "#]],
+    );
+}
+
+#[test]
+fn gates_that_cannot_affect_a_measurement_are_removed() {
+    check_optimized(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use (q0, q1, q2) = (Qubit(), Qubit(), Qubit());
+                    H(q0);
+                    CNOT(q0, q1);
+                    X(q0);
+                    T(q2);
+                    H(q2);
+                    M(q1)
+                }
+            }
+        "},
+        &expect![[r#"
+            OPENQASM 3.0;
+            include "stdgates.inc";
+
+            qubit[3] q;
+            bit[1] c;
+            output bit[1] result;
+
+            h q[0];
+            cx q[0], q[1];
+            c[0] = measure q[1];
+            result[0] = c[0];
+        "#]],
+    );
+}
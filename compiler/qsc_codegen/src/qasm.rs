@@ -0,0 +1,683 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Generation of OpenQASM 3 from the structure of a program, without running it.
+//!
+//! The generator walks the entry expression and inlines each call it reaches, so the program must
+//! already be in a form where every classical value is known: loops unrolled and classical
+//! computations resolved, as partial evaluation and the unrolling pass leave them. Each qubit is
+//! an element of a single qubit register and each measurement writes the next element of a
+//! single bit register. The only value that can be unknown is a measurement result, and it can
+//! only be compared with a result literal to decide a branch. Intrinsics that are not in the
+//! standard gate library get a gate definition in terms of the standard gates.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    gate_set::{self, GateSet},
+    peephole,
+    qir_base::lower_store,
+    walk::{self, Walk, Walker},
+};
+use miette::Diagnostic;
+use qsc_data_structures::{index_map::IndexMap, span::Span};
+use qsc_eval::{
+    binop_value,
+    debug::map_hir_package_to_fir,
+    partial_eval, unop_value,
+    val::{self, Qubit, Value},
+    PackageSpan,
+};
+use qsc_fir::fir::{
+    BinOp, BlockId, CallableDecl, CallableKind, ExprId, LocalVarId, PackageId, PackageLookup,
+    PackageStore, StoreItemId, UnOp,
+};
+use qsc_frontend::compile;
+use qsc_hir::hir;
+use std::{fmt::Write, mem, rc::Rc};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("cannot generate OpenQASM for a loop")]
+    #[diagnostic(help("loops should be unrolled before generating OpenQASM"))]
+    #[diagnostic(code("Qsc.Codegen.Qasm.Loop"))]
+    Loop(#[label] PackageSpan),
+
+    #[error("cannot generate OpenQASM for a value that depends on a measurement result")]
+    #[diagnostic(help(
+        "a measurement result can only be compared with `Zero` or `One` to decide a branch"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.Qasm.MeasurementValue"))]
+    MeasurementValue(#[label] PackageSpan),
+
+    #[error("cannot {0} in a branch on a measurement result")]
+    #[diagnostic(help(
+        "a branch on a measurement result can only apply operations, and not assign variables or exit early"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.Qasm.EffectInBranch"))]
+    EffectInBranch(&'static str, #[label] PackageSpan),
+
+    #[error("cannot generate OpenQASM for a call to the intrinsic function `{0}`")]
+    #[diagnostic(help(
+        "calls to intrinsic functions should be classically resolved before generating OpenQASM"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.Qasm.IntrinsicFunction"))]
+    IntrinsicFunction(String, #[label] PackageSpan),
+
+    #[error("the intrinsic operation `{0}` has no OpenQASM gate")]
+    #[diagnostic(code("Qsc.Codegen.Qasm.UnsupportedIntrinsic"))]
+    UnsupportedIntrinsic(String, #[label] PackageSpan),
+
+    #[error("cannot write a value of type `{0}` as OpenQASM output")]
+    #[diagnostic(help(
+        "the entry expression should return a result, or an array or tuple of results"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.Qasm.UnsupportedOutput"))]
+    UnsupportedOutput(String, #[label] PackageSpan),
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Eval(qsc_eval::Error),
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GateSet(gate_set::Error),
+}
+
+impl From<qsc_eval::Error> for Error {
+    fn from(error: qsc_eval::Error) -> Self {
+        Error::Eval(error)
+    }
+}
+
+/// Generates OpenQASM 3 for the entry expression of the given package, lowering every package in
+/// the store to FIR and partially evaluating the given package first. With a gate set, each gate
+/// that is not native to it is rewritten into its decomposition. With `optimize`, inverse gates
+/// cancel, rotations fuse, and gates that cannot affect a measurement are removed.
+///
+/// # Errors
+///
+/// Returns the first construct that cannot be written as OpenQASM.
+pub fn generate_qasm(
+    store: &compile::PackageStore,
+    package: hir::PackageId,
+    gate_set: Option<&GateSet>,
+    optimize: bool,
+) -> Result<String, Error> {
+    let mut fir_store = lower_store(store);
+    let package = map_hir_package_to_fir(package);
+    partial_eval::partially_evaluate(&mut fir_store, package, partial_eval::DEFAULT_STEP_BUDGET);
+    generate_qasm_from_fir(&fir_store, package, gate_set, optimize)
+}
+
+/// Generates OpenQASM 3 for the entry expression of the given package in a store that has
+/// already been lowered to FIR, mapping and optimizing it as for [`generate_qasm`].
+///
+/// # Errors
+///
+/// Returns the first construct that cannot be written as OpenQASM.
+///
+/// # Panics
+///
+/// Panics if the package has no entry expression.
+pub fn generate_qasm_from_fir(
+    store: &PackageStore,
+    package: PackageId,
+    gate_set: Option<&GateSet>,
+    optimize: bool,
+) -> Result<String, Error> {
+    let mut program = walk_program(store, package)?;
+    if let Some(gate_set) = gate_set {
+        gate_set.map(&mut program.instrs).map_err(Error::GateSet)?;
+    }
+    if optimize {
+        peephole::optimize(&mut program.instrs);
+    }
+    let mut qasm = "OPENQASM 3.0;\ninclude \"stdgates.inc\";\n".to_string();
+    let mut gates = Vec::new();
+    defined_gates(&program.instrs, &mut gates);
+    if !gates.is_empty() {
+        qasm.push('\n');
+        for gate in gates {
+            writeln!(qasm, "{}", gate_definition(gate)).expect("writing to string should succeed");
+        }
+    }
+    qasm.push('\n');
+    if program.qubits > 0 {
+        writeln!(qasm, "qubit[{}] q;", program.qubits).expect("writing to string should succeed");
+    }
+    if program.bits > 0 {
+        writeln!(qasm, "bit[{}] c;", program.bits).expect("writing to string should succeed");
+    }
+    if !program.outputs.is_empty() {
+        writeln!(qasm, "output bit[{}] result;", program.outputs.len())
+            .expect("writing to string should succeed");
+    }
+    qasm.push('\n');
+    write_instrs(&mut qasm, &program.instrs, 0);
+    for (i, output) in program.outputs.iter().enumerate() {
+        match output {
+            Output::Bit(bit) => writeln!(qasm, "result[{i}] = c[{bit}];"),
+            &Output::Lit(value) => writeln!(qasm, "result[{i}] = {};", u8::from(value)),
+        }
+        .expect("writing to string should succeed");
+    }
+    Ok(qasm)
+}
+
+/// A program as the instructions it applies, found by walking its structure without running it.
+pub(crate) struct Program {
+    pub(crate) instrs: Vec<Instr>,
+    pub(crate) qubits: usize,
+    pub(crate) bits: usize,
+    /// The name of the variable in the entry package that each qubit was first bound to.
+    pub(crate) labels: Vec<Option<Rc<str>>>,
+    /// The bits that the entry expression returns.
+    pub(crate) outputs: Vec<Output>,
+    /// The value that the entry expression returns, with each measurement result as its bit.
+    pub(crate) value: Value,
+}
+
+pub(crate) enum Instr {
+    /// A gate from [`GATES`], or from [`gate_set::GATES`] after mapping to a gate set, with its
+    /// angles and the qubits it acts on.
+    Gate(&'static str, Vec<f64>, Vec<usize>),
+    /// A measurement of the qubit into the bit.
+    Measure(usize, usize),
+    Reset(usize),
+    /// The instructions to apply when the bit has the value, and the ones to apply otherwise.
+    If(usize, bool, Vec<Instr>, Vec<Instr>),
+}
+
+pub(crate) enum Output {
+    Bit(usize),
+    Lit(bool),
+}
+
+/// Walks the entry expression of the given package in a store that has already been lowered to
+/// FIR, and returns the instructions it applies.
+///
+/// # Errors
+///
+/// Returns the first construct that cannot be resolved without running the program.
+///
+/// # Panics
+///
+/// Panics if the package has no entry expression.
+pub(crate) fn walk_program(store: &PackageStore, package: PackageId) -> Result<Program, Error> {
+    let entry = store.get(package).entry.expect("package should have entry");
+    let mut gen = Generator::new(store, package);
+    let val = match gen.expr(entry) {
+        Ok(val) | Err(Exit::Return(val)) => val,
+        Err(Exit::Error(error)) => return Err(error),
+    };
+    let span = store.get(package).get_expr(entry).span;
+    let value = gen.known_value(val, span)?;
+    let mut outputs = Vec::new();
+    gen.output(&value, &mut outputs, span)?;
+    Ok(Program {
+        instrs: gen.instrs,
+        qubits: gen.labels.len(),
+        bits: gen.next_bit,
+        labels: gen.labels,
+        outputs,
+        value,
+    })
+}
+
+/// Adds the gates that the instructions use and that the program defines itself, in the order
+/// they are first used.
+fn defined_gates(instrs: &[Instr], gates: &mut Vec<&'static str>) {
+    for instr in instrs {
+        match instr {
+            Instr::Gate(gate, ..) => {
+                let is_defined = GATES
+                    .iter()
+                    .any(|&(_, name, is_defined)| name == *gate && is_defined);
+                if is_defined && !gates.contains(gate) {
+                    gates.push(gate);
+                }
+            }
+            Instr::If(_, _, then, otherwise) => {
+                defined_gates(then, gates);
+                defined_gates(otherwise, gates);
+            }
+            Instr::Measure(..) | Instr::Reset(_) => {}
+        }
+    }
+}
+
+fn write_instrs(qasm: &mut String, instrs: &[Instr], depth: usize) {
+    let indent = "    ".repeat(depth);
+    for instr in instrs {
+        match instr {
+            Instr::Gate(gate, params, qubits) => {
+                let params = if params.is_empty() {
+                    String::new()
+                } else {
+                    let params: Vec<_> = params.iter().map(|&param| angle(param)).collect();
+                    format!("({})", params.join(", "))
+                };
+                let qubits: Vec<_> = qubits.iter().map(|q| format!("q[{q}]")).collect();
+                writeln!(qasm, "{indent}{gate}{params} {};", qubits.join(", "))
+            }
+            Instr::Measure(qubit, bit) => writeln!(qasm, "{indent}c[{bit}] = measure q[{qubit}];"),
+            Instr::Reset(qubit) => writeln!(qasm, "{indent}reset q[{qubit}];"),
+            Instr::If(bit, value, then, otherwise) => {
+                writeln!(qasm, "{indent}if (c[{bit}] == {}) {{", u8::from(*value))
+                    .expect("writing to string should succeed");
+                write_instrs(qasm, then, depth + 1);
+                if !otherwise.is_empty() {
+                    writeln!(qasm, "{indent}}} else {{").expect("writing to string should succeed");
+                    write_instrs(qasm, otherwise, depth + 1);
+                }
+                writeln!(qasm, "{indent}}}")
+            }
+        }
+        .expect("writing to string should succeed");
+    }
+}
+
+/// The OpenQASM gate for each intrinsic operation, and whether it is defined by the generated
+/// program rather than the standard gate library.
+const GATES: &[(&str, &str, bool)] = &[
+    ("__quantum__qis__ccx__body", "ccx", false),
+    ("__quantum__qis__cx__body", "cx", false),
+    ("__quantum__qis__cy__body", "cy", false),
+    ("__quantum__qis__cz__body", "cz", false),
+    ("__quantum__qis__rx__body", "rx", false),
+    ("__quantum__qis__rxx__body", "rxx", true),
+    ("__quantum__qis__ry__body", "ry", false),
+    ("__quantum__qis__ryy__body", "ryy", true),
+    ("__quantum__qis__rz__body", "rz", false),
+    ("__quantum__qis__rzz__body", "rzz", true),
+    ("__quantum__qis__h__body", "h", false),
+    ("__quantum__qis__s__body", "s", false),
+    ("__quantum__qis__s__adj", "sdg", false),
+    ("__quantum__qis__t__body", "t", false),
+    ("__quantum__qis__t__adj", "tdg", false),
+    ("__quantum__qis__x__body", "x", false),
+    ("__quantum__qis__y__body", "y", false),
+    ("__quantum__qis__z__body", "z", false),
+    ("__quantum__qis__swap__body", "swap", false),
+];
+
+/// The definitions of the gates that are not in the standard gate library.
+fn gate_definition(gate: &str) -> &'static str {
+    match gate {
+        "rxx" => "gate rxx(theta) a, b { h a; h b; cx a, b; rz(theta) b; cx a, b; h a; h b; }",
+        "ryy" => "gate ryy(theta) a, b { rx(pi / 2) a; rx(pi / 2) b; cx a, b; rz(theta) b; cx a, b; rx(-pi / 2) a; rx(-pi / 2) b; }",
+        "rzz" => "gate rzz(theta) a, b { cx a, b; rz(theta) b; cx a, b; }",
+        _ => panic!("gate `{gate}` should be in the standard gate library"),
+    }
+}
+
+/// A value while generating.
+#[derive(Clone, Debug, PartialEq)]
+enum Val {
+    /// A value that is known while generating. A measurement result is known as the index of the
+    /// bit it is written to, but the value of that bit is not.
+    Value(Value),
+    /// Whether the bit with the given index has the given value.
+    Cond(usize, bool),
+}
+
+impl Val {
+    fn unit() -> Self {
+        Val::Value(Value::unit())
+    }
+}
+
+impl From<Value> for Val {
+    fn from(value: Value) -> Self {
+        Val::Value(value)
+    }
+}
+
+type Exit = walk::Exit<Val, Error>;
+
+struct Generator<'a> {
+    walk: Walk<'a, Val>,
+    /// The package of the entry expression, whose variable names label the qubits bound to them.
+    entry_package: PackageId,
+    /// The label of each qubit allocated so far.
+    labels: Vec<Option<Rc<str>>>,
+    next_bit: usize,
+    /// The instructions of the innermost branch being generated.
+    instrs: Vec<Instr>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(store: &'a PackageStore, package: PackageId) -> Self {
+        Self {
+            walk: Walk::new(store, package),
+            entry_package: package,
+            labels: Vec::new(),
+            next_bit: 0,
+            instrs: Vec::new(),
+        }
+    }
+
+    fn allocate(&mut self) -> Value {
+        self.labels.push(None);
+        Value::Qubit(Qubit(self.labels.len() - 1))
+    }
+
+    /// Labels each unlabeled qubit in the value with the name of the variable it is bound to, or
+    /// with the name and its index if it is in an array.
+    fn label(&mut self, name: &Rc<str>, value: &Value) {
+        match value {
+            &Value::Qubit(Qubit(q)) => {
+                self.labels[q].get_or_insert_with(|| name.clone());
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if let &Value::Qubit(Qubit(q)) = item {
+                        self.labels[q].get_or_insert_with(|| format!("{name}[{i}]").into());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Generates a branch on the value of a bit. The branch can only apply operations, so the
+    /// local variables must be the same after each side of it.
+    fn branch(
+        &mut self,
+        bit: usize,
+        value: bool,
+        then: ExprId,
+        otherwise: Option<ExprId>,
+        span: Span,
+    ) -> Result<Val, Exit> {
+        let instrs = mem::take(&mut self.instrs);
+        self.walk.branches += 1;
+        let scopes = self.walk.scopes.clone();
+        let then_val = self.expr(then)?;
+        let then_instrs = mem::take(&mut self.instrs);
+        let mut changed = !same_locals(&self.walk.scopes, &scopes);
+        let mut else_val = Val::unit();
+        if let Some(otherwise) = otherwise {
+            self.walk.scopes.clone_from(&scopes);
+            else_val = self.expr(otherwise)?;
+            changed |= !same_locals(&self.walk.scopes, &scopes);
+        }
+        self.walk.branches -= 1;
+        let else_instrs = mem::replace(&mut self.instrs, instrs);
+        self.instrs
+            .push(Instr::If(bit, value, then_instrs, else_instrs));
+
+        if changed {
+            Err(Error::EffectInBranch("assign a variable", self.walk.span(span)).into())
+        } else if then_val == else_val {
+            Ok(then_val)
+        } else {
+            Err(Error::MeasurementValue(self.walk.span(span)).into())
+        }
+    }
+
+    /// Adds each bit of the output for the value.
+    fn output(&self, value: &Value, outputs: &mut Vec<Output>, span: Span) -> Result<(), Error> {
+        match value {
+            Value::Array(items) => {
+                for item in items.iter() {
+                    self.output(item, outputs, span)?;
+                }
+            }
+            Value::Tuple(items) => {
+                for item in items.iter() {
+                    self.output(item, outputs, span)?;
+                }
+            }
+            Value::Udt(_, value) => self.output(value, outputs, span)?,
+            &Value::Result(val::Result::Id(bit)) => outputs.push(Output::Bit(bit)),
+            &Value::Result(val::Result::Val(value)) => outputs.push(Output::Lit(value)),
+            value => {
+                return Err(Error::UnsupportedOutput(
+                    value.type_name().to_string(),
+                    self.walk.span(span),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Walker<'a> for Generator<'a> {
+    type Val = Val;
+    type Error = Error;
+
+    fn walk(&self) -> &Walk<'a, Val> {
+        &self.walk
+    }
+
+    fn walk_mut(&mut self) -> &mut Walk<'a, Val> {
+        &mut self.walk
+    }
+
+    fn known_value(&self, val: Val, span: Span) -> Result<Value, Error> {
+        match val {
+            Val::Value(value) => Ok(value),
+            Val::Cond(..) => Err(Error::MeasurementValue(self.walk.span(span))),
+        }
+    }
+
+    fn tuple(&self, items: Vec<Val>, span: Span) -> Result<Val, Error> {
+        let mut values = Vec::with_capacity(items.len());
+        for item in items {
+            values.push(self.known_value(item, span)?);
+        }
+        Ok(Val::Value(Value::Tuple(values.into())))
+    }
+
+    fn items(&self, val: Val) -> Vec<Val> {
+        let Val::Value(Value::Tuple(items)) = val else {
+            panic!("value should be a tuple");
+        };
+        items.iter().cloned().map(Val::Value).collect()
+    }
+
+    fn exit_in_branch(&self, exit: &'static str, span: PackageSpan) -> Error {
+        Error::EffectInBranch(exit, span)
+    }
+
+    fn bound(&mut self, name: &Rc<str>, val: &Val) {
+        if self.walk.package == self.entry_package && !name.starts_with('@') {
+            if let Val::Value(value) = val {
+                self.label(name, value);
+            }
+        }
+    }
+
+    fn binop(&mut self, op: BinOp, lhs: Val, rhs: ExprId, span: Span) -> Result<Val, Exit> {
+        match (op, &lhs) {
+            (BinOp::AndL, Val::Value(Value::Bool(false)))
+            | (BinOp::OrL, Val::Value(Value::Bool(true))) => return Ok(lhs),
+            (BinOp::AndL | BinOp::OrL, Val::Value(Value::Bool(_))) => return self.expr(rhs),
+            _ => {}
+        }
+
+        let rhs_span = self.walk.package().get_expr(rhs).span;
+        let rhs = self.expr(rhs)?;
+        let is_eq = match op {
+            BinOp::Eq => true,
+            BinOp::Neq => false,
+            _ => {
+                let lhs = self.known_value(lhs, span)?;
+                let rhs = self.known_value(rhs, span)?;
+                if is_measured(&lhs) || is_measured(&rhs) {
+                    return Err(Error::MeasurementValue(self.walk.span(span)).into());
+                }
+                return Ok(Val::Value(
+                    binop_value(op, lhs, rhs, self.walk.span(rhs_span)).map_err(Error::Eval)?,
+                ));
+            }
+        };
+
+        // A comparison of a measurement result with a literal is a condition on its bit.
+        match (lhs, rhs) {
+            (
+                Val::Value(Value::Result(val::Result::Id(bit))),
+                Val::Value(Value::Result(val::Result::Val(value))),
+            )
+            | (
+                Val::Value(Value::Result(val::Result::Val(value))),
+                Val::Value(Value::Result(val::Result::Id(bit))),
+            ) => Ok(Val::Cond(bit, value == is_eq)),
+            (Val::Cond(bit, value), Val::Value(Value::Bool(b)))
+            | (Val::Value(Value::Bool(b)), Val::Cond(bit, value)) => {
+                Ok(Val::Cond(bit, value == (b == is_eq)))
+            }
+            (Val::Value(lhs), Val::Value(rhs)) if !is_measured(&lhs) && !is_measured(&rhs) => {
+                Ok(Val::Value(
+                    binop_value(op, lhs, rhs, self.walk.span(rhs_span)).map_err(Error::Eval)?,
+                ))
+            }
+            _ => Err(Error::MeasurementValue(self.walk.span(span)).into()),
+        }
+    }
+
+    fn unop(&mut self, op: UnOp, val: Val, span: Span) -> Result<Val, Error> {
+        match (op, val) {
+            (UnOp::NotL, Val::Cond(bit, value)) => Ok(Val::Cond(bit, !value)),
+            (_, Val::Cond(..)) => Err(Error::MeasurementValue(self.walk.span(span))),
+            (op, Val::Value(value)) => Ok(Val::Value(unop_value(op, value))),
+        }
+    }
+
+    fn if_branch(
+        &mut self,
+        cond: Val,
+        then: ExprId,
+        otherwise: Option<ExprId>,
+        span: Span,
+    ) -> Result<Val, Exit> {
+        match cond {
+            Val::Cond(bit, value) => self.branch(bit, value, then, otherwise, span),
+            Val::Value(_) => panic!("condition should be a bool"),
+        }
+    }
+
+    fn while_loop(&mut self, _cond: ExprId, _body: BlockId, span: Span) -> Result<Val, Exit> {
+        Err(Error::Loop(self.walk.span(span)).into())
+    }
+
+    fn resolve_call(
+        &mut self,
+        id: StoreItemId,
+        decl: &'a CallableDecl,
+        _callee: &Value,
+        arg: &Val,
+        span: Span,
+    ) -> Result<Option<Val>, Error> {
+        let arg = self.known_value(arg.clone(), span)?;
+
+        // The qubit array callables of the core library allocate and release in a loop, so they
+        // are generated here instead.
+        if id.package == PackageId::CORE {
+            match decl.name.name.as_ref() {
+                "AllocateQubitArray" => {
+                    let size = arg.unwrap_int();
+                    let len = usize::try_from(size).map_err(|_| {
+                        Error::Eval(qsc_eval::Error::InvalidArrayLength(
+                            size,
+                            self.walk.span(span),
+                        ))
+                    })?;
+                    let qubits = (0..len).map(|_| self.allocate()).collect::<Vec<_>>();
+                    return Ok(Some(Val::Value(Value::Array(qubits.into()))));
+                }
+                "ReleaseQubitArray" => return Ok(Some(Val::unit())),
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    fn intrinsic(
+        &mut self,
+        decl: &'a CallableDecl,
+        _package: PackageId,
+        arg: Val,
+        span: Span,
+    ) -> Result<Val, Error> {
+        let arg = self.known_value(arg, span)?;
+        let name = decl.name.name.as_ref();
+        if decl.kind == CallableKind::Function {
+            return Err(Error::IntrinsicFunction(
+                name.to_string(),
+                self.walk.span(span),
+            ));
+        }
+
+        match name {
+            "__quantum__rt__qubit_allocate" => Ok(Val::Value(self.allocate())),
+            "__quantum__qis__m__body" | "__quantum__qis__mresetz__body" => {
+                let qubit = arg.unwrap_qubit().0;
+                let bit = self.next_bit;
+                self.next_bit += 1;
+                self.instrs.push(Instr::Measure(qubit, bit));
+                if name == "__quantum__qis__mresetz__body" {
+                    self.instrs.push(Instr::Reset(qubit));
+                }
+                Ok(Val::Value(Value::Result(val::Result::Id(bit))))
+            }
+            "__quantum__qis__reset__body" => {
+                let qubit = arg.unwrap_qubit().0;
+                self.instrs.push(Instr::Reset(qubit));
+                Ok(Val::unit())
+            }
+            _ => {
+                let Some(&(_, gate, _)) = GATES.iter().find(|(intrinsic, ..)| *intrinsic == name)
+                else {
+                    return Err(Error::UnsupportedIntrinsic(
+                        name.to_string(),
+                        self.walk.span(span),
+                    ));
+                };
+                let args = match arg {
+                    Value::Tuple(items) => items.to_vec(),
+                    arg => vec![arg],
+                };
+                let mut params = Vec::new();
+                let mut qubits = Vec::new();
+                for arg in args {
+                    match arg {
+                        Value::Double(d) => params.push(d),
+                        Value::Qubit(q) => qubits.push(q.0),
+                        _ => panic!("gate argument should be a double or a qubit"),
+                    }
+                }
+                self.instrs.push(Instr::Gate(gate, params, qubits));
+                Ok(Val::unit())
+            }
+        }
+    }
+}
+
+fn same_locals(a: &[IndexMap<LocalVarId, Val>], b: &[IndexMap<LocalVarId, Val>]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.iter().eq(b.iter()))
+}
+
+/// Whether the value contains a measurement result whose bit is not known.
+fn is_measured(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(is_measured),
+        Value::Closure(items, ..) | Value::Tuple(items) => items.iter().any(is_measured),
+        Value::Result(val::Result::Id(_)) => true,
+        Value::Udt(_, value) => is_measured(value),
+        _ => false,
+    }
+}
+
+/// Writes an angle so that a whole number still reads as a floating-point literal.
+fn angle(value: f64) -> String {
+    if (value.floor() - value.ceil()).abs() < f64::EPSILON {
+        format!("{value:.1}")
+    } else {
+        format!("{value}")
+    }
+}
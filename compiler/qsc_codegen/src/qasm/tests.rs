@@ -0,0 +1,169 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+
+use super::{generate_qasm, Error};
+
+fn generate(program: &str) -> Result<String, Error> {
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::all());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let std = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let package = store.insert(unit);
+    generate_qasm(&store, package, None, false)
+}
+
+fn check(program: &str, expect: &Expect) {
+    match generate(program) {
+        Ok(qasm) => expect.assert_eq(&qasm),
+        Err(error) => expect.assert_debug_eq(&error),
+    }
+}
+
+#[test]
+fn measured_bell_pair() {
+    check(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    CNOT(q0, q1);
+                    [M(q0), M(q1)]
+                }
+            }
+        "},
+        &expect![[r#"
+            OPENQASM 3.0;
+            include "stdgates.inc";
+
+            qubit[2] q;
+            bit[2] c;
+            output bit[2] result;
+
+            h q[0];
+            cx q[0], q[1];
+            c[0] = measure q[0];
+            c[1] = measure q[1];
+            result[0] = c[0];
+            result[1] = c[1];
+        "#]],
+    );
+}
+
+#[test]
+fn branch_on_measurement_and_defined_gate() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                @EntryPoint()
+                operation Main() : Result {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    Rxx(0.5, q0, q1);
+                    if M(q0) == One {
+                        X(q1);
+                    } else {
+                        Z(q1);
+                        S(q1);
+                    }
+                    MResetZ(q1)
+                }
+            }
+        "},
+        &expect![[r#"
+            OPENQASM 3.0;
+            include "stdgates.inc";
+
+            gate rxx(theta) a, b { h a; h b; cx a, b; rz(theta) b; cx a, b; h a; h b; }
+
+            qubit[2] q;
+            bit[2] c;
+            output bit[1] result;
+
+            rxx(0.5) q[0], q[1];
+            c[0] = measure q[0];
+            if (c[0] == 1) {
+                x q[1];
+            } else {
+                z q[1];
+                s q[1];
+            }
+            c[1] = measure q[1];
+            reset q[1];
+            result[0] = c[1];
+        "#]],
+    );
+}
+
+#[test]
+fn loop_is_error() {
+    let error = generate(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                for _ in 0..2 {
+                    H(q);
+                }
+            }
+        }
+    "})
+    .expect_err("OpenQASM should not be generated");
+    assert!(matches!(error, Error::Loop(_)), "{error:?}");
+}
+
+#[test]
+fn assignment_in_branch_on_measurement_is_error() {
+    let error = generate(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                mutable flips = 0;
+                if M(q) == One {
+                    set flips += 1;
+                }
+                flips
+            }
+        }
+    "})
+    .expect_err("OpenQASM should not be generated");
+    assert!(
+        matches!(error, Error::EffectInBranch("assign a variable", _)),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,956 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Generation of adaptive profile QIR, in which a program can branch on measurement results and
+//! compute with the values that depend on them.
+//!
+//! The entry expression is interpreted while generating. Every value that does not depend on a
+//! measurement result is computed then, so loops are unrolled and calls are inlined, and only the
+//! quantum operations and the classical computations on measurement results are written to the
+//! program. A value that depends on a measurement result is dynamic, and each use of one needs a
+//! capability of the target: a branch on a bool needs forward branching, and arithmetic on an
+//! integer or a double needs integer or floating-point computations. Each use that the target or
+//! the generator does not support is reported at the expression where it happens.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    qir_base::lower_store,
+    walk::{self, Walk, Walker},
+};
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use qsc_eval::{
+    backend::SparseSim,
+    binop_value,
+    debug::{map_fir_package_to_hir, map_hir_package_to_fir},
+    output::GenericReceiver,
+    unop_value,
+    val::{self, Qubit, Value},
+    Env, PackageSpan, State, StepAction, StepResult,
+};
+use qsc_fir::fir::{
+    BinOp, BlockId, CallableDecl, CallableImpl, CallableKind, ExprId, PackageId, PackageLookup,
+    PackageStore, StoreItemId, UnOp,
+};
+use qsc_frontend::compile::{self, RuntimeCapabilityFlags};
+use qsc_hir::hir;
+use rustc_hash::FxHashSet;
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    mem,
+    rc::Rc,
+};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("cannot use a dynamic bool value")]
+    #[diagnostic(help(
+        "using a bool value that depends on a measurement result requires the target to support forward branching"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.UseOfDynamicBool"))]
+    UseOfDynamicBool(#[label] PackageSpan),
+
+    #[error("cannot use a dynamic integer value")]
+    #[diagnostic(help(
+        "using an integer value that depends on a measurement result requires the target to support integer computations"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.UseOfDynamicInt"))]
+    UseOfDynamicInt(#[label] PackageSpan),
+
+    #[error("cannot use a dynamic double value")]
+    #[diagnostic(help(
+        "using a double value that depends on a measurement result requires the target to support floating-point computations"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.UseOfDynamicDouble"))]
+    UseOfDynamicDouble(#[label] PackageSpan),
+
+    #[error("cannot use a dynamic value of type `{0}` here")]
+    #[diagnostic(help(
+        "QIR can only be generated for values that depend on a measurement result when they are bools, integers, or doubles used as operands, branch conditions, or arguments to quantum operations"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.UnsupportedDynamicValue"))]
+    UnsupportedDynamicValue(String, #[label] PackageSpan),
+
+    #[error("cannot loop a dynamic number of times")]
+    #[diagnostic(help(
+        "QIR can only be generated for loops whose condition does not depend on a measurement result"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.DynamicLoop"))]
+    DynamicLoop(#[label] PackageSpan),
+
+    #[error("cannot {0} in a branch on a dynamic value")]
+    #[diagnostic(help(
+        "QIR can only be generated for branches on a value that depends on a measurement result when they run to their end"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.ExitInDynamicBranch"))]
+    ExitInDynamicBranch(&'static str, #[label] PackageSpan),
+
+    #[error("cannot call `{0}` with a value that depends on a measurement result")]
+    #[diagnostic(code("Qsc.Codegen.UnsupportedIntrinsic"))]
+    UnsupportedIntrinsic(String, #[label] PackageSpan),
+
+    #[error("cannot record a value of type `{0}` as output")]
+    #[diagnostic(help(
+        "the entry expression should return results, bools, integers, or doubles, or arrays or tuples of them"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.UnsupportedOutput"))]
+    UnsupportedOutput(String, #[label] PackageSpan),
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Eval(qsc_eval::Error),
+}
+
+impl From<qsc_eval::Error> for Error {
+    fn from(error: qsc_eval::Error) -> Self {
+        Error::Eval(error)
+    }
+}
+
+/// Generates adaptive profile QIR text for the entry expression of the given package, lowering
+/// every package in the store to FIR first.
+///
+/// # Errors
+///
+/// Returns the first construct that the capabilities of the target do not support, or the error
+/// that ends the program if it fails while generating.
+pub fn generate_qir(
+    store: &compile::PackageStore,
+    package: hir::PackageId,
+    capabilities: RuntimeCapabilityFlags,
+) -> Result<String, Error> {
+    let fir_store = lower_store(store);
+    generate_qir_from_fir(&fir_store, map_hir_package_to_fir(package), capabilities)
+}
+
+/// Generates adaptive profile QIR text for the entry expression of the given package in a store
+/// that has already been lowered to FIR.
+///
+/// # Errors
+///
+/// Returns the first construct that the capabilities of the target do not support, or the error
+/// that ends the program if it fails while generating.
+///
+/// # Panics
+///
+/// Panics if the package has no entry expression.
+pub fn generate_qir_from_fir(
+    store: &PackageStore,
+    package: PackageId,
+    capabilities: RuntimeCapabilityFlags,
+) -> Result<String, Error> {
+    let entry = store.get(package).entry.expect("package should have entry");
+    let mut gen = Generator::new(store, package, capabilities);
+    let val = match gen.expr(entry) {
+        Ok(val) | Err(Exit::Return(val)) => val,
+        Err(Exit::Error(error)) => return Err(error),
+    };
+    let span = store.get(package).get_expr(entry).span;
+    gen.write_output(&val, span)?;
+    Ok(gen.finish())
+}
+
+/// The intrinsics declared by every program, which are not declared again when they are called.
+const DECLARED_INTRINSICS: &[&str] = &[
+    "__quantum__qis__ccx__body",
+    "__quantum__qis__cx__body",
+    "__quantum__qis__cy__body",
+    "__quantum__qis__cz__body",
+    "__quantum__qis__rx__body",
+    "__quantum__qis__rxx__body",
+    "__quantum__qis__ry__body",
+    "__quantum__qis__ryy__body",
+    "__quantum__qis__rz__body",
+    "__quantum__qis__rzz__body",
+    "__quantum__qis__h__body",
+    "__quantum__qis__s__body",
+    "__quantum__qis__s__adj",
+    "__quantum__qis__t__body",
+    "__quantum__qis__t__adj",
+    "__quantum__qis__x__body",
+    "__quantum__qis__y__body",
+    "__quantum__qis__z__body",
+    "__quantum__qis__swap__body",
+    "__quantum__qis__reset__body",
+];
+
+/// The type of a value that can be held in a register.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scalar {
+    Bool,
+    Int,
+    Double,
+}
+
+impl Scalar {
+    fn llvm(self) -> &'static str {
+        match self {
+            Scalar::Bool => "i1",
+            Scalar::Int => "i64",
+            Scalar::Double => "double",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Scalar::Bool => "Bool",
+            Scalar::Int => "Int",
+            Scalar::Double => "Double",
+        }
+    }
+}
+
+/// A register, which holds the result of an instruction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Reg(usize);
+
+impl Display for Reg {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "%var_{}", self.0)
+    }
+}
+
+/// A value while generating, which is either known or depends on a measurement result.
+#[derive(Clone, Debug, PartialEq)]
+enum Val {
+    /// A value that is known while generating. A measurement result is known as the ID of the
+    /// result it is written to, but the value of that result is dynamic.
+    Static(Value),
+    /// A scalar that depends on a measurement result, held in a register.
+    Dynamic(Scalar, Reg),
+    /// A tuple with at least one dynamic item.
+    Tuple(Rc<[Val]>),
+}
+
+impl Val {
+    fn unit() -> Self {
+        Val::Static(Value::unit())
+    }
+
+    fn tuple(items: Vec<Val>) -> Self {
+        if items.iter().all(|item| matches!(item, Val::Static(_))) {
+            Val::Static(Value::Tuple(
+                items
+                    .into_iter()
+                    .map(|item| match item {
+                        Val::Static(value) => value,
+                        _ => unreachable!("item should be static"),
+                    })
+                    .collect(),
+            ))
+        } else {
+            Val::Tuple(items.into())
+        }
+    }
+
+    /// The items of the value, if it is a tuple.
+    fn items(&self) -> Option<Vec<Val>> {
+        match self {
+            Val::Static(Value::Tuple(items)) => {
+                Some(items.iter().cloned().map(Val::Static).collect())
+            }
+            Val::Tuple(items) => Some(items.to_vec()),
+            _ => None,
+        }
+    }
+
+    /// The scalar type and the operand text of the value, if it is a bool, an integer, or a
+    /// double.
+    fn operand(&self) -> Option<(Scalar, String)> {
+        match self {
+            &Val::Dynamic(ty, reg) => Some((ty, reg.to_string())),
+            Val::Static(Value::Bool(b)) => Some((Scalar::Bool, b.to_string())),
+            Val::Static(Value::Int(i)) => Some((Scalar::Int, i.to_string())),
+            Val::Static(Value::Double(d)) => Some((Scalar::Double, double(*d))),
+            _ => None,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Val::Static(value) => value.type_name(),
+            Val::Dynamic(ty, _) => ty.name(),
+            Val::Tuple(_) => "Tuple",
+        }
+    }
+}
+
+impl From<Value> for Val {
+    fn from(value: Value) -> Self {
+        Val::Static(value)
+    }
+}
+
+/// Writes a double so that a whole number still reads as a double.
+fn double(value: f64) -> String {
+    if (value.floor() - value.ceil()).abs() < f64::EPSILON {
+        format!("{value:.1}")
+    } else {
+        format!("{value}")
+    }
+}
+
+/// Whether the value can be computed by the evaluator, because it has no measurement result
+/// whose value is dynamic.
+fn is_classical(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().all(is_classical),
+        Value::Closure(items, ..) | Value::Tuple(items) => items.iter().all(is_classical),
+        Value::Result(val::Result::Id(_)) => false,
+        Value::Udt(_, value) => is_classical(value),
+        _ => true,
+    }
+}
+
+type Exit = walk::Exit<Val, Error>;
+
+struct Generator<'a> {
+    walk: Walk<'a, Val>,
+    capabilities: RuntimeCapabilityFlags,
+    next_qubit: usize,
+    next_result: usize,
+    next_reg: usize,
+    next_block: usize,
+    /// The basic block that instructions are written to.
+    block: usize,
+    body: String,
+    decls: String,
+    decl_names: FxHashSet<String>,
+    /// The simulator for calls to classical functions, which need no quantum state.
+    sim: SparseSim,
+}
+
+impl<'a> Generator<'a> {
+    fn new(
+        store: &'a PackageStore,
+        package: PackageId,
+        capabilities: RuntimeCapabilityFlags,
+    ) -> Self {
+        Self {
+            walk: Walk::new(store, package),
+            capabilities,
+            next_qubit: 0,
+            next_result: 0,
+            next_reg: 0,
+            next_block: 1,
+            block: 0,
+            body: String::new(),
+            decls: String::new(),
+            decl_names: FxHashSet::default(),
+            sim: SparseSim::new(),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        let mut flags = String::new();
+        let mut flag_refs = (0..4).map(|i| format!("!{i}")).collect::<Vec<_>>();
+        for (capability, flag) in [
+            (
+                RuntimeCapabilityFlags::IntegerComputations,
+                r#"!{i32 5, !"int_computations", !{!"i64"}}"#,
+            ),
+            (
+                RuntimeCapabilityFlags::FloatingPointComputations,
+                r#"!{i32 5, !"float_computations", !{!"double"}}"#,
+            ),
+        ] {
+            if self.capabilities.contains(capability) {
+                writeln!(flags, "!{} = {flag}", flag_refs.len())
+                    .expect("writing to string should succeed");
+                flag_refs.push(format!("!{}", flag_refs.len()));
+            }
+        }
+
+        let mut qir = include_str!("./qir_adaptive/prefix.ll").to_string();
+        qir.push_str(&self.body);
+        write!(
+            qir,
+            include_str!("./qir_adaptive/postfix.ll"),
+            mem::take(&mut self.decls),
+            self.next_qubit,
+            self.next_result,
+            flag_refs.join(", "),
+            flags
+        )
+        .expect("writing to string should succeed");
+        qir
+    }
+
+    fn next_reg(&mut self) -> Reg {
+        let reg = Reg(self.next_reg);
+        self.next_reg += 1;
+        reg
+    }
+
+    fn next_block(&mut self) -> usize {
+        let block = self.next_block;
+        self.next_block += 1;
+        block
+    }
+
+    fn start_block(&mut self, block: usize) {
+        self.block = block;
+        writeln!(self.body, "block_{block}:").expect("writing to string should succeed");
+    }
+
+    fn instr(&mut self, instr: fmt::Arguments) {
+        writeln!(self.body, "  {instr}").expect("writing to string should succeed");
+    }
+
+    /// Writes an instruction that produces a scalar, and returns the register that holds it.
+    fn assign_reg(&mut self, ty: Scalar, instr: fmt::Arguments) -> Val {
+        let reg = self.next_reg();
+        self.instr(format_args!("{reg} = {instr}"));
+        Val::Dynamic(ty, reg)
+    }
+
+    /// Checks that the target can compute with a dynamic value of the scalar type.
+    fn require(&self, ty: Scalar, span: Span) -> Result<(), Error> {
+        let (capability, error): (_, fn(PackageSpan) -> Error) = match ty {
+            Scalar::Bool => (
+                RuntimeCapabilityFlags::ForwardBranching,
+                Error::UseOfDynamicBool,
+            ),
+            Scalar::Int => (
+                RuntimeCapabilityFlags::IntegerComputations,
+                Error::UseOfDynamicInt,
+            ),
+            Scalar::Double => (
+                RuntimeCapabilityFlags::FloatingPointComputations,
+                Error::UseOfDynamicDouble,
+            ),
+        };
+        if self.capabilities.contains(capability) {
+            Ok(())
+        } else {
+            Err(error(self.walk.span(span)))
+        }
+    }
+
+    /// The scalar type and the operand text of the value, reading a measurement result into a
+    /// bool.
+    fn scalar(&mut self, val: Val, span: Span) -> Result<(Scalar, String), Error> {
+        let val = match val {
+            Val::Static(Value::Result(val::Result::Val(b))) => Val::Static(Value::Bool(b)),
+            Val::Static(Value::Result(val::Result::Id(id))) => {
+                self.require(Scalar::Bool, span)?;
+                self.assign_reg(
+                    Scalar::Bool,
+                    format_args!(
+                        "call i1 @__quantum__qis__read_result__body({})",
+                        ResultPtr(id)
+                    ),
+                )
+            }
+            val => val,
+        };
+        val.operand().ok_or_else(|| {
+            Error::UnsupportedDynamicValue(val.type_name().to_string(), self.walk.span(span))
+        })
+    }
+
+    fn dynamic_binop(&mut self, op: BinOp, lhs: Val, rhs: Val, span: Span) -> Result<Val, Error> {
+        let (ty, lhs) = self.scalar(lhs, span)?;
+        let (_, rhs) = self.scalar(rhs, span)?;
+        self.require(ty, span)?;
+
+        let (instr, result_ty) = match (op, ty) {
+            (BinOp::Eq, Scalar::Bool) => ("icmp eq", ty),
+            (BinOp::Neq, Scalar::Bool) => ("icmp ne", ty),
+            (BinOp::Add, Scalar::Int) => ("add", ty),
+            (BinOp::Sub, Scalar::Int) => ("sub", ty),
+            (BinOp::Mul, Scalar::Int) => ("mul", ty),
+            (BinOp::Div, Scalar::Int) => ("sdiv", ty),
+            (BinOp::Mod, Scalar::Int) => ("srem", ty),
+            (BinOp::AndB, Scalar::Int) => ("and", ty),
+            (BinOp::OrB, Scalar::Int) => ("or", ty),
+            (BinOp::XorB, Scalar::Int) => ("xor", ty),
+            (BinOp::Shl, Scalar::Int) => ("shl", ty),
+            (BinOp::Shr, Scalar::Int) => ("ashr", ty),
+            (BinOp::Eq, Scalar::Int) => ("icmp eq", Scalar::Bool),
+            (BinOp::Neq, Scalar::Int) => ("icmp ne", Scalar::Bool),
+            (BinOp::Gt, Scalar::Int) => ("icmp sgt", Scalar::Bool),
+            (BinOp::Gte, Scalar::Int) => ("icmp sge", Scalar::Bool),
+            (BinOp::Lt, Scalar::Int) => ("icmp slt", Scalar::Bool),
+            (BinOp::Lte, Scalar::Int) => ("icmp sle", Scalar::Bool),
+            (BinOp::Add, Scalar::Double) => ("fadd", ty),
+            (BinOp::Sub, Scalar::Double) => ("fsub", ty),
+            (BinOp::Mul, Scalar::Double) => ("fmul", ty),
+            (BinOp::Div, Scalar::Double) => ("fdiv", ty),
+            (BinOp::Eq, Scalar::Double) => ("fcmp oeq", Scalar::Bool),
+            (BinOp::Neq, Scalar::Double) => ("fcmp one", Scalar::Bool),
+            (BinOp::Gt, Scalar::Double) => ("fcmp ogt", Scalar::Bool),
+            (BinOp::Gte, Scalar::Double) => ("fcmp oge", Scalar::Bool),
+            (BinOp::Lt, Scalar::Double) => ("fcmp olt", Scalar::Bool),
+            (BinOp::Lte, Scalar::Double) => ("fcmp ole", Scalar::Bool),
+            _ => {
+                return Err(Error::UnsupportedDynamicValue(
+                    ty.name().to_string(),
+                    self.walk.span(span),
+                ))
+            }
+        };
+        if result_ty == Scalar::Bool {
+            self.require(result_ty, span)?;
+        }
+        Ok(self.assign_reg(
+            result_ty,
+            format_args!("{instr} {} {lhs}, {rhs}", ty.llvm()),
+        ))
+    }
+
+    /// Generates a branch on a dynamic bool, and merges the values that each side produces and
+    /// the local variables that each side assigns.
+    fn branch(
+        &mut self,
+        cond: Val,
+        span: Span,
+        then: impl FnOnce(&mut Self) -> Result<Val, Exit>,
+        otherwise: impl FnOnce(&mut Self) -> Result<Val, Exit>,
+    ) -> Result<Val, Exit> {
+        let (ty, cond) = self.scalar(cond, span)?;
+        assert!(ty == Scalar::Bool, "condition should be a bool");
+        self.require(ty, span)?;
+
+        let then_block = self.next_block();
+        let else_block = self.next_block();
+        let cont_block = self.next_block();
+        self.instr(format_args!(
+            "br i1 {cond}, label %block_{then_block}, label %block_{else_block}"
+        ));
+
+        self.walk.branches += 1;
+        let scopes = self.walk.scopes.clone();
+        self.start_block(then_block);
+        let then_val = then(self)?;
+        let then_end = self.block;
+        self.instr(format_args!("br label %block_{cont_block}"));
+        let then_scopes = mem::replace(&mut self.walk.scopes, scopes);
+
+        self.start_block(else_block);
+        let else_val = otherwise(self)?;
+        let else_end = self.block;
+        self.instr(format_args!("br label %block_{cont_block}"));
+        self.walk.branches -= 1;
+
+        self.start_block(cont_block);
+        let mut assigned = Vec::new();
+        for (depth, (then_scope, else_scope)) in
+            then_scopes.iter().zip(&self.walk.scopes).enumerate()
+        {
+            for (id, then_local) in then_scope.iter() {
+                let else_local = else_scope
+                    .get(id)
+                    .expect("local should be in both branches");
+                if then_local != else_local {
+                    assigned.push((depth, id, then_local.clone(), else_local.clone()));
+                }
+            }
+        }
+        for (depth, id, then_local, else_local) in assigned {
+            let merged = self.merge(then_local, then_end, &else_local, else_end, span)?;
+            self.walk.scopes[depth].insert(id, merged);
+        }
+        Ok(self.merge(then_val, then_end, &else_val, else_end, span)?)
+    }
+
+    /// Merges the values that the two sides of a branch produce into the value after the branch.
+    fn merge(
+        &mut self,
+        then_val: Val,
+        then_block: usize,
+        else_val: &Val,
+        else_block: usize,
+        span: Span,
+    ) -> Result<Val, Error> {
+        if then_val == *else_val {
+            return Ok(then_val);
+        }
+        if let (Some(then_items), Some(else_items)) = (then_val.items(), else_val.items()) {
+            if then_items.len() == else_items.len() {
+                let mut items = Vec::with_capacity(then_items.len());
+                for (then_item, else_item) in then_items.into_iter().zip(else_items) {
+                    items.push(self.merge(then_item, then_block, &else_item, else_block, span)?);
+                }
+                return Ok(Val::tuple(items));
+            }
+        }
+        match (then_val.operand(), else_val.operand()) {
+            (Some((ty, then_operand)), Some((else_ty, else_operand))) if ty == else_ty => {
+                self.require(ty, span)?;
+                Ok(self.assign_reg(
+                    ty,
+                    format_args!(
+                        "phi {} [{then_operand}, %block_{then_block}], [{else_operand}, %block_{else_block}]",
+                        ty.llvm()
+                    ),
+                ))
+            }
+            _ => Err(Error::UnsupportedDynamicValue(
+                then_val.type_name().to_string(),
+                self.walk.span(span),
+            )),
+        }
+    }
+
+    /// Computes a call to a classical function with the evaluator.
+    fn eval_call(&mut self, callee: Value, arg: Value, span: Span) -> Result<Value, Error> {
+        let mut state = State::new(self.walk.package, None);
+        state.set_progress_interval(None);
+        state.push_call(callee, arg, span);
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        match state.eval(
+            self.walk.store,
+            &mut Env::default(),
+            &mut self.sim,
+            &mut out,
+            &[],
+            StepAction::Continue,
+        ) {
+            Ok(StepResult::Return(value)) => Ok(value),
+            Ok(_) => unreachable!("evaluation should run to completion"),
+            Err((error, _)) => Err(Error::Eval(error)),
+        }
+    }
+
+    /// Writes the calls that record the value as the output of the program.
+    fn write_output(&mut self, val: &Val, span: Span) -> Result<(), Error> {
+        match val {
+            Val::Static(Value::Array(items)) => {
+                self.instr(format_args!(
+                    "call void @__quantum__rt__array_record_output(i64 {}, i8* null)",
+                    items.len()
+                ));
+                for item in items.iter() {
+                    self.write_output(&Val::Static(item.clone()), span)?;
+                }
+            }
+            Val::Static(Value::Tuple(_)) | Val::Tuple(_) => {
+                let items = val.items().expect("value should be a tuple");
+                self.instr(format_args!(
+                    "call void @__quantum__rt__tuple_record_output(i64 {}, i8* null)",
+                    items.len()
+                ));
+                for item in &items {
+                    self.write_output(item, span)?;
+                }
+            }
+            Val::Static(Value::Udt(_, value)) => {
+                self.write_output(&Val::Static((**value).clone()), span)?;
+            }
+            &Val::Static(Value::Result(val::Result::Id(result))) => {
+                self.instr(format_args!(
+                    "call void @__quantum__rt__result_record_output({}, i8* null)",
+                    ResultPtr(result)
+                ));
+            }
+            val => match val.operand() {
+                Some((ty, operand)) => {
+                    let kind = match ty {
+                        Scalar::Bool => "bool",
+                        Scalar::Int => "int",
+                        Scalar::Double => "double",
+                    };
+                    self.instr(format_args!(
+                        "call void @__quantum__rt__{kind}_record_output({} {operand}, i8* null)",
+                        ty.llvm()
+                    ));
+                }
+                None => {
+                    return Err(Error::UnsupportedOutput(
+                        val.type_name().to_string(),
+                        self.walk.span(span),
+                    ))
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Walker<'a> for Generator<'a> {
+    type Val = Val;
+    type Error = Error;
+
+    fn walk(&self) -> &Walk<'a, Val> {
+        &self.walk
+    }
+
+    fn walk_mut(&mut self) -> &mut Walk<'a, Val> {
+        &mut self.walk
+    }
+
+    fn known_value(&self, val: Val, span: Span) -> Result<Value, Error> {
+        match val {
+            Val::Static(value) => Ok(value),
+            val => Err(Error::UnsupportedDynamicValue(
+                val.type_name().to_string(),
+                self.walk.span(span),
+            )),
+        }
+    }
+
+    fn tuple(&self, items: Vec<Val>, _span: Span) -> Result<Val, Error> {
+        Ok(Val::tuple(items))
+    }
+
+    fn items(&self, val: Val) -> Vec<Val> {
+        val.items().expect("value should be a tuple")
+    }
+
+    fn exit_in_branch(&self, exit: &'static str, span: PackageSpan) -> Error {
+        Error::ExitInDynamicBranch(exit, span)
+    }
+
+    fn binop(&mut self, op: BinOp, lhs: Val, rhs: ExprId, span: Span) -> Result<Val, Exit> {
+        if let BinOp::AndL | BinOp::OrL = op {
+            let is_and = op == BinOp::AndL;
+            return match lhs {
+                Val::Static(Value::Bool(lhs)) if lhs == is_and => self.expr(rhs),
+                Val::Static(Value::Bool(lhs)) => Ok(Val::Static(Value::Bool(lhs))),
+                // The right-hand side is only evaluated when it decides the value, so it is
+                // generated in a branch of its own.
+                cond => self.branch(
+                    cond,
+                    span,
+                    |gen| {
+                        if is_and {
+                            gen.expr(rhs)
+                        } else {
+                            Ok(Val::Static(Value::Bool(true)))
+                        }
+                    },
+                    |gen| {
+                        if is_and {
+                            Ok(Val::Static(Value::Bool(false)))
+                        } else {
+                            gen.expr(rhs)
+                        }
+                    },
+                ),
+            };
+        }
+
+        let rhs_span = self.walk.package().get_expr(rhs).span;
+        let rhs = self.expr(rhs)?;
+        match (lhs, rhs) {
+            // Only a comparison reads the value of a measurement result.
+            (Val::Static(lhs), Val::Static(rhs))
+                if !matches!(op, BinOp::Eq | BinOp::Neq)
+                    || is_classical(&lhs) && is_classical(&rhs) =>
+            {
+                Ok(Val::Static(
+                    binop_value(op, lhs, rhs, self.walk.span(rhs_span)).map_err(Error::Eval)?,
+                ))
+            }
+            (lhs, rhs) => Ok(self.dynamic_binop(op, lhs, rhs, span)?),
+        }
+    }
+
+    fn unop(&mut self, op: UnOp, val: Val, span: Span) -> Result<Val, Error> {
+        match val {
+            Val::Static(value) => Ok(Val::Static(unop_value(op, value))),
+            Val::Dynamic(ty, reg) => match (op, ty) {
+                (UnOp::Pos, _) => Ok(val),
+                (UnOp::Neg, Scalar::Int) => {
+                    Ok(self.assign_reg(ty, format_args!("sub i64 0, {reg}")))
+                }
+                (UnOp::Neg, Scalar::Double) => {
+                    Ok(self.assign_reg(ty, format_args!("fneg double {reg}")))
+                }
+                (UnOp::NotB, Scalar::Int) => {
+                    Ok(self.assign_reg(ty, format_args!("xor i64 {reg}, -1")))
+                }
+                (UnOp::NotL, Scalar::Bool) => {
+                    Ok(self.assign_reg(ty, format_args!("xor i1 {reg}, true")))
+                }
+                _ => Err(Error::UnsupportedDynamicValue(
+                    ty.name().to_string(),
+                    self.walk.span(span),
+                )),
+            },
+            Val::Tuple(_) => Err(Error::UnsupportedDynamicValue(
+                val.type_name().to_string(),
+                self.walk.span(span),
+            )),
+        }
+    }
+
+    fn if_branch(
+        &mut self,
+        cond: Val,
+        then: ExprId,
+        otherwise: Option<ExprId>,
+        span: Span,
+    ) -> Result<Val, Exit> {
+        self.branch(
+            cond,
+            span,
+            |gen| gen.expr(then),
+            |gen| match otherwise {
+                Some(otherwise) => gen.expr(otherwise),
+                None => Ok(Val::unit()),
+            },
+        )
+    }
+
+    fn while_loop(&mut self, cond: ExprId, body: BlockId, span: Span) -> Result<Val, Exit> {
+        loop {
+            match self.expr(cond)? {
+                Val::Static(Value::Bool(true)) => {
+                    self.block(body)?;
+                }
+                Val::Static(Value::Bool(false)) => break,
+                _ => return Err(Error::DynamicLoop(self.walk.span(span)).into()),
+            }
+        }
+        Ok(Val::unit())
+    }
+
+    fn resolve_call(
+        &mut self,
+        _id: StoreItemId,
+        decl: &'a CallableDecl,
+        callee: &Value,
+        arg: &Val,
+        span: Span,
+    ) -> Result<Option<Val>, Error> {
+        // A function whose arguments are known is computed by the evaluator. Intrinsic functions
+        // are only computed there when they cannot inspect qubits, which have no state there.
+        if let Val::Static(arg) = arg {
+            let fixed_classical = match callee {
+                Value::Closure(fixed_args, ..) => fixed_args.iter().all(is_classical),
+                _ => true,
+            };
+            if decl.kind == CallableKind::Function
+                && fixed_classical
+                && is_classical(arg)
+                && !(decl.implementation == CallableImpl::Intrinsic && has_qubit(arg))
+            {
+                return Ok(Some(Val::Static(self.eval_call(
+                    callee.clone(),
+                    arg.clone(),
+                    span,
+                )?)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn intrinsic(
+        &mut self,
+        decl: &'a CallableDecl,
+        package: PackageId,
+        arg: Val,
+        span: Span,
+    ) -> Result<Val, Error> {
+        let name = decl.name.name.as_ref();
+        if decl.kind == CallableKind::Function {
+            return Err(Error::UnsupportedIntrinsic(
+                name.to_string(),
+                self.walk.span(span),
+            ));
+        }
+
+        match name {
+            "__quantum__rt__qubit_allocate" => {
+                let qubit = self.next_qubit;
+                self.next_qubit += 1;
+                Ok(Val::Static(Value::Qubit(Qubit(qubit))))
+            }
+            "__quantum__qis__m__body" | "__quantum__qis__mresetz__body" => {
+                let qubit = self.known_value(arg, span)?.unwrap_qubit();
+                let result = self.next_result;
+                self.next_result += 1;
+                let gate = if name == "__quantum__qis__m__body" {
+                    "mz"
+                } else {
+                    "mresetz"
+                };
+                self.instr(format_args!(
+                    "call void @__quantum__qis__{gate}__body({}, {}) #1",
+                    QubitPtr(qubit.0),
+                    ResultPtr(result)
+                ));
+                Ok(Val::Static(Value::Result(val::Result::Id(result))))
+            }
+            _ => {
+                let callee_span = PackageSpan {
+                    package: map_fir_package_to_hir(package),
+                    span: decl.span,
+                };
+                if decl.output != qsc_fir::ty::Ty::UNIT {
+                    return Err(Error::Eval(qsc_eval::Error::UnsupportedIntrinsicType(
+                        name.to_string(),
+                        callee_span,
+                    )));
+                }
+                let args = match &arg {
+                    Val::Static(Value::Tuple(_)) | Val::Tuple(_) => {
+                        arg.items().expect("value should be a tuple")
+                    }
+                    _ => vec![arg],
+                };
+                let mut operands = Vec::with_capacity(args.len());
+                for arg in args {
+                    let operand = match arg {
+                        Val::Static(Value::Qubit(q)) => ("%Qubit*", QubitPtr(q.0).to_string()),
+                        Val::Static(Value::Result(val::Result::Id(r))) => {
+                            ("%Result*", ResultPtr(r).to_string())
+                        }
+                        arg => match arg.operand() {
+                            Some((ty, operand)) => (ty.llvm(), format!("{} {operand}", ty.llvm())),
+                            None => {
+                                return Err(Error::Eval(qsc_eval::Error::IntrinsicFail(
+                                    name.to_string(),
+                                    format!("unsupported argument type: {}", arg.type_name()),
+                                    self.walk.span(span),
+                                )))
+                            }
+                        },
+                    };
+                    operands.push(operand);
+                }
+                if !DECLARED_INTRINSICS.contains(&name) && self.decl_names.insert(name.to_string())
+                {
+                    let types: Vec<_> = operands.iter().map(|(ty, _)| *ty).collect();
+                    writeln!(self.decls, "declare void @{name}({})", types.join(", "))
+                        .expect("writing to string should succeed");
+                }
+                let operands: Vec<_> = operands.into_iter().map(|(_, operand)| operand).collect();
+                self.instr(format_args!("call void @{name}({})", operands.join(", ")));
+                Ok(Val::unit())
+            }
+        }
+    }
+}
+
+fn has_qubit(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(has_qubit),
+        Value::Closure(items, ..) | Value::Tuple(items) => items.iter().any(has_qubit),
+        Value::Qubit(_) => true,
+        Value::Udt(_, value) => has_qubit(value),
+        _ => false,
+    }
+}
+
+struct QubitPtr(usize);
+
+impl Display for QubitPtr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "%Qubit* inttoptr (i64 {} to %Qubit*)", self.0)
+    }
+}
+
+struct ResultPtr(usize);
+
+impl Display for ResultPtr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "%Result* inttoptr (i64 {} to %Result*)", self.0)
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+
+use super::{generate_qir, Error};
+use crate::test_utils::entry_definition;
+
+fn generate(program: &str, capabilities: RuntimeCapabilityFlags) -> Result<String, Error> {
+    // The program is compiled without limits, since the generator checks the capabilities itself.
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::all());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let std = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let package = store.insert(unit);
+    generate_qir(&store, package, capabilities)
+}
+
+/// Checks the definition of the entry point, leaving out the declarations and attributes.
+fn check_entry(program: &str, capabilities: RuntimeCapabilityFlags, expect: &Expect) {
+    let qir = generate(program, capabilities).expect("QIR should be generated");
+    expect.assert_eq(entry_definition(&qir));
+}
+
+#[test]
+fn result_conditioned_gate() {
+    let qir = generate(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    let r = M(q0);
+                    if r == One {
+                        X(q1);
+                    }
+                    M(q1)
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::ForwardBranching,
+    )
+    .expect("QIR should be generated");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+          %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          %var_1 = icmp eq i1 %var_0, true
+          br i1 %var_1, label %block_1, label %block_2
+        block_1:
+          call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+          br label %block_3
+        block_2:
+          br label %block_3
+        block_3:
+          call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*)) #1
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__ccx__body(%Qubit*, %Qubit*, %Qubit*)
+        declare void @__quantum__qis__cx__body(%Qubit*, %Qubit*)
+        declare void @__quantum__qis__cy__body(%Qubit*, %Qubit*)
+        declare void @__quantum__qis__cz__body(%Qubit*, %Qubit*)
+        declare void @__quantum__qis__rx__body(double, %Qubit*)
+        declare void @__quantum__qis__rxx__body(double, %Qubit*, %Qubit*)
+        declare void @__quantum__qis__ry__body(double, %Qubit*)
+        declare void @__quantum__qis__ryy__body(double, %Qubit*, %Qubit*)
+        declare void @__quantum__qis__rz__body(double, %Qubit*)
+        declare void @__quantum__qis__rzz__body(double, %Qubit*, %Qubit*)
+        declare void @__quantum__qis__h__body(%Qubit*)
+        declare void @__quantum__qis__s__body(%Qubit*)
+        declare void @__quantum__qis__s__adj(%Qubit*)
+        declare void @__quantum__qis__t__body(%Qubit*)
+        declare void @__quantum__qis__t__adj(%Qubit*)
+        declare void @__quantum__qis__x__body(%Qubit*)
+        declare void @__quantum__qis__y__body(%Qubit*)
+        declare void @__quantum__qis__z__body(%Qubit*)
+        declare void @__quantum__qis__swap__body(%Qubit*, %Qubit*)
+        declare void @__quantum__qis__reset__body(%Qubit*)
+        declare void @__quantum__qis__mz__body(%Qubit*, %Result* writeonly) #1
+        declare void @__quantum__qis__mresetz__body(%Qubit*, %Result* writeonly) #1
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+        declare void @__quantum__rt__result_record_output(%Result*, i8*)
+        declare void @__quantum__rt__array_record_output(i64, i8*)
+        declare void @__quantum__rt__tuple_record_output(i64, i8*)
+        declare void @__quantum__rt__bool_record_output(i1, i8*)
+        declare void @__quantum__rt__int_record_output(i64, i8*)
+        declare void @__quantum__rt__double_record_output(double, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="2" "required_num_results"="2" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+    "#]]
+    .assert_eq(&qir);
+}
+
+#[test]
+fn integer_computed_from_results_is_merged_after_branches() {
+    check_entry(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Int {
+                    use qs = Qubit[2];
+                    mutable count = 0;
+                    for q in qs {
+                        if M(q) == One {
+                            set count += 1;
+                        }
+                    }
+                    count
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::IntegerComputations,
+        &expect![[r#"
+            define void @ENTRYPOINT__main() #0 {
+            block_0:
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+              %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+              %var_1 = icmp eq i1 %var_0, true
+              br i1 %var_1, label %block_1, label %block_2
+            block_1:
+              br label %block_3
+            block_2:
+              br label %block_3
+            block_3:
+              %var_2 = phi i64 [1, %block_1], [0, %block_2]
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*)) #1
+              %var_3 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 1 to %Result*))
+              %var_4 = icmp eq i1 %var_3, true
+              br i1 %var_4, label %block_4, label %block_5
+            block_4:
+              %var_5 = add i64 %var_2, 1
+              br label %block_6
+            block_5:
+              br label %block_6
+            block_6:
+              %var_6 = phi i64 [%var_5, %block_4], [%var_2, %block_5]
+              call void @__quantum__rt__int_record_output(i64 %var_6, i8* null)
+              ret void
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn tuple_of_result_and_dynamic_bool_is_recorded() {
+    check_entry(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : (Result, Bool) {
+                    use q = Qubit();
+                    H(q);
+                    let r = M(q);
+                    (r, r == One)
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::ForwardBranching,
+        &expect![[r#"
+            define void @ENTRYPOINT__main() #0 {
+            block_0:
+              call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+              call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+              %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+              %var_1 = icmp eq i1 %var_0, true
+              call void @__quantum__rt__tuple_record_output(i64 2, i8* null)
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+              call void @__quantum__rt__bool_record_output(i1 %var_1, i8* null)
+              ret void
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn branch_on_result_without_forward_branching_is_error() {
+    let error = generate(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    if M(q) == One {
+                        X(q);
+                    }
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::empty(),
+    )
+    .expect_err("QIR should not be generated");
+    assert!(matches!(error, Error::UseOfDynamicBool(_)), "{error:?}");
+}
+
+#[test]
+fn dynamic_integer_without_integer_computations_is_error() {
+    let error = generate(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Int {
+                    use q = Qubit();
+                    mutable count = 0;
+                    if M(q) == One {
+                        set count += 1;
+                    }
+                    count
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::ForwardBranching,
+    )
+    .expect_err("QIR should not be generated");
+    assert!(matches!(error, Error::UseOfDynamicInt(_)), "{error:?}");
+}
+
+#[test]
+fn loop_on_result_is_error() {
+    let error = generate(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    mutable r = Zero;
+                    while r == Zero {
+                        H(q);
+                        set r = M(q);
+                    }
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect_err("QIR should not be generated");
+    assert!(matches!(error, Error::DynamicLoop(_)), "{error:?}");
+}
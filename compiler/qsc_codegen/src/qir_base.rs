@@ -21,6 +21,9 @@ use qsc_hir::hir::{self};
 use rustc_hash::FxHashSet;
 use std::fmt::{Display, Write};
 
+/// Generates base profile QIR text for the entry expression of the given package, lowering every
+/// package in the store to FIR first.
+///
 /// # Errors
 ///
 /// This function will return an error if execution was unable to complete.
@@ -31,6 +34,12 @@ pub fn generate_qir(
     store: &PackageStore,
     package: hir::PackageId,
 ) -> std::result::Result<String, (Error, Vec<Frame>)> {
+    let fir_store = lower_store(store);
+    generate_qir_from_fir(&fir_store, map_hir_package_to_fir(package))
+}
+
+/// Lowers every package in the store to FIR, keeping the IDs of the packages.
+pub(crate) fn lower_store(store: &PackageStore) -> fir::PackageStore {
     let mut fir_lowerer = qsc_eval::lower::Lowerer::new();
     let mut fir_store = fir::PackageStore::new();
     for (id, unit) in store {
@@ -39,8 +48,27 @@ pub fn generate_qir(
             fir_lowerer.lower_package(&unit.package),
         );
     }
+    fir_store
+}
 
-    let package = map_hir_package_to_fir(package);
+/// Generates base profile QIR text for the entry expression of the given package in a store that
+/// has already been lowered to FIR, such as one that has been partially evaluated.
+///
+/// The entry expression is traced with a backend that records each intrinsic as a call into the
+/// quantum instruction set, so every classical value and branch must be known when it runs. The
+/// value it returns is written as output recording calls.
+///
+/// # Errors
+///
+/// This function will return an error if execution was unable to complete.
+/// # Panics
+///
+/// This function will panic if the package has no entry expression or in out-of-memory
+/// conditions.
+pub fn generate_qir_from_fir(
+    fir_store: &fir::PackageStore,
+    package: fir::PackageId,
+) -> std::result::Result<String, (Error, Vec<Frame>)> {
     let unit = fir_store.get(package);
     let entry_expr = unit.entry.expect("package should have entry");
 
@@ -49,9 +77,11 @@ pub fn generate_qir(
     let mut out = GenericReceiver::new(&mut stdout);
     let result = eval(
         package,
+        vec![package],
+        None,
         None,
         entry_expr.into(),
-        &fir_store,
+        fir_store,
         &mut Env::default(),
         &mut sim,
         &mut out,
@@ -150,6 +180,7 @@ impl BaseProfSim {
                     self.write_output_recording(val)?;
                 }
             }
+            Value::Udt(_, val) => self.write_output_recording(val)?,
             _ => panic!("unexpected value type: {val:?}"),
         }
         Ok(())
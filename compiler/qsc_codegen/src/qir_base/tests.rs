@@ -9,12 +9,15 @@ use std::sync::Arc;
 use expect_test::{expect, Expect};
 use indoc::indoc;
 use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::{debug::map_hir_package_to_fir, lower::Lowerer, partial_eval};
+use qsc_fir::fir;
 use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
 use qsc_passes::{run_core_passes, run_default_passes, PackageType};
 
-use crate::qir_base::generate_qir;
+use crate::qir_base::{generate_qir, generate_qir_from_fir};
 
-fn check(program: &str, expr: Option<&str>, expect: &Expect) {
+fn compile_program(program: &str, expr: Option<&str>) -> (PackageStore, PackageId) {
     let mut core = compile::core();
     assert!(run_core_passes(&mut core).is_empty());
     let mut store = PackageStore::new(core);
@@ -33,7 +36,7 @@ fn check(program: &str, expr: Option<&str>, expect: &Expect) {
 
     let mut unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         RuntimeCapabilityFlags::empty(),
         LanguageFeatures::default(),
@@ -47,7 +50,11 @@ fn check(program: &str, expr: Option<&str>, expect: &Expect) {
     )
     .is_empty());
     let package = store.insert(unit);
+    (store, package)
+}
 
+fn check(program: &str, expr: Option<&str>, expect: &Expect) {
+    let (store, package) = compile_program(program, expr);
     let qir = generate_qir(&store, package);
     match qir {
         Ok(qir) => expect.assert_eq(&qir),
@@ -1606,3 +1613,52 @@ fn custom_intrinsic_fail_on_non_unit_return() {
         "#]],
     );
 }
+
+#[test]
+fn partially_evaluated_fir_generates_same_qir() {
+    let (store, package) = compile_program(
+        indoc! {"
+        namespace Test {
+            function Angle(n : Int) : Double {
+                mutable angle = 0.0;
+                for _ in 1..n {
+                    set angle += 0.5;
+                }
+                angle
+            }
+
+            @EntryPoint()
+            operation Test() : Result {
+                use q = Qubit();
+                Rx(Angle(3), q);
+                if Angle(2) > 0.5 {
+                    X(q);
+                }
+                M(q)
+            }
+        }
+        "},
+        None,
+    );
+
+    let mut lowerer = Lowerer::new();
+    let mut fir_store = fir::PackageStore::new();
+    for (id, unit) in &store {
+        fir_store.insert(
+            map_hir_package_to_fir(id),
+            lowerer.lower_package(&unit.package),
+        );
+    }
+    let fir_package = map_hir_package_to_fir(package);
+    partial_eval::partially_evaluate(
+        &mut fir_store,
+        fir_package,
+        partial_eval::DEFAULT_STEP_BUDGET,
+    );
+
+    let qir = generate_qir_from_fir(&fir_store, fir_package).expect("QIR should generate");
+    let expected = generate_qir(&store, package).expect("QIR should generate");
+    assert_eq!(qir, expected);
+    assert!(qir.contains("call void @__quantum__qis__rx__body(double 1.5,"));
+    assert!(qir.contains("call void @__quantum__qis__x__body("));
+}
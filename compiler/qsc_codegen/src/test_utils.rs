@@ -0,0 +1,12 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+/// The definition of the entry point in generated QIR, leaving out the declarations and
+/// attributes.
+pub(crate) fn entry_definition(qir: &str) -> &str {
+    let start = qir
+        .find("define")
+        .expect("QIR should define the entry point");
+    let end = start + qir[start..].find("\n}\n").expect("entry point should end");
+    &qir[start..end + 3]
+}
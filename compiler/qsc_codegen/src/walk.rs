@@ -0,0 +1,516 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The walk over the structure of a program that the generators share. The walk starts at the
+//! entry expression and inlines each call it reaches, computing every value that is known while
+//! generating. A generator decides how to represent a value that depends on a measurement result,
+//! and it generates the expressions that can produce or consume one: operators, branches, loops,
+//! and calls to intrinsics.
+
+use qsc_data_structures::{functors::FunctorApp, index_map::IndexMap, span::Span};
+use qsc_eval::{
+    debug::map_fir_package_to_hir, field_value, index_value, lit_to_val, update_field_value,
+    update_index_value, val::Value, PackageSpan,
+};
+use qsc_fir::{
+    fir::{
+        BinOp, BlockId, CallableDecl, CallableImpl, CallableKind, ExprId, ExprKind, Global,
+        LocalVarId, Package, PackageId, PackageLookup, PackageStore, PackageStoreLookup, PatId,
+        PatKind, Res, StmtId, StmtKind, StoreItemId, StringComponent, UnOp,
+    },
+    ty::Ty,
+};
+use std::{fmt::Write, iter, mem, rc::Rc};
+
+/// How the generation of an expression ends early.
+pub(crate) enum Exit<V, E> {
+    /// The callable being generated returns the value.
+    Return(V),
+    /// Generation fails with the error.
+    Error(E),
+}
+
+impl<V, E> From<E> for Exit<V, E> {
+    fn from(error: E) -> Self {
+        Exit::Error(error)
+    }
+}
+
+/// The state of the walk, which locates the expression being generated.
+pub(crate) struct Walk<'a, V> {
+    pub(crate) store: &'a PackageStore,
+    /// The package of the callable being generated, which its IDs refer to.
+    pub(crate) package: PackageId,
+    /// The local variables of the callable being generated, by scope with the innermost last.
+    pub(crate) scopes: Vec<IndexMap<LocalVarId, V>>,
+    /// The number of branches on measurement results that enclose the expression being generated.
+    pub(crate) branches: usize,
+    /// The number of branches on measurement results that enclose the call to the callable being
+    /// generated, which it can only return from outside of any branch of its own.
+    pub(crate) frame_branches: usize,
+}
+
+impl<'a, V> Walk<'a, V> {
+    pub(crate) fn new(store: &'a PackageStore, package: PackageId) -> Self {
+        Self {
+            store,
+            package,
+            scopes: vec![IndexMap::new()],
+            branches: 0,
+            frame_branches: 0,
+        }
+    }
+
+    pub(crate) fn span(&self, span: Span) -> PackageSpan {
+        PackageSpan {
+            package: map_fir_package_to_hir(self.package),
+            span,
+        }
+    }
+
+    pub(crate) fn package(&self) -> &'a Package {
+        self.store.get(self.package)
+    }
+
+    pub(crate) fn local(&mut self, id: LocalVarId) -> &mut V {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(id))
+            .expect("local variable should be bound")
+    }
+}
+
+type WalkResult<'a, W> =
+    Result<<W as Walker<'a>>::Val, Exit<<W as Walker<'a>>::Val, <W as Walker<'a>>::Error>>;
+
+/// A generator that walks the structure of a program. The provided methods generate the
+/// expressions whose values are known, and the required ones generate the expressions that can
+/// depend on a measurement result.
+pub(crate) trait Walker<'a>: Sized {
+    /// A value while generating, which is either known or depends on a measurement result.
+    type Val: Clone + PartialEq + From<Value>;
+    type Error: From<qsc_eval::Error>;
+
+    fn walk(&self) -> &Walk<'a, Self::Val>;
+
+    fn walk_mut(&mut self) -> &mut Walk<'a, Self::Val>;
+
+    /// The known value, or an error if the value depends on a measurement result.
+    fn known_value(&self, val: Self::Val, span: Span) -> Result<Value, Self::Error>;
+
+    /// The tuple of the items.
+    fn tuple(&self, items: Vec<Self::Val>, span: Span) -> Result<Self::Val, Self::Error>;
+
+    /// The items of a tuple.
+    fn items(&self, val: Self::Val) -> Vec<Self::Val>;
+
+    /// The error for an expression that exits a branch on a measurement result early, like `fail`
+    /// or a `return` from the callable that contains the branch.
+    fn exit_in_branch(&self, exit: &'static str, span: PackageSpan) -> Self::Error;
+
+    /// Called when a variable is bound to the value.
+    fn bound(&mut self, _name: &Rc<str>, _val: &Self::Val) {}
+
+    fn binop(&mut self, op: BinOp, lhs: Self::Val, rhs: ExprId, span: Span)
+        -> WalkResult<'a, Self>;
+
+    fn unop(&mut self, op: UnOp, val: Self::Val, span: Span) -> Result<Self::Val, Self::Error>;
+
+    /// Generates an `if` expression whose condition is not a known bool.
+    fn if_branch(
+        &mut self,
+        cond: Self::Val,
+        then: ExprId,
+        otherwise: Option<ExprId>,
+        span: Span,
+    ) -> WalkResult<'a, Self>;
+
+    fn while_loop(&mut self, cond: ExprId, body: BlockId, span: Span) -> WalkResult<'a, Self>;
+
+    /// Generates a call without walking the callable, like a call to a classical function whose
+    /// value can be computed. Returns `None` if the callable should be walked.
+    fn resolve_call(
+        &mut self,
+        id: StoreItemId,
+        decl: &'a CallableDecl,
+        callee: &Value,
+        arg: &Self::Val,
+        span: Span,
+    ) -> Result<Option<Self::Val>, Self::Error>;
+
+    /// Generates a call to an intrinsic other than `Length`, a diagnostic function, or a qubit
+    /// release, which are the same for every target.
+    fn intrinsic(
+        &mut self,
+        decl: &'a CallableDecl,
+        package: PackageId,
+        arg: Self::Val,
+        span: Span,
+    ) -> Result<Self::Val, Self::Error>;
+
+    #[allow(clippy::too_many_lines)]
+    fn expr(&mut self, id: ExprId) -> WalkResult<'a, Self> {
+        let expr = self.walk().package().get_expr(id);
+        let span = expr.span;
+        match &expr.kind {
+            ExprKind::Array(items) => {
+                let mut values = Vec::with_capacity(items.len());
+                for &item in items {
+                    let val = self.expr(item)?;
+                    values.push(self.known_value(val, span)?);
+                }
+                Ok(Value::Array(values.into()).into())
+            }
+            &ExprKind::ArrayRepeat(item, size) => {
+                let item = self.expr(item)?;
+                let item = self.known_value(item, span)?;
+                let size = self.expr(size)?;
+                let size = self.known_value(size, span)?.unwrap_int();
+                let len = usize::try_from(size).map_err(|_| {
+                    Self::Error::from(qsc_eval::Error::InvalidArrayLength(
+                        size,
+                        self.walk().span(span),
+                    ))
+                })?;
+                Ok(Value::Array(vec![item; len].into()).into())
+            }
+            &ExprKind::Assign(lhs, rhs) => {
+                let val = self.expr(rhs)?;
+                self.assign(lhs, val);
+                Ok(Value::unit().into())
+            }
+            &ExprKind::AssignOp(op, lhs, rhs) => {
+                let current = self.expr(lhs)?;
+                let val = self.binop(op, current, rhs, span)?;
+                self.assign(lhs, val);
+                Ok(Value::unit().into())
+            }
+            &ExprKind::AssignField(record, ref field, replace) => {
+                let current = self.expr(record)?;
+                let current = self.known_value(current, span)?;
+                let val = self.expr(replace)?;
+                let val = self.known_value(val, span)?;
+                self.assign(record, update_field_value(current, field, val).into());
+                Ok(Value::unit().into())
+            }
+            &ExprKind::AssignIndex(array, index, replace) => {
+                let current = self.expr(array)?;
+                let val = self.update_index(current, index, replace, span)?;
+                self.assign(array, val);
+                Ok(Value::unit().into())
+            }
+            &ExprKind::BinOp(op, lhs, rhs) => {
+                let lhs = self.expr(lhs)?;
+                self.binop(op, lhs, rhs, span)
+            }
+            &ExprKind::Block(block) => self.block(block),
+            &ExprKind::Call(callee, arg) => {
+                let callee = self.expr(callee)?;
+                let callee = self.known_value(callee, span)?;
+                let arg = self.expr(arg)?;
+                self.call(&callee, arg, span)
+            }
+            ExprKind::Closure(args, callable) => {
+                let mut values = Vec::with_capacity(args.len());
+                for &arg in args {
+                    let val = self.walk_mut().local(arg).clone();
+                    values.push(self.known_value(val, span)?);
+                }
+                Ok(Value::Closure(
+                    values.into(),
+                    StoreItemId {
+                        package: self.walk().package,
+                        item: *callable,
+                    },
+                    FunctorApp::default(),
+                )
+                .into())
+            }
+            &ExprKind::Fail(message) => {
+                let message = self.expr(message)?;
+                let message = self.known_value(message, span)?.unwrap_string();
+                let span = self.walk().span(span);
+                if self.walk().branches > 0 {
+                    Err(self.exit_in_branch("fail", span).into())
+                } else {
+                    Err(
+                        Self::Error::from(qsc_eval::Error::UserFail(message.to_string(), span))
+                            .into(),
+                    )
+                }
+            }
+            &ExprKind::Field(record, ref field) => {
+                let record = self.expr(record)?;
+                let record = self.known_value(record, span)?;
+                Ok(field_value(record, field).into())
+            }
+            ExprKind::Hole => panic!("hole expr should be disallowed by passes"),
+            &ExprKind::If(cond, then, otherwise) => {
+                let cond = self.expr(cond)?;
+                if cond == Value::Bool(true).into() {
+                    self.expr(then)
+                } else if cond == Value::Bool(false).into() {
+                    match otherwise {
+                        Some(otherwise) => self.expr(otherwise),
+                        None => Ok(Value::unit().into()),
+                    }
+                } else {
+                    self.if_branch(cond, then, otherwise, span)
+                }
+            }
+            &ExprKind::Index(array, index) => {
+                let array = self.expr(array)?;
+                let array = self.known_value(array, span)?.unwrap_array();
+                let index = self.expr(index)?;
+                let index = self.known_value(index, span)?;
+                let value = index_value(&array, &index, self.walk().span(span))
+                    .map_err(Self::Error::from)?;
+                Ok(value.into())
+            }
+            ExprKind::Lit(lit) => Ok(lit_to_val(lit).into()),
+            &ExprKind::Range(start, step, end) => {
+                let mut bound = |expr: Option<ExprId>| -> Result<Option<i64>, Exit<_, _>> {
+                    match expr {
+                        Some(expr) => {
+                            let val = self.expr(expr)?;
+                            Ok(Some(self.known_value(val, span)?.unwrap_int()))
+                        }
+                        None => Ok(None),
+                    }
+                };
+                let start = bound(start)?;
+                let step = bound(step)?;
+                let end = bound(end)?;
+                Ok(Value::Range(start, step.unwrap_or(1), end).into())
+            }
+            &ExprKind::Return(val) => {
+                let val = self.expr(val)?;
+                let walk = self.walk();
+                if walk.branches > walk.frame_branches {
+                    Err(self.exit_in_branch("return", walk.span(span)).into())
+                } else {
+                    Err(Exit::Return(val))
+                }
+            }
+            ExprKind::String(components) => {
+                let mut string = String::new();
+                for component in components {
+                    match component {
+                        StringComponent::Expr(expr) => {
+                            let val = self.expr(*expr)?;
+                            let value = self.known_value(val, span)?;
+                            write!(string, "{value}").expect("writing to string should succeed");
+                        }
+                        StringComponent::Lit(lit) => string.push_str(lit),
+                    }
+                }
+                Ok(Value::String(string.into()).into())
+            }
+            &ExprKind::UpdateIndex(array, index, replace) => {
+                let array = self.expr(array)?;
+                self.update_index(array, index, replace, span)
+            }
+            ExprKind::Tuple(items) => {
+                let mut vals = Vec::with_capacity(items.len());
+                for &item in items {
+                    vals.push(self.expr(item)?);
+                }
+                Ok(self.tuple(vals, span)?)
+            }
+            &ExprKind::UnOp(op, operand) => {
+                let val = self.expr(operand)?;
+                Ok(self.unop(op, val, span)?)
+            }
+            &ExprKind::UpdateField(record, ref field, replace) => {
+                let record = self.expr(record)?;
+                let record = self.known_value(record, span)?;
+                let val = self.expr(replace)?;
+                let val = self.known_value(val, span)?;
+                Ok(update_field_value(record, field, val).into())
+            }
+            ExprKind::Var(res, _) => match res {
+                Res::Err => panic!("resolution error"),
+                Res::Item(item) => Ok(Value::Global(
+                    StoreItemId {
+                        package: item.package.unwrap_or(self.walk().package),
+                        item: item.item,
+                    },
+                    FunctorApp::default(),
+                )
+                .into()),
+                &Res::Local(id) => Ok(self.walk_mut().local(id).clone()),
+            },
+            &ExprKind::While(cond, body) => self.while_loop(cond, body, span),
+        }
+    }
+
+    fn block(&mut self, id: BlockId) -> WalkResult<'a, Self> {
+        let block = self.walk().package().get_block(id);
+        self.walk_mut().scopes.push(IndexMap::new());
+        let mut result = Ok(Value::unit().into());
+        for &stmt in &block.stmts {
+            result = self.stmt(stmt);
+            if result.is_err() {
+                break;
+            }
+        }
+        self.walk_mut().scopes.pop();
+        result
+    }
+
+    fn stmt(&mut self, id: StmtId) -> WalkResult<'a, Self> {
+        let package = self.walk().package();
+        match &package.get_stmt(id).kind {
+            &StmtKind::Expr(expr) => self.expr(expr),
+            &StmtKind::Semi(expr) => {
+                self.expr(expr)?;
+                Ok(Value::unit().into())
+            }
+            &StmtKind::Local(_, pat, expr) => {
+                let val = self.expr(expr)?;
+                self.bind(pat, val);
+                Ok(Value::unit().into())
+            }
+            StmtKind::Item(_) => Ok(Value::unit().into()),
+        }
+    }
+
+    fn bind(&mut self, pat: PatId, val: Self::Val) {
+        let package = self.walk().package();
+        match &package.get_pat(pat).kind {
+            PatKind::Bind(ident) => {
+                self.bound(&ident.name, &val);
+                self.walk_mut()
+                    .scopes
+                    .last_mut()
+                    .expect("binding should have a scope")
+                    .insert(ident.id, val);
+            }
+            PatKind::Discard => {}
+            PatKind::Tuple(pats) => {
+                for (&pat, item) in pats.iter().zip(self.items(val)) {
+                    self.bind(pat, item);
+                }
+            }
+        }
+    }
+
+    fn assign(&mut self, lhs: ExprId, val: Self::Val) {
+        let package = self.walk().package();
+        match &package.get_expr(lhs).kind {
+            ExprKind::Hole => {}
+            &ExprKind::Var(Res::Local(id), _) => *self.walk_mut().local(id) = val,
+            ExprKind::Tuple(exprs) => {
+                for (&expr, item) in exprs.iter().zip(self.items(val)) {
+                    self.assign(expr, item);
+                }
+            }
+            _ => panic!("unassignable pattern should be disallowed by compiler"),
+        }
+    }
+
+    fn update_index(
+        &mut self,
+        array: Self::Val,
+        index: ExprId,
+        replace: ExprId,
+        span: Span,
+    ) -> WalkResult<'a, Self> {
+        let array = self.known_value(array, span)?.unwrap_array();
+        let index = self.expr(index)?;
+        let index = self.known_value(index, span)?;
+        let val = self.expr(replace)?;
+        let val = self.known_value(val, span)?;
+        let value = update_index_value(&array, &index, val, self.walk().span(span))
+            .map_err(Self::Error::from)?;
+        Ok(value.into())
+    }
+
+    /// Generates a call by inlining the body of the callable, unless the generator resolves it.
+    fn call(&mut self, callee: &Value, arg: Self::Val, span: Span) -> WalkResult<'a, Self> {
+        let (id, functor, fixed_args) = match callee {
+            Value::Closure(fixed_args, id, functor) => (*id, *functor, Some(fixed_args.clone())),
+            &Value::Global(id, functor) => (id, functor, None),
+            _ => panic!("value is not callable"),
+        };
+
+        let store = self.walk().store;
+        let decl = match store.get_global(id) {
+            Some(Global::Callable(decl)) => decl,
+            Some(Global::Udt(udt)) => {
+                let arg = self.known_value(arg, span)?;
+                return Ok(Value::Udt(Rc::new(udt.clone()), Rc::new(arg)).into());
+            }
+            None => {
+                let span = self.walk().span(span);
+                return Err(Self::Error::from(qsc_eval::Error::UnboundName(span)).into());
+            }
+        };
+
+        if let Some(val) = self.resolve_call(id, decl, callee, &arg, span)? {
+            return Ok(val);
+        }
+
+        let spec = match &decl.implementation {
+            CallableImpl::Intrinsic => {
+                let is_function = decl.kind == CallableKind::Function;
+                return Ok(match decl.name.name.as_ref() {
+                    "Length" if is_function => {
+                        let len = self.known_value(arg, span)?.unwrap_array().len();
+                        Value::Int(len.try_into().expect("array length should fit in Int")).into()
+                    }
+                    // Diagnostics have no effect on the program that runs on the target.
+                    _ if is_function && decl.output == Ty::UNIT => Value::unit().into(),
+                    // Every qubit is allocated once, so a released qubit is never used again.
+                    "__quantum__rt__qubit_release" => Value::unit().into(),
+                    _ => self.intrinsic(decl, id.package, arg, span)?,
+                });
+            }
+            CallableImpl::Spec(spec) => spec,
+        };
+        let spec_decl = match (functor.adjoint, functor.controlled) {
+            (false, 0) => Some(&spec.body),
+            (true, 0) => spec.adj.as_ref(),
+            (false, _) => spec.ctl.as_ref(),
+            (true, _) => spec.ctl_adj.as_ref(),
+        }
+        .expect("missing specialization should be a compilation error");
+
+        let mut arg = arg;
+        let mut ctls = Vec::new();
+        if spec_decl.input.is_some() {
+            for _ in 0..functor.controlled {
+                let items = self.items(arg);
+                let [ctl, rest] = &items[..] else {
+                    panic!("tuple should be arity 2");
+                };
+                let ctl = self.known_value(ctl.clone(), span)?;
+                ctls.extend_from_slice(&ctl.unwrap_array());
+                arg = rest.clone();
+            }
+        }
+        if let Some(fixed_args) = fixed_args {
+            let items = fixed_args.iter().cloned().map(Into::into);
+            arg = self.tuple(items.chain(iter::once(arg)).collect(), span)?;
+        }
+
+        let walk = self.walk_mut();
+        let scopes = mem::replace(&mut walk.scopes, vec![IndexMap::new()]);
+        let package = mem::replace(&mut walk.package, id.package);
+        let frame_branches = mem::replace(&mut walk.frame_branches, walk.branches);
+        if let Some(spec_input) = spec_decl.input {
+            self.bind(spec_input, Value::Array(ctls.into()).into());
+        }
+        self.bind(decl.input, arg);
+        let result = match self.block(spec_decl.block) {
+            Ok(val) | Err(Exit::Return(val)) => Ok(val),
+            Err(Exit::Error(error)) => Err(Exit::Error(error)),
+        };
+        let walk = self.walk_mut();
+        walk.scopes = scopes;
+        walk.package = package;
+        walk.frame_branches = frame_branches;
+        result
+    }
+}
@@ -1,9 +1,11 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod cancellation;
 pub mod display;
 pub mod functors;
 pub mod index_map;
 pub mod language_features;
 pub mod line_column;
+pub mod lints;
 pub mod span;
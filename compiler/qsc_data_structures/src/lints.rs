@@ -0,0 +1,95 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use bitflags::bitflags;
+use serde::Deserialize;
+use std::{str::FromStr, sync::Arc};
+
+/// Optional warnings that are only reported when they are enabled.
+#[derive(Deserialize, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Copy)]
+pub struct Lints(u8);
+
+bitflags! {
+    impl Lints: u8 {
+        /// A local binding shadows a local or parameter in an enclosing scope.
+        const ShadowedLocal = 0b1;
+    }
+}
+
+impl Default for Lints {
+    fn default() -> Self {
+        Lints::empty()
+    }
+}
+
+impl<I> FromIterator<I> for Lints
+where
+    I: AsRef<str>,
+{
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        iter.into_iter().fold(Lints::empty(), |acc, x| {
+            acc | match x.as_ref() {
+                "shadowed-local" => Lints::ShadowedLocal,
+                _ => Lints::empty(),
+            }
+        })
+    }
+}
+
+/// How a warning is reported.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// The warning is not reported.
+    Allow,
+    /// The warning is reported as a warning.
+    Warn,
+    /// The warning is reported as an error.
+    Deny,
+}
+
+impl FromStr for Severity {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "warn" => Ok(Self::Warn),
+            "deny" => Ok(Self::Deny),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The severity of warnings by their code, like `Qsc.Resolve.UnusedOpen`. A warning whose code has
+/// no severity is reported as a warning.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WarningLevels(Vec<(Arc<str>, Severity)>);
+
+impl WarningLevels {
+    /// Sets the severity of the warning with the given code, replacing any severity it had.
+    pub fn set(&mut self, code: impl Into<Arc<str>>, severity: Severity) {
+        let code = code.into();
+        self.0.retain(|(other, _)| *other != code);
+        self.0.push((code, severity));
+    }
+
+    /// The severity of the warning with the given code.
+    #[must_use]
+    pub fn get(&self, code: &str) -> Severity {
+        self.0
+            .iter()
+            .find(|(other, _)| &**other == code)
+            .map_or(Severity::Warn, |&(_, severity)| severity)
+    }
+}
+
+impl<C: Into<Arc<str>>> FromIterator<(C, Severity)> for WarningLevels {
+    fn from_iter<T: IntoIterator<Item = (C, Severity)>>(iter: T) -> Self {
+        let mut levels = Self::default();
+        for (code, severity) in iter {
+            levels.set(code, severity);
+        }
+        levels
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Exports the items of a compiled package as JSON, so that tools written in other languages, like
+//! API diff checkers and documentation generators, can read them without linking the compiler.
+//!
+//! The export is an object with these fields:
+//!
+//! - `formatVersion`: the version of the format, [`FORMAT_VERSION`]. It changes whenever a field is
+//!   removed or changes meaning, but not when a field is added.
+//! - `items`: each callable and UDT declared directly in a namespace, in declaration order.
+//!
+//! Each item has these fields:
+//!
+//! - `kind`: `"function"`, `"operation"`, or `"udt"`.
+//! - `namespace` and `name`: the namespace the item is declared in, and its name.
+//! - `visibility`: `"public"` or `"internal"`.
+//! - `signature`: the declaration as Q# source, like `operation Foo(q : Qubit) : Unit is Adj`.
+//! - `typeParameters`: the names of the type parameters of a callable, like `'T`.
+//! - `parameters`: the parameters of a callable, or the fields of a UDT, each with a `name` that is
+//!   `null` if it has none and a `type` as Q# source.
+//! - `output`: the return type of a callable as Q# source, or `null` for a UDT.
+//! - `functors`: the functors a callable supports, `"Adj"`, `"Ctl"` or `"Adj + Ctl"`, or `null`.
+//! - `attributes`: each attribute with its `name`, and the `replacement` of a deprecated item if
+//!   it has one.
+//! - `doc`: the doc comments, as the `summary`, `description`, `output`, `inputs` and
+//!   `typeParameters` by name, `examples`, and the other `sections` by header.
+
+#[cfg(test)]
+mod tests;
+
+use crate::display::{hir_callable_signature, hir_udt_signature};
+use qsc_hir::{
+    doc::{Doc, Entry},
+    hir::{Attr, CallableKind, Item, ItemKind, Package, Pat, PatKind, Visibility},
+    ty::{FunctorSetValue, GenericParam, Ty, UdtDef, UdtDefKind},
+};
+use serde::Serialize;
+use std::rc::Rc;
+
+/// The version of the export format.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Api {
+    format_version: u32,
+    items: Vec<ApiItem>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiItem {
+    kind: &'static str,
+    namespace: Rc<str>,
+    name: Rc<str>,
+    visibility: &'static str,
+    signature: String,
+    type_parameters: Vec<Rc<str>>,
+    parameters: Vec<ApiParam>,
+    output: Option<String>,
+    functors: Option<String>,
+    attributes: Vec<ApiAttr>,
+    doc: ApiDoc,
+}
+
+#[derive(Serialize)]
+struct ApiParam {
+    name: Option<Rc<str>>,
+    #[serde(rename = "type")]
+    ty: String,
+}
+
+#[derive(Serialize)]
+struct ApiAttr {
+    name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replacement: Option<Rc<str>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiDoc {
+    summary: String,
+    description: String,
+    inputs: Vec<ApiEntry>,
+    output: String,
+    type_parameters: Vec<ApiEntry>,
+    examples: Vec<String>,
+    sections: Vec<ApiEntry>,
+}
+
+#[derive(Serialize)]
+struct ApiEntry {
+    name: String,
+    text: String,
+}
+
+/// Exports the callables and UDTs declared in the namespaces of the package as pretty-printed
+/// JSON in the format described in the [module documentation](self).
+///
+/// # Panics
+///
+/// Panics if an item's parent is not in the package.
+#[must_use]
+pub fn export_package(package: &Package) -> String {
+    let items = package
+        .items
+        .values()
+        .filter_map(|item| api_item(package, item))
+        .collect();
+    let api = Api {
+        format_version: FORMAT_VERSION,
+        items,
+    };
+    serde_json::to_string_pretty(&api).expect("API should be serializable")
+}
+
+fn api_item(package: &Package, item: &Item) -> Option<ApiItem> {
+    let parent = package
+        .items
+        .get(item.parent?)
+        .expect("parent item should be in package");
+    let ItemKind::Namespace(namespace, _) = &parent.kind else {
+        return None;
+    };
+
+    let (kind, name, signature, type_parameters, parameters, output, functors) = match &item.kind {
+        ItemKind::Callable(decl) => {
+            let kind = match decl.kind {
+                CallableKind::Function => "function",
+                CallableKind::Operation => "operation",
+            };
+            let type_parameters = decl
+                .generics
+                .iter()
+                .filter_map(|param| match param {
+                    GenericParam::Ty { name, .. } => Some(name.name.clone()),
+                    GenericParam::Functor(_) => None,
+                })
+                .collect();
+            let parameters = match &decl.input.kind {
+                PatKind::Tuple(items) => items.iter().map(api_param).collect(),
+                _ => vec![api_param(&decl.input)],
+            };
+            let functors =
+                (decl.functors != FunctorSetValue::Empty).then(|| decl.functors.to_string());
+            (
+                kind,
+                decl.name.name.clone(),
+                hir_callable_signature(decl).to_string(),
+                type_parameters,
+                parameters,
+                Some(decl.output.display()),
+                functors,
+            )
+        }
+        ItemKind::Ty(name, udt) => {
+            let parameters = match &udt.definition.kind {
+                UdtDefKind::Field(_) => vec![api_field(&udt.definition)],
+                UdtDefKind::Tuple(defs) => defs.iter().map(api_field).collect(),
+            };
+            (
+                "udt",
+                name.name.clone(),
+                hir_udt_signature(udt).to_string(),
+                Vec::new(),
+                parameters,
+                None,
+                None,
+            )
+        }
+        ItemKind::Namespace(..) => return None,
+    };
+
+    Some(ApiItem {
+        kind,
+        namespace: namespace.name.clone(),
+        name,
+        visibility: match item.visibility {
+            Visibility::Public => "public",
+            Visibility::Internal => "internal",
+        },
+        signature,
+        type_parameters,
+        parameters,
+        output,
+        functors,
+        attributes: item.attrs.iter().map(api_attr).collect(),
+        doc: api_doc(item.parsed_doc()),
+    })
+}
+
+fn api_param(pat: &Pat) -> ApiParam {
+    ApiParam {
+        name: match &pat.kind {
+            PatKind::Bind(name) => Some(name.name.clone()),
+            PatKind::Discard | PatKind::Tuple(_) | PatKind::Err => None,
+        },
+        ty: pat.ty.display(),
+    }
+}
+
+fn api_field(def: &UdtDef) -> ApiParam {
+    fn def_ty(def: &UdtDef) -> Ty {
+        match &def.kind {
+            UdtDefKind::Field(field) => field.ty.clone(),
+            UdtDefKind::Tuple(defs) => Ty::Tuple(defs.iter().map(def_ty).collect()),
+        }
+    }
+
+    ApiParam {
+        name: match &def.kind {
+            UdtDefKind::Field(field) => field.name.clone(),
+            UdtDefKind::Tuple(_) => None,
+        },
+        ty: def_ty(def).display(),
+    }
+}
+
+fn api_attr(attr: &Attr) -> ApiAttr {
+    let (name, replacement) = match attr {
+        Attr::Config => ("Config", None),
+        Attr::EntryPoint => ("EntryPoint", None),
+        Attr::Unimplemented => ("Unimplemented", None),
+        Attr::Deprecated(replacement) => ("Deprecated", replacement.clone()),
+        Attr::Allow => ("Allow", None),
+        Attr::NoInline => ("NoInline", None),
+    };
+    ApiAttr { name, replacement }
+}
+
+fn api_doc(doc: Doc) -> ApiDoc {
+    fn entries(entries: Vec<Entry>) -> Vec<ApiEntry> {
+        entries
+            .into_iter()
+            .map(|entry| ApiEntry {
+                name: entry.name,
+                text: entry.text,
+            })
+            .collect()
+    }
+
+    ApiDoc {
+        summary: doc.summary,
+        description: doc.description,
+        inputs: entries(doc.inputs),
+        output: doc.output,
+        type_parameters: entries(doc.type_params),
+        examples: doc.examples,
+        sections: entries(doc.sections),
+    }
+}
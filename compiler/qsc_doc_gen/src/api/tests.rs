@@ -0,0 +1,181 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::export_package;
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+
+fn check(source: &str, expect: &Expect) {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    expect.assert_eq(&export_package(&unit.package));
+}
+
+#[test]
+fn operation_is_exported_with_doc_and_attributes() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                /// # Summary
+                /// Applies a gate.
+                /// # Input
+                /// ## q
+                /// The qubit.
+                @Deprecated("Test.Other")
+                operation Apply(q : Qubit) : Unit is Adj {}
+            }
+        "#},
+        &expect![[r#"
+            {
+              "formatVersion": 1,
+              "items": [
+                {
+                  "kind": "operation",
+                  "namespace": "Test",
+                  "name": "Apply",
+                  "visibility": "public",
+                  "signature": "operation Apply(q : Qubit) : Unit is Adj",
+                  "typeParameters": [],
+                  "parameters": [
+                    {
+                      "name": "q",
+                      "type": "Qubit"
+                    }
+                  ],
+                  "output": "Unit",
+                  "functors": "Adj",
+                  "attributes": [
+                    {
+                      "name": "Deprecated",
+                      "replacement": "Test.Other"
+                    }
+                  ],
+                  "doc": {
+                    "summary": "Applies a gate.",
+                    "description": "",
+                    "inputs": [
+                      {
+                        "name": "q",
+                        "text": "The qubit."
+                      }
+                    ],
+                    "output": "",
+                    "typeParameters": [],
+                    "examples": [],
+                    "sections": []
+                  }
+                }
+              ]
+            }"#]],
+    );
+}
+
+#[test]
+fn generic_function_is_exported_with_visibility() {
+    check(
+        indoc! {"
+            namespace Test {
+                internal function Repeat<'T>(x : 'T, n : Int) : 'T[] {
+                    [x, size = n]
+                }
+            }
+        "},
+        &expect![[r#"
+            {
+              "formatVersion": 1,
+              "items": [
+                {
+                  "kind": "function",
+                  "namespace": "Test",
+                  "name": "Repeat",
+                  "visibility": "internal",
+                  "signature": "function Repeat<'T>(x : 'T, n : Int) : 'T[]",
+                  "typeParameters": [
+                    "'T"
+                  ],
+                  "parameters": [
+                    {
+                      "name": "x",
+                      "type": "'T"
+                    },
+                    {
+                      "name": "n",
+                      "type": "Int"
+                    }
+                  ],
+                  "output": "'T[]",
+                  "functors": null,
+                  "attributes": [],
+                  "doc": {
+                    "summary": "",
+                    "description": "",
+                    "inputs": [],
+                    "output": "",
+                    "typeParameters": [],
+                    "examples": [],
+                    "sections": []
+                  }
+                }
+              ]
+            }"#]],
+    );
+}
+
+#[test]
+fn udt_is_exported_with_fields() {
+    check(
+        indoc! {"
+            namespace Test {
+                newtype Pair = (First : Int, Second : Double);
+            }
+        "},
+        &expect![[r#"
+            {
+              "formatVersion": 1,
+              "items": [
+                {
+                  "kind": "udt",
+                  "namespace": "Test",
+                  "name": "Pair",
+                  "visibility": "public",
+                  "signature": "newtype Pair = (First: Int, Second: Double)",
+                  "typeParameters": [],
+                  "parameters": [
+                    {
+                      "name": "First",
+                      "type": "Int"
+                    },
+                    {
+                      "name": "Second",
+                      "type": "Double"
+                    }
+                  ],
+                  "output": null,
+                  "functors": null,
+                  "attributes": [],
+                  "doc": {
+                    "summary": "",
+                    "description": "",
+                    "inputs": [],
+                    "output": "",
+                    "typeParameters": [],
+                    "examples": [],
+                    "sections": []
+                  }
+                }
+              ]
+            }"#]],
+    );
+}
@@ -4,8 +4,9 @@
 use qsc_ast::ast;
 use qsc_frontend::resolve;
 use qsc_hir::{
+    doc::Doc,
     hir::{self, PackageId},
-    ty::{self, GenericParam},
+    ty::{self, ClassConstraint, GenericParam},
 };
 use regex_lite::Regex;
 use std::{
@@ -107,6 +108,16 @@ impl<'a> CodeDisplay<'a> {
     // but there's no reason they couldn't be
 }
 
+/// Displays the signature of a callable, which doesn't need a [`Lookup`].
+pub(crate) fn hir_callable_signature(decl: &hir::CallableDecl) -> impl Display + '_ {
+    HirCallableDecl { decl }
+}
+
+/// Displays the declaration of a UDT, which doesn't need a [`Lookup`].
+pub(crate) fn hir_udt_signature(udt: &ty::Udt) -> impl Display + '_ {
+    HirUdt { udt }
+}
+
 // Display impls for each syntax/hir element we may encounter
 
 struct IdentTy<'a> {
@@ -208,7 +219,14 @@ impl<'a> Display for AstCallableDecl<'a> {
                 .decl
                 .generics
                 .iter()
-                .map(|p| p.name.clone())
+                .map(|p| {
+                    let bounds = p
+                        .constraints
+                        .iter()
+                        .filter_map(|c| c.name.parse().ok())
+                        .collect::<Vec<_>>();
+                    display_bounded_param(&p.ty.name, &bounds)
+                })
                 .collect::<Vec<_>>()
                 .join(", ");
             write!(f, "<{type_params}>")?;
@@ -494,6 +512,9 @@ struct AstPath<'a> {
 
 impl<'a> Display for AstPath<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        if let Some(package) = &self.path.package {
+            write!(f, "{}::", package.name)?;
+        }
         match self.path.namespace.as_ref() {
             Some(ns) => write!(f, "{ns}.{}", self.path.name.name),
             None => write!(f, "{}", self.path.name.name),
@@ -544,11 +565,24 @@ where
     Ok(())
 }
 
+fn display_bounded_param(name: &str, bounds: &[ClassConstraint]) -> String {
+    if bounds.is_empty() {
+        name.to_string()
+    } else {
+        let bounds = bounds
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" + ");
+        format!("{name}: {bounds}")
+    }
+}
+
 fn display_type_params(generics: &[GenericParam]) -> String {
     let type_params = generics
         .iter()
         .filter_map(|generic| match generic {
-            GenericParam::Ty(name) => Some(name.name.clone()),
+            GenericParam::Ty { name, bounds } => Some(display_bounded_param(&name.name, bounds)),
             GenericParam::Functor(_) => None,
         })
         .collect::<Vec<_>>()
@@ -619,18 +653,7 @@ pub fn increase_header_level(doc: &str) -> String {
 /// such section can be found, returns the original doc string.
 #[must_use]
 pub fn parse_doc_for_summary(doc: &str) -> String {
-    let re = Regex::new(r"(?mi)(?:^# Summary$)([\s\S]*?)(?:(^# .*)|\z)").expect("Invalid regex");
-    match re.captures(doc) {
-        Some(captures) => {
-            let capture = captures
-                .get(1)
-                .expect("Didn't find the capture for the given regex");
-            capture.as_str()
-        }
-        None => doc,
-    }
-    .trim()
-    .to_string()
+    Doc::parse(doc).summary
 }
 
 /// Takes a doc string from a Q# callable and the name of a parameter of
@@ -638,29 +661,5 @@ pub fn parse_doc_for_summary(doc: &str) -> String {
 /// doc string. If no description is found, returns the empty string.
 #[must_use]
 pub fn parse_doc_for_param(doc: &str, param: &str) -> String {
-    let re = Regex::new(r"(?mi)(?:^# Input$)([\s\S]*?)(?:(^# .*)|\z)").expect("Invalid regex");
-    let input = match re.captures(doc) {
-        Some(captures) => {
-            let capture = captures
-                .get(1)
-                .expect("Didn't find the capture for the given regex");
-            capture.as_str()
-        }
-        None => return String::new(),
-    }
-    .trim();
-
-    let re = Regex::new(format!(r"(?mi)(?:^## {param}$)([\s\S]*?)(?:(^(#|##) .*)|\z)").as_str())
-        .expect("Invalid regex");
-    match re.captures(input) {
-        Some(captures) => {
-            let capture = captures
-                .get(1)
-                .expect("Didn't find the capture for the given regex");
-            capture.as_str()
-        }
-        None => return String::new(),
-    }
-    .trim()
-    .to_string()
+    Doc::parse(doc).input(param).unwrap_or_default().to_string()
 }
@@ -1,12 +1,26 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+#[cfg(test)]
+mod tests;
+
+pub mod connectivity;
+pub mod deterministic;
+pub mod loss;
+pub mod remote;
+pub mod stim;
+pub mod synthesis;
+pub(crate) mod unitary;
+
 use num_bigint::BigUint;
-use num_complex::Complex;
+use num_complex::{Complex, Complex64};
+use qsc_fir::fir::Pauli;
 use quantum_sparse_sim::QuantumSim;
 use rand::RngCore;
+use std::cmp::Ordering;
 
-use crate::val::Value;
+use crate::{intrinsic::utils::split_state, output::StateDump, state, val::Value};
+use unitary::ControlledGate;
 
 /// The trait that must be implemented by a quantum backend, whose functions will be invoked when
 /// quantum intrinsics are called.
@@ -38,13 +52,67 @@ pub trait Backend {
     fn qubit_allocate(&mut self) -> usize;
     fn qubit_release(&mut self, q: usize);
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize);
+
+    /// Captures the quantum state as a [`StateDump`] that produces its amplitudes in chunks. By
+    /// default, the state is captured with [`Backend::capture_quantum_state`], so backends that can
+    /// produce amplitudes incrementally should override this to avoid materializing large states.
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        let (state, qubit_count) = self.capture_quantum_state();
+        StateDump::from_state(state, qubit_count)
+    }
+
     fn qubit_is_zero(&mut self, q: usize) -> bool;
 
+    /// Computes the expectation value ⟨ψ|P|ψ⟩ of the Pauli string `paulis` applied to `qubits`
+    /// without collapsing the state. Returns `None` if the backend does not support it.
+    fn pauli_expectation(&mut self, _paulis: &[Pauli], _qubits: &[usize]) -> Option<f64> {
+        None
+    }
+
+    /// Returns the amplitude of the computational basis state `basis_state` of `qubits`, where the
+    /// first qubit corresponds to the most significant bit of the basis state. When `qubits` is a
+    /// strict subset of the allocated qubits, it must not be entangled with the remaining qubits and
+    /// the amplitude is only defined up to a global phase. Returns `None` if the backend does not
+    /// support state inspection.
+    fn basis_state_amplitude(
+        &mut self,
+        _qubits: &[usize],
+        _basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        None
+    }
+
+    /// Applies the unitary `matrix`, given as rows, to `qubits`, where the first qubit corresponds to
+    /// the most significant bit of the matrix index. The evaluator validates that the matrix is a
+    /// unitary of matching dimension before calling this. Returns `None` if the backend does not
+    /// support custom unitaries.
+    fn apply_unitary(
+        &mut self,
+        _matrix: &[Vec<Complex64>],
+        _qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        None
+    }
+
     fn custom_intrinsic(&mut self, _name: &str, _arg: Value) -> Option<Result<Value, String>> {
         None
     }
 
+    /// Sets the seed for the backend's own stochastic behavior, such as measurement sampling
+    /// and noise. This is independent of the classical seed used by the evaluator.
     fn set_seed(&mut self, _seed: Option<u64>) {}
+
+    /// Returns and clears the warnings raised by the backend since the last call, such as qubit
+    /// loss events. The evaluator reports them with the span of the intrinsic call that raised them.
+    fn take_warnings(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns and clears the error raised by the backend since the last call, such as a violated
+    /// constraint. The evaluator fails with it at the span of the intrinsic call that raised it.
+    fn take_error(&mut self) -> Option<String> {
+        None
+    }
 }
 
 /// Default backend used when targeting sparse simulation.
@@ -65,6 +133,36 @@ impl SparseSim {
             sim: QuantumSim::new(),
         }
     }
+
+    /// The state of the simulator, sorted by basis state but with the indices left in the bit
+    /// order of the simulator, which is the opposite of the expected one. The simulator only gives
+    /// out its state as a whole, so the indices are reversed with [`reverse_bits`] as the state is
+    /// consumed rather than all at once.
+    fn sorted_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        let (mut state, count) = self.sim.get_state();
+        state.sort_unstable_by(|(a, _), (b, _)| cmp_reversed_bits(a, b, count));
+        (state, count)
+    }
+}
+
+/// Reverses the order of the lowest `count` bits of `idx`.
+fn reverse_bits(idx: &BigUint, count: usize) -> BigUint {
+    let mut reversed = BigUint::default();
+    for i in 0..(count as u64) {
+        if idx.bit((count as u64) - 1 - i) {
+            reversed.set_bit(i, true);
+        }
+    }
+    reversed
+}
+
+/// Compares two indices as if the order of their lowest `count` bits were reversed, without
+/// reversing them.
+fn cmp_reversed_bits(a: &BigUint, b: &BigUint, count: usize) -> Ordering {
+    (0..count as u64)
+        .map(|i| a.bit(i).cmp(&b.bit(i)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
 }
 
 impl Backend for SparseSim {
@@ -189,29 +287,94 @@ impl Backend for SparseSim {
     }
 
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
-        let (state, count) = self.sim.get_state();
-        // Because the simulator returns the state indices with opposite endianness from the
-        // expected one, we need to reverse the bit order of the indices.
-        let mut new_state = state
+        let (state, count) = self.sorted_state();
+        let state = state
+            .into_iter()
+            .map(|(idx, val)| (reverse_bits(&idx, count), val))
+            .collect();
+        (state, count)
+    }
+
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        let (state, count) = self.sorted_state();
+        let total = state.len();
+        let amplitudes = state
             .into_iter()
-            .map(|(idx, val)| {
-                let mut new_idx = BigUint::default();
-                for i in 0..(count as u64) {
-                    if idx.bit((count as u64) - 1 - i) {
-                        new_idx.set_bit(i, true);
-                    }
-                }
-                (new_idx, val)
-            })
-            .collect::<Vec<_>>();
-        new_state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
-        (new_state, count)
+            .map(move |(idx, val)| (reverse_bits(&idx, count), val));
+        StateDump::new(amplitudes, count, total)
     }
 
     fn qubit_is_zero(&mut self, q: usize) -> bool {
         self.sim.qubit_is_zero(q)
     }
 
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        let (state, qubit_count) = self.capture_quantum_state();
+        Some(state::pauli_expectation(
+            &state,
+            qubit_count,
+            paulis,
+            qubits,
+        ))
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        let (state, qubit_count) = self.capture_quantum_state();
+        if qubits.len() == qubit_count {
+            // The qubits cover the whole register, so the amplitude can be read directly
+            // without losing the global phase to separation.
+            return Some(Ok(state::basis_state_amplitude(
+                &state,
+                qubit_count,
+                qubits,
+                basis_state,
+            )));
+        }
+        Some(match split_state(qubits, state, qubit_count) {
+            Ok(state) => {
+                let identity = (0..qubits.len()).collect::<Vec<_>>();
+                Ok(state::basis_state_amplitude(
+                    &state,
+                    qubits.len(),
+                    &identity,
+                    basis_state,
+                ))
+            }
+            Err(()) => Err("qubits are not separable".to_string()),
+        })
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        let ids = |indices: &[usize]| indices.iter().map(|i| qubits[*i]).collect::<Vec<_>>();
+        let mut global_phase = 0.0;
+        for gate in unitary::decompose(matrix, qubits.len()) {
+            match gate {
+                ControlledGate::X(ctls, q) => self.sim.mcx(&ids(&ctls), qubits[q]),
+                ControlledGate::Ry(ctls, theta, q) => self.sim.mcry(&ids(&ctls), theta, qubits[q]),
+                ControlledGate::Rz(ctls, theta, q) => self.sim.mcrz(&ids(&ctls), theta, qubits[q]),
+                ControlledGate::GlobalPhase(alpha) => global_phase += alpha,
+            }
+        }
+        if global_phase != 0.0 {
+            // Rz(2α) applies the phase e^{iα} to the one state, so a temporary qubit in the one
+            // state picks up the global phase without affecting the other qubits.
+            let ancilla = self.sim.allocate();
+            self.sim.x(ancilla);
+            self.sim.rz(2.0 * global_phase, ancilla);
+            self.sim.x(ancilla);
+            self.sim.release(ancilla);
+        }
+        Some(Ok(()))
+    }
+
     fn custom_intrinsic(&mut self, name: &str, _arg: Value) -> Option<Result<Value, String>> {
         match name {
             "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
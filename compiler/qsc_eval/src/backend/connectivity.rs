@@ -0,0 +1,282 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::output::StateDump;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use qsc_fir::fir::Pauli;
+use rustc_hash::FxHashSet;
+
+/// An undirected graph of the pairs of physical qubits that multi-qubit gates can act on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CouplingGraph {
+    edges: FxHashSet<(usize, usize)>,
+}
+
+impl CouplingGraph {
+    #[must_use]
+    pub fn new(edges: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        Self {
+            edges: edges
+                .into_iter()
+                .map(|(q0, q1)| (q0.min(q1), q0.max(q1)))
+                .collect(),
+        }
+    }
+
+    /// Creates a graph that connects each qubit to the next one, up to `qubit_count` qubits.
+    #[must_use]
+    pub fn linear(qubit_count: usize) -> Self {
+        Self::new((1..qubit_count).map(|q| (q - 1, q)))
+    }
+
+    #[must_use]
+    pub fn is_adjacent(&self, q0: usize, q1: usize) -> bool {
+        self.edges.contains(&(q0.min(q1), q0.max(q1)))
+    }
+}
+
+/// How a [`ConnectivityCheck`] reports gates that act on non-adjacent qubits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ViolationMode {
+    /// The first violation fails the program.
+    #[default]
+    Error,
+    /// Violations are counted and reported as runtime warnings.
+    Count,
+}
+
+/// Backend wrapper that checks multi-qubit gates against a [`CouplingGraph`], treating qubit ids
+/// as physical qubits.
+///
+/// Every pair of qubits that a gate acts on must be adjacent in the graph, so that, for example, a
+/// `CCNOT` requires its three qubits to form a triangle. Gates are forwarded to the inner backend
+/// whether or not they violate the constraints.
+pub struct ConnectivityCheck<B: Backend> {
+    inner: B,
+    graph: CouplingGraph,
+    mode: ViolationMode,
+    violation_count: usize,
+    warnings: Vec<String>,
+    error: Option<String>,
+}
+
+impl<B: Backend> ConnectivityCheck<B> {
+    #[must_use]
+    pub fn new(inner: B, graph: CouplingGraph, mode: ViolationMode) -> Self {
+        Self {
+            inner,
+            graph,
+            mode,
+            violation_count: 0,
+            warnings: Vec::new(),
+            error: None,
+        }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// The number of gates applied so far that acted on non-adjacent qubits.
+    #[must_use]
+    pub fn violation_count(&self) -> usize {
+        self.violation_count
+    }
+
+    /// Records a violation if any pair of `qubits` is not adjacent in the coupling graph.
+    fn check(&mut self, name: &str, qubits: &[usize]) {
+        let violation = qubits.iter().enumerate().find_map(|(i, &q0)| {
+            qubits[i + 1..]
+                .iter()
+                .find(|&&q1| !self.graph.is_adjacent(q0, q1))
+                .map(|&q1| (q0, q1))
+        });
+        let Some((q0, q1)) = violation else {
+            return;
+        };
+
+        self.violation_count += 1;
+        let msg = format!("`{name}` acts on qubits {q0} and {q1}, which are not adjacent");
+        match self.mode {
+            ViolationMode::Error => {
+                self.error.get_or_insert(msg);
+            }
+            ViolationMode::Count => self.warnings.push(msg),
+        }
+    }
+}
+
+impl<B: Backend> Backend for ConnectivityCheck<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.check("CCNOT", &[ctl0, ctl1, q]);
+        self.inner.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.check("CNOT", &[ctl, q]);
+        self.inner.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.check("CY", &[ctl, q]);
+        self.inner.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.check("CZ", &[ctl, q]);
+        self.inner.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.inner.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.inner.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.inner.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.inner.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.inner.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.check("Rxx", &[q0, q1]);
+        self.inner.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.inner.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.check("Ryy", &[q0, q1]);
+        self.inner.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.inner.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.check("Rzz", &[q0, q1]);
+        self.inner.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.inner.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.inner.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.check("SWAP", &[q0, q1]);
+        self.inner.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.inner.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.inner.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.inner.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.inner.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.inner.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.inner.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.inner.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        self.inner.dump_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.inner.qubit_is_zero(q)
+    }
+
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        self.inner.pauli_expectation(paulis, qubits)
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        self.inner.basis_state_amplitude(qubits, basis_state)
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        self.check("ApplyUnitary", qubits);
+        self.inner.apply_unitary(matrix, qubits)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        let mut warnings = std::mem::take(&mut self.warnings);
+        warnings.extend(self.inner.take_warnings());
+        warnings
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take().or_else(|| self.inner.take_error())
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{ConnectivityCheck, CouplingGraph, ViolationMode};
+use crate::backend::{Backend, SparseSim};
+
+#[test]
+fn adjacent_gates_are_allowed() {
+    let mut sim = ConnectivityCheck::new(
+        SparseSim::new(),
+        CouplingGraph::linear(3),
+        ViolationMode::Error,
+    );
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.cx(q0, q1);
+    sim.rzz(1.0, q2, q1);
+    sim.h(q0);
+    assert_eq!(sim.violation_count(), 0);
+    assert_eq!(sim.take_error(), None);
+}
+
+#[test]
+fn non_adjacent_gate_is_an_error() {
+    let mut sim = ConnectivityCheck::new(
+        SparseSim::new(),
+        CouplingGraph::linear(3),
+        ViolationMode::Error,
+    );
+    let q0 = sim.qubit_allocate();
+    let _ = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.cx(q0, q2);
+    assert_eq!(
+        sim.take_error().as_deref(),
+        Some("`CNOT` acts on qubits 0 and 2, which are not adjacent")
+    );
+    assert!(sim.m(q2), "the gate should still be applied");
+}
+
+#[test]
+fn violations_are_counted_as_warnings() {
+    let mut sim = ConnectivityCheck::new(
+        SparseSim::new(),
+        CouplingGraph::new([(0, 1), (1, 2)]),
+        ViolationMode::Count,
+    );
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.swap(q2, q0);
+    sim.ccx(q0, q1, q2);
+    assert_eq!(sim.violation_count(), 2);
+    assert_eq!(sim.take_error(), None);
+    assert_eq!(
+        sim.take_warnings(),
+        vec![
+            "`SWAP` acts on qubits 2 and 0, which are not adjacent".to_string(),
+            "`CCNOT` acts on qubits 0 and 2, which are not adjacent".to_string(),
+        ]
+    );
+}
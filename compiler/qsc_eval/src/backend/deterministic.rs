@@ -0,0 +1,225 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::output::StateDump;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use qsc_fir::fir::Pauli;
+
+/// The default tolerance within which a measurement outcome probability must be 0 or 1.
+pub const DEFAULT_TOLERANCE: f64 = 1e-8;
+
+/// Backend wrapper that raises an error whenever a measurement outcome is not deterministic, that
+/// is, when the probability of measuring `One` is not 0 or 1 within the tolerance.
+///
+/// This is a strict mode for verifying that routines such as uncomputation or amplitude
+/// amplification leave qubits in the expected basis states. The outcome probability is computed
+/// with [`Backend::pauli_expectation`], so measurements are not checked on backends that do not
+/// support it.
+pub struct DeterministicMeasurement<B: Backend> {
+    inner: B,
+    tolerance: f64,
+    error: Option<String>,
+}
+
+impl<B: Backend> DeterministicMeasurement<B> {
+    #[must_use]
+    pub fn new(inner: B) -> Self {
+        Self::with_tolerance(inner, DEFAULT_TOLERANCE)
+    }
+
+    #[must_use]
+    pub fn with_tolerance(inner: B, tolerance: f64) -> Self {
+        Self {
+            inner,
+            tolerance,
+            error: None,
+        }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Records an error if measuring the qubit in the computational basis does not have a
+    /// deterministic outcome.
+    fn check(&mut self, q: usize) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Some(expectation) = self.inner.pauli_expectation(&[Pauli::Z], &[q]) {
+            // ⟨Z⟩ = 1 - 2p, where p is the probability of measuring One.
+            let probability = (1.0 - expectation) / 2.0;
+            if probability > self.tolerance && probability < 1.0 - self.tolerance {
+                self.error = Some(format!(
+                    "measurement of qubit {q} is not deterministic, the probability of `One` is {probability:.6}"
+                ));
+            }
+        }
+    }
+}
+
+impl<B: Backend> Backend for DeterministicMeasurement<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.inner.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.inner.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.inner.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.inner.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.inner.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.check(q);
+        self.inner.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.check(q);
+        self.inner.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.inner.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.inner.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.inner.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.inner.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.inner.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.inner.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.inner.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.inner.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.inner.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.inner.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.inner.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.inner.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.inner.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.inner.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.inner.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.inner.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.inner.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        self.inner.dump_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.inner.qubit_is_zero(q)
+    }
+
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        self.inner.pauli_expectation(paulis, qubits)
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        self.inner.basis_state_amplitude(qubits, basis_state)
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        self.inner.apply_unitary(matrix, qubits)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        self.inner.take_warnings()
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take().or_else(|| self.inner.take_error())
+    }
+}
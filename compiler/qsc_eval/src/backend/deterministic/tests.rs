@@ -0,0 +1,43 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::DeterministicMeasurement;
+use crate::backend::{Backend, SparseSim};
+
+#[test]
+fn basis_state_measurements_are_allowed() {
+    let mut sim = DeterministicMeasurement::new(SparseSim::new());
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.cx(q0, q1);
+    assert!(sim.m(q0));
+    assert!(sim.mresetz(q1));
+    assert!(!sim.m(q1));
+    assert_eq!(sim.take_error(), None);
+}
+
+#[test]
+fn uncomputed_superposition_is_allowed() {
+    let mut sim = DeterministicMeasurement::new(SparseSim::new());
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    sim.t(q);
+    sim.tadj(q);
+    sim.h(q);
+    assert!(!sim.m(q));
+    assert_eq!(sim.take_error(), None);
+}
+
+#[test]
+fn superposition_measurement_is_an_error() {
+    let mut sim = DeterministicMeasurement::new(SparseSim::new());
+    let q = sim.qubit_allocate();
+    sim.ry(std::f64::consts::FRAC_PI_3, q);
+    sim.m(q);
+    assert_eq!(
+        sim.take_error().as_deref(),
+        Some("measurement of qubit 0 is not deterministic, the probability of `One` is 0.250000")
+    );
+    assert_eq!(sim.take_error(), None);
+}
@@ -0,0 +1,319 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::output::StateDump;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use qsc_fir::fir::Pauli;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::FxHashMap;
+
+/// The outcome reported when measuring a qubit that has been lost or has leaked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LostMeasurement {
+    /// The measurement always reports `Zero`.
+    #[default]
+    Zero,
+    /// The measurement always reports `One`.
+    One,
+    /// The measurement reports a uniformly random result.
+    Random,
+}
+
+/// Model of qubit loss and leakage events.
+///
+/// After every gate, each qubit it acted on is independently lost with probability
+/// `loss_probability` or leaks out of the computational subspace with probability
+/// `leakage_probability`. Lost qubits stay lost until they are released, while leaked qubits return
+/// to the computational subspace in the zero state when they are reset. Gates acting on a lost or
+/// leaked qubit are not applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LossModel {
+    pub loss_probability: f64,
+    pub leakage_probability: f64,
+    /// The outcome of measuring a lost or leaked qubit.
+    pub lost_measurement: LostMeasurement,
+    /// Whether loss and leakage events are reported as runtime warnings.
+    pub warn: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QubitStatus {
+    Lost,
+    Leaked,
+}
+
+/// Backend wrapper that injects qubit loss and leakage events according to a [`LossModel`] before
+/// forwarding operations to the inner backend.
+///
+/// When a qubit is lost or leaks, it is measured and reset in the inner backend, so that any
+/// entanglement with the remaining qubits is broken as if the qubit had been traced out.
+pub struct QubitLoss<B: Backend<ResultType = bool>> {
+    inner: B,
+    model: LossModel,
+    status: FxHashMap<usize, QubitStatus>,
+    warnings: Vec<String>,
+    rng: StdRng,
+}
+
+impl<B: Backend<ResultType = bool>> QubitLoss<B> {
+    #[must_use]
+    pub fn new(inner: B, model: LossModel) -> Self {
+        Self {
+            inner,
+            model,
+            status: FxHashMap::default(),
+            warnings: Vec::new(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns true if the qubit has been lost.
+    #[must_use]
+    pub fn is_lost(&self, q: usize) -> bool {
+        self.status.get(&q) == Some(&QubitStatus::Lost)
+    }
+
+    /// Returns true if the qubit has leaked out of the computational subspace.
+    #[must_use]
+    pub fn is_leaked(&self, q: usize) -> bool {
+        self.status.get(&q) == Some(&QubitStatus::Leaked)
+    }
+
+    /// Applies `gate` to the inner backend unless one of `qubits` is lost or leaked, and then
+    /// samples loss and leakage events for `qubits`.
+    fn apply(&mut self, name: &str, qubits: &[usize], gate: impl FnOnce(&mut B)) {
+        if let Some(q) = qubits.iter().find(|q| self.status.contains_key(q)) {
+            if self.model.warn {
+                self.warnings.push(format!(
+                    "`{name}` was not applied because qubit {q} is unavailable"
+                ));
+            }
+            return;
+        }
+
+        gate(&mut self.inner);
+
+        for &q in qubits {
+            let sample = self.rng.gen::<f64>();
+            let status = if sample < self.model.loss_probability {
+                QubitStatus::Lost
+            } else if sample < self.model.loss_probability + self.model.leakage_probability {
+                QubitStatus::Leaked
+            } else {
+                continue;
+            };
+
+            self.inner.mresetz(q);
+            self.status.insert(q, status);
+            if self.model.warn {
+                self.warnings.push(match status {
+                    QubitStatus::Lost => format!("qubit {q} was lost during `{name}`"),
+                    QubitStatus::Leaked => format!("qubit {q} leaked during `{name}`"),
+                });
+            }
+        }
+    }
+
+    /// Measures the qubit, reporting the configured outcome if it is lost or leaked.
+    fn measure(&mut self, q: usize, measure: impl FnOnce(&mut B) -> bool) -> bool {
+        if self.status.contains_key(&q) {
+            match self.model.lost_measurement {
+                LostMeasurement::Zero => false,
+                LostMeasurement::One => true,
+                LostMeasurement::Random => self.rng.gen_bool(0.5),
+            }
+        } else {
+            measure(&mut self.inner)
+        }
+    }
+}
+
+impl<B: Backend<ResultType = bool>> Backend for QubitLoss<B> {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.apply("CCNOT", &[ctl0, ctl1, q], |sim| sim.ccx(ctl0, ctl1, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.apply("CNOT", &[ctl, q], |sim| sim.cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.apply("CY", &[ctl, q], |sim| sim.cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.apply("CZ", &[ctl, q], |sim| sim.cz(ctl, q));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.apply("H", &[q], |sim| sim.h(q));
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.measure(q, |sim| sim.m(q))
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let result = self.measure(q, |sim| sim.mresetz(q));
+        if self.is_leaked(q) {
+            self.status.remove(&q);
+        }
+        result
+    }
+
+    fn reset(&mut self, q: usize) {
+        if self.is_leaked(q) {
+            self.status.remove(&q);
+        } else if !self.is_lost(q) {
+            self.inner.reset(q);
+        }
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.apply("Rx", &[q], |sim| sim.rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.apply("Rxx", &[q0, q1], |sim| sim.rxx(theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.apply("Ry", &[q], |sim| sim.ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.apply("Ryy", &[q0, q1], |sim| sim.ryy(theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.apply("Rz", &[q], |sim| sim.rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.apply("Rzz", &[q0, q1], |sim| sim.rzz(theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.apply("Adjoint S", &[q], |sim| sim.sadj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.apply("S", &[q], |sim| sim.s(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.apply("SWAP", &[q0, q1], |sim| sim.swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.apply("Adjoint T", &[q], |sim| sim.tadj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.apply("T", &[q], |sim| sim.t(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.apply("X", &[q], |sim| sim.x(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        self.apply("Y", &[q], |sim| sim.y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        self.apply("Z", &[q], |sim| sim.z(q));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.inner.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.status.remove(&q);
+        self.inner.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        self.inner.dump_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.status.contains_key(&q) || self.inner.qubit_is_zero(q)
+    }
+
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        self.inner.pauli_expectation(paulis, qubits)
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        self.inner.basis_state_amplitude(qubits, basis_state)
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        let mut result = Some(Ok(()));
+        self.apply("ApplyUnitary", qubits, |sim| {
+            result = sim.apply_unitary(matrix, qubits);
+        });
+        result
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        // Derive the seed of the inner backend so that its sampling is not correlated with the
+        // sampling of loss events.
+        let inner_seed = seed.map(|_| self.rng.gen());
+        self.inner.set_seed(inner_seed);
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        let mut warnings = std::mem::take(&mut self.warnings);
+        warnings.extend(self.inner.take_warnings());
+        warnings
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.inner.take_error()
+    }
+}
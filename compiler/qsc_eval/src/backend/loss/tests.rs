@@ -0,0 +1,90 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{LossModel, LostMeasurement, QubitLoss};
+use crate::backend::{Backend, SparseSim};
+
+fn lossy_sim(model: LossModel) -> QubitLoss<SparseSim> {
+    let mut sim = QubitLoss::new(SparseSim::new(), model);
+    sim.set_seed(Some(42));
+    sim
+}
+
+#[test]
+fn no_loss_forwards_gates() {
+    let mut sim = lossy_sim(LossModel::default());
+    let q = sim.qubit_allocate();
+    sim.x(q);
+    assert!(!sim.is_lost(q));
+    assert!(sim.m(q));
+    assert!(sim.take_warnings().is_empty());
+}
+
+#[test]
+fn lost_qubit_reports_configured_measurement() {
+    let mut sim = lossy_sim(LossModel {
+        loss_probability: 1.0,
+        lost_measurement: LostMeasurement::One,
+        ..LossModel::default()
+    });
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    assert!(sim.is_lost(q));
+    assert!(sim.m(q));
+    assert!(sim.mresetz(q));
+    assert!(sim.is_lost(q), "reset should not recover a lost qubit");
+    assert!(sim.inner_mut().qubit_is_zero(q));
+}
+
+#[test]
+fn gates_on_lost_qubits_are_skipped() {
+    let mut sim = lossy_sim(LossModel {
+        loss_probability: 1.0,
+        warn: true,
+        ..LossModel::default()
+    });
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.cx(q0, q1);
+    assert!(!sim.is_lost(q1));
+    assert!(sim.inner_mut().qubit_is_zero(q1));
+    assert_eq!(
+        sim.take_warnings(),
+        vec![
+            "qubit 0 was lost during `X`".to_string(),
+            "`CNOT` was not applied because qubit 0 is unavailable".to_string(),
+        ]
+    );
+    assert!(sim.take_warnings().is_empty());
+}
+
+#[test]
+fn leaked_qubit_recovers_on_reset() {
+    let mut sim = lossy_sim(LossModel {
+        leakage_probability: 1.0,
+        lost_measurement: LostMeasurement::One,
+        ..LossModel::default()
+    });
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    assert!(sim.is_leaked(q));
+    assert!(sim.m(q));
+    sim.reset(q);
+    assert!(!sim.is_leaked(q));
+    assert!(sim.qubit_is_zero(q));
+}
+
+#[test]
+fn released_qubit_is_available_again() {
+    let mut sim = lossy_sim(LossModel {
+        loss_probability: 1.0,
+        ..LossModel::default()
+    });
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    assert!(sim.is_lost(q));
+    sim.qubit_release(q);
+    let q = sim.qubit_allocate();
+    assert!(!sim.is_lost(q));
+}
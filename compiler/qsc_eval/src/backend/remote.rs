@@ -0,0 +1,771 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use qsc_fir::fir::Pauli;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+
+/// A call to a [`Backend`], serialized as one JSON object per line. Basis states are decimal
+/// strings, and matrices are rows of pairs of real and imaginary parts.
+///
+/// Only calls that return a value are answered with a [`Response`], so gates can be streamed to
+/// the remote backend without waiting for it. The evaluator takes the warnings and error of the
+/// backend after each intrinsic call, though, which waits for the remote backend to catch up.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "call", rename_all = "camelCase")]
+pub enum Request {
+    Ccx {
+        ctl0: usize,
+        ctl1: usize,
+        q: usize,
+    },
+    Cx {
+        ctl: usize,
+        q: usize,
+    },
+    Cy {
+        ctl: usize,
+        q: usize,
+    },
+    Cz {
+        ctl: usize,
+        q: usize,
+    },
+    H {
+        q: usize,
+    },
+    M {
+        q: usize,
+    },
+    Mresetz {
+        q: usize,
+    },
+    Reset {
+        q: usize,
+    },
+    Rx {
+        theta: f64,
+        q: usize,
+    },
+    Rxx {
+        theta: f64,
+        q0: usize,
+        q1: usize,
+    },
+    Ry {
+        theta: f64,
+        q: usize,
+    },
+    Ryy {
+        theta: f64,
+        q0: usize,
+        q1: usize,
+    },
+    Rz {
+        theta: f64,
+        q: usize,
+    },
+    Rzz {
+        theta: f64,
+        q0: usize,
+        q1: usize,
+    },
+    Sadj {
+        q: usize,
+    },
+    S {
+        q: usize,
+    },
+    Swap {
+        q0: usize,
+        q1: usize,
+    },
+    Tadj {
+        q: usize,
+    },
+    T {
+        q: usize,
+    },
+    X {
+        q: usize,
+    },
+    Y {
+        q: usize,
+    },
+    Z {
+        q: usize,
+    },
+    QubitAllocate,
+    QubitRelease {
+        q: usize,
+    },
+    CaptureQuantumState,
+    QubitIsZero {
+        q: usize,
+    },
+    SetSeed {
+        seed: Option<u64>,
+    },
+    PauliExpectation {
+        paulis: Vec<Pauli>,
+        qubits: Vec<usize>,
+    },
+    BasisStateAmplitude {
+        qubits: Vec<usize>,
+        basis_state: String,
+    },
+    ApplyUnitary {
+        matrix: Vec<Vec<(f64, f64)>>,
+        qubits: Vec<usize>,
+    },
+    CustomIntrinsic {
+        name: String,
+        arg: Value,
+    },
+    TakeWarnings,
+    TakeError,
+}
+
+/// The calls that are answered with a [`Response`], as they are named in a serialized [`Request`].
+const CALLS_WITH_RESPONSE: &[&str] = &[
+    "m",
+    "mresetz",
+    "qubitAllocate",
+    "captureQuantumState",
+    "qubitIsZero",
+    "pauliExpectation",
+    "basisStateAmplitude",
+    "applyUnitary",
+    "customIntrinsic",
+    "takeWarnings",
+    "takeError",
+];
+
+/// The qubit id returned when a remote allocation fails. No live qubit has this id, and the
+/// failure is raised through [`Backend::take_error`] before the id can be used.
+const FAILED_QUBIT: usize = usize::MAX;
+
+impl Request {
+    /// Returns true if the remote backend answers this call with a [`Response`].
+    #[must_use]
+    pub fn expects_response(&self) -> bool {
+        matches!(
+            self,
+            Request::M { .. }
+                | Request::Mresetz { .. }
+                | Request::QubitAllocate
+                | Request::CaptureQuantumState
+                | Request::QubitIsZero { .. }
+                | Request::PauliExpectation { .. }
+                | Request::BasisStateAmplitude { .. }
+                | Request::ApplyUnitary { .. }
+                | Request::CustomIntrinsic { .. }
+                | Request::TakeWarnings
+                | Request::TakeError
+        )
+    }
+}
+
+/// The answer to a [`Request`] that returns a value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Response {
+    /// A measurement result or a qubit zero check.
+    Bool { value: bool },
+    /// The id of an allocated qubit.
+    Qubit { id: usize },
+    /// The quantum state, with basis state ids as decimal strings and amplitudes as pairs of real
+    /// and imaginary parts.
+    State {
+        amplitudes: Vec<(String, f64, f64)>,
+        qubit_count: usize,
+    },
+    /// A Pauli expectation value.
+    Expectation { value: f64 },
+    /// The amplitude of a basis state, as its real and imaginary parts.
+    Amplitude { re: f64, im: f64 },
+    /// A unitary was applied.
+    Done,
+    /// The value returned by a custom intrinsic.
+    Value { value: Value },
+    /// The warnings raised by the backend since they were last taken.
+    Warnings { warnings: Vec<String> },
+    /// The error raised by the backend since it was last taken, if any.
+    BackendError { message: Option<String> },
+    /// The backend could not perform the call, like reading the amplitude of qubits that are
+    /// entangled with others.
+    Rejected { message: String },
+    /// The backend does not support the call.
+    Unsupported,
+    /// The remote backend failed to handle the request.
+    Error { message: String },
+}
+
+/// Backend proxy that forwards every call as a [`Request`] to a backend in another process, such
+/// as a third-party simulator, which answers using [`serve`].
+///
+/// Communication failures cannot be returned from backend calls, so the first failure is raised
+/// through [`Backend::take_error`] and later calls return default values.
+pub struct RemoteBackend<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+    error: Option<String>,
+    failed: bool,
+}
+
+impl<R: BufRead, W: Write> RemoteBackend<R, W> {
+    /// Creates a proxy that writes requests to `writer` and reads responses from `reader`.
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            error: None,
+            failed: false,
+        }
+    }
+
+    fn send(&mut self, request: &Request) {
+        if self.failed {
+            return;
+        }
+        let result = serde_json::to_writer(&mut self.writer, request)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(self.writer))
+            .and_then(|()| {
+                if request.expects_response() {
+                    self.writer.flush()
+                } else {
+                    Ok(())
+                }
+            });
+        if let Err(e) = result {
+            self.fail(format!("failed to send request to remote backend: {e}"));
+        }
+    }
+
+    fn call(&mut self, request: &Request) -> Option<Response> {
+        self.send(request);
+        if self.failed {
+            return None;
+        }
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => {
+                self.fail("remote backend closed the connection".to_string());
+                None
+            }
+            Ok(_) => match serde_json::from_str(&line) {
+                Ok(Response::Error { message }) => {
+                    self.fail(format!("remote backend failed: {message}"));
+                    None
+                }
+                Ok(response) => Some(response),
+                Err(e) => {
+                    self.fail(format!("invalid response from remote backend: {e}"));
+                    None
+                }
+            },
+            Err(e) => {
+                self.fail(format!(
+                    "failed to receive response from remote backend: {e}"
+                ));
+                None
+            }
+        }
+    }
+
+    fn call_bool(&mut self, request: &Request) -> bool {
+        match self.call(request) {
+            Some(Response::Bool { value }) => value,
+            Some(response) => {
+                self.unexpected(&response);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn unexpected(&mut self, response: &Response) {
+        self.fail(format!(
+            "unexpected response from remote backend: {response:?}"
+        ));
+    }
+
+    fn fail(&mut self, message: String) {
+        self.failed = true;
+        self.error.get_or_insert(message);
+    }
+
+    /// The result of a call that the remote backend may reject or not support, where `value`
+    /// extracts the result from a response of the expected kind and returns any other response.
+    fn call_fallible<T>(
+        &mut self,
+        request: &Request,
+        value: impl FnOnce(Response) -> Result<T, Response>,
+    ) -> Option<Result<T, String>> {
+        match self.call(request).map(value) {
+            Some(Ok(value)) => Some(Ok(value)),
+            Some(Err(Response::Rejected { message })) => Some(Err(message)),
+            Some(Err(Response::Unsupported)) => None,
+            Some(Err(response)) => {
+                self.unexpected(&response);
+                Some(Err(self.failure()))
+            }
+            None => Some(Err(self.failure())),
+        }
+    }
+
+    /// The error of a call that did not reach the remote backend. The error is also returned as
+    /// the result of the call, since the evaluator may give up on the call before taking it.
+    fn failure(&self) -> String {
+        self.error
+            .clone()
+            .unwrap_or_else(|| "remote backend failed".to_string())
+    }
+}
+
+impl<R: BufRead, W: Write> Drop for RemoteBackend<R, W> {
+    fn drop(&mut self) {
+        // Gates are buffered until a call that expects a response, so push out any remaining ones.
+        let _ = self.writer.flush();
+    }
+}
+
+impl<R: BufRead, W: Write> Backend for RemoteBackend<R, W> {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.send(&Request::Ccx { ctl0, ctl1, q });
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.send(&Request::Cx { ctl, q });
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.send(&Request::Cy { ctl, q });
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.send(&Request::Cz { ctl, q });
+    }
+
+    fn h(&mut self, q: usize) {
+        self.send(&Request::H { q });
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.call_bool(&Request::M { q })
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.call_bool(&Request::Mresetz { q })
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.send(&Request::Reset { q });
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.send(&Request::Rx { theta, q });
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.send(&Request::Rxx { theta, q0, q1 });
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.send(&Request::Ry { theta, q });
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.send(&Request::Ryy { theta, q0, q1 });
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.send(&Request::Rz { theta, q });
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.send(&Request::Rzz { theta, q0, q1 });
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.send(&Request::Sadj { q });
+    }
+
+    fn s(&mut self, q: usize) {
+        self.send(&Request::S { q });
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.send(&Request::Swap { q0, q1 });
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.send(&Request::Tadj { q });
+    }
+
+    fn t(&mut self, q: usize) {
+        self.send(&Request::T { q });
+    }
+
+    fn x(&mut self, q: usize) {
+        self.send(&Request::X { q });
+    }
+
+    fn y(&mut self, q: usize) {
+        self.send(&Request::Y { q });
+    }
+
+    fn z(&mut self, q: usize) {
+        self.send(&Request::Z { q });
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        match self.call(&Request::QubitAllocate) {
+            Some(Response::Qubit { id }) => id,
+            Some(response) => {
+                self.unexpected(&response);
+                FAILED_QUBIT
+            }
+            None => {
+                // An earlier failure may already have been taken, so raise the allocation failure
+                // rather than let the evaluator use the qubit.
+                self.fail("failed to allocate a qubit on remote backend".to_string());
+                FAILED_QUBIT
+            }
+        }
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.send(&Request::QubitRelease { q });
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        match self.call(&Request::CaptureQuantumState) {
+            Some(Response::State {
+                amplitudes,
+                qubit_count,
+            }) => {
+                let mut state = Vec::with_capacity(amplitudes.len());
+                for (id, re, im) in amplitudes {
+                    let Ok(id) = id.parse() else {
+                        self.fail(format!("invalid basis state id from remote backend: {id}"));
+                        return (Vec::new(), 0);
+                    };
+                    state.push((id, Complex::new(re, im)));
+                }
+                (state, qubit_count)
+            }
+            Some(response) => {
+                self.unexpected(&response);
+                (Vec::new(), 0)
+            }
+            None => (Vec::new(), 0),
+        }
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        // A failed check would be reported as a released qubit that is not in the zero state,
+        // which would hide the communication failure.
+        self.call_bool(&Request::QubitIsZero { q }) || self.failed
+    }
+
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        let request = Request::PauliExpectation {
+            paulis: paulis.to_vec(),
+            qubits: qubits.to_vec(),
+        };
+        match self.call(&request) {
+            Some(Response::Expectation { value }) => Some(value),
+            Some(Response::Unsupported) | None => None,
+            Some(response) => {
+                self.unexpected(&response);
+                None
+            }
+        }
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        let request = Request::BasisStateAmplitude {
+            qubits: qubits.to_vec(),
+            basis_state: basis_state.to_string(),
+        };
+        self.call_fallible(&request, |response| match response {
+            Response::Amplitude { re, im } => Ok(Complex64::new(re, im)),
+            response => Err(response),
+        })
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        let request = Request::ApplyUnitary {
+            matrix: matrix
+                .iter()
+                .map(|row| row.iter().map(|entry| (entry.re, entry.im)).collect())
+                .collect(),
+            qubits: qubits.to_vec(),
+        };
+        self.call_fallible(&request, |response| match response {
+            Response::Done => Ok(()),
+            response => Err(response),
+        })
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        // Values like qubits and closures cannot be serialized, which fails the call rather than
+        // the connection.
+        if let Err(e) = serde_json::to_string(&arg) {
+            return Some(Err(format!(
+                "argument cannot be sent to remote backend: {e}"
+            )));
+        }
+        let request = Request::CustomIntrinsic {
+            name: name.to_string(),
+            arg,
+        };
+        self.call_fallible(&request, |response| match response {
+            Response::Value { value } => Ok(value),
+            response => Err(response),
+        })
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.send(&Request::SetSeed { seed });
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        match self.call(&Request::TakeWarnings) {
+            Some(Response::Warnings { warnings }) => warnings,
+            Some(response) => {
+                self.unexpected(&response);
+                Vec::new()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        if let Some(error) = self.error.take() {
+            return Some(error);
+        }
+        match self.call(&Request::TakeError) {
+            Some(Response::BackendError { message }) => message,
+            Some(response) => {
+                self.unexpected(&response);
+                self.error.take()
+            }
+            None => self.error.take(),
+        }
+    }
+}
+
+/// Handles the requests read from `reader` with `backend`, writing responses to `writer`, until
+/// `reader` is exhausted. This is the adapter on the side of the process that hosts the backend.
+/// # Errors
+/// Returns an error if reading requests or writing responses fails.
+pub fn serve(
+    backend: &mut impl Backend<ResultType = bool>,
+    reader: impl BufRead,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    // The client only reads a response to a call that expects one, so an invalid request for any
+    // other call is raised through the next `takeError` call instead.
+    let mut invalid = None;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(Request::TakeError) => Some(Response::BackendError {
+                message: invalid.take().or_else(|| backend.take_error()),
+            }),
+            Ok(request) => handle(backend, request),
+            Err(e) => {
+                let message = format!("invalid request: {e}");
+                if call_expects_response(&line) {
+                    Some(Response::Error { message })
+                } else {
+                    invalid.get_or_insert(message);
+                    None
+                }
+            }
+        };
+        if let Some(response) = response {
+            serde_json::to_writer(&mut writer, &response)?;
+            writeln!(writer)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the line names a call that is answered with a [`Response`], even if the rest of
+/// the request is invalid.
+fn call_expects_response(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|request| {
+            let call = request.get("call")?.as_str()?;
+            Some(CALLS_WITH_RESPONSE.contains(&call))
+        })
+        .unwrap_or(false)
+}
+
+fn handle(backend: &mut impl Backend<ResultType = bool>, request: Request) -> Option<Response> {
+    match request {
+        Request::M { .. }
+        | Request::Mresetz { .. }
+        | Request::QubitAllocate
+        | Request::CaptureQuantumState
+        | Request::QubitIsZero { .. }
+        | Request::TakeWarnings
+        | Request::TakeError => Some(query(backend, &request)),
+        Request::PauliExpectation { .. }
+        | Request::BasisStateAmplitude { .. }
+        | Request::ApplyUnitary { .. }
+        | Request::CustomIntrinsic { .. } => Some(fallible_call(backend, request)),
+        request => {
+            apply(backend, &request);
+            None
+        }
+    }
+}
+
+/// Applies a call that is not answered, such as a gate.
+fn apply(backend: &mut impl Backend<ResultType = bool>, request: &Request) {
+    match *request {
+        Request::Ccx { ctl0, ctl1, q } => backend.ccx(ctl0, ctl1, q),
+        Request::Cx { ctl, q } => backend.cx(ctl, q),
+        Request::Cy { ctl, q } => backend.cy(ctl, q),
+        Request::Cz { ctl, q } => backend.cz(ctl, q),
+        Request::H { q } => backend.h(q),
+        Request::Reset { q } => backend.reset(q),
+        Request::Rx { theta, q } => backend.rx(theta, q),
+        Request::Rxx { theta, q0, q1 } => backend.rxx(theta, q0, q1),
+        Request::Ry { theta, q } => backend.ry(theta, q),
+        Request::Ryy { theta, q0, q1 } => backend.ryy(theta, q0, q1),
+        Request::Rz { theta, q } => backend.rz(theta, q),
+        Request::Rzz { theta, q0, q1 } => backend.rzz(theta, q0, q1),
+        Request::Sadj { q } => backend.sadj(q),
+        Request::S { q } => backend.s(q),
+        Request::Swap { q0, q1 } => backend.swap(q0, q1),
+        Request::Tadj { q } => backend.tadj(q),
+        Request::T { q } => backend.t(q),
+        Request::X { q } => backend.x(q),
+        Request::Y { q } => backend.y(q),
+        Request::Z { q } => backend.z(q),
+        Request::QubitRelease { q } => backend.qubit_release(q),
+        Request::SetSeed { seed } => backend.set_seed(seed),
+        _ => unreachable!("call with a response should be handled separately"),
+    }
+}
+
+/// Answers a call that the backend always supports, such as a measurement.
+fn query(backend: &mut impl Backend<ResultType = bool>, request: &Request) -> Response {
+    match *request {
+        Request::M { q } => Response::Bool {
+            value: backend.m(q),
+        },
+        Request::Mresetz { q } => Response::Bool {
+            value: backend.mresetz(q),
+        },
+        Request::QubitAllocate => Response::Qubit {
+            id: backend.qubit_allocate(),
+        },
+        Request::CaptureQuantumState => {
+            let (state, qubit_count) = backend.capture_quantum_state();
+            Response::State {
+                amplitudes: state
+                    .into_iter()
+                    .map(|(id, amplitude)| (id.to_string(), amplitude.re, amplitude.im))
+                    .collect(),
+                qubit_count,
+            }
+        }
+        Request::QubitIsZero { q } => Response::Bool {
+            value: backend.qubit_is_zero(q),
+        },
+        Request::TakeWarnings => Response::Warnings {
+            warnings: backend.take_warnings(),
+        },
+        Request::TakeError => Response::BackendError {
+            message: backend.take_error(),
+        },
+        _ => unreachable!("call should be answered by the backend unconditionally"),
+    }
+}
+
+/// Answers a call that the backend may reject or not support.
+fn fallible_call(backend: &mut impl Backend<ResultType = bool>, request: Request) -> Response {
+    match request {
+        Request::PauliExpectation { paulis, qubits } => {
+            match backend.pauli_expectation(&paulis, &qubits) {
+                Some(value) => Response::Expectation { value },
+                None => Response::Unsupported,
+            }
+        }
+        Request::BasisStateAmplitude {
+            qubits,
+            basis_state,
+        } => {
+            let Ok(basis_state) = basis_state.parse() else {
+                return Response::Error {
+                    message: format!("invalid basis state: {basis_state}"),
+                };
+            };
+            fallible_response(
+                backend.basis_state_amplitude(&qubits, &basis_state),
+                |amplitude| Response::Amplitude {
+                    re: amplitude.re,
+                    im: amplitude.im,
+                },
+            )
+        }
+        Request::ApplyUnitary { matrix, qubits } => {
+            let matrix = matrix
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|(re, im)| Complex64::new(re, im))
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+            fallible_response(backend.apply_unitary(&matrix, &qubits), |()| Response::Done)
+        }
+        Request::CustomIntrinsic { name, arg } => {
+            fallible_response(backend.custom_intrinsic(&name, arg), |value| {
+                Response::Value { value }
+            })
+        }
+        _ => unreachable!("call should be one that the backend may reject or not support"),
+    }
+}
+
+/// The response to a call that the backend may reject or not support.
+fn fallible_response<T>(
+    result: Option<Result<T, String>>,
+    response: impl FnOnce(T) -> Response,
+) -> Response {
+    match result {
+        Some(Ok(value)) => response(value),
+        Some(Err(message)) => Response::Rejected { message },
+        None => Response::Unsupported,
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{serve, RemoteBackend, Request, Response};
+use crate::{
+    backend::{Backend, SparseSim},
+    val::Value,
+};
+use qsc_fir::fir::Pauli;
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+struct PipeWriter(Sender<Vec<u8>>);
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct PipeReader {
+    receiver: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            match self.receiver.recv() {
+                Ok(bytes) => self.pending = bytes,
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.pending.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+        Ok(len)
+    }
+}
+
+fn pipe() -> (PipeWriter, BufReader<PipeReader>) {
+    let (sender, receiver) = channel();
+    (
+        PipeWriter(sender),
+        BufReader::new(PipeReader {
+            receiver,
+            pending: Vec::new(),
+        }),
+    )
+}
+
+type Proxy = RemoteBackend<BufReader<PipeReader>, PipeWriter>;
+
+/// Connects a proxy to a sparse simulator served on another thread.
+fn connect() -> (Proxy, JoinHandle<io::Result<()>>) {
+    let (request_writer, request_reader) = pipe();
+    let (response_writer, response_reader) = pipe();
+    let server = thread::spawn(move || {
+        let mut sim = SparseSim::new();
+        serve(&mut sim, request_reader, response_writer)
+    });
+    (RemoteBackend::new(response_reader, request_writer), server)
+}
+
+#[test]
+fn requests_round_trip_through_json() {
+    let request = Request::Rzz {
+        theta: 0.5,
+        q0: 1,
+        q1: 2,
+    };
+    let json = serde_json::to_string(&request).expect("request should serialize");
+    assert_eq!(json, r#"{"call":"rzz","theta":0.5,"q0":1,"q1":2}"#);
+    assert_eq!(
+        serde_json::from_str::<Request>(&json).expect("request should deserialize"),
+        request
+    );
+}
+
+#[test]
+fn gates_and_measurements_are_forwarded() {
+    let (mut proxy, server) = connect();
+    let q0 = proxy.qubit_allocate();
+    let q1 = proxy.qubit_allocate();
+    proxy.x(q0);
+    proxy.cx(q0, q1);
+    assert!(proxy.m(q1));
+    assert!(proxy.mresetz(q0));
+    assert!(proxy.qubit_is_zero(q0));
+    proxy.reset(q1);
+    proxy.qubit_release(q0);
+    proxy.qubit_release(q1);
+    assert_eq!(proxy.take_error(), None);
+    drop(proxy);
+    server
+        .join()
+        .expect("server thread should not panic")
+        .expect("server should succeed");
+}
+
+#[test]
+fn state_is_captured_remotely() {
+    let (mut proxy, _server) = connect();
+    let q0 = proxy.qubit_allocate();
+    let _ = proxy.qubit_allocate();
+    proxy.x(q0);
+    let (state, qubit_count) = proxy.capture_quantum_state();
+    assert_eq!(qubit_count, 2);
+    assert_eq!(state.len(), 1);
+    assert_eq!(state[0].0, 2_u8.into());
+    assert!((state[0].1.re - 1.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn closed_connection_is_an_error() {
+    let (request_writer, _) = pipe();
+    let (_, response_reader) = pipe();
+    let mut proxy = RemoteBackend::new(response_reader, request_writer);
+    assert!(!proxy.m(0));
+    assert!(proxy.take_error().is_some());
+    assert_eq!(proxy.take_error(), None);
+}
+
+#[test]
+fn state_inspection_and_custom_intrinsics_are_forwarded() {
+    let (mut proxy, _server) = connect();
+    let q = proxy.qubit_allocate();
+    proxy.h(q);
+    let expectation = proxy
+        .pauli_expectation(&[Pauli::X], &[q])
+        .expect("expectation should be supported");
+    assert!((expectation - 1.0).abs() < 1e-9);
+    let amplitude = proxy
+        .basis_state_amplitude(&[q], &1_u8.into())
+        .expect("amplitude should be supported")
+        .expect("amplitude should be read");
+    assert!((amplitude.re - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-9);
+    assert_eq!(
+        proxy.custom_intrinsic("BeginEstimateCaching", Value::unit()),
+        Some(Ok(Value::Bool(true)))
+    );
+    assert_eq!(proxy.custom_intrinsic("Unknown", Value::unit()), None);
+    assert_eq!(proxy.take_warnings(), Vec::<String>::new());
+    assert_eq!(proxy.take_error(), None);
+}
+
+#[test]
+fn invalid_request_is_only_answered_if_its_call_expects_a_response() {
+    let (mut request_writer, request_reader) = pipe();
+    let (response_writer, mut response_reader) = pipe();
+    let _server = thread::spawn(move || {
+        let mut sim = SparseSim::new();
+        serve(&mut sim, request_reader, response_writer)
+    });
+    let mut call = |request: &str| {
+        writeln!(request_writer, "{request}").expect("request should be written");
+    };
+    let mut response = || {
+        let mut line = String::new();
+        response_reader
+            .read_line(&mut line)
+            .expect("response should be read");
+        serde_json::from_str::<Response>(&line).expect("response should deserialize")
+    };
+
+    call(r#"{"call":"h"}"#);
+    call(r#"{"call":"m"}"#);
+    assert!(matches!(response(), Response::Error { .. }));
+    call(r#"{"call":"takeError"}"#);
+    assert!(matches!(
+        response(),
+        Response::BackendError { message: Some(message) } if message.starts_with("invalid request")
+    ));
+    call(r#"{"call":"takeError"}"#);
+    assert_eq!(response(), Response::BackendError { message: None });
+}
+
+#[test]
+fn calls_with_response_match_requests() {
+    let requests = [
+        Request::M { q: 0 },
+        Request::Mresetz { q: 0 },
+        Request::QubitAllocate,
+        Request::CaptureQuantumState,
+        Request::QubitIsZero { q: 0 },
+        Request::PauliExpectation {
+            paulis: Vec::new(),
+            qubits: Vec::new(),
+        },
+        Request::BasisStateAmplitude {
+            qubits: Vec::new(),
+            basis_state: "0".to_string(),
+        },
+        Request::ApplyUnitary {
+            matrix: Vec::new(),
+            qubits: Vec::new(),
+        },
+        Request::CustomIntrinsic {
+            name: String::new(),
+            arg: Value::unit(),
+        },
+        Request::TakeWarnings,
+        Request::TakeError,
+    ];
+    for request in requests {
+        assert!(request.expects_response());
+        let json = serde_json::to_string(&request).expect("request should serialize");
+        assert!(super::call_expects_response(&json), "{json}");
+    }
+    assert!(!super::call_expects_response(r#"{"call":"h","q":0}"#));
+}
+
+#[test]
+fn failed_allocation_is_an_error() {
+    let (request_writer, _) = pipe();
+    let (_, response_reader) = pipe();
+    let mut proxy = RemoteBackend::new(response_reader, request_writer);
+    assert_eq!(proxy.qubit_allocate(), usize::MAX);
+    assert!(proxy.take_error().is_some());
+    assert_eq!(proxy.qubit_allocate(), usize::MAX);
+    assert!(proxy.take_error().is_some());
+}
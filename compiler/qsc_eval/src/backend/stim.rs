@@ -0,0 +1,579 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::output::StateDump;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use qsc_fir::fir::Pauli;
+use std::{f64::consts::FRAC_PI_2, fmt::Write};
+use thiserror::Error;
+
+/// A Clifford operation or measurement in a [`CliffordTrace`], acting on qubit ids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CliffordOp {
+    H(usize),
+    S(usize),
+    Sadj(usize),
+    SqrtX(usize),
+    SqrtXAdj(usize),
+    SqrtY(usize),
+    SqrtYAdj(usize),
+    X(usize),
+    Y(usize),
+    Z(usize),
+    Cx(usize, usize),
+    Cy(usize, usize),
+    Cz(usize, usize),
+    Swap(usize, usize),
+    SqrtXX(usize, usize),
+    SqrtXXAdj(usize, usize),
+    SqrtYY(usize, usize),
+    SqrtYYAdj(usize, usize),
+    SqrtZZ(usize, usize),
+    SqrtZZAdj(usize, usize),
+    M(usize),
+    MResetZ(usize),
+    Reset(usize),
+}
+
+impl CliffordOp {
+    /// The name of the operation in the Stim circuit format.
+    #[must_use]
+    pub fn stim_name(self) -> &'static str {
+        match self {
+            CliffordOp::H(_) => "H",
+            CliffordOp::S(_) => "S",
+            CliffordOp::Sadj(_) => "S_DAG",
+            CliffordOp::SqrtX(_) => "SQRT_X",
+            CliffordOp::SqrtXAdj(_) => "SQRT_X_DAG",
+            CliffordOp::SqrtY(_) => "SQRT_Y",
+            CliffordOp::SqrtYAdj(_) => "SQRT_Y_DAG",
+            CliffordOp::X(_) => "X",
+            CliffordOp::Y(_) => "Y",
+            CliffordOp::Z(_) => "Z",
+            CliffordOp::Cx(..) => "CX",
+            CliffordOp::Cy(..) => "CY",
+            CliffordOp::Cz(..) => "CZ",
+            CliffordOp::Swap(..) => "SWAP",
+            CliffordOp::SqrtXX(..) => "SQRT_XX",
+            CliffordOp::SqrtXXAdj(..) => "SQRT_XX_DAG",
+            CliffordOp::SqrtYY(..) => "SQRT_YY",
+            CliffordOp::SqrtYYAdj(..) => "SQRT_YY_DAG",
+            CliffordOp::SqrtZZ(..) => "SQRT_ZZ",
+            CliffordOp::SqrtZZAdj(..) => "SQRT_ZZ_DAG",
+            CliffordOp::M(_) => "M",
+            CliffordOp::MResetZ(_) => "MR",
+            CliffordOp::Reset(_) => "R",
+        }
+    }
+
+    /// The qubits the operation acts on.
+    #[must_use]
+    pub fn qubits(self) -> Vec<usize> {
+        match self {
+            CliffordOp::H(q)
+            | CliffordOp::S(q)
+            | CliffordOp::Sadj(q)
+            | CliffordOp::SqrtX(q)
+            | CliffordOp::SqrtXAdj(q)
+            | CliffordOp::SqrtY(q)
+            | CliffordOp::SqrtYAdj(q)
+            | CliffordOp::X(q)
+            | CliffordOp::Y(q)
+            | CliffordOp::Z(q)
+            | CliffordOp::M(q)
+            | CliffordOp::MResetZ(q)
+            | CliffordOp::Reset(q) => vec![q],
+            CliffordOp::Cx(q0, q1)
+            | CliffordOp::Cy(q0, q1)
+            | CliffordOp::Cz(q0, q1)
+            | CliffordOp::Swap(q0, q1)
+            | CliffordOp::SqrtXX(q0, q1)
+            | CliffordOp::SqrtXXAdj(q0, q1)
+            | CliffordOp::SqrtYY(q0, q1)
+            | CliffordOp::SqrtYYAdj(q0, q1)
+            | CliffordOp::SqrtZZ(q0, q1)
+            | CliffordOp::SqrtZZAdj(q0, q1) => vec![q0, q1],
+        }
+    }
+
+    /// Applies the operation to `backend`, up to global phase, returning the result if it is a
+    /// measurement.
+    pub fn apply<B: Backend>(self, backend: &mut B) -> Option<B::ResultType> {
+        match self {
+            CliffordOp::H(q) => backend.h(q),
+            CliffordOp::S(q) => backend.s(q),
+            CliffordOp::Sadj(q) => backend.sadj(q),
+            CliffordOp::SqrtX(q) => backend.rx(FRAC_PI_2, q),
+            CliffordOp::SqrtXAdj(q) => backend.rx(-FRAC_PI_2, q),
+            CliffordOp::SqrtY(q) => backend.ry(FRAC_PI_2, q),
+            CliffordOp::SqrtYAdj(q) => backend.ry(-FRAC_PI_2, q),
+            CliffordOp::X(q) => backend.x(q),
+            CliffordOp::Y(q) => backend.y(q),
+            CliffordOp::Z(q) => backend.z(q),
+            CliffordOp::Cx(q0, q1) => backend.cx(q0, q1),
+            CliffordOp::Cy(q0, q1) => backend.cy(q0, q1),
+            CliffordOp::Cz(q0, q1) => backend.cz(q0, q1),
+            CliffordOp::Swap(q0, q1) => backend.swap(q0, q1),
+            CliffordOp::SqrtXX(q0, q1) => backend.rxx(FRAC_PI_2, q0, q1),
+            CliffordOp::SqrtXXAdj(q0, q1) => backend.rxx(-FRAC_PI_2, q0, q1),
+            CliffordOp::SqrtYY(q0, q1) => backend.ryy(FRAC_PI_2, q0, q1),
+            CliffordOp::SqrtYYAdj(q0, q1) => backend.ryy(-FRAC_PI_2, q0, q1),
+            CliffordOp::SqrtZZ(q0, q1) => backend.rzz(FRAC_PI_2, q0, q1),
+            CliffordOp::SqrtZZAdj(q0, q1) => backend.rzz(-FRAC_PI_2, q0, q1),
+            CliffordOp::M(q) => return Some(backend.m(q)),
+            CliffordOp::MResetZ(q) => return Some(backend.mresetz(q)),
+            CliffordOp::Reset(q) => backend.reset(q),
+        }
+        None
+    }
+}
+
+/// Writes the operations as a Stim circuit, with one instruction per line.
+#[must_use]
+pub fn to_stim(ops: &[CliffordOp]) -> String {
+    let mut circuit = String::new();
+    for op in ops {
+        circuit.push_str(op.stim_name());
+        for q in op.qubits() {
+            write!(circuit, " {q}").expect("writing to a string should succeed");
+        }
+        circuit.push('\n');
+    }
+    circuit
+}
+
+/// An error in a Stim circuit that cannot be imported.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("invalid Stim circuit on line {line}: {message}")]
+pub struct StimParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parses a Stim circuit into Clifford operations.
+///
+/// `REPEAT` blocks are unrolled, and annotations such as `TICK`, `DETECTOR`, and
+/// `OBSERVABLE_INCLUDE` are ignored since they do not affect the operations. Noise channels,
+/// classically controlled gates, and inverted measurement targets are not supported.
+/// # Errors
+/// Returns an error if the circuit contains an unsupported or malformed instruction.
+pub fn from_stim(circuit: &str) -> Result<Vec<CliffordOp>, StimParseError> {
+    let lines = circuit.lines().enumerate().collect::<Vec<_>>();
+    let mut ops = Vec::new();
+    let rest = parse_block(&lines, &mut ops)?;
+    if let Some(&(index, _)) = rest.first() {
+        return Err(StimParseError {
+            line: index + 1,
+            message: "unmatched `}`".to_string(),
+        });
+    }
+    Ok(ops)
+}
+
+/// Parses lines until the end of the circuit or a closing brace, returning the remaining lines
+/// starting at the closing brace.
+fn parse_block<'a, 'b>(
+    mut lines: &'a [(usize, &'b str)],
+    ops: &mut Vec<CliffordOp>,
+) -> Result<&'a [(usize, &'b str)], StimParseError> {
+    while let Some((&(index, line), rest)) = lines.split_first() {
+        let error = |message: String| StimParseError {
+            line: index + 1,
+            message,
+        };
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line == "}" {
+            return Ok(lines);
+        }
+        lines = rest;
+        let mut tokens = line.split_whitespace();
+        let Some(name) = tokens.next() else {
+            continue;
+        };
+        // Parenthesized arguments, such as coordinates or noise probabilities, follow the name.
+        let (name, has_args) = match name.split_once('(') {
+            Some((name, _)) => (name.to_uppercase(), true),
+            None => (name.to_uppercase(), false),
+        };
+
+        if name == "REPEAT" {
+            let count = tokens
+                .next()
+                .and_then(|count| count.parse::<usize>().ok())
+                .ok_or_else(|| error("`REPEAT` requires a repetition count".to_string()))?;
+            if tokens.next() != Some("{") {
+                return Err(error("`REPEAT` must be followed by `{`".to_string()));
+            }
+            let mut body = Vec::new();
+            lines = parse_block(lines, &mut body)?;
+            let Some((_, rest)) = lines.split_first() else {
+                return Err(error("`REPEAT` block is not closed".to_string()));
+            };
+            lines = rest;
+            for _ in 0..count {
+                ops.extend_from_slice(&body);
+            }
+            continue;
+        }
+
+        if matches!(
+            name.as_str(),
+            "TICK" | "DETECTOR" | "OBSERVABLE_INCLUDE" | "QUBIT_COORDS" | "SHIFT_COORDS"
+        ) {
+            continue;
+        }
+
+        let single_qubit = single_qubit_op(&name);
+        let two_qubit = two_qubit_op(&name);
+        if single_qubit.is_none() && two_qubit.is_none() {
+            return Err(error(format!("unsupported instruction `{name}`")));
+        }
+        if has_args {
+            return Err(error(format!("unsupported arguments for `{name}`")));
+        }
+        let targets = tokens
+            .map(|target| {
+                target
+                    .parse::<usize>()
+                    .map_err(|_| error(format!("unsupported target `{target}` for `{name}`")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(op) = single_qubit {
+            ops.extend(targets.into_iter().map(op));
+        } else if let Some(op) = two_qubit {
+            if targets.len() % 2 != 0 {
+                return Err(error(format!(
+                    "`{name}` requires an even number of targets"
+                )));
+            }
+            ops.extend(targets.chunks_exact(2).map(|pair| op(pair[0], pair[1])));
+        }
+    }
+    Ok(lines)
+}
+
+fn single_qubit_op(name: &str) -> Option<fn(usize) -> CliffordOp> {
+    Some(match name {
+        "H" | "H_XZ" => CliffordOp::H,
+        "S" | "SQRT_Z" => CliffordOp::S,
+        "S_DAG" | "SQRT_Z_DAG" => CliffordOp::Sadj,
+        "SQRT_X" => CliffordOp::SqrtX,
+        "SQRT_X_DAG" => CliffordOp::SqrtXAdj,
+        "SQRT_Y" => CliffordOp::SqrtY,
+        "SQRT_Y_DAG" => CliffordOp::SqrtYAdj,
+        "X" => CliffordOp::X,
+        "Y" => CliffordOp::Y,
+        "Z" => CliffordOp::Z,
+        "M" | "MZ" => CliffordOp::M,
+        "MR" | "MRZ" => CliffordOp::MResetZ,
+        "R" | "RZ" => CliffordOp::Reset,
+        _ => return None,
+    })
+}
+
+fn two_qubit_op(name: &str) -> Option<fn(usize, usize) -> CliffordOp> {
+    Some(match name {
+        "CX" | "CNOT" | "ZCX" => CliffordOp::Cx,
+        "CY" | "ZCY" => CliffordOp::Cy,
+        "CZ" | "ZCZ" => CliffordOp::Cz,
+        "SWAP" => CliffordOp::Swap,
+        "SQRT_XX" => CliffordOp::SqrtXX,
+        "SQRT_XX_DAG" => CliffordOp::SqrtXXAdj,
+        "SQRT_YY" => CliffordOp::SqrtYY,
+        "SQRT_YY_DAG" => CliffordOp::SqrtYYAdj,
+        "SQRT_ZZ" => CliffordOp::SqrtZZ,
+        "SQRT_ZZ_DAG" => CliffordOp::SqrtZZAdj,
+        _ => return None,
+    })
+}
+
+/// Backend wrapper that records the Clifford operations and measurements applied to the inner
+/// backend, so that the execution can be exported as a Stim circuit with [`to_stim`].
+///
+/// Rotations by multiples of π/2 are recorded as the equivalent Clifford gates up to global phase.
+/// Any other gate fails the program, since it cannot be represented in the trace.
+pub struct CliffordTrace<B: Backend> {
+    inner: B,
+    ops: Vec<CliffordOp>,
+    error: Option<String>,
+}
+
+impl<B: Backend> CliffordTrace<B> {
+    #[must_use]
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            ops: Vec::new(),
+            error: None,
+        }
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// The operations recorded so far.
+    #[must_use]
+    pub fn ops(&self) -> &[CliffordOp] {
+        &self.ops
+    }
+
+    /// The operations recorded so far as a Stim circuit.
+    #[must_use]
+    pub fn to_stim(&self) -> String {
+        to_stim(&self.ops)
+    }
+
+    fn non_clifford(&mut self, name: &str) {
+        self.error.get_or_insert_with(|| {
+            format!("`{name}` is not a Clifford operation and cannot be recorded")
+        });
+    }
+
+    /// Records a rotation by `theta`, given the operations for rotations by π/2, π, and 3π/2.
+    fn rotation(
+        &mut self,
+        name: &str,
+        theta: f64,
+        quarter: &[CliffordOp],
+        half: &[CliffordOp],
+        three_quarters: &[CliffordOp],
+    ) {
+        let multiple = (theta / FRAC_PI_2).round();
+        if (multiple * FRAC_PI_2 - theta).abs() > 1e-10 {
+            self.non_clifford(name);
+            return;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let ops = match (multiple as i64).rem_euclid(4) {
+            1 => quarter,
+            2 => half,
+            3 => three_quarters,
+            _ => &[],
+        };
+        self.ops.extend_from_slice(ops);
+    }
+}
+
+impl<B: Backend> Backend for CliffordTrace<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.non_clifford("CCNOT");
+        self.inner.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.ops.push(CliffordOp::Cx(ctl, q));
+        self.inner.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.ops.push(CliffordOp::Cy(ctl, q));
+        self.inner.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.ops.push(CliffordOp::Cz(ctl, q));
+        self.inner.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.ops.push(CliffordOp::H(q));
+        self.inner.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.ops.push(CliffordOp::M(q));
+        self.inner.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.ops.push(CliffordOp::MResetZ(q));
+        self.inner.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.ops.push(CliffordOp::Reset(q));
+        self.inner.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.rotation(
+            "Rx",
+            theta,
+            &[CliffordOp::SqrtX(q)],
+            &[CliffordOp::X(q)],
+            &[CliffordOp::SqrtXAdj(q)],
+        );
+        self.inner.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation(
+            "Rxx",
+            theta,
+            &[CliffordOp::SqrtXX(q0, q1)],
+            &[CliffordOp::X(q0), CliffordOp::X(q1)],
+            &[CliffordOp::SqrtXXAdj(q0, q1)],
+        );
+        self.inner.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.rotation(
+            "Ry",
+            theta,
+            &[CliffordOp::SqrtY(q)],
+            &[CliffordOp::Y(q)],
+            &[CliffordOp::SqrtYAdj(q)],
+        );
+        self.inner.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation(
+            "Ryy",
+            theta,
+            &[CliffordOp::SqrtYY(q0, q1)],
+            &[CliffordOp::Y(q0), CliffordOp::Y(q1)],
+            &[CliffordOp::SqrtYYAdj(q0, q1)],
+        );
+        self.inner.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.rotation(
+            "Rz",
+            theta,
+            &[CliffordOp::S(q)],
+            &[CliffordOp::Z(q)],
+            &[CliffordOp::Sadj(q)],
+        );
+        self.inner.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation(
+            "Rzz",
+            theta,
+            &[CliffordOp::SqrtZZ(q0, q1)],
+            &[CliffordOp::Z(q0), CliffordOp::Z(q1)],
+            &[CliffordOp::SqrtZZAdj(q0, q1)],
+        );
+        self.inner.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.ops.push(CliffordOp::Sadj(q));
+        self.inner.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.ops.push(CliffordOp::S(q));
+        self.inner.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.ops.push(CliffordOp::Swap(q0, q1));
+        self.inner.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.non_clifford("Adjoint T");
+        self.inner.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.non_clifford("T");
+        self.inner.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.ops.push(CliffordOp::X(q));
+        self.inner.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.ops.push(CliffordOp::Y(q));
+        self.inner.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.ops.push(CliffordOp::Z(q));
+        self.inner.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.inner.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.inner.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        self.inner.dump_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.inner.qubit_is_zero(q)
+    }
+
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        self.inner.pauli_expectation(paulis, qubits)
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        self.inner.basis_state_amplitude(qubits, basis_state)
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        self.non_clifford("ApplyUnitary");
+        self.inner.apply_unitary(matrix, qubits)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        self.inner.take_warnings()
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take().or_else(|| self.inner.take_error())
+    }
+}
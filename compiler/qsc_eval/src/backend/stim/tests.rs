@@ -0,0 +1,148 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{from_stim, to_stim, CliffordOp, CliffordTrace, StimParseError};
+use crate::backend::{Backend, SparseSim};
+use expect_test::expect;
+use indoc::indoc;
+use std::f64::consts::PI;
+
+#[test]
+fn trace_is_exported_as_stim() {
+    let mut sim = CliffordTrace::new(SparseSim::new());
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+    sim.rz(-PI / 2.0, q1);
+    sim.rzz(PI, q0, q1);
+    sim.m(q0);
+    sim.mresetz(q1);
+    sim.reset(q0);
+    assert_eq!(sim.take_error(), None);
+    expect![[r"
+        H 0
+        CX 0 1
+        S_DAG 1
+        Z 0
+        Z 1
+        M 0
+        MR 1
+        R 0
+    "]]
+    .assert_eq(&sim.to_stim());
+}
+
+#[test]
+fn non_clifford_gate_is_an_error() {
+    let mut sim = CliffordTrace::new(SparseSim::new());
+    let q = sim.qubit_allocate();
+    sim.rx(PI / 2.0, q);
+    sim.t(q);
+    sim.ry(0.1, q);
+    assert_eq!(
+        sim.take_error().as_deref(),
+        Some("`T` is not a Clifford operation and cannot be recorded")
+    );
+    assert_eq!(sim.ops(), &[CliffordOp::SqrtX(q)]);
+}
+
+#[test]
+fn syndrome_extraction_circuit_is_imported() {
+    let ops = from_stim(indoc! {"
+        # Repetition code with two data qubits and one ancilla.
+        QUBIT_COORDS(0) 0
+        R 0 1 2
+        TICK
+        REPEAT 2 {
+            CNOT 0 2 1 2
+            MR 2  # syndrome
+            DETECTOR(1, 0) rec[-1]
+        }
+        M 0 1
+        OBSERVABLE_INCLUDE(0) rec[-1]
+    "})
+    .expect("circuit should parse");
+    assert_eq!(
+        ops,
+        vec![
+            CliffordOp::Reset(0),
+            CliffordOp::Reset(1),
+            CliffordOp::Reset(2),
+            CliffordOp::Cx(0, 2),
+            CliffordOp::Cx(1, 2),
+            CliffordOp::MResetZ(2),
+            CliffordOp::Cx(0, 2),
+            CliffordOp::Cx(1, 2),
+            CliffordOp::MResetZ(2),
+            CliffordOp::M(0),
+            CliffordOp::M(1),
+        ]
+    );
+}
+
+#[test]
+fn export_round_trips_through_import() {
+    let ops = vec![
+        CliffordOp::H(0),
+        CliffordOp::SqrtYAdj(1),
+        CliffordOp::Cz(1, 0),
+        CliffordOp::SqrtXX(0, 1),
+        CliffordOp::Swap(0, 1),
+        CliffordOp::M(1),
+    ];
+    assert_eq!(from_stim(&to_stim(&ops)), Ok(ops));
+}
+
+#[test]
+fn unsupported_instructions_are_errors() {
+    assert_eq!(
+        from_stim("H 0\nX_ERROR(0.1) 0\n"),
+        Err(StimParseError {
+            line: 2,
+            message: "unsupported instruction `X_ERROR`".to_string(),
+        })
+    );
+    assert_eq!(
+        from_stim("M(0.01) 0"),
+        Err(StimParseError {
+            line: 1,
+            message: "unsupported arguments for `M`".to_string(),
+        })
+    );
+    assert_eq!(
+        from_stim("M !0"),
+        Err(StimParseError {
+            line: 1,
+            message: "unsupported target `!0` for `M`".to_string(),
+        })
+    );
+    assert_eq!(
+        from_stim("CX 0 1 2"),
+        Err(StimParseError {
+            line: 1,
+            message: "`CX` requires an even number of targets".to_string(),
+        })
+    );
+    assert_eq!(
+        from_stim("REPEAT 2 {\nH 0\n"),
+        Err(StimParseError {
+            line: 1,
+            message: "`REPEAT` block is not closed".to_string(),
+        })
+    );
+}
+
+#[test]
+fn imported_ops_are_replayed_on_backend() {
+    let ops = from_stim("X 0\nCX 0 1\nSQRT_X 2\nSQRT_X 2\nM 0 1 2").expect("circuit should parse");
+    let mut sim = SparseSim::new();
+    for _ in 0..3 {
+        sim.qubit_allocate();
+    }
+    let results = ops
+        .into_iter()
+        .filter_map(|op| op.apply(&mut sim))
+        .collect::<Vec<_>>();
+    assert_eq!(results, vec![true, true, true]);
+}
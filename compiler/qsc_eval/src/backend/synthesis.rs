@@ -0,0 +1,600 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::output::StateDump;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use qsc_fir::fir::Pauli;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    f64::consts::{FRAC_1_SQRT_2, PI},
+    rc::Rc,
+};
+
+/// Maximum recursion depth of the Solovay-Kitaev algorithm. Each level roughly multiplies the
+/// sequence length by five, so deeper levels quickly become impractical for simulation.
+const MAX_DEPTH: usize = 4;
+
+/// Maximum length of the Clifford+T words used as the base approximations.
+const BASE_WORD_LENGTH: usize = 14;
+
+/// A gate of the Clifford+T gate set emitted by the synthesis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CliffordT {
+    H,
+    S,
+    Sadj,
+    T,
+    Tadj,
+    Z,
+}
+
+impl CliffordT {
+    fn adjoint(self) -> Self {
+        match self {
+            Self::H => Self::H,
+            Self::S => Self::Sadj,
+            Self::Sadj => Self::S,
+            Self::T => Self::Tadj,
+            Self::Tadj => Self::T,
+            Self::Z => Self::Z,
+        }
+    }
+
+    /// The phase of the gate as a multiple of π/4, if it is diagonal.
+    fn phase(self) -> Option<u8> {
+        match self {
+            Self::H => None,
+            Self::T => Some(1),
+            Self::S => Some(2),
+            Self::Z => Some(4),
+            Self::Sadj => Some(6),
+            Self::Tadj => Some(7),
+        }
+    }
+
+    fn matrix(self) -> Mat {
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        if let Some(phase) = self.phase() {
+            let phase = f64::from(phase) * PI / 4.0;
+            [[one, zero], [zero, Complex64::from_polar(1.0, phase)]]
+        } else {
+            let h = Complex64::new(FRAC_1_SQRT_2, 0.0);
+            [[h, h], [h, -h]]
+        }
+    }
+}
+
+/// Overhead of the rotations synthesized into Clifford+T sequences.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SynthesisOverhead {
+    /// Number of rotations that were synthesized
+    pub rotation_count: usize,
+    /// Number of T and adjoint T gates emitted for the rotations
+    pub t_count: usize,
+    /// Number of Clifford gates emitted for the rotations
+    pub clifford_count: usize,
+    /// Largest approximation error of a synthesized rotation, measured as the distance between the
+    /// requested rotation and the emitted sequence up to global phase
+    pub max_error: f64,
+}
+
+/// Backend wrapper that decomposes single-qubit rotations into Clifford+T sequences before
+/// forwarding them to the inner backend.
+///
+/// Rotations by multiples of π/4 are decomposed exactly. All other rotations are approximated with
+/// the Solovay-Kitaev algorithm, increasing the recursion depth until the requested precision is
+/// reached or the maximum depth is exhausted. The largest error actually achieved is reported as
+/// part of the [`SynthesisOverhead`].
+pub struct RotationSynthesizer<B: Backend> {
+    inner: B,
+    precision: f64,
+    overhead: SynthesisOverhead,
+    base: Vec<(Vec<CliffordT>, Mat)>,
+    cache: FxHashMap<u64, (Rc<[CliffordT]>, f64)>,
+}
+
+impl<B: Backend> RotationSynthesizer<B> {
+    /// Creates a wrapper around `inner` that synthesizes rotations with an approximation error of
+    /// at most `precision`, where possible.
+    #[must_use]
+    pub fn new(inner: B, precision: f64) -> Self {
+        Self {
+            inner,
+            precision,
+            overhead: SynthesisOverhead::default(),
+            base: Vec::new(),
+            cache: FxHashMap::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn overhead(&self) -> &SynthesisOverhead {
+        &self.overhead
+    }
+
+    #[must_use]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the Clifford+T sequence, in application order, that implements `Rz(theta)` up to
+    /// global phase, along with its approximation error.
+    pub fn synthesize_rz(&mut self, theta: f64) -> (Rc<[CliffordT]>, f64) {
+        if let Some(entry) = self.cache.get(&theta.to_bits()) {
+            return entry.clone();
+        }
+
+        let multiple = (theta / (PI / 4.0)).round();
+        let entry = if ((multiple * (PI / 4.0)) - theta).abs() <= f64::EPSILON {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            // The multiple is reduced modulo 8 before narrowing
+            let phase = (multiple as i64).rem_euclid(8) as u8;
+            (phase_gates(phase).into(), 0.0)
+        } else {
+            let target = rz_matrix(theta);
+            if self.base.is_empty() {
+                self.base = base_approximations();
+            }
+            let mut best = (Vec::new(), f64::INFINITY);
+            for depth in 0..=MAX_DEPTH {
+                let (word, matrix) = solovay_kitaev(&self.base, &target, depth);
+                let error = distance(&matrix, &target);
+                if error < best.1 {
+                    best = (word, error);
+                }
+                if best.1 <= self.precision {
+                    break;
+                }
+            }
+            (simplify(&best.0).into(), best.1)
+        };
+
+        self.cache.insert(theta.to_bits(), entry.clone());
+        entry
+    }
+
+    fn apply_rz(&mut self, theta: f64, q: usize) {
+        let (word, error) = self.synthesize_rz(theta);
+        self.overhead.rotation_count += 1;
+        self.overhead.max_error = self.overhead.max_error.max(error);
+        for gate in word.iter() {
+            if matches!(gate, CliffordT::T | CliffordT::Tadj) {
+                self.overhead.t_count += 1;
+            } else {
+                self.overhead.clifford_count += 1;
+            }
+            match gate {
+                CliffordT::H => self.inner.h(q),
+                CliffordT::S => self.inner.s(q),
+                CliffordT::Sadj => self.inner.sadj(q),
+                CliffordT::T => self.inner.t(q),
+                CliffordT::Tadj => self.inner.tadj(q),
+                CliffordT::Z => self.inner.z(q),
+            }
+        }
+    }
+}
+
+impl<B: Backend> Backend for RotationSynthesizer<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.inner.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.inner.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.inner.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.inner.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.inner.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.inner.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.inner.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.inner.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.inner.h(q);
+        self.apply_rz(theta, q);
+        self.inner.h(q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.inner.h(q0);
+        self.inner.h(q1);
+        self.rzz(theta, q0, q1);
+        self.inner.h(q1);
+        self.inner.h(q0);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.inner.sadj(q);
+        self.inner.h(q);
+        self.apply_rz(theta, q);
+        self.inner.h(q);
+        self.inner.s(q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.inner.sadj(q0);
+        self.inner.h(q0);
+        self.inner.sadj(q1);
+        self.inner.h(q1);
+        self.rzz(theta, q0, q1);
+        self.inner.h(q1);
+        self.inner.s(q1);
+        self.inner.h(q0);
+        self.inner.s(q0);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.apply_rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.inner.cx(q1, q0);
+        self.apply_rz(theta, q0);
+        self.inner.cx(q1, q0);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.inner.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.inner.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.inner.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.inner.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.inner.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.inner.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.inner.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.inner.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.inner.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.inner.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn dump_quantum_state(&mut self) -> StateDump<'_> {
+        self.inner.dump_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.inner.qubit_is_zero(q)
+    }
+
+    fn pauli_expectation(&mut self, paulis: &[Pauli], qubits: &[usize]) -> Option<f64> {
+        self.inner.pauli_expectation(paulis, qubits)
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &BigUint,
+    ) -> Option<Result<Complex64, String>> {
+        self.inner.basis_state_amplitude(qubits, basis_state)
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        self.inner.apply_unitary(matrix, qubits)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        self.inner.take_warnings()
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.inner.take_error()
+    }
+}
+
+type Mat = [[Complex64; 2]; 2];
+
+fn mul(a: &Mat, b: &Mat) -> Mat {
+    [
+        [
+            a[0][0] * b[0][0] + a[0][1] * b[1][0],
+            a[0][0] * b[0][1] + a[0][1] * b[1][1],
+        ],
+        [
+            a[1][0] * b[0][0] + a[1][1] * b[1][0],
+            a[1][0] * b[0][1] + a[1][1] * b[1][1],
+        ],
+    ]
+}
+
+fn adjoint(a: &Mat) -> Mat {
+    [
+        [a[0][0].conj(), a[1][0].conj()],
+        [a[0][1].conj(), a[1][1].conj()],
+    ]
+}
+
+fn identity() -> Mat {
+    let zero = Complex64::new(0.0, 0.0);
+    let one = Complex64::new(1.0, 0.0);
+    [[one, zero], [zero, one]]
+}
+
+/// Distance between two unitaries up to global phase, which is zero if and only if the unitaries
+/// are equal up to global phase.
+fn distance(a: &Mat, b: &Mat) -> f64 {
+    let product = mul(&adjoint(a), b);
+    let trace = product[0][0] + product[1][1];
+    (1.0 - trace.norm() / 2.0).max(0.0).sqrt()
+}
+
+fn rz_matrix(theta: f64) -> Mat {
+    let zero = Complex64::new(0.0, 0.0);
+    [
+        [Complex64::from_polar(1.0, -theta / 2.0), zero],
+        [zero, Complex64::from_polar(1.0, theta / 2.0)],
+    ]
+}
+
+/// Returns the rotation `exp(-iθ/2 n·σ)` about the unit `axis` by angle `theta`.
+fn rotation(axis: [f64; 3], theta: f64) -> Mat {
+    let (sin, cos) = (theta / 2.0).sin_cos();
+    let [x, y, z] = axis;
+    [
+        [
+            Complex64::new(cos, -sin * z),
+            Complex64::new(-sin * y, -sin * x),
+        ],
+        [
+            Complex64::new(sin * y, -sin * x),
+            Complex64::new(cos, sin * z),
+        ],
+    ]
+}
+
+/// Returns the axis and angle of the given unitary, interpreted as a rotation in SU(2) with
+/// non-negative trace.
+fn axis_angle(a: &Mat) -> ([f64; 3], f64) {
+    let det = a[0][0] * a[1][1] - a[0][1] * a[1][0];
+    let phase = det.sqrt();
+    let mut a = a.map(|row| row.map(|entry| entry / phase));
+    if (a[0][0] + a[1][1]).re < 0.0 {
+        a = a.map(|row| row.map(|entry| -entry));
+    }
+    let trace = a[0][0] + a[1][1];
+    let cos = (trace.re / 2.0).clamp(-1.0, 1.0);
+    let x = -(a[0][1].im + a[1][0].im) / 2.0;
+    let y = (a[1][0].re - a[0][1].re) / 2.0;
+    let z = (a[1][1].im - a[0][0].im) / 2.0;
+    let sin = (x * x + y * y + z * z).sqrt();
+    if sin <= f64::EPSILON {
+        ([0.0, 0.0, 1.0], 0.0)
+    } else {
+        ([x / sin, y / sin, z / sin], 2.0 * sin.atan2(cos))
+    }
+}
+
+/// Decomposes the given unitary into a balanced group commutator `V W V† W†` up to global phase,
+/// following Dawson and Nielsen.
+fn group_commutator_decompose(a: &Mat) -> (Mat, Mat) {
+    let (axis, theta) = axis_angle(a);
+    let phi = 2.0 * ((1.0 - (theta / 2.0).cos()) / 2.0).powf(0.25).asin();
+    let v = rotation([1.0, 0.0, 0.0], phi);
+    let w = rotation([0.0, 1.0, 0.0], phi);
+    let commutator = mul(&mul(&v, &w), &mul(&adjoint(&v), &adjoint(&w)));
+    let (commutator_axis, _) = axis_angle(&commutator);
+
+    // Find the rotation that maps the axis of the commutator onto the axis of the target.
+    let [ax, ay, az] = commutator_axis;
+    let [bx, by, bz] = axis;
+    let cross = [ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx];
+    let dot = (ax * bx + ay * by + az * bz).clamp(-1.0, 1.0);
+    let cross_norm = cross.iter().map(|c| c * c).sum::<f64>().sqrt();
+    let similarity = if cross_norm <= f64::EPSILON {
+        if dot > 0.0 {
+            identity()
+        } else {
+            // The axes are antiparallel, so rotate by π about any perpendicular axis.
+            let perpendicular = if ax.abs() < 0.9 {
+                [0.0, -az, ay]
+            } else {
+                [-ay, ax, 0.0]
+            };
+            let norm = perpendicular.iter().map(|c| c * c).sum::<f64>().sqrt();
+            rotation(perpendicular.map(|c| c / norm), PI)
+        }
+    } else {
+        rotation(cross.map(|c| c / cross_norm), dot.acos())
+    };
+
+    let similarity_adj = adjoint(&similarity);
+    (
+        mul(&mul(&similarity, &v), &similarity_adj),
+        mul(&mul(&similarity, &w), &similarity_adj),
+    )
+}
+
+/// Approximates the target unitary with a Clifford+T word using the Solovay-Kitaev algorithm with
+/// the given recursion depth. Words are in application order and are returned with their matrix.
+fn solovay_kitaev(
+    base: &[(Vec<CliffordT>, Mat)],
+    target: &Mat,
+    depth: usize,
+) -> (Vec<CliffordT>, Mat) {
+    if depth == 0 {
+        return base
+            .iter()
+            .min_by(|(_, a), (_, b)| distance(a, target).total_cmp(&distance(b, target)))
+            .cloned()
+            .expect("base approximations should not be empty");
+    }
+
+    let (prev_word, prev) = solovay_kitaev(base, target, depth - 1);
+    let (v, w) = group_commutator_decompose(&mul(target, &adjoint(&prev)));
+    let (v_word, v) = solovay_kitaev(base, &v, depth - 1);
+    let (w_word, w) = solovay_kitaev(base, &w, depth - 1);
+
+    // The matrix V W V† W† U is applied right to left, so the word is built in reverse.
+    let matrix = mul(&mul(&mul(&v, &w), &mul(&adjoint(&v), &adjoint(&w))), &prev);
+    let word = prev_word
+        .into_iter()
+        .chain(adjoint_word(&w_word))
+        .chain(adjoint_word(&v_word))
+        .chain(w_word.iter().copied())
+        .chain(v_word.iter().copied())
+        .collect();
+    (word, matrix)
+}
+
+fn adjoint_word(word: &[CliffordT]) -> impl Iterator<Item = CliffordT> + '_ {
+    word.iter().rev().map(|gate| gate.adjoint())
+}
+
+/// Enumerates the distinct unitaries, up to global phase, reachable with short words over H and T.
+fn base_approximations() -> Vec<(Vec<CliffordT>, Mat)> {
+    let mut seen = FxHashSet::default();
+    seen.insert(key(&identity()));
+    let mut base = vec![(Vec::new(), identity())];
+    let mut frontier = base.clone();
+    for _ in 0..BASE_WORD_LENGTH {
+        let mut next = Vec::new();
+        for (word, matrix) in &frontier {
+            for gate in [CliffordT::H, CliffordT::T] {
+                let matrix = mul(&gate.matrix(), matrix);
+                if seen.insert(key(&matrix)) {
+                    let mut word = word.clone();
+                    word.push(gate);
+                    next.push((word, matrix));
+                }
+            }
+        }
+        base.extend(next.iter().cloned());
+        frontier = next;
+    }
+    base
+}
+
+/// Computes a hashable key for the unitary that is invariant under global phase.
+fn key(a: &Mat) -> [i64; 8] {
+    let pivot = a
+        .iter()
+        .flatten()
+        .find(|entry| entry.norm() > 1e-6)
+        .expect("unitary should have a nonzero entry");
+    let phase = pivot.conj() / pivot.norm();
+    let entries = a.map(|row| row.map(|entry| entry * phase));
+    let mut key = [0; 8];
+    #[allow(clippy::cast_possible_truncation)] // Entries of unitaries are bounded by one
+    for (i, entry) in entries.iter().flatten().enumerate() {
+        key[2 * i] = (entry.re * 1e6).round() as i64;
+        key[2 * i + 1] = (entry.im * 1e6).round() as i64;
+    }
+    key
+}
+
+/// Returns the gates implementing the phase `phase · π/4` with at most one T gate.
+fn phase_gates(phase: u8) -> Vec<CliffordT> {
+    match phase % 8 {
+        0 => vec![],
+        1 => vec![CliffordT::T],
+        2 => vec![CliffordT::S],
+        3 => vec![CliffordT::S, CliffordT::T],
+        4 => vec![CliffordT::Z],
+        5 => vec![CliffordT::Z, CliffordT::T],
+        6 => vec![CliffordT::Sadj],
+        _ => vec![CliffordT::Tadj],
+    }
+}
+
+/// Simplifies a word by merging runs of diagonal gates and cancelling adjacent Hadamard gates.
+fn simplify(word: &[CliffordT]) -> Vec<CliffordT> {
+    let mut simplified: Vec<CliffordT> = Vec::new();
+    let mut phase = 0_u8;
+    for gate in word {
+        if let Some(gate_phase) = gate.phase() {
+            phase = (phase + gate_phase) % 8;
+        } else {
+            if phase == 0 && simplified.last() == Some(&CliffordT::H) {
+                simplified.pop();
+                // Cancelling the Hadamard pair may expose a run of diagonal gates to merge with.
+                while let Some(last_phase) = simplified.last().and_then(|gate| gate.phase()) {
+                    phase = (phase + last_phase) % 8;
+                    simplified.pop();
+                }
+                continue;
+            }
+            simplified.extend(phase_gates(phase));
+            phase = 0;
+            simplified.push(*gate);
+        }
+    }
+    simplified.extend(phase_gates(phase));
+    simplified
+}
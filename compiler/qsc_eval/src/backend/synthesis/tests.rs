@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    adjoint, distance, group_commutator_decompose, mul, rotation, simplify, CliffordT,
+    RotationSynthesizer,
+};
+use crate::backend::{Backend, SparseSim};
+use num_complex::Complex64;
+use std::f64::consts::PI;
+
+fn state_overlap(
+    a: &[(num_bigint::BigUint, Complex64)],
+    b: &[(num_bigint::BigUint, Complex64)],
+) -> f64 {
+    a.iter()
+        .map(|(label, amplitude)| {
+            b.iter()
+                .find(|(other, _)| other == label)
+                .map_or(Complex64::new(0.0, 0.0), |(_, other)| {
+                    amplitude.conj() * other
+                })
+        })
+        .sum::<Complex64>()
+        .norm()
+}
+
+#[test]
+fn exact_rotations_are_not_approximated() {
+    let mut synthesizer = RotationSynthesizer::new(SparseSim::new(), 1e-3);
+    let (word, error) = synthesizer.synthesize_rz(PI / 4.0);
+    assert_eq!(&*word, &[CliffordT::T]);
+    assert!(error.abs() <= f64::EPSILON);
+    let (word, _) = synthesizer.synthesize_rz(-PI / 2.0);
+    assert_eq!(&*word, &[CliffordT::Sadj]);
+    let (word, _) = synthesizer.synthesize_rz(5.0 * PI / 4.0);
+    assert_eq!(&*word, &[CliffordT::Z, CliffordT::T]);
+}
+
+#[test]
+fn group_commutator_decomposition_is_accurate() {
+    for (axis, theta) in [
+        ([1.0, 0.0, 0.0], 0.1),
+        ([0.0, 0.0, 1.0], 0.05),
+        ([0.6, 0.0, 0.8], 0.2),
+        ([0.0, -0.6, 0.8], 0.01),
+    ] {
+        let target = rotation(axis, theta);
+        let (v, w) = group_commutator_decompose(&target);
+        let commutator = mul(&mul(&v, &w), &mul(&adjoint(&v), &adjoint(&w)));
+        assert!(distance(&commutator, &target) < 1e-7);
+    }
+}
+
+#[test]
+fn words_are_simplified() {
+    use CliffordT::{Sadj, Tadj, H, S, T, Z};
+    assert_eq!(simplify(&[T, T, H, H, T, S]), vec![Z, T]);
+    assert_eq!(simplify(&[H, Tadj, T, H]), vec![]);
+    assert_eq!(simplify(&[T, H, S, Sadj, H, Tadj, H]), vec![H]);
+}
+
+#[test]
+fn synthesized_rotation_meets_precision() {
+    let precision = 1e-2;
+    let mut synthesizer = RotationSynthesizer::new(SparseSim::new(), precision);
+    let q = synthesizer.qubit_allocate();
+    synthesizer.h(q);
+    synthesizer.rz(1.0, q);
+    synthesizer.ry(-0.3, q);
+    let (actual, _) = synthesizer.capture_quantum_state();
+
+    let mut sim = SparseSim::new();
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    sim.rz(1.0, q);
+    sim.ry(-0.3, q);
+    let (expected, _) = sim.capture_quantum_state();
+
+    let overhead = synthesizer.overhead();
+    assert_eq!(overhead.rotation_count, 2);
+    assert!(overhead.t_count > 0);
+    assert!(overhead.max_error <= precision);
+    assert!(state_overlap(&actual, &expected) > 1.0 - 4.0 * precision);
+}
+
+#[test]
+fn repeated_angles_reuse_sequence() {
+    let mut synthesizer = RotationSynthesizer::new(SparseSim::new(), 1e-2);
+    let q = synthesizer.qubit_allocate();
+    synthesizer.rx(0.7, q);
+    let t_count = synthesizer.overhead().t_count;
+    synthesizer.rx(0.7, q);
+    assert_eq!(synthesizer.overhead().rotation_count, 2);
+    assert_eq!(synthesizer.overhead().t_count, 2 * t_count);
+}
@@ -0,0 +1,52 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Backend, SparseSim};
+use num_bigint::BigUint;
+
+#[test]
+fn dumped_state_matches_captured_state() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q2);
+    sim.x(q1);
+    sim.rx(0.5, q2);
+
+    let (captured, qubit_count) = sim.capture_quantum_state();
+    let mut dump = sim.dump_quantum_state().with_chunk_size(3);
+    assert_eq!(dump.qubit_count(), qubit_count);
+    assert_eq!(dump.total(), captured.len());
+    let mut dumped = Vec::new();
+    while let Some(chunk) = dump.next_chunk() {
+        dumped.extend(chunk.into_iter().map(|entry| (entry.id, entry.amplitude)));
+    }
+    assert_eq!(dumped, captured);
+}
+
+#[test]
+fn dumped_state_is_sorted_with_first_qubit_most_significant() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.h(q2);
+
+    let mut dump = sim.dump_quantum_state();
+    let labels = dump
+        .next_chunk()
+        .expect("state should not be empty")
+        .into_iter()
+        .map(|entry| (entry.id, entry.label))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            (BigUint::from(0b100_u8), "|100⟩".to_string()),
+            (BigUint::from(0b101_u8), "|101⟩".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,221 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use num_complex::Complex64;
+
+/// Tolerance below which amplitudes and angles are treated as zero.
+const TOLERANCE: f64 = 1e-12;
+
+/// A multi-controlled gate produced by [`decompose`]. Qubits are indices into the list of qubits the
+/// unitary acts on, and all controls are conditioned on the one state.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ControlledGate {
+    X(Vec<usize>, usize),
+    Ry(Vec<usize>, f64, usize),
+    Rz(Vec<usize>, f64, usize),
+    /// The phase `e^{iα}` applied to the whole state.
+    GlobalPhase(f64),
+}
+
+/// Decomposes the unitary `matrix` on `qubit_count` qubits into a sequence of multi-controlled
+/// gates, in application order, that implements it exactly, including its global phase. The first
+/// qubit corresponds to the most significant bit of the matrix index.
+///
+/// The matrix is reduced to a diagonal matrix by two-level Givens rotations, each of which is
+/// implemented as a multi-controlled single-qubit gate conjugated by a Gray code sequence of
+/// multi-controlled X gates.
+pub(crate) fn decompose(matrix: &[Vec<Complex64>], qubit_count: usize) -> Vec<ControlledGate> {
+    let dim = matrix.len();
+    let mut reduced = matrix.to_vec();
+    let mut rotations = Vec::new();
+
+    for col in 0..dim {
+        for row in (col + 1)..dim {
+            let lower = reduced[row][col];
+            if lower.norm() <= TOLERANCE {
+                continue;
+            }
+            let diag = reduced[col][col];
+            let norm = (diag.norm_sqr() + lower.norm_sqr()).sqrt();
+            let givens = [
+                [diag.conj() / norm, lower.conj() / norm],
+                [-lower / norm, diag / norm],
+            ];
+            let (upper_rows, lower_rows) = reduced.split_at_mut(row);
+            for (upper_entry, lower_entry) in upper_rows[col].iter_mut().zip(&mut lower_rows[0]) {
+                let (upper_val, lower_val) = (*upper_entry, *lower_entry);
+                *upper_entry = givens[0][0] * upper_val + givens[0][1] * lower_val;
+                *lower_entry = givens[1][0] * upper_val + givens[1][1] * lower_val;
+            }
+            rotations.push((col, row, givens));
+        }
+    }
+
+    // The matrix is now diagonal, so that U = G₁† ⋯ Gₖ† D, which is applied starting with D.
+    let mut gates = Vec::new();
+    for (index, row) in reduced.iter().enumerate() {
+        let phase = row[index].arg();
+        if phase.abs() > TOLERANCE {
+            let values = (0..qubit_count)
+                .map(|q| (q, index_bit(index, q, qubit_count)))
+                .collect::<Vec<_>>();
+            with_control_values(&mut gates, &values, |gates, controls| {
+                controlled_phase(gates, controls, phase);
+            });
+        }
+    }
+    for (col, row, givens) in rotations.into_iter().rev() {
+        let adjoint = [
+            [givens[0][0].conj(), givens[1][0].conj()],
+            [givens[0][1].conj(), givens[1][1].conj()],
+        ];
+        two_level(&mut gates, qubit_count, col, row, adjoint);
+    }
+    gates
+}
+
+/// Returns the value of the given qubit in the basis state with the given index.
+fn index_bit(index: usize, qubit: usize, qubit_count: usize) -> bool {
+    index >> (qubit_count - qubit - 1) & 1 == 1
+}
+
+/// Applies the unitary `u` to the two basis states `s` and `t`, leaving all other basis states
+/// unchanged, where the first row and column of `u` correspond to `s`.
+fn two_level(
+    gates: &mut Vec<ControlledGate>,
+    qubit_count: usize,
+    s: usize,
+    t: usize,
+    u: [[Complex64; 2]; 2],
+) {
+    let differing = (0..qubit_count)
+        .filter(|&q| index_bit(s, q, qubit_count) != index_bit(t, q, qubit_count))
+        .collect::<Vec<_>>();
+    let (&target, path) = differing
+        .split_last()
+        .expect("basis states should be distinct");
+
+    // Walk from s towards t along a Gray code, until the current state differs from t only in the
+    // target qubit.
+    let mut walk = Vec::new();
+    let mut current = s;
+    for &q in path {
+        let values = (0..qubit_count)
+            .filter(|&other| other != q)
+            .map(|other| (other, index_bit(current, other, qubit_count)))
+            .collect::<Vec<_>>();
+        walk.push((values, q));
+        current ^= 1 << (qubit_count - q - 1);
+    }
+    for (values, q) in &walk {
+        with_control_values(gates, values, |gates, controls| {
+            gates.push(ControlledGate::X(controls, *q));
+        });
+    }
+
+    // The target qubit is zero in the current state if the first basis state of u is the zero
+    // state of the target, otherwise the roles of the basis states are swapped.
+    let u = if index_bit(current, target, qubit_count) {
+        [[u[1][1], u[1][0]], [u[0][1], u[0][0]]]
+    } else {
+        u
+    };
+    let values = (0..qubit_count)
+        .filter(|&q| q != target)
+        .map(|q| (q, index_bit(current, q, qubit_count)))
+        .collect::<Vec<_>>();
+    with_control_values(gates, &values, |gates, controls| {
+        controlled_unitary(gates, controls, target, u);
+    });
+
+    for (values, q) in walk.iter().rev() {
+        with_control_values(gates, values, |gates, controls| {
+            gates.push(ControlledGate::X(controls, *q));
+        });
+    }
+}
+
+/// Emits the gates produced by `emit` controlled on the given qubits having the given values, by
+/// conjugating the controls that must be zero with X gates.
+fn with_control_values(
+    gates: &mut Vec<ControlledGate>,
+    values: &[(usize, bool)],
+    emit: impl FnOnce(&mut Vec<ControlledGate>, Vec<usize>),
+) {
+    let flipped = values
+        .iter()
+        .filter(|(_, value)| !value)
+        .map(|(q, _)| *q)
+        .collect::<Vec<_>>();
+    for &q in &flipped {
+        gates.push(ControlledGate::X(Vec::new(), q));
+    }
+    emit(gates, values.iter().map(|(q, _)| *q).collect());
+    for &q in &flipped {
+        gates.push(ControlledGate::X(Vec::new(), q));
+    }
+}
+
+/// Emits the single-qubit unitary `u` on `target`, controlled on `controls`, using the ZYZ
+/// decomposition `u = e^{iα} Rz(β) Ry(γ) Rz(δ)`.
+fn controlled_unitary(
+    gates: &mut Vec<ControlledGate>,
+    controls: Vec<usize>,
+    target: usize,
+    u: [[Complex64; 2]; 2],
+) {
+    let det = u[0][0] * u[1][1] - u[0][1] * u[1][0];
+    let alpha = det.arg() / 2.0;
+    let phase = Complex64::from_polar(1.0, -alpha);
+    let (v10, v11) = (u[1][0] * phase, u[1][1] * phase);
+    let gamma = 2.0 * v10.norm().atan2(v11.norm());
+    let (arg10, arg11) = (
+        if v10.norm() > TOLERANCE {
+            v10.arg()
+        } else {
+            0.0
+        },
+        if v11.norm() > TOLERANCE {
+            v11.arg()
+        } else {
+            0.0
+        },
+    );
+    let (beta, delta) = if v10.norm() <= TOLERANCE {
+        (2.0 * arg11, 0.0)
+    } else if v11.norm() <= TOLERANCE {
+        (2.0 * arg10, 0.0)
+    } else {
+        (arg11 + arg10, arg11 - arg10)
+    };
+
+    if delta.abs() > TOLERANCE {
+        gates.push(ControlledGate::Rz(controls.clone(), delta, target));
+    }
+    if gamma.abs() > TOLERANCE {
+        gates.push(ControlledGate::Ry(controls.clone(), gamma, target));
+    }
+    if beta.abs() > TOLERANCE {
+        gates.push(ControlledGate::Rz(controls.clone(), beta, target));
+    }
+    controlled_phase(gates, controls, alpha);
+}
+
+/// Emits the phase `e^{iα}` controlled on `controls`.
+fn controlled_phase(gates: &mut Vec<ControlledGate>, mut controls: Vec<usize>, mut alpha: f64) {
+    // The phase diag(1, e^{iα}) on the last control equals e^{iα/2} Rz(α), so the remaining
+    // phase is applied recursively on the other controls.
+    while let Some(last) = controls.pop() {
+        if alpha.abs() <= TOLERANCE {
+            return;
+        }
+        gates.push(ControlledGate::Rz(controls.clone(), alpha, last));
+        alpha /= 2.0;
+    }
+    if alpha.abs() > TOLERANCE {
+        gates.push(ControlledGate::GlobalPhase(alpha));
+    }
+}
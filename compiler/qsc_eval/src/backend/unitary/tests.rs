@@ -0,0 +1,76 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::backend::{Backend, SparseSim};
+use num_complex::Complex64;
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+fn c(re: f64, im: f64) -> Complex64 {
+    Complex64::new(re, im)
+}
+
+/// Applies the unitary to every basis state of a fresh register and checks that the resulting
+/// state is the corresponding column of the matrix, including its global phase.
+fn check_unitary(matrix: &[Vec<Complex64>]) {
+    let qubit_count = matrix.len().trailing_zeros() as usize;
+    for col in 0..matrix.len() {
+        let mut sim = SparseSim::new();
+        let qubits = (0..qubit_count)
+            .map(|_| sim.qubit_allocate())
+            .collect::<Vec<_>>();
+        for (i, q) in qubits.iter().enumerate() {
+            if col >> (qubit_count - i - 1) & 1 == 1 {
+                sim.x(*q);
+            }
+        }
+        sim.apply_unitary(matrix, &qubits)
+            .expect("sparse simulator should support custom unitaries")
+            .expect("unitary should be applied");
+        let (state, _) = sim.capture_quantum_state();
+        for (row, entries) in matrix.iter().enumerate() {
+            let amplitude = state
+                .iter()
+                .find(|(label, _)| *label == row.into())
+                .map_or(c(0.0, 0.0), |(_, amplitude)| *amplitude);
+            assert!(
+                (amplitude - entries[col]).norm() < 1e-9,
+                "entry ({row}, {col}) should be {} but was {amplitude}",
+                entries[col]
+            );
+        }
+    }
+}
+
+#[test]
+fn single_qubit_unitary_with_phase() {
+    let h = FRAC_1_SQRT_2;
+    check_unitary(&[vec![c(0.0, h), c(0.0, h)], vec![c(0.0, h), c(0.0, -h)]]);
+}
+
+#[test]
+fn two_qubit_iswap() {
+    let zero = c(0.0, 0.0);
+    let one = c(1.0, 0.0);
+    check_unitary(&[
+        vec![one, zero, zero, zero],
+        vec![zero, zero, c(0.0, 1.0), zero],
+        vec![zero, c(0.0, 1.0), zero, zero],
+        vec![zero, zero, zero, one],
+    ]);
+}
+
+#[test]
+fn three_qubit_fourier_transform() {
+    let dim = 8;
+    let scale = 1.0 / f64::from(dim).sqrt();
+    let matrix = (0..dim)
+        .map(|row| {
+            (0..dim)
+                .map(|col| {
+                    Complex64::from_polar(scale, 2.0 * PI * f64::from(row * col) / f64::from(dim))
+                })
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    check_unitary(&matrix);
+}
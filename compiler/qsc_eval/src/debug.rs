@@ -1,19 +1,106 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+#[cfg(test)]
+mod tests;
+
 use qsc_data_structures::span::Span;
 
+use crate::val::Value;
+use crate::VariableInfo;
 use qsc_data_structures::functors::FunctorApp;
 use qsc_fir::fir;
 use qsc_fir::fir::{PackageId, StoreItemId};
 use qsc_hir::hir;
+use std::fmt::{self, Display, Formatter, Write};
+use std::str::FromStr;
+
+/// The maximum number of values, counting nested array elements, that an array argument may hold
+/// to be captured in a stack frame. Larger arrays are summarized.
+pub const MAX_CAPTURED_ARRAY_SIZE: usize = 16;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Frame {
     pub span: Span,
     pub id: StoreItemId,
     pub caller: PackageId,
     pub functor: FunctorApp,
+    /// The argument bound at call time, if argument capture is enabled.
+    pub arg: Option<FrameArg>,
+}
+
+/// An argument captured in a stack frame.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FrameArg {
+    /// The argument value.
+    Value(Value),
+    /// A tuple argument, some of whose items are summarized.
+    Tuple(Vec<FrameArg>),
+    /// The summary of an array too large to capture, with its element type and length.
+    Summary(&'static str, usize),
+}
+
+impl FrameArg {
+    /// Captures the given argument, summarizing arrays holding more than
+    /// [`MAX_CAPTURED_ARRAY_SIZE`] values.
+    #[must_use]
+    pub fn capture(arg: &Value) -> Self {
+        match arg {
+            Value::Array(arr) if size_exceeds(arg, MAX_CAPTURED_ARRAY_SIZE) => {
+                Self::Summary(arr.first().map_or("Unit", Value::type_name), arr.len())
+            }
+            Value::Tuple(items) if size_exceeds(arg, MAX_CAPTURED_ARRAY_SIZE) => {
+                Self::Tuple(items.iter().map(Self::capture).collect())
+            }
+            Value::Udt(_, value) if size_exceeds(arg, MAX_CAPTURED_ARRAY_SIZE) => {
+                Self::capture(value)
+            }
+            _ => Self::Value(arg.clone()),
+        }
+    }
+}
+
+impl Display for FrameArg {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Value(value) => value.fmt(f),
+            Self::Tuple(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.fmt(f)?;
+                }
+                write!(f, ")")
+            }
+            Self::Summary(type_name, len) => write!(f, "{type_name}[{len}]"),
+        }
+    }
+}
+
+/// Returns true if the value holds more than `limit` values, counting nested items.
+fn size_exceeds(value: &Value, limit: usize) -> bool {
+    fn count(value: &Value, remaining: &mut usize) -> bool {
+        let items: &[Value] = match value {
+            Value::Array(arr) => arr,
+            Value::Tuple(items) => items,
+            Value::Udt(_, value) => return count(value, remaining),
+            _ => return false,
+        };
+        for item in items {
+            if *remaining == 0 {
+                return true;
+            }
+            *remaining -= 1;
+            if count(item, remaining) {
+                return true;
+            }
+        }
+        false
+    }
+    let mut remaining = limit;
+    count(value, &mut remaining)
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -32,6 +119,11 @@ impl CallStack {
         self.frames.len()
     }
 
+    #[must_use]
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
     #[must_use]
     pub fn into_frames(self) -> Vec<Frame> {
         self.frames
@@ -55,3 +147,81 @@ pub fn map_hir_package_to_fir(package: hir::PackageId) -> fir::PackageId {
 pub fn map_fir_package_to_hir(package: fir::PackageId) -> hir::PackageId {
     hir::PackageId::from(<fir::PackageId as Into<usize>>::into(package))
 }
+
+/// A condition on the number of times a breakpoint has been hit, which must hold for execution to
+/// pause at the breakpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitCondition {
+    /// Pauses only on the given hit, counting from one.
+    Equal(u32),
+    /// Pauses on every hit that is a multiple of the given count.
+    Multiple(u32),
+    /// Pauses on every hit after the given number of hits.
+    After(u32),
+}
+
+impl HitCondition {
+    /// Returns true if execution should pause on the given hit, counting from one.
+    #[must_use]
+    pub fn is_met(self, hit: u32) -> bool {
+        match self {
+            Self::Equal(n) => hit == n,
+            Self::Multiple(n) => n != 0 && hit % n == 0,
+            Self::After(n) => hit > n,
+        }
+    }
+}
+
+impl FromStr for HitCondition {
+    type Err = String;
+
+    /// Parses a hit condition in the form used by debug adapters: `N` or `== N` for
+    /// [`HitCondition::Equal`], `% N` for [`HitCondition::Multiple`], and `> N` or `>= N` for
+    /// [`HitCondition::After`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (ctor, count): (fn(u32) -> Self, &str) = if let Some(count) = s.strip_prefix(">=") {
+            (|n| Self::After(n.saturating_sub(1)), count)
+        } else if let Some(count) = s.strip_prefix('>') {
+            (Self::After, count)
+        } else if let Some(count) = s.strip_prefix('%') {
+            (Self::Multiple, count)
+        } else if let Some(count) = s.strip_prefix("==") {
+            (Self::Equal, count)
+        } else {
+            (Self::Equal, s)
+        };
+        count
+            .trim()
+            .parse()
+            .map(ctor)
+            .map_err(|_| format!("invalid hit condition `{s}`"))
+    }
+}
+
+/// Renders a logpoint message template, replacing each `{name}` placeholder with the value of the
+/// innermost variable with that name. Braces are escaped by doubling them, and placeholders naming
+/// unknown variables are rendered as `<name: not found>`.
+#[must_use]
+pub fn render_logpoint(template: &str, variables: &[VariableInfo]) -> String {
+    let mut message = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => message.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => message.push('}'),
+            '{' => {
+                let name = chars.by_ref().take_while(|&c| c != '}').collect::<String>();
+                let name = name.trim();
+                match variables.iter().rev().find(|var| &*var.name == name) {
+                    Some(var) => message.push_str(&var.value.to_string()),
+                    None => {
+                        let _ = write!(message, "<{name}: not found>");
+                    }
+                }
+            }
+            c => message.push(c),
+        }
+    }
+    message
+}
@@ -0,0 +1,30 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::HitCondition;
+
+#[test]
+fn hit_condition_parses_debug_adapter_forms() {
+    assert_eq!("5".parse(), Ok(HitCondition::Equal(5)));
+    assert_eq!("== 5".parse(), Ok(HitCondition::Equal(5)));
+    assert_eq!("% 3".parse(), Ok(HitCondition::Multiple(3)));
+    assert_eq!("> 10".parse(), Ok(HitCondition::After(10)));
+    assert_eq!(">=10".parse(), Ok(HitCondition::After(9)));
+    assert_eq!(
+        "< 2".parse::<HitCondition>(),
+        Err("invalid hit condition `< 2`".to_string())
+    );
+}
+
+#[test]
+fn hit_condition_is_met() {
+    let hits = |condition: HitCondition| {
+        (1..=10)
+            .filter(|&hit| condition.is_met(hit))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(hits(HitCondition::Equal(4)), vec![4]);
+    assert_eq!(hits(HitCondition::Multiple(3)), vec![3, 6, 9]);
+    assert_eq!(hits(HitCondition::After(8)), vec![9, 10]);
+    assert!(hits(HitCondition::Multiple(0)).is_empty());
+}
@@ -1,29 +1,45 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-mod utils;
+pub(crate) mod utils;
 
 #[cfg(test)]
 mod tests;
 
 use crate::{
     backend::Backend,
+    debug::Frame,
     error::PackageSpan,
-    output::Receiver,
-    val::{self, Qubit, Value},
+    output::{Receiver, StateDump},
+    state,
+    val::{self, Qubit, ResultBits, Value},
     Error,
 };
-use num_bigint::BigInt;
+use num_bigint::{BigInt, BigUint};
+use num_complex::Complex64;
+use num_traits::Zero;
 use rand::{rngs::StdRng, Rng};
 use rustc_hash::FxHashSet;
 use std::array;
 
-#[allow(clippy::too_many_lines)]
+/// The largest number of qubits whose reduced density matrix can be dumped, which keeps the
+/// matrix to about a million elements.
+const MAX_REDUCED_QUBITS: usize = 10;
+
+/// The largest number of qubits in the smaller part of a bipartition whose entanglement entropy
+/// can be computed, which bounds the cost of diagonalizing its reduced density matrix.
+const MAX_ENTROPY_QUBITS: usize = 8;
+
+/// Calls the intrinsic with the given name. The call span and caller frame locate any output that
+/// the intrinsic emits.
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 pub(crate) fn call(
     name: &str,
     name_span: PackageSpan,
     arg: Value,
     arg_span: PackageSpan,
+    call_span: PackageSpan,
+    caller: Option<&Frame>,
     sim: &mut dyn Backend<ResultType = impl Into<val::Result>>,
     rng: &mut StdRng,
     out: &mut dyn Receiver,
@@ -36,34 +52,193 @@ pub(crate) fn call(
         #[allow(clippy::cast_precision_loss)]
         "IntAsDouble" => Ok(Value::Double(arg.unwrap_int() as f64)),
         "IntAsBigInt" => Ok(Value::BigInt(BigInt::from(arg.unwrap_int()))),
-        "DumpMachine" => {
+        "ResultArrayAsInt" => {
+            let bits = ResultBits::try_from(&arg).expect("value should be an array of results");
+            bits.to_int().map(Value::Int).ok_or_else(|| {
+                Error::IntrinsicFail(
+                    name.to_string(),
+                    format!(
+                        "`Length(bits)` must be less than 64, but was {}.",
+                        bits.len()
+                    ),
+                    arg_span,
+                )
+            })
+        }
+        "DumpMachine" => match out.state_dump(sim.dump_quantum_state()) {
+            Ok(()) => Ok(Value::unit()),
+            Err(_) => Err(Error::OutputFail(name_span)),
+        },
+        "DumpRegister" => {
+            let qubits = unwrap_unique_qubits(arg, arg_span)?;
             let (state, qubit_count) = sim.capture_quantum_state();
-            match out.state(state, qubit_count) {
+            let state = utils::split_state(&qubits, state, qubit_count)
+                .map_err(|()| Error::QubitsNotSeparable(arg_span))?;
+            match out.state_dump(StateDump::from_state(state, qubits.len())) {
                 Ok(()) => Ok(Value::unit()),
                 Err(_) => Err(Error::OutputFail(name_span)),
             }
         }
-        "DumpRegister" => {
-            let qubits = arg.unwrap_array();
-            let qubits = qubits
-                .iter()
-                .map(|q| q.clone().unwrap_qubit().0)
-                .collect::<Vec<_>>();
-            if qubits.len() != qubits.iter().collect::<FxHashSet<_>>().len() {
-                return Err(Error::QubitUniqueness(arg_span));
+        "DumpReducedDensityMatrix" => {
+            let qubits = unwrap_unique_qubits(arg, arg_span)?;
+            if qubits.len() > MAX_REDUCED_QUBITS {
+                return Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    format!("at most {MAX_REDUCED_QUBITS} qubits are supported"),
+                    arg_span,
+                ));
             }
             let (state, qubit_count) = sim.capture_quantum_state();
-            let state = utils::split_state(&qubits, state, qubit_count)
-                .map_err(|()| Error::QubitsNotSeparable(arg_span))?;
-            match out.state(state, qubits.len()) {
+            let matrix = state::reduced_density_matrix(&state, qubit_count, &qubits);
+            match out.matrix(matrix) {
                 Ok(()) => Ok(Value::unit()),
                 Err(_) => Err(Error::OutputFail(name_span)),
             }
         }
-        "Message" => match out.message(&arg.unwrap_string()) {
+        "BlochVector" => {
+            let qubit = arg.unwrap_qubit().0;
+            let (state, qubit_count) = sim.capture_quantum_state();
+            let vector = state::bloch_vector(&state, qubit_count, qubit);
+            Ok(Value::Tuple(
+                [vector.x, vector.y, vector.z].map(Value::Double).into(),
+            ))
+        }
+        "DumpBlochVector" => {
+            let qubit = arg.unwrap_qubit().0;
+            let (state, qubit_count) = sim.capture_quantum_state();
+            match out.bloch_vector(&state::bloch_vector(&state, qubit_count, qubit)) {
+                Ok(()) => Ok(Value::unit()),
+                Err(_) => Err(Error::OutputFail(name_span)),
+            }
+        }
+        "EntanglementEntropy" => {
+            let qubits = unwrap_unique_qubits(arg, arg_span)?;
+            let (state, qubit_count) = sim.capture_quantum_state();
+            if state::smaller_part(qubit_count, &qubits).len() > MAX_ENTROPY_QUBITS {
+                return Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    format!("at most {MAX_ENTROPY_QUBITS} qubits are supported on the smaller side of the bipartition"),
+                    arg_span,
+                ));
+            }
+            Ok(Value::Double(state::entanglement_entropy(
+                &state,
+                qubit_count,
+                &qubits,
+            )))
+        }
+        "Message" => match out.message_at(&arg.unwrap_string(), call_span, caller) {
             Ok(()) => Ok(Value::unit()),
             Err(_) => Err(Error::OutputFail(name_span)),
         },
+        "PauliExpectation" => {
+            let [paulis, qubits] = unwrap_tuple(arg);
+            let paulis = paulis
+                .unwrap_array()
+                .iter()
+                .map(|p| p.clone().unwrap_pauli())
+                .collect::<Vec<_>>();
+            let qubits = unwrap_unique_qubits(qubits, arg_span)?;
+            if paulis.len() != qubits.len() {
+                return Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    "number of Paulis must match number of qubits".to_string(),
+                    arg_span,
+                ));
+            }
+            match sim.pauli_expectation(&paulis, &qubits) {
+                Some(expectation) => Ok(Value::Double(expectation)),
+                None => Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    "expectation values are not supported by the current backend".to_string(),
+                    name_span,
+                )),
+            }
+        }
+        "BasisStateAmplitude" => {
+            let [qubits, basis_state] = unwrap_tuple(arg);
+            let qubits = unwrap_unique_qubits(qubits, arg_span)?;
+            let basis_state = basis_state.unwrap_array();
+            if basis_state.len() != qubits.len() {
+                return Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    "length of basis state must match number of qubits".to_string(),
+                    arg_span,
+                ));
+            }
+            let mut label = BigUint::zero();
+            for (i, bit) in basis_state.iter().enumerate() {
+                if bit.clone().unwrap_bool() {
+                    label.set_bit((basis_state.len() - i - 1) as u64, true);
+                }
+            }
+            match sim.basis_state_amplitude(&qubits, &label) {
+                Some(Ok(amplitude)) => Ok(Value::Tuple(
+                    vec![Value::Double(amplitude.re), Value::Double(amplitude.im)].into(),
+                )),
+                Some(Err(message)) => {
+                    Err(Error::IntrinsicFail(name.to_string(), message, arg_span))
+                }
+                None => Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    "state inspection is not supported by the current backend".to_string(),
+                    name_span,
+                )),
+            }
+        }
+        "ApplyUnitary" => {
+            let [matrix, qubits] = unwrap_tuple(arg);
+            let qubits = unwrap_unique_qubits(qubits, arg_span)?;
+            let matrix = matrix
+                .unwrap_array()
+                .iter()
+                .map(|row| {
+                    row.clone()
+                        .unwrap_array()
+                        .iter()
+                        .map(|entry| {
+                            let [re, im] = unwrap_tuple(entry.clone());
+                            Complex64::new(re.unwrap_double(), im.unwrap_double())
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let dim = u32::try_from(qubits.len())
+                .ok()
+                .and_then(|n| 1_usize.checked_shl(n));
+            let is_square = dim.is_some_and(|dim| {
+                matrix.len() == dim && matrix.iter().all(|row| row.len() == dim)
+            });
+            if !is_square {
+                return Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    format!(
+                        "matrix must be square with dimension 2^{} to act on {} qubits",
+                        qubits.len(),
+                        qubits.len()
+                    ),
+                    arg_span,
+                ));
+            }
+            if !is_unitary(&matrix) {
+                return Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    "matrix is not unitary".to_string(),
+                    arg_span,
+                ));
+            }
+            match sim.apply_unitary(&matrix, &qubits) {
+                Some(Ok(())) => Ok(Value::unit()),
+                Some(Err(message)) => {
+                    Err(Error::IntrinsicFail(name.to_string(), message, name_span))
+                }
+                None => Err(Error::IntrinsicFail(
+                    name.to_string(),
+                    "custom unitaries are not supported by the current backend".to_string(),
+                    name_span,
+                )),
+            }
+        }
         "CheckZero" => Ok(Value::Bool(sim.qubit_is_zero(arg.unwrap_qubit().0))),
         "ArcCos" => Ok(Value::Double(arg.unwrap_double().acos())),
         "ArcSin" => Ok(Value::Double(arg.unwrap_double().asin())),
@@ -228,6 +403,33 @@ fn two_qubit_rotation(
     }
 }
 
+fn unwrap_unique_qubits(arg: Value, arg_span: PackageSpan) -> Result<Vec<usize>, Error> {
+    let qubits = arg
+        .unwrap_array()
+        .iter()
+        .map(|q| q.clone().unwrap_qubit().0)
+        .collect::<Vec<_>>();
+    if qubits.len() == qubits.iter().collect::<FxHashSet<_>>().len() {
+        Ok(qubits)
+    } else {
+        Err(Error::QubitUniqueness(arg_span))
+    }
+}
+
+/// Checks whether the square matrix, given as rows, is unitary within tolerance.
+fn is_unitary(matrix: &[Vec<Complex64>]) -> bool {
+    let dim = matrix.len();
+    (0..dim).all(|i| {
+        (0..dim).all(|j| {
+            let product = (0..dim)
+                .map(|k| matrix[k][i].conj() * matrix[k][j])
+                .sum::<Complex64>();
+            let expected = if i == j { 1.0 } else { 0.0 };
+            (product - expected).norm() <= 1e-8
+        })
+    })
+}
+
 fn unwrap_tuple<const N: usize>(value: Value) -> [Value; N] {
     let values = value.unwrap_tuple();
     array::from_fn(|i| values[i].clone())
@@ -135,6 +135,26 @@ impl Backend for CustomSim {
         self.sim.qubit_is_zero(q)
     }
 
+    fn pauli_expectation(&mut self, paulis: &[fir::Pauli], qubits: &[usize]) -> Option<f64> {
+        self.sim.pauli_expectation(paulis, qubits)
+    }
+
+    fn basis_state_amplitude(
+        &mut self,
+        qubits: &[usize],
+        basis_state: &num_bigint::BigUint,
+    ) -> Option<Result<num_complex::Complex64, String>> {
+        self.sim.basis_state_amplitude(qubits, basis_state)
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<num_complex::Complex64>],
+        qubits: &[usize],
+    ) -> Option<Result<(), String>> {
+        self.sim.apply_unitary(matrix, qubits)
+    }
+
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         match name {
             "Add1" => Some(Ok(Value::Int(arg.unwrap_int() + 1))),
@@ -166,7 +186,7 @@ fn check_intrinsic(file: &str, expr: &str, out: &mut impl Receiver) -> Result<Va
     let sources = SourceMap::new([("test".into(), file.into())], Some(expr.into()));
     let mut unit = compile(
         &store,
-        &[std_id],
+        &[(std_id, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -293,6 +313,260 @@ fn dump_machine_endianness() {
     );
 }
 
+#[test]
+fn pauli_expectation_bell_state() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            open Microsoft.Quantum.Diagnostics;
+            open Microsoft.Quantum.Math;
+            use qs = Qubit[3];
+            H(qs[0]);
+            CNOT(qs[0], qs[2]);
+            let expectations = [
+                Round(PauliExpectation([PauliZ, PauliZ], [qs[0], qs[2]])),
+                Round(PauliExpectation([PauliX, PauliX], [qs[0], qs[2]])),
+                Round(PauliExpectation([PauliY, PauliY], [qs[0], qs[2]])),
+                Round(PauliExpectation([PauliZ], [qs[0]])),
+                Round(PauliExpectation([PauliZ], [qs[1]])),
+                Round(PauliExpectation([PauliI, PauliX], [qs[1], qs[2]])),
+            ];
+            ResetAll(qs);
+            expectations
+        }"},
+        &expect!["[1, 1, -1, 0, 1, 0]"],
+    );
+}
+
+#[test]
+fn pauli_expectation_does_not_collapse_state() {
+    check_intrinsic_output(
+        "",
+        indoc! {"{
+            use q = Qubit();
+            Ry(Microsoft.Quantum.Math.PI() / 3.0, q);
+            let _ = Microsoft.Quantum.Diagnostics.PauliExpectation([PauliZ], [q]);
+            Microsoft.Quantum.Diagnostics.DumpMachine();
+            Reset(q);
+        }"},
+        &expect![[r#"
+            STATE:
+            |0⟩: 0.8660+0.0000𝑖
+            |1⟩: 0.5000+0.0000𝑖
+        "#]],
+    );
+}
+
+#[test]
+fn dump_reduced_density_matrix_of_entangled_qubit() {
+    check_intrinsic_output(
+        "",
+        indoc! {"{
+            use qs = Qubit[2];
+            H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            Microsoft.Quantum.Diagnostics.DumpReducedDensityMatrix([qs[1]]);
+            ResetAll(qs);
+        }"},
+        &expect![[r#"
+            MATRIX:
+            0.5000+0.0000𝑖 0.0000+0.0000𝑖
+            0.0000+0.0000𝑖 0.5000+0.0000𝑖
+        "#]],
+    );
+}
+
+#[test]
+fn dump_reduced_density_matrix_of_too_many_qubits() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            use qs = Qubit[11];
+            Microsoft.Quantum.Diagnostics.DumpReducedDensityMatrix(qs)
+        }"},
+        &expect![
+            "intrinsic callable `DumpReducedDensityMatrix` failed: at most 10 qubits are supported"
+        ],
+    );
+}
+
+#[test]
+fn bloch_vector_of_superpositions() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            open Microsoft.Quantum.Math;
+            use qs = Qubit[3];
+            H(qs[0]);
+            H(qs[1]);
+            S(qs[1]);
+            X(qs[2]);
+            let round = ((x, y, z)) -> (Round(x), Round(y), Round(z));
+            let vectors = [
+                round(Microsoft.Quantum.Diagnostics.BlochVector(qs[0])),
+                round(Microsoft.Quantum.Diagnostics.BlochVector(qs[1])),
+                round(Microsoft.Quantum.Diagnostics.BlochVector(qs[2])),
+            ];
+            ResetAll(qs);
+            vectors
+        }"},
+        &expect!["[(1, 0, 0), (0, 1, 0), (0, 0, -1)]"],
+    );
+}
+
+#[test]
+fn dump_bloch_vector_of_entangled_qubit() {
+    check_intrinsic_output(
+        "",
+        indoc! {"{
+            use qs = Qubit[2];
+            H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            Microsoft.Quantum.Diagnostics.DumpBlochVector(qs[0]);
+            ResetAll(qs);
+        }"},
+        &expect![[r#"
+            BLOCH VECTOR: (0.0000, 0.0000, 0.0000)
+        "#]],
+    );
+}
+
+#[test]
+fn entanglement_entropy_of_bell_pair() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            use qs = Qubit[3];
+            H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            let entropies = [
+                Microsoft.Quantum.Math.Round(Microsoft.Quantum.Diagnostics.EntanglementEntropy([qs[0]])),
+                Microsoft.Quantum.Math.Round(Microsoft.Quantum.Diagnostics.EntanglementEntropy([qs[2]])),
+            ];
+            ResetAll(qs);
+            entropies
+        }"},
+        &expect!["[1, 0]"],
+    );
+}
+
+#[test]
+fn pauli_expectation_length_mismatch() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            use qs = Qubit[2];
+            Microsoft.Quantum.Diagnostics.PauliExpectation([PauliZ], qs)
+        }"},
+        &expect!["intrinsic callable `PauliExpectation` failed: number of Paulis must match number of qubits"],
+    );
+}
+
+#[test]
+fn basis_state_amplitude_full_register() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            use qs = Qubit[2];
+            X(qs[1]);
+            S(qs[1]);
+            let amplitudes = [
+                Microsoft.Quantum.Diagnostics.BasisStateAmplitude(qs, [false, true]),
+                Microsoft.Quantum.Diagnostics.BasisStateAmplitude(qs, [true, false]),
+                Microsoft.Quantum.Diagnostics.BasisStateAmplitude([qs[1], qs[0]], [true, false]),
+            ];
+            ResetAll(qs);
+            amplitudes
+        }"},
+        &expect!["[(0.0, 1.0), (0.0, 0.0), (0.0, 1.0)]"],
+    );
+}
+
+#[test]
+fn basis_state_amplitude_separable_subset() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            use qs = Qubit[3];
+            H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            X(qs[2]);
+            let amplitudes = [
+                Microsoft.Quantum.Diagnostics.BasisStateAmplitude([qs[2]], [true]),
+                Microsoft.Quantum.Diagnostics.BasisStateAmplitude([qs[2]], [false]),
+            ];
+            ResetAll(qs);
+            amplitudes
+        }"},
+        &expect!["[(1.0, 0.0), (0.0, 0.0)]"],
+    );
+}
+
+#[test]
+fn basis_state_amplitude_entangled_subset() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            use qs = Qubit[2];
+            H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            Microsoft.Quantum.Diagnostics.BasisStateAmplitude([qs[0]], [true])
+        }"},
+        &expect!["intrinsic callable `BasisStateAmplitude` failed: qubits are not separable"],
+    );
+}
+
+#[test]
+fn apply_unitary_swaps_qubits() {
+    check_intrinsic_output(
+        "",
+        indoc! {"{
+            open Microsoft.Quantum.Math;
+            use qs = Qubit[2];
+            X(qs[0]);
+            let (zero, one) = (Complex(0.0, 0.0), Complex(1.0, 0.0));
+            ApplyUnitary([
+                [one, zero, zero, zero],
+                [zero, zero, one, zero],
+                [zero, one, zero, zero],
+                [zero, zero, zero, one]
+            ], qs);
+            Microsoft.Quantum.Diagnostics.DumpMachine();
+            ResetAll(qs);
+        }"},
+        &expect![[r#"
+            STATE:
+            |01⟩: 1.0000+0.0000𝑖
+        "#]],
+    );
+}
+
+#[test]
+fn apply_unitary_dimension_mismatch() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            open Microsoft.Quantum.Math;
+            use qs = Qubit[2];
+            ApplyUnitary([[Complex(0.0, 0.0), Complex(1.0, 0.0)], [Complex(1.0, 0.0), Complex(0.0, 0.0)]], qs)
+        }"},
+        &expect!["intrinsic callable `ApplyUnitary` failed: matrix must be square with dimension 2^2 to act on 2 qubits"],
+    );
+}
+
+#[test]
+fn apply_unitary_not_unitary() {
+    check_intrinsic_result(
+        "",
+        indoc! {"{
+            open Microsoft.Quantum.Math;
+            use q = Qubit();
+            ApplyUnitary([[Complex(1.0, 0.0), Complex(1.0, 0.0)], [Complex(0.0, 0.0), Complex(1.0, 0.0)]], [q])
+        }"},
+        &expect!["intrinsic callable `ApplyUnitary` failed: matrix is not unitary"],
+    );
+}
+
 #[test]
 fn dump_register_all_qubits() {
     check_intrinsic_output(
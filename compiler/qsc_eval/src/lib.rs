@@ -10,39 +10,61 @@ mod error;
 mod intrinsic;
 pub mod lower;
 pub mod output;
+pub mod partial_eval;
 pub mod state;
 pub mod val;
 
 use crate::val::Value;
 use backend::Backend;
-use debug::{map_fir_package_to_hir, CallStack, Frame};
-use error::PackageSpan;
+use debug::{map_fir_package_to_hir, render_logpoint, CallStack, Frame, FrameArg, HitCondition};
+pub use error::PackageSpan;
 use miette::Diagnostic;
 use num_bigint::BigInt;
-use output::Receiver;
+use num_complex::Complex64;
+use output::{Progress, Receiver};
 use qsc_data_structures::{functors::FunctorApp, index_map::IndexMap, span::Span};
 use qsc_fir::fir::{
     self, BinOp, BlockId, CallableImpl, Expr, ExprId, ExprKind, Field, Functor, Global, Lit,
     LocalItemId, LocalVarId, Mutability, PackageId, PackageStoreLookup, PatId, PatKind, PrimField,
     Res, StmtId, StmtKind, StoreItemId, StringComponent, UnOp,
 };
-use qsc_fir::ty::Ty;
+use qsc_fir::ty::{Ty, Udt};
 use rand::{rngs::StdRng, SeedableRng};
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{
     cell::RefCell,
     fmt::{self, Display, Formatter, Write},
+    future::Future,
     iter,
+    mem::size_of,
+    num::{NonZeroU32, NonZeroU64},
     ops::Neg,
+    pin::Pin,
     rc::Rc,
+    task::{Context, Poll},
 };
 use thiserror::Error;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// The default number of statements between progress heartbeats, which is large enough that
+/// heartbeats are sent at most a few times per second.
+pub const DEFAULT_PROGRESS_INTERVAL: NonZeroU64 = match NonZeroU64::new(1_000_000) {
+    Some(interval) => interval,
+    None => unreachable!(),
+};
+
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub enum Error {
     #[error("array too large")]
     #[diagnostic(code("Qsc.Eval.ArrayTooLarge"))]
     ArrayTooLarge(#[label("this array has too many items")] PackageSpan),
 
+    #[error("backend failed: {0}")]
+    #[diagnostic(code("Qsc.Eval.BackendFail"))]
+    BackendFail(String, #[label("failed during this operation")] PackageSpan),
+
     #[error("invalid array length: {0}")]
     #[diagnostic(code("Qsc.Eval.InvalidArrayLength"))]
     InvalidArrayLength(i64, #[label("cannot be used as a length")] PackageSpan),
@@ -127,6 +149,7 @@ impl Error {
     pub fn span(&self) -> &PackageSpan {
         match self {
             Error::ArrayTooLarge(span)
+            | Error::BackendFail(_, span)
             | Error::DivZero(span)
             | Error::EmptyRange(span)
             | Error::IndexOutOfRange(_, span)
@@ -192,20 +215,32 @@ impl From<StmtId> for EvalId {
 }
 
 /// Evaluates the given code with the given context.
+/// The `classical_seed` drives classical randomness such as `DrawRandomInt`, while the
+/// `quantum_seed`, if provided, is used to reseed the backend before evaluation so that
+/// measurement sampling and noise can be held fixed independently of classical randomness.
+/// The `user_packages` are the packages of user code, as for [`State::set_user_packages`].
 /// # Errors
 /// Returns the first error encountered during execution.
 /// # Panics
 /// On internal error where no result is returned.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn eval(
     package: PackageId,
-    seed: Option<u64>,
+    user_packages: Vec<PackageId>,
+    classical_seed: Option<u64>,
+    quantum_seed: Option<u64>,
     id: EvalId,
     globals: &impl PackageStoreLookup,
     env: &mut Env,
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     receiver: &mut impl Receiver,
 ) -> Result<Value, (Error, Vec<Frame>)> {
-    let mut state = State::new(package, seed);
+    if quantum_seed.is_some() {
+        sim.set_seed(quantum_seed);
+    }
+    let mut state = State::new(package, classical_seed);
+    state.set_user_packages(user_packages);
     match id {
         EvalId::Expr(expr) => state.push_expr(expr),
         EvalId::Stmt(stmt) => state.push_stmt(stmt),
@@ -214,6 +249,12 @@ pub fn eval(
     let StepResult::Return(value) = res else {
         panic!("eval should always return a value");
     };
+    if let EvalId::Expr(expr) = id {
+        receiver.result(&value).map_err(|_| {
+            let span = globals.get_expr((package, expr).into()).span;
+            (Error::OutputFail(state.to_global_span(span)), Vec::new())
+        })?;
+    }
     Ok(value)
 }
 
@@ -233,9 +274,43 @@ pub enum StepResult {
     Next,
     StepIn,
     StepOut,
+    /// Execution paused because a `fail` expression or an intrinsic is about to produce the given
+    /// error. Resuming execution reports the error.
+    ExceptionHit(Error),
+    /// Execution paused because the number of statements set with [`State::set_yield_interval`]
+    /// was evaluated, giving the host a chance to do other work or cancel evaluation.
+    Yield,
     Return(Value),
 }
 
+/// A future that returns pending once, waking its task immediately, so that the executor can run
+/// other tasks before it completes.
+#[derive(Default)]
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// The FIR node that evaluation continues with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NextNode {
+    Stmt(PackageId, StmtId),
+    Expr(PackageId, ExprId),
+}
+
 pub fn eval_push_expr(state: &mut State, expr: ExprId) {
     state.push_expr(expr);
 }
@@ -339,6 +414,23 @@ impl Env {
             .expect("scope should be entered first before leaving");
     }
 
+    /// Returns the number of bytes of heap memory retained by the environment, including the
+    /// values of its variables. Allocations that are shared through `Rc`s, including between
+    /// variables, are counted once.
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        let mut seen = FxHashSet::default();
+        let mut size = self.0.capacity() * size_of::<Scope>();
+        for scope in &self.0 {
+            for (_, var) in scope.bindings.iter() {
+                size += size_of::<Option<Variable>>()
+                    + val::rc_heap_size(&var.name, &mut seen, |name, _| name.len())
+                    + var.value.heap_size_with(&mut seen);
+            }
+        }
+        size
+    }
+
     #[must_use]
     pub fn get_variables_in_top_frame(&self) -> Vec<VariableInfo> {
         if let Some(scope) = self.0.last() {
@@ -363,7 +455,10 @@ impl Env {
                 bindings
                     .map(|(_, var)| VariableInfo {
                         name: var.name.clone(),
-                        type_name: var.value.type_name().to_string(),
+                        type_name: match &var.value {
+                            Value::Udt(udt, _) => udt.name.to_string(),
+                            value => value.type_name().to_string(),
+                        },
                         value: var.value.clone(),
                         mutability: var.mutability,
                         span: var.span,
@@ -404,7 +499,7 @@ enum Action {
     Assign(ExprId),
     Bind(PatId, Mutability),
     BinOp(BinOp, Span, Option<ExprId>),
-    Call(Span, Span),
+    Call(Span, Span, Span),
     Consume,
     Fail(Span),
     Field(Field),
@@ -427,9 +522,33 @@ pub struct State {
     action_stack: Vec<Action>,
     vals: Vec<Value>,
     package: PackageId,
+    /// The packages of user code rather than of libraries, which starts as the package that
+    /// evaluation started in.
+    user_packages: Vec<PackageId>,
     call_stack: CallStack,
+    /// The span of the call that pushed each frame on the call stack, in the package of its caller.
+    call_sites: Vec<Span>,
     current_span: Span,
     rng: RefCell<StdRng>,
+    break_on_fail: bool,
+    pending_error: Option<Error>,
+    logpoints: IndexMap<StmtId, Rc<str>>,
+    capture_args: bool,
+    hit_conditions: IndexMap<StmtId, HitCondition>,
+    hit_counts: IndexMap<StmtId, u32>,
+    yield_interval: Option<NonZeroU32>,
+    stmts_since_yield: u32,
+    yielded_frame: Option<usize>,
+    progress_interval: Option<NonZeroU64>,
+    stmts_executed: u64,
+    next_progress: u64,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: Instant,
+    /// The entered spans of the frames on the call stack.
+    #[cfg(feature = "tracing")]
+    call_spans: Vec<tracing::span::EnteredSpan>,
+    /// The user-defined types of the values constructed so far, shared between their values.
+    udts: FxHashMap<StoreItemId, Rc<Udt>>,
 }
 
 impl State {
@@ -444,10 +563,135 @@ impl State {
             action_stack: Vec::new(),
             vals: Vec::new(),
             package,
+            user_packages: vec![package],
             call_stack: CallStack::default(),
+            call_sites: Vec::new(),
             current_span: Span::default(),
             rng,
-        }
+            break_on_fail: false,
+            pending_error: None,
+            logpoints: IndexMap::new(),
+            capture_args: false,
+            hit_conditions: IndexMap::new(),
+            hit_counts: IndexMap::new(),
+            yield_interval: None,
+            stmts_since_yield: 0,
+            yielded_frame: None,
+            progress_interval: Some(DEFAULT_PROGRESS_INTERVAL),
+            stmts_executed: 0,
+            next_progress: DEFAULT_PROGRESS_INTERVAL.get(),
+            #[cfg(not(target_arch = "wasm32"))]
+            started: Instant::now(),
+            #[cfg(feature = "tracing")]
+            call_spans: Vec::new(),
+            udts: FxHashMap::default(),
+        }
+    }
+
+    /// Sets the number of statements between progress heartbeats sent to the receiver, or `None`
+    /// to never send them. Defaults to [`DEFAULT_PROGRESS_INTERVAL`].
+    pub fn set_progress_interval(&mut self, interval: Option<NonZeroU64>) {
+        self.progress_interval = interval;
+        self.next_progress = interval.map_or(u64::MAX, |interval| {
+            self.stmts_executed.saturating_add(interval.get())
+        });
+    }
+
+    /// Sets the number of statements after which evaluation pauses with [`StepResult::Yield`], or
+    /// `None` to never yield. Resuming evaluation continues from the next statement.
+    pub fn set_yield_interval(&mut self, interval: Option<NonZeroU32>) {
+        self.yield_interval = interval;
+        self.stmts_since_yield = 0;
+    }
+
+    /// Sets the hit condition of the breakpoint on the given statement. Hits are counted each time
+    /// the statement executes while it has a breakpoint, and execution only pauses there when the
+    /// condition is met.
+    pub fn set_hit_condition(&mut self, stmt: StmtId, condition: HitCondition) {
+        self.hit_conditions.insert(stmt, condition);
+    }
+
+    /// Removes all hit conditions and resets the hit counts of all breakpoints.
+    pub fn clear_hit_conditions(&mut self) {
+        self.hit_conditions.clear();
+        self.hit_counts.clear();
+    }
+
+    /// Returns the number of times the breakpoint on the given statement has been hit.
+    #[must_use]
+    pub fn hit_count(&self, stmt: StmtId) -> u32 {
+        self.hit_counts.get(stmt).copied().unwrap_or_default()
+    }
+
+    /// Sends a progress heartbeat to the receiver and schedules the next one.
+    fn report_progress(
+        &mut self,
+        globals: &impl PackageStoreLookup,
+        out: &mut impl Receiver,
+    ) -> Result<(), Error> {
+        if let Some(interval) = self.progress_interval {
+            self.next_progress = self.stmts_executed.saturating_add(interval.get());
+        }
+        let callable =
+            self.call_stack
+                .frames()
+                .last()
+                .and_then(|frame| match globals.get_global(frame.id) {
+                    Some(Global::Callable(decl)) => Some(decl.name.name.clone()),
+                    _ => None,
+                });
+        #[cfg(not(target_arch = "wasm32"))]
+        let elapsed = Some(self.started.elapsed());
+        #[cfg(target_arch = "wasm32")]
+        let elapsed = None;
+        out.progress(&Progress {
+            statements: self.stmts_executed,
+            callable,
+            elapsed,
+        })
+        .map_err(|_| Error::OutputFail(self.to_global_span(self.current_span)))
+    }
+
+    /// Records a hit of the breakpoint on the given statement, returning true if execution should
+    /// pause there.
+    fn hit_breakpoint(&mut self, stmt: StmtId) -> bool {
+        let hit = self.hit_count(stmt) + 1;
+        self.hit_counts.insert(stmt, hit);
+        self.hit_conditions
+            .get(stmt)
+            .map_or(true, |condition| condition.is_met(hit))
+    }
+
+    /// Sets the packages of user code, such as a source package and the interactive package that
+    /// calls into it. Problems that the backend reports are attributed to the innermost call made
+    /// from one of them.
+    pub fn set_user_packages(&mut self, packages: Vec<PackageId>) {
+        self.user_packages = packages;
+    }
+
+    /// Sets whether the argument of each call is captured in its stack frame, so that it can be
+    /// shown alongside the callable. Large arrays are summarized rather than captured.
+    pub fn set_capture_args(&mut self, capture_args: bool) {
+        self.capture_args = capture_args;
+    }
+
+    /// Sets whether evaluation pauses with [`StepResult::ExceptionHit`] before a `fail` expression
+    /// or a failing intrinsic produces an error, leaving the call stack intact for inspection.
+    pub fn set_break_on_fail(&mut self, break_on_fail: bool) {
+        self.break_on_fail = break_on_fail;
+    }
+
+    /// Sets a logpoint on the given statement. Each time the statement executes, the message
+    /// template is rendered against the variables in the current frame and sent to the receiver
+    /// as a message, without pausing execution. See [`debug::render_logpoint`] for the template
+    /// syntax.
+    pub fn set_logpoint(&mut self, stmt: StmtId, template: &str) {
+        self.logpoints.insert(stmt, template.into());
+    }
+
+    /// Removes all logpoints.
+    pub fn clear_logpoints(&mut self) {
+        self.logpoints.clear();
     }
 
     fn pop_cont(&mut self) -> Option<Cont> {
@@ -468,12 +712,14 @@ impl State {
             .extend(exprs.iter().rev().map(|expr| Cont::Expr(*expr)));
     }
 
-    fn push_frame(&mut self, id: StoreItemId, functor: FunctorApp) {
+    fn push_frame(&mut self, id: StoreItemId, functor: FunctorApp, arg: &Value, call_span: Span) {
+        self.call_sites.push(call_span);
         self.call_stack.push_frame(Frame {
             span: self.current_span,
             id,
             caller: self.package,
             functor,
+            arg: self.capture_args.then(|| FrameArg::capture(arg)),
         });
         self.cont_stack.push(Cont::Frame(self.vals.len()));
         self.package = id.package;
@@ -484,12 +730,31 @@ impl State {
             .call_stack
             .pop_frame()
             .expect("frame should be present");
+        self.call_sites.pop();
         self.package = frame.caller;
+        #[cfg(feature = "tracing")]
+        self.call_spans.pop();
         let frame_val = self.pop_val();
         self.vals.drain(len..);
         self.push_val(frame_val);
     }
 
+    /// The span of the innermost call made from user code rather than from a library. A problem
+    /// that the backend reports during an intrinsic call is attributed to it, since the call of the
+    /// intrinsic itself is inside the library that implements the operation.
+    fn user_call_site(&self) -> Option<PackageSpan> {
+        self.call_stack
+            .frames()
+            .iter()
+            .zip(&self.call_sites)
+            .rev()
+            .find(|(frame, _)| self.user_packages.contains(&frame.caller))
+            .map(|(frame, span)| PackageSpan {
+                package: map_fir_package_to_hir(frame.caller),
+                span: *span,
+            })
+    }
+
     fn push_scope(&mut self, env: &mut Env) {
         env.push_scope(self.call_stack.len());
         self.cont_stack.push(Cont::Scope);
@@ -525,6 +790,25 @@ impl State {
         self.vals.push(val);
     }
 
+    /// Returns the statement or expression that is evaluated next, if any.
+    #[must_use]
+    pub fn next_node(&self) -> Option<NextNode> {
+        let mut package = self.package;
+        let mut frames = self.call_stack.frames().iter().rev();
+        for cont in self.cont_stack.iter().rev() {
+            match cont {
+                Cont::Stmt(stmt) => return Some(NextNode::Stmt(package, *stmt)),
+                Cont::Expr(expr) => return Some(NextNode::Expr(package, *expr)),
+                Cont::Frame(_) => {
+                    // Continuations below a frame belong to the caller.
+                    package = frames.next().expect("frame should be present").caller;
+                }
+                Cont::Action | Cont::Scope => {}
+            }
+        }
+        None
+    }
+
     #[must_use]
     pub fn get_stack_frames(&self) -> Vec<Frame> {
         let mut frames = self.call_stack.clone().into_frames();
@@ -549,15 +833,27 @@ impl State {
         breakpoints: &[StmtId],
         step: StepAction,
     ) -> Result<StepResult, (Error, Vec<Frame>)> {
-        let current_frame = self.call_stack.len();
+        if let Some(error) = self.pending_error.take() {
+            return Err((error, self.get_stack_frames()));
+        }
+
+        // When resuming after a yield, the step continues relative to the frame it started in.
+        let current_frame = self.yielded_frame.take().unwrap_or(self.call_stack.len());
 
         while let Some(cont) = self.pop_cont() {
             let res = match cont {
                 Cont::Action => {
                     let action = self.action_stack.pop().expect("action should be present");
-                    self.cont_action(env, sim, globals, action, out)
-                        .map_err(|e| (e, self.get_stack_frames()))?;
-                    continue;
+                    match self.cont_action(env, sim, globals, action, out) {
+                        Ok(()) => continue,
+                        Err(error @ (Error::UserFail(..) | Error::IntrinsicFail(..)))
+                            if self.break_on_fail =>
+                        {
+                            self.pending_error = Some(error.clone());
+                            StepResult::ExceptionHit(error)
+                        }
+                        Err(error) => return Err((error, self.get_stack_frames())),
+                    }
                 }
                 Cont::Expr(expr) => {
                     self.cont_expr(env, globals, expr)
@@ -573,9 +869,33 @@ impl State {
                     continue;
                 }
                 Cont::Stmt(stmt) => {
+                    if self
+                        .yield_interval
+                        .is_some_and(|interval| self.stmts_since_yield >= interval.get())
+                    {
+                        self.stmts_since_yield = 0;
+                        self.yielded_frame = Some(current_frame);
+                        self.push_stmt(stmt);
+                        return Ok(StepResult::Yield);
+                    }
+                    self.stmts_since_yield += 1;
+                    self.stmts_executed += 1;
+                    if self.stmts_executed == self.next_progress {
+                        self.report_progress(globals, out)
+                            .map_err(|e| (e, self.get_stack_frames()))?;
+                    }
                     self.cont_stmt(globals, stmt);
-                    if let Some(bp) = breakpoints.iter().find(|&bp| *bp == stmt) {
-                        StepResult::BreakpointHit(*bp)
+                    if let Some(template) = self.logpoints.get(stmt) {
+                        let message = render_logpoint(template, &env.get_variables_in_top_frame());
+                        out.message(&message).map_err(|_| {
+                            (
+                                Error::OutputFail(self.to_global_span(self.current_span)),
+                                self.get_stack_frames(),
+                            )
+                        })?;
+                    }
+                    if breakpoints.contains(&stmt) && self.hit_breakpoint(stmt) {
+                        StepResult::BreakpointHit(stmt)
                     } else {
                         if self.current_span == Span::default() {
                             // if there is no span, we are in generated code, so we should skip
@@ -606,10 +926,41 @@ impl State {
         Ok(StepResult::Return(self.get_result()))
     }
 
+    /// Evaluates like [`State::eval`], but instead of returning [`StepResult::Yield`], hands
+    /// control back to the executor of the returned future and then resumes. This keeps hosts with
+    /// a single-threaded event loop responsive during long evaluations, and dropping the future
+    /// cancels evaluation at the next yield point.
+    /// # Errors
+    /// Returns the first error encountered during execution.
+    pub async fn eval_async(
+        &mut self,
+        globals: &impl PackageStoreLookup,
+        env: &mut Env,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        out: &mut impl Receiver,
+        breakpoints: &[StmtId],
+        step: StepAction,
+    ) -> Result<StepResult, (Error, Vec<Frame>)> {
+        loop {
+            match self.eval(globals, env, sim, out, breakpoints, step)? {
+                StepResult::Yield => YieldNow::default().await,
+                result => return Ok(result),
+            }
+        }
+    }
+
     pub fn get_result(&mut self) -> Value {
         self.pop_val()
     }
 
+    /// Pushes a call of the callable value with the argument, so that evaluation makes the call
+    /// next and returns its result. The span is reported as the location of the call.
+    pub fn push_call(&mut self, callee: Value, arg: Value, span: Span) {
+        self.push_action(Action::Call(span, span, span));
+        self.push_val(callee);
+        self.push_val(arg);
+    }
+
     #[allow(clippy::similar_names)]
     fn cont_expr(
         &mut self,
@@ -807,7 +1158,12 @@ impl State {
     fn cont_call(&mut self, globals: &impl PackageStoreLookup, callee: ExprId, args: ExprId) {
         let callee_expr = globals.get_expr((self.package, callee).into());
         let args_expr = globals.get_expr((self.package, args).into());
-        self.push_action(Action::Call(callee_expr.span, args_expr.span));
+        // The current span is the span of the call expression itself.
+        self.push_action(Action::Call(
+            self.current_span,
+            callee_expr.span,
+            args_expr.span,
+        ));
         self.push_expr(args);
         self.push_expr(callee);
     }
@@ -911,8 +1267,8 @@ impl State {
             Action::Assign(lhs) => self.eval_assign(env, globals, lhs)?,
             Action::BinOp(op, span, rhs) => self.eval_binop(op, span, rhs)?,
             Action::Bind(pat, mutability) => self.eval_bind(env, globals, pat, mutability),
-            Action::Call(callable_span, args_span) => {
-                self.eval_call(env, sim, globals, callable_span, args_span, out)?;
+            Action::Call(call_span, callable_span, args_span) => {
+                self.eval_call(env, sim, globals, call_span, callable_span, args_span, out)?;
             }
             Action::Consume => {
                 self.pop_val();
@@ -923,7 +1279,7 @@ impl State {
                     self.to_global_span(span),
                 ));
             }
-            Action::Field(field) => self.eval_field(field),
+            Action::Field(field) => self.eval_field(&field),
             Action::If(then_expr, else_expr) => self.eval_if(then_expr, else_expr),
             Action::Index(span) => self.eval_index(span)?,
             Action::Range(has_start, has_step, has_end) => {
@@ -938,7 +1294,7 @@ impl State {
             }
             Action::Tuple(len) => self.eval_tup(len),
             Action::UnOp(op) => self.eval_unop(op),
-            Action::UpdateField(field) => self.eval_update_field(field),
+            Action::UpdateField(field) => self.eval_update_field(&field),
             Action::While(cond_expr, block) => self.eval_while(env, globals, cond_expr, block),
         }
         Ok(())
@@ -1070,11 +1426,13 @@ impl State {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn eval_call(
         &mut self,
         env: &mut Env,
         sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
         globals: &impl PackageStoreLookup,
+        call_span: Span,
         callable_span: Span,
         arg_span: Span,
         out: &mut impl Receiver,
@@ -1090,30 +1448,58 @@ impl State {
 
         let callee = match globals.get_global(callee_id) {
             Some(Global::Callable(callable)) => callable,
-            Some(Global::Udt) => {
-                self.push_val(arg);
+            Some(Global::Udt(udt)) => {
+                let udt = self
+                    .udts
+                    .entry(callee_id)
+                    .or_insert_with(|| Rc::new(udt.clone()))
+                    .clone();
+                self.push_val(Value::Udt(udt, Rc::new(arg)));
                 return Ok(());
             }
             None => return Err(Error::UnboundName(self.to_global_span(callable_span))),
         };
 
         let callee_span = self.to_global_span(callee.span);
+        let call_site = self.to_global_span(call_span);
 
         let spec = spec_from_functor_app(functor);
-        self.push_frame(callee_id, functor);
+        self.push_frame(callee_id, functor, &arg, call_span);
+        #[cfg(feature = "tracing")]
+        self.call_spans
+            .push(tracing::info_span!("call", callable = %callee.name.name, ?functor).entered());
         self.push_scope(env);
         match &callee.implementation {
             CallableImpl::Intrinsic => {
                 let name = &callee.name.name;
+                // The frame of the intrinsic itself is on top, so the caller is the one below it.
+                let frames = self.call_stack.frames();
+                let caller_frame = frames.len().checked_sub(2).map(|index| &frames[index]);
+                #[cfg(feature = "tracing")]
+                let _span = tracing::info_span!("intrinsic", name = %name).entered();
                 let val = intrinsic::call(
                     name,
                     callee_span,
                     arg,
                     arg_span,
+                    call_site,
+                    caller_frame,
                     sim,
                     &mut self.rng.borrow_mut(),
                     out,
                 )?;
+                let warnings = sim.take_warnings();
+                if !warnings.is_empty() {
+                    let span = self.user_call_site().unwrap_or(call_site);
+                    for warning in warnings {
+                        out.warning(&warning, span)
+                            .map_err(|_| Error::OutputFail(span))?;
+                    }
+                }
+                if let Some(error) = sim.take_error() {
+                    let span = self.user_call_site().unwrap_or(call_site);
+                    return Err(Error::BackendFail(error, span));
+                }
                 if val == Value::unit() && callee.output != Ty::UNIT {
                     return Err(Error::UnsupportedIntrinsicType(
                         callee.name.name.to_string(),
@@ -1146,18 +1532,9 @@ impl State {
         }
     }
 
-    fn eval_field(&mut self, field: Field) {
+    fn eval_field(&mut self, field: &Field) {
         let record = self.pop_val();
-        let val = match (record, field) {
-            (Value::Range(Some(start), _, _), Field::Prim(PrimField::Start)) => Value::Int(start),
-            (Value::Range(_, step, _), Field::Prim(PrimField::Step)) => Value::Int(step),
-            (Value::Range(_, _, Some(end)), Field::Prim(PrimField::End)) => Value::Int(end),
-            (record, Field::Path(path)) => {
-                follow_field_path(record, &path.indices).expect("field path should be valid")
-            }
-            _ => panic!("invalid field access"),
-        };
-        self.push_val(val);
+        self.push_val(field_value(record, field));
     }
 
     fn eval_if(&mut self, then_expr: ExprId, else_expr: Option<ExprId>) {
@@ -1173,19 +1550,7 @@ impl State {
     fn eval_index(&mut self, span: Span) -> Result<(), Error> {
         let index_val = self.pop_val();
         let arr = self.pop_val().unwrap_array();
-        match &index_val {
-            Value::Int(i) => self.push_val(index_array(&arr, *i, self.to_global_span(span))?),
-            &Value::Range(start, step, end) => {
-                self.push_val(slice_array(
-                    &arr,
-                    start,
-                    step,
-                    end,
-                    self.to_global_span(span),
-                )?);
-            }
-            _ => panic!("array should only be indexed by Int or Range"),
-        }
+        self.push_val(index_value(&arr, &index_val, self.to_global_span(span))?);
         Ok(())
     }
 
@@ -1237,59 +1602,7 @@ impl State {
         let update = self.pop_val();
         let index = self.pop_val();
         let span = self.to_global_span(span);
-        match index {
-            Value::Int(index) => self.eval_update_index_single(&values, index, update, span),
-            Value::Range(start, step, end) => {
-                self.eval_update_index_range(&values, start, step, end, update, span)
-            }
-            _ => unreachable!("array should only be indexed by Int or Range"),
-        }
-    }
-
-    fn eval_update_index_single(
-        &mut self,
-        values: &[Value],
-        index: i64,
-        update: Value,
-        span: PackageSpan,
-    ) -> Result<(), Error> {
-        if index < 0 {
-            return Err(Error::InvalidNegativeInt(index, span));
-        }
-        let i = index.as_index(span)?;
-        let mut values = values.to_vec();
-        match values.get_mut(i) {
-            Some(value) => {
-                *value = update;
-            }
-            None => return Err(Error::IndexOutOfRange(index, span)),
-        }
-        self.push_val(Value::Array(values.into()));
-        Ok(())
-    }
-
-    fn eval_update_index_range(
-        &mut self,
-        values: &[Value],
-        start: Option<i64>,
-        step: i64,
-        end: Option<i64>,
-        update: Value,
-        span: PackageSpan,
-    ) -> Result<(), Error> {
-        let range = make_range(values, start, step, end, span)?;
-        let mut values = values.to_vec();
-        let update = update.unwrap_array();
-        for (idx, update) in range.into_iter().zip(update.iter()) {
-            let i = idx.as_index(span)?;
-            match values.get_mut(i) {
-                Some(value) => {
-                    *value = update.clone();
-                }
-                None => return Err(Error::IndexOutOfRange(idx, span)),
-            }
-        }
-        self.push_val(Value::Array(values.into()));
+        self.push_val(update_index_value(&values, &index, update, span)?);
         Ok(())
     }
 
@@ -1325,57 +1638,13 @@ impl State {
 
     fn eval_unop(&mut self, op: UnOp) {
         let val = self.pop_val();
-        match op {
-            UnOp::Functor(functor) => match val {
-                Value::Closure(args, id, app) => {
-                    self.push_val(Value::Closure(args, id, update_functor_app(functor, app)));
-                }
-                Value::Global(id, app) => {
-                    self.push_val(Value::Global(id, update_functor_app(functor, app)));
-                }
-                _ => panic!("value should be callable"),
-            },
-            UnOp::Neg => match val {
-                Value::BigInt(v) => self.push_val(Value::BigInt(v.neg())),
-                Value::Double(v) => self.push_val(Value::Double(v.neg())),
-                Value::Int(v) => self.push_val(Value::Int(v.wrapping_neg())),
-                _ => panic!("value should be number"),
-            },
-            UnOp::NotB => match val {
-                Value::Int(v) => self.push_val(Value::Int(!v)),
-                Value::BigInt(v) => self.push_val(Value::BigInt(!v)),
-                _ => panic!("value should be Int or BigInt"),
-            },
-            UnOp::NotL => match val {
-                Value::Bool(b) => self.push_val(Value::Bool(!b)),
-                _ => panic!("value should be bool"),
-            },
-            UnOp::Pos => match val {
-                Value::BigInt(_) | Value::Int(_) | Value::Double(_) => self.push_val(val),
-                _ => panic!("value should be number"),
-            },
-            UnOp::Unwrap => self.push_val(val),
-        }
+        self.push_val(unop_value(op, val));
     }
 
-    fn eval_update_field(&mut self, field: Field) {
+    fn eval_update_field(&mut self, field: &Field) {
         let value = self.pop_val();
         let record = self.pop_val();
-        let update = match (record, field) {
-            (Value::Range(_, step, end), Field::Prim(PrimField::Start)) => {
-                Value::Range(Some(value.unwrap_int()), step, end)
-            }
-            (Value::Range(start, _, end), Field::Prim(PrimField::Step)) => {
-                Value::Range(start, value.unwrap_int(), end)
-            }
-            (Value::Range(start, step, _), Field::Prim(PrimField::End)) => {
-                Value::Range(start, step, Some(value.unwrap_int()))
-            }
-            (record, Field::Path(path)) => update_field_path(&record, &path.indices, &value)
-                .expect("field path should be valid"),
-            _ => panic!("invalid field access"),
-        };
-        self.push_val(update);
+        self.push_val(update_field_value(record, field, value));
     }
 
     fn eval_while(
@@ -1650,7 +1919,179 @@ fn resolve_closure(
     Ok(Value::Closure(args.into(), callable, FunctorApp::default()))
 }
 
-fn lit_to_val(lit: &Lit) -> Value {
+/// Applies a binary operator to two values the way the evaluator does. The operands of a logical
+/// operator have both been evaluated already, so it does not short-circuit.
+/// # Errors
+/// Returns an error if the operator is not defined for the values, such as a division by zero.
+/// # Panics
+/// Panics if the values do not have a type the operator accepts.
+pub fn binop_value(
+    op: BinOp,
+    lhs: Value,
+    rhs: Value,
+    rhs_span: PackageSpan,
+) -> Result<Value, Error> {
+    Ok(match op {
+        BinOp::Add => eval_binop_add(lhs, rhs),
+        BinOp::AndB => eval_binop_andb(lhs, rhs),
+        BinOp::AndL => Value::Bool(lhs.unwrap_bool() && rhs.unwrap_bool()),
+        BinOp::Div => eval_binop_div(lhs, rhs, rhs_span)?,
+        BinOp::Eq => Value::Bool(lhs == rhs),
+        BinOp::Exp => eval_binop_exp(lhs, rhs, rhs_span)?,
+        BinOp::Gt => eval_binop_gt(lhs, rhs),
+        BinOp::Gte => eval_binop_gte(lhs, rhs),
+        BinOp::Lt => eval_binop_lt(lhs, rhs),
+        BinOp::Lte => eval_binop_lte(lhs, rhs),
+        BinOp::Mod => eval_binop_mod(lhs, rhs, rhs_span)?,
+        BinOp::Mul => eval_binop_mul(lhs, rhs),
+        BinOp::Neq => Value::Bool(lhs != rhs),
+        BinOp::OrB => eval_binop_orb(lhs, rhs),
+        BinOp::OrL => Value::Bool(lhs.unwrap_bool() || rhs.unwrap_bool()),
+        BinOp::Shl => eval_binop_shl(lhs, rhs, rhs_span)?,
+        BinOp::Shr => eval_binop_shr(lhs, rhs, rhs_span)?,
+        BinOp::Sub => eval_binop_sub(lhs, rhs),
+        BinOp::XorB => eval_binop_xorb(lhs, rhs),
+    })
+}
+
+/// Applies a unary operator to a value the way the evaluator does.
+/// # Panics
+/// Panics if the value does not have a type the operator accepts.
+#[must_use]
+pub fn unop_value(op: UnOp, val: Value) -> Value {
+    match op {
+        UnOp::Functor(functor) => match val {
+            Value::Closure(args, id, app) => {
+                Value::Closure(args, id, update_functor_app(functor, app))
+            }
+            Value::Global(id, app) => Value::Global(id, update_functor_app(functor, app)),
+            _ => panic!("value should be callable"),
+        },
+        UnOp::Neg => match val {
+            Value::BigInt(v) => Value::BigInt(v.neg()),
+            Value::Complex(v) => Value::Complex(v.neg()),
+            Value::Double(v) => Value::Double(v.neg()),
+            Value::Int(v) => Value::Int(v.wrapping_neg()),
+            _ => panic!("value should be number"),
+        },
+        UnOp::NotB => match val {
+            Value::Int(v) => Value::Int(!v),
+            Value::BigInt(v) => Value::BigInt(!v),
+            _ => panic!("value should be Int or BigInt"),
+        },
+        UnOp::NotL => match val {
+            Value::Bool(b) => Value::Bool(!b),
+            _ => panic!("value should be bool"),
+        },
+        UnOp::Pos => match val {
+            Value::BigInt(_) | Value::Complex(_) | Value::Int(_) | Value::Double(_) => val,
+            _ => panic!("value should be number"),
+        },
+        UnOp::Unwrap => complex_as_tuple(val.erase_udt()),
+    }
+}
+
+/// Accesses a field of a record or a range the way the evaluator does.
+/// # Panics
+/// Panics if the value does not have the field.
+#[must_use]
+pub fn field_value(record: Value, field: &Field) -> Value {
+    match (record, field) {
+        (Value::Range(Some(start), _, _), Field::Prim(PrimField::Start)) => Value::Int(start),
+        (Value::Range(_, step, _), Field::Prim(PrimField::Step)) => Value::Int(step),
+        (Value::Range(_, _, Some(end)), Field::Prim(PrimField::End)) => Value::Int(end),
+        (record, Field::Path(path)) => {
+            follow_field_path(record, &path.indices).expect("field path should be valid")
+        }
+        _ => panic!("invalid field access"),
+    }
+}
+
+/// Copies a record or a range with one of its fields replaced, the way the evaluator does.
+/// # Panics
+/// Panics if the value does not have the field.
+#[must_use]
+pub fn update_field_value(record: Value, field: &Field, value: Value) -> Value {
+    match (record, field) {
+        (Value::Range(_, step, end), Field::Prim(PrimField::Start)) => {
+            Value::Range(Some(value.unwrap_int()), step, end)
+        }
+        (Value::Range(start, _, end), Field::Prim(PrimField::Step)) => {
+            Value::Range(start, value.unwrap_int(), end)
+        }
+        (Value::Range(start, step, _), Field::Prim(PrimField::End)) => {
+            Value::Range(start, step, Some(value.unwrap_int()))
+        }
+        (Value::Udt(udt, record), Field::Path(path)) => Value::Udt(
+            udt,
+            Rc::new(
+                update_field_path(&record, &path.indices, &value)
+                    .expect("field path should be valid"),
+            ),
+        ),
+        (record, Field::Path(path)) => {
+            update_field_path(&record, &path.indices, &value).expect("field path should be valid")
+        }
+        _ => panic!("invalid field access"),
+    }
+}
+
+/// Indexes an array with an integer or slices it with a range, the way the evaluator does.
+/// # Errors
+/// Returns an error if the index is out of range.
+/// # Panics
+/// Panics if the index is not an integer or a range.
+pub fn index_value(arr: &[Value], index: &Value, span: PackageSpan) -> Result<Value, Error> {
+    match *index {
+        Value::Int(i) => index_array(arr, i, span),
+        Value::Range(start, step, end) => slice_array(arr, start, step, end, span),
+        _ => panic!("array should only be indexed by Int or Range"),
+    }
+}
+
+/// Copies an array with the item at an integer index, or the items in a range, replaced the way
+/// the evaluator does.
+/// # Errors
+/// Returns an error if the index is out of range.
+/// # Panics
+/// Panics if the index is not an integer or a range.
+pub fn update_index_value(
+    values: &[Value],
+    index: &Value,
+    update: Value,
+    span: PackageSpan,
+) -> Result<Value, Error> {
+    let mut values = values.to_vec();
+    match *index {
+        Value::Int(index) => {
+            if index < 0 {
+                return Err(Error::InvalidNegativeInt(index, span));
+            }
+            let i = index.as_index(span)?;
+            match values.get_mut(i) {
+                Some(value) => *value = update,
+                None => return Err(Error::IndexOutOfRange(index, span)),
+            }
+        }
+        Value::Range(start, step, end) => {
+            let range = make_range(&values, start, step, end, span)?;
+            let update = update.unwrap_array();
+            for (idx, update) in range.into_iter().zip(update.iter()) {
+                let i = idx.as_index(span)?;
+                match values.get_mut(i) {
+                    Some(value) => *value = update.clone(),
+                    None => return Err(Error::IndexOutOfRange(idx, span)),
+                }
+            }
+        }
+        _ => unreachable!("array should only be indexed by Int or Range"),
+    }
+    Ok(Value::Array(values.into()))
+}
+
+/// The value of a literal.
+#[must_use]
+pub fn lit_to_val(lit: &Lit) -> Value {
     match lit {
         Lit::BigInt(v) => Value::BigInt(v.clone()),
         Lit::Bool(v) => Value::Bool(*v),
@@ -1720,6 +2161,10 @@ fn eval_binop_add(lhs_val: Value, rhs_val: Value) -> Value {
             let rhs = rhs_val.unwrap_big_int();
             Value::BigInt(val + rhs)
         }
+        Value::Complex(val) => {
+            let rhs = rhs_val.unwrap_complex();
+            Value::Complex(val + rhs)
+        }
         Value::Double(val) => {
             let rhs = rhs_val.unwrap_double();
             Value::Double(val + rhs)
@@ -1768,6 +2213,10 @@ fn eval_binop_div(lhs_val: Value, rhs_val: Value, rhs_span: PackageSpan) -> Resu
                 Ok(Value::Int(val.wrapping_div(rhs)))
             }
         }
+        Value::Complex(val) => {
+            let rhs = rhs_val.unwrap_complex();
+            Ok(Value::Complex(val / rhs))
+        }
         Value::Double(val) => {
             let rhs = rhs_val.unwrap_double();
             Ok(Value::Double(val / rhs))
@@ -1790,6 +2239,7 @@ fn eval_binop_exp(lhs_val: Value, rhs_val: Value, rhs_span: PackageSpan) -> Resu
                 Ok(Value::BigInt(val.pow(rhs_val)))
             }
         }
+        Value::Complex(val) => Ok(Value::Complex(val.powc(rhs_val.unwrap_complex()))),
         Value::Double(val) => Ok(Value::Double(val.powf(rhs_val.unwrap_double()))),
         Value::Int(val) => {
             let rhs_val = rhs_val.unwrap_int();
@@ -1921,6 +2371,10 @@ fn eval_binop_mul(lhs_val: Value, rhs_val: Value) -> Value {
             let rhs = rhs_val.unwrap_int();
             Value::Int(val.wrapping_mul(rhs))
         }
+        Value::Complex(val) => {
+            let rhs = rhs_val.unwrap_complex();
+            Value::Complex(val * rhs)
+        }
         Value::Double(val) => {
             let rhs = rhs_val.unwrap_double();
             Value::Double(val * rhs)
@@ -2009,6 +2463,10 @@ fn eval_binop_sub(lhs_val: Value, rhs_val: Value) -> Value {
             let rhs = rhs_val.unwrap_big_int();
             Value::BigInt(val - rhs)
         }
+        Value::Complex(val) => {
+            let rhs = rhs_val.unwrap_complex();
+            Value::Complex(val - rhs)
+        }
         Value::Double(val) => {
             let rhs = rhs_val.unwrap_double();
             Value::Double(val - rhs)
@@ -2048,9 +2506,19 @@ fn update_functor_app(functor: Functor, app: FunctorApp) -> FunctorApp {
     }
 }
 
-fn follow_field_path(mut value: Value, path: &[usize]) -> Option<Value> {
+/// Converts a complex number into the tuple of its real and imaginary parts, which is how the
+/// `Complex` type of the standard library is laid out, leaving other values unchanged.
+fn complex_as_tuple(value: Value) -> Value {
+    match value {
+        Value::Complex(c) => Value::Tuple([Value::Double(c.re), Value::Double(c.im)].into()),
+        value => value,
+    }
+}
+
+fn follow_field_path(value: Value, path: &[usize]) -> Option<Value> {
+    let mut value = value.erase_udt();
     for &index in path {
-        let Value::Tuple(items) = value else {
+        let Value::Tuple(items) = complex_as_tuple(value) else {
             return None;
         };
         value = items[index].clone();
@@ -2059,6 +2527,14 @@ fn follow_field_path(mut value: Value, path: &[usize]) -> Option<Value> {
 }
 
 fn update_field_path(record: &Value, path: &[usize], replace: &Value) -> Option<Value> {
+    if let (Value::Complex(c), &[index]) = (record, path) {
+        let part = replace.clone().unwrap_double();
+        return match index {
+            0 => Some(Value::Complex(Complex64::new(part, c.im))),
+            1 => Some(Value::Complex(Complex64::new(c.re, part))),
+            _ => None,
+        };
+    }
     match (record, path) {
         (_, []) => Some(replace.clone()),
         (Value::Tuple(items), &[next_index, ..]) if next_index < items.len() => {
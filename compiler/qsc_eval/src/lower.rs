@@ -68,7 +68,9 @@ impl Lowerer {
             pats,
             stmts,
         };
-        qsc_fir::validate::validate(&package);
+        if cfg!(debug_assertions) {
+            qsc_fir::validate::validate(&package);
+        }
         package
     }
 
@@ -100,7 +102,9 @@ impl Lowerer {
             fir_package.items.insert(k, v);
         }
 
-        qsc_fir::validate::validate(fir_package);
+        if cfg!(debug_assertions) {
+            qsc_fir::validate::validate(fir_package);
+        }
 
         new_stmts
     }
@@ -519,7 +523,17 @@ fn lower_generics(generics: &[qsc_hir::ty::GenericParam]) -> Vec<qsc_fir::ty::Ge
 }
 
 fn lower_attrs(attrs: &[hir::Attr]) -> Vec<fir::Attr> {
-    attrs.iter().map(|_| fir::Attr::EntryPoint).collect()
+    attrs
+        .iter()
+        .filter_map(|attr| match attr {
+            hir::Attr::EntryPoint => Some(fir::Attr::EntryPoint),
+            hir::Attr::Allow
+            | hir::Attr::Config
+            | hir::Attr::NoInline
+            | hir::Attr::Unimplemented
+            | hir::Attr::Deprecated(_) => None,
+        })
+        .collect()
 }
 
 fn lower_functors(functors: qsc_hir::ty::FunctorSetValue) -> qsc_fir::ty::FunctorSetValue {
@@ -528,7 +542,7 @@ fn lower_functors(functors: qsc_hir::ty::FunctorSetValue) -> qsc_fir::ty::Functo
 
 fn lower_generic_param(g: &qsc_hir::ty::GenericParam) -> qsc_fir::ty::GenericParam {
     match g {
-        qsc_hir::ty::GenericParam::Ty(_) => qsc_fir::ty::GenericParam::Ty,
+        qsc_hir::ty::GenericParam::Ty { .. } => qsc_fir::ty::GenericParam::Ty,
         qsc_hir::ty::GenericParam::Functor(value) => {
             qsc_fir::ty::GenericParam::Functor(lower_functor_set_value(*value))
         }
@@ -1,50 +1,629 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::io::{Cursor, Write};
+#[cfg(test)]
+mod tests;
 
-use crate::state::{fmt_complex, format_state_id};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{Cursor, Write},
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{
+    debug::Frame,
+    error::PackageSpan,
+    intrinsic::utils::split_state,
+    state::{fmt_complex, fmt_dirac, format_state_id, get_latex, BlochVector, StateFormat},
+    val::Value,
+};
 use num_bigint::BigUint;
 use num_complex::Complex64;
 
 #[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Error;
 
+/// The default number of amplitudes in each chunk of a [`StateDump`].
+pub const DEFAULT_CHUNK_SIZE: usize = 1024;
+
+/// A single amplitude of a dumped state, together with the label of its basis state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateEntry {
+    pub id: BigUint,
+    /// The basis state label, such as `|01⟩`.
+    pub label: String,
+    pub amplitude: Complex64,
+}
+
+/// A quantum state that is produced lazily in chunks of amplitudes, so that large states can be
+/// streamed to a [`Receiver`] without collecting every amplitude into a single allocation.
+pub struct StateDump<'a> {
+    amplitudes: Box<dyn Iterator<Item = (BigUint, Complex64)> + 'a>,
+    qubit_count: usize,
+    total: usize,
+    chunk_size: usize,
+    threshold: f64,
+    omitted: usize,
+}
+
+impl<'a> StateDump<'a> {
+    /// Creates a dump of the `total` amplitudes produced by `amplitudes`, which must be sorted by
+    /// basis state.
+    pub fn new(
+        amplitudes: impl Iterator<Item = (BigUint, Complex64)> + 'a,
+        qubit_count: usize,
+        total: usize,
+    ) -> Self {
+        Self {
+            amplitudes: Box::new(amplitudes),
+            qubit_count,
+            total,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            threshold: 0.0,
+            omitted: 0,
+        }
+    }
+
+    /// Creates a dump of an already captured state.
+    #[must_use]
+    pub fn from_state(state: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Self {
+        let total = state.len();
+        Self::new(state.into_iter(), qubit_count, total)
+    }
+
+    /// Sets the maximum number of amplitudes in each chunk.
+    #[must_use]
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Omits amplitudes whose magnitude is below `threshold`.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Keeps only the `k` amplitudes of largest magnitude, omitting the rest. This collects the
+    /// remaining amplitudes of the state.
+    #[must_use]
+    pub fn with_top_k(mut self, k: usize) -> Self {
+        let mut state = self.amplitudes.collect::<Vec<_>>();
+        if state.len() > k {
+            state.sort_by(|(_, a), (_, b)| b.norm_sqr().total_cmp(&a.norm_sqr()));
+            self.omitted += state.len() - k;
+            state.truncate(k);
+            state.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        self.amplitudes = Box::new(state.into_iter());
+        self
+    }
+
+    /// Restricts the dump to the state of the given qubits, in the given order, which is only
+    /// possible if they are not entangled with the other qubits. Otherwise, the unchanged dump is
+    /// returned as the error. This collects the remaining amplitudes of the state.
+    /// # Errors
+    /// Returns the unchanged dump if the qubits are entangled with the other qubits.
+    pub fn with_qubits(mut self, qubits: &[usize]) -> Result<Self, Self> {
+        let state = self.amplitudes.collect::<Vec<_>>();
+        if let Ok(split) = split_state(qubits, state.clone(), self.qubit_count) {
+            self.qubit_count = qubits.len();
+            self.total = split.len();
+            self.amplitudes = Box::new(split.into_iter());
+            Ok(self)
+        } else {
+            self.amplitudes = Box::new(state.into_iter());
+            Err(self)
+        }
+    }
+
+    /// Keeps only the first `count` amplitudes, dropping the rest without counting them as omitted.
+    #[must_use]
+    pub fn with_max_amplitudes(mut self, count: usize) -> Self {
+        self.amplitudes = Box::new(self.amplitudes.take(count));
+        self
+    }
+
+    /// Collects the remaining amplitudes that meet the threshold into an array of tuples of basis
+    /// state and amplitude, as `(BigInt, Complex)` values.
+    #[must_use]
+    pub fn into_value(mut self) -> Value {
+        let mut entries = Vec::new();
+        while let Some(chunk) = self.next_chunk() {
+            entries.extend(chunk.into_iter().map(|entry| {
+                Value::Tuple([Value::BigInt(entry.id.into()), entry.amplitude.into()].into())
+            }));
+        }
+        Value::Array(entries.into())
+    }
+
+    #[must_use]
+    pub fn qubit_count(&self) -> usize {
+        self.qubit_count
+    }
+
+    /// The total number of amplitudes in the state, including those omitted by the threshold.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The number of amplitudes omitted by the threshold and top-K filters so far.
+    #[must_use]
+    pub fn omitted(&self) -> usize {
+        self.omitted
+    }
+
+    /// Returns the next chunk of amplitudes that meet the threshold, or `None` once the state is
+    /// exhausted.
+    pub fn next_chunk(&mut self) -> Option<Vec<StateEntry>> {
+        let mut chunk = Vec::new();
+        for (id, amplitude) in self.amplitudes.by_ref() {
+            if amplitude.norm() < self.threshold {
+                self.omitted += 1;
+                continue;
+            }
+            chunk.push(StateEntry {
+                label: format_state_id(&id, self.qubit_count),
+                id,
+                amplitude,
+            });
+            if chunk.len() == self.chunk_size {
+                break;
+            }
+        }
+        (!chunk.is_empty()).then_some(chunk)
+    }
+}
+
 pub trait Receiver {
-    /// Receive state output
+    /// Receive state output. By default, the state is passed to [`Receiver::state_dump`].
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn state(&mut self, state: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Result<(), Error> {
+        self.state_dump(StateDump::from_state(state, qubit_count))
+    }
+
+    /// Receive state output as a stream of chunks. Receivers should handle one chunk at a time
+    /// rather than collecting them, so that large states are never held in full.
     /// # Errors
     /// This will return an error if handling the output fails.
-    fn state(&mut self, state: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Result<(), Error>;
+    fn state_dump(&mut self, dump: StateDump) -> Result<(), Error>;
 
     /// Receive generic message output
     /// # Errors
     /// This will return an error if handling the output fails.
     fn message(&mut self, msg: &str) -> Result<(), Error>;
+
+    /// Receive message output emitted by the call at the given span. The caller is the frame of
+    /// the callable that made the call, or `None` if it was made at the top level. By default, the
+    /// location is dropped and the message is passed to [`Receiver::message`].
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn message_at(
+        &mut self,
+        msg: &str,
+        _span: PackageSpan,
+        _caller: Option<&Frame>,
+    ) -> Result<(), Error> {
+        self.message(msg)
+    }
+
+    /// Receive a runtime warning raised at the given span. By default, warnings are written as
+    /// messages.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn warning(&mut self, msg: &str, _span: PackageSpan) -> Result<(), Error> {
+        self.message(&format!("warning: {msg}"))
+    }
+
+    /// Receive a matrix, such as the unitary of an operation, whose rows are indexed by basis
+    /// state. By default, the rows are written as a message.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn matrix(&mut self, matrix: Vec<Vec<Complex64>>) -> Result<(), Error> {
+        let mut msg = "MATRIX:".to_string();
+        for row in &matrix {
+            msg.push('\n');
+            let row = row.iter().map(fmt_complex).collect::<Vec<_>>();
+            msg.push_str(&row.join(" "));
+        }
+        self.message(&msg)
+    }
+
+    /// Receive the Bloch vector of a qubit. By default, the vector is written as a message.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn bloch_vector(&mut self, vector: &BlochVector) -> Result<(), Error> {
+        self.message(&format!("BLOCH VECTOR: {vector}"))
+    }
+
+    /// Receive a progress heartbeat, which is sent periodically during long-running evaluations.
+    /// By default, heartbeats are ignored.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn progress(&mut self, _progress: &Progress) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Receive the value of an evaluated entry expression. By default, the value is ignored, since
+    /// it is also returned to the caller of the evaluation.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn result(&mut self, _value: &Value) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Receive a marker that output was truncated by a [`LimitedReceiver`]. By default, the
+    /// truncation is written as a message.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn truncated(&mut self, truncation: &Truncation) -> Result<(), Error> {
+        self.message(&truncation.to_string())
+    }
+}
+
+/// A progress heartbeat of a running evaluation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    /// The number of statements executed so far.
+    pub statements: u64,
+    /// The name of the callable being executed, or `None` at the top level.
+    pub callable: Option<Rc<str>>,
+    /// The time elapsed since evaluation started, or `None` on targets without a clock.
+    pub elapsed: Option<Duration>,
+}
+
+/// An output produced during evaluation, as recorded by a [`CaptureReceiver`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputEvent {
+    Message(String),
+    StateDump {
+        qubit_count: usize,
+        /// The amplitudes of the basis states, sorted by basis state.
+        amplitudes: Vec<StateEntry>,
+    },
+    Matrix(Vec<Vec<Complex64>>),
+    BlochVector(BlochVector),
+    Result(Value),
+    Truncated(Truncation),
+}
+
+/// A receiver that records output as a list of [`OutputEvent`]s instead of formatting it, so that
+/// hosts and tests can inspect the output programmatically.
+#[derive(Default)]
+pub struct CaptureReceiver {
+    events: Vec<OutputEvent>,
+    filter: DumpFilter,
+}
+
+impl CaptureReceiver {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `filter` to state output. A note that the qubit subset could not be applied is
+    /// recorded as a message after the state.
+    #[must_use]
+    pub fn with_filter(mut self, filter: DumpFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// The events recorded so far, in the order they were received.
+    #[must_use]
+    pub fn events(&self) -> &[OutputEvent] {
+        &self.events
+    }
+
+    /// Removes and returns the events recorded so far.
+    pub fn take_events(&mut self) -> Vec<OutputEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Receiver for CaptureReceiver {
+    fn state_dump(&mut self, dump: StateDump) -> Result<(), Error> {
+        let (mut dump, note) = self.filter.apply(dump);
+        let mut amplitudes = Vec::new();
+        while let Some(chunk) = dump.next_chunk() {
+            amplitudes.extend(chunk);
+        }
+        self.events.push(OutputEvent::StateDump {
+            qubit_count: dump.qubit_count(),
+            amplitudes,
+        });
+        self.events.extend(note.map(OutputEvent::Message));
+        Ok(())
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), Error> {
+        self.events.push(OutputEvent::Message(msg.to_string()));
+        Ok(())
+    }
+
+    fn matrix(&mut self, matrix: Vec<Vec<Complex64>>) -> Result<(), Error> {
+        self.events.push(OutputEvent::Matrix(matrix));
+        Ok(())
+    }
+
+    fn bloch_vector(&mut self, vector: &BlochVector) -> Result<(), Error> {
+        self.events.push(OutputEvent::BlochVector(*vector));
+        Ok(())
+    }
+
+    fn result(&mut self, value: &Value) -> Result<(), Error> {
+        self.events.push(OutputEvent::Result(value.clone()));
+        Ok(())
+    }
+
+    fn truncated(&mut self, truncation: &Truncation) -> Result<(), Error> {
+        self.events.push(OutputEvent::Truncated(*truncation));
+        Ok(())
+    }
+}
+
+/// Caps on the volume of output, in bytes. Messages and warnings are measured by the length of
+/// their text, each amplitude of a state dump counts as 16 bytes plus one byte per qubit, and each
+/// element of a matrix counts as 16 bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OutputLimits {
+    /// The maximum size of a single output event. Larger events are cut to this size.
+    pub max_event_size: Option<usize>,
+    /// The maximum size of all output combined. Once it is reached, further output is dropped.
+    pub max_total_size: Option<usize>,
+}
+
+/// A marker that output was truncated to stay within the [`OutputLimits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Truncation {
+    /// An event of `size` bytes was cut to the per-event limit.
+    Event { size: usize, limit: usize },
+    /// The total limit was reached, so the rest of the output is dropped.
+    Total { limit: usize },
+}
+
+impl Display for Truncation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Truncation::Event { size, limit } => write!(
+                f,
+                "(output of {size} bytes truncated to the limit of {limit} bytes per event)"
+            ),
+            Truncation::Total { limit } => write!(
+                f,
+                "(output limit of {limit} bytes reached, further output is omitted)"
+            ),
+        }
+    }
+}
+
+/// A receiver that enforces [`OutputLimits`] on the output forwarded to an inner receiver, so that
+/// runaway output cannot consume unbounded memory. Output that exceeds a limit is cut and followed
+/// by a [`Truncation`] marker rather than failing. Progress heartbeats and entry results are not
+/// counted and are always forwarded.
+pub struct LimitedReceiver<'a> {
+    inner: &'a mut dyn Receiver,
+    limits: OutputLimits,
+    used: usize,
+    exhausted: bool,
+}
+
+impl<'a> LimitedReceiver<'a> {
+    pub fn new(inner: &'a mut impl Receiver, limits: OutputLimits) -> Self {
+        Self {
+            inner,
+            limits,
+            used: 0,
+            exhausted: false,
+        }
+    }
+
+    /// The size of the output forwarded so far.
+    #[must_use]
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Returns how much of an event of the given size may be forwarded, along with the truncation
+    /// to report, if any. Returns `None` if the total limit was already reached.
+    fn admit(&mut self, size: usize) -> Option<(usize, Option<Truncation>)> {
+        if self.exhausted {
+            return None;
+        }
+        let mut allowed = size;
+        let mut truncation = None;
+        if let Some(limit) = self.limits.max_event_size {
+            if size > limit {
+                allowed = limit;
+                truncation = Some(Truncation::Event { size, limit });
+            }
+        }
+        if let Some(limit) = self.limits.max_total_size {
+            let remaining = limit.saturating_sub(self.used);
+            if allowed > remaining {
+                allowed = remaining;
+                truncation = Some(Truncation::Total { limit });
+                self.exhausted = true;
+            }
+        }
+        self.used += allowed;
+        Some((allowed, truncation))
+    }
+
+    /// Forwards the part of a message that fits within the limits, cut at a character boundary.
+    fn forward_text(
+        &mut self,
+        msg: &str,
+        forward: impl FnOnce(&mut dyn Receiver, &str) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let Some((mut allowed, truncation)) = self.admit(msg.len()) else {
+            return Ok(());
+        };
+        while !msg.is_char_boundary(allowed) {
+            allowed -= 1;
+        }
+        if allowed > 0 || truncation.is_none() {
+            forward(self.inner, &msg[..allowed])?;
+        }
+        self.report(truncation)
+    }
+
+    fn report(&mut self, truncation: Option<Truncation>) -> Result<(), Error> {
+        match truncation {
+            Some(truncation) => self.inner.truncated(&truncation),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The size of a complex number in the output.
+const COMPLEX_SIZE: usize = std::mem::size_of::<Complex64>();
+
+/// The size of a Bloch vector in the output, which is never truncated partially.
+const BLOCH_VECTOR_SIZE: usize = std::mem::size_of::<BlochVector>();
+
+impl Receiver for LimitedReceiver<'_> {
+    fn state_dump(&mut self, dump: StateDump) -> Result<(), Error> {
+        let amplitude_size = COMPLEX_SIZE + dump.qubit_count();
+        let Some((allowed, truncation)) = self.admit(dump.total() * amplitude_size) else {
+            return Ok(());
+        };
+        let count = allowed / amplitude_size;
+        if count > 0 || truncation.is_none() {
+            self.inner.state_dump(dump.with_max_amplitudes(count))?;
+        }
+        self.report(truncation)
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), Error> {
+        self.forward_text(msg, |inner, msg| inner.message(msg))
+    }
+
+    fn message_at(
+        &mut self,
+        msg: &str,
+        span: PackageSpan,
+        caller: Option<&Frame>,
+    ) -> Result<(), Error> {
+        self.forward_text(msg, |inner, msg| inner.message_at(msg, span, caller))
+    }
+
+    fn warning(&mut self, msg: &str, span: PackageSpan) -> Result<(), Error> {
+        self.forward_text(msg, |inner, msg| inner.warning(msg, span))
+    }
+
+    fn matrix(&mut self, mut matrix: Vec<Vec<Complex64>>) -> Result<(), Error> {
+        let row_size = matrix.first().map_or(0, Vec::len) * COMPLEX_SIZE;
+        let Some((allowed, truncation)) = self.admit(matrix.len() * row_size) else {
+            return Ok(());
+        };
+        if let Some(rows) = allowed.checked_div(row_size) {
+            matrix.truncate(rows);
+        }
+        if !matrix.is_empty() || truncation.is_none() {
+            self.inner.matrix(matrix)?;
+        }
+        self.report(truncation)
+    }
+
+    fn bloch_vector(&mut self, vector: &BlochVector) -> Result<(), Error> {
+        let Some((allowed, truncation)) = self.admit(BLOCH_VECTOR_SIZE) else {
+            return Ok(());
+        };
+        if allowed == BLOCH_VECTOR_SIZE {
+            self.inner.bloch_vector(vector)?;
+        }
+        self.report(truncation)
+    }
+
+    fn progress(&mut self, progress: &Progress) -> Result<(), Error> {
+        self.inner.progress(progress)
+    }
+
+    fn result(&mut self, value: &Value) -> Result<(), Error> {
+        self.inner.result(value)
+    }
+
+    fn truncated(&mut self, truncation: &Truncation) -> Result<(), Error> {
+        self.inner.truncated(truncation)
+    }
+}
+
+/// How a [`GenericReceiver`] renders dumped states.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DumpStyle {
+    /// One line per basis state with its amplitude.
+    #[default]
+    Table,
+    /// A single line in Dirac notation, such as `0.7071|00⟩ + 0.7071|11⟩`.
+    Dirac,
+    /// A LaTeX equation, falling back to Dirac notation for states that have no concise LaTeX form.
+    Latex,
 }
 
 pub struct GenericReceiver<'a> {
     writer: &'a mut dyn Write,
+    filter: DumpFilter,
+    format: StateFormat,
+    style: DumpStyle,
 }
 
 impl<'a> GenericReceiver<'a> {
     pub fn new(writer: &'a mut impl Write) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            filter: DumpFilter::default(),
+            format: StateFormat::default(),
+            style: DumpStyle::default(),
+        }
+    }
+
+    /// Renders state output in `style`.
+    #[must_use]
+    pub fn with_style(mut self, style: DumpStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Formats the amplitudes and basis state labels of state output with `format`.
+    #[must_use]
+    pub fn with_format(mut self, format: StateFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Omits amplitudes whose magnitude is below `threshold` from state output.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.filter = self.filter.with_threshold(threshold);
+        self
+    }
+
+    /// Applies `filter` to state output.
+    #[must_use]
+    pub fn with_filter(mut self, filter: DumpFilter) -> Self {
+        self.filter = filter;
+        self
     }
 }
 
 impl<'a> Receiver for GenericReceiver<'a> {
-    fn state(&mut self, state: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Result<(), Error> {
-        writeln!(self.writer, "STATE:").map_err(|_| Error)?;
-        for (id, state) in state {
-            writeln!(
-                self.writer,
+    fn state_dump(&mut self, dump: StateDump) -> Result<(), Error> {
+        let format = self.format;
+        if self.style != DumpStyle::Table {
+            return write_state_formula(self.writer, dump, &self.filter, self.style, &format);
+        }
+        write_state_dump(self.writer, dump, &self.filter, |entry, qubit_count| {
+            format!(
                 "{}: {}",
-                format_state_id(&id, qubit_count),
-                fmt_complex(&state),
+                format.format_label(&entry.id, qubit_count),
+                format.format_amplitude(&entry.amplitude)
             )
-            .map_err(|_| Error)?;
-        }
-        Ok(())
+        })
     }
 
     fn message(&mut self, msg: &str) -> Result<(), Error> {
@@ -54,12 +633,31 @@ impl<'a> Receiver for GenericReceiver<'a> {
 
 pub struct CursorReceiver<'a> {
     cursor: &'a mut Cursor<Vec<u8>>,
+    filter: DumpFilter,
 }
 
 impl<'a> CursorReceiver<'a> {
     pub fn new(cursor: &'a mut Cursor<Vec<u8>>) -> Self {
-        Self { cursor }
+        Self {
+            cursor,
+            filter: DumpFilter::default(),
+        }
+    }
+
+    /// Omits amplitudes whose magnitude is below `threshold` from state output.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.filter = self.filter.with_threshold(threshold);
+        self
+    }
+
+    /// Applies `filter` to state output.
+    #[must_use]
+    pub fn with_filter(mut self, filter: DumpFilter) -> Self {
+        self.filter = filter;
+        self
     }
+
     pub fn dump(&mut self) -> String {
         let v = self.cursor.get_mut();
         let s = match std::str::from_utf8(v) {
@@ -72,21 +670,146 @@ impl<'a> CursorReceiver<'a> {
 }
 
 impl<'a> Receiver for CursorReceiver<'a> {
-    fn state(&mut self, state: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Result<(), Error> {
-        writeln!(self.cursor, "STATE:").map_err(|_| Error)?;
-        for (id, state) in state {
-            writeln!(
-                self.cursor,
-                "{}: {}",
-                format_state_id(&id, qubit_count),
-                state
-            )
-            .map_err(|_| Error)?;
-        }
-        Ok(())
+    fn state_dump(&mut self, dump: StateDump) -> Result<(), Error> {
+        write_state_dump(self.cursor, dump, &self.filter, |entry, _| {
+            format!("{}: {}", entry.label, entry.amplitude)
+        })
     }
 
     fn message(&mut self, msg: &str) -> Result<(), Error> {
         writeln!(self.cursor, "{msg}").map_err(|_| Error)
     }
 }
+
+/// Filters that a receiver applies to dumped states before they are formatted or transferred, for
+/// states that are too large to be read in full.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DumpFilter {
+    qubits: Option<Vec<usize>>,
+    threshold: f64,
+    top_k: Option<usize>,
+}
+
+impl DumpFilter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dumps only the state of the given qubits, in the given order. If they are entangled with the
+    /// other qubits, the full state is dumped instead, along with a note.
+    #[must_use]
+    pub fn with_qubits(mut self, qubits: Vec<usize>) -> Self {
+        self.qubits = Some(qubits);
+        self
+    }
+
+    /// Omits amplitudes whose magnitude is below `threshold`.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Omits basis states whose probability is below `probability`.
+    #[must_use]
+    pub fn with_min_probability(self, probability: f64) -> Self {
+        self.with_threshold(probability.sqrt())
+    }
+
+    /// Keeps only the `k` amplitudes of largest magnitude.
+    #[must_use]
+    pub fn with_top_k(mut self, k: usize) -> Self {
+        self.top_k = Some(k);
+        self
+    }
+
+    /// Applies the filter to `dump`, along with a note to report if the qubit subset could not be
+    /// applied.
+    fn apply<'a>(&self, mut dump: StateDump<'a>) -> (StateDump<'a>, Option<String>) {
+        let mut note = None;
+        if let Some(qubits) = &self.qubits {
+            dump = dump.with_qubits(qubits).unwrap_or_else(|dump| {
+                let qubits = qubits
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                note = Some(format!(
+                    "(the qubit subset [{qubits}] is entangled with other qubits, so the full state is shown)"
+                ));
+                dump
+            });
+        }
+        if let Some(k) = self.top_k {
+            dump = dump.with_top_k(k);
+        }
+        (dump.with_threshold(self.threshold), note)
+    }
+}
+
+/// Writes the state one chunk at a time in the same format as [`Receiver::state`], after applying
+/// the filter, followed by a note on how many amplitudes were omitted by the filter, if any.
+fn write_state_dump(
+    writer: &mut dyn Write,
+    dump: StateDump,
+    filter: &DumpFilter,
+    fmt_entry: impl Fn(&StateEntry, usize) -> String,
+) -> Result<(), Error> {
+    let (mut dump, note) = filter.apply(dump);
+    writeln!(writer, "STATE:").map_err(|_| Error)?;
+    while let Some(chunk) = dump.next_chunk() {
+        for entry in &chunk {
+            writeln!(writer, "{}", fmt_entry(entry, dump.qubit_count())).map_err(|_| Error)?;
+        }
+    }
+    write_dump_notes(writer, &dump, note)
+}
+
+/// Writes the whole state as a single formula in the given style, after applying the filter,
+/// followed by the same notes as [`write_state_dump`].
+fn write_state_formula(
+    writer: &mut dyn Write,
+    dump: StateDump,
+    filter: &DumpFilter,
+    style: DumpStyle,
+    format: &StateFormat,
+) -> Result<(), Error> {
+    let (mut dump, note) = filter.apply(dump);
+    let mut state = Vec::new();
+    while let Some(chunk) = dump.next_chunk() {
+        state.extend(chunk.into_iter().map(|entry| (entry.id, entry.amplitude)));
+    }
+    let qubit_count = dump.qubit_count();
+    let latex = match style {
+        DumpStyle::Latex => get_latex(&state, qubit_count),
+        DumpStyle::Table | DumpStyle::Dirac => String::new(),
+    };
+    let formula = if latex.is_empty() {
+        fmt_dirac(&state, qubit_count, format)
+    } else {
+        latex
+    };
+    writeln!(writer, "STATE:\n{formula}").map_err(|_| Error)?;
+    write_dump_notes(writer, &dump, note)
+}
+
+fn write_dump_notes(
+    writer: &mut dyn Write,
+    dump: &StateDump,
+    note: Option<String>,
+) -> Result<(), Error> {
+    if dump.omitted() > 0 {
+        writeln!(
+            writer,
+            "({} of {} amplitudes omitted)",
+            dump.omitted(),
+            dump.total()
+        )
+        .map_err(|_| Error)?;
+    }
+    if let Some(note) = note {
+        writeln!(writer, "{note}").map_err(|_| Error)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,360 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    CaptureReceiver, CursorReceiver, DumpFilter, DumpStyle, GenericReceiver, LimitedReceiver,
+    OutputEvent, OutputLimits, Receiver, StateDump, Truncation,
+};
+use crate::{
+    state::{AmplitudeForm, BlochVector, Endianness, LabelNotation, StateFormat},
+    val::Value,
+};
+use expect_test::expect;
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use std::io::Cursor;
+
+fn uniform_state(qubit_count: usize) -> Vec<(BigUint, Complex64)> {
+    let count = 1_usize << qubit_count;
+    #[allow(clippy::cast_precision_loss)]
+    let amplitude = Complex64::new(1.0 / (count as f64).sqrt(), 0.0);
+    (0..count)
+        .map(|id| (BigUint::from(id), amplitude))
+        .collect()
+}
+
+#[test]
+fn state_dump_is_split_into_chunks() {
+    let mut dump = StateDump::from_state(uniform_state(3), 3).with_chunk_size(3);
+    assert_eq!(dump.total(), 8);
+    let mut labels = Vec::new();
+    while let Some(chunk) = dump.next_chunk() {
+        labels.push(
+            chunk
+                .into_iter()
+                .map(|entry| entry.label)
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+    }
+    assert_eq!(
+        labels,
+        vec!["|000⟩ |001⟩ |010⟩", "|011⟩ |100⟩ |101⟩", "|110⟩ |111⟩",]
+    );
+    assert_eq!(dump.omitted(), 0);
+}
+
+#[test]
+fn state_dump_omits_amplitudes_below_threshold() {
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(0.999, 0.0)),
+        (BigUint::from(1_u8), Complex64::new(0.0, 0.001)),
+        (BigUint::from(3_u8), Complex64::new(-0.0447, 0.0)),
+    ];
+    let mut cursor = Cursor::new(Vec::new());
+    let mut out = CursorReceiver::new(&mut cursor).with_threshold(0.01);
+    out.state_dump(StateDump::from_state(state, 2))
+        .expect("writing to a cursor should succeed");
+    expect![[r"
+        STATE:
+        |00⟩: 0.999+0i
+        |11⟩: -0.0447+0i
+        (1 of 3 amplitudes omitted)"]]
+    .assert_eq(&out.dump());
+}
+
+#[test]
+fn default_state_is_received_as_state_dump() {
+    struct ChunkReceiver(Vec<usize>, usize);
+    impl Receiver for ChunkReceiver {
+        fn state_dump(&mut self, dump: StateDump) -> Result<(), super::Error> {
+            let mut dump = dump.with_chunk_size(5);
+            while let Some(chunk) = dump.next_chunk() {
+                self.0.push(chunk.len());
+            }
+            self.1 = dump.qubit_count();
+            Ok(())
+        }
+
+        fn message(&mut self, _msg: &str) -> Result<(), super::Error> {
+            Ok(())
+        }
+    }
+
+    let mut out = ChunkReceiver(Vec::new(), 0);
+    out.state(uniform_state(4), 4)
+        .expect("receiving the state should succeed");
+    assert_eq!(out.0, vec![5, 5, 5, 1]);
+    assert_eq!(out.1, 4);
+}
+
+#[test]
+fn capture_receiver_records_events_in_order() {
+    let mut out = CaptureReceiver::new();
+    out.message("hello").expect("capturing should succeed");
+    out.state(uniform_state(1), 1)
+        .expect("capturing should succeed");
+    out.matrix(vec![vec![Complex64::new(0.0, 1.0)]])
+        .expect("capturing should succeed");
+    out.bloch_vector(&BlochVector {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    })
+    .expect("capturing should succeed");
+    out.result(&Value::Int(42))
+        .expect("capturing should succeed");
+
+    let events = out.take_events();
+    assert!(out.events().is_empty());
+    let [OutputEvent::Message(msg), OutputEvent::StateDump {
+        qubit_count,
+        amplitudes,
+    }, OutputEvent::Matrix(matrix), OutputEvent::BlochVector(vector), OutputEvent::Result(value)] =
+        &events[..]
+    else {
+        panic!("unexpected events: {events:?}");
+    };
+    assert_eq!(msg, "hello");
+    assert_eq!(*qubit_count, 1);
+    assert_eq!(
+        amplitudes
+            .iter()
+            .map(|entry| (entry.label.as_str(), entry.amplitude))
+            .collect::<Vec<_>>(),
+        uniform_state(1)
+            .into_iter()
+            .zip(["|0⟩", "|1⟩"])
+            .map(|((_, amplitude), label)| (label, amplitude))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(matrix, &vec![vec![Complex64::new(0.0, 1.0)]]);
+    assert_eq!(
+        vector,
+        &BlochVector {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0
+        }
+    );
+    assert_eq!(value, &Value::Int(42));
+}
+
+#[test]
+fn default_matrix_output_is_a_message() {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut out = CursorReceiver::new(&mut cursor);
+    out.matrix(vec![
+        vec![Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0)],
+        vec![Complex64::new(1.0, 0.0), Complex64::new(0.0, 0.0)],
+    ])
+    .expect("writing to a cursor should succeed");
+    expect![[r"
+        MATRIX:
+        0.0000+0.0000𝑖 1.0000+0.0000𝑖
+        1.0000+0.0000𝑖 0.0000+0.0000𝑖"]]
+    .assert_eq(&out.dump());
+}
+
+fn product_state() -> Vec<(BigUint, Complex64)> {
+    // The state (|0⟩ + i|1⟩)/√2 ⊗ (0.6|0⟩ + 0.8|1⟩) ⊗ |1⟩.
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    vec![
+        (BigUint::from(0b001_u8), Complex64::new(0.6 * half, 0.0)),
+        (BigUint::from(0b011_u8), Complex64::new(0.8 * half, 0.0)),
+        (BigUint::from(0b101_u8), Complex64::new(0.0, 0.6 * half)),
+        (BigUint::from(0b111_u8), Complex64::new(0.0, 0.8 * half)),
+    ]
+}
+
+#[test]
+fn state_dump_keeps_top_k_amplitudes() {
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(0.1, 0.0)),
+        (BigUint::from(1_u8), Complex64::new(0.0, 0.7)),
+        (BigUint::from(2_u8), Complex64::new(0.1, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(-0.7, 0.0)),
+    ];
+    let mut cursor = Cursor::new(Vec::new());
+    let mut out = CursorReceiver::new(&mut cursor).with_filter(DumpFilter::new().with_top_k(2));
+    out.state_dump(StateDump::from_state(state, 2))
+        .expect("writing to a cursor should succeed");
+    expect![[r"
+        STATE:
+        |01⟩: 0+0.7i
+        |11⟩: -0.7+0i
+        (2 of 4 amplitudes omitted)"]]
+    .assert_eq(&out.dump());
+}
+
+#[test]
+fn state_dump_omits_states_below_probability() {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut out =
+        CursorReceiver::new(&mut cursor).with_filter(DumpFilter::new().with_min_probability(0.25));
+    out.state_dump(StateDump::from_state(product_state(), 3))
+        .expect("writing to a cursor should succeed");
+    expect![[r"
+        STATE:
+        |011⟩: 0.5656854249492381+0i
+        |111⟩: 0+0.5656854249492381i
+        (2 of 4 amplitudes omitted)"]]
+    .assert_eq(&out.dump());
+}
+
+#[test]
+fn state_dump_of_qubit_subset() {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut out =
+        CursorReceiver::new(&mut cursor).with_filter(DumpFilter::new().with_qubits(vec![2, 0]));
+    out.state_dump(StateDump::from_state(product_state(), 3))
+        .expect("writing to a cursor should succeed");
+    expect![[r"
+        STATE:
+        |10⟩: 0-0.7071067811865475i
+        |11⟩: 0.7071067811865475+0i"]]
+    .assert_eq(&out.dump());
+}
+
+#[test]
+fn state_dump_of_entangled_qubit_subset_shows_full_state() {
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(half, 0.0)),
+    ];
+    let mut out = CaptureReceiver::new().with_filter(DumpFilter::new().with_qubits(vec![0]));
+    out.state_dump(StateDump::from_state(state, 2))
+        .expect("capturing should succeed");
+    let events = out.take_events();
+    let [OutputEvent::StateDump {
+        qubit_count,
+        amplitudes,
+    }, OutputEvent::Message(note)] = &events[..]
+    else {
+        panic!("unexpected events: {events:?}");
+    };
+    assert_eq!(*qubit_count, 2);
+    assert_eq!(amplitudes.len(), 2);
+    expect!["(the qubit subset [0] is entangled with other qubits, so the full state is shown)"]
+        .assert_eq(note);
+}
+
+#[test]
+fn generic_receiver_formats_state_dump() {
+    let mut buffer = Vec::new();
+    let mut out = GenericReceiver::new(&mut buffer).with_format(StateFormat {
+        form: AmplitudeForm::Polar,
+        precision: 3,
+        endianness: Endianness::Little,
+        notation: LabelNotation::Decimal,
+    });
+    out.state_dump(StateDump::from_state(product_state(), 3))
+        .expect("writing to a buffer should succeed");
+    expect![[r"
+        STATE:
+        |4⟩: 0.424∠0.000
+        |6⟩: 0.566∠0.000
+        |5⟩: 0.424∠1.571
+        |7⟩: 0.566∠1.571
+    "]]
+    .assert_eq(&String::from_utf8(buffer).expect("output should be UTF-8"));
+}
+
+#[test]
+fn generic_receiver_renders_state_dump_in_dirac_notation() {
+    let mut buffer = Vec::new();
+    let mut out = GenericReceiver::new(&mut buffer)
+        .with_style(DumpStyle::Dirac)
+        .with_filter(DumpFilter::new().with_top_k(2));
+    out.state_dump(StateDump::from_state(product_state(), 3))
+        .expect("writing to a buffer should succeed");
+    expect![[r"
+        STATE:
+        0.5657|011⟩ + (0.0000+0.5657𝑖)|111⟩
+        (2 of 4 amplitudes omitted)
+    "]]
+    .assert_eq(&String::from_utf8(buffer).expect("output should be UTF-8"));
+}
+
+#[test]
+fn generic_receiver_renders_state_dump_as_latex() {
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(half, 0.0)),
+    ];
+    let mut buffer = Vec::new();
+    let mut out = GenericReceiver::new(&mut buffer).with_style(DumpStyle::Latex);
+    out.state_dump(StateDump::from_state(state, 2))
+        .expect("writing to a buffer should succeed");
+    expect![[r"
+        STATE:
+        $|\psi\rangle = \frac{\sqrt{2}}{2}|00\rangle+\frac{\sqrt{2}}{2}|11\rangle$
+    "]]
+    .assert_eq(&String::from_utf8(buffer).expect("output should be UTF-8"));
+}
+
+#[test]
+fn limited_receiver_drops_output_past_total_limit() {
+    let mut capture = CaptureReceiver::new();
+    let mut out = LimitedReceiver::new(
+        &mut capture,
+        OutputLimits {
+            max_event_size: None,
+            max_total_size: Some(10),
+        },
+    );
+    for _ in 0..100 {
+        out.message("abcd").expect("forwarding should succeed");
+    }
+    out.result(&Value::Int(1))
+        .expect("forwarding should succeed");
+    assert_eq!(out.used(), 10);
+    assert_eq!(
+        capture.take_events(),
+        vec![
+            OutputEvent::Message("abcd".to_string()),
+            OutputEvent::Message("abcd".to_string()),
+            OutputEvent::Message("ab".to_string()),
+            OutputEvent::Truncated(Truncation::Total { limit: 10 }),
+            OutputEvent::Result(Value::Int(1)),
+        ]
+    );
+}
+
+#[test]
+fn limited_receiver_cuts_large_events() {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut inner = CursorReceiver::new(&mut cursor);
+    let mut out = LimitedReceiver::new(
+        &mut inner,
+        OutputLimits {
+            max_event_size: Some(40),
+            max_total_size: None,
+        },
+    );
+    out.message("⟨ψ|ψ⟩ = 1").expect("forwarding should succeed");
+    out.message(&"x".repeat(50))
+        .expect("forwarding should succeed");
+    out.state_dump(StateDump::from_state(uniform_state(2), 2))
+        .expect("forwarding should succeed");
+    expect![[r"
+        ⟨ψ|ψ⟩ = 1
+        xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx
+        (output of 50 bytes truncated to the limit of 40 bytes per event)
+        STATE:
+        |00⟩: 0.5+0i
+        |01⟩: 0.5+0i
+        (output of 72 bytes truncated to the limit of 40 bytes per event)"]]
+    .assert_eq(&inner.dump());
+}
+
+#[test]
+fn state_dump_into_value() {
+    let value = StateDump::from_state(uniform_state(1), 1)
+        .with_threshold(0.5)
+        .into_value();
+    expect!["[(0, Complex(Real=0.7071067811865475, Imag=0.0)), (1, Complex(Real=0.7071067811865475, Imag=0.0))]"]
+        .assert_eq(&value.to_string());
+}
@@ -0,0 +1,323 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Partial evaluation, which runs the classical parts of a package at compile time and leaves a
+//! residual package with the quantum operations and the control flow that depends on them.
+//!
+//! An expression is classical if it is built only from literals, immutable variables bound to
+//! literals, and calls to functions with classical arguments. Each classical expression is run
+//! with the evaluator and, if its value can be written as a literal, replaced with that literal.
+//! An `if` whose condition becomes a literal is replaced with the branch it takes. An expression
+//! is left as it is if evaluating it fails, produces output, or takes more than the step budget,
+//! so that the residual package behaves the same when it runs.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backend::SparseSim,
+    output::{self, Receiver, StateDump},
+    val::{self, Value},
+    Env, State, StepAction, StepResult,
+};
+use qsc_fir::fir::{
+    BlockId, CallableImpl, CallableKind, ExprId, ExprKind, Global, ItemKind, Lit, LocalVarId,
+    Mutability, Package, PackageId, PackageLookup, PackageStore, PackageStoreLookup, PatKind, Res,
+    StmtId, StmtKind, StoreItemId, StringComponent, UnOp,
+};
+use rustc_hash::FxHashMap;
+use std::{num::NonZeroU32, ops::BitAnd};
+
+/// The default number of statements that evaluating a single classical expression can take
+/// before it is left for run time.
+pub const DEFAULT_STEP_BUDGET: NonZeroU32 = match NonZeroU32::new(100_000) {
+    Some(budget) => budget,
+    None => unreachable!(),
+};
+
+/// Partially evaluates the package with the given ID in the store, evaluating each classical
+/// expression for at most `budget` statements.
+pub fn partially_evaluate(store: &mut PackageStore, package: PackageId, budget: NonZeroU32) {
+    let mut blocks = Vec::new();
+    for item in store.get(package).items.values() {
+        if let ItemKind::Callable(decl) = &item.kind {
+            if let CallableImpl::Spec(spec) = &decl.implementation {
+                blocks.extend(
+                    [
+                        Some(&spec.body),
+                        spec.adj.as_ref(),
+                        spec.ctl.as_ref(),
+                        spec.ctl_adj.as_ref(),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .map(|spec| spec.block),
+                );
+            }
+        }
+    }
+    let entry = store.get(package).entry;
+
+    let mut evaluator = PartialEvaluator {
+        store,
+        package,
+        budget,
+        sim: SparseSim::new(),
+        known: FxHashMap::default(),
+    };
+    // Local variable IDs are only unique within a callable, so the known values are cleared for
+    // each specialization.
+    for block in blocks {
+        evaluator.known.clear();
+        evaluator.reduce_block(block);
+    }
+    if let Some(entry) = entry {
+        evaluator.known.clear();
+        evaluator.reduce_expr(entry);
+    }
+}
+
+struct PartialEvaluator<'a> {
+    store: &'a mut PackageStore,
+    package: PackageId,
+    budget: NonZeroU32,
+    sim: SparseSim,
+    /// The literals bound to immutable variables in the current callable.
+    known: FxHashMap<LocalVarId, ExprKind>,
+}
+
+impl PartialEvaluator<'_> {
+    fn get(&self) -> &Package {
+        self.store.get(self.package)
+    }
+
+    fn set_kind(&mut self, expr: ExprId, kind: ExprKind) {
+        self.store
+            .get_mut(self.package)
+            .exprs
+            .get_mut(expr)
+            .expect("expression should exist")
+            .kind = kind;
+    }
+
+    fn reduce_block(&mut self, block: BlockId) {
+        let stmts = self.get().get_block(block).stmts.clone();
+        for stmt in stmts {
+            self.reduce_stmt(stmt);
+        }
+    }
+
+    fn reduce_stmt(&mut self, stmt: StmtId) {
+        match self.get().get_stmt(stmt).kind.clone() {
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => {
+                self.reduce_expr(expr);
+            }
+            StmtKind::Local(mutability, pat, expr) => {
+                self.reduce_expr(expr);
+                let package = self.get();
+                if let (Mutability::Immutable, PatKind::Bind(name)) =
+                    (mutability, &package.get_pat(pat).kind)
+                {
+                    let kind = &package.get_expr(expr).kind;
+                    if is_literal(kind) {
+                        self.known.insert(name.id, kind.clone());
+                    }
+                }
+            }
+            StmtKind::Item(_) => {}
+        }
+    }
+
+    /// Reduces the expression and the expressions it contains, and returns whether the expression
+    /// is classical.
+    fn reduce_expr(&mut self, expr: ExprId) -> bool {
+        let classical = match self.get().get_expr(expr).kind.clone() {
+            ExprKind::Lit(_) => return true,
+            ExprKind::String(components) => {
+                let exprs: Vec<_> = components
+                    .iter()
+                    .filter_map(|component| match component {
+                        StringComponent::Expr(expr) => Some(*expr),
+                        StringComponent::Lit(_) => None,
+                    })
+                    .collect();
+                if exprs.is_empty() {
+                    return true;
+                }
+                self.reduce_all(&exprs)
+            }
+            ExprKind::Array(items) | ExprKind::Tuple(items) => self.reduce_all(&items),
+            ExprKind::ArrayRepeat(lhs, rhs)
+            | ExprKind::BinOp(_, lhs, rhs)
+            | ExprKind::Index(lhs, rhs)
+            | ExprKind::UpdateField(lhs, _, rhs) => self.reduce_all(&[lhs, rhs]),
+            ExprKind::UpdateIndex(container, index, value) => {
+                self.reduce_all(&[container, index, value])
+            }
+            ExprKind::Field(inner, _) => self.reduce_expr(inner),
+            ExprKind::UnOp(op, operand) => {
+                self.reduce_expr(operand) && !matches!(op, UnOp::Functor(_))
+            }
+            ExprKind::Range(start, step, end) => {
+                let exprs: Vec<_> = [start, step, end].into_iter().flatten().collect();
+                self.reduce_all(&exprs)
+            }
+            ExprKind::Call(callee, arg) => {
+                let callee = self.is_function(callee) || {
+                    self.reduce_expr(callee);
+                    false
+                };
+                self.reduce_expr(arg) && callee
+            }
+            ExprKind::Var(Res::Local(var), _) => {
+                return match self.known.get(&var).cloned() {
+                    Some(kind) => {
+                        self.set_kind(expr, kind);
+                        true
+                    }
+                    None => false,
+                };
+            }
+            ExprKind::If(cond, then, otherwise) => {
+                self.reduce_expr(cond);
+                if let ExprKind::Lit(Lit::Bool(value)) = self.get().get_expr(cond).kind {
+                    let branch = if value { Some(then) } else { otherwise };
+                    let kind = match branch {
+                        Some(branch) => {
+                            self.reduce_expr(branch);
+                            self.get().get_expr(branch).kind.clone()
+                        }
+                        None => ExprKind::Tuple(Vec::new()),
+                    };
+                    self.set_kind(expr, kind);
+                } else {
+                    self.reduce_expr(then);
+                    if let Some(otherwise) = otherwise {
+                        self.reduce_expr(otherwise);
+                    }
+                }
+                false
+            }
+            ExprKind::While(cond, body) => {
+                self.reduce_expr(cond);
+                if self.get().get_expr(cond).kind == ExprKind::Lit(Lit::Bool(false)) {
+                    self.set_kind(expr, ExprKind::Tuple(Vec::new()));
+                } else {
+                    self.reduce_block(body);
+                }
+                false
+            }
+            ExprKind::Block(block) => {
+                self.reduce_block(block);
+                false
+            }
+            // The assigned variable is mutable, so only the assigned value can be reduced.
+            ExprKind::Assign(_, value)
+            | ExprKind::AssignOp(_, _, value)
+            | ExprKind::AssignField(_, _, value) => {
+                self.reduce_expr(value);
+                false
+            }
+            ExprKind::AssignIndex(_, index, value) => {
+                self.reduce_all(&[index, value]);
+                false
+            }
+            ExprKind::Fail(inner) | ExprKind::Return(inner) => {
+                self.reduce_expr(inner);
+                false
+            }
+            ExprKind::Closure(..) | ExprKind::Hole | ExprKind::Var(..) => false,
+        };
+
+        classical && self.fold(expr)
+    }
+
+    /// Reduces each of the expressions, and returns whether they are all classical.
+    fn reduce_all(&mut self, exprs: &[ExprId]) -> bool {
+        exprs
+            .iter()
+            .map(|&expr| self.reduce_expr(expr))
+            .fold(true, BitAnd::bitand)
+    }
+
+    /// Whether the expression is a function that is called directly.
+    fn is_function(&self, expr: ExprId) -> bool {
+        let ExprKind::Var(Res::Item(item), _) = &self.get().get_expr(expr).kind else {
+            return false;
+        };
+        let id = StoreItemId {
+            package: item.package.unwrap_or(self.package),
+            item: item.item,
+        };
+        matches!(
+            self.store.get_global(id),
+            Some(Global::Callable(decl)) if decl.kind == CallableKind::Function
+        )
+    }
+
+    /// Evaluates the classical expression and replaces it with its value if the value can be
+    /// written as a literal. Returns whether the expression could be evaluated.
+    fn fold(&mut self, expr: ExprId) -> bool {
+        let mut state = State::new(self.package, Some(0));
+        state.set_yield_interval(Some(self.budget));
+        state.push_expr(expr);
+        let Ok(StepResult::Return(value)) = state.eval(
+            &*self.store,
+            &mut Env::default(),
+            &mut self.sim,
+            &mut NoOutput,
+            &[],
+            StepAction::Continue,
+        ) else {
+            return false;
+        };
+
+        if let Some(kind) = literal(value) {
+            self.set_kind(expr, kind);
+        }
+        true
+    }
+}
+
+/// The literal expression for the value, if it has one.
+fn literal(value: Value) -> Option<ExprKind> {
+    let lit = match value {
+        Value::BigInt(value) => Lit::BigInt(value),
+        Value::Bool(value) => Lit::Bool(value),
+        Value::Double(value) => Lit::Double(value),
+        Value::Int(value) => Lit::Int(value),
+        Value::Pauli(value) => Lit::Pauli(value),
+        Value::Result(val::Result::Val(value)) => Lit::Result(if value {
+            qsc_fir::fir::Result::One
+        } else {
+            qsc_fir::fir::Result::Zero
+        }),
+        Value::String(value) => return Some(ExprKind::String(vec![StringComponent::Lit(value)])),
+        _ => return None,
+    };
+    Some(ExprKind::Lit(lit))
+}
+
+fn is_literal(kind: &ExprKind) -> bool {
+    match kind {
+        ExprKind::Lit(_) => true,
+        ExprKind::String(components) => components
+            .iter()
+            .all(|component| matches!(component, StringComponent::Lit(_))),
+        _ => false,
+    }
+}
+
+/// A receiver that rejects all output, so that an expression that produces output is left to run
+/// at run time.
+struct NoOutput;
+
+impl Receiver for NoOutput {
+    fn state_dump(&mut self, _dump: StateDump) -> Result<(), output::Error> {
+        Err(output::Error)
+    }
+
+    fn message(&mut self, _msg: &str) -> Result<(), output::Error> {
+        Err(output::Error)
+    }
+}
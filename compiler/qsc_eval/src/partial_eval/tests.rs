@@ -0,0 +1,244 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{partially_evaluate, DEFAULT_STEP_BUDGET};
+use crate::{debug::map_hir_package_to_fir, lower::Lowerer};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_fir::fir::{
+    self, BlockId, CallableImpl, ExprKind, ItemKind, Lit, PackageId, PackageLookup, StmtKind,
+};
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+use std::num::NonZeroU32;
+
+/// Compiles the sources, partially evaluates the package, and returns the FIR store and the ID of
+/// the package.
+fn partial_eval(
+    file: &str,
+    entry: Option<&str>,
+    budget: NonZeroU32,
+) -> (fir::PackageStore, PackageId) {
+    let mut lowerer = Lowerer::new();
+    let mut core = compile::core();
+    run_core_passes(&mut core);
+    let core_fir = lowerer.lower_package(&core.package);
+    let mut store = PackageStore::new(core);
+
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::all());
+    assert!(std.errors.is_empty());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all()
+    )
+    .is_empty());
+    let std_fir = lowerer.lower_package(&std.package);
+    let std_id = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), file.into())], entry.map(Into::into));
+    let mut unit = compile(
+        &store,
+        &[(std_id, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    let pass_errors = run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(pass_errors.is_empty(), "{pass_errors:?}");
+    let unit_fir = lowerer.lower_package(&unit.package);
+    let id = map_hir_package_to_fir(store.insert(unit));
+
+    let mut fir_store = fir::PackageStore::new();
+    fir_store.insert(
+        map_hir_package_to_fir(qsc_hir::hir::PackageId::CORE),
+        core_fir,
+    );
+    fir_store.insert(map_hir_package_to_fir(std_id), std_fir);
+    fir_store.insert(id, unit_fir);
+
+    partially_evaluate(&mut fir_store, id, budget);
+    (fir_store, id)
+}
+
+/// The kind of the entry expression after partial evaluation.
+fn entry(file: &str, entry: &str, budget: NonZeroU32) -> ExprKind {
+    let (store, id) = partial_eval(file, Some(entry), budget);
+    let package = store.get(id);
+    let entry = package
+        .entry
+        .expect("package should have an entry expression");
+    package.get_expr(entry).kind.clone()
+}
+
+/// The kind of the expression in each statement of the body of `Main` after partial evaluation.
+fn main_body(file: &str) -> Vec<ExprKind> {
+    let (store, id) = partial_eval(file, None, DEFAULT_STEP_BUDGET);
+    let package = store.get(id);
+    let block = package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            ItemKind::Callable(decl) if decl.name.name.as_ref() == "Main" => {
+                match &decl.implementation {
+                    CallableImpl::Spec(spec) => Some(spec.body.block),
+                    CallableImpl::Intrinsic => None,
+                }
+            }
+            _ => None,
+        })
+        .expect("package should have a Main callable");
+    stmt_exprs(package, block)
+}
+
+fn stmt_exprs(package: &fir::Package, block: BlockId) -> Vec<ExprKind> {
+    package
+        .get_block(block)
+        .stmts
+        .iter()
+        .map(|&stmt| match package.get_stmt(stmt).kind {
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) | StmtKind::Local(_, _, expr) => {
+                package.get_expr(expr).kind.clone()
+            }
+            StmtKind::Item(_) => panic!("statement should not be an item"),
+        })
+        .collect()
+}
+
+#[test]
+fn function_call_is_evaluated() {
+    let kind = entry(
+        indoc! {"
+            namespace Test {
+                function Square(x : Int) : Int { x * x }
+            }
+        "},
+        "Test.Square(4) + 1",
+        DEFAULT_STEP_BUDGET,
+    );
+    assert_eq!(kind, ExprKind::Lit(Lit::Int(17)));
+}
+
+#[test]
+fn call_with_array_argument_is_evaluated() {
+    let kind = entry("", "Length([1, 2, 3]) * 2", DEFAULT_STEP_BUDGET);
+    assert_eq!(kind, ExprKind::Lit(Lit::Int(6)));
+}
+
+#[test]
+fn immutable_variable_is_propagated_into_condition() {
+    let body = main_body(indoc! {"
+        namespace Test {
+            function Square(x : Int) : Int { x * x }
+            operation Main(q : Qubit) : Unit {
+                let n = Square(2);
+                if n > 3 {
+                    X(q);
+                } else {
+                    Y(q);
+                }
+            }
+        }
+    "});
+    assert_eq!(body[0], ExprKind::Lit(Lit::Int(4)));
+    assert!(
+        matches!(body[1], ExprKind::Block(_)),
+        "condition should be replaced with the branch it takes, found {}",
+        body[1]
+    );
+}
+
+#[test]
+fn false_condition_without_else_is_removed() {
+    let body = main_body(indoc! {"
+        namespace Test {
+            function Square(x : Int) : Int { x * x }
+            operation Main(q : Qubit) : Unit {
+                if Square(1) > 2 {
+                    X(q);
+                }
+            }
+        }
+    "});
+    assert_eq!(body[0], ExprKind::Tuple(Vec::new()));
+}
+
+#[test]
+fn dynamic_condition_is_left() {
+    let body = main_body(indoc! {"
+        namespace Test {
+            operation Main(q : Qubit) : Unit {
+                if M(q) == One {
+                    X(q);
+                }
+            }
+        }
+    "});
+    assert!(matches!(body[0], ExprKind::If(..)), "found {}", body[0]);
+}
+
+#[test]
+fn mutable_variable_is_left() {
+    let body = main_body(indoc! {"
+        namespace Test {
+            operation Main(q : Qubit) : Unit {
+                mutable n = 1;
+                set n = 5;
+                if n > 3 {
+                    X(q);
+                }
+            }
+        }
+    "});
+    assert!(matches!(body[2], ExprKind::If(..)), "found {}", body[2]);
+}
+
+#[test]
+fn call_with_output_is_left() {
+    let kind = entry(
+        indoc! {"
+            namespace Test {
+                function Log(x : Int) : Int {
+                    Message(\"x\");
+                    x
+                }
+            }
+        "},
+        "Test.Log(1)",
+        DEFAULT_STEP_BUDGET,
+    );
+    assert!(matches!(kind, ExprKind::Call(..)), "found {kind}");
+}
+
+#[test]
+fn expression_that_fails_is_left() {
+    let kind = entry("", "1 / 0", DEFAULT_STEP_BUDGET);
+    assert!(matches!(kind, ExprKind::BinOp(..)), "found {kind}");
+}
+
+#[test]
+fn call_over_budget_is_left() {
+    let kind = entry(
+        indoc! {"
+            namespace Test {
+                function Spin() : Int {
+                    mutable i = 0;
+                    while true {
+                        set i += 1;
+                    }
+                    i
+                }
+            }
+        "},
+        "Test.Spin()",
+        NonZeroU32::new(1000).expect("budget should not be zero"),
+    );
+    assert!(matches!(kind, ExprKind::Call(..)), "found {kind}");
+}
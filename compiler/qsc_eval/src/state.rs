@@ -6,7 +6,14 @@ mod tests;
 
 use num_bigint::BigUint;
 use num_complex::{Complex, Complex64};
-use std::fmt::Write;
+use num_traits::{One, Zero};
+use qsc_fir::fir::Pauli;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter, Write},
+};
 
 #[must_use]
 pub fn format_state_id(id: &BigUint, qubit_count: usize) -> String {
@@ -20,18 +27,36 @@ pub fn get_phase(c: &Complex<f64>) -> f64 {
 
 #[must_use]
 pub fn fmt_complex(c: &Complex<f64>) -> String {
+    fmt_complex_with_precision(c, 4)
+}
+
+fn fmt_complex_with_precision(c: &Complex<f64>, precision: usize) -> String {
     // Format -0 as 0
     // Also using Unicode Minus Sign instead of ASCII Hyphen-Minus
     // and Unicode Mathematical Italic Small I instead of ASCII i.
     format!(
-        "{}{:.4}{}{:.4}𝑖",
-        if c.re <= -0.00005 { "−" } else { "" },
+        "{}{:.precision$}{}{:.precision$}𝑖",
+        if rounds_negative(c.re, precision) {
+            "−"
+        } else {
+            ""
+        },
         c.re.abs(),
-        if c.im <= -0.00005 { "−" } else { "+" },
+        if rounds_negative(c.im, precision) {
+            "−"
+        } else {
+            "+"
+        },
         c.im.abs()
     )
 }
 
+/// Returns true if the value is still negative when rounded to the given number of decimal places.
+fn rounds_negative(value: f64, precision: usize) -> bool {
+    let precision = i32::try_from(precision).unwrap_or(i32::MAX);
+    value <= -0.5 * 10_f64.powi(-precision)
+}
+
 #[must_use]
 pub fn fmt_basis_state_label(id: &BigUint, qubit_count: usize) -> String {
     // This will generate a bit string that shows the qubits in the order
@@ -39,6 +64,454 @@ pub fn fmt_basis_state_label(id: &BigUint, qubit_count: usize) -> String {
     format!("{:0>qubit_count$}", id.to_str_radix(2))
 }
 
+/// The form in which [`StateFormat`] writes amplitudes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AmplitudeForm {
+    /// The real and imaginary parts, as in `0.7071+0.7071𝑖`.
+    #[default]
+    Cartesian,
+    /// The magnitude and the phase in radians, as in `1.0000∠0.7854`.
+    Polar,
+}
+
+/// The order in which [`StateFormat`] assigns qubits to the bits of basis state labels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// The first allocated qubit is the most significant bit, so that binary labels show the
+    /// qubits in allocation order from left to right.
+    #[default]
+    Big,
+    /// The first allocated qubit is the least significant bit, so that binary labels show the
+    /// qubits in allocation order from right to left.
+    Little,
+}
+
+/// The notation in which [`StateFormat`] writes basis state labels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LabelNotation {
+    /// A bit string with one bit per qubit, as in `|101⟩`.
+    #[default]
+    Binary,
+    /// The decimal value of the bit string, as in `|5⟩`.
+    Decimal,
+}
+
+/// Options for formatting the amplitudes and basis state labels of a dumped state. The default
+/// options match [`fmt_complex`] and [`format_state_id`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateFormat {
+    pub form: AmplitudeForm,
+    /// The number of digits after the decimal point.
+    pub precision: usize,
+    pub endianness: Endianness,
+    pub notation: LabelNotation,
+}
+
+impl Default for StateFormat {
+    fn default() -> Self {
+        Self {
+            form: AmplitudeForm::default(),
+            precision: 4,
+            endianness: Endianness::default(),
+            notation: LabelNotation::default(),
+        }
+    }
+}
+
+impl StateFormat {
+    /// Formats the label of the basis state with the given ID, which places the first allocated
+    /// qubit in the most significant bit.
+    #[must_use]
+    pub fn format_label(&self, id: &BigUint, qubit_count: usize) -> String {
+        let reversed;
+        let id = match self.endianness {
+            Endianness::Big => id,
+            Endianness::Little => {
+                let mut bits = BigUint::zero();
+                for qubit in 0..qubit_count {
+                    if id.bit(qubit_bit(qubit, qubit_count)) {
+                        bits.set_bit(qubit as u64, true);
+                    }
+                }
+                reversed = bits;
+                &reversed
+            }
+        };
+        match self.notation {
+            LabelNotation::Binary => format_state_id(id, qubit_count),
+            LabelNotation::Decimal => format!("|{id}⟩"),
+        }
+    }
+
+    #[must_use]
+    pub fn format_amplitude(&self, c: &Complex64) -> String {
+        match self.form {
+            AmplitudeForm::Cartesian => fmt_complex_with_precision(c, self.precision),
+            AmplitudeForm::Polar => {
+                let precision = self.precision;
+                let phase = get_phase(c);
+                format!(
+                    "{:.precision$}∠{}{:.precision$}",
+                    c.norm(),
+                    if rounds_negative(phase, precision) {
+                        "−"
+                    } else {
+                        ""
+                    },
+                    phase.abs()
+                )
+            }
+        }
+    }
+}
+
+/// Returns the position in a basis state label of the bit that corresponds to the given qubit.
+/// Labels place the first allocated qubit in the most significant bit.
+#[must_use]
+fn qubit_bit(qubit: usize, qubit_count: usize) -> u64 {
+    (qubit_count - qubit - 1) as u64
+}
+
+/// Formats the state as a sum of basis states in Dirac notation, as in `0.7071|00⟩ + 0.7071|11⟩`,
+/// using `format` for the amplitudes and labels. Real amplitudes are written with their sign as the
+/// operator between terms, while other amplitudes are parenthesized.
+#[must_use]
+pub fn fmt_dirac(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    format: &StateFormat,
+) -> String {
+    let precision = format.precision;
+    let mut dirac = String::new();
+    for (id, amplitude) in state {
+        let label = format.format_label(id, qubit_count);
+        let is_real = format.form == AmplitudeForm::Cartesian
+            && !rounds_negative(amplitude.im, precision)
+            && !rounds_negative(-amplitude.im, precision);
+        let (negative, coefficient) = if is_real {
+            (
+                rounds_negative(amplitude.re, precision),
+                format!("{:.precision$}", amplitude.re.abs()),
+            )
+        } else {
+            (false, format!("({})", format.format_amplitude(amplitude)))
+        };
+        let operator = match (dirac.is_empty(), negative) {
+            (true, false) => "",
+            (true, true) => "−",
+            (false, false) => " + ",
+            (false, true) => " − ",
+        };
+        write!(dirac, "{operator}{coefficient}{label}").expect("writing to string should succeed");
+    }
+    if dirac.is_empty() {
+        dirac.push('0');
+    }
+    dirac
+}
+
+/// Computes the expectation value ⟨ψ|P|ψ⟩ of the Pauli string `paulis` acting on `qubits` for
+/// a state as captured from a backend.
+#[must_use]
+pub fn pauli_expectation(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    paulis: &[Pauli],
+    qubits: &[usize],
+) -> f64 {
+    let amplitudes = state.iter().cloned().collect::<FxHashMap<_, _>>();
+    let mut flip_mask = BigUint::zero();
+    for (pauli, &qubit) in paulis.iter().zip(qubits) {
+        if matches!(pauli, Pauli::X | Pauli::Y) {
+            flip_mask.set_bit(qubit_bit(qubit, qubit_count), true);
+        }
+    }
+
+    let mut expectation = Complex64::zero();
+    for (label, amplitude) in state {
+        // Applying the Pauli string maps each basis state to exactly one other basis state,
+        // picking up a phase from any Y and Z factors along the way.
+        let mut phase = Complex64::one();
+        for (pauli, &qubit) in paulis.iter().zip(qubits) {
+            let is_one = label.bit(qubit_bit(qubit, qubit_count));
+            match pauli {
+                Pauli::I | Pauli::X => {}
+                Pauli::Y => {
+                    phase *= if is_one {
+                        -Complex64::i()
+                    } else {
+                        Complex64::i()
+                    }
+                }
+                Pauli::Z => {
+                    if is_one {
+                        phase = -phase;
+                    }
+                }
+            }
+        }
+        if let Some(target) = amplitudes.get(&(label ^ &flip_mask)) {
+            expectation += target.conj() * phase * amplitude;
+        }
+    }
+    expectation.re
+}
+
+/// Looks up the amplitude of a computational basis state in a state as captured from a backend.
+/// The basis state is given for `qubits`, with the first qubit in the most significant bit,
+/// and is mapped onto the labels of the captured state before the lookup.
+#[must_use]
+pub fn basis_state_amplitude(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    qubits: &[usize],
+    basis_state: &BigUint,
+) -> Complex64 {
+    let mut label = BigUint::zero();
+    for (i, &qubit) in qubits.iter().enumerate() {
+        if basis_state.bit((qubits.len() - i - 1) as u64) {
+            label.set_bit(qubit_bit(qubit, qubit_count), true);
+        }
+    }
+    state
+        .iter()
+        .find_map(|(id, amplitude)| (*id == label).then_some(*amplitude))
+        .unwrap_or_default()
+}
+
+/// The probability of measuring a basis state, as an entry of a probability distribution.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasisStateProbability {
+    /// The basis state, with the first of the measured qubits in the most significant bit.
+    #[serde(skip)]
+    pub id: BigUint,
+    /// The basis state as a bit string with one bit per measured qubit, as in `01`.
+    pub label: String,
+    pub probability: f64,
+}
+
+/// Computes the probabilities of the outcomes of measuring `qubits`, or all qubits if not given,
+/// for a state as captured from a backend. The probabilities of the basis states that agree on the
+/// measured qubits are summed, and the distribution is normalized so that it sums to one. The
+/// entries are ordered by basis state, with the first of the measured qubits in the most
+/// significant bit.
+#[must_use]
+pub fn probability_distribution(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    qubits: Option<&[usize]>,
+) -> Vec<BasisStateProbability> {
+    let all_qubits: Vec<usize>;
+    let qubits = if let Some(qubits) = qubits {
+        qubits
+    } else {
+        all_qubits = (0..qubit_count).collect();
+        &all_qubits
+    };
+
+    let mut probabilities = BTreeMap::<BigUint, f64>::new();
+    for (id, amplitude) in state {
+        let outcome = subsystem_basis_state(id, qubit_count, qubits);
+        *probabilities.entry(outcome).or_default() += amplitude.norm_sqr();
+    }
+
+    let total: f64 = probabilities.values().sum();
+    probabilities
+        .into_iter()
+        .map(|(id, probability)| BasisStateProbability {
+            label: fmt_basis_state_label(&id, qubits.len()),
+            id,
+            probability: if total > 0.0 {
+                probability / total
+            } else {
+                probability
+            },
+        })
+        .collect()
+}
+
+/// Computes the reduced density matrix of `qubits` for a pure state as captured from a backend, by
+/// tracing out the remaining qubits. The rows and columns are indexed by the basis states of
+/// `qubits`, with the first qubit in the most significant bit.
+#[must_use]
+pub fn reduced_density_matrix(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    qubits: &[usize],
+) -> Vec<Vec<Complex64>> {
+    // Only amplitudes that agree on the remaining qubits contribute to the same matrix elements, so
+    // the amplitudes are grouped by the basis state of the remaining qubits first.
+    let mut environments = FxHashMap::<BigUint, Vec<(usize, Complex64)>>::default();
+    for (id, amplitude) in state {
+        let mut environment = id.clone();
+        for &qubit in qubits {
+            environment.set_bit(qubit_bit(qubit, qubit_count), false);
+        }
+        let index = usize::try_from(subsystem_basis_state(id, qubit_count, qubits))
+            .expect("subsystem basis state should fit in usize");
+        environments
+            .entry(environment)
+            .or_default()
+            .push((index, *amplitude));
+    }
+
+    let dimension = 1 << qubits.len();
+    let mut matrix = vec![vec![Complex64::zero(); dimension]; dimension];
+    for amplitudes in environments.values() {
+        for &(row, a) in amplitudes {
+            for &(column, b) in amplitudes {
+                matrix[row][column] += a * b.conj();
+            }
+        }
+    }
+    matrix
+}
+
+/// The coordinates of the state of a single qubit on the Bloch sphere, which are the expectation
+/// values of the Pauli X, Y and Z operators. Pure states lie on the surface of the unit sphere,
+/// while a qubit entangled with other qubits lies inside it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct BlochVector {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Display for BlochVector {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let coordinate = |v: f64| if rounds_negative(v, 4) { v } else { v.abs() };
+        write!(
+            f,
+            "({:.4}, {:.4}, {:.4})",
+            coordinate(self.x),
+            coordinate(self.y),
+            coordinate(self.z)
+        )
+    }
+}
+
+/// Computes the Bloch vector of `qubit` for a state as captured from a backend, from the reduced
+/// density matrix of the qubit.
+#[must_use]
+pub fn bloch_vector(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    qubit: usize,
+) -> BlochVector {
+    // ρ = (I + xX + yY + zZ) / 2, so ρ₁₀ = (x + 𝑖y) / 2.
+    let rho = reduced_density_matrix(state, qubit_count, &[qubit]);
+    BlochVector {
+        x: 2.0 * rho[1][0].re,
+        y: 2.0 * rho[1][0].im,
+        z: rho[0][0].re - rho[1][1].re,
+    }
+}
+
+/// Computes the von Neumann entropy, in bits, of the bipartition of a pure state as captured from a
+/// backend into `qubits` and the remaining qubits. The entropy is zero when the two parts are not
+/// entangled and at most the number of qubits in the smaller part. It is computed from the reduced
+/// density matrix of the smaller part.
+#[must_use]
+pub fn entanglement_entropy(
+    state: &[(BigUint, Complex64)],
+    qubit_count: usize,
+    qubits: &[usize],
+) -> f64 {
+    let smaller_part = smaller_part(qubit_count, qubits);
+    let rho = reduced_density_matrix(state, qubit_count, &smaller_part);
+    hermitian_eigenvalues(&rho)
+        .into_iter()
+        .filter(|&eigenvalue| eigenvalue > 1e-12)
+        .map(|eigenvalue| -eigenvalue * eigenvalue.log2())
+        .sum::<f64>()
+        .max(0.0)
+}
+
+/// Returns the smaller of `qubits` and the remaining qubits.
+#[must_use]
+pub(crate) fn smaller_part(qubit_count: usize, qubits: &[usize]) -> Vec<usize> {
+    if 2 * qubits.len() <= qubit_count {
+        qubits.to_vec()
+    } else {
+        (0..qubit_count)
+            .filter(|qubit| !qubits.contains(qubit))
+            .collect()
+    }
+}
+
+/// Computes the eigenvalues of a Hermitian matrix with the cyclic Jacobi method. The method is
+/// applied to the real symmetric matrix `[[Re, −Im], [Im, Re]]`, which has each eigenvalue of the
+/// Hermitian matrix twice.
+fn hermitian_eigenvalues(matrix: &[Vec<Complex64>]) -> Vec<f64> {
+    const MAX_SWEEPS: usize = 64;
+    let dimension = matrix.len();
+    let size = 2 * dimension;
+    let mut symmetric = vec![vec![0.0; size]; size];
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, element) in row.iter().enumerate() {
+            symmetric[i][j] = element.re;
+            symmetric[i + dimension][j + dimension] = element.re;
+            symmetric[i][j + dimension] = -element.im;
+            symmetric[i + dimension][j] = element.im;
+        }
+    }
+
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal = symmetric
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter().enumerate().filter(move |&(j, _)| j != i))
+            .map(|(_, element)| element * element)
+            .sum::<f64>();
+        if off_diagonal < 1e-24 {
+            break;
+        }
+        for p in 0..size {
+            for q in p + 1..size {
+                if symmetric[p][q] == 0.0 {
+                    continue;
+                }
+                // Rotate the plane of p and q so that the element at (p, q) becomes zero.
+                let theta = (symmetric[q][q] - symmetric[p][p]) / (2.0 * symmetric[p][q]);
+                let tan = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                let cos = 1.0 / tan.hypot(1.0);
+                let sin = tan * cos;
+                let rotate = |x: &mut f64, y: &mut f64| {
+                    (*x, *y) = (cos * *x - sin * *y, sin * *x + cos * *y);
+                };
+                for row in &mut symmetric {
+                    let (left, right) = row.split_at_mut(q);
+                    rotate(&mut left[p], &mut right[0]);
+                }
+                let (upper, lower) = symmetric.split_at_mut(q);
+                for (x, y) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    rotate(x, y);
+                }
+            }
+        }
+    }
+
+    let mut eigenvalues = (0..size).map(|i| symmetric[i][i]).collect::<Vec<_>>();
+    eigenvalues.sort_by(f64::total_cmp);
+    eigenvalues
+        .chunks(2)
+        .map(|pair| pair.iter().sum::<f64>() / 2.0)
+        .collect()
+}
+
+/// Returns the basis state of `qubits` within the basis state with the given ID, with the first
+/// qubit in the most significant bit.
+fn subsystem_basis_state(id: &BigUint, qubit_count: usize, qubits: &[usize]) -> BigUint {
+    let mut basis_state = BigUint::zero();
+    for (i, &qubit) in qubits.iter().enumerate() {
+        if id.bit(qubit_bit(qubit, qubit_count)) {
+            basis_state.set_bit((qubits.len() - i - 1) as u64, true);
+        }
+    }
+    basis_state
+}
+
 #[must_use]
 fn is_significant(x: f64) -> bool {
     x.abs() > 1e-9
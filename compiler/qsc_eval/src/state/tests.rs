@@ -9,8 +9,13 @@ use super::{
     write_latex_for_term, AlgebraicNumber, CartesianForm, ComplexNumber, DecimalNumber, PolarForm,
     RationalNumber, RealNumber, Term,
 };
-use crate::state::{is_fractional_part_significant, is_significant};
+use crate::state::{
+    bloch_vector, entanglement_entropy, fmt_complex, fmt_dirac, format_state_id,
+    is_fractional_part_significant, is_significant, probability_distribution,
+    reduced_density_matrix, AmplitudeForm, Endianness, LabelNotation, StateFormat,
+};
 use expect_test::{expect, Expect};
+use num_bigint::BigUint;
 use num_complex::Complex64;
 use std::{f64::consts::PI, time::Instant};
 
@@ -924,3 +929,243 @@ fn check_get_latex_perf() {
         Instant::now().duration_since(start)
     );
 }
+
+#[test]
+fn default_state_format_matches_default_formatting() {
+    let format = StateFormat::default();
+    let id = BigUint::from(0b110_u8);
+    assert_eq!(format.format_label(&id, 4), format_state_id(&id, 4));
+    for c in [
+        Complex64::new(0.5, -0.5),
+        Complex64::new(-0.00004, 0.00004),
+        Complex64::new(-0.6, 0.0),
+    ] {
+        assert_eq!(format.format_amplitude(&c), fmt_complex(&c));
+    }
+}
+
+#[test]
+fn state_format_labels() {
+    let id = BigUint::from(0b0110_u8);
+    let labels = [
+        (Endianness::Big, LabelNotation::Binary),
+        (Endianness::Little, LabelNotation::Binary),
+        (Endianness::Big, LabelNotation::Decimal),
+        (Endianness::Little, LabelNotation::Decimal),
+    ]
+    .map(|(endianness, notation)| {
+        StateFormat {
+            endianness,
+            notation,
+            ..StateFormat::default()
+        }
+        .format_label(&id, 5)
+    });
+    expect![[r#"
+        [
+            "|00110⟩",
+            "|01100⟩",
+            "|6⟩",
+            "|12⟩",
+        ]
+    "#]]
+    .assert_debug_eq(&labels);
+}
+
+#[test]
+fn state_format_amplitudes() {
+    let amplitudes = [
+        Complex64::new(0.5, 0.5),
+        Complex64::new(-0.6, 0.0),
+        Complex64::new(0.0, -0.25),
+    ];
+    let formatted = [
+        (AmplitudeForm::Cartesian, 2),
+        (AmplitudeForm::Polar, 4),
+        (AmplitudeForm::Polar, 1),
+    ]
+    .map(|(form, precision)| {
+        let format = StateFormat {
+            form,
+            precision,
+            ..StateFormat::default()
+        };
+        amplitudes
+            .iter()
+            .map(|c| format.format_amplitude(c))
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+    expect![[r#"
+        [
+            "0.50+0.50𝑖 −0.60+0.00𝑖 0.00−0.25𝑖",
+            "0.7071∠0.7854 0.6000∠3.1416 0.2500∠−1.5708",
+            "0.7∠0.8 0.6∠3.1 0.2∠−1.6",
+        ]
+    "#]]
+    .assert_debug_eq(&formatted);
+}
+
+#[test]
+fn dirac_notation_of_real_amplitudes() {
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(-half, 0.0)),
+    ];
+    expect!["0.7071|00⟩ − 0.7071|11⟩"].assert_eq(&fmt_dirac(&state, 2, &StateFormat::default()));
+    let state = vec![
+        (BigUint::from(1_u8), Complex64::new(-half, 0.0)),
+        (BigUint::from(2_u8), Complex64::new(half, 0.0)),
+    ];
+    expect!["−0.7071|01⟩ + 0.7071|10⟩"].assert_eq(&fmt_dirac(&state, 2, &StateFormat::default()));
+}
+
+#[test]
+fn dirac_notation_of_complex_amplitudes() {
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(0.6, 0.0)),
+        (BigUint::from(1_u8), Complex64::new(0.0, 0.8)),
+    ];
+    expect!["0.6000|0⟩ + (0.0000+0.8000𝑖)|1⟩"].assert_eq(&fmt_dirac(
+        &state,
+        1,
+        &StateFormat::default(),
+    ));
+    let format = StateFormat {
+        form: AmplitudeForm::Polar,
+        precision: 2,
+        ..StateFormat::default()
+    };
+    expect!["(0.60∠0.00)|0⟩ + (0.80∠1.57)|1⟩"].assert_eq(&fmt_dirac(&state, 1, &format));
+}
+
+#[test]
+fn probability_distribution_is_normalized() {
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(1.0, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(0.0, 1.0)),
+    ];
+    let distribution = probability_distribution(&state, 2, None);
+    expect![[r#"
+        [{"label":"00","probability":0.5},{"label":"11","probability":0.5}]
+    "#]]
+    .assert_eq(&format!(
+        "{}\n",
+        serde_json::to_string(&distribution).expect("distribution should serialize")
+    ));
+}
+
+#[test]
+fn probability_distribution_marginalizes_onto_qubits() {
+    // 0.6|100⟩ + 0.8|011⟩, where qubit 0 is the leftmost bit.
+    let state = vec![
+        (BigUint::from(4_u8), Complex64::new(0.6, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(0.8, 0.0)),
+    ];
+    let distribution = probability_distribution(&state, 3, Some(&[2, 0]));
+    let entries = distribution
+        .iter()
+        .map(|entry| format!("{}: {:.2}", entry.label, entry.probability))
+        .collect::<Vec<_>>();
+    expect![[r#"
+        [
+            "01: 0.36",
+            "10: 0.64",
+        ]
+    "#]]
+    .assert_debug_eq(&entries);
+    let distribution = probability_distribution(&state, 3, Some(&[1, 2]));
+    assert_eq!(distribution.len(), 2);
+    assert_eq!(distribution[0].id, BigUint::from(0_u8));
+    assert_eq!(distribution[1].id, BigUint::from(3_u8));
+}
+
+fn fmt_matrix(matrix: &[Vec<Complex64>]) -> String {
+    matrix
+        .iter()
+        .map(|row| row.iter().map(fmt_complex).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn reduced_density_matrix_of_entangled_qubit_is_mixed() {
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(half, 0.0)),
+    ];
+    expect![[r#"
+        0.5000+0.0000𝑖 0.0000+0.0000𝑖
+        0.0000+0.0000𝑖 0.5000+0.0000𝑖"#]]
+    .assert_eq(&fmt_matrix(&reduced_density_matrix(&state, 2, &[1])));
+}
+
+#[test]
+fn reduced_density_matrix_of_product_state_is_pure() {
+    // (|0⟩ + 𝑖|1⟩)/√2 ⊗ |1⟩, where qubit 0 is the leftmost bit.
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    let state = vec![
+        (BigUint::from(1_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(0.0, half)),
+    ];
+    expect![[r#"
+        0.5000+0.0000𝑖 0.0000−0.5000𝑖
+        0.0000+0.5000𝑖 0.5000+0.0000𝑖"#]]
+    .assert_eq(&fmt_matrix(&reduced_density_matrix(&state, 2, &[0])));
+    expect![[r#"
+        0.0000+0.0000𝑖 0.0000+0.0000𝑖 0.0000+0.0000𝑖 0.0000+0.0000𝑖
+        0.0000+0.0000𝑖 0.0000+0.0000𝑖 0.0000+0.0000𝑖 0.0000+0.0000𝑖
+        0.0000+0.0000𝑖 0.0000+0.0000𝑖 0.5000+0.0000𝑖 0.0000−0.5000𝑖
+        0.0000+0.0000𝑖 0.0000+0.0000𝑖 0.0000+0.5000𝑖 0.5000+0.0000𝑖"#]]
+    .assert_eq(&fmt_matrix(&reduced_density_matrix(&state, 2, &[1, 0])));
+}
+
+#[test]
+fn bloch_vector_of_pure_and_mixed_qubits() {
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    // (|0⟩ − 𝑖|1⟩)/√2 ⊗ |1⟩, where qubit 0 is the leftmost bit.
+    let state = vec![
+        (BigUint::from(1_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(0.0, -half)),
+    ];
+    expect!["(0.0000, -1.0000, 0.0000)"].assert_eq(&bloch_vector(&state, 2, 0).to_string());
+    expect!["(0.0000, 0.0000, -1.0000)"].assert_eq(&bloch_vector(&state, 2, 1).to_string());
+    let bell = vec![
+        (BigUint::from(0_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(3_u8), Complex64::new(half, 0.0)),
+    ];
+    expect!["(0.0000, 0.0000, 0.0000)"].assert_eq(&bloch_vector(&bell, 2, 1).to_string());
+}
+
+#[test]
+fn entanglement_entropy_of_product_and_entangled_states() {
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    // (|0⟩ + 𝑖|1⟩)/√2 ⊗ (|0⟩ + |1⟩)/√2, whose reduced states have off-diagonal elements.
+    let product = vec![
+        (BigUint::from(0_u8), Complex64::new(0.5, 0.0)),
+        (BigUint::from(1_u8), Complex64::new(0.5, 0.0)),
+        (BigUint::from(2_u8), Complex64::new(0.0, 0.5)),
+        (BigUint::from(3_u8), Complex64::new(0.0, 0.5)),
+    ];
+    expect!["0.0000"].assert_eq(&format!("{:.4}", entanglement_entropy(&product, 2, &[0])));
+    // A Bell pair on qubits 0 and 2, next to qubit 1 in the |1⟩ state.
+    let bell = vec![
+        (BigUint::from(0b010_u8), Complex64::new(half, 0.0)),
+        (BigUint::from(0b111_u8), Complex64::new(0.0, half)),
+    ];
+    expect!["1.0000"].assert_eq(&format!("{:.4}", entanglement_entropy(&bell, 3, &[0])));
+    expect!["1.0000"].assert_eq(&format!("{:.4}", entanglement_entropy(&bell, 3, &[0, 1])));
+    expect!["0.0000"].assert_eq(&format!("{:.4}", entanglement_entropy(&bell, 3, &[0, 2])));
+}
+
+#[test]
+fn entanglement_entropy_of_partially_entangled_state() {
+    // √0.9|00⟩ + √0.1|11⟩ has entropy −0.9 log₂ 0.9 − 0.1 log₂ 0.1.
+    let state = vec![
+        (BigUint::from(0_u8), Complex64::new(0.9_f64.sqrt(), 0.0)),
+        (BigUint::from(3_u8), Complex64::new(0.1_f64.sqrt(), 0.0)),
+    ];
+    expect!["0.4690"].assert_eq(&format!("{:.4}", entanglement_entropy(&state, 2, &[1])));
+}
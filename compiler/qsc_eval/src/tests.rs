@@ -6,12 +6,14 @@
 use crate::{
     backend::{Backend, SparseSim},
     debug::{map_hir_package_to_fir, Frame},
+    error::PackageSpan,
     output::{GenericReceiver, Receiver},
     val, Env, Error, State, StepAction, StepResult, Value,
 };
 use expect_test::{expect, Expect};
 use indoc::indoc;
-use qsc_data_structures::language_features::LanguageFeatures;
+use num_complex::Complex64;
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
 use qsc_fir::fir;
 use qsc_fir::fir::{ExprId, PackageId, PackageStoreLookup};
 use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
@@ -61,7 +63,7 @@ fn check_expr(file: &str, expr: &str, expect: &Expect) {
     let sources = SourceMap::new([("test".into(), file.into())], Some(expr.into()));
     let mut unit = compile(
         &store,
-        &[std_id],
+        &[(std_id, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -374,6 +376,7 @@ fn block_qubit_use_array_invalid_count_expr() {
                             adjoint: false,
                             controlled: 0,
                         },
+                        arg: None,
                     },
                 ],
             )
@@ -2052,7 +2055,7 @@ fn update_udt_known_field_name() {
             let p = Pair(1, 2);
             p w/ First <- 3
         }"},
-        &expect!["(3, 2)"],
+        &expect!["Pair(First=3, Second=2)"],
     );
 }
 
@@ -2069,7 +2072,57 @@ fn update_udt_nested_field() {
             let p = Triple(1, (2, 3));
             p w/ Third <- 4
         }"},
-        &expect!["(1, (2, 4))"],
+        &expect!["Triple(First=1, (Second=2, Third=4))"],
+    );
+}
+
+#[test]
+fn udt_with_single_unnamed_field_displays_type_name() {
+    check_expr(
+        indoc! {"
+            namespace A {
+                newtype Wrapper = Int;
+            }
+        "},
+        indoc! {"{
+            open A;
+            Wrapper(5)
+        }"},
+        &expect!["Wrapper(5)"],
+    );
+}
+
+#[test]
+fn udt_nested_in_udt_displays_both_type_names() {
+    check_expr(
+        indoc! {"
+            namespace A {
+                newtype Pair = (First : Int, Second : Int);
+                newtype Counted = (Value : Pair, Count : Int);
+            }
+        "},
+        indoc! {"{
+            open A;
+            Counted(Pair(1, 2), 3)
+        }"},
+        &expect!["Counted(Value=Pair(First=1, Second=2), Count=3)"],
+    );
+}
+
+#[test]
+fn udt_field_access_and_unwrap_yield_underlying_values() {
+    check_expr(
+        indoc! {"
+            namespace A {
+                newtype Pair = (First : Int, Second : Int);
+            }
+        "},
+        indoc! {"{
+            open A;
+            let p = Pair(1, 2);
+            (p::Second, p!)
+        }"},
+        &expect!["(2, (1, 2))"],
     );
 }
 
@@ -2377,7 +2430,7 @@ fn assignupdate_expr_using_field_name() {
             set p w/= First <- 3;
             p
         }"},
-        &expect!["(3, 2)"],
+        &expect!["Pair(First=3, Second=2)"],
     );
 }
 
@@ -2976,6 +3029,7 @@ fn call_adjoint_expr() {
                             adjoint: true,
                             controlled: 0,
                         },
+                        arg: None,
                     },
                 ],
             )
@@ -3040,6 +3094,7 @@ fn call_adjoint_adjoint_expr() {
                             adjoint: false,
                             controlled: 0,
                         },
+                        arg: None,
                     },
                 ],
             )
@@ -3099,6 +3154,7 @@ fn call_adjoint_self_expr() {
                             adjoint: true,
                             controlled: 0,
                         },
+                        arg: None,
                     },
                 ],
             )
@@ -3577,3 +3633,41 @@ fn partial_app_mutable_arg() {
         &expect!["((0, 1), (0, 2))"],
     );
 }
+
+#[test]
+fn complex_arithmetic() {
+    let a = Value::Complex(Complex64::new(1.0, 2.0));
+    let b = Value::Complex(Complex64::new(3.0, -1.0));
+    let span = PackageSpan {
+        package: qsc_hir::hir::PackageId::CORE,
+        span: Span::default(),
+    };
+    expect!["Complex(Real=4.0, Imag=1.0)"]
+        .assert_eq(&super::eval_binop_add(a.clone(), b.clone()).to_string());
+    expect!["Complex(Real=-2.0, Imag=3.0)"]
+        .assert_eq(&super::eval_binop_sub(a.clone(), b.clone()).to_string());
+    expect!["Complex(Real=5.0, Imag=5.0)"]
+        .assert_eq(&super::eval_binop_mul(a.clone(), b.clone()).to_string());
+    expect!["Complex(Real=0.1, Imag=0.7)"].assert_eq(
+        &super::eval_binop_div(a, b, span)
+            .expect("division should succeed")
+            .to_string(),
+    );
+}
+
+#[test]
+fn complex_fields_behave_like_tuple() {
+    let c = Value::Complex(Complex64::new(1.0, 2.0));
+    assert_eq!(
+        super::follow_field_path(c.clone(), &[1]),
+        Some(Value::Double(2.0))
+    );
+    assert_eq!(
+        super::update_field_path(&c, &[0], &Value::Double(-1.0)),
+        Some(Value::Complex(Complex64::new(-1.0, 2.0)))
+    );
+    assert_eq!(
+        super::complex_as_tuple(c),
+        Value::Tuple([Value::Double(1.0), Value::Double(2.0)].into())
+    );
+}
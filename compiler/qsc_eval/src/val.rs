@@ -1,11 +1,21 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+#[cfg(test)]
+mod tests;
+
 use num_bigint::BigInt;
+use num_complex::Complex64;
 use qsc_data_structures::{display::join, functors::FunctorApp};
-use qsc_fir::fir::{Pauli, StoreItemId};
+use qsc_fir::{
+    fir::{Pauli, StoreItemId},
+    ty::{Udt, UdtDef, UdtDefKind},
+};
+use rustc_hash::FxHashSet;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fmt::{self, Display, Formatter},
+    mem::{size_of, size_of_val},
     rc::Rc,
 };
 
@@ -17,6 +27,9 @@ pub enum Value {
     BigInt(BigInt),
     Bool(bool),
     Closure(Rc<[Value]>, StoreItemId, FunctorApp),
+    /// A complex number, which stands for the `Complex` type of the standard library and behaves
+    /// like the tuple of its real and imaginary parts.
+    Complex(Complex64),
     Double(f64),
     Global(StoreItemId, FunctorApp),
     Int(i64),
@@ -26,6 +39,9 @@ pub enum Value {
     Result(Result),
     String(Rc<str>),
     Tuple(Rc<[Value]>),
+    /// A value of a user-defined type, which keeps the type so that the value can be displayed
+    /// with its type and field names. It otherwise behaves like the underlying value.
+    Udt(Rc<Udt>, Rc<Value>),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -58,6 +74,165 @@ impl Result {
     }
 }
 
+/// The error returned when converting a [`Value`] into a Rust type fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionError {
+    expected: &'static str,
+    found: &'static str,
+    /// The indices of the items, outermost first, that lead from the converted value to the value
+    /// that has the wrong type.
+    path: Vec<usize>,
+}
+
+impl ConversionError {
+    fn new(expected: &'static str, found: &Value) -> Self {
+        Self {
+            expected,
+            found: found.type_name(),
+            path: Vec::new(),
+        }
+    }
+
+    fn at(mut self, index: usize) -> Self {
+        self.path.insert(0, index);
+        self
+    }
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)?;
+        if !self.path.is_empty() {
+            write!(f, " at item ")?;
+            join(f, self.path.iter(), ".")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+macro_rules! impl_value_conversions {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(val: $ty) -> Self {
+                    Self::$variant(val.into())
+                }
+            }
+
+            impl TryFrom<Value> for $ty {
+                type Error = ConversionError;
+
+                fn try_from(value: Value) -> std::result::Result<Self, Self::Error> {
+                    match value {
+                        Value::$variant(val) => Ok(val.into()),
+                        value => Err(ConversionError::new(stringify!($variant), &value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_value_conversions! {
+    BigInt => BigInt,
+    bool => Bool,
+    Complex64 => Complex,
+    f64 => Double,
+    i64 => Int,
+    Pauli => Pauli,
+}
+
+impl From<String> for Value {
+    fn from(val: String) -> Self {
+        Self::String(val.into())
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> std::result::Result<Self, Self::Error> {
+        match value {
+            Value::String(val) => Ok(val.to_string()),
+            value => Err(ConversionError::new("String", &value)),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(val: &str) -> Self {
+        Self::String(val.into())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(val: Vec<T>) -> Self {
+        Self::Array(Rc::new(val.into_iter().map(Into::into).collect()))
+    }
+}
+
+impl<T: TryFrom<Value, Error = ConversionError>> TryFrom<Value> for Vec<T> {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> std::result::Result<Self, Self::Error> {
+        let Value::Array(items) = value else {
+            return Err(ConversionError::new("Array", &value));
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| T::try_from(item.clone()).map_err(|e| e.at(index)))
+            .collect()
+    }
+}
+
+impl From<()> for Value {
+    fn from((): ()) -> Self {
+        Self::unit()
+    }
+}
+
+impl TryFrom<Value> for () {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> std::result::Result<Self, Self::Error> {
+        match value {
+            Value::Tuple(items) if items.is_empty() => Ok(()),
+            value => Err(ConversionError::new("Unit", &value)),
+        }
+    }
+}
+
+macro_rules! impl_tuple_conversions {
+    ($len:literal: $($name:ident $index:tt),+) => {
+        impl<$($name: Into<Value>),+> From<($($name,)+)> for Value {
+            fn from(val: ($($name,)+)) -> Self {
+                Self::Tuple([$(val.$index.into()),+].into())
+            }
+        }
+
+        impl<$($name: TryFrom<Value, Error = ConversionError>),+> TryFrom<Value> for ($($name,)+) {
+            type Error = ConversionError;
+
+            fn try_from(value: Value) -> std::result::Result<Self, Self::Error> {
+                match value.erase_udt() {
+                    Value::Tuple(items) if items.len() == $len => Ok((
+                        $($name::try_from(items[$index].clone()).map_err(|e| e.at($index))?,)+
+                    )),
+                    value => Err(ConversionError::new(concat!("Tuple of ", $len), &value)),
+                }
+            }
+        }
+    };
+}
+
+impl_tuple_conversions!(2: A 0, B 1);
+impl_tuple_conversions!(3: A 0, B 1, C 2);
+impl_tuple_conversions!(4: A 0, B 1, C 2, D 3);
+impl_tuple_conversions!(5: A 0, B 1, C 2, D 3, E 4);
+
 impl From<bool> for Result {
     fn from(val: bool) -> Self {
         Self::Val(val)
@@ -73,6 +248,118 @@ impl From<usize> for Result {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Qubit(pub usize);
 
+/// A packed array of measured results, which stores one bit per result with `One` as a set bit.
+/// This is the compact form of a `Result[]` value, suited to collecting the results of many shots.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ResultBits {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl ResultBits {
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        let offset = self.len % Self::WORD_BITS;
+        if offset == 0 {
+            self.words.push(0);
+        }
+        if bit {
+            *self
+                .words
+                .last_mut()
+                .expect("a word should have been pushed") |= 1 << offset;
+        }
+        self.len += 1;
+    }
+
+    /// Returns whether the result at `index` is `One`, or `None` if the index is out of bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len)
+            .then(|| self.words[index / Self::WORD_BITS] & (1 << (index % Self::WORD_BITS)) != 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.get(index).expect("index should be in bounds"))
+    }
+
+    /// Returns the integer whose little-endian binary representation is the results, like
+    /// `ResultArrayAsInt`, or `None` if there are 64 or more results.
+    #[must_use]
+    pub fn to_int(&self) -> Option<i64> {
+        if self.len < Self::WORD_BITS {
+            let word = self.words.first().copied().unwrap_or_default();
+            Some(i64::try_from(word).expect("fewer than 64 bits should fit in an Int"))
+        } else {
+            None
+        }
+    }
+}
+
+impl FromIterator<bool> for ResultBits {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let mut bits = Self::new();
+        for bit in iter {
+            bits.push(bit);
+        }
+        bits
+    }
+}
+
+impl Display for ResultBits {
+    /// Writes the results as a bitstring, in the same order as the array, such as `0110`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&ResultBits> for Value {
+    fn from(bits: &ResultBits) -> Self {
+        Self::Array(Rc::new(
+            bits.iter()
+                .map(|bit| Value::Result(Result::Val(bit)))
+                .collect(),
+        ))
+    }
+}
+
+impl TryFrom<&Value> for ResultBits {
+    type Error = ConversionError;
+
+    fn try_from(value: &Value) -> std::result::Result<Self, Self::Error> {
+        let Value::Array(items) = value else {
+            return Err(ConversionError::new("Array", value));
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| match item {
+                Value::Result(Result::Val(bit)) => Ok(*bit),
+                item => Err(ConversionError::new("measured Result", item).at(index)),
+            })
+            .collect()
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
@@ -84,6 +371,12 @@ impl Display for Value {
             Value::BigInt(v) => write!(f, "{v}"),
             Value::Bool(v) => write!(f, "{v}"),
             Value::Closure(..) => f.write_str("<closure>"),
+            Value::Complex(v) => write!(
+                f,
+                "Complex(Real={}, Imag={})",
+                Value::Double(v.re),
+                Value::Double(v.im)
+            ),
             Value::Double(v) => {
                 if (v.floor() - v.ceil()).abs() < f64::EPSILON {
                     // The value is a whole number, which by convention is displayed with one decimal point
@@ -129,10 +422,47 @@ impl Display for Value {
                 }
                 write!(f, ")")
             }
+            Value::Udt(udt, value) => {
+                write!(f, "{}", udt.name)?;
+                if let UdtDefKind::Field(_) = udt.definition.kind {
+                    write!(f, "(")?;
+                    fmt_udt_fields(f, &udt.definition, value)?;
+                    write!(f, ")")
+                } else {
+                    fmt_udt_fields(f, &udt.definition, value)
+                }
+            }
         }
     }
 }
 
+/// Writes `value` laid out according to the type definition `def`, prefixing each item with the
+/// name of its field. Values that don't match the layout of the definition are written as is.
+fn fmt_udt_fields(f: &mut Formatter, def: &UdtDef, value: &Value) -> fmt::Result {
+    match (&def.kind, value) {
+        (UdtDefKind::Field(field), value) => {
+            if let Some(name) = &field.name {
+                write!(f, "{name}=")?;
+            }
+            write!(f, "{value}")
+        }
+        (UdtDefKind::Tuple(defs), Value::Tuple(items)) if defs.len() == items.len() => {
+            write!(f, "(")?;
+            for (index, (def, item)) in defs.iter().zip(items.iter()).enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                fmt_udt_fields(f, def, item)?;
+            }
+            if items.len() == 1 {
+                write!(f, ",")?;
+            }
+            write!(f, ")")
+        }
+        (_, value) => write!(f, "{value}"),
+    }
+}
+
 thread_local! {
     static UNIT: Rc<[Value; 0]> = Rc::new([]);
 }
@@ -219,6 +549,17 @@ impl Value {
         v
     }
 
+    /// Convert the [Value] into a complex number
+    /// # Panics
+    /// This will panic if the [Value] is not a [`Value::Complex`].
+    #[must_use]
+    pub fn unwrap_complex(self) -> Complex64 {
+        let Value::Complex(v) = self else {
+            panic!("value should be Complex, got {}", self.type_name());
+        };
+        v
+    }
+
     /// Convert the [Value] into a global tuple
     /// # Panics
     /// This will panic if the [Value] is not a [`Value::Global`].
@@ -298,13 +639,74 @@ impl Value {
 
     /// Convert the [Value] into an array of [Value]
     /// # Panics
-    /// This will panic if the [Value] is not a [`Value::Tuple`].
+    /// This will panic if the [Value] is not a [`Value::Tuple`] or a [`Value::Udt`] of a tuple.
     #[must_use]
     pub fn unwrap_tuple(self) -> Rc<[Self]> {
-        let Value::Tuple(v) = self else {
-            panic!("value should be Tuple, got {}", self.type_name());
-        };
-        v
+        match self {
+            Value::Tuple(v) => v,
+            Value::Udt(_, value) => Rc::unwrap_or_clone(value).unwrap_tuple(),
+            _ => panic!("value should be Tuple, got {}", self.type_name()),
+        }
+    }
+
+    /// Returns the underlying value of a value of a user-defined type, leaving other values
+    /// unchanged.
+    #[must_use]
+    pub fn erase_udt(self) -> Self {
+        match self {
+            Value::Udt(_, value) => Rc::unwrap_or_clone(value),
+            value => value,
+        }
+    }
+
+    /// Returns the number of bytes of heap memory retained by the value, not including the value
+    /// itself. Allocations that are shared through `Rc`s are counted once.
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        self.heap_size_with(&mut FxHashSet::default())
+    }
+
+    /// Returns the heap memory retained by the value, skipping the `Rc` allocations in `seen` and
+    /// adding the ones it counts.
+    pub(crate) fn heap_size_with(&self, seen: &mut FxHashSet<*const ()>) -> usize {
+        match self {
+            Value::Array(arr) => rc_heap_size(arr, seen, |arr, seen| {
+                size_of::<Vec<Value>>()
+                    + arr.capacity() * size_of::<Value>()
+                    + arr
+                        .iter()
+                        .map(|item| item.heap_size_with(seen))
+                        .sum::<usize>()
+            }),
+            Value::BigInt(v) => {
+                usize::try_from(v.bits().div_ceil(64)).unwrap_or(usize::MAX) * size_of::<u64>()
+            }
+            Value::Closure(items, ..) | Value::Tuple(items) => {
+                rc_heap_size(items, seen, |items, seen| {
+                    size_of_val(items)
+                        + items
+                            .iter()
+                            .map(|item| item.heap_size_with(seen))
+                            .sum::<usize>()
+                })
+            }
+            Value::String(v) => rc_heap_size(v, seen, |v, _| v.len()),
+            Value::Udt(udt, value) => {
+                rc_heap_size(udt, seen, |_, _| size_of::<Udt>())
+                    + rc_heap_size(value, seen, |value, seen| {
+                        size_of::<Value>() + value.heap_size_with(seen)
+                    })
+            }
+            Value::Bool(_)
+            | Value::Complex(_)
+            | Value::Double(_)
+            | Value::Global(..)
+            | Value::Int(_)
+            | Value::Pauli(_)
+            | Value::Qubit(_)
+            | Value::Range(..)
+            | Value::Result(_) => 0,
+        }
     }
 
     #[must_use]
@@ -314,6 +716,7 @@ impl Value {
             Value::BigInt(_) => "BigInt",
             Value::Bool(_) => "Bool",
             Value::Closure(..) => "Closure",
+            Value::Complex(_) => "Complex",
             Value::Double(_) => "Double",
             Value::Global(..) => "Global",
             Value::Int(_) => "Int",
@@ -323,6 +726,155 @@ impl Value {
             Value::Result(_) => "Result",
             Value::String(_) => "String",
             Value::Tuple(_) => "Tuple",
+            Value::Udt(..) => "UDT",
         }
     }
 }
+
+/// Returns the size of the allocation of `rc`, including its reference counts, and the heap memory
+/// retained by its contents, unless the allocation is already in `seen`.
+pub(crate) fn rc_heap_size<T: ?Sized>(
+    rc: &Rc<T>,
+    seen: &mut FxHashSet<*const ()>,
+    contents: impl FnOnce(&T, &mut FxHashSet<*const ()>) -> usize,
+) -> usize {
+    if seen.insert(Rc::as_ptr(rc).cast::<()>()) {
+        2 * size_of::<usize>() + contents(rc, seen)
+    } else {
+        0
+    }
+}
+
+/// The serialized form of a [`Value`], tagged with its type so that values round-trip exactly.
+/// Values that only have meaning inside a running program, such as qubits and callables, have no
+/// serialized form. Values of user-defined types are serialized as their underlying value.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+enum SerializedValue {
+    Array(Vec<SerializedValue>),
+    /// A big integer, as a decimal string since it may not fit in a JSON number.
+    BigInt(String),
+    Bool(bool),
+    /// A complex number, as its real and imaginary parts.
+    Complex(f64, f64),
+    Double(f64),
+    Int(i64),
+    Pauli(SerializedPauli),
+    Range {
+        start: Option<i64>,
+        step: i64,
+        end: Option<i64>,
+    },
+    Result(SerializedResult),
+    String(String),
+    Tuple(Vec<SerializedValue>),
+}
+
+#[derive(Serialize, Deserialize)]
+enum SerializedPauli {
+    PauliI,
+    PauliX,
+    PauliY,
+    PauliZ,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SerializedResult {
+    Zero,
+    One,
+}
+
+impl TryFrom<&Value> for SerializedValue {
+    type Error = String;
+
+    fn try_from(value: &Value) -> std::result::Result<Self, Self::Error> {
+        let items = |items: &[Value]| {
+            items
+                .iter()
+                .map(Self::try_from)
+                .collect::<std::result::Result<Vec<_>, _>>()
+        };
+        Ok(match value {
+            Value::Array(arr) => Self::Array(items(arr)?),
+            Value::BigInt(v) => Self::BigInt(v.to_string()),
+            Value::Bool(v) => Self::Bool(*v),
+            Value::Complex(v) => Self::Complex(v.re, v.im),
+            Value::Double(v) => Self::Double(*v),
+            Value::Int(v) => Self::Int(*v),
+            Value::Pauli(v) => Self::Pauli(match v {
+                Pauli::I => SerializedPauli::PauliI,
+                Pauli::X => SerializedPauli::PauliX,
+                Pauli::Y => SerializedPauli::PauliY,
+                Pauli::Z => SerializedPauli::PauliZ,
+            }),
+            &Value::Range(start, step, end) => Self::Range { start, step, end },
+            Value::Result(Result::Val(v)) => Self::Result(if *v {
+                SerializedResult::One
+            } else {
+                SerializedResult::Zero
+            }),
+            Value::String(v) => Self::String(v.to_string()),
+            Value::Tuple(tup) => Self::Tuple(items(tup)?),
+            Value::Udt(_, value) => Self::try_from(&**value)?,
+            Value::Closure(..)
+            | Value::Global(..)
+            | Value::Qubit(_)
+            | Value::Result(Result::Id(_)) => {
+                return Err(format!(
+                    "a value of type {} cannot be serialized",
+                    value.type_name()
+                ));
+            }
+        })
+    }
+}
+
+impl TryFrom<SerializedValue> for Value {
+    type Error = String;
+
+    fn try_from(value: SerializedValue) -> std::result::Result<Self, Self::Error> {
+        let items = |items: Vec<SerializedValue>| {
+            items
+                .into_iter()
+                .map(Self::try_from)
+                .collect::<std::result::Result<Vec<_>, _>>()
+        };
+        Ok(match value {
+            SerializedValue::Array(arr) => Value::Array(items(arr)?.into()),
+            SerializedValue::BigInt(v) => Value::BigInt(
+                v.parse()
+                    .map_err(|_| format!("invalid big integer `{v}`"))?,
+            ),
+            SerializedValue::Bool(v) => Value::Bool(v),
+            SerializedValue::Complex(re, im) => Value::Complex(Complex64::new(re, im)),
+            SerializedValue::Double(v) => Value::Double(v),
+            SerializedValue::Int(v) => Value::Int(v),
+            SerializedValue::Pauli(v) => Value::Pauli(match v {
+                SerializedPauli::PauliI => Pauli::I,
+                SerializedPauli::PauliX => Pauli::X,
+                SerializedPauli::PauliY => Pauli::Y,
+                SerializedPauli::PauliZ => Pauli::Z,
+            }),
+            SerializedValue::Range { start, step, end } => Value::Range(start, step, end),
+            SerializedValue::Result(v) => {
+                Value::Result(Result::Val(matches!(v, SerializedResult::One)))
+            }
+            SerializedValue::String(v) => Value::String(v.into()),
+            SerializedValue::Tuple(tup) => Value::Tuple(items(tup)?.into()),
+        })
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        SerializedValue::try_from(self)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        Value::try_from(SerializedValue::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Qubit, Result, ResultBits, Value};
+use expect_test::expect;
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use qsc_fir::fir::Pauli;
+use std::mem::size_of;
+
+#[test]
+fn value_round_trips_through_json() {
+    let value = Value::Tuple(
+        [
+            Value::Array(vec![Value::Int(1), Value::Int(-2)].into()),
+            Value::BigInt(BigInt::from(2).pow(80)),
+            Value::Bool(true),
+            Value::Complex(Complex64::new(0.5, -0.5)),
+            Value::Double(1.5),
+            Value::Pauli(Pauli::Y),
+            Value::Range(Some(0), 2, None),
+            Value::Result(Result::Val(true)),
+            Value::String("hello".into()),
+            Value::unit(),
+        ]
+        .into(),
+    );
+    let json = serde_json::to_string(&value).expect("value should serialize");
+    expect![[r#"{"type":"Tuple","value":[{"type":"Array","value":[{"type":"Int","value":1},{"type":"Int","value":-2}]},{"type":"BigInt","value":"1208925819614629174706176"},{"type":"Bool","value":true},{"type":"Complex","value":[0.5,-0.5]},{"type":"Double","value":1.5},{"type":"Pauli","value":"PauliY"},{"type":"Range","value":{"start":0,"step":2,"end":null}},{"type":"Result","value":"One"},{"type":"String","value":"hello"},{"type":"Tuple","value":[]}]}"#]]
+    .assert_eq(&json);
+    let round_trip: Value = serde_json::from_str(&json).expect("value should deserialize");
+    assert_eq!(round_trip, value);
+}
+
+#[test]
+fn qubit_cannot_be_serialized() {
+    let error = serde_json::to_string(&Value::Array(vec![Value::Qubit(Qubit(0))].into()))
+        .expect_err("qubit should not serialize");
+    expect!["a value of type Qubit cannot be serialized"].assert_eq(&error.to_string());
+}
+
+#[test]
+fn invalid_big_int_is_rejected() {
+    let error = serde_json::from_str::<Value>(r#"{"type":"BigInt","value":"12x"}"#)
+        .expect_err("invalid big integer should not deserialize");
+    expect!["invalid big integer `12x`"].assert_eq(&error.to_string());
+}
+
+#[test]
+fn native_values_convert_to_and_from_values() {
+    let value = Value::from((1_i64, vec![true, false], ("two".to_string(), 3.0_f64)));
+    expect!["(1, [true, false], (two, 3.0))"].assert_eq(&value.to_string());
+    let native: (i64, Vec<bool>, (String, f64)) = value.try_into().expect("value should convert");
+    assert_eq!(native, (1, vec![true, false], ("two".to_string(), 3.0)));
+    assert_eq!(Value::from(()), Value::unit());
+    assert_eq!(
+        BigInt::try_from(Value::from(BigInt::from(7))),
+        Ok(BigInt::from(7))
+    );
+}
+
+#[test]
+fn conversion_error_names_the_mismatched_item() {
+    let value = Value::from(vec![(1_i64, 1.0_f64), (2, 2.0)]);
+    let error = Vec::<(i64, i64)>::try_from(value).expect_err("conversion should fail");
+    expect!["expected Int, found Double at item 0.1"].assert_eq(&error.to_string());
+    let error = i64::try_from(Value::from("one")).expect_err("conversion should fail");
+    expect!["expected Int, found String"].assert_eq(&error.to_string());
+}
+
+#[test]
+fn result_bits_round_trip_through_values() {
+    let bits: ResultBits = (0..70).map(|index| index % 3 == 0).collect();
+    assert_eq!(bits.len(), 70);
+    assert_eq!(bits.get(69), Some(true));
+    assert_eq!(bits.get(70), None);
+    let value = Value::from(&bits);
+    assert_eq!(ResultBits::try_from(&value), Ok(bits));
+}
+
+#[test]
+fn result_bits_display_as_bitstring_and_convert_to_int() {
+    let value = Value::Array(
+        vec![
+            Value::Result(Result::Val(true)),
+            Value::Result(Result::Val(false)),
+            Value::Result(Result::Val(true)),
+            Value::Result(Result::Val(true)),
+        ]
+        .into(),
+    );
+    let bits = ResultBits::try_from(&value).expect("value should convert");
+    expect!["1011"].assert_eq(&bits.to_string());
+    assert_eq!(bits.to_int(), Some(0b1101));
+    let wide: ResultBits = std::iter::repeat(true).take(64).collect();
+    assert_eq!(wide.to_int(), None);
+}
+
+#[test]
+fn result_bits_reject_items_other_than_results() {
+    let value = Value::Array(vec![Value::Result(Result::Val(true)), Value::Int(0)].into());
+    let error = ResultBits::try_from(&value).expect_err("conversion should fail");
+    expect!["expected measured Result, found Int at item 1"].assert_eq(&error.to_string());
+}
+
+#[test]
+fn heap_size_counts_shared_allocations_once() {
+    let string = Value::from("hello");
+    assert_eq!(string.heap_size(), 2 * size_of::<usize>() + 5);
+    let pair = Value::Tuple([string.clone(), string.clone()].into());
+    assert_eq!(
+        pair.heap_size(),
+        2 * size_of::<usize>() + 2 * size_of::<Value>() + string.heap_size()
+    );
+    assert_eq!(Value::Int(1).heap_size(), 0);
+}
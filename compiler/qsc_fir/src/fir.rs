@@ -15,6 +15,7 @@ use qsc_data_structures::{
     index_map::{IndexMap, Iter},
     span::Span,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     fmt::{self, Debug, Display, Formatter, Write},
@@ -37,7 +38,7 @@ fn set_indentation<'a, 'b>(
 }
 
 /// A unique identifier for an FIR node.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct NodeId(u32);
 
 impl NodeId {
@@ -114,7 +115,7 @@ impl Hash for NodeId {
 macro_rules! fir_id {
     ($id:ident) => {
         /// A unique identifier for an FIR node.
-        #[derive(Debug, Clone, Copy)]
+        #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
         pub struct $id(pub u32);
 
         impl $id {
@@ -212,7 +213,7 @@ fir_id!(StmtId);
 fir_id!(LocalVarId);
 
 /// A unique identifier for a package within a package store.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct PackageId(usize);
 
 impl PackageId {
@@ -245,7 +246,9 @@ impl From<usize> for PackageId {
 }
 
 /// A unique identifier for an item within a package.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct LocalItemId(usize);
 
 impl LocalItemId {
@@ -275,7 +278,7 @@ impl From<LocalItemId> for usize {
 }
 
 /// A unique identifier for an item within a package store.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct ItemId {
     /// The package ID or `None` for the local package.
     pub package: Option<PackageId>,
@@ -294,7 +297,7 @@ impl Display for ItemId {
 
 /// A resolution. This connects a usage of a name with the declaration of that name by uniquely
 /// identifying the node that declared it.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Res {
     /// An invalid resolution.
     Err,
@@ -319,11 +322,11 @@ pub enum Global<'a> {
     /// A global callable.
     Callable(&'a CallableDecl),
     /// A global user-defined type.
-    Udt,
+    Udt(&'a Udt),
 }
 
 /// A unique identifier for an item within a package store.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct StoreItemId {
     /// The package ID.
     pub package: PackageId,
@@ -457,7 +460,7 @@ pub trait PackageStoreLookup {
 }
 
 /// A FIR package store.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct PackageStore(IndexMap<PackageId, Package>);
 
 impl PackageStoreLookup for PackageStore {
@@ -548,7 +551,7 @@ pub trait PackageLookup {
 /// within the containing node. Node ids are used to identify nodes within
 /// the package and require mapping from the HIR node id to the new FIR node id.
 /// `PackageId`s and `LocalItemId`s are 1:1 from the HIR and are not remapped.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Package {
     /// The items in the package.
     pub items: IndexMap<LocalItemId, Item>,
@@ -623,7 +626,7 @@ impl PackageLookup for Package {
         match &self.items.get(id)?.kind {
             ItemKind::Callable(callable) => Some(Global::Callable(callable)),
             ItemKind::Namespace(..) => None,
-            ItemKind::Ty(..) => Some(Global::Udt),
+            ItemKind::Ty(_, udt) => Some(Global::Udt(udt)),
         }
     }
 
@@ -641,7 +644,7 @@ impl PackageLookup for Package {
 }
 
 /// An item.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Item {
     /// The ID.
     pub id: LocalItemId,
@@ -690,7 +693,7 @@ impl Display for Item {
 }
 
 /// An item kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum ItemKind {
     /// A `function` or `operation` declaration.
     Callable(CallableDecl),
@@ -723,7 +726,7 @@ impl Display for ItemKind {
 }
 
 /// A callable declaration header.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CallableDecl {
     /// The node ID.
     pub id: NodeId,
@@ -788,7 +791,7 @@ impl Display for CallableDecl {
 }
 
 /// A callable implementations.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum CallableImpl {
     /// An intrinsic callable implementation.
     Intrinsic,
@@ -815,7 +818,7 @@ impl Display for CallableImpl {
 }
 
 /// A specialized implementation.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SpecImpl {
     /// The body implementation.
     pub body: SpecDecl,
@@ -850,7 +853,7 @@ impl Display for SpecImpl {
 }
 
 /// A specialization declaration.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SpecDecl {
     /// The node ID.
     pub id: NodeId,
@@ -873,7 +876,7 @@ impl Display for SpecDecl {
 }
 
 /// A sequenced block of statements.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Block {
     /// The node ID.
     pub id: BlockId,
@@ -906,7 +909,7 @@ impl Display for Block {
 }
 
 /// A statement.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Stmt {
     /// The stmt ID.
     pub id: StmtId,
@@ -923,7 +926,7 @@ impl Display for Stmt {
 }
 
 /// A statement kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum StmtKind {
     /// An expression without a trailing semicolon.
     Expr(ExprId),
@@ -954,7 +957,7 @@ impl Display for StmtKind {
 }
 
 /// An expression.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Expr {
     /// The expr ID.
     pub id: ExprId,
@@ -977,7 +980,7 @@ impl Display for Expr {
 }
 
 /// An expression kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum ExprKind {
     /// An array: `[a, b, c]`.
     Array(Vec<ExprId>),
@@ -1321,7 +1324,7 @@ fn display_while(mut indent: Indented<Formatter>, cond: ExprId, block: BlockId)
 }
 
 /// A string component.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum StringComponent {
     /// An expression.
     Expr(ExprId),
@@ -1330,7 +1333,7 @@ pub enum StringComponent {
 }
 
 /// A pattern.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Pat {
     /// The node ID.
     pub id: PatId,
@@ -1353,7 +1356,7 @@ impl Display for Pat {
 }
 
 /// A pattern kind.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum PatKind {
     /// A binding.
     Bind(Ident),
@@ -1388,7 +1391,7 @@ impl Display for PatKind {
 }
 
 /// An identifier.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Ident {
     /// The node ID.
     pub id: LocalVarId,
@@ -1405,14 +1408,14 @@ impl Display for Ident {
 }
 
 /// An attribute.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Attr {
     /// Indicates that a callable is an entry point to a program.
     EntryPoint,
 }
 
 /// A field.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Field {
     /// A field path.
     Path(FieldPath),
@@ -1433,14 +1436,14 @@ impl Display for Field {
 }
 
 /// A path to a field in a tuple or user-defined type.
-#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct FieldPath {
     /// The tuple item indices to follow in order from top to bottom.
     pub indices: Vec<usize>,
 }
 
 /// A primitive field for a built-in type.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum PrimField {
     /// The start of a range.
     Start,
@@ -1464,7 +1467,7 @@ impl FromStr for PrimField {
 }
 
 /// The visibility of a declaration.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Visibility {
     /// Visible everywhere.
     Public,
@@ -1473,7 +1476,7 @@ pub enum Visibility {
 }
 
 /// A callable kind.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum CallableKind {
     /// A function.
     Function,
@@ -1491,7 +1494,7 @@ impl Display for CallableKind {
 }
 
 /// The mutability of a binding.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Mutability {
     /// An immutable binding.
     Immutable,
@@ -1500,7 +1503,7 @@ pub enum Mutability {
 }
 
 /// The source of an allocated qubit.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum QubitSource {
     /// A qubit initialized to the zero state.
     Fresh,
@@ -1510,7 +1513,7 @@ pub enum QubitSource {
 }
 
 /// A literal.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Lit {
     /// A big integer literal.
     BigInt(BigInt),
@@ -1541,7 +1544,7 @@ impl Display for Lit {
 }
 
 /// A measurement result.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Result {
     /// The zero eigenvalue.
     Zero,
@@ -1550,7 +1553,7 @@ pub enum Result {
 }
 
 /// A Pauli operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Pauli {
     /// The Pauli I operator.
     I,
@@ -1563,7 +1566,7 @@ pub enum Pauli {
 }
 
 /// A functor that may be applied to an operation.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Functor {
     /// The adjoint functor.
     Adj,
@@ -1581,7 +1584,7 @@ impl Display for Functor {
 }
 
 /// A unary operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum UnOp {
     /// A functor application.
     Functor(Functor),
@@ -1608,7 +1611,7 @@ impl Display for UnOp {
 }
 
 /// A binary operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BinOp {
     /// Addition: `+`.
     Add,
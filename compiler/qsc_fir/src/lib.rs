@@ -5,6 +5,7 @@ pub mod assigner;
 pub mod fir;
 pub mod global;
 pub mod mut_visit;
+pub mod pretty;
 pub mod ty;
 pub mod validate;
 pub mod visit;
@@ -0,0 +1,278 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A pretty-printer that renders a FIR node together with all of the nodes it contains, as an
+//! indented tree with one node per line. Unlike the `Display` implementations of the nodes, which
+//! only refer to their children by ID, the printer follows the IDs through the package.
+//!
+//! Each line shows the kind of node, its ID, its span, and its type if it has one, so the output
+//! is stable for a given package and can be compared against in tests.
+
+use crate::{
+    fir::{
+        Block, BlockId, CallableDecl, CallableImpl, Expr, ExprId, ExprKind, Item, ItemKind,
+        LocalItemId, Package, PackageLookup, Pat, PatId, PatKind, SpecDecl, SpecImpl, Stmt, StmtId,
+        StmtKind, StringComponent,
+    },
+    visit::{self, Visitor},
+};
+use std::fmt::Write;
+
+/// Renders every item in the package, followed by the entry expression if it has one.
+#[must_use]
+pub fn package_to_string(package: &Package) -> String {
+    let mut printer = Printer::new(package);
+    printer.visit_package(package);
+    printer.finish()
+}
+
+/// Renders the item with the given ID and all of the nodes it contains.
+///
+/// # Panics
+///
+/// Panics if the item is not in the package.
+#[must_use]
+pub fn item_to_string(package: &Package, item: LocalItemId) -> String {
+    let mut printer = Printer::new(package);
+    printer.visit_item(package.items.get(item).expect("item should be in package"));
+    printer.finish()
+}
+
+/// Renders the statement with the given ID and all of the nodes it contains.
+#[must_use]
+pub fn stmt_to_string(package: &Package, stmt: StmtId) -> String {
+    let mut printer = Printer::new(package);
+    printer.visit_stmt(stmt);
+    printer.finish()
+}
+
+/// Renders the expression with the given ID and all of the nodes it contains.
+#[must_use]
+pub fn expr_to_string(package: &Package, expr: ExprId) -> String {
+    let mut printer = Printer::new(package);
+    printer.visit_expr(expr);
+    printer.finish()
+}
+
+/// Renders the block with the given ID and all of the nodes it contains.
+#[must_use]
+pub fn block_to_string(package: &Package, block: BlockId) -> String {
+    let mut printer = Printer::new(package);
+    printer.visit_block(block);
+    printer.finish()
+}
+
+struct Printer<'a> {
+    package: &'a Package,
+    output: String,
+    depth: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(package: &'a Package) -> Self {
+        Self {
+            package,
+            output: String::new(),
+            depth: 0,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        let len = self.output.trim_end().len();
+        self.output.truncate(len);
+        self.output
+    }
+
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    fn nested(&mut self, walk: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        walk(self);
+        self.depth -= 1;
+    }
+
+    fn spec_decl(&mut self, label: &str, decl: &'a SpecDecl) {
+        self.line(&format!("SpecDecl {} {}: {label}", decl.id, decl.span));
+        self.nested(|printer| visit::walk_spec_decl(printer, decl));
+    }
+}
+
+impl<'a> Visitor<'a> for Printer<'a> {
+    fn visit_package(&mut self, package: &'a Package) {
+        for item in package.items.values() {
+            self.visit_item(item);
+        }
+        if let Some(entry) = package.entry {
+            self.line("Entry");
+            self.nested(|printer| printer.visit_expr(entry));
+        }
+    }
+
+    fn visit_item(&mut self, item: &'a Item) {
+        let kind = match &item.kind {
+            ItemKind::Callable(_) => "Callable".to_string(),
+            ItemKind::Namespace(name, items) => {
+                format!("Namespace \"{}\": [{}]", name.name, join(items))
+            }
+            ItemKind::Ty(name, _) => format!("Newtype \"{}\"", name.name),
+        };
+        let parent = item
+            .parent
+            .map_or(String::new(), |parent| format!(" [Parent {parent}]"));
+        self.line(&format!(
+            "Item {} {}{parent} ({:?}): {kind}",
+            item.id, item.span, item.visibility
+        ));
+        self.nested(|printer| visit::walk_item(printer, item));
+    }
+
+    fn visit_callable_decl(&mut self, decl: &'a CallableDecl) {
+        let generics = if decl.generics.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", join(&decl.generics))
+        };
+        self.line(&format!(
+            "CallableDecl {} {} ({}): \"{}\"{generics} [Output {}] [Functors {}]",
+            decl.id, decl.span, decl.kind, decl.name.name, decl.output, decl.functors
+        ));
+        self.nested(|printer| {
+            printer.visit_pat(decl.input);
+            match &decl.implementation {
+                CallableImpl::Intrinsic => printer.line("Intrinsic"),
+                CallableImpl::Spec(spec_impl) => printer.visit_spec_impl(spec_impl),
+            }
+        });
+    }
+
+    fn visit_spec_impl(&mut self, spec_impl: &'a SpecImpl) {
+        self.spec_decl("body", &spec_impl.body);
+        if let Some(adj) = &spec_impl.adj {
+            self.spec_decl("adjoint", adj);
+        }
+        if let Some(ctl) = &spec_impl.ctl {
+            self.spec_decl("controlled", ctl);
+        }
+        if let Some(ctl_adj) = &spec_impl.ctl_adj {
+            self.spec_decl("controlled adjoint", ctl_adj);
+        }
+    }
+
+    fn visit_block(&mut self, block: BlockId) {
+        let block = self.get_block(block);
+        self.line(&format!(
+            "Block {} {} [Type {}]",
+            block.id, block.span, block.ty
+        ));
+        self.nested(|printer| visit::walk_block(printer, block.id));
+    }
+
+    fn visit_stmt(&mut self, stmt: StmtId) {
+        let stmt = self.get_stmt(stmt);
+        let kind = match &stmt.kind {
+            StmtKind::Expr(_) => "Expr".to_string(),
+            StmtKind::Item(item) => format!("Item {item}"),
+            StmtKind::Local(mutability, _, _) => format!("Local ({mutability:?})"),
+            StmtKind::Semi(_) => "Semi".to_string(),
+        };
+        self.line(&format!("Stmt {} {}: {kind}", stmt.id, stmt.span));
+        self.nested(|printer| visit::walk_stmt(printer, stmt.id));
+    }
+
+    fn visit_expr(&mut self, expr: ExprId) {
+        let expr = self.get_expr(expr);
+        self.line(&format!(
+            "Expr {} {} [Type {}]: {}",
+            expr.id,
+            expr.span,
+            expr.ty,
+            expr_kind_label(&expr.kind)
+        ));
+        self.nested(|printer| visit::walk_expr(printer, expr.id));
+    }
+
+    fn visit_pat(&mut self, pat: PatId) {
+        let pat = self.get_pat(pat);
+        let kind = match &pat.kind {
+            PatKind::Bind(name) => format!("Bind {} \"{}\"", name.id, name.name),
+            PatKind::Discard => "Discard".to_string(),
+            PatKind::Tuple(_) => "Tuple".to_string(),
+        };
+        self.line(&format!(
+            "Pat {} {} [Type {}]: {kind}",
+            pat.id, pat.span, pat.ty
+        ));
+        self.nested(|printer| visit::walk_pat(printer, pat.id));
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+}
+
+/// Describes an expression kind on a single line, leaving out the nodes it contains.
+fn expr_kind_label(kind: &ExprKind) -> String {
+    match kind {
+        ExprKind::Array(_) => "Array".to_string(),
+        ExprKind::ArrayRepeat(..) => "ArrayRepeat".to_string(),
+        ExprKind::Assign(..) => "Assign".to_string(),
+        ExprKind::AssignOp(op, ..) => format!("AssignOp ({op:?})"),
+        ExprKind::AssignField(_, field, _) => format!("AssignField ({field})"),
+        ExprKind::AssignIndex(..) => "AssignIndex".to_string(),
+        ExprKind::BinOp(op, ..) => format!("BinOp ({op:?})"),
+        ExprKind::Block(_) => "Block".to_string(),
+        ExprKind::Call(..) => "Call".to_string(),
+        ExprKind::Closure(args, callable) => format!("Closure([{}], {callable})", join(args)),
+        ExprKind::Fail(_) => "Fail".to_string(),
+        ExprKind::Field(_, field) => format!("Field ({field})"),
+        ExprKind::Hole => "Hole".to_string(),
+        ExprKind::If(..) => "If".to_string(),
+        ExprKind::Index(..) => "Index".to_string(),
+        ExprKind::Lit(lit) => format!("Lit: {lit}"),
+        ExprKind::Range(..) => "Range".to_string(),
+        ExprKind::Return(_) => "Return".to_string(),
+        ExprKind::String(components) => {
+            let mut label = "String: ".to_string();
+            for component in components {
+                let _ = match component {
+                    StringComponent::Expr(expr) => write!(label, "{{{expr}}}"),
+                    StringComponent::Lit(lit) => write!(label, "{lit:?}"),
+                };
+            }
+            label
+        }
+        ExprKind::UpdateIndex(..) => "UpdateIndex".to_string(),
+        ExprKind::Tuple(_) => "Tuple".to_string(),
+        ExprKind::UnOp(op, _) => format!("UnOp ({op})"),
+        ExprKind::UpdateField(_, field, _) => format!("UpdateField ({field})"),
+        ExprKind::Var(res, args) if args.is_empty() => format!("Var: {res}"),
+        ExprKind::Var(res, args) => format!("Var: {res} <{}>", join(args)),
+        ExprKind::While(..) => "While".to_string(),
+    }
+}
+
+fn join(items: &[impl ToString]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
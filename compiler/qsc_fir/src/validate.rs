@@ -1,42 +1,284 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+//! Validation of the structural invariants of lowered FIR: every ID refers to a node in the
+//! package, every literal has the type of its value, every callable has exactly the
+//! specializations its functors require, and every local variable is used only where it is bound.
+//!
+//! The evaluator assumes these invariants hold, so a lowering that breaks one would otherwise
+//! surface as a panic in the middle of evaluation. Hosts that build or transform FIR themselves can
+//! use [`check`] to find these errors before the package runs.
+
+#[cfg(test)]
+mod tests;
+
 use crate::{
-    fir::{Block, BlockId, Expr, ExprId, Package, Pat, PatId, Stmt, StmtId},
-    visit::Visitor,
+    fir::{
+        BlockId, CallableDecl, CallableImpl, ExprId, ExprKind, Functor, ItemKind, Lit, LocalItemId,
+        LocalVarId, Package, PatId, PatKind, Res, SpecDecl, StmtId, StmtKind, StringComponent,
+    },
+    ty::{FunctorSetValue, Prim, Ty},
 };
+use rustc_hash::FxHashSet;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("block {0} is not in the package")]
+    MissingBlock(BlockId),
+
+    #[error("expression {0} is not in the package")]
+    MissingExpr(ExprId),
+
+    #[error("pattern {0} is not in the package")]
+    MissingPat(PatId),
+
+    #[error("statement {0} is not in the package")]
+    MissingStmt(StmtId),
 
-pub struct Validator<'a> {
-    pub package: &'a Package,
+    #[error("literal expression {0} has type {1}")]
+    LitTy(ExprId, Ty),
+
+    #[error("callable {0} supports {1} but has no {2} specialization")]
+    MissingSpec(LocalItemId, FunctorSetValue, &'static str),
+
+    #[error("callable {0} supports {1} but has a {2} specialization")]
+    UnexpectedSpec(LocalItemId, FunctorSetValue, &'static str),
+
+    #[error("expression {0} uses local variable {1}, which is not in scope")]
+    LocalNotInScope(ExprId, LocalVarId),
 }
 
+/// Validates that the FIR is well-formed.
+///
+/// # Panics
+///
+/// Panics if the package breaks any of the invariants checked by [`check`].
 pub fn validate(package: &Package) {
-    let mut v = Validator { package };
-    v.validate();
+    let errors = check(package);
+    assert!(
+        errors.is_empty(),
+        "FIR should be valid, but found:\n{}",
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
 }
 
-/// Validates that the FIR is well-formed.
-/// Running `validate` will validate the entire package.
-impl Validator<'_> {
-    pub fn validate(&mut self) {
-        self.visit_package(self.package);
+/// Checks the items and the entry expression of the package, and returns each broken invariant.
+#[must_use]
+pub fn check(package: &Package) -> Vec<Error> {
+    let mut checker = Checker {
+        package,
+        scopes: Vec::new(),
+        errors: Vec::new(),
+    };
+    for item in package.items.values() {
+        if let ItemKind::Callable(decl) = &item.kind {
+            checker.check_callable(item.id, decl);
+        }
     }
+    if let Some(entry) = package.entry {
+        checker.scopes.push(FxHashSet::default());
+        checker.check_expr(entry);
+        checker.scopes.pop();
+    }
+    checker.errors
+}
+
+struct Checker<'a> {
+    package: &'a Package,
+    /// The local variables bound in each enclosing scope, innermost last.
+    scopes: Vec<FxHashSet<LocalVarId>>,
+    errors: Vec<Error>,
 }
 
-impl<'a> Visitor<'a> for Validator<'a> {
-    fn get_block(&self, id: BlockId) -> &'a Block {
-        self.package.blocks.get(id).expect("block not found")
+impl Checker<'_> {
+    fn check_callable(&mut self, item: LocalItemId, decl: &CallableDecl) {
+        let CallableImpl::Spec(spec) = &decl.implementation else {
+            self.scopes.push(FxHashSet::default());
+            self.bind(decl.input);
+            self.scopes.pop();
+            return;
+        };
+
+        let is_adj = decl.functors.contains(&Functor::Adj);
+        let is_ctl = decl.functors.contains(&Functor::Ctl);
+        for (expected, actual, name) in [
+            (is_adj, spec.adj.is_some(), "adjoint"),
+            (is_ctl, spec.ctl.is_some(), "controlled"),
+            (
+                is_adj && is_ctl,
+                spec.ctl_adj.is_some(),
+                "controlled adjoint",
+            ),
+        ] {
+            if expected && !actual {
+                self.errors
+                    .push(Error::MissingSpec(item, decl.functors, name));
+            } else if actual && !expected {
+                self.errors
+                    .push(Error::UnexpectedSpec(item, decl.functors, name));
+            }
+        }
+
+        for spec_decl in [
+            Some(&spec.body),
+            spec.adj.as_ref(),
+            spec.ctl.as_ref(),
+            spec.ctl_adj.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.check_spec(decl.input, spec_decl);
+        }
+    }
+
+    fn check_spec(&mut self, input: PatId, decl: &SpecDecl) {
+        self.scopes.push(FxHashSet::default());
+        self.bind(input);
+        if let Some(spec_input) = decl.input {
+            self.bind(spec_input);
+        }
+        self.check_block(decl.block);
+        self.scopes.pop();
+    }
+
+    fn check_block(&mut self, id: BlockId) {
+        let Some(block) = self.package.blocks.get(id) else {
+            self.errors.push(Error::MissingBlock(id));
+            return;
+        };
+        self.scopes.push(FxHashSet::default());
+        for &stmt in &block.stmts {
+            self.check_stmt(stmt);
+        }
+        self.scopes.pop();
+    }
+
+    fn check_stmt(&mut self, id: StmtId) {
+        let Some(stmt) = self.package.stmts.get(id) else {
+            self.errors.push(Error::MissingStmt(id));
+            return;
+        };
+        match &stmt.kind {
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => self.check_expr(*expr),
+            // The variables of a binding are in scope only after its initializer.
+            StmtKind::Local(_, pat, expr) => {
+                self.check_expr(*expr);
+                self.bind(*pat);
+            }
+            StmtKind::Item(_) => {}
+        }
     }
 
-    fn get_expr(&self, id: ExprId) -> &'a Expr {
-        self.package.exprs.get(id).expect("expr not found")
+    /// Binds the variables of the pattern in the innermost scope.
+    fn bind(&mut self, id: PatId) {
+        let Some(pat) = self.package.pats.get(id) else {
+            self.errors.push(Error::MissingPat(id));
+            return;
+        };
+        match &pat.kind {
+            PatKind::Bind(ident) => {
+                self.scopes
+                    .last_mut()
+                    .expect("there should be a scope to bind in")
+                    .insert(ident.id);
+            }
+            PatKind::Discard => {}
+            PatKind::Tuple(items) => {
+                for &item in items {
+                    self.bind(item);
+                }
+            }
+        }
     }
 
-    fn get_pat(&self, id: PatId) -> &'a Pat {
-        self.package.pats.get(id).expect("pat not found")
+    fn check_local(&mut self, expr: ExprId, var: LocalVarId) {
+        if !self.scopes.iter().any(|scope| scope.contains(&var)) {
+            self.errors.push(Error::LocalNotInScope(expr, var));
+        }
     }
 
-    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
-        self.package.stmts.get(id).expect("stmt not found")
+    fn check_expr(&mut self, id: ExprId) {
+        let Some(expr) = self.package.exprs.get(id) else {
+            self.errors.push(Error::MissingExpr(id));
+            return;
+        };
+        match &expr.kind {
+            ExprKind::Array(items) | ExprKind::Tuple(items) => {
+                for &item in items {
+                    self.check_expr(item);
+                }
+            }
+            ExprKind::ArrayRepeat(lhs, rhs)
+            | ExprKind::Assign(lhs, rhs)
+            | ExprKind::AssignOp(_, lhs, rhs)
+            | ExprKind::AssignField(lhs, _, rhs)
+            | ExprKind::BinOp(_, lhs, rhs)
+            | ExprKind::Call(lhs, rhs)
+            | ExprKind::Index(lhs, rhs)
+            | ExprKind::UpdateField(lhs, _, rhs) => {
+                self.check_expr(*lhs);
+                self.check_expr(*rhs);
+            }
+            ExprKind::AssignIndex(container, index, value)
+            | ExprKind::UpdateIndex(container, index, value) => {
+                self.check_expr(*container);
+                self.check_expr(*index);
+                self.check_expr(*value);
+            }
+            ExprKind::Block(block) => self.check_block(*block),
+            ExprKind::Closure(captures, _) => {
+                for &var in captures {
+                    self.check_local(id, var);
+                }
+            }
+            ExprKind::Fail(inner)
+            | ExprKind::Field(inner, _)
+            | ExprKind::Return(inner)
+            | ExprKind::UnOp(_, inner) => self.check_expr(*inner),
+            ExprKind::If(cond, then, otherwise) => {
+                self.check_expr(*cond);
+                self.check_expr(*then);
+                if let Some(otherwise) = otherwise {
+                    self.check_expr(*otherwise);
+                }
+            }
+            ExprKind::Lit(lit) => {
+                let prim = match lit {
+                    Lit::BigInt(_) => Prim::BigInt,
+                    Lit::Bool(_) => Prim::Bool,
+                    Lit::Double(_) => Prim::Double,
+                    Lit::Int(_) => Prim::Int,
+                    Lit::Pauli(_) => Prim::Pauli,
+                    Lit::Result(_) => Prim::Result,
+                };
+                if expr.ty != Ty::Prim(prim) && expr.ty != Ty::Err {
+                    self.errors.push(Error::LitTy(id, expr.ty.clone()));
+                }
+            }
+            ExprKind::Range(start, step, end) => {
+                for part in [start, step, end].into_iter().flatten() {
+                    self.check_expr(*part);
+                }
+            }
+            ExprKind::String(components) => {
+                for component in components {
+                    if let StringComponent::Expr(expr) = component {
+                        self.check_expr(*expr);
+                    }
+                }
+            }
+            ExprKind::Var(Res::Local(var), _) => self.check_local(id, *var),
+            ExprKind::While(cond, body) => {
+                self.check_expr(*cond);
+                self.check_block(*body);
+            }
+            ExprKind::Hole | ExprKind::Var(..) => {}
+        }
     }
 }
@@ -0,0 +1,272 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{check, Error};
+use crate::{
+    fir::{
+        Block, BlockId, CallableDecl, CallableImpl, CallableKind, Expr, ExprId, ExprKind, Ident,
+        Item, ItemKind, Lit, LocalItemId, LocalVarId, Mutability, NodeId, Package, Pat, PatId,
+        PatKind, Res, SpecDecl, SpecImpl, Stmt, StmtId, StmtKind, Visibility,
+    },
+    ty::{FunctorSetValue, Prim, Ty},
+};
+use qsc_data_structures::span::Span;
+
+/// Builds a package node by node, giving each node the next ID of its kind.
+#[derive(Default)]
+struct Builder {
+    package: Package,
+    next_block: u32,
+    next_expr: u32,
+    next_pat: u32,
+    next_stmt: u32,
+}
+
+impl Builder {
+    fn expr(&mut self, ty: Ty, kind: ExprKind) -> ExprId {
+        let id = ExprId(self.next_expr);
+        self.next_expr += 1;
+        self.package.exprs.insert(
+            id,
+            Expr {
+                id,
+                span: Span::default(),
+                ty,
+                kind,
+            },
+        );
+        id
+    }
+
+    fn int(&mut self, value: i64) -> ExprId {
+        self.expr(Ty::Prim(Prim::Int), ExprKind::Lit(Lit::Int(value)))
+    }
+
+    fn local(&mut self, var: u32) -> ExprId {
+        self.expr(
+            Ty::Prim(Prim::Int),
+            ExprKind::Var(Res::Local(LocalVarId(var)), Vec::new()),
+        )
+    }
+
+    fn bind(&mut self, var: u32) -> PatId {
+        let id = PatId(self.next_pat);
+        self.next_pat += 1;
+        self.package.pats.insert(
+            id,
+            Pat {
+                id,
+                span: Span::default(),
+                ty: Ty::Prim(Prim::Int),
+                kind: PatKind::Bind(Ident {
+                    id: LocalVarId(var),
+                    span: Span::default(),
+                    name: "x".into(),
+                }),
+            },
+        );
+        id
+    }
+
+    fn stmt(&mut self, kind: StmtKind) -> StmtId {
+        let id = StmtId(self.next_stmt);
+        self.next_stmt += 1;
+        self.package.stmts.insert(
+            id,
+            Stmt {
+                id,
+                span: Span::default(),
+                kind,
+            },
+        );
+        id
+    }
+
+    fn let_int(&mut self, var: u32, value: i64) -> StmtId {
+        let pat = self.bind(var);
+        let init = self.int(value);
+        self.stmt(StmtKind::Local(Mutability::Immutable, pat, init))
+    }
+
+    fn block(&mut self, stmts: Vec<StmtId>) -> BlockId {
+        let id = BlockId(self.next_block);
+        self.next_block += 1;
+        self.package.blocks.insert(
+            id,
+            Block {
+                id,
+                span: Span::default(),
+                ty: Ty::UNIT,
+                stmts,
+            },
+        );
+        id
+    }
+
+    fn block_expr(&mut self, stmts: Vec<StmtId>) -> ExprId {
+        let block = self.block(stmts);
+        self.expr(Ty::UNIT, ExprKind::Block(block))
+    }
+
+    fn spec(&mut self) -> SpecDecl {
+        let block = self.block(Vec::new());
+        SpecDecl {
+            id: NodeId::FIRST,
+            span: Span::default(),
+            block,
+            input: None,
+        }
+    }
+
+    /// Adds an operation that takes an `Int` bound to the local variable 0.
+    fn operation(&mut self, functors: FunctorSetValue, implementation: CallableImpl) {
+        let input = self.bind(0);
+        let id = LocalItemId::from(0);
+        self.package.items.insert(
+            id,
+            Item {
+                id,
+                span: Span::default(),
+                parent: None,
+                doc: "".into(),
+                attrs: Vec::new(),
+                visibility: Visibility::Public,
+                kind: ItemKind::Callable(CallableDecl {
+                    id: NodeId::FIRST,
+                    span: Span::default(),
+                    kind: CallableKind::Operation,
+                    name: Ident {
+                        id: LocalVarId::default(),
+                        span: Span::default(),
+                        name: "Op".into(),
+                    },
+                    generics: Vec::new(),
+                    input,
+                    output: Ty::UNIT,
+                    functors,
+                    implementation,
+                }),
+            },
+        );
+    }
+
+    fn check_entry(mut self, entry: ExprId) -> Vec<Error> {
+        self.package.entry = Some(entry);
+        check(&self.package)
+    }
+}
+
+#[test]
+fn valid_package_has_no_errors() {
+    let mut builder = Builder::default();
+    let binding = builder.let_int(0, 1);
+    let use_local = builder.local(0);
+    let use_stmt = builder.stmt(StmtKind::Expr(use_local));
+    let entry = builder.block_expr(vec![binding, use_stmt]);
+    assert_eq!(builder.check_entry(entry), []);
+}
+
+#[test]
+fn dangling_ids_are_reported() {
+    let mut builder = Builder::default();
+    let stmt = builder.stmt(StmtKind::Semi(ExprId(7)));
+    let entry = builder.block_expr(vec![stmt, StmtId(5)]);
+    assert_eq!(
+        builder.check_entry(entry),
+        [Error::MissingExpr(ExprId(7)), Error::MissingStmt(StmtId(5))]
+    );
+}
+
+#[test]
+fn literal_with_wrong_type_is_reported() {
+    let mut builder = Builder::default();
+    let entry = builder.expr(Ty::Prim(Prim::Bool), ExprKind::Lit(Lit::Int(1)));
+    assert_eq!(
+        builder.check_entry(entry),
+        [Error::LitTy(entry, Ty::Prim(Prim::Bool))]
+    );
+}
+
+#[test]
+fn local_used_before_binding_is_reported() {
+    let mut builder = Builder::default();
+    let use_local = builder.local(0);
+    let use_stmt = builder.stmt(StmtKind::Expr(use_local));
+    let binding = builder.let_int(0, 1);
+    let entry = builder.block_expr(vec![use_stmt, binding]);
+    assert_eq!(
+        builder.check_entry(entry),
+        [Error::LocalNotInScope(use_local, LocalVarId(0))]
+    );
+}
+
+#[test]
+fn local_used_outside_its_block_is_reported() {
+    let mut builder = Builder::default();
+    let binding = builder.let_int(0, 1);
+    let inner = builder.block_expr(vec![binding]);
+    let inner_stmt = builder.stmt(StmtKind::Semi(inner));
+    let use_local = builder.local(0);
+    let use_stmt = builder.stmt(StmtKind::Expr(use_local));
+    let entry = builder.block_expr(vec![inner_stmt, use_stmt]);
+    assert_eq!(
+        builder.check_entry(entry),
+        [Error::LocalNotInScope(use_local, LocalVarId(0))]
+    );
+}
+
+#[test]
+fn callable_input_is_in_scope_in_its_specializations() {
+    let mut builder = Builder::default();
+    let use_local = builder.local(0);
+    let use_stmt = builder.stmt(StmtKind::Expr(use_local));
+    let block = builder.block(vec![use_stmt]);
+    let body = SpecDecl {
+        id: NodeId::FIRST,
+        span: Span::default(),
+        block,
+        input: None,
+    };
+    let adj = builder.spec();
+    builder.operation(
+        FunctorSetValue::Adj,
+        CallableImpl::Spec(SpecImpl {
+            body,
+            adj: Some(adj),
+            ctl: None,
+            ctl_adj: None,
+        }),
+    );
+    assert_eq!(check(&builder.package), []);
+}
+
+#[test]
+fn specializations_that_do_not_match_functors_are_reported() {
+    let mut builder = Builder::default();
+    let body = builder.spec();
+    let ctl = builder.spec();
+    builder.operation(
+        FunctorSetValue::Adj,
+        CallableImpl::Spec(SpecImpl {
+            body,
+            adj: None,
+            ctl: Some(ctl),
+            ctl_adj: None,
+        }),
+    );
+    let item = LocalItemId::from(0);
+    assert_eq!(
+        check(&builder.package),
+        [
+            Error::MissingSpec(item, FunctorSetValue::Adj, "adjoint"),
+            Error::UnexpectedSpec(item, FunctorSetValue::Adj, "controlled"),
+        ]
+    );
+}
+
+#[test]
+fn intrinsic_callable_needs_no_specializations() {
+    let mut builder = Builder::default();
+    builder.operation(FunctorSetValue::CtlAdj, CallableImpl::Intrinsic);
+    assert_eq!(check(&builder.package), []);
+}
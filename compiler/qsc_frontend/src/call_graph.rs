@@ -0,0 +1,334 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The call graph of the callables in a package store. Each edge is a place in the body of a
+//! callable where it refers to another callable: by calling it, by creating a closure of it, or by
+//! using it as a value that something else may call. The graph can be queried for the callers and
+//! callees of a callable, for the callables that call themselves through other callables, and for
+//! the callables that the entry points of the store can reach.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compile::PackageStore;
+use qsc_data_structures::{functors::FunctorApp, span::Span};
+use qsc_hir::{
+    hir::{Attr, Expr, ExprKind, Functor, ItemId, ItemKind, LocalItemId, PackageId, Res, UnOp},
+    visit::{self, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// A callable in a package store.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CallableId {
+    pub package: PackageId,
+    pub item: LocalItemId,
+}
+
+/// How a callable refers to another callable.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CallKind {
+    /// The callee is called directly by name.
+    Direct,
+    /// A closure of the callee is created, like a lambda or a partial application, which calls the
+    /// callee when the closure is called.
+    Closure,
+    /// The callee is used as a value, like an argument to another callable, which may call it.
+    Value,
+}
+
+/// A reference from one callable to another.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Call {
+    /// The callable that refers to the callee, or `None` for the entry expression or top-level
+    /// statements of a package.
+    pub caller: Option<CallableId>,
+    pub callee: CallableId,
+    pub kind: CallKind,
+    /// The functors applied to the callee where it is referred to.
+    pub functor: FunctorApp,
+    /// The package whose sources contain the reference.
+    pub package: PackageId,
+    /// The span of the reference, relative to the sources of `package`.
+    pub span: Span,
+}
+
+/// The call graph of a package store.
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    /// Every callable in the store, in the order of their packages and IDs.
+    callables: Vec<CallableId>,
+    /// The callables with an `@EntryPoint()` attribute, and those that the entry expression or
+    /// top-level statements of a package refer to.
+    entry_points: Vec<CallableId>,
+    calls: Vec<Call>,
+    /// The indices in `calls` of the calls that each callable makes.
+    callees: FxHashMap<CallableId, Vec<usize>>,
+    /// The indices in `calls` of the calls to each callable.
+    callers: FxHashMap<CallableId, Vec<usize>>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of every package in the store.
+    #[must_use]
+    pub fn new(store: &PackageStore) -> Self {
+        let mut graph = Self::default();
+        for (package, unit) in store {
+            let mut builder = Builder {
+                store,
+                package,
+                caller: None,
+                calls: Vec::new(),
+            };
+            for item in unit.package.items.values() {
+                if let ItemKind::Callable(decl) = &item.kind {
+                    let id = CallableId {
+                        package,
+                        item: item.id,
+                    };
+                    graph.callables.push(id);
+                    if item.attrs.contains(&Attr::EntryPoint) {
+                        graph.entry_points.push(id);
+                    }
+                    builder.caller = Some(id);
+                    builder.visit_callable_decl(decl);
+                }
+            }
+
+            builder.caller = None;
+            for stmt in &unit.package.stmts {
+                builder.visit_stmt(stmt);
+            }
+            if let Some(entry) = &unit.package.entry {
+                builder.visit_expr(entry);
+            }
+
+            for call in builder.calls {
+                if call.caller.is_none() && !graph.entry_points.contains(&call.callee) {
+                    graph.entry_points.push(call.callee);
+                }
+                graph.add(call);
+            }
+        }
+        graph
+    }
+
+    fn add(&mut self, call: Call) {
+        let index = self.calls.len();
+        if let Some(caller) = call.caller {
+            self.callees.entry(caller).or_default().push(index);
+        }
+        self.callers.entry(call.callee).or_default().push(index);
+        self.calls.push(call);
+    }
+
+    /// Every callable in the store.
+    #[must_use]
+    pub fn callables(&self) -> &[CallableId] {
+        &self.callables
+    }
+
+    /// The callables with an `@EntryPoint()` attribute, and those that the entry expression or
+    /// top-level statements of a package refer to.
+    #[must_use]
+    pub fn entry_points(&self) -> &[CallableId] {
+        &self.entry_points
+    }
+
+    /// Every reference from one callable to another.
+    #[must_use]
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// The references that the callable makes to other callables, in the order they appear.
+    pub fn callees(&self, callable: CallableId) -> impl Iterator<Item = &Call> {
+        self.callees
+            .get(&callable)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.calls[index])
+    }
+
+    /// The references to the callable from other callables and from entry expressions.
+    pub fn callers(&self, callable: CallableId) -> impl Iterator<Item = &Call> {
+        self.callers
+            .get(&callable)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.calls[index])
+    }
+
+    /// The callables that the given callables can reach, including themselves.
+    #[must_use]
+    pub fn reachable(&self, roots: impl IntoIterator<Item = CallableId>) -> FxHashSet<CallableId> {
+        let mut reached = FxHashSet::default();
+        let mut stack: Vec<_> = roots.into_iter().collect();
+        while let Some(callable) = stack.pop() {
+            if reached.insert(callable) {
+                stack.extend(self.callees(callable).map(|call| call.callee));
+            }
+        }
+        reached
+    }
+
+    /// The callables that the entry points can reach. A callable that is not reachable is never
+    /// run by the entry points.
+    #[must_use]
+    pub fn reachable_from_entry_points(&self) -> FxHashSet<CallableId> {
+        self.reachable(self.entry_points.iter().copied())
+    }
+
+    /// The groups of callables that call each other, so that each callable in a group can reach
+    /// itself. A callable that only calls itself is a group of one.
+    #[must_use]
+    pub fn cycles(&self) -> Vec<Vec<CallableId>> {
+        let mut finder = CycleFinder {
+            graph: self,
+            index: FxHashMap::default(),
+            low: FxHashMap::default(),
+            stack: Vec::new(),
+            on_stack: FxHashSet::default(),
+            cycles: Vec::new(),
+        };
+        for &callable in &self.callables {
+            if !finder.index.contains_key(&callable) {
+                finder.visit(callable);
+            }
+        }
+        finder.cycles
+    }
+}
+
+/// Collects the references to callables in a package.
+struct Builder<'a> {
+    store: &'a PackageStore,
+    package: PackageId,
+    caller: Option<CallableId>,
+    calls: Vec<Call>,
+}
+
+impl Builder<'_> {
+    /// The callable that the expression refers to, with the functors applied to it.
+    fn callee(&self, expr: &Expr) -> Option<(CallableId, FunctorApp)> {
+        match &expr.kind {
+            ExprKind::Var(Res::Item(id), _) => {
+                self.callable(*id).map(|id| (id, FunctorApp::default()))
+            }
+            ExprKind::UnOp(UnOp::Functor(functor), operand) => {
+                let (id, mut app) = self.callee(operand)?;
+                match functor {
+                    Functor::Adj => app.adjoint = !app.adjoint,
+                    Functor::Ctl => app.controlled += 1,
+                }
+                Some((id, app))
+            }
+            _ => None,
+        }
+    }
+
+    /// The callable with the ID, if the item is a callable.
+    fn callable(&self, id: ItemId) -> Option<CallableId> {
+        let package = id.package.unwrap_or(self.package);
+        let item = self.store.get(package)?.package.items.get(id.item)?;
+        matches!(item.kind, ItemKind::Callable(_)).then_some(CallableId {
+            package,
+            item: id.item,
+        })
+    }
+
+    fn add(&mut self, callee: CallableId, kind: CallKind, functor: FunctorApp, span: Span) {
+        self.calls.push(Call {
+            caller: self.caller,
+            callee,
+            kind,
+            functor,
+            package: self.package,
+            span,
+        });
+    }
+}
+
+impl<'a> Visitor<'a> for Builder<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Call(callee, arg) => {
+                if let Some((id, functor)) = self.callee(callee) {
+                    self.add(id, CallKind::Direct, functor, callee.span);
+                    self.visit_expr(arg);
+                } else {
+                    visit::walk_expr(self, expr);
+                }
+            }
+            ExprKind::Closure(_, item) => {
+                let id = CallableId {
+                    package: self.package,
+                    item: *item,
+                };
+                self.add(id, CallKind::Closure, FunctorApp::default(), expr.span);
+            }
+            ExprKind::Var(..) | ExprKind::UnOp(UnOp::Functor(_), _) => {
+                if let Some((id, functor)) = self.callee(expr) {
+                    self.add(id, CallKind::Value, functor, expr.span);
+                } else {
+                    visit::walk_expr(self, expr);
+                }
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+/// Finds the strongly connected components of the call graph with Tarjan's algorithm, keeping
+/// those that contain a cycle.
+struct CycleFinder<'a> {
+    graph: &'a CallGraph,
+    index: FxHashMap<CallableId, usize>,
+    low: FxHashMap<CallableId, usize>,
+    stack: Vec<CallableId>,
+    on_stack: FxHashSet<CallableId>,
+    cycles: Vec<Vec<CallableId>>,
+}
+
+impl CycleFinder<'_> {
+    fn visit(&mut self, callable: CallableId) {
+        let index = self.index.len();
+        self.index.insert(callable, index);
+        self.low.insert(callable, index);
+        self.stack.push(callable);
+        self.on_stack.insert(callable);
+
+        let graph = self.graph;
+        for call in graph.callees(callable) {
+            let callee = call.callee;
+            if !self.index.contains_key(&callee) {
+                self.visit(callee);
+                let low = self.low[&callable].min(self.low[&callee]);
+                self.low.insert(callable, low);
+            } else if self.on_stack.contains(&callee) {
+                let low = self.low[&callable].min(self.index[&callee]);
+                self.low.insert(callable, low);
+            }
+        }
+
+        if self.low[&callable] == index {
+            let mut component = Vec::new();
+            loop {
+                let member = self.stack.pop().expect("callable should be on the stack");
+                self.on_stack.remove(&member);
+                component.push(member);
+                if member == callable {
+                    break;
+                }
+            }
+            let calls_itself = self
+                .graph
+                .callees(callable)
+                .any(|call| call.callee == callable);
+            if component.len() > 1 || calls_itself {
+                component.reverse();
+                self.cycles.push(component);
+            }
+        }
+    }
+}
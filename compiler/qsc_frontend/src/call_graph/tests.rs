@@ -0,0 +1,171 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{CallGraph, CallableId};
+use crate::compile::{self, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_hir::hir::{ItemKind, PackageId};
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        @EntryPoint()
+        operation Main() : Unit {
+            use q = Qubit();
+            Adjoint Prepare(q);
+            ApplyTwice(Prepare, q);
+            let f = r => Prepare(r);
+            f(q);
+        }
+
+        operation Prepare(q : Qubit) : Unit is Adj + Ctl {}
+
+        operation ApplyTwice(op : Qubit => Unit, q : Qubit) : Unit {
+            op(q);
+            op(q);
+        }
+
+        function Even(n : Int) : Bool {
+            n == 0 or Odd(n - 1)
+        }
+
+        function Odd(n : Int) : Bool {
+            n != 0 and Even(n - 1)
+        }
+
+        function Fact(n : Int) : Int {
+            n <= 1 ? 1 | n * Fact(n - 1)
+        }
+
+        function Unused() : Unit {}
+    }
+"};
+
+fn compile_graph() -> (PackageStore, PackageId, CallGraph) {
+    let mut store = PackageStore::new(compile::core());
+    let unit = compile::compile(
+        &store,
+        &[],
+        SourceMap::new([("test".into(), SOURCE.into())], None),
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    let package = store.insert(unit);
+    let graph = CallGraph::new(&store);
+    (store, package, graph)
+}
+
+fn name(store: &PackageStore, id: CallableId) -> String {
+    let unit = store.get(id.package).expect("package should be in store");
+    let item = unit
+        .package
+        .items
+        .get(id.item)
+        .expect("item should be in package");
+    let ItemKind::Callable(decl) = &item.kind else {
+        panic!("item should be a callable");
+    };
+    decl.name.name.to_string()
+}
+
+fn check_sorted(lines: impl Iterator<Item = String>, expect: &Expect) {
+    let mut lines: Vec<_> = lines.collect();
+    lines.sort();
+    expect.assert_eq(&lines.join("\n"));
+}
+
+#[test]
+fn calls_have_kind_and_functors() {
+    let (store, package, graph) = compile_graph();
+    let calls = graph
+        .calls()
+        .iter()
+        .filter(|call| call.package == package)
+        .map(|call| {
+            let caller = name(&store, call.caller.expect("call should have a caller"));
+            let mut line = format!("{caller} -> {}: {:?}", name(&store, call.callee), call.kind);
+            if call.functor.adjoint {
+                line.push_str(" adjoint");
+            }
+            for _ in 0..call.functor.controlled {
+                line.push_str(" controlled");
+            }
+            line
+        });
+    check_sorted(
+        calls,
+        &expect![[r#"
+            Even -> Odd: Direct
+            Fact -> Fact: Direct
+            Main -> ApplyTwice: Direct
+            Main -> Prepare: Direct adjoint
+            Main -> Prepare: Value
+            Main -> lambda: Closure
+            Odd -> Even: Direct
+            lambda -> Prepare: Direct"#]],
+    );
+}
+
+#[test]
+fn callers_include_every_reference() {
+    let (store, _, graph) = compile_graph();
+    let prepare = graph
+        .callables()
+        .iter()
+        .copied()
+        .find(|&id| name(&store, id) == "Prepare")
+        .expect("callable should be in graph");
+    let callers = graph.callers(prepare).map(|call| {
+        let caller = call.caller.expect("call should have a caller");
+        format!("{}: {:?}", name(&store, caller), call.kind)
+    });
+    check_sorted(
+        callers,
+        &expect![[r#"
+            Main: Direct
+            Main: Value
+            lambda: Direct"#]],
+    );
+}
+
+#[test]
+fn cycles_include_mutual_and_self_recursion() {
+    let (store, package, graph) = compile_graph();
+    let cycles = graph
+        .cycles()
+        .into_iter()
+        .filter(|cycle| cycle[0].package == package)
+        .map(|cycle| {
+            let names: Vec<_> = cycle.into_iter().map(|id| name(&store, id)).collect();
+            names.join(", ")
+        });
+    check_sorted(
+        cycles,
+        &expect![[r#"
+            Even, Odd
+            Fact"#]],
+    );
+}
+
+#[test]
+fn callables_unreachable_from_entry_points() {
+    let (store, package, graph) = compile_graph();
+    let reachable = graph.reachable_from_entry_points();
+    let unreachable = graph
+        .callables()
+        .iter()
+        .filter(|id| id.package == package && !reachable.contains(id))
+        .map(|&id| name(&store, id));
+    check_sorted(
+        unreachable,
+        &expect![[r#"
+            Even
+            Fact
+            Odd
+            Unused"#]],
+    );
+}
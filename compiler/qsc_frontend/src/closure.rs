@@ -129,6 +129,7 @@ pub(super) fn lift(
         },
         generics: Vec::new(),
         input,
+        defaults: Vec::new(),
         output: lambda.body.ty.clone(),
         functors: lambda.functors,
         body: SpecDecl {
@@ -147,6 +148,7 @@ pub(super) fn lift(
                     }],
                 },
             ),
+            generated: None,
         },
         adj: None,
         ctl: None,
@@ -4,12 +4,16 @@
 #[cfg(test)]
 mod tests;
 
+pub mod artifact;
+pub mod loader;
 pub mod preprocess;
+pub(crate) mod warnings;
 
 use crate::{
     error::WithSource,
+    lint,
     lower::{self, Lowerer},
-    resolve::{self, Locals, Names, Resolver},
+    resolve::{self, Explanations, GlobalScope, Locals, Names, References, Resolver},
     typeck::{self, Checker, Table},
 };
 use bitflags::bitflags;
@@ -23,17 +27,20 @@ use qsc_ast::{
     visit::Visitor as _,
 };
 use qsc_data_structures::{
+    cancellation::CancellationToken,
     index_map::{self, IndexMap},
     language_features::LanguageFeatures,
+    lints::{Lints, WarningLevels},
     span::Span,
 };
 use qsc_hir::{
     assigner::Assigner as HirAssigner,
     global,
-    hir::{self, PackageId},
+    hir::{self, ItemId, LocalItemId, PackageId},
     validate::Validator as HirValidator,
     visit::Visitor as _,
 };
+use serde::{Deserialize, Serialize};
 use std::{fmt::Debug, str::FromStr, sync::Arc};
 use thiserror::Error;
 
@@ -48,7 +55,7 @@ bitflags! {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ConfigAttr {
     Unrestricted,
     Base,
@@ -97,6 +104,8 @@ pub struct CompileUnit {
     pub assigner: HirAssigner,
     pub sources: SourceMap,
     pub errors: Vec<Error>,
+    /// Diagnostics that don't prevent the unit from compiling, such as unused opens.
+    pub warnings: Vec<Warning>,
     pub dropped_names: Vec<TrackedName>,
 }
 
@@ -106,6 +115,12 @@ pub struct AstPackage {
     pub tys: Table,
     pub names: Names,
     pub locals: Locals,
+    /// How each name resolved, if the compilation was configured to explain names.
+    pub explanations: Explanations,
+    /// The references to each item and local.
+    pub references: References,
+    /// The global names that were visible to the package, for completion.
+    pub globals: GlobalScope,
 }
 
 #[derive(Debug, Default)]
@@ -173,6 +188,11 @@ impl SourceMap {
     pub fn iter(&self) -> impl Iterator<Item = &Source> {
         self.sources.iter()
     }
+
+    #[must_use]
+    pub fn entry(&self) -> Option<&Source> {
+        self.entry.as_ref()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -192,16 +212,54 @@ pub type SourceContents = Arc<str>;
 pub struct Error(pub(super) ErrorKind);
 
 #[derive(Clone, Debug, Diagnostic, Error)]
-#[diagnostic(transparent)]
 pub(super) enum ErrorKind {
     #[error("syntax error")]
+    #[diagnostic(transparent)]
     Parse(#[from] qsc_parse::Error),
     #[error("name error")]
+    #[diagnostic(transparent)]
     Resolve(#[from] resolve::Error),
     #[error("type error")]
+    #[diagnostic(transparent)]
     Type(#[from] typeck::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     Lower(#[from] lower::Error),
+    #[error("compilation was cancelled")]
+    #[diagnostic(code("Qsc.Cancelled"))]
+    Cancelled,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Denied(warnings::Denied),
+}
+
+impl Error {
+    /// The error that a cancelled compilation reports in place of all others.
+    #[must_use]
+    pub fn cancelled() -> Self {
+        Self(ErrorKind::Cancelled)
+    }
+
+    /// Whether the error is the one reported in place of all others when a compilation is
+    /// cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.0, ErrorKind::Cancelled)
+    }
+}
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+#[diagnostic(transparent)]
+#[error(transparent)]
+pub struct Warning(pub(super) WarningKind);
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+#[diagnostic(transparent)]
+pub(super) enum WarningKind {
+    #[error("name warning")]
+    Resolve(#[from] resolve::Warning),
+    #[error(transparent)]
+    Lint(#[from] lint::Lint),
 }
 
 pub struct PackageStore {
@@ -251,6 +309,26 @@ impl PackageStore {
         Iter(self.units.iter())
     }
 
+    /// Finds the references to an item in every unit of the store, including the unit that
+    /// declares it, as pairs of the referencing unit and the span of the reference.
+    #[must_use]
+    pub fn item_references(&self, package: PackageId, item: LocalItemId) -> Vec<(PackageId, Span)> {
+        self.units
+            .iter()
+            .flat_map(|(id, unit)| {
+                let item_id = ItemId {
+                    package: (id != package).then_some(package),
+                    item,
+                };
+                unit.ast
+                    .references
+                    .item(item_id)
+                    .iter()
+                    .map(move |&span| (id, span))
+            })
+            .collect()
+    }
+
     /// "Opens" the package store. This inserts an empty
     /// package into the store, which will be considered
     /// the open package and which can be incrementally updated.
@@ -337,16 +415,146 @@ impl MutVisitor for Offsetter {
     }
 }
 
+/// Settings for a compilation that most embedders leave at their defaults.
+#[derive(Clone, Debug)]
+pub struct CompileOptions {
+    /// The namespaces that are implicitly opened, which are [`resolve::PRELUDE`] by default.
+    pub prelude: Vec<Arc<str>>,
+    /// The optional warnings to report, which are none by default.
+    pub lints: Lints,
+    /// Whether to record how each name resolved, which is off by default.
+    pub explain_names: bool,
+    /// The most name errors to report for each source, which is unlimited by default. Errors past
+    /// the limit are replaced with one error that counts them.
+    pub max_name_errors: Option<usize>,
+    /// Whether to infer the functors of operations declared without a functor set from their
+    /// bodies, which is off by default.
+    pub infer_functors: bool,
+    /// The symbols defined for the compilation, which `@Config` attributes can require. None are
+    /// defined by default.
+    pub defines: Vec<Arc<str>>,
+    /// The token that abandons the compilation when it is cancelled, which is never cancelled by
+    /// default. A cancelled compilation stops at the next check and reports only
+    /// [`Error::is_cancelled`].
+    pub cancellation: CancellationToken,
+    /// The severity of warnings by their code. Every warning is reported as a warning by default.
+    pub warnings: WarningLevels,
+    /// The lint rules to run on a package that type checks, which are none by default.
+    pub lint_rules: lint::Registry,
+    /// The size, in statements and expressions, of the largest callable whose calls are inlined,
+    /// which is zero by default so that no calls are inlined.
+    pub inline_threshold: usize,
+    /// The largest number of iterations of a `for` loop over a constant range that is unrolled,
+    /// which is zero by default so that no loops are unrolled.
+    pub unroll_budget: usize,
+    /// Whether constant expressions are folded and immutable constants propagated, which is off by
+    /// default so that the debugger steps through the code as written.
+    pub const_fold: bool,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            prelude: resolve::PRELUDE
+                .iter()
+                .map(|&namespace| namespace.into())
+                .collect(),
+            lints: Lints::default(),
+            explain_names: false,
+            max_name_errors: None,
+            infer_functors: false,
+            defines: Vec::new(),
+            cancellation: CancellationToken::default(),
+            warnings: WarningLevels::default(),
+            lint_rules: lint::Registry::default(),
+            inline_threshold: 0,
+            unroll_budget: 0,
+            const_fold: false,
+        }
+    }
+}
+
+/// Compiles the sources into a package. Each dependency can have an alias, which lets the sources
+/// refer to its items with package-qualified paths like `Alias::Namespace.Name`.
+#[must_use]
 pub fn compile(
     store: &PackageStore,
-    dependencies: &[PackageId],
+    dependencies: &[(PackageId, Option<Arc<str>>)],
     sources: SourceMap,
     capabilities: RuntimeCapabilityFlags,
     language_features: LanguageFeatures,
 ) -> CompileUnit {
-    let (mut ast_package, parse_errors) = parse_all(&sources, language_features);
+    compile_with_options(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        language_features,
+        &CompileOptions::default(),
+    )
+}
 
-    let mut cond_compile = preprocess::Conditional::new(capabilities);
+/// Compiles the sources into a package like [`compile`], with the given options.
+#[must_use]
+pub fn compile_with_options(
+    store: &PackageStore,
+    dependencies: &[(PackageId, Option<Arc<str>>)],
+    sources: SourceMap,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    options: &CompileOptions,
+) -> CompileUnit {
+    compile_cached(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        language_features,
+        options,
+        None,
+    )
+}
+
+/// Compiles the sources into a package like [`compile_with_options`], reusing the name resolution
+/// of each namespace that did not change since the last compilation with the cache. The cache
+/// should only be used to compile versions of the same package with the same dependencies.
+pub fn compile_with_cache(
+    store: &PackageStore,
+    dependencies: &[(PackageId, Option<Arc<str>>)],
+    sources: SourceMap,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    options: &CompileOptions,
+    cache: &mut resolve::Cache,
+) -> CompileUnit {
+    compile_cached(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        language_features,
+        options,
+        Some(cache),
+    )
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+fn compile_cached(
+    store: &PackageStore,
+    dependencies: &[(PackageId, Option<Arc<str>>)],
+    sources: SourceMap,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    options: &CompileOptions,
+    cache: Option<&mut resolve::Cache>,
+) -> CompileUnit {
+    let (mut ast_package, parse_errors) =
+        parse_all(&sources, language_features, &options.cancellation);
+    if options.cancellation.is_cancelled() {
+        return cancelled(sources);
+    }
+
+    let mut cond_compile = preprocess::Conditional::new(capabilities, &options.defines);
     cond_compile.visit_package(&mut ast_package);
     let dropped_names = cond_compile.into_names();
 
@@ -354,14 +562,37 @@ pub fn compile(
     ast_assigner.visit_package(&mut ast_package);
     AstValidator::default().visit_package(&ast_package);
     let mut hir_assigner = HirAssigner::new();
-    let (names, locals, name_errors) = resolve_all(
-        store,
-        dependencies,
-        &mut hir_assigner,
-        &ast_package,
-        dropped_names.clone(),
-    );
-    let (tys, ty_errors) = typeck_all(store, dependencies, &ast_package, &names);
+    let (names, locals, explanations, references, globals, name_errors, name_warnings) =
+        resolve_all(
+            store,
+            dependencies,
+            &mut hir_assigner,
+            &ast_package,
+            &sources,
+            dropped_names.clone(),
+            options,
+            cache.map(|cache| (cache, language_features)),
+        );
+    if options.cancellation.is_cancelled() {
+        return cancelled(sources);
+    }
+
+    let (tys, ty_errors) = typeck_all(store, dependencies, &ast_package, &names, options);
+    if options.cancellation.is_cancelled() {
+        return cancelled(sources);
+    }
+
+    // Lints assume a package that resolves and type checks, so they are skipped otherwise.
+    let lints = if parse_errors.is_empty() && name_errors.is_empty() && ty_errors.is_empty() {
+        options.lint_rules.check(&lint::Context {
+            package: &ast_package,
+            names: &names,
+            tys: &tys,
+        })
+    } else {
+        Vec::new()
+    };
+
     let mut lowerer = Lowerer::new();
     let package = lowerer
         .with(&mut hir_assigner, &names, &tys)
@@ -369,6 +600,13 @@ pub fn compile(
     HirValidator::default().visit_package(&package);
     let lower_errors = lowerer.drain_errors();
 
+    let warnings = name_warnings
+        .into_iter()
+        .map(Into::into)
+        .chain(lints.into_iter().map(Into::into))
+        .map(Warning)
+        .collect();
+    let (warnings, denied) = warnings::apply_levels(&ast_package, warnings, &options.warnings);
     let errors = parse_errors
         .into_iter()
         .map(Into::into)
@@ -376,6 +614,7 @@ pub fn compile(
         .chain(ty_errors.into_iter().map(Into::into))
         .chain(lower_errors.into_iter().map(Into::into))
         .map(Error)
+        .chain(denied)
         .collect();
 
     CompileUnit {
@@ -385,14 +624,28 @@ pub fn compile(
             tys,
             names,
             locals,
+            explanations,
+            references,
+            globals,
         },
         assigner: hir_assigner,
         sources,
         errors,
+        warnings,
         dropped_names,
     }
 }
 
+/// The unit of a cancelled compilation, which has only the sources and the error that reports
+/// the cancellation.
+fn cancelled(sources: SourceMap) -> CompileUnit {
+    CompileUnit {
+        sources,
+        errors: vec![Error::cancelled()],
+        ..CompileUnit::default()
+    }
+}
+
 /// Compiles the core library.
 ///
 /// # Panics
@@ -438,7 +691,7 @@ pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> Compil
 
     let mut unit = compile(
         store,
-        &[PackageId::CORE],
+        &[(PackageId::CORE, None)],
         sources,
         capabilities,
         LanguageFeatures::default(),
@@ -447,13 +700,19 @@ pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> Compil
     unit
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 fn parse_all(
     sources: &SourceMap,
     features: LanguageFeatures,
+    cancellation: &CancellationToken,
 ) -> (ast::Package, Vec<qsc_parse::Error>) {
     let mut namespaces = Vec::new();
     let mut errors = Vec::new();
     for source in &sources.sources {
+        if cancellation.is_cancelled() {
+            break;
+        }
+
         let (source_namespaces, source_errors) = qsc_parse::namespaces(&source.contents, features);
         for mut namespace in source_namespaces {
             Offsetter(source.offset).visit_namespace(&mut namespace);
@@ -483,54 +742,85 @@ fn parse_all(
     (package, errors)
 }
 
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 fn resolve_all(
     store: &PackageStore,
-    dependencies: &[PackageId],
+    dependencies: &[(PackageId, Option<Arc<str>>)],
     assigner: &mut HirAssigner,
     package: &ast::Package,
+    sources: &SourceMap,
     mut dropped_names: Vec<TrackedName>,
-) -> (Names, Locals, Vec<resolve::Error>) {
+    options: &CompileOptions,
+    cache: Option<(&mut resolve::Cache, LanguageFeatures)>,
+) -> (
+    Names,
+    Locals,
+    Explanations,
+    References,
+    GlobalScope,
+    Vec<resolve::Error>,
+    Vec<resolve::Warning>,
+) {
     let mut globals = resolve::GlobalTable::new();
     if let Some(unit) = store.get(PackageId::CORE) {
-        globals.add_external_package(PackageId::CORE, &unit.package);
+        globals.add_external_package(PackageId::CORE, &unit.package, None);
         dropped_names.extend(unit.dropped_names.iter().cloned());
     }
 
-    for &id in dependencies {
+    for (id, alias) in dependencies {
         let unit = store
-            .get(id)
+            .get(*id)
             .expect("dependency should be in package store before compilation");
-        globals.add_external_package(id, &unit.package);
+        globals.add_external_package(*id, &unit.package, alias.as_ref());
         dropped_names.extend(unit.dropped_names.iter().cloned());
     }
 
     let mut errors = globals.add_local_package(assigner, package);
     let mut resolver = Resolver::new(globals, dropped_names);
-    resolver.with(assigner).visit_package(package);
-    let (names, locals, mut resolver_errors) = resolver.into_result();
+    resolver.set_options(options);
+    match cache {
+        Some((cache, language_features)) => {
+            resolver.resolve_cached(assigner, package, sources, language_features, cache);
+        }
+        None => resolver.with(assigner).visit_package(package),
+    }
+    let (names, locals, explanations, references, globals, mut resolver_errors, warnings) =
+        resolver.into_result();
     errors.append(&mut resolver_errors);
-    (names, locals, errors)
+    (
+        names,
+        locals,
+        explanations,
+        references,
+        globals,
+        resolve::consolidate_errors(errors, sources, options.max_name_errors),
+        warnings,
+    )
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 fn typeck_all(
     store: &PackageStore,
-    dependencies: &[PackageId],
+    dependencies: &[(PackageId, Option<Arc<str>>)],
     package: &ast::Package,
     names: &Names,
+    options: &CompileOptions,
 ) -> (typeck::Table, Vec<typeck::Error>) {
     let mut globals = typeck::GlobalTable::new();
     if let Some(unit) = store.get(PackageId::CORE) {
         globals.add_external_package(PackageId::CORE, &unit.package);
     }
 
-    for &id in dependencies {
+    for (id, _) in dependencies {
         let unit = store
-            .get(id)
+            .get(*id)
             .expect("dependency should be added to package store before compilation");
-        globals.add_external_package(id, &unit.package);
+        globals.add_external_package(*id, &unit.package);
     }
 
     let mut checker = Checker::new(globals);
+    checker.set_options(options);
     checker.check_package(names, package);
     checker.into_table()
 }
@@ -0,0 +1,107 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Saves a compiled package as bytes and loads it back, so that a library like the standard library
+//! can be compiled once ahead of time instead of in every process that uses it.
+
+use super::{preprocess::TrackedName, CompileUnit, RuntimeCapabilityFlags, SourceMap};
+use miette::Diagnostic;
+use qsc_hir::{assigner::Assigner as HirAssigner, hir};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The version of the artifact format. An artifact saved with a different version cannot be loaded.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("package artifact could not be read")]
+    #[diagnostic(code("Qsc.Artifact.Format"))]
+    Format,
+
+    #[error("package artifact has format version {0}, expected version {1}")]
+    #[diagnostic(help("rebuild the artifact with this version of the compiler"))]
+    #[diagnostic(code("Qsc.Artifact.Version"))]
+    Version(u32, u32),
+
+    #[error("package artifact was compiled for different target capabilities")]
+    #[diagnostic(code("Qsc.Artifact.Capabilities"))]
+    Capabilities,
+}
+
+/// A saved package. It is written from borrowed parts, so that saving doesn't copy the package.
+#[derive(Serialize)]
+struct SavedArtifact<'a> {
+    version: u32,
+    capabilities: u32,
+    package: &'a hir::Package,
+    assigner: &'a HirAssigner,
+    sources: Vec<(&'a str, &'a str)>,
+    entry: Option<&'a str>,
+    dropped_names: &'a [TrackedName],
+}
+
+/// A loaded package, with the same fields as [`SavedArtifact`].
+#[derive(Deserialize)]
+struct Artifact {
+    version: u32,
+    capabilities: u32,
+    package: hir::Package,
+    assigner: HirAssigner,
+    sources: Vec<(Arc<str>, Arc<str>)>,
+    entry: Option<Arc<str>>,
+    dropped_names: Vec<TrackedName>,
+}
+
+/// Saves the HIR of a compiled package with its sources, which locate the spans in the HIR, and the
+/// capabilities that it was compiled for. The AST of the package is not saved.
+///
+/// # Panics
+///
+/// Panics if the package cannot be written, which only happens if a value in it is not
+/// serializable.
+#[must_use]
+pub fn save(unit: &CompileUnit, capabilities: RuntimeCapabilityFlags) -> Vec<u8> {
+    let artifact = SavedArtifact {
+        version: FORMAT_VERSION,
+        capabilities: capabilities.bits(),
+        package: &unit.package,
+        assigner: &unit.assigner,
+        sources: unit
+            .sources
+            .iter()
+            .map(|source| (&*source.name, &*source.contents))
+            .collect(),
+        entry: unit.sources.entry.as_ref().map(|source| &*source.contents),
+        dropped_names: &unit.dropped_names,
+    };
+
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&artifact, &mut bytes).expect("package should be serializable");
+    bytes
+}
+
+/// Loads a package saved by [`save`]. The loaded package has an empty AST, so tools that need the
+/// syntax of the package, like finding references in its sources, should compile it instead.
+///
+/// # Errors
+///
+/// Returns an error if the bytes are not an artifact of this format version, or if the artifact was
+/// compiled for capabilities other than the given capabilities.
+pub fn load(bytes: &[u8], capabilities: RuntimeCapabilityFlags) -> Result<CompileUnit, Error> {
+    let artifact: Artifact = ciborium::from_reader(bytes).map_err(|_| Error::Format)?;
+    if artifact.version != FORMAT_VERSION {
+        return Err(Error::Version(artifact.version, FORMAT_VERSION));
+    } else if artifact.capabilities != capabilities.bits() {
+        return Err(Error::Capabilities);
+    }
+
+    Ok(CompileUnit {
+        package: artifact.package,
+        assigner: artifact.assigner,
+        sources: SourceMap::new(artifact.sources, artifact.entry),
+        dropped_names: artifact.dropped_names,
+        ..CompileUnit::default()
+    })
+}
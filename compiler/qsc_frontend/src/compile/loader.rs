@@ -0,0 +1,97 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{SourceContents, SourceMap, SourceName};
+use miette::Diagnostic;
+use std::{collections::VecDeque, sync::Arc};
+use thiserror::Error;
+
+/// Fetches the sources of a package as they are found, so that a host can read them from disk,
+/// from memory, or from a virtual file system without collecting every source up front.
+pub trait SourceLoader {
+    /// Reads the source with the given name, or returns `None` if there is no such source.
+    fn load(&mut self, name: &str) -> Option<SourceContents>;
+
+    /// The names of the other sources that belong to the same package as the given source, like
+    /// the files next to it in its directory. By default, a source brings in no other sources.
+    fn references(&mut self, name: &str, contents: &str) -> Vec<SourceName> {
+        let _ = (name, contents);
+        Vec::new()
+    }
+}
+
+/// A loader for sources that are already in memory, like the open documents of an editor. Every
+/// source belongs to the same package as every other.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryLoader {
+    sources: Vec<(SourceName, SourceContents)>,
+}
+
+impl MemoryLoader {
+    pub fn new(sources: impl IntoIterator<Item = (SourceName, SourceContents)>) -> Self {
+        Self {
+            sources: sources.into_iter().collect(),
+        }
+    }
+}
+
+impl SourceLoader for MemoryLoader {
+    fn load(&mut self, name: &str) -> Option<SourceContents> {
+        self.sources
+            .iter()
+            .find(|(source, _)| source.as_ref() == name)
+            .map(|(_, contents)| Arc::clone(contents))
+    }
+
+    fn references(&mut self, _: &str, _: &str) -> Vec<SourceName> {
+        self.sources
+            .iter()
+            .map(|(name, _)| Arc::clone(name))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("source `{0}` was not found")]
+    #[diagnostic(code("Qsc.Load.NotFound"))]
+    NotFound(String),
+
+    #[error("source `{1}` referenced by `{0}` was not found")]
+    #[diagnostic(code("Qsc.Load.ReferenceNotFound"))]
+    ReferenceNotFound(String, String),
+}
+
+/// Loads the entry source and every source that it references, directly or indirectly. The sources
+/// are in the order they are found, starting with the entry source, and each is loaded only once.
+///
+/// # Errors
+///
+/// Returns an error if the entry source or a referenced source cannot be loaded.
+pub fn load(
+    loader: &mut impl SourceLoader,
+    entry: &str,
+    entry_expr: Option<Arc<str>>,
+) -> Result<SourceMap, Error> {
+    let contents = loader
+        .load(entry)
+        .ok_or_else(|| Error::NotFound(entry.to_string()))?;
+    let mut sources = vec![(SourceName::from(entry), contents)];
+    let mut pending = VecDeque::from([0]);
+    while let Some(index) = pending.pop_front() {
+        let (name, contents) = sources[index].clone();
+        for reference in loader.references(&name, &contents) {
+            if sources.iter().any(|(source, _)| *source == reference) {
+                continue;
+            }
+
+            let contents = loader
+                .load(&reference)
+                .ok_or_else(|| Error::ReferenceNotFound(name.to_string(), reference.to_string()))?;
+            pending.push_back(sources.len());
+            sources.push((reference, contents));
+        }
+    }
+
+    Ok(SourceMap::new(sources, entry_expr))
+}
@@ -3,30 +3,108 @@
 
 use core::str::FromStr;
 use qsc_ast::{
-    ast::{Attr, ExprKind, ItemKind, Namespace, Stmt, StmtKind},
+    ast::{Attr, BinOp, Expr, ExprKind, Item, ItemKind, Namespace, Stmt, StmtKind, UnOp},
     mut_visit::MutVisitor,
 };
+use qsc_data_structures::span::Span;
 use qsc_hir::hir;
-use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use super::{ConfigAttr, RuntimeCapabilityFlags};
 
-#[derive(PartialEq, Hash, Clone, Debug)]
+/// An item that was dropped because its `@Config` attribute doesn't match the target capabilities
+/// or the defined symbols.
+#[derive(PartialEq, Hash, Clone, Debug, Deserialize, Serialize)]
 pub struct TrackedName {
-    pub name: Rc<str>,
-    pub namespace: Rc<str>,
+    pub name: Arc<str>,
+    /// The namespace of the item, or empty for an item declared in a block.
+    pub namespace: Arc<str>,
+    /// The span of the whole item, relative to the sources of the package that declared it.
+    pub span: Span,
+    /// The configuration the item requires.
+    pub config: Config,
+}
+
+/// The condition in a `@Config` attribute, which combines target capabilities and symbols defined
+/// for the compilation with `not`, `and`, and `or`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Config {
+    /// A target capability: `Base` or `Unrestricted`.
+    Target(ConfigAttr),
+    /// A symbol, which holds if it is defined for the compilation.
+    Symbol(Arc<str>),
+    /// A negated condition: `not a`.
+    Not(Box<Config>),
+    /// A conjunction: `a and b`.
+    And(Box<Config>, Box<Config>),
+    /// A disjunction: `a or b`.
+    Or(Box<Config>, Box<Config>),
+}
+
+impl Config {
+    /// The target capability, if the condition is only a target capability. Converting it into
+    /// [`RuntimeCapabilityFlags`] gives target capabilities that would include the item.
+    #[must_use]
+    pub fn target(&self) -> Option<ConfigAttr> {
+        match self {
+            Self::Target(target) => Some(*target),
+            _ => None,
+        }
+    }
+
+    /// Reads the condition from the argument of a `@Config` attribute, if it is well-formed.
+    pub(crate) fn from_expr(expr: &Expr) -> Option<Self> {
+        match expr.kind.as_ref() {
+            ExprKind::Paren(inner) => Self::from_expr(inner),
+            ExprKind::Path(path) if path.package.is_none() && path.namespace.is_none() => Some(
+                ConfigAttr::from_str(&path.name.name)
+                    .map_or_else(|()| Self::Symbol(Arc::clone(&path.name.name)), Self::Target),
+            ),
+            ExprKind::UnOp(UnOp::NotL, inner) => Some(Self::Not(Box::new(Self::from_expr(inner)?))),
+            ExprKind::BinOp(BinOp::AndL, lhs, rhs) => Some(Self::And(
+                Box::new(Self::from_expr(lhs)?),
+                Box::new(Self::from_expr(rhs)?),
+            )),
+            ExprKind::BinOp(BinOp::OrL, lhs, rhs) => Some(Self::Or(
+                Box::new(Self::from_expr(lhs)?),
+                Box::new(Self::from_expr(rhs)?),
+            )),
+            _ => None,
+        }
+    }
+
+    fn holds(&self, capabilities: RuntimeCapabilityFlags, defines: &[Arc<str>]) -> bool {
+        match self {
+            // We can't do membership tests on the capabilities because Base is not a subset of
+            // any capabilities, it is a lack of capabilities.
+            Self::Target(ConfigAttr::Unrestricted) => capabilities.is_all(),
+            Self::Target(ConfigAttr::Base) => capabilities.is_empty(),
+            Self::Symbol(name) => defines.iter().any(|define| **define == **name),
+            Self::Not(inner) => !inner.holds(capabilities, defines),
+            Self::And(lhs, rhs) => {
+                lhs.holds(capabilities, defines) && rhs.holds(capabilities, defines)
+            }
+            Self::Or(lhs, rhs) => {
+                lhs.holds(capabilities, defines) || rhs.holds(capabilities, defines)
+            }
+        }
+    }
 }
 
 pub(crate) struct Conditional {
     capabilities: RuntimeCapabilityFlags,
+    defines: Vec<Arc<str>>,
     dropped_names: Vec<TrackedName>,
-    included_names: Vec<TrackedName>,
+    /// The namespace and name of each item that was kept.
+    included_names: Vec<(Arc<str>, Arc<str>)>,
 }
 
 impl Conditional {
-    pub(crate) fn new(capabilities: RuntimeCapabilityFlags) -> Self {
+    pub(crate) fn new(capabilities: RuntimeCapabilityFlags, defines: &[Arc<str>]) -> Self {
         Self {
             capabilities,
+            defines: defines.to_vec(),
             dropped_names: Vec::new(),
             included_names: Vec::new(),
         }
@@ -35,111 +113,85 @@ impl Conditional {
     pub(crate) fn into_names(self) -> Vec<TrackedName> {
         self.dropped_names
             .into_iter()
-            .filter(|n| !self.included_names.contains(n))
+            .filter(|n| {
+                !self
+                    .included_names
+                    .iter()
+                    .any(|(namespace, name)| *namespace == n.namespace && *name == n.name)
+            })
             .collect()
     }
+
+    /// Records the item as kept, or as dropped if its configuration doesn't match. Returns whether
+    /// the item is kept.
+    fn track(&mut self, namespace: &Arc<str>, item: &Item) -> bool {
+        let name = match item.kind.as_ref() {
+            ItemKind::Callable(callable) => Some(&callable.name.name),
+            ItemKind::Ty(ident, _) => Some(&ident.name),
+            _ => None,
+        };
+        match unmatched_config(&item.attrs, self.capabilities, &self.defines) {
+            None => {
+                if let Some(name) = name {
+                    self.included_names
+                        .push((Arc::clone(namespace), Arc::clone(name)));
+                }
+                true
+            }
+            Some(config) => {
+                if let Some(name) = name {
+                    self.dropped_names.push(TrackedName {
+                        name: Arc::clone(name),
+                        namespace: Arc::clone(namespace),
+                        span: item.span,
+                        config,
+                    });
+                }
+                false
+            }
+        }
+    }
 }
 
 impl MutVisitor for Conditional {
     fn visit_namespace(&mut self, namespace: &mut Namespace) {
+        let namespace_name = namespace.full_name();
         namespace.items = namespace
             .items
             .iter()
-            .filter_map(|item| {
-                if matches_config(&item.attrs, self.capabilities) {
-                    match item.kind.as_ref() {
-                        ItemKind::Callable(callable) => {
-                            self.included_names.push(TrackedName {
-                                name: callable.name.name.clone(),
-                                namespace: namespace.name.name.clone(),
-                            });
-                        }
-                        ItemKind::Ty(ident, _) => self.included_names.push(TrackedName {
-                            name: ident.name.clone(),
-                            namespace: namespace.name.name.clone(),
-                        }),
-                        _ => {}
-                    }
-                    Some(item.clone())
-                } else {
-                    match item.kind.as_ref() {
-                        ItemKind::Callable(callable) => {
-                            self.dropped_names.push(TrackedName {
-                                name: callable.name.name.clone(),
-                                namespace: namespace.name.name.clone(),
-                            });
-                        }
-                        ItemKind::Ty(ident, _) => self.dropped_names.push(TrackedName {
-                            name: ident.name.clone(),
-                            namespace: namespace.name.name.clone(),
-                        }),
-                        _ => {}
-                    }
-                    None
-                }
-            })
+            .filter(|item| self.track(&namespace_name, item))
+            .cloned()
             .collect::<Vec<_>>()
             .into_boxed_slice();
     }
 
     fn visit_stmt(&mut self, stmt: &mut Stmt) {
         if let StmtKind::Item(item) = stmt.kind.as_mut() {
-            if matches_config(&item.attrs, self.capabilities) {
-                match item.kind.as_ref() {
-                    ItemKind::Callable(callable) => {
-                        self.included_names.push(TrackedName {
-                            name: callable.name.name.clone(),
-                            namespace: Rc::from(""),
-                        });
-                    }
-                    ItemKind::Ty(ident, _) => self.included_names.push(TrackedName {
-                        name: ident.name.clone(),
-                        namespace: Rc::from(""),
-                    }),
-                    _ => {}
-                }
-            } else {
-                match item.kind.as_ref() {
-                    ItemKind::Callable(callable) => {
-                        self.dropped_names.push(TrackedName {
-                            name: callable.name.name.clone(),
-                            namespace: Rc::from(""),
-                        });
-                    }
-                    ItemKind::Ty(ident, _) => self.dropped_names.push(TrackedName {
-                        name: ident.name.clone(),
-                        namespace: Rc::from(""),
-                    }),
-                    _ => {}
-                }
+            if !self.track(&Arc::from(""), item) {
                 stmt.kind = Box::new(StmtKind::Empty);
             }
         }
     }
 }
 
-fn matches_config(attrs: &[Box<Attr>], capabilities: RuntimeCapabilityFlags) -> bool {
-    attrs.iter().all(|attr| {
-        if hir::Attr::from_str(attr.name.name.as_ref()) == Ok(hir::Attr::Config) {
-            if let ExprKind::Paren(inner) = attr.arg.kind.as_ref() {
-                match inner.kind.as_ref() {
-                    // If there is no config attribute, then we assume that the item matches
-                    // the target. We can't do membership tests on the capabilities because
-                    // Base is not a subset of any capabilities, it is a lack of capabilities.
-                    ExprKind::Path(path) => match ConfigAttr::from_str(path.name.name.as_ref()) {
-                        Ok(ConfigAttr::Unrestricted) => capabilities.is_all(),
-                        Ok(ConfigAttr::Base) => capabilities.is_empty(),
-                        _ => true,
-                    },
-                    _ => true, // Unknown config attribute, so we assume it matches
-                }
-            } else {
-                // Something other than a parenthesized expression, so we assume it matches
-                true
-            }
-        } else {
+/// Returns the configuration required by the item's `@Config` attribute, if it doesn't match the
+/// capabilities and defined symbols.
+fn unmatched_config(
+    attrs: &[Box<Attr>],
+    capabilities: RuntimeCapabilityFlags,
+    defines: &[Arc<str>],
+) -> Option<Config> {
+    attrs.iter().find_map(|attr| {
+        if hir::Attr::from_str(attr.name.name.as_ref()) != Ok(hir::Attr::Config) {
             // Unknown attribute, so we assume it matches
-            true
+            return None;
         }
+        let ExprKind::Paren(inner) = attr.arg.kind.as_ref() else {
+            // Something other than a parenthesized expression, so we assume it matches
+            return None;
+        };
+        // A malformed condition is reported during lowering, so we assume it matches
+        let config = Config::from_expr(inner)?;
+        (!config.holds(capabilities, defines)).then_some(config)
     })
 }
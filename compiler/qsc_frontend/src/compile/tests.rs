@@ -3,18 +3,28 @@
 
 #![allow(clippy::needless_raw_string_hashes)]
 
-use crate::compile::RuntimeCapabilityFlags;
+use crate::{
+    compile::RuntimeCapabilityFlags,
+    resolve::{MemberKind, Origin},
+};
 
-use super::{compile, CompileUnit, Error, PackageStore, SourceMap};
+use super::{
+    artifact, compile, compile_with_cache, compile_with_options,
+    loader::{self, MemoryLoader, SourceLoader},
+    preprocess::{Config, TrackedName},
+    CompileOptions, CompileUnit, ConfigAttr, Error, PackageStore, SourceContents, SourceMap,
+    SourceName, Warning,
+};
 use expect_test::expect;
 use indoc::indoc;
 use miette::Diagnostic;
-use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use qsc_ast::visit::Visitor as AstVisitor;
+use qsc_data_structures::{language_features::LanguageFeatures, lints::Severity, span::Span};
 use qsc_hir::{
     global,
     hir::{
-        Block, Expr, ExprKind, ItemId, ItemKind, Lit, LocalItemId, NodeId, Res, SpecBody, Stmt,
-        StmtKind,
+        Block, Expr, ExprKind, ItemId, ItemKind, ItemStatus, Lit, LocalItemId, NodeId, Res,
+        SpecBody, Stmt, StmtKind,
     },
     mut_visit::MutVisitor,
     ty::{Prim, Ty},
@@ -462,7 +472,7 @@ fn package_dependency() {
     );
     let unit2 = compile(
         &store,
-        &[package1],
+        &[(package1, None)],
         sources2,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -491,6 +501,126 @@ fn package_dependency() {
     .assert_eq(&unit2.package.to_string());
 }
 
+#[test]
+fn package_qualified_dependencies() {
+    let mut store = PackageStore::new(super::core());
+    let mut math_package = |result| {
+        let sources = SourceMap::new(
+            [(
+                "test".into(),
+                format!("namespace Math {{ function Add() : Int {{ {result} }} }}").into(),
+            )],
+            None,
+        );
+        let unit = compile(
+            &store,
+            &[],
+            sources,
+            RuntimeCapabilityFlags::all(),
+            LanguageFeatures::default(),
+        );
+        assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+        store.insert(unit)
+    };
+    let package1 = math_package(1);
+    let package2 = math_package(2);
+
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Main {
+                    function Foo() : Int {
+                        First::Math.Add() + Second::Math.Add()
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = compile(
+        &store,
+        &[
+            (package1, Some("First".into())),
+            (package2, Some("Second".into())),
+        ],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    expect![[r#"
+        Package:
+            Item 0 [0-98] (Public):
+                Namespace (Ident 13 [10-14] "Main"): Item 1
+            Item 1 [21-96] (Public):
+                Parent: 0
+                Callable 0 [21-96] (function):
+                    name: Ident 1 [30-33] "Foo"
+                    input: Pat 2 [33-35] [Type Unit]: Unit
+                    output: Int
+                    functors: empty set
+                    body: SpecDecl 3 [21-96]: Impl:
+                        Block 4 [42-96] [Type Int]:
+                            Stmt 5 [52-90]: Expr: Expr 6 [52-90] [Type Int]: BinOp (Add):
+                                Expr 7 [52-69] [Type Int]: Call:
+                                    Expr 8 [52-67] [Type (Unit -> Int)]: Var: Item 1 (Package 1)
+                                    Expr 9 [67-69] [Type Unit]: Unit
+                                Expr 10 [72-90] [Type Int]: Call:
+                                    Expr 11 [72-88] [Type (Unit -> Int)]: Var: Item 1 (Package 2)
+                                    Expr 12 [88-90] [Type Unit]: Unit
+                    adj: <none>
+                    ctl: <none>
+                    ctl-adj: <none>"#]]
+    .assert_eq(&unit.package.to_string());
+}
+
+#[test]
+fn package_qualified_unknown_alias_error() {
+    let store = PackageStore::new(super::core());
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Main {
+                    function Foo() : Int {
+                        Missing::Math.Add()
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+
+    let errors: Vec<_> = unit
+        .errors
+        .iter()
+        .map(|error| source_span(&unit.sources, error))
+        .collect();
+    expect![[r#"
+        [
+            (
+                "test",
+                Span {
+                    lo: 52,
+                    hi: 59,
+                },
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&errors);
+}
+
 #[test]
 fn package_dependency_internal_error() {
     let mut store = PackageStore::new(super::core());
@@ -535,7 +665,7 @@ fn package_dependency_internal_error() {
     );
     let unit2 = compile(
         &store,
-        &[package1],
+        &[(package1, None)],
         sources2,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -615,7 +745,7 @@ fn package_dependency_udt() {
     );
     let unit2 = compile(
         &store,
-        &[package1],
+        &[(package1, None)],
         sources2,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -695,7 +825,7 @@ fn package_dependency_nested_udt() {
     );
     let unit2 = compile(
         &store,
-        &[package1],
+        &[(package1, None)],
         sources2,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -775,7 +905,7 @@ fn std_dependency() {
 
     let unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -807,7 +937,7 @@ fn std_dependency_base_profile() {
 
     let unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         RuntimeCapabilityFlags::empty(),
         LanguageFeatures::default(),
@@ -835,7 +965,7 @@ fn introduce_prelude_ambiguity() {
 
     let unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -852,6 +982,108 @@ fn introduce_prelude_ambiguity() {
     );
 }
 
+#[test]
+fn custom_prelude_avoids_ambiguity() {
+    let mut store = PackageStore::new(super::core());
+    let std = store.insert(super::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"namespace Microsoft.Quantum.Canon {
+                function Length () : () { }
+            }
+                namespace Foo {
+                    function Main (): () { Length() }
+                }"}
+            .into(),
+        )],
+        Some("Foo.Main()".into()),
+    );
+
+    let unit = compile_with_options(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions {
+            prelude: vec![
+                "Microsoft.Quantum.Canon".into(),
+                "Microsoft.Quantum.Intrinsic".into(),
+            ],
+            ..CompileOptions::default()
+        },
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+}
+
+#[test]
+fn custom_prelude_opens_namespace() {
+    let store = PackageStore::new(super::core());
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Teaching.Helpers {
+                    function Greet() : Unit {}
+                }
+                namespace Foo {
+                    function Main() : Unit { Greet() }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+
+    let unit = compile_with_options(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions {
+            prelude: vec!["Microsoft.Quantum.Core".into(), "Teaching.Helpers".into()],
+            ..CompileOptions::default()
+        },
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+}
+
+#[test]
+fn empty_prelude_hides_core() {
+    let store = PackageStore::new(super::core());
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace Foo { function Main() : Int { Length([1]) } }".into(),
+        )],
+        None,
+    );
+
+    let unit = compile_with_options(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions {
+            prelude: Vec::new(),
+            ..CompileOptions::default()
+        },
+    );
+    assert!(
+        unit.errors.iter().any(|error| matches!(
+            error,
+            Error(super::ErrorKind::Resolve(super::resolve::Error::NotFound(
+                ..
+            )))
+        )),
+        "{:#?}",
+        unit.errors
+    );
+}
+
 #[test]
 fn entry_parse_error() {
     let sources = SourceMap::new(
@@ -949,7 +1181,7 @@ fn unimplemented_call_from_dependency_produces_error() {
     );
     let unit = compile(
         &store,
-        &[lib],
+        &[(lib, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -972,6 +1204,111 @@ fn unimplemented_call_from_dependency_produces_error() {
     .assert_debug_eq(&unit.errors);
 }
 
+#[test]
+fn deprecated_call_from_dependency_produces_warning() {
+    let lib_sources = SourceMap::new(
+        [(
+            "lib".into(),
+            indoc! {r#"
+                namespace Foo {
+                    @Deprecated("Foo.Baz")
+                    operation Bar() : Unit {}
+                    operation Baz() : Unit {}
+                }
+            "#}
+            .into(),
+        )],
+        None,
+    );
+    let mut store = PackageStore::new(super::core());
+    let lib = compile(
+        &store,
+        &[],
+        lib_sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(lib.errors.is_empty(), "{:#?}", lib.errors);
+    let lib = store.insert(lib);
+
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    open Foo;
+                    operation Main() : Unit {
+                        Bar();
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = compile(
+        &store,
+        &[(lib, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    expect![[r#"
+        [
+            Warning(
+                Resolve(
+                    Deprecated {
+                        name: "Bar",
+                        help: Some(
+                            "use `Foo.Baz` instead",
+                        ),
+                        span: Span {
+                            lo: 69,
+                            hi: 72,
+                        },
+                    },
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&unit.warnings);
+}
+
+#[test]
+fn deprecated_attribute_with_non_string_expr_error() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Foo {
+                    @Deprecated(1)
+                    operation Bar() : Unit {}
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = default_compile(sources);
+    expect![[r#"
+        [
+            Error(
+                Lower(
+                    InvalidAttrArgs(
+                        "() or a replacement name string",
+                        Span {
+                            lo: 31,
+                            hi: 34,
+                        },
+                    ),
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&unit.errors);
+}
+
 #[test]
 fn unimplemented_attribute_call_within_unit_error() {
     let sources = SourceMap::new(
@@ -1090,7 +1427,7 @@ fn unimplemented_attribute_avoids_ambiguous_error_with_duplicate_names_in_scope(
     );
     let unit = compile(
         &store,
-        &[lib],
+        &[(lib, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -1142,7 +1479,7 @@ fn duplicate_intrinsic_from_dependency() {
 
     let unit = compile(
         &store,
-        &[lib],
+        &[(lib, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -1193,7 +1530,7 @@ fn reject_use_qubit_block_syntax_if_preview_feature_is_on() {
 
     let unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         RuntimeCapabilityFlags::empty(),
         LanguageFeatures::V2PreviewSyntax,
@@ -1248,10 +1585,951 @@ fn accept_use_qubit_block_syntax_if_preview_feature_is_off() {
 
     let unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         RuntimeCapabilityFlags::empty(),
         LanguageFeatures::default(),
     );
     assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
 }
+
+fn explained_origins(source: &str, name_at: &str) -> Vec<Origin> {
+    struct PathFinder {
+        offset: u32,
+        id: Option<qsc_ast::ast::NodeId>,
+    }
+
+    impl AstVisitor<'_> for PathFinder {
+        fn visit_path(&mut self, path: &qsc_ast::ast::Path) {
+            if path.span.lo == self.offset {
+                self.id = Some(path.id);
+            }
+        }
+    }
+
+    let store = PackageStore::new(super::core());
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let unit = compile_with_options(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions {
+            explain_names: true,
+            ..CompileOptions::default()
+        },
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let mut finder = PathFinder {
+        offset: source
+            .find(name_at)
+            .expect("name should be in source")
+            .try_into()
+            .expect("offset should fit into u32"),
+        id: None,
+    };
+    finder.visit_package(&unit.ast.package);
+    let id = finder.id.expect("path should be at offset");
+    let explanation = unit
+        .ast
+        .explanations
+        .get(id)
+        .expect("path should have an explanation");
+    std::iter::once(explanation.origin.clone())
+        .chain(
+            explanation
+                .shadowed
+                .iter()
+                .map(|(_, origin)| origin.clone()),
+        )
+        .collect()
+}
+
+#[test]
+fn explain_open_shadows_prelude() {
+    let source = indoc! {"
+        namespace A { function Length(xs : Int[]) : Int { 0 } }
+        namespace B {
+            open A;
+            function Foo() : Int { Length([1]) }
+        }
+    "};
+    let open_span = Span { lo: 79, hi: 80 };
+    assert_eq!(&source[open_span.lo as usize..open_span.hi as usize], "A");
+    assert_eq!(
+        explained_origins(source, "Length([1])"),
+        vec![
+            Origin::Open {
+                namespace: "A".into(),
+                span: open_span,
+            },
+            Origin::Prelude("Microsoft.Quantum.Core".into()),
+        ]
+    );
+}
+
+#[test]
+fn explain_local_shadows_open_and_prelude() {
+    let source = indoc! {"
+        namespace A { function Length(xs : Int[]) : Int { 0 } }
+        namespace B {
+            open A;
+            function Foo() : Int {
+                let Length = 3;
+                Length
+            }
+        }
+    "};
+    let origins = explained_origins(source, "Length\n");
+    assert!(matches!(origins[0], Origin::Scope(_)), "{origins:?}");
+    assert_eq!(
+        origins[1..],
+        [
+            Origin::Open {
+                namespace: "A".into(),
+                span: Span { lo: 79, hi: 80 },
+            },
+            Origin::Prelude("Microsoft.Quantum.Core".into()),
+        ]
+    );
+}
+
+#[test]
+fn explain_qualified_path() {
+    let source = indoc! {"
+        namespace A { function Length(xs : Int[]) : Int { 0 } }
+        namespace B {
+            function Foo() : Int { A.Length([1]) }
+        }
+    "};
+    assert_eq!(
+        explained_origins(source, "A.Length([1])"),
+        vec![Origin::Namespace("A".into())]
+    );
+}
+
+#[test]
+fn names_are_not_explained_by_default() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace A { function Foo() : Int { Length([1]) } }".into(),
+        )],
+        None,
+    );
+    let unit = default_compile(sources);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    assert!(unit.ast.explanations.iter().next().is_none());
+}
+
+#[test]
+fn item_references_span_dependent_packages() {
+    let lib_sources = SourceMap::new(
+        [(
+            "lib".into(),
+            indoc! {"
+                namespace Foo {
+                    operation Bar() : Unit {}
+                    operation Baz() : Unit { Bar(); }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let mut store = PackageStore::new(super::core());
+    let lib = compile(
+        &store,
+        &[],
+        lib_sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(lib.errors.is_empty(), "{:#?}", lib.errors);
+    let lib = store.insert(lib);
+
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    open Foo;
+                    operation Main() : Unit {
+                        Bar();
+                        Foo.Bar();
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = compile(
+        &store,
+        &[(lib, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    let test = store.insert(unit);
+
+    assert_eq!(
+        store.item_references(lib, LocalItemId::from(1)),
+        vec![
+            (lib, Span { lo: 75, hi: 78 }),
+            (test, Span { lo: 69, hi: 72 }),
+            (test, Span { lo: 88, hi: 91 }),
+        ]
+    );
+}
+
+#[test]
+fn local_references_exclude_declaration() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    function Main() : Int {
+                        let x = 1;
+                        let y = x + x;
+                        y
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = default_compile(sources);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let mut locals = unit
+        .ast
+        .names
+        .iter()
+        .filter_map(|(_, res)| match res {
+            crate::resolve::Res::Local(id) => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    locals.sort_unstable();
+    locals.dedup();
+    let references = locals
+        .into_iter()
+        .map(|id| unit.ast.references.local(id).to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        references,
+        vec![
+            vec![Span { lo: 80, hi: 81 }, Span { lo: 84, hi: 85 }],
+            vec![Span { lo: 95, hi: 96 }],
+        ]
+    );
+}
+
+fn offset_of(source: &str, text: &str) -> u32 {
+    source
+        .find(text)
+        .expect("text should be in source")
+        .try_into()
+        .expect("offset should fit into u32")
+}
+
+#[test]
+fn members_at_offset_include_enclosing_opened_and_prelude_namespaces() {
+    let source = indoc! {"
+        namespace A {
+            function Foo() : Unit {}
+            @Deprecated()
+            function Old() : Unit {}
+            newtype T = Int;
+        }
+        namespace B {
+            open A;
+            function Bar() : Unit { }
+        }
+    "};
+    let unit = default_compile(SourceMap::new([("test".into(), source.into())], None));
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let offset = offset_of(source, "{ }") + 1;
+    let members = unit
+        .ast
+        .locals
+        .get_members_at_offset(&unit.ast.globals, offset);
+    let find = |name: &str, namespace: &str| {
+        members
+            .iter()
+            .filter(|member| &*member.name == name && &*member.namespace == namespace)
+            .map(|member| member.kind)
+            .collect::<Vec<_>>()
+    };
+
+    assert!(matches!(
+        find("Foo", "A")[..],
+        [MemberKind::Term(crate::resolve::Res::Item(
+            _,
+            ItemStatus::Available
+        ))]
+    ));
+    assert!(matches!(
+        find("Old", "A")[..],
+        [MemberKind::Term(crate::resolve::Res::Item(
+            _,
+            ItemStatus::Deprecated
+        ))]
+    ));
+    assert!(matches!(
+        find("T", "A")[..],
+        [MemberKind::Term(_), MemberKind::Ty(_)]
+    ));
+    assert!(matches!(find("Bar", "B")[..], [MemberKind::Term(_)]));
+    assert!(matches!(
+        find("Length", "Microsoft.Quantum.Core")[..],
+        [MemberKind::Term(_)]
+    ));
+    assert_eq!(find("A", "A"), vec![MemberKind::Namespace]);
+}
+
+#[test]
+fn path_members_at_offset_follow_aliases_and_namespace_prefixes() {
+    let source = indoc! {"
+        namespace Outer.Inner {
+            function Foo() : Unit {}
+        }
+        namespace B {
+            open Outer.Inner as I;
+            function Bar() : Unit { }
+        }
+    "};
+    let unit = default_compile(SourceMap::new([("test".into(), source.into())], None));
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let offset = offset_of(source, "{ }") + 1;
+    let names = |qualifier: &str| {
+        unit.ast
+            .locals
+            .get_path_members_at_offset(&unit.ast.globals, offset, qualifier)
+            .into_iter()
+            .map(|member| (member.name.to_string(), member.namespace.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names("I"),
+        vec![("Foo".to_string(), "Outer.Inner".to_string())]
+    );
+    assert_eq!(
+        names("Outer"),
+        vec![("Inner".to_string(), "Outer.Inner".to_string())]
+    );
+    assert_eq!(
+        names("Outer.Inner"),
+        vec![("Foo".to_string(), "Outer.Inner".to_string())]
+    );
+}
+
+#[test]
+fn dropped_names_record_span_and_required_config() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace A {
+                    @Config(Base)
+                    function Dropped() : Unit {}
+                    @Config(Unrestricted)
+                    function Kept() : Unit {}
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = default_compile(sources);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    assert_eq!(
+        unit.dropped_names,
+        vec![TrackedName {
+            name: "Dropped".into(),
+            namespace: "A".into(),
+            span: Span { lo: 18, hi: 64 },
+            config: Config::Target(ConfigAttr::Base),
+        }]
+    );
+    assert_eq!(
+        unit.dropped_names[0]
+            .config
+            .target()
+            .map(RuntimeCapabilityFlags::from),
+        Some(RuntimeCapabilityFlags::empty())
+    );
+}
+
+#[test]
+fn config_symbols_select_items() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace A {
+                    @Config(HARDWARE_A)
+                    function Target() : Int { 1 }
+                    @Config(not HARDWARE_A and Unrestricted)
+                    function Target() : Int { 2 }
+                    @Config(not HARDWARE_A or HARDWARE_B)
+                    function OnlyB() : Unit {}
+                    function Main() : Int { Target() }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+
+    let store = PackageStore::new(super::core());
+    let unit = compile_with_options(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions {
+            defines: vec!["HARDWARE_A".into()],
+            ..CompileOptions::default()
+        },
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    let dropped: Vec<_> = unit
+        .dropped_names
+        .iter()
+        .map(|n| (n.name.as_ref(), &n.config))
+        .collect();
+    assert_eq!(
+        dropped,
+        vec![(
+            "OnlyB",
+            &Config::Or(
+                Box::new(Config::Not(Box::new(Config::Symbol("HARDWARE_A".into())))),
+                Box::new(Config::Symbol("HARDWARE_B".into())),
+            )
+        )]
+    );
+}
+
+#[test]
+fn repeated_not_found_errors_are_grouped_per_source() {
+    let sources = SourceMap::new(
+        [
+            (
+                "a".into(),
+                "namespace A { function F() : Unit { Missing(); Missing(); Other(); } }".into(),
+            ),
+            (
+                "b".into(),
+                "namespace B { function G() : Unit { Missing(); } }".into(),
+            ),
+        ],
+        None,
+    );
+    let unit = default_compile(sources);
+    let errors = unit
+        .errors
+        .iter()
+        .filter_map(|error| match &error.0 {
+            super::ErrorKind::Resolve(error) => Some(error),
+            _ => None,
+        })
+        .map(|error| {
+            let labels = error
+                .labels()
+                .into_iter()
+                .flatten()
+                .map(|label| (label.label().map(ToString::to_string), label.offset()))
+                .collect::<Vec<_>>();
+            (error.to_string(), labels)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "`Missing` not found".to_string(),
+                vec![(None, 36), (Some("also not found here".to_string()), 47)]
+            ),
+            ("`Other` not found".to_string(), vec![(None, 58)]),
+            ("`Missing` not found".to_string(), vec![(None, 107)]),
+        ]
+    );
+}
+
+#[test]
+fn name_errors_are_limited_per_source() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace A { function F() : Unit { X(); Y(); Z(); W(); } }".into(),
+        )],
+        None,
+    );
+    let unit = compile_with_options(
+        &PackageStore::new(super::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions {
+            max_name_errors: Some(2),
+            ..CompileOptions::default()
+        },
+    );
+    let errors = unit
+        .errors
+        .iter()
+        .filter_map(|error| match &error.0 {
+            super::ErrorKind::Resolve(error) => Some(error),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors
+            .iter()
+            .map(|error| {
+                let label = error
+                    .labels()
+                    .and_then(|mut labels| labels.next())
+                    .expect("error should have a label");
+                (error.to_string(), label.offset())
+            })
+            .collect::<Vec<_>>(),
+        vec![
+            ("`X` not found".to_string(), 36),
+            ("`Y` not found".to_string(), 41),
+            ("too many name errors in `test`".to_string(), 46),
+        ]
+    );
+    assert_eq!(
+        errors[2].help().map(|help| help.to_string()).as_deref(),
+        Some("2 more name errors in this source were not reported")
+    );
+}
+
+#[test]
+fn operation_functors_are_inferred_from_body() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace A {
+                    operation X(q : Qubit) : Unit is Adj + Ctl { body intrinsic; }
+                    operation M(q : Qubit) : Result { body intrinsic; }
+                    operation Flip(q : Qubit) : Unit { X(q); }
+                    operation Measure(q : Qubit) : Unit { let r = M(q); }
+                    operation Loop(q : Qubit) : Unit {
+                        mutable i = 0;
+                        while i < 2 { X(q); set i += 1; }
+                    }
+                    operation Narrow(q : Qubit) : Unit is Adj { X(q); }
+                    operation Main() : Unit {
+                        use q = Qubit();
+                        Adjoint Flip(q);
+                        Controlled Flip([q], q);
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = compile_with_options(
+        &PackageStore::new(super::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions {
+            infer_functors: true,
+            ..CompileOptions::default()
+        },
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let functors = unit
+        .package
+        .items
+        .values()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Callable(decl) => Some(format!("{}: {}", decl.name.name, decl.functors)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        functors,
+        vec![
+            "X: Adj + Ctl",
+            "M: empty set",
+            "Flip: Adj + Ctl",
+            "Measure: empty set",
+            "Loop: Ctl",
+            "Narrow: Adj",
+            "Main: Adj + Ctl",
+        ]
+    );
+}
+
+fn compile_cached(cache: &mut crate::resolve::Cache, sources: &[(&str, &str)]) -> CompileUnit {
+    compile_with_cache(
+        &PackageStore::new(super::core()),
+        &[],
+        SourceMap::new(
+            sources
+                .iter()
+                .map(|&(name, contents)| (name.into(), contents.into())),
+            None,
+        ),
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &CompileOptions::default(),
+        cache,
+    )
+}
+
+#[test]
+fn cached_resolution_matches_full_resolution_after_edit() {
+    let a = indoc! {"
+        namespace A {
+            function Foo(x : Int) : Int { x }
+        }
+    "};
+    let b = indoc! {"
+        namespace B {
+            open A;
+            function Bar() : Int {
+                function Local() : Int { Foo(1) }
+                let y = Local();
+                y
+            }
+        }
+    "};
+    let edited_a = indoc! {"
+        namespace A {
+            function Foo(x : Int) : Int {
+                let z = x;
+                z
+            }
+        }
+    "};
+
+    let mut cache = crate::resolve::Cache::default();
+    let unit = compile_cached(&mut cache, &[("a", a), ("b", b)]);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let cached = compile_cached(&mut cache, &[("a", edited_a), ("b", b)]);
+    assert!(cached.errors.is_empty(), "{:#?}", cached.errors);
+    let full = default_compile(SourceMap::new(
+        [("a".into(), edited_a.into()), ("b".into(), b.into())],
+        None,
+    ));
+    assert_eq!(
+        format!("{:?}", cached.ast.names),
+        format!("{:?}", full.ast.names)
+    );
+    assert_eq!(cached.package.to_string(), full.package.to_string());
+}
+
+#[test]
+fn cached_resolution_is_invalidated_by_removed_dependency() {
+    let a = "namespace A { function Foo() : Unit {} }";
+    let b = "namespace B { open A; function Bar() : Unit { Foo(); } }";
+
+    let mut cache = crate::resolve::Cache::default();
+    let unit = compile_cached(&mut cache, &[("a", a), ("b", b)]);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let unit = compile_cached(
+        &mut cache,
+        &[("a", "namespace A { function Baz() : Unit {} }"), ("b", b)],
+    );
+    let errors = unit
+        .errors
+        .iter()
+        .filter_map(|error| match &error.0 {
+            super::ErrorKind::Resolve(error) => Some(error.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(errors, vec!["`Foo` not found".to_string()]);
+}
+
+struct TestLoader {
+    sources: Vec<(&'static str, &'static str, Vec<&'static str>)>,
+    loads: Vec<String>,
+}
+
+impl SourceLoader for TestLoader {
+    fn load(&mut self, name: &str) -> Option<SourceContents> {
+        self.loads.push(name.to_string());
+        self.sources
+            .iter()
+            .find(|(source, _, _)| *source == name)
+            .map(|(_, contents, _)| (*contents).into())
+    }
+
+    fn references(&mut self, name: &str, _: &str) -> Vec<SourceName> {
+        self.sources
+            .iter()
+            .find(|(source, _, _)| *source == name)
+            .map(|(_, _, references)| references.iter().map(|&r| r.into()).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[test]
+fn loader_loads_referenced_sources_once() {
+    let mut loader = TestLoader {
+        sources: vec![
+            (
+                "main.qs",
+                "namespace Main { function F() : Int { Lib.G() } }",
+                vec!["lib.qs", "util.qs"],
+            ),
+            (
+                "lib.qs",
+                "namespace Lib { function G() : Int { Util.H() } }",
+                vec!["util.qs", "main.qs"],
+            ),
+            (
+                "util.qs",
+                "namespace Util { function H() : Int { 1 } }",
+                vec![],
+            ),
+            ("unused.qs", "namespace Unused {}", vec![]),
+        ],
+        loads: Vec::new(),
+    };
+
+    let sources = loader::load(&mut loader, "main.qs", None).expect("sources should load");
+    assert_eq!(loader.loads, vec!["main.qs", "lib.qs", "util.qs"]);
+    let unit = default_compile(sources);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+}
+
+#[test]
+fn loader_reports_missing_reference() {
+    let mut loader = TestLoader {
+        sources: vec![("main.qs", "namespace Main {}", vec!["missing.qs"])],
+        loads: Vec::new(),
+    };
+
+    let error = loader::load(&mut loader, "main.qs", None)
+        .expect_err("missing reference should fail to load");
+    expect![[r#"
+        ReferenceNotFound(
+            "main.qs",
+            "missing.qs",
+        )
+    "#]]
+    .assert_debug_eq(&error);
+}
+
+#[test]
+fn memory_loader_loads_every_source() {
+    let mut loader = MemoryLoader::new([
+        (
+            "a.qs".into(),
+            "namespace A { function F() : Unit { B.G(); } }".into(),
+        ),
+        (
+            "b.qs".into(),
+            "namespace B { function G() : Unit {} }".into(),
+        ),
+    ]);
+
+    let sources = loader::load(&mut loader, "a.qs", None).expect("sources should load");
+    let unit = default_compile(sources);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+}
+
+#[test]
+fn cancelled_compilation_reports_only_cancellation() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace Test { function Foo() : Int { Bar() } }".into(),
+        )],
+        None,
+    );
+    let options = CompileOptions::default();
+    let token = options.cancellation.clone();
+    token.cancel();
+    let unit = compile_with_options(
+        &PackageStore::new(super::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &options,
+    );
+
+    assert_eq!(unit.errors.len(), 1);
+    assert!(unit.errors[0].is_cancelled());
+    assert!(unit.package.items.values().next().is_none());
+    assert_eq!(unit.sources.iter().count(), 1);
+}
+
+#[test]
+fn saved_artifact_loads_same_package() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace Test { function Foo(x : Int) : Int { x + 1 } }".into(),
+        )],
+        None,
+    );
+    let store = PackageStore::new(super::core());
+    let unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let bytes = artifact::save(&unit, RuntimeCapabilityFlags::all());
+    let loaded =
+        artifact::load(&bytes, RuntimeCapabilityFlags::all()).expect("artifact should load");
+    assert_eq!(loaded.package.to_string(), unit.package.to_string());
+    let source = loaded
+        .sources
+        .find_by_name("test")
+        .expect("source should be loaded");
+    assert_eq!(source.offset, 0);
+
+    assert!(matches!(
+        artifact::load(&bytes, RuntimeCapabilityFlags::empty()),
+        Err(artifact::Error::Capabilities)
+    ));
+    assert!(matches!(
+        artifact::load(&bytes[..bytes.len() / 2], RuntimeCapabilityFlags::all()),
+        Err(artifact::Error::Format)
+    ));
+}
+
+#[test]
+fn allow_attribute_suppresses_warning_in_item() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {r#"
+                namespace Foo {
+                    @Deprecated()
+                    function A() : Unit {}
+                    @Allow("Qsc.Resolve.Deprecated")
+                    function B() : Unit { A(); }
+                    function C() : Unit { A(); }
+                }
+            "#}
+            .into(),
+        )],
+        None,
+    );
+    let unit = default_compile(sources);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    let codes: Vec<_> = unit.warnings.iter().map(Warning::code_str).collect();
+    assert_eq!(codes, vec!["Qsc.Resolve.Deprecated"]);
+    let source = unit
+        .sources
+        .find_by_name("test")
+        .expect("source should exist");
+    let labels = unit.warnings[0]
+        .labels()
+        .expect("warning should have labels")
+        .next()
+        .expect("warning should have a label");
+    assert!(source.contents[..labels.offset()].ends_with("function C() : Unit { "));
+}
+
+#[test]
+fn allow_attribute_with_non_string_arg_error() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Foo {
+                    @Allow(1)
+                    function A() : Unit {}
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = default_compile(sources);
+    expect![[r#"
+        [
+            Error(
+                Lower(
+                    InvalidAttrArgs(
+                        "a warning code string",
+                        Span {
+                            lo: 26,
+                            hi: 29,
+                        },
+                    ),
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&unit.errors);
+}
+
+#[test]
+fn warning_levels_allow_and_deny_warnings() {
+    let source = indoc! {"
+        namespace Bar {
+            function C() : Unit {}
+        }
+        namespace Foo {
+            open Bar;
+            @Deprecated()
+            function A() : Unit {}
+            function B() : Unit { A(); }
+        }
+    "};
+    let compile_with_levels = |levels: &[(&str, Severity)]| {
+        let options = CompileOptions {
+            warnings: levels.iter().copied().collect(),
+            ..CompileOptions::default()
+        };
+        compile_with_options(
+            &PackageStore::new(super::core()),
+            &[],
+            SourceMap::new([("test".into(), source.into())], None),
+            RuntimeCapabilityFlags::all(),
+            LanguageFeatures::default(),
+            &options,
+        )
+    };
+
+    let unit = compile_with_levels(&[]);
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    assert_eq!(unit.warnings.len(), 2);
+
+    let unit = compile_with_levels(&[
+        ("Qsc.Resolve.UnusedOpen", Severity::Allow),
+        ("Qsc.Resolve.Deprecated", Severity::Deny),
+    ]);
+    assert!(unit.warnings.is_empty(), "{:#?}", unit.warnings);
+    assert_eq!(unit.errors.len(), 1);
+    let error = &unit.errors[0];
+    assert_eq!(
+        error.code().map(|code| code.to_string()).as_deref(),
+        Some("Qsc.Resolve.Deprecated")
+    );
+    assert_eq!(error.severity(), Some(miette::Severity::Error));
+}
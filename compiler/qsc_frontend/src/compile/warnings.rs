@@ -0,0 +1,126 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Reports each warning with the severity that the compilation options give its code, and drops the
+//! warnings that an `@Allow` attribute suppresses on the item they are in.
+
+use super::{Error, ErrorKind, Warning, WarningKind};
+use miette::{Diagnostic, LabeledSpan, Severity, SourceCode};
+use qsc_ast::{
+    ast::{self, ExprKind, Lit},
+    visit::{self, Visitor},
+};
+use qsc_data_structures::{
+    lints::{self, WarningLevels},
+    span::Span,
+};
+use qsc_hir::hir;
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+/// A warning that the compilation options deny, which is reported as an error.
+#[derive(Clone, Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct Denied(Warning);
+
+impl Diagnostic for Denied {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Error)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.help()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.0.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.0.labels()
+    }
+}
+
+impl Warning {
+    /// The code of the warning, like `Qsc.Resolve.UnusedOpen`, which names it in the warning levels
+    /// and in `@Allow` attributes.
+    #[must_use]
+    pub fn code_str(&self) -> String {
+        self.code().map(|code| code.to_string()).unwrap_or_default()
+    }
+
+    fn span(&self) -> Span {
+        match &self.0 {
+            WarningKind::Resolve(warning) => warning.span(),
+            WarningKind::Lint(lint) => lint.span,
+        }
+    }
+}
+
+/// Drops the warnings that are allowed, either by the warning levels or by an `@Allow` attribute on
+/// an item that contains them, and turns the denied warnings into errors.
+pub(super) fn apply_levels(
+    package: &ast::Package,
+    warnings: Vec<Warning>,
+    levels: &WarningLevels,
+) -> (Vec<Warning>, Vec<Error>) {
+    let mut allows = Allows(Vec::new());
+    allows.visit_package(package);
+
+    let mut reported = Vec::new();
+    let mut denied = Vec::new();
+    for warning in warnings {
+        let code = warning.code_str();
+        let span = warning.span();
+        let allowed = allows
+            .0
+            .iter()
+            .any(|(item, allowed)| **allowed == *code && item.lo <= span.lo && span.hi <= item.hi);
+        let severity = if allowed {
+            lints::Severity::Allow
+        } else {
+            levels.get(&code)
+        };
+        match severity {
+            lints::Severity::Allow => {}
+            lints::Severity::Warn => reported.push(warning),
+            lints::Severity::Deny => denied.push(Error(ErrorKind::Denied(Denied(warning)))),
+        }
+    }
+
+    (reported, denied)
+}
+
+/// The code that an `@Allow` attribute suppresses, which is its only argument, a string.
+pub(crate) fn allowed_code(attr: &ast::Attr) -> Option<Arc<str>> {
+    match &*attr.arg.kind {
+        ExprKind::Paren(inner) => match &*inner.kind {
+            ExprKind::Lit(lit) => match &**lit {
+                Lit::String(code) => Some(Arc::clone(code)),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The span of each item with an `@Allow` attribute and the code that the attribute suppresses.
+struct Allows(Vec<(Span, Arc<str>)>);
+
+impl<'a> Visitor<'a> for Allows {
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        for attr in &*item.attrs {
+            if hir::Attr::from_str(attr.name.name.as_ref()) == Ok(hir::Attr::Allow) {
+                if let Some(code) = allowed_code(attr) {
+                    self.0.push((item.span, code));
+                }
+            }
+        }
+
+        visit::walk_item(self, item);
+    }
+}
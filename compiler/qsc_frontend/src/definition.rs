@@ -0,0 +1,195 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compile::{CompileUnit, PackageStore},
+    resolve::{self, Res},
+};
+use qsc_ast::{
+    ast::{self, ExprKind, Ident, NodeId, PatKind, TernOp},
+    visit::{self as ast_visit, Visitor as AstVisitor},
+};
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    hir::{self, ItemId, ItemStatus, PackageId},
+    ty::Ty,
+};
+use std::rc::Rc;
+
+/// The definition of the name at an offset.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Definition {
+    /// What the name refers to. For a field, this is the UDT that declares the field.
+    pub res: Res,
+    /// The field name, if the name is a field of a UDT.
+    pub field: Option<Rc<str>>,
+    /// The package that contains the definition.
+    pub package: PackageId,
+    /// The span of the defining name, relative to the sources of `package`.
+    pub span: Span,
+}
+
+/// Finds the definition of the name at an offset in a package's sources. The name can be part of a
+/// path, a declaration, a type parameter, or a field access or field update.
+///
+/// Returns `None` if there is no name at the offset, or if the name has no definition in source,
+/// such as a primitive type.
+#[must_use]
+pub fn at_offset(store: &PackageStore, package: PackageId, offset: u32) -> Option<Definition> {
+    let unit = store.get(package)?;
+    let mut finder = Finder {
+        offset,
+        unit,
+        callable: None,
+        found: None,
+    };
+    finder.visit_package(&unit.ast.package);
+    let (res, field, callable) = finder.found?;
+
+    match (res, field) {
+        (Res::Item(id, _), Some(field)) => {
+            let (item_package, item) = hir_item(store, package, id)?;
+            let hir::ItemKind::Ty(_, udt) = &item.kind else {
+                return None;
+            };
+            let span = udt.find_field_by_name(&field)?.name_span?;
+            Some(Definition {
+                res,
+                field: Some(field),
+                package: item_package,
+                span,
+            })
+        }
+        (Res::Item(id, _), None) => {
+            let (item_package, item) = hir_item(store, package, id)?;
+            let span = match &item.kind {
+                hir::ItemKind::Callable(decl) => decl.name.span,
+                hir::ItemKind::Namespace(name, _) | hir::ItemKind::Ty(name, _) => name.span,
+            };
+            Some(Definition {
+                res,
+                field: None,
+                package: item_package,
+                span,
+            })
+        }
+        (Res::Local(id), _) => Some(Definition {
+            res,
+            field: None,
+            package,
+            span: local_decl(&unit.ast.package, id)?.span,
+        }),
+        (Res::Param(id), _) => Some(Definition {
+            res,
+            field: None,
+            package,
+            span: callable?.generics.get(usize::from(id))?.ty.span,
+        }),
+        (Res::PrimTy(_) | Res::UnitTy, _) => None,
+    }
+}
+
+/// Finds the identifier that declares a local variable or parameter.
+pub(crate) fn local_decl(package: &ast::Package, id: NodeId) -> Option<&Ident> {
+    struct BindingFinder<'a> {
+        id: NodeId,
+        ident: Option<&'a Ident>,
+    }
+
+    impl<'a> AstVisitor<'a> for BindingFinder<'a> {
+        fn visit_pat(&mut self, pat: &'a ast::Pat) {
+            match &*pat.kind {
+                PatKind::Bind(name, _) if name.id == self.id => self.ident = Some(name),
+                _ => ast_visit::walk_pat(self, pat),
+            }
+        }
+    }
+
+    let mut finder = BindingFinder { id, ident: None };
+    finder.visit_package(package);
+    finder.ident
+}
+
+fn hir_item(
+    store: &PackageStore,
+    package: PackageId,
+    id: ItemId,
+) -> Option<(PackageId, &hir::Item)> {
+    let package = id.package.unwrap_or(package);
+    let item = store.get(package)?.package.items.get(id.item)?;
+    Some((package, item))
+}
+
+fn touches(span: Span, offset: u32) -> bool {
+    span.lo <= offset && offset <= span.hi
+}
+
+type Found<'a> = (Res, Option<Rc<str>>, Option<&'a ast::CallableDecl>);
+
+struct Finder<'a> {
+    offset: u32,
+    unit: &'a CompileUnit,
+    callable: Option<&'a ast::CallableDecl>,
+    found: Option<Found<'a>>,
+}
+
+impl Finder<'_> {
+    /// Records the field of the UDT that is the type of `record`.
+    fn found_field(&mut self, record: &ast::Expr, field: &str) {
+        if let Some(Ty::Udt(_, hir::Res::Item(id))) = self.unit.ast.tys.terms.get(record.id) {
+            let res = Res::Item(*id, ItemStatus::Available);
+            self.found = Some((res, Some(Rc::from(field)), self.callable));
+        }
+    }
+}
+
+impl<'a> AstVisitor<'a> for Finder<'a> {
+    fn visit_callable_decl(&mut self, decl: &'a ast::CallableDecl) {
+        if touches(decl.span, self.offset) {
+            let outer = self.callable.replace(decl);
+            ast_visit::walk_callable_decl(self, decl);
+            self.callable = outer;
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if !touches(expr.span, self.offset) {
+            return;
+        }
+
+        match &*expr.kind {
+            ExprKind::Field(record, name) if touches(name.span, self.offset) => {
+                self.found_field(record, &name.name);
+            }
+            ExprKind::AssignUpdate(container, index, _)
+            | ExprKind::TernOp(TernOp::Update, container, index, _)
+                if touches(index.span, self.offset) =>
+            {
+                match resolve::extract_field_name(&self.unit.ast.names, index) {
+                    Some(field) => self.found_field(container, field),
+                    None => ast_visit::walk_expr(self, expr),
+                }
+            }
+            _ => ast_visit::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_path(&mut self, path: &'a ast::Path) {
+        if touches(path.span, self.offset) {
+            if let Some(&res) = self.unit.ast.names.get(path.id) {
+                self.found = Some((res, None, self.callable));
+            }
+        }
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident) {
+        if touches(ident.span, self.offset) {
+            if let Some(&res) = self.unit.ast.names.get(ident.id) {
+                self.found = Some((res, None, self.callable));
+            }
+        }
+    }
+}
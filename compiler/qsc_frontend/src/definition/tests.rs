@@ -0,0 +1,129 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{at_offset, Definition};
+use crate::{
+    compile::{self, PackageStore, RuntimeCapabilityFlags, SourceMap},
+    resolve::Res,
+};
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use qsc_hir::hir::{ItemId, PackageId};
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        newtype Pair = (First : Int, Second : Int);
+        function Swap<'T>(x : 'T) : 'T { x }
+        function Main() : Int {
+            let p = Pair(1, 2);
+            let q = p w/ First <- 3;
+            q::Second + Swap(p::First)
+        }
+    }
+"};
+
+fn compile_into(store: &mut PackageStore, deps: &[PackageId], source: &str) -> PackageId {
+    let deps = deps.iter().map(|&id| (id, None)).collect::<Vec<_>>();
+    let unit = compile::compile(
+        store,
+        &deps,
+        SourceMap::new([("test".into(), source.into())], None),
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    store.insert(unit)
+}
+
+fn definition_span(offset: u32) -> Option<(Option<String>, Span)> {
+    let mut store = PackageStore::new(compile::core());
+    let package = compile_into(&mut store, &[], SOURCE);
+    at_offset(&store, package, offset).map(|definition| {
+        assert_eq!(definition.package, package);
+        (
+            definition.field.map(|field| field.to_string()),
+            definition.span,
+        )
+    })
+}
+
+#[test]
+fn udt_constructor() {
+    assert_eq!(definition_span(150), Some((None, Span { lo: 29, hi: 33 })));
+}
+
+#[test]
+fn field_access() {
+    assert_eq!(
+        definition_span(206),
+        Some((Some("Second".to_string()), Span { lo: 50, hi: 56 }))
+    );
+    assert_eq!(
+        definition_span(223),
+        Some((Some("First".to_string()), Span { lo: 37, hi: 42 }))
+    );
+}
+
+#[test]
+fn field_update() {
+    assert_eq!(
+        definition_span(183),
+        Some((Some("First".to_string()), Span { lo: 37, hi: 42 }))
+    );
+}
+
+#[test]
+fn local() {
+    assert_eq!(
+        definition_span(203),
+        Some((None, Span { lo: 174, hi: 175 }))
+    );
+    assert_eq!(definition_span(102), Some((None, Span { lo: 87, hi: 88 })));
+}
+
+#[test]
+fn type_parameter() {
+    assert_eq!(definition_span(91), Some((None, Span { lo: 83, hi: 85 })));
+}
+
+#[test]
+fn primitive_type_has_no_definition() {
+    let offset = SOURCE
+        .find("Int {")
+        .expect("source should have return type");
+    assert_eq!(
+        definition_span(offset.try_into().expect("offset should fit into u32")),
+        None
+    );
+}
+
+#[test]
+fn item_in_dependency() {
+    let mut store = PackageStore::new(compile::core());
+    let lib = compile_into(
+        &mut store,
+        &[],
+        "namespace Foo { function Bar() : Unit {} }",
+    );
+    let package = compile_into(
+        &mut store,
+        &[lib],
+        "namespace Test { function Main() : Unit { Foo.Bar(); } }",
+    );
+    let definition = at_offset(&store, package, 46).expect("name should have a definition");
+    assert_eq!(
+        definition,
+        Definition {
+            res: Res::Item(
+                ItemId {
+                    package: Some(lib),
+                    item: 1.into(),
+                },
+                qsc_hir::hir::ItemStatus::Available,
+            ),
+            field: None,
+            package: lib,
+            span: Span { lo: 25, hi: 28 },
+        }
+    );
+}
@@ -6,8 +6,8 @@ mod tests;
 
 use crate::{
     compile::{
-        self, preprocess, AstPackage, CompileUnit, Offsetter, PackageStore, RuntimeCapabilityFlags,
-        SourceMap,
+        self, preprocess, AstPackage, CompileOptions, CompileUnit, Offsetter, PackageStore,
+        RuntimeCapabilityFlags, SourceMap, SourceName,
     },
     error::WithSource,
     lower::Lowerer,
@@ -21,14 +21,16 @@ use qsc_ast::{
     validate::Validator as AstValidator,
     visit::Visitor as AstVisitor,
 };
-use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_data_structures::{
+    cancellation::CancellationToken, language_features::LanguageFeatures, span::Span,
+};
 use qsc_hir::{
     assigner::Assigner as HirAssigner,
     hir::{self, PackageId},
     validate::Validator as HirValidator,
     visit::Visitor as HirVisitor,
 };
-use std::mem::take;
+use std::{mem::take, rc::Rc, sync::Arc};
 
 /// The frontend for an incremental compiler.
 /// It is used to update a single `CompileUnit`
@@ -39,6 +41,8 @@ pub struct Compiler {
     checker: Checker,
     lowerer: Lowerer,
     capabilities: RuntimeCapabilityFlags,
+    defines: Vec<Arc<str>>,
+    cancellation: CancellationToken,
     language_features: LanguageFeatures,
 }
 
@@ -51,13 +55,52 @@ pub type Error = WithSource<compile::Error>;
 pub struct Increment {
     pub ast: AstPackage,
     pub hir: hir::Package,
+    /// The callables and types of earlier fragments that these fragments redefined.
+    pub redefinitions: Vec<Redefinition>,
+}
+
+/// A notice that a fragment redefined a callable or type of an earlier fragment. The old item is
+/// tombstoned: names in this and later fragments refer to the new item, while the items of earlier
+/// fragments that used the old item keep using it until they are redefined as well.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Redefinition {
+    /// The name of the item, qualified with its namespace if it has one.
+    pub name: Rc<str>,
+    /// The span of the new declaration's name.
+    pub span: Span,
+    /// The items of earlier fragments that still refer to the old item, in the order they were
+    /// declared.
+    pub stale_dependents: Vec<Rc<str>>,
+}
+
+/// A callable, type, or open that the fragments of an incremental session defined, and that is
+/// still in effect.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionItem {
+    pub kind: SessionItemKind,
+    /// The name of the item, qualified with its namespace if it has one. For an open, the name of
+    /// the opened namespace.
+    pub name: Rc<str>,
+    /// The declaration as written, without the body of a callable and with whitespace collapsed.
+    pub signature: String,
+    /// The name of the fragment source that defined the item.
+    pub source: SourceName,
+    /// The span of the item, relative to the start of its source.
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SessionItemKind {
+    Callable,
+    Ty,
+    Open,
 }
 
 impl Compiler {
     /// Creates a new compiler.
     pub fn new(
         store: &PackageStore,
-        dependencies: impl IntoIterator<Item = PackageId>,
+        dependencies: impl IntoIterator<Item = (PackageId, Option<Arc<str>>)>,
         capabilities: RuntimeCapabilityFlags,
         language_features: LanguageFeatures,
     ) -> Self {
@@ -65,16 +108,16 @@ impl Compiler {
         let mut typeck_globals = typeck::GlobalTable::new();
         let mut dropped_names = Vec::new();
         if let Some(unit) = store.get(PackageId::CORE) {
-            resolve_globals.add_external_package(PackageId::CORE, &unit.package);
+            resolve_globals.add_external_package(PackageId::CORE, &unit.package, None);
             typeck_globals.add_external_package(PackageId::CORE, &unit.package);
             dropped_names.extend(unit.dropped_names.iter().cloned());
         }
 
-        for id in dependencies {
+        for (id, alias) in dependencies {
             let unit = store
                 .get(id)
                 .expect("dependency should be added to package store before compilation");
-            resolve_globals.add_external_package(id, &unit.package);
+            resolve_globals.add_external_package(id, &unit.package, alias.as_ref());
             typeck_globals.add_external_package(id, &unit.package);
             dropped_names.extend(unit.dropped_names.iter().cloned());
         }
@@ -85,10 +128,19 @@ impl Compiler {
             checker: Checker::new(typeck_globals),
             lowerer: Lowerer::new(),
             capabilities,
+            defines: Vec::new(),
+            cancellation: CancellationToken::default(),
             language_features,
         }
     }
 
+    /// Applies the options to subsequent fragments, replacing the default options.
+    pub fn set_options(&mut self, options: &CompileOptions) {
+        self.resolver.set_options(options);
+        self.defines.clone_from(&options.defines);
+        self.cancellation = options.cancellation.clone();
+    }
+
     /// Compiles Q# fragments.
     ///
     /// Uses the assigners and other mutable state from the passed in
@@ -128,14 +180,19 @@ impl Compiler {
 
         accumulate_errors(errors)?;
 
+        let redefinitions = self.redefinitions(&unit.ast.package);
         Ok(Increment {
             ast: AstPackage {
                 package: ast,
                 names: self.resolver.names().clone(),
                 locals: self.resolver.locals().clone(),
+                explanations: self.resolver.explanations(),
+                references: self.resolver.references().clone(),
+                globals: self.resolver.globals().clone(),
                 tys: self.checker.table().clone(),
             },
             hir,
+            redefinitions,
         })
     }
 
@@ -177,9 +234,13 @@ impl Compiler {
                 package: ast,
                 names: self.resolver.names().clone(),
                 locals: self.resolver.locals().clone(),
+                explanations: self.resolver.explanations(),
+                references: self.resolver.references().clone(),
+                globals: self.resolver.globals().clone(),
                 tys: self.checker.table().clone(),
             },
             hir,
+            redefinitions: Vec::new(),
         })
     }
 
@@ -193,6 +254,9 @@ impl Compiler {
         unit.ast.names = new.ast.names;
         unit.ast.tys = new.ast.tys;
         unit.ast.locals = new.ast.locals;
+        unit.ast.explanations = new.ast.explanations;
+        unit.ast.references = new.ast.references;
+        unit.ast.globals = new.ast.globals;
 
         // Update the HIR
         extend_hir(&mut unit.package, new.hir);
@@ -203,7 +267,7 @@ impl Compiler {
         unit: &mut CompileUnit,
         ast: &mut ast::Package,
     ) -> (hir::Package, Vec<Error>) {
-        let mut cond_compile = preprocess::Conditional::new(self.capabilities);
+        let mut cond_compile = preprocess::Conditional::new(self.capabilities, &self.defines);
         cond_compile.visit_package(ast);
 
         self.ast_assigner.visit_package(ast);
@@ -212,6 +276,11 @@ impl Compiler {
             .extend_dropped_names(cond_compile.into_names());
         self.resolver.bind_fragments(ast, &mut unit.assigner);
         self.resolver.with(&mut unit.assigner).visit_package(ast);
+        if self.cancellation.is_cancelled() {
+            self.lowerer.clear_items();
+            let error = WithSource::from_map(&unit.sources, compile::Error::cancelled());
+            return (hir::Package::default(), vec![error]);
+        }
 
         self.checker.check_package(self.resolver.names(), ast);
         self.checker.solve(self.resolver.names());
@@ -220,7 +289,8 @@ impl Compiler {
 
         let errors = self
             .resolver
-            .drain_errors()
+            .drain_errors(&unit.sources)
+            .into_iter()
             .map(|e| compile::Error(e.into()))
             .chain(
                 self.checker
@@ -242,6 +312,74 @@ impl Compiler {
         (package, errors)
     }
 
+    /// Takes the items that the last fragments redefined, finding the items of the earlier
+    /// fragments in the package that still refer to each old item.
+    fn redefinitions(&mut self, package: &ast::Package) -> Vec<Redefinition> {
+        let redefinitions = self.resolver.drain_redefinitions();
+        if redefinitions.is_empty() {
+            return Vec::new();
+        }
+
+        let names = self.resolver.names();
+        let globals = self.resolver.globals();
+        let items = package
+            .nodes
+            .iter()
+            .flat_map(|node| match node {
+                TopLevelNode::Namespace(namespace) => {
+                    let namespace_name = namespace.full_name();
+                    namespace
+                        .items
+                        .iter()
+                        .map(|item| (Some(Arc::clone(&namespace_name)), &**item))
+                        .collect()
+                }
+                TopLevelNode::Stmt(stmt) => match &*stmt.kind {
+                    ast::StmtKind::Item(item) => vec![(None, &**item)],
+                    _ => Vec::new(),
+                },
+            })
+            .filter_map(|(namespace, item)| {
+                let name = match &*item.kind {
+                    ast::ItemKind::Callable(decl) => &decl.name,
+                    ast::ItemKind::Ty(name, _) => name,
+                    _ => return None,
+                };
+                match names.get(name.id) {
+                    Some(&resolve::Res::Item(id, _)) if !globals.is_tombstoned(id) => {
+                        let name = match namespace {
+                            Some(namespace) => format!("{namespace}.{}", name.name).into(),
+                            None => Rc::from(&*name.name),
+                        };
+                        Some((name, item.span))
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let references = self.resolver.references();
+        redefinitions
+            .into_iter()
+            .map(|redefinition| {
+                let spans = references.item(redefinition.old);
+                Redefinition {
+                    name: Rc::from(&*redefinition.name),
+                    span: redefinition.span,
+                    stale_dependents: items
+                        .iter()
+                        .filter(|(_, item)| {
+                            spans
+                                .iter()
+                                .any(|span| item.lo <= span.lo && span.hi <= item.hi)
+                        })
+                        .map(|(name, _)| Rc::clone(name))
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
     /// Creates a new `Package` by combining two packages.
     /// The two packages should not contain any conflicting `NodeId`s.
     /// Entry expressions are ignored.
@@ -322,6 +460,101 @@ impl Compiler {
     }
 }
 
+/// Lists the items that the fragments compiled into the unit defined, in the order they were
+/// defined. Items that a later fragment redefined are left out, and so are opens inside a namespace,
+/// which apply only to that namespace.
+#[must_use]
+pub fn session_items(unit: &CompileUnit) -> Vec<SessionItem> {
+    let mut items = Vec::new();
+    for node in &*unit.ast.package.nodes {
+        match node {
+            TopLevelNode::Namespace(namespace) => {
+                let namespace_name = namespace.full_name();
+                items.extend(
+                    namespace
+                        .items
+                        .iter()
+                        .filter_map(|item| session_item(unit, Some(&namespace_name), item)),
+                );
+            }
+            TopLevelNode::Stmt(stmt) => {
+                if let ast::StmtKind::Item(item) = &*stmt.kind {
+                    items.extend(session_item(unit, None, item));
+                }
+            }
+        }
+    }
+    items
+}
+
+fn session_item(
+    unit: &CompileUnit,
+    namespace: Option<&Arc<str>>,
+    item: &ast::Item,
+) -> Option<SessionItem> {
+    let (kind, name, span) = match &*item.kind {
+        ast::ItemKind::Callable(decl) => {
+            let hi = decl
+                .functors
+                .as_ref()
+                .map_or(decl.output.span.hi, |functors| functors.span.hi);
+            let span = Span {
+                lo: decl.span.lo,
+                hi,
+            };
+            (SessionItemKind::Callable, &decl.name, span)
+        }
+        ast::ItemKind::Ty(name, def) => {
+            let span = Span {
+                lo: name.span.lo,
+                hi: def.span.hi,
+            };
+            (SessionItemKind::Ty, name, span)
+        }
+        ast::ItemKind::Open(name, alias) if namespace.is_none() => {
+            let hi = alias.as_ref().map_or(name.span.hi, |alias| alias.span.hi);
+            let span = Span {
+                lo: name.span.lo,
+                hi,
+            };
+            (SessionItemKind::Open, name, span)
+        }
+        _ => return None,
+    };
+
+    if kind != SessionItemKind::Open {
+        match unit.ast.names.get(name.id) {
+            Some(&resolve::Res::Item(id, _)) if !unit.ast.globals.is_tombstoned(id) => {}
+            _ => return None,
+        }
+    }
+
+    let source = unit
+        .sources
+        .find_by_offset(item.span.lo)
+        .expect("item should be in a source");
+    let text = source.contents[span - source.offset]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let signature = match kind {
+        SessionItemKind::Callable => text,
+        SessionItemKind::Ty => format!("newtype {text}"),
+        SessionItemKind::Open => format!("open {text}"),
+    };
+
+    Some(SessionItem {
+        kind,
+        name: match namespace {
+            Some(namespace) => format!("{namespace}.{}", name.name).into(),
+            None => Rc::from(&*name.name),
+        },
+        signature,
+        source: Arc::clone(&source.name),
+        span: item.span - source.offset,
+    })
+}
+
 /// Extends the `Package` with the contents of another `Package`.
 /// `other` should not contain any `LocalItemId`s
 /// that conflict with the current `Package`.
@@ -5,10 +5,12 @@ use super::{Compiler, Increment};
 use crate::{
     compile::{self, CompileUnit, PackageStore, RuntimeCapabilityFlags},
     incremental::Error,
+    resolve::Res,
 };
 use expect_test::{expect, Expect};
 use indoc::indoc;
 use miette::Diagnostic;
+use qsc_ast::ast::{ExprKind, ItemKind, StmtKind, TopLevelNode};
 use qsc_data_structures::language_features::LanguageFeatures;
 use std::fmt::Write;
 
@@ -164,12 +166,14 @@ fn one_statement() {
                         tys: {},
                         terms: {},
                         vars: {
-                            "q": (
-                                16,
-                                NodeId(
-                                    3,
+                            "q": [
+                                (
+                                    16,
+                                    NodeId(
+                                        3,
+                                    ),
                                 ),
-                            ),
+                            ],
                         },
                         ty_vars: {},
                     },
@@ -264,7 +268,7 @@ fn errors_across_multiple_lines() {
     let std_id = store.insert(std);
     let mut compiler = Compiler::new(
         &store,
-        [std_id],
+        [(std_id, None)],
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
     );
@@ -456,6 +460,155 @@ fn continue_after_lower_error() {
     "#]].assert_debug_eq(&errors);
 }
 
+#[test]
+fn redefined_callable_binds_new_references() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+
+    let increment = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_1",
+            "namespace Foo { function A() : Int { 1 } function B() : Int { A() } }",
+            fail_on_error,
+        )
+        .expect("compilation should succeed");
+    let old = declared_res(&increment);
+    assert!(increment.redefinitions.is_empty());
+    compiler.update(&mut unit, increment);
+
+    let increment = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_2",
+            "namespace Foo { function A() : Int { 2 } }",
+            fail_on_error,
+        )
+        .expect("redefinition should succeed");
+    let new = declared_res(&increment);
+    assert_ne!(old, new);
+    assert_eq!(redefinitions(&increment), vec![("Foo.A", vec!["Foo.B"])]);
+    compiler.update(&mut unit, increment);
+
+    let increment = compiler
+        .compile_expr(&mut unit, "test_3", "Foo.A()")
+        .expect("compilation should succeed");
+    let TopLevelNode::Stmt(stmt) = &increment.ast.package.nodes[0] else {
+        panic!("expression should be a statement");
+    };
+    let StmtKind::Expr(expr) = &*stmt.kind else {
+        panic!("statement should be an expression");
+    };
+    let ExprKind::Call(callee, _) = &*expr.kind else {
+        panic!("expression should be a call");
+    };
+    let ExprKind::Path(path) = &*callee.kind else {
+        panic!("callee should be a path");
+    };
+    assert_eq!(increment.ast.names.get(path.id), Some(&new));
+}
+
+#[test]
+fn redefined_top_level_items_list_live_dependents() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+
+    for (name, source, expected) in [
+        (
+            "test_1",
+            "newtype A = Int; function B() : A { A(1) } function C() : Int { 0 }",
+            vec![],
+        ),
+        ("test_2", "function A() : Int { 2 }", vec![("A", vec!["B"])]),
+        ("test_3", "function B() : Int { A() }", vec![("B", vec![])]),
+        ("test_4", "function A() : Int { 3 }", vec![("A", vec!["B"])]),
+    ] {
+        let increment = compiler
+            .compile_fragments(&mut unit, name, source, fail_on_error)
+            .expect("compilation should succeed");
+        assert_eq!(redefinitions(&increment), expected);
+        compiler.update(&mut unit, increment);
+    }
+}
+
+#[test]
+fn duplicate_in_redefining_fragment_is_error() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+
+    let increment = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_1",
+            "namespace Foo { function A() : Unit {} }",
+            fail_on_error,
+        )
+        .expect("compilation should succeed");
+    compiler.update(&mut unit, increment);
+
+    let errors = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_2",
+            "namespace Foo { function A() : Unit {} function A() : Unit {} }",
+            fail_on_error,
+        )
+        .expect_err("duplicate should fail");
+    assert!(errors.iter().any(|error| error
+        .code()
+        .is_some_and(|code| code.to_string() == "Qsc.Resolve.Duplicate")));
+}
+
+/// The resolution of the first callable declared in the first namespace of the increment.
+fn declared_res(increment: &Increment) -> Res {
+    let TopLevelNode::Namespace(namespace) = &increment.ast.package.nodes[0] else {
+        panic!("first node should be a namespace");
+    };
+    let ItemKind::Callable(decl) = &*namespace.items[0].kind else {
+        panic!("first item should be a callable");
+    };
+    *increment
+        .ast
+        .names
+        .get(decl.name.id)
+        .expect("callable name should be resolved")
+}
+
+fn redefinitions(increment: &Increment) -> Vec<(&str, Vec<&str>)> {
+    increment
+        .redefinitions
+        .iter()
+        .map(|redefinition| {
+            (
+                redefinition.name.as_ref(),
+                redefinition
+                    .stale_dependents
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
 fn check_unit(expect: &Expect, actual: &Increment) {
     let ast = format!("ast:\n{}", actual.ast.package);
 
@@ -1,10 +1,15 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod call_graph;
 mod closure;
 pub mod compile;
+pub mod definition;
 pub mod error;
 pub mod incremental;
+pub mod lint;
 mod lower;
+pub mod rename;
 pub mod resolve;
+pub mod type_hints;
 pub mod typeck;
@@ -0,0 +1,119 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Lints check a package after type checking for code that compiles but is likely not what was
+//! meant, or could be written more simply. Each lint is reported as a warning with the code of its
+//! rule, so its severity can be configured like any other warning.
+
+#[cfg(test)]
+mod tests;
+
+mod rules;
+
+use crate::{resolve::Res, typeck::Table};
+use miette::{Diagnostic, LabeledSpan, Severity};
+use qsc_ast::ast::{self, NodeId};
+use qsc_data_structures::{index_map::IndexMap, span::Span};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    iter,
+    sync::Arc,
+};
+use thiserror::Error;
+
+pub use rules::{CouldBeFunction, DoubleAdjoint, NeedlessMut, RedundantParens};
+
+/// What a lint rule can inspect in a type-checked package.
+pub struct Context<'a> {
+    /// The syntax of the package.
+    pub package: &'a ast::Package,
+    /// What each name in the package resolved to, by the node ID of the name.
+    pub names: &'a IndexMap<NodeId, Res>,
+    /// The types of the package.
+    pub tys: &'a Table,
+}
+
+/// A rule that reports lints for a package.
+pub trait Rule: Send + Sync {
+    /// The code of the lints that the rule reports, like `Qsc.Lint.NeedlessMut`.
+    fn code(&self) -> &'static str;
+
+    /// Checks the package, adding a lint for each problem that the rule finds.
+    fn check(&self, cx: &Context, lints: &mut Vec<Lint>);
+}
+
+/// A problem that a lint rule found.
+#[derive(Clone, Debug, Error)]
+#[error("{message}")]
+pub struct Lint {
+    /// The code of the rule that found the problem.
+    pub code: &'static str,
+    pub message: String,
+    pub help: Option<String>,
+    pub span: Span,
+}
+
+impl Diagnostic for Lint {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(self.code))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Warning)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(|help| Box::new(help) as Box<dyn Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(iter::once(LabeledSpan::new_with_span(
+            None, self.span,
+        ))))
+    }
+}
+
+/// The lint rules to run for a compilation.
+#[derive(Clone, Default)]
+pub struct Registry {
+    rules: Vec<Arc<dyn Rule>>,
+}
+
+impl Registry {
+    /// The rules that the compiler ships with.
+    #[must_use]
+    pub fn recommended() -> Self {
+        let mut registry = Self::default();
+        registry.register(NeedlessMut);
+        registry.register(RedundantParens);
+        registry.register(CouldBeFunction);
+        registry.register(DoubleAdjoint);
+        registry
+    }
+
+    /// Adds a rule, which replaces any rule with the same code.
+    pub fn register(&mut self, rule: impl Rule + 'static) {
+        self.rules.retain(|other| other.code() != rule.code());
+        self.rules.push(Arc::new(rule));
+    }
+
+    /// Runs every rule, returning the lints sorted by where they are in the package.
+    pub(crate) fn check(&self, cx: &Context) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        for rule in &self.rules {
+            rule.check(cx, &mut lints);
+        }
+        lints.sort_by_key(|lint| lint.span);
+        lints
+    }
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules.iter().map(|rule| rule.code()))
+            .finish()
+    }
+}
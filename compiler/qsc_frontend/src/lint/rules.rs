@@ -0,0 +1,298 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Context, Lint, Rule};
+use crate::resolve::Res;
+use qsc_ast::{
+    ast::{
+        self, CallableBody, CallableDecl, Expr, ExprKind, Functor, Mutability, NodeId, Pat,
+        PatKind, StmtKind, UnOp,
+    },
+    visit::{self, Visitor},
+};
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    hir::{self, CallableKind},
+    ty::Ty,
+};
+use rustc_hash::FxHashSet;
+use std::{str::FromStr, sync::Arc};
+
+/// A mutable variable that is never updated.
+pub struct NeedlessMut;
+
+impl Rule for NeedlessMut {
+    fn code(&self) -> &'static str {
+        "Qsc.Lint.NeedlessMut"
+    }
+
+    fn check(&self, cx: &Context, lints: &mut Vec<Lint>) {
+        let mut mutables = Mutables {
+            cx,
+            declared: Vec::new(),
+            updated: FxHashSet::default(),
+        };
+        mutables.visit_package(cx.package);
+        for (id, name, span) in mutables.declared {
+            if !mutables.updated.contains(&id) {
+                lints.push(Lint {
+                    code: self.code(),
+                    message: format!("mutable variable `{name}` is never updated"),
+                    help: Some("declare the variable with `let` instead".to_string()),
+                    span,
+                });
+            }
+        }
+    }
+}
+
+struct Mutables<'a, 'b> {
+    cx: &'a Context<'b>,
+    declared: Vec<(NodeId, Arc<str>, Span)>,
+    updated: FxHashSet<NodeId>,
+}
+
+impl Mutables<'_, '_> {
+    fn declare(&mut self, pat: &Pat) {
+        match &*pat.kind {
+            PatKind::Bind(name, _) => {
+                self.declared
+                    .push((name.id, Arc::clone(&name.name), name.span));
+            }
+            PatKind::Paren(pat) => self.declare(pat),
+            PatKind::Tuple(pats) => pats.iter().for_each(|pat| self.declare(pat)),
+            PatKind::Discard(_) | PatKind::Elided | PatKind::Err => {}
+        }
+    }
+
+    fn update(&mut self, lhs: &Expr) {
+        match &*lhs.kind {
+            ExprKind::Path(path) => {
+                if let Some(&Res::Local(id)) = self.cx.names.get(path.id) {
+                    self.updated.insert(id);
+                }
+            }
+            ExprKind::Paren(expr) => self.update(expr),
+            ExprKind::Tuple(exprs) => exprs.iter().for_each(|expr| self.update(expr)),
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Mutables<'_, '_> {
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        if let StmtKind::Local(Mutability::Mutable, pat, _) = &*stmt.kind {
+            self.declare(pat);
+        }
+
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            ExprKind::Assign(lhs, _)
+            | ExprKind::AssignOp(_, lhs, _)
+            | ExprKind::AssignUpdate(lhs, _, _) => self.update(lhs),
+            _ => {}
+        }
+
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// Parentheses around an expression that is already delimited, like the value of a `let` or the
+/// condition of an `if`.
+pub struct RedundantParens;
+
+impl Rule for RedundantParens {
+    fn code(&self) -> &'static str {
+        "Qsc.Lint.RedundantParens"
+    }
+
+    fn check(&self, cx: &Context, lints: &mut Vec<Lint>) {
+        let mut parens = Parens { spans: Vec::new() };
+        parens.visit_package(cx.package);
+        lints.extend(parens.spans.into_iter().map(|span| Lint {
+            code: self.code(),
+            message: "unnecessary parentheses".to_string(),
+            help: Some("remove the parentheses".to_string()),
+            span,
+        }));
+    }
+}
+
+struct Parens {
+    spans: Vec<Span>,
+}
+
+impl Parens {
+    fn check(&mut self, expr: &Expr) {
+        if matches!(&*expr.kind, ExprKind::Paren(_)) {
+            self.spans.push(expr.span);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Parens {
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        if let StmtKind::Local(_, _, value) = &*stmt.kind {
+            self.check(value);
+        }
+
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            ExprKind::If(cond, ..) | ExprKind::While(cond, _) | ExprKind::Paren(cond) => {
+                self.check(cond);
+            }
+            _ => {}
+        }
+
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// An operation that calls no operations and allocates no qubits, which could be a function.
+pub struct CouldBeFunction;
+
+impl Rule for CouldBeFunction {
+    fn code(&self) -> &'static str {
+        "Qsc.Lint.CouldBeFunction"
+    }
+
+    fn check(&self, cx: &Context, lints: &mut Vec<Lint>) {
+        let mut decls = OperationDecls { decls: Vec::new() };
+        decls.visit_package(cx.package);
+        for decl in decls.decls {
+            let CallableBody::Block(block) = &*decl.body else {
+                continue;
+            };
+
+            let mut body = QuantumBody { cx, quantum: false };
+            body.visit_block(block);
+            if !body.quantum {
+                lints.push(Lint {
+                    code: self.code(),
+                    message: format!("operation `{}` could be a function", decl.name.name),
+                    help: Some(
+                        "it calls no operations and allocates no qubits, so it can be declared \
+                         with `function`"
+                            .to_string(),
+                    ),
+                    span: decl.name.span,
+                });
+            }
+        }
+    }
+}
+
+/// The operations that declare neither functors nor specializations and are not entry points.
+struct OperationDecls<'a> {
+    decls: Vec<&'a CallableDecl>,
+}
+
+impl<'a> Visitor<'a> for OperationDecls<'a> {
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if let ast::ItemKind::Callable(decl) = &*item.kind {
+            let entry_point = item.attrs.iter().any(|attr| {
+                hir::Attr::from_str(attr.name.name.as_ref()) == Ok(hir::Attr::EntryPoint)
+            });
+            if decl.kind == ast::CallableKind::Operation && decl.functors.is_none() && !entry_point
+            {
+                self.decls.push(decl);
+            }
+        }
+
+        visit::walk_item(self, item);
+    }
+}
+
+/// Finds whether a body does anything that only an operation can do.
+struct QuantumBody<'a, 'b> {
+    cx: &'a Context<'b>,
+    quantum: bool,
+}
+
+impl<'a> Visitor<'a> for QuantumBody<'_, '_> {
+    // Items declared in the body are checked on their own.
+    fn visit_item(&mut self, _: &'a ast::Item) {}
+
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        if matches!(&*stmt.kind, StmtKind::Qubit(..)) {
+            self.quantum = true;
+        }
+
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            ExprKind::Call(callee, _) => {
+                let function = matches!(
+                    self.cx.tys.terms.get(callee.id),
+                    Some(Ty::Arrow(arrow)) if arrow.kind == CallableKind::Function
+                );
+                if !function {
+                    self.quantum = true;
+                }
+            }
+            ExprKind::Conjugate(..)
+            | ExprKind::Repeat(..)
+            | ExprKind::Lambda(ast::CallableKind::Operation, ..) => self.quantum = true,
+            _ => {}
+        }
+
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// The adjoint of an adjoint, which is the operation itself.
+pub struct DoubleAdjoint;
+
+impl Rule for DoubleAdjoint {
+    fn code(&self) -> &'static str {
+        "Qsc.Lint.DoubleAdjoint"
+    }
+
+    fn check(&self, cx: &Context, lints: &mut Vec<Lint>) {
+        let mut adjoints = Adjoints { spans: Vec::new() };
+        adjoints.visit_package(cx.package);
+        lints.extend(adjoints.spans.into_iter().map(|span| {
+            Lint {
+                code: self.code(),
+                message: "adjoint of an adjoint".to_string(),
+                help: Some(
+                    "`Adjoint Adjoint` is the same as the operation itself, so both can be removed"
+                        .to_string(),
+                ),
+                span,
+            }
+        }));
+    }
+}
+
+struct Adjoints {
+    spans: Vec<Span>,
+}
+
+fn is_adjoint(expr: &Expr) -> bool {
+    match &*expr.kind {
+        ExprKind::Paren(inner) => is_adjoint(inner),
+        ExprKind::UnOp(UnOp::Functor(Functor::Adj), _) => true,
+        _ => false,
+    }
+}
+
+impl<'a> Visitor<'a> for Adjoints {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::UnOp(UnOp::Functor(Functor::Adj), inner) = &*expr.kind {
+            if is_adjoint(inner) {
+                self.spans.push(expr.span);
+            }
+        }
+
+        visit::walk_expr(self, expr);
+    }
+}
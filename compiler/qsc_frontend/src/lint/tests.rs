@@ -0,0 +1,201 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{Context, Lint, Registry, Rule};
+use crate::compile::{self, CompileOptions, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use miette::Diagnostic;
+use qsc_ast::ast::{ItemKind, TopLevelNode};
+use qsc_data_structures::language_features::LanguageFeatures;
+
+fn check_with(registry: Registry, source: &str, expect: &Expect) {
+    let options = CompileOptions {
+        lint_rules: registry,
+        ..CompileOptions::default()
+    };
+    let unit = compile::compile_with_options(
+        &PackageStore::new(compile::core()),
+        &[],
+        SourceMap::new([("test".into(), source.into())], None),
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        &options,
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let lints: Vec<_> = unit
+        .warnings
+        .iter()
+        .map(|warning| {
+            let label = warning
+                .labels()
+                .and_then(|mut labels| labels.next())
+                .expect("warning should have a label");
+            let span = &source[label.offset()..label.offset() + label.len()];
+            format!("{}: {warning} `{span}`", warning.code_str())
+        })
+        .collect();
+    expect.assert_eq(&lints.join("\n"));
+}
+
+fn check(source: &str, expect: &Expect) {
+    check_with(Registry::recommended(), source, expect);
+}
+
+#[test]
+fn needless_mut() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Foo() : Int {
+                    mutable x = 1;
+                    mutable (y, z) = (2, 3);
+                    set y += 1;
+                    set (z, _) = (4, 5);
+                    x
+                }
+            }
+        "},
+        &expect![[r#"Qsc.Lint.NeedlessMut: mutable variable `x` is never updated `x`"#]],
+    );
+}
+
+#[test]
+fn redundant_parens() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Foo(a : Bool) : Int {
+                    let x = (1 + 2);
+                    let y = (1, 2);
+                    if (a) {
+                        return ((x));
+                    }
+                    x * (x + 1)
+                }
+            }
+        "},
+        &expect![[r#"
+            Qsc.Lint.RedundantParens: unnecessary parentheses `(1 + 2)`
+            Qsc.Lint.RedundantParens: unnecessary parentheses `(a)`
+            Qsc.Lint.RedundantParens: unnecessary parentheses `(x)`"#]],
+    );
+}
+
+#[test]
+fn could_be_function() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Classical(x : Int) : Int { x + Helper() }
+                function Helper() : Int { 1 }
+                operation Quantum() : Unit { use q = Qubit(); }
+                operation CallsOp() : Unit { Quantum(); }
+                operation Adjointable() : Unit is Adj {}
+                @EntryPoint()
+                operation Main() : Unit {}
+            }
+        "},
+        &expect![[
+            r#"Qsc.Lint.CouldBeFunction: operation `Classical` could be a function `Classical`"#
+        ]],
+    );
+}
+
+#[test]
+fn double_adjoint() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Op() : Unit is Adj {}
+                operation Main() : Unit is Adj {
+                    Adjoint Adjoint Op();
+                    Adjoint (Adjoint Op)();
+                    Adjoint Op();
+                }
+            }
+        "},
+        &expect![[r#"
+            Qsc.Lint.DoubleAdjoint: adjoint of an adjoint `Adjoint Adjoint Op`
+            Qsc.Lint.DoubleAdjoint: adjoint of an adjoint `Adjoint (Adjoint Op)`"#]],
+    );
+}
+
+#[test]
+fn lints_respect_allow_attribute() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                @Allow("Qsc.Lint.NeedlessMut")
+                function Foo() : Int {
+                    mutable x = 1;
+                    x
+                }
+            }
+        "#},
+        &expect![""],
+    );
+}
+
+#[test]
+fn no_lints_by_default() {
+    check_with(
+        Registry::default(),
+        indoc! {"
+            namespace Test {
+                function Foo() : Int {
+                    mutable x = (1);
+                    x
+                }
+            }
+        "},
+        &expect![""],
+    );
+}
+
+struct NoFoo;
+
+impl Rule for NoFoo {
+    fn code(&self) -> &'static str {
+        "Test.NoFoo"
+    }
+
+    fn check(&self, cx: &Context, lints: &mut Vec<Lint>) {
+        for namespace in cx.package.nodes.iter().filter_map(|node| match node {
+            TopLevelNode::Namespace(namespace) => Some(namespace),
+            TopLevelNode::Stmt(_) => None,
+        }) {
+            for item in &*namespace.items {
+                if let ItemKind::Callable(decl) = &*item.kind {
+                    if &*decl.name.name == "Foo" {
+                        lints.push(Lint {
+                            code: self.code(),
+                            message: "callables should not be named `Foo`".to_string(),
+                            help: None,
+                            span: decl.name.span,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn custom_rule() {
+    let mut registry = Registry::default();
+    registry.register(NoFoo);
+    check_with(
+        registry,
+        indoc! {"
+            namespace Test {
+                function Foo() : Unit {}
+                function Bar() : Unit {}
+            }
+        "},
+        &expect![[r#"Test.NoFoo: callables should not be named `Foo` `Foo`"#]],
+    );
+}
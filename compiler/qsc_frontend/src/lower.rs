@@ -6,7 +6,7 @@ mod tests;
 
 use crate::{
     closure::{self, Lambda, PartialApp},
-    compile::ConfigAttr,
+    compile::{preprocess::Config, warnings},
     resolve::{self, Names},
     typeck::{self, convert},
 };
@@ -17,15 +17,18 @@ use qsc_hir::{
     assigner::Assigner,
     hir::{self, LocalItemId},
     mut_visit::MutVisitor,
-    ty::{Arrow, FunctorSetValue, Ty},
+    ty::{Arrow, FunctorSet, FunctorSetValue, GenericArg, Prim, Ty, UdtDef, UdtDefKind},
 };
+use rustc_hash::FxHashMap;
 use std::{clone::Clone, rc::Rc, str::FromStr, vec};
 use thiserror::Error;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub(super) enum Error {
     #[error("unknown attribute {0}")]
-    #[diagnostic(help("supported attributes are: EntryPoint, Config"))]
+    #[diagnostic(help(
+        "supported attributes are: EntryPoint, Config, Deprecated, Allow, NoInline"
+    ))]
     #[diagnostic(code("Qsc.LowerAst.UnknownAttr"))]
     UnknownAttr(String, #[label] Span),
     #[error("invalid attribute arguments: expected {0}")]
@@ -101,6 +104,7 @@ pub(super) struct With<'a> {
 }
 
 impl With<'_> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub(super) fn lower_package(&mut self, package: &ast::Package) -> hir::Package {
         let mut stmts = Vec::new();
         for node in &*package.nodes {
@@ -137,12 +141,15 @@ impl With<'_> {
             .filter_map(|i| self.lower_item(ItemScope::Global, i))
             .collect();
 
-        let name = self.lower_ident(&namespace.name);
+        let name = hir::Ident {
+            name: Rc::from(&*namespace.full_name()),
+            ..self.lower_ident(&namespace.name)
+        };
         self.lowerer.items.push(hir::Item {
             id,
             span: namespace.span,
             parent: None,
-            doc: Rc::clone(&namespace.doc),
+            doc: Rc::from(&*namespace.doc),
             attrs: Vec::new(),
             visibility: hir::Visibility::Public,
             kind: hir::ItemKind::Namespace(name, items),
@@ -172,7 +179,9 @@ impl With<'_> {
         };
 
         let (id, kind) = match &*item.kind {
-            ast::ItemKind::Err | ast::ItemKind::Open(..) => return None,
+            ast::ItemKind::Err | ast::ItemKind::Export(..) | ast::ItemKind::Open(..) => {
+                return None
+            }
             ast::ItemKind::Callable(callable) => {
                 let id = resolve_id(callable.name.id);
                 let grandparent = self.lowerer.parent;
@@ -197,7 +206,7 @@ impl With<'_> {
             id: id.item,
             span: item.span,
             parent: self.lowerer.parent,
-            doc: Rc::clone(&item.doc),
+            doc: Rc::from(&*item.doc),
             attrs,
             visibility,
             kind,
@@ -226,13 +235,41 @@ impl With<'_> {
                     None
                 }
             },
+            Ok(hir::Attr::NoInline) => match &*attr.arg.kind {
+                ast::ExprKind::Tuple(args) if args.is_empty() => Some(hir::Attr::NoInline),
+                _ => {
+                    self.lowerer
+                        .errors
+                        .push(Error::InvalidAttrArgs("()", attr.arg.span));
+                    None
+                }
+            },
+            Ok(hir::Attr::Deprecated(_)) => {
+                if let Ok(replacement) = resolve::deprecated_replacement(attr) {
+                    Some(hir::Attr::Deprecated(replacement))
+                } else {
+                    self.lowerer.errors.push(Error::InvalidAttrArgs(
+                        "() or a replacement name string",
+                        attr.arg.span,
+                    ));
+                    None
+                }
+            }
+            Ok(hir::Attr::Allow) => {
+                if warnings::allowed_code(attr).is_none() {
+                    self.lowerer.errors.push(Error::InvalidAttrArgs(
+                        "a warning code string",
+                        attr.arg.span,
+                    ));
+                }
+                None
+            }
             Ok(hir::Attr::Config) => {
                 if !matches!(attr.arg.kind.as_ref(), ast::ExprKind::Paren(inner)
-                    if matches!(inner.kind.as_ref(), ast::ExprKind::Path(path)
-                        if ConfigAttr::from_str(path.name.name.as_ref()).is_ok()))
+                    if Config::from_expr(inner).is_some())
                 {
                     self.lowerer.errors.push(Error::InvalidAttrArgs(
-                        "Unrestricted or Base",
+                        "a target or symbol, or a combination using `not`, `and`, and `or`",
                         attr.arg.span,
                     ));
                 }
@@ -255,7 +292,12 @@ impl With<'_> {
         let mut input = self.lower_pat(&decl.input);
         let output = convert::ty_from_ast(self.names, &decl.output).0;
         let generics = convert::synthesize_callable_generics(&decl.generics, &mut input);
-        let functors = convert::ast_callable_functors(decl);
+        let functors = self
+            .tys
+            .functors
+            .get(decl.id)
+            .copied()
+            .unwrap_or_else(|| convert::ast_callable_functors(decl));
 
         let (body, adj, ctl, ctl_adj) = match decl.body.as_ref() {
             ast::CallableBody::Block(block) => {
@@ -263,6 +305,7 @@ impl With<'_> {
                     id: self.assigner.next_node(),
                     span: decl.span,
                     body: hir::SpecBody::Impl(None, self.lower_block(block)),
+                    generated: None,
                 };
                 (body, None, None, None)
             }
@@ -273,6 +316,7 @@ impl With<'_> {
                         id: self.assigner.next_node(),
                         span: decl.span,
                         body: hir::SpecBody::Gen(hir::SpecGen::Auto),
+                        generated: None,
                     }
                 });
                 let adj = self.find_spec(specs, ast::Spec::Adj);
@@ -282,20 +326,98 @@ impl With<'_> {
             }
         };
 
-        hir::CallableDecl {
+        let mut callable = hir::CallableDecl {
             id,
             span: decl.span,
             kind,
             name,
             generics,
             input,
+            defaults: Vec::new(),
             output,
             functors,
             body,
             adj,
             ctl,
             ctl_adj,
-        }
+        };
+        callable.defaults = self.lower_defaults(decl, &callable);
+        callable
+    }
+
+    /// Lowers each default parameter value into an item that computes the value, so that it can be
+    /// called in place of a missing argument.
+    fn lower_defaults(
+        &mut self,
+        decl: &ast::CallableDecl,
+        callable: &hir::CallableDecl,
+    ) -> Vec<Option<LocalItemId>> {
+        let param_tys: Vec<_> = callable.params().iter().map(|p| p.ty.clone()).collect();
+        decl.defaults
+            .iter()
+            .zip(decl.params())
+            .zip(param_tys)
+            .map(|((default, param), ty)| {
+                let value = default.as_ref()?;
+                let Some(&resolve::Res::Item(id, _)) = self.names.get(param.id) else {
+                    panic!("default value should have item ID");
+                };
+                let value = self.lower_expr(value);
+                let body = hir::SpecDecl {
+                    id: self.assigner.next_node(),
+                    span: value.span,
+                    body: hir::SpecBody::Impl(
+                        None,
+                        hir::Block {
+                            id: self.assigner.next_node(),
+                            span: value.span,
+                            ty: ty.clone(),
+                            stmts: vec![hir::Stmt {
+                                id: self.assigner.next_node(),
+                                span: value.span,
+                                kind: hir::StmtKind::Expr(value),
+                            }],
+                        },
+                    ),
+                    generated: None,
+                };
+                let input = hir::Pat {
+                    id: self.assigner.next_node(),
+                    span: param.span,
+                    ty: Ty::UNIT,
+                    kind: hir::PatKind::Tuple(Vec::new()),
+                };
+
+                self.lowerer.items.push(hir::Item {
+                    id: id.item,
+                    span: param.span,
+                    parent: self.lowerer.parent,
+                    doc: "".into(),
+                    attrs: Vec::new(),
+                    visibility: hir::Visibility::Internal,
+                    kind: hir::ItemKind::Callable(hir::CallableDecl {
+                        id: self.assigner.next_node(),
+                        span: param.span,
+                        kind: callable.kind,
+                        name: hir::Ident {
+                            id: self.assigner.next_node(),
+                            span: param.span,
+                            name: "default".into(),
+                        },
+                        generics: callable.generics.clone(),
+                        input,
+                        defaults: Vec::new(),
+                        output: ty,
+                        functors: FunctorSetValue::Empty,
+                        body,
+                        adj: None,
+                        ctl: None,
+                        ctl_adj: None,
+                    }),
+                });
+                Some(id.item)
+            })
+            .collect()
     }
 
     fn find_spec(
@@ -336,6 +458,7 @@ impl With<'_> {
                     hir::SpecBody::Impl(self.lower_spec_decl_pat(input), self.lower_block(block))
                 }
             },
+            generated: None,
         }
     }
 
@@ -448,8 +571,9 @@ impl With<'_> {
                 Box::new(self.lower_expr(rhs)),
             ),
             ast::ExprKind::Block(block) => hir::ExprKind::Block(self.lower_block(block)),
-            ast::ExprKind::Call(callee, arg) => match &ty {
-                Ty::Arrow(arrow) if is_partial_app(arg) => hir::ExprKind::Block(
+            ast::ExprKind::Call(callee, arg) => match (&ty, self.tys.args.get(expr.id)) {
+                (_, Some(args)) => self.lower_call_args(callee, arg, args, &ty, expr.span),
+                (Ty::Arrow(arrow), None) if is_partial_app(arg) => hir::ExprKind::Block(
                     self.lower_partial_app(callee, arg, (**arrow).clone(), expr.span),
                 ),
                 _ => hir::ExprKind::Call(
@@ -460,7 +584,7 @@ impl With<'_> {
             ast::ExprKind::Conjugate(within, apply) => {
                 hir::ExprKind::Conjugate(self.lower_block(within), self.lower_block(apply))
             }
-            ast::ExprKind::Err => hir::ExprKind::Err,
+            ast::ExprKind::Err | ast::ExprKind::NamedArg(..) => hir::ExprKind::Err,
             ast::ExprKind::Fail(message) => hir::ExprKind::Fail(Box::new(self.lower_expr(message))),
             ast::ExprKind::Field(container, name) => {
                 let container = self.lower_expr(container);
@@ -504,6 +628,9 @@ impl With<'_> {
                 self.lower_lambda(lambda, expr.span)
             }
             ast::ExprKind::Lit(lit) => lower_lit(lit),
+            ast::ExprKind::Match(scrutinee, arms) => {
+                self.lower_match(scrutinee, arms, &ty, expr.span)
+            }
             ast::ExprKind::Paren(_) => unreachable!("parentheses should be removed earlier"),
             ast::ExprKind::Path(path) => {
                 let args = self
@@ -549,6 +676,7 @@ impl With<'_> {
                     )
                 }
             }
+            ast::ExprKind::Struct(path, fields) => self.lower_struct(path, fields, &ty, expr.span),
             ast::ExprKind::Tuple(items) => {
                 hir::ExprKind::Tuple(items.iter().map(|i| self.lower_expr(i)).collect())
             }
@@ -639,10 +767,372 @@ impl With<'_> {
         }
     }
 
+    /// Lowers a struct constructor into a call to the type's constructor. If the fields are not
+    /// given in declaration order, their values are first bound to locals so that they are still
+    /// evaluated in the order they are written.
+    fn lower_struct(
+        &mut self,
+        path: &ast::Path,
+        fields: &[Box<ast::FieldAssign>],
+        ty: &Ty,
+        span: Span,
+    ) -> hir::ExprKind {
+        let Ty::Udt(_, hir::Res::Item(item)) = ty else {
+            return hir::ExprKind::Err;
+        };
+        let Some(udt) = self.tys.udts.get(item).cloned() else {
+            return hir::ExprKind::Err;
+        };
+        let Some(names) = udt.field_names() else {
+            return hir::ExprKind::Err;
+        };
+
+        let in_order = fields
+            .iter()
+            .map(|field| &*field.field.name)
+            .eq(names.iter().map(AsRef::as_ref));
+        let mut stmts = Vec::new();
+        let mut values = FxHashMap::default();
+        for field in fields {
+            let value = self.lower_expr(&field.value);
+            let value = if in_order {
+                value
+            } else {
+                let (stmt, var) = self.bind_value(&Rc::from(&*field.field.name), value);
+                stmts.push(stmt);
+                var
+            };
+            values.insert(Rc::from(&*field.field.name), value);
+        }
+
+        let Some(arg) = self.lower_struct_arg(&udt.definition, &mut values, span) else {
+            return hir::ExprKind::Err;
+        };
+        let callee = hir::Expr {
+            id: self.assigner.next_node(),
+            span: path.span,
+            ty: Ty::Arrow(Box::new(Arrow {
+                kind: hir::CallableKind::Function,
+                input: Box::new(arg.ty.clone()),
+                output: Box::new(ty.clone()),
+                functors: FunctorSet::Value(FunctorSetValue::Empty),
+            })),
+            kind: hir::ExprKind::Var(hir::Res::Item(*item), Vec::new()),
+        };
+        let call = hir::ExprKind::Call(Box::new(callee), Box::new(arg));
+        self.with_bindings(stmts, call, ty, span)
+    }
+
+    fn lower_struct_arg(
+        &mut self,
+        def: &UdtDef,
+        values: &mut FxHashMap<Rc<str>, hir::Expr>,
+        span: Span,
+    ) -> Option<hir::Expr> {
+        match &def.kind {
+            UdtDefKind::Field(field) => values.remove(field.name.as_ref()?),
+            UdtDefKind::Tuple(defs) => {
+                let items = defs
+                    .iter()
+                    .map(|def| self.lower_struct_arg(def, values, span))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(hir::Expr {
+                    id: self.assigner.next_node(),
+                    span,
+                    ty: Ty::Tuple(items.iter().map(|item| item.ty.clone()).collect()),
+                    kind: hir::ExprKind::Tuple(items),
+                })
+            }
+        }
+    }
+
+    /// Lowers a match expression into a conditional chain that compares the matched value with
+    /// the literal of each arm in turn. The matched value is bound to a local so that it is only
+    /// evaluated once, and the last arm becomes the final `else`, since type checking ensures that
+    /// the arms are exhaustive.
+    fn lower_match(
+        &mut self,
+        scrutinee: &ast::Expr,
+        arms: &[Box<ast::MatchArm>],
+        ty: &Ty,
+        span: Span,
+    ) -> hir::ExprKind {
+        let scrutinee = self.lower_expr(scrutinee);
+        let (stmt, var) = self.bind_value(&"scrutinee".into(), scrutinee);
+
+        let mut otherwise: Option<hir::Expr> = None;
+        for arm in arms.iter().rev() {
+            let body = self.lower_match_body(arm, &var, ty);
+            otherwise = Some(match (&*arm.pat.kind, otherwise) {
+                (ast::MatchPatKind::Lit(lit), Some(otherwise)) => {
+                    let lit = hir::Expr {
+                        id: self.assigner.next_node(),
+                        span: arm.pat.span,
+                        ty: var.ty.clone(),
+                        kind: lower_lit(lit),
+                    };
+                    let cond = hir::Expr {
+                        id: self.assigner.next_node(),
+                        span: arm.pat.span,
+                        ty: Ty::Prim(Prim::Bool),
+                        kind: hir::ExprKind::BinOp(
+                            hir::BinOp::Eq,
+                            Box::new(self.copy_var(&var)),
+                            Box::new(lit),
+                        ),
+                    };
+                    hir::Expr {
+                        id: self.assigner.next_node(),
+                        span: arm.span,
+                        ty: ty.clone(),
+                        kind: hir::ExprKind::If(
+                            Box::new(cond),
+                            Box::new(body),
+                            Some(Box::new(otherwise)),
+                        ),
+                    }
+                }
+                _ => body,
+            });
+        }
+
+        match otherwise {
+            Some(chain) => self.with_bindings(vec![stmt], chain.kind, ty, span),
+            None => hir::ExprKind::Err,
+        }
+    }
+
+    /// Lowers the body of a match arm into a block, first binding the matched value if the arm's
+    /// pattern names it.
+    fn lower_match_body(&mut self, arm: &ast::MatchArm, var: &hir::Expr, ty: &Ty) -> hir::Expr {
+        let mut stmts = Vec::new();
+        if let ast::MatchPatKind::Bind(name) = &*arm.pat.kind {
+            let name = self.lower_ident(name);
+            self.lowerer
+                .locals
+                .insert(name.id, (name.clone(), var.ty.clone()));
+            stmts.push(hir::Stmt {
+                id: self.assigner.next_node(),
+                span: arm.pat.span,
+                kind: hir::StmtKind::Local(
+                    hir::Mutability::Immutable,
+                    hir::Pat {
+                        id: self.lower_id(arm.pat.id),
+                        span: arm.pat.span,
+                        ty: var.ty.clone(),
+                        kind: hir::PatKind::Bind(name),
+                    },
+                    self.copy_var(var),
+                ),
+            });
+        }
+
+        let body = self.lower_expr(&arm.body);
+        stmts.push(hir::Stmt {
+            id: self.assigner.next_node(),
+            span: arm.body.span,
+            kind: hir::StmtKind::Expr(body),
+        });
+        hir::Expr {
+            id: self.assigner.next_node(),
+            span: arm.body.span,
+            ty: ty.clone(),
+            kind: hir::ExprKind::Block(hir::Block {
+                id: self.assigner.next_node(),
+                span: arm.body.span,
+                ty: ty.clone(),
+                stmts,
+            }),
+        }
+    }
+
+    /// Another use of a local bound by [`Self::bind_value`].
+    fn copy_var(&mut self, var: &hir::Expr) -> hir::Expr {
+        hir::Expr {
+            id: self.assigner.next_node(),
+            ..var.clone()
+        }
+    }
+
+    /// Lowers a call whose arguments were matched to the parameters by name or completed with
+    /// default values. If the written arguments are not in parameter order, they are first bound
+    /// to locals so that they are still evaluated in the order they are written.
+    fn lower_call_args(
+        &mut self,
+        callee: &ast::Expr,
+        input: &ast::Expr,
+        args: &[typeck::Arg],
+        ty: &Ty,
+        span: Span,
+    ) -> hir::ExprKind {
+        let callee = self.lower_expr(callee);
+        let Ty::Arrow(arrow) = &callee.ty else {
+            return hir::ExprKind::Err;
+        };
+        let kind = arrow.kind;
+        let param_tys = match &*arrow.input {
+            Ty::Tuple(tys) if args.len() != 1 => tys.clone(),
+            ty => vec![ty.clone()],
+        };
+        let generics = match &callee.kind {
+            hir::ExprKind::Var(_, generics) => generics.clone(),
+            _ => Vec::new(),
+        };
+
+        let written = call_args(input);
+        let in_order = args
+            .iter()
+            .take(written.len())
+            .enumerate()
+            .all(|(ix, &arg)| arg == typeck::Arg::Given(ix));
+        let mut stmts = Vec::new();
+        let mut values = Vec::new();
+        for arg in written {
+            let (name, value) = match arg.kind.as_ref() {
+                ast::ExprKind::NamedArg(name, value) => (Rc::from(&*name.name), value.as_ref()),
+                _ => ("arg".into(), arg),
+            };
+            let value = self.lower_expr(value);
+            if in_order {
+                values.push(Some(value));
+            } else {
+                let (stmt, var) = self.bind_value(&name, value);
+                stmts.push(stmt);
+                values.push(Some(var));
+            }
+        }
+
+        let mut items = Vec::new();
+        for (&arg, param_ty) in args.iter().zip(param_tys) {
+            let item = match arg {
+                typeck::Arg::Given(ix) => values.get_mut(ix).and_then(Option::take),
+                typeck::Arg::Default(item) => {
+                    Some(self.default_value(item, kind, generics.clone(), param_ty, span))
+                }
+            };
+            let Some(item) = item else {
+                return hir::ExprKind::Err;
+            };
+            items.push(item);
+        }
+
+        let arg = if items.len() == 1 {
+            items.pop().expect("there should be one argument")
+        } else {
+            hir::Expr {
+                id: self.assigner.next_node(),
+                span: input.span,
+                ty: Ty::Tuple(items.iter().map(|item| item.ty.clone()).collect()),
+                kind: hir::ExprKind::Tuple(items),
+            }
+        };
+        let call = hir::ExprKind::Call(Box::new(callee), Box::new(arg));
+        self.with_bindings(stmts, call, ty, span)
+    }
+
+    /// Calls the item that computes the default value of a parameter.
+    fn default_value(
+        &mut self,
+        item: hir::ItemId,
+        kind: hir::CallableKind,
+        generics: Vec<GenericArg>,
+        ty: Ty,
+        span: Span,
+    ) -> hir::Expr {
+        let callee = hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::Arrow(Box::new(Arrow {
+                kind,
+                input: Box::new(Ty::UNIT),
+                output: Box::new(ty.clone()),
+                functors: FunctorSet::Value(FunctorSetValue::Empty),
+            })),
+            kind: hir::ExprKind::Var(hir::Res::Item(item), generics),
+        };
+        let unit = hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::UNIT,
+            kind: hir::ExprKind::Tuple(Vec::new()),
+        };
+        hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty,
+            kind: hir::ExprKind::Call(Box::new(callee), Box::new(unit)),
+        }
+    }
+
+    /// Evaluates the bindings before the expression, if there are any.
+    fn with_bindings(
+        &mut self,
+        mut stmts: Vec<hir::Stmt>,
+        kind: hir::ExprKind,
+        ty: &Ty,
+        span: Span,
+    ) -> hir::ExprKind {
+        if stmts.is_empty() {
+            return kind;
+        }
+
+        stmts.push(hir::Stmt {
+            id: self.assigner.next_node(),
+            span,
+            kind: hir::StmtKind::Expr(hir::Expr {
+                id: self.assigner.next_node(),
+                span,
+                ty: ty.clone(),
+                kind,
+            }),
+        });
+        hir::ExprKind::Block(hir::Block {
+            id: self.assigner.next_node(),
+            span,
+            ty: ty.clone(),
+            stmts,
+        })
+    }
+
+    /// Binds the value to a new immutable local, returning the binding and a use of the local.
+    fn bind_value(&mut self, name: &Rc<str>, value: hir::Expr) -> (hir::Stmt, hir::Expr) {
+        let span = value.span;
+        let ident = hir::Ident {
+            id: self.assigner.next_node(),
+            span,
+            name: Rc::clone(name),
+        };
+        self.lowerer
+            .locals
+            .insert(ident.id, (ident.clone(), value.ty.clone()));
+
+        let var = hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: value.ty.clone(),
+            kind: hir::ExprKind::Var(hir::Res::Local(ident.id), Vec::new()),
+        };
+        let stmt = hir::Stmt {
+            id: self.assigner.next_node(),
+            span,
+            kind: hir::StmtKind::Local(
+                hir::Mutability::Immutable,
+                hir::Pat {
+                    id: self.assigner.next_node(),
+                    span,
+                    ty: value.ty.clone(),
+                    kind: hir::PatKind::Bind(ident),
+                },
+                value,
+            ),
+        };
+        (stmt, var)
+    }
+
     fn lower_string_component(&mut self, component: &ast::StringComponent) -> hir::StringComponent {
         match component {
             ast::StringComponent::Expr(expr) => hir::StringComponent::Expr(self.lower_expr(expr)),
-            ast::StringComponent::Lit(str) => hir::StringComponent::Lit(Rc::clone(str)),
+            ast::StringComponent::Lit(str) => hir::StringComponent::Lit(Rc::from(&**str)),
         }
     }
 
@@ -726,7 +1216,7 @@ impl With<'_> {
         hir::Ident {
             id: self.lower_id(ident.id),
             span: ident.span,
-            name: ident.name.clone(),
+            name: Rc::from(&*ident.name),
         }
     }
 
@@ -812,7 +1302,7 @@ fn lower_lit(lit: &ast::Lit) -> hir::ExprKind {
             hir::ExprKind::Lit(hir::Lit::Result(hir::Result::Zero))
         }
         ast::Lit::String(value) => {
-            hir::ExprKind::String(vec![hir::StringComponent::Lit(Rc::clone(value))])
+            hir::ExprKind::String(vec![hir::StringComponent::Lit(Rc::from(&**value))])
         }
     }
 }
@@ -824,6 +1314,14 @@ fn lower_functor(functor: ast::Functor) -> hir::Functor {
     }
 }
 
+fn call_args(input: &ast::Expr) -> Vec<&ast::Expr> {
+    match input.kind.as_ref() {
+        ast::ExprKind::Paren(inner) => vec![&**inner],
+        ast::ExprKind::Tuple(items) => items.iter().map(AsRef::as_ref).collect(),
+        _ => vec![input],
+    }
+}
+
 fn is_partial_app(arg: &ast::Expr) -> bool {
     match arg.kind.as_ref() {
         ast::ExprKind::Hole => true,
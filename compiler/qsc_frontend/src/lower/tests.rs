@@ -128,7 +128,7 @@ fn test_target_profile_attr_wrong_args() {
     check_errors(
         indoc! {"
             namespace input {
-                @Config(Bar)
+                @Config(1)
                 operation Foo() : Unit {
                     body ... {}
                 }
@@ -137,10 +137,10 @@ fn test_target_profile_attr_wrong_args() {
         &expect![[r#"
             [
                 InvalidAttrArgs(
-                    "Unrestricted or Base",
+                    "a target or symbol, or a combination using `not`, `and`, and `or`",
                     Span {
                         lo: 29,
-                        hi: 34,
+                        hi: 32,
                     },
                 ),
             ]
@@ -148,6 +148,23 @@ fn test_target_profile_attr_wrong_args() {
     );
 }
 
+#[test]
+fn test_config_symbol_attr_allowed() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @Config(not Bar and (Unrestricted or Baz))
+                operation Foo() : Unit {
+                    body ... {}
+                }
+            }
+        "},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
 #[test]
 fn test_unknown_attr() {
     check_errors(
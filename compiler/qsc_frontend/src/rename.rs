@@ -0,0 +1,316 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compile::{CompileUnit, PackageStore},
+    definition,
+    resolve::{self, LocalKind, Res},
+};
+use miette::Diagnostic;
+use qsc_ast::{
+    ast::{self, ExprKind, NodeId},
+    visit::{self as ast_visit, Visitor as AstVisitor},
+};
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use qsc_hir::hir::{self, ItemId, LocalItemId, PackageId};
+use std::rc::Rc;
+use thiserror::Error;
+
+/// A text edit that replaces the span in a package's sources with the new name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Edit {
+    pub package: PackageId,
+    pub span: Span,
+    pub text: Rc<str>,
+}
+
+#[derive(Clone, Debug, Diagnostic, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("`{0}` is not a valid name")]
+    #[diagnostic(code("Qsc.Rename.InvalidName"))]
+    InvalidName(String),
+
+    #[error("`{0}` cannot be renamed")]
+    #[diagnostic(help("only callables, types, locals, and namespaces can be renamed"))]
+    #[diagnostic(code("Qsc.Rename.NotRenamable"))]
+    NotRenamable(String),
+
+    #[error("`{name}` is already declared in namespace `{namespace}`")]
+    #[diagnostic(code("Qsc.Rename.Conflict"))]
+    Conflict { name: String, namespace: String },
+
+    #[error("namespace `{0}` is already declared")]
+    #[diagnostic(code("Qsc.Rename.NamespaceConflict"))]
+    NamespaceConflict(String),
+
+    #[error("renaming to `{0}` would change what this name refers to")]
+    #[diagnostic(code("Qsc.Rename.LocalConflict"))]
+    LocalConflict(String, #[label] Span),
+}
+
+/// Computes the edits that rename a callable or type declared by the package, including its
+/// declaration and every reference in the units of the store.
+///
+/// # Errors
+///
+/// Returns an error if the new name is not an identifier, if the item is not a callable or type, or
+/// if the item's namespace already declares an item with the new name.
+pub fn item(
+    store: &PackageStore,
+    package: PackageId,
+    item: LocalItemId,
+    new_name: &str,
+) -> Result<Vec<Edit>, Error> {
+    check_ident(new_name)?;
+    let unit = store.get(package).expect("package should be in store");
+    let hir_item = unit.package.items.get(item).expect("item should exist");
+    let decl_name = match &hir_item.kind {
+        hir::ItemKind::Callable(decl) => &decl.name,
+        hir::ItemKind::Ty(name, _) => name,
+        hir::ItemKind::Namespace(name, _) => {
+            return Err(Error::NotRenamable(name.name.to_string()))
+        }
+    };
+
+    if let Some(namespace) = parent_namespace(&unit.package, hir_item) {
+        let mut globals = resolve::GlobalTable::new();
+        globals.add_declaring_package(package, &unit.package);
+        if decl_name.name.as_ref() != new_name && globals.contains_item(namespace, new_name) {
+            return Err(Error::Conflict {
+                name: new_name.to_string(),
+                namespace: namespace.to_string(),
+            });
+        }
+    }
+
+    let text: Rc<str> = new_name.into();
+    Ok(std::iter::once((package, decl_name.span))
+        .chain(store.item_references(package, item))
+        .map(|(package, span)| Edit {
+            package,
+            span,
+            text: Rc::clone(&text),
+        })
+        .collect())
+}
+
+/// Computes the edits that rename the local declared by the given node, including its declaration
+/// and every reference to it.
+///
+/// # Errors
+///
+/// Returns an error if the new name is not an identifier, or if renaming the local would make one
+/// of its references refer to a different binding, or make a reference to a different binding
+/// refer to the local.
+pub fn local(
+    store: &PackageStore,
+    package: PackageId,
+    decl: NodeId,
+    new_name: &str,
+) -> Result<Vec<Edit>, Error> {
+    check_ident(new_name)?;
+    let unit = store.get(package).expect("package should be in store");
+    let decl_ident = definition::local_decl(&unit.ast.package, decl)
+        .expect("local should be declared in the package");
+
+    let references = unit.ast.references.local(decl);
+    let binds_other = |offset| {
+        unit.ast
+            .locals
+            .get_all_at_offset(offset)
+            .into_iter()
+            .any(|local| {
+                local.name.as_ref() == new_name
+                    && !matches!(local.kind, LocalKind::Var(id) if id == decl)
+            })
+    };
+    if let Some(&span) = references.iter().find(|span| binds_other(span.lo)) {
+        return Err(Error::LocalConflict(new_name.to_string(), span));
+    }
+
+    let mut captured = CaptureFinder {
+        unit,
+        name: new_name,
+        old_name: &decl_ident.name,
+        decl,
+        span: None,
+    };
+    captured.visit_package(&unit.ast.package);
+    if let Some(span) = captured.span {
+        return Err(Error::LocalConflict(new_name.to_string(), span));
+    }
+
+    let text: Rc<str> = new_name.into();
+    Ok(std::iter::once(decl_ident.span)
+        .chain(references.iter().copied())
+        .map(|span| Edit {
+            package,
+            span,
+            text: Rc::clone(&text),
+        })
+        .collect())
+}
+
+/// Computes the edits that rename a namespace declared by the package, including its declarations,
+/// the opens of it, and the paths qualified with it, in every unit of the store. An alias given to
+/// the namespace by an open is not renamed, so paths qualified with the alias are left unchanged.
+///
+/// # Errors
+///
+/// Returns an error if the new name is not a namespace name, or if the package already declares a
+/// namespace with the new name.
+pub fn namespace(
+    store: &PackageStore,
+    package: PackageId,
+    name: &str,
+    new_name: &str,
+) -> Result<Vec<Edit>, Error> {
+    if new_name.split('.').any(|part| check_ident(part).is_err()) {
+        return Err(Error::InvalidName(new_name.to_string()));
+    }
+
+    let unit = store.get(package).expect("package should be in store");
+    let mut globals = resolve::GlobalTable::new();
+    globals.add_declaring_package(package, &unit.package);
+    if !globals.contains_namespace(name) {
+        return Err(Error::NotRenamable(name.to_string()));
+    }
+    if name != new_name && globals.contains_namespace(new_name) {
+        return Err(Error::NamespaceConflict(new_name.to_string()));
+    }
+
+    let text: Rc<str> = new_name.into();
+    let mut edits = Vec::new();
+    for (id, unit) in store {
+        let mut finder = NamespaceFinder {
+            store,
+            unit_id: id,
+            unit,
+            name,
+            spans: Vec::new(),
+        };
+        finder.visit_package(&unit.ast.package);
+        edits.extend(finder.spans.into_iter().map(|span| Edit {
+            package: id,
+            span,
+            text: Rc::clone(&text),
+        }));
+    }
+    Ok(edits)
+}
+
+/// Checks that the name parses as an unqualified identifier, which rules out keywords.
+fn check_ident(name: &str) -> Result<(), Error> {
+    let (expr, errors) = qsc_parse::expr(name, LanguageFeatures::default());
+    match &*expr.kind {
+        ExprKind::Path(path)
+            if errors.is_empty()
+                && path.package.is_none()
+                && path.namespace.is_none()
+                && path.name.name.as_ref() == name =>
+        {
+            Ok(())
+        }
+        _ => Err(Error::InvalidName(name.to_string())),
+    }
+}
+
+fn parent_namespace<'a>(package: &'a hir::Package, item: &hir::Item) -> Option<&'a str> {
+    let parent = package.items.get(item.parent?)?;
+    match &parent.kind {
+        hir::ItemKind::Namespace(name, _) => Some(name.name.as_ref()),
+        _ => None,
+    }
+}
+
+/// Finds an unqualified path that already uses the new name for a different binding, where the
+/// renamed local would be in scope and capture it.
+struct CaptureFinder<'a> {
+    unit: &'a CompileUnit,
+    name: &'a str,
+    old_name: &'a str,
+    decl: NodeId,
+    span: Option<Span>,
+}
+
+impl AstVisitor<'_> for CaptureFinder<'_> {
+    fn visit_path(&mut self, path: &ast::Path) {
+        if self.span.is_none()
+            && path.package.is_none()
+            && path.namespace.is_none()
+            && path.name.name.as_ref() == self.name
+            && self.unit.ast.names.get(path.id) != Some(&Res::Local(self.decl))
+            && self
+                .unit
+                .ast
+                .locals
+                .get_all_at_offset(path.span.lo)
+                .into_iter()
+                .any(|local| {
+                    local.name.as_ref() == self.old_name
+                        && matches!(local.kind, LocalKind::Var(id) if id == self.decl)
+                })
+        {
+            self.span = Some(path.name.span);
+        }
+    }
+}
+
+/// Finds the spans that name a namespace in one unit of the store.
+struct NamespaceFinder<'a> {
+    store: &'a PackageStore,
+    unit_id: PackageId,
+    unit: &'a CompileUnit,
+    name: &'a str,
+    spans: Vec<Span>,
+}
+
+impl NamespaceFinder<'_> {
+    /// Whether the path resolved to an item that is declared in the namespace, as opposed to an
+    /// item found through an open alias that happens to share the namespace's name.
+    fn resolves_into_namespace(&self, path: &ast::Path) -> bool {
+        let Some(Res::Item(id, _)) = self.unit.ast.names.get(path.id) else {
+            return false;
+        };
+        let ItemId { package, item } = *id;
+        let package = self
+            .store
+            .get(package.unwrap_or(self.unit_id))
+            .map(|unit| &unit.package);
+        package
+            .and_then(|package| {
+                let item = package.items.get(item)?;
+                parent_namespace(package, item)
+            })
+            .is_some_and(|namespace| namespace == self.name)
+    }
+}
+
+impl<'a> AstVisitor<'a> for NamespaceFinder<'a> {
+    fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+        if namespace.full_name().as_ref() == self.name {
+            self.spans.push(namespace.name.span);
+        }
+        ast_visit::walk_namespace(self, namespace);
+    }
+
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if let ast::ItemKind::Open(namespace, _) = &*item.kind {
+            if namespace.name.as_ref() == self.name {
+                self.spans.push(namespace.span);
+            }
+        }
+        ast_visit::walk_item(self, item);
+    }
+
+    fn visit_path(&mut self, path: &'a ast::Path) {
+        if let Some(namespace) = &path.namespace {
+            if namespace.name.as_ref() == self.name && self.resolves_into_namespace(path) {
+                self.spans.push(namespace.span);
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Edit, Error};
+use crate::{
+    compile::{self, PackageStore, RuntimeCapabilityFlags, SourceMap},
+    resolve::Res,
+};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_hir::hir::{LocalItemId, PackageId};
+
+fn compile(source: &str) -> (PackageStore, PackageId) {
+    let mut store = PackageStore::new(compile::core());
+    let unit = compile::compile(
+        &store,
+        &[],
+        SourceMap::new([("test".into(), source.into())], None),
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    let id = store.insert(unit);
+    (store, id)
+}
+
+fn apply(source: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| edit.span.lo);
+    let mut output = source.to_string();
+    for edit in edits.iter().rev() {
+        output.replace_range(edit.span.lo as usize..edit.span.hi as usize, &edit.text);
+    }
+    output
+}
+
+fn first_local(store: &PackageStore, package: PackageId) -> qsc_ast::ast::NodeId {
+    let unit = store.get(package).expect("package should be in store");
+    unit.ast
+        .names
+        .iter()
+        .filter_map(|(_, res)| match res {
+            Res::Local(id) => Some(*id),
+            _ => None,
+        })
+        .min()
+        .expect("package should have a local")
+}
+
+#[test]
+fn rename_callable_with_qualified_references() {
+    let source = indoc! {"
+        namespace Foo {
+            function Bar() : Unit {}
+        }
+        namespace Test {
+            open Foo as F;
+            function Main() : Unit {
+                Foo.Bar();
+                F.Bar();
+            }
+        }
+    "};
+    let (store, package) = compile(source);
+    let edits =
+        super::item(&store, package, LocalItemId::from(1), "Baz").expect("rename should succeed");
+    assert_eq!(
+        apply(source, edits),
+        indoc! {"
+            namespace Foo {
+                function Baz() : Unit {}
+            }
+            namespace Test {
+                open Foo as F;
+                function Main() : Unit {
+                    Foo.Baz();
+                    F.Baz();
+                }
+            }
+        "}
+    );
+}
+
+#[test]
+fn rename_udt_renames_type_and_constructor_references() {
+    let source = indoc! {"
+        namespace Foo {
+            newtype Pair = (Int, Int);
+            function Swap(p : Pair) : Pair {
+                let (a, b) = p!;
+                Pair(b, a)
+            }
+        }
+    "};
+    let (store, package) = compile(source);
+    let edits = super::item(&store, package, LocalItemId::from(1), "Couple")
+        .expect("rename should succeed");
+    assert_eq!(
+        apply(source, edits),
+        indoc! {"
+            namespace Foo {
+                newtype Couple = (Int, Int);
+                function Swap(p : Couple) : Couple {
+                    let (a, b) = p!;
+                    Couple(b, a)
+                }
+            }
+        "}
+    );
+}
+
+#[test]
+fn rename_callable_to_existing_item_conflicts() {
+    let source = indoc! {"
+        namespace Foo {
+            function Bar() : Unit {}
+            function Baz() : Unit {}
+        }
+    "};
+    let (store, package) = compile(source);
+    assert_eq!(
+        super::item(&store, package, LocalItemId::from(1), "Baz"),
+        Err(Error::Conflict {
+            name: "Baz".to_string(),
+            namespace: "Foo".to_string(),
+        })
+    );
+}
+
+#[test]
+fn rename_to_keyword_is_invalid() {
+    let source = "namespace Foo { function Bar() : Unit {} }";
+    let (store, package) = compile(source);
+    assert_eq!(
+        super::item(&store, package, LocalItemId::from(1), "let"),
+        Err(Error::InvalidName("let".to_string()))
+    );
+}
+
+#[test]
+fn rename_local() {
+    let source = indoc! {"
+        namespace Foo {
+            function Bar() : Int {
+                let x = 1;
+                x + x
+            }
+        }
+    "};
+    let (store, package) = compile(source);
+    let decl = first_local(&store, package);
+    let edits = super::local(&store, package, decl, "y").expect("rename should succeed");
+    assert_eq!(
+        apply(source, edits),
+        indoc! {"
+            namespace Foo {
+                function Bar() : Int {
+                    let y = 1;
+                    y + y
+                }
+            }
+        "}
+    );
+}
+
+#[test]
+fn rename_local_to_shadowing_name_conflicts() {
+    let source = indoc! {"
+        namespace Foo {
+            function Bar() : Int {
+                let x = 1;
+                let y = 2;
+                x + y
+            }
+        }
+    "};
+    let (store, package) = compile(source);
+    let decl = first_local(&store, package);
+    assert!(matches!(
+        super::local(&store, package, decl, "y"),
+        Err(Error::LocalConflict(..))
+    ));
+}
+
+#[test]
+fn rename_local_capturing_item_reference_conflicts() {
+    let source = indoc! {"
+        namespace Foo {
+            function Baz() : Int { 0 }
+            function Bar() : Int {
+                let x = 1;
+                x + Baz()
+            }
+        }
+    "};
+    let (store, package) = compile(source);
+    let decl = first_local(&store, package);
+    assert!(matches!(
+        super::local(&store, package, decl, "Baz"),
+        Err(Error::LocalConflict(..))
+    ));
+}
+
+#[test]
+fn rename_namespace_leaves_open_alias() {
+    let source = indoc! {"
+        namespace Foo {
+            function Bar() : Unit {}
+        }
+        namespace Test {
+            open Foo as F;
+            function Main() : Unit {
+                Foo.Bar();
+                F.Bar();
+            }
+        }
+    "};
+    let (store, package) = compile(source);
+    let edits =
+        super::namespace(&store, package, "Foo", "Qux.Quux").expect("rename should succeed");
+    assert_eq!(
+        apply(source, edits),
+        indoc! {"
+            namespace Qux.Quux {
+                function Bar() : Unit {}
+            }
+            namespace Test {
+                open Qux.Quux as F;
+                function Main() : Unit {
+                    Qux.Quux.Bar();
+                    F.Bar();
+                }
+            }
+        "}
+    );
+}
+
+#[test]
+fn rename_namespace_to_existing_namespace_conflicts() {
+    let source = indoc! {"
+        namespace Foo {}
+        namespace Test {}
+    "};
+    let (store, package) = compile(source);
+    assert_eq!(
+        super::namespace(&store, package, "Foo", "Test"),
+        Err(Error::NamespaceConflict("Test".to_string()))
+    );
+}
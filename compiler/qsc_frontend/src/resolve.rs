@@ -1,15 +1,20 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod cache;
 #[cfg(test)]
 mod tests;
 
-use miette::Diagnostic;
+pub use cache::Cache;
+
+use miette::{Diagnostic, LabeledSpan};
 use qsc_ast::{
     ast::{self, CallableBody, CallableDecl, Ident, NodeId, SpecBody, SpecGen, TopLevelNode},
     visit::{self as ast_visit, walk_attr, Visitor as AstVisitor},
 };
-use qsc_data_structures::{index_map::IndexMap, span::Span};
+use qsc_data_structures::{
+    cancellation::CancellationToken, index_map::IndexMap, lints::Lints, span::Span,
+};
 use qsc_hir::{
     assigner::Assigner,
     global,
@@ -17,12 +22,14 @@ use qsc_hir::{
     ty::{ParamId, Prim},
 };
 use rustc_hash::{FxHashMap, FxHashSet};
-use std::{collections::hash_map::Entry, rc::Rc, str::FromStr, vec};
+use std::{collections::hash_map::Entry, mem::take, rc::Rc, str::FromStr, sync::Arc};
 use thiserror::Error;
 
-use crate::compile::preprocess::TrackedName;
+use crate::compile::{preprocess::TrackedName, CompileOptions, SourceMap};
 
-const PRELUDE: &[&str] = &[
+/// The namespaces that are implicitly opened in every compilation, unless the compilation is
+/// configured with a different prelude.
+pub const PRELUDE: &[&str] = &[
     "Microsoft.Quantum.Canon",
     "Microsoft.Quantum.Core",
     "Microsoft.Quantum.Intrinsic",
@@ -48,6 +55,77 @@ pub enum Res {
     UnitTy,
 }
 
+/// Explanations of how names resolved, keyed by the node ID of the path or identifier.
+pub type Explanations = IndexMap<NodeId, Explanation>;
+
+/// An explanation of how a name resolved, for tooling.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Explanation {
+    /// Where the resolution was found.
+    pub origin: Origin,
+    /// The other resolutions that were in scope for the name but lost to `origin`, from highest to
+    /// lowest precedence.
+    pub shadowed: Vec<(Res, Origin)>,
+}
+
+/// The spans of the names that refer to each item and local, built during resolution.
+///
+/// Declarations are not references, so a declaration's own name is not included.
+#[derive(Clone, Debug, Default)]
+pub struct References {
+    items: FxHashMap<ItemId, Vec<Span>>,
+    locals: FxHashMap<NodeId, Vec<Span>>,
+}
+
+impl References {
+    /// The spans of the references to the item, in the order they were resolved. Items declared in
+    /// the package being compiled have no package ID.
+    #[must_use]
+    pub fn item(&self, id: ItemId) -> &[Span] {
+        self.items.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The spans of the references to the local declared by the given node, in the order they were
+    /// resolved.
+    #[must_use]
+    pub fn local(&self, id: NodeId) -> &[Span] {
+        self.locals.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    fn insert(&mut self, res: Res, span: Span) {
+        match res {
+            Res::Item(id, _) => self.items.entry(id).or_default().push(span),
+            Res::Local(id) => self.locals.entry(id).or_default().push(span),
+            Res::Param(_) | Res::PrimTy(_) | Res::UnitTy => {}
+        }
+    }
+}
+
+/// An item of an earlier fragment that a later fragment declared again.
+pub(super) struct Redefinition {
+    /// The name of the item, qualified with its namespace if it has one.
+    pub(super) name: Arc<str>,
+    /// The item that was declared before.
+    pub(super) old: ItemId,
+    /// The span of the new declaration's name.
+    pub(super) span: Span,
+}
+
+/// Where a resolution was found.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Origin {
+    /// A local, parameter, or item declared in the scope with the given span.
+    Scope(Span),
+    /// An item in a namespace opened by the open statement whose namespace name has the given span.
+    Open { namespace: Arc<str>, span: Span },
+    /// An item in a namespace that the prelude implicitly opens.
+    Prelude(Arc<str>),
+    /// An item named by a path qualified with its namespace.
+    Namespace(Arc<str>),
+    /// An item named by a path qualified with the alias of its package.
+    Package(Arc<str>),
+}
+
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub(super) enum Error {
     #[error("`{name}` could refer to the item in `{first_open}` or `{second_open}`")]
@@ -65,12 +143,15 @@ pub(super) enum Error {
     },
 
     #[error("`{name}` could refer to the item in `{candidate_a}` or an item in `{candidate_b}`")]
-    #[diagnostic(help("both namespaces are implicitly opened by the prelude"))]
+    #[diagnostic(help(
+        "both namespaces are implicitly opened by the prelude, which contains: {prelude}"
+    ))]
     #[diagnostic(code("Qsc.Resolve.AmbiguousPrelude"))]
     AmbiguousPrelude {
         name: String,
         candidate_a: String,
         candidate_b: String,
+        prelude: String,
         #[label("ambiguous name")]
         span: Span,
     },
@@ -91,10 +172,24 @@ pub(super) enum Error {
     #[diagnostic(code("Qsc.Resolve.DuplicateIntrinsic"))]
     DuplicateIntrinsic(String, #[label] Span),
 
+    #[error("export of `{0}` outside of a namespace")]
+    #[diagnostic(help("export statements are only allowed directly inside a namespace"))]
+    #[diagnostic(code("Qsc.Resolve.LocalExport"))]
+    LocalExport(String, #[label] Span),
+
     #[error("`{0}` not found")]
     #[diagnostic(code("Qsc.Resolve.NotFound"))]
     NotFound(String, #[label] Span),
 
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NotFoundRepeated(RepeatedNotFound),
+
+    #[error("package `{0}` not found")]
+    #[diagnostic(help("a package-qualified path must name the alias of a dependency"))]
+    #[diagnostic(code("Qsc.Resolve.NotFound"))]
+    PackageNotFound(String, #[label] Span),
+
     #[error("`{0}` not found")]
     #[diagnostic(help(
         "found a matching item `{1}` that is not available for the current compilation configuration"
@@ -106,6 +201,171 @@ pub(super) enum Error {
     #[diagnostic(help("this item is not implemented and cannot be used"))]
     #[diagnostic(code("Qsc.Resolve.Unimplemented"))]
     Unimplemented(String, #[label] Span),
+
+    #[error("too many name errors in `{0}`")]
+    #[diagnostic(help("{1} more name errors in this source were not reported"))]
+    #[diagnostic(code("Qsc.Resolve.TooManyErrors"))]
+    TooManyErrors(String, usize, #[label("first unreported error")] Span),
+}
+
+impl Error {
+    fn span(&self) -> Span {
+        match self {
+            Error::Ambiguous { name_span, .. } => *name_span,
+            Error::NotFoundRepeated(error) => error.span,
+            Error::AmbiguousPrelude { span, .. }
+            | Error::Duplicate(_, _, span)
+            | Error::DuplicateBinding(_, span)
+            | Error::DuplicateIntrinsic(_, span)
+            | Error::LocalExport(_, span)
+            | Error::NotFound(_, span)
+            | Error::PackageNotFound(_, span)
+            | Error::NotAvailable(_, _, span)
+            | Error::Unimplemented(_, span)
+            | Error::TooManyErrors(_, _, span) => *span,
+        }
+    }
+}
+
+/// A name that was not found in more than one place in the same source.
+#[derive(Clone, Debug, Error)]
+#[error("`{name}` not found")]
+pub(super) struct RepeatedNotFound {
+    name: String,
+    span: Span,
+    others: Vec<Span>,
+}
+
+impl Diagnostic for RepeatedNotFound {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new("Qsc.Resolve.NotFound"))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "`{}` is not found in {} places",
+            self.name,
+            self.others.len() + 1
+        )))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(
+            std::iter::once(LabeledSpan::new_with_span(None, self.span)).chain(
+                self.others.iter().map(|&span| {
+                    LabeledSpan::new_with_span(Some("also not found here".to_string()), span)
+                }),
+            ),
+        ))
+    }
+}
+
+/// Groups the errors for a name that is not found more than once in the same source into one
+/// error, then keeps at most `max_errors` errors for each source and reports how many of the rest
+/// were left out.
+pub(super) fn consolidate_errors(
+    errors: Vec<Error>,
+    sources: &SourceMap,
+    max_errors: Option<usize>,
+) -> Vec<Error> {
+    let source_of = |span: Span| sources.find_by_offset(span.lo).map(|source| source.offset);
+
+    let mut grouped: Vec<Error> = Vec::with_capacity(errors.len());
+    let mut first_not_found = FxHashMap::default();
+    for error in errors {
+        let Error::NotFound(name, span) = error else {
+            grouped.push(error);
+            continue;
+        };
+        match first_not_found.entry((source_of(span), name)) {
+            Entry::Occupied(entry) => match &mut grouped[*entry.get()] {
+                Error::NotFound(name, first) => {
+                    let repeated = RepeatedNotFound {
+                        name: name.clone(),
+                        span: *first,
+                        others: vec![span],
+                    };
+                    grouped[*entry.get()] = Error::NotFoundRepeated(repeated);
+                }
+                Error::NotFoundRepeated(repeated) => repeated.others.push(span),
+                _ => unreachable!("grouped error should be a not found error"),
+            },
+            Entry::Vacant(entry) => {
+                let name = entry.key().1.clone();
+                entry.insert(grouped.len());
+                grouped.push(Error::NotFound(name, span));
+            }
+        }
+    }
+
+    let Some(max_errors) = max_errors else {
+        return grouped;
+    };
+    let mut counts = FxHashMap::default();
+    let mut omitted = Vec::<(Option<u32>, usize, Span)>::new();
+    grouped.retain(|error| {
+        let source = source_of(error.span());
+        let count = counts.entry(source).or_insert(0);
+        *count += 1;
+        if *count <= max_errors {
+            return true;
+        }
+        match omitted
+            .iter_mut()
+            .find(|(omitted_source, ..)| *omitted_source == source)
+        {
+            Some((_, omitted, _)) => *omitted += 1,
+            None => omitted.push((source, 1, error.span())),
+        }
+        false
+    });
+    grouped.extend(omitted.into_iter().map(|(source, omitted, span)| {
+        let name = source
+            .and_then(|offset| sources.find_by_offset(offset))
+            .map_or_else(String::new, |source| source.name.to_string());
+        Error::TooManyErrors(name, omitted, span)
+    }));
+    grouped
+}
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub(super) enum Warning {
+    #[error("unused open of namespace `{0}`")]
+    #[diagnostic(help("no name is resolved through this open statement, so it can be removed"))]
+    #[diagnostic(code("Qsc.Resolve.UnusedOpen"), severity(Warning))]
+    UnusedOpen(String, #[label("unused open")] Span),
+
+    #[error("use of deprecated item `{name}`")]
+    #[diagnostic(code("Qsc.Resolve.Deprecated"), severity(Warning))]
+    Deprecated {
+        name: String,
+        #[help]
+        help: Option<String>,
+        #[label]
+        span: Span,
+    },
+
+    #[error("`{name}` shadows a local variable from an enclosing scope")]
+    #[diagnostic(help("use `set` to update the shadowed variable, or choose a different name"))]
+    #[diagnostic(code("Qsc.Resolve.ShadowedLocal"), severity(Warning))]
+    ShadowedLocal {
+        name: String,
+        #[label("new binding")]
+        span: Span,
+        #[label("shadowed binding")]
+        shadowed_span: Span,
+    },
+}
+
+impl Warning {
+    /// The span that the warning is reported at.
+    pub(super) fn span(&self) -> Span {
+        match self {
+            Warning::UnusedOpen(_, span)
+            | Warning::Deprecated { span, .. }
+            | Warning::ShadowedLocal { span, .. } => *span,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -115,21 +375,19 @@ pub struct Scope {
     span: Span,
     kind: ScopeKind,
     /// Open statements. The key is the namespace name or alias.
-    opens: FxHashMap<Rc<str>, Vec<Open>>,
+    opens: FxHashMap<Arc<str>, Vec<Open>>,
     /// Local newtype declarations.
-    tys: FxHashMap<Rc<str>, ItemId>,
+    tys: FxHashMap<Arc<str>, ItemId>,
     /// Local callable and newtype declarations.
-    terms: FxHashMap<Rc<str>, ItemId>,
+    terms: FxHashMap<Arc<str>, ItemId>,
     /// Local variables, including callable parameters, for loop bindings, etc.
-    /// The u32 is the `valid_at` offset - the lowest offset at which the variable name is available.
-    /// It's used to determine which variables are visible at a specific offset in the scope.
-    ///
-    /// Bug: Because we keep track of only one `valid_at` offset per name,
-    /// when a variable is later shadowed in the same scope,
-    /// it is missed in the list. https://github.com/microsoft/qsharp/issues/897
-    vars: FxHashMap<Rc<str>, (u32, NodeId)>,
+    /// Each name maps to all of its bindings in this scope, in declaration order.
+    /// The u32 is the `valid_at` offset - the lowest offset at which that binding is available.
+    /// A binding stays visible until the next binding of the same name becomes available,
+    /// so the list determines which variable a name refers to at a specific offset in the scope.
+    vars: FxHashMap<Arc<str>, Vec<(u32, NodeId)>>,
     /// Type parameters.
-    ty_vars: FxHashMap<Rc<str>, ParamId>,
+    ty_vars: FxHashMap<Arc<str>, ParamId>,
 }
 
 impl Scope {
@@ -152,6 +410,12 @@ impl Scope {
         };
         items.get(name)
     }
+
+    /// Unbinds the local item with the name, returning it.
+    fn tombstone(&mut self, name: &str) -> Option<ItemId> {
+        let ty = self.tys.remove(name);
+        self.terms.remove(name).or(ty)
+    }
 }
 
 type ScopeId = usize;
@@ -205,6 +469,101 @@ impl Locals {
         all_locals
     }
 
+    /// The global names that can be written without qualification at the offset: items declared
+    /// in the enclosing namespace, in namespaces opened without an alias, and in the prelude,
+    /// followed by the open aliases and every namespace.
+    #[must_use]
+    pub fn get_members_at_offset(&self, globals: &GlobalScope, offset: u32) -> Vec<Member> {
+        let mut namespaces = Vec::new();
+        let mut aliases = Vec::new();
+        self.for_each_scope_at_offset(offset, |scope| {
+            if let ScopeKind::Namespace(namespace) = &scope.kind {
+                namespaces.push(Arc::clone(namespace));
+            }
+            for (key, opens) in &scope.opens {
+                for open in opens {
+                    if key.is_empty() {
+                        namespaces.push(Arc::clone(&open.namespace));
+                    } else {
+                        aliases.push(Member {
+                            name: Arc::clone(key),
+                            namespace: Arc::clone(&open.namespace),
+                            kind: MemberKind::Namespace,
+                        });
+                    }
+                }
+            }
+        });
+        namespaces.extend(globals.prelude.iter().cloned());
+
+        let mut seen = FxHashSet::default();
+        namespaces.retain(|namespace| seen.insert(Arc::clone(namespace)));
+        aliases.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut all_namespaces = globals.namespaces.iter().cloned().collect::<Vec<_>>();
+        all_namespaces.sort();
+
+        let mut members = namespaces
+            .iter()
+            .flat_map(|namespace| globals.members(namespace))
+            .collect::<Vec<_>>();
+        members.extend(aliases);
+        members.extend(all_namespaces.into_iter().map(|namespace| Member {
+            name: Arc::clone(&namespace),
+            namespace,
+            kind: MemberKind::Namespace,
+        }));
+        members
+    }
+
+    /// The global names that can follow `qualifier.` at the offset, where the qualifier is an open
+    /// alias, a namespace, or a prefix of a namespace. Items come first, followed by the next
+    /// segments of the namespaces that start with the qualifier.
+    #[must_use]
+    pub fn get_path_members_at_offset(
+        &self,
+        globals: &GlobalScope,
+        offset: u32,
+        qualifier: &str,
+    ) -> Vec<Member> {
+        let mut namespaces = Vec::new();
+        self.for_each_scope_at_offset(offset, |scope| {
+            if let Some(opens) = scope.opens.get(qualifier) {
+                namespaces.extend(opens.iter().map(|open| Arc::clone(&open.namespace)));
+            }
+        });
+        if let Some(namespace) = globals.namespaces.get(qualifier) {
+            namespaces.push(Arc::clone(namespace));
+        }
+
+        let mut seen = FxHashSet::default();
+        namespaces.retain(|namespace| seen.insert(Arc::clone(namespace)));
+
+        let prefix = format!("{qualifier}.");
+        let mut children = globals
+            .namespaces
+            .iter()
+            .filter_map(|namespace| {
+                let rest = namespace.strip_prefix(&prefix)?;
+                let segment = rest.split('.').next().unwrap_or(rest);
+                Some(Arc::<str>::from(segment))
+            })
+            .collect::<Vec<_>>();
+        children.sort();
+        children.dedup();
+
+        let mut members = namespaces
+            .iter()
+            .flat_map(|namespace| globals.members(namespace))
+            .collect::<Vec<_>>();
+        members.extend(children.into_iter().map(|segment| Member {
+            namespace: format!("{prefix}{segment}").into(),
+            name: segment,
+            kind: MemberKind::Namespace,
+        }));
+        members
+    }
+
     fn for_each_scope_at_offset<F>(&self, offset: u32, mut f: F)
     where
         F: FnMut(&Scope),
@@ -221,7 +580,7 @@ impl Locals {
 
 #[derive(Debug)]
 pub struct Local {
-    pub name: Rc<str>,
+    pub name: Arc<str>,
     pub kind: LocalKind,
 }
 
@@ -235,15 +594,95 @@ pub enum LocalKind {
     Var(NodeId),
 }
 
-#[derive(Debug, Clone, Default)]
+/// A global name that is visible at an offset, for completion.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Member {
+    /// The name, as written after any qualifier.
+    pub name: Arc<str>,
+    /// The namespace that declares the item, or the full name of the namespace.
+    pub namespace: Arc<str>,
+    pub kind: MemberKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemberKind {
+    /// A type. An item's resolution includes its availability status.
+    Ty(Res),
+    /// A callable or a newtype constructor. An item's resolution includes its availability status.
+    Term(Res),
+    /// A namespace or an open alias of one.
+    Namespace,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct GlobalScope {
-    tys: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Res>>,
-    terms: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Res>>,
-    namespaces: FxHashSet<Rc<str>>,
-    intrinsics: FxHashSet<Rc<str>>,
+    tys: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Res>>,
+    terms: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Res>>,
+    namespaces: FxHashSet<Arc<str>>,
+    intrinsics: FxHashSet<Arc<str>>,
+    /// The public items of each dependency that has an alias, for package-qualified paths.
+    packages: FxHashMap<Arc<str>, GlobalScope>,
+    /// The namespaces that are implicitly opened.
+    prelude: Vec<Arc<str>>,
+    /// The replacements named by deprecated items.
+    replacements: FxHashMap<ItemId, Arc<str>>,
+    /// The items of earlier fragments that later fragments redefined. Names no longer resolve to
+    /// these items, but the items that referred to them before the redefinition still do.
+    tombstones: FxHashSet<ItemId>,
 }
 
 impl GlobalScope {
+    /// The terms and then the types declared in the namespace, each sorted by name.
+    fn members(&self, namespace: &Arc<str>) -> Vec<Member> {
+        let sorted = |items: Option<&FxHashMap<Arc<str>, Res>>| {
+            let mut items = items
+                .into_iter()
+                .flatten()
+                .map(|(name, &res)| (Arc::clone(name), res))
+                .collect::<Vec<_>>();
+            items.sort_by(|a, b| a.0.cmp(&b.0));
+            items
+        };
+        let terms = sorted(self.terms.get(namespace))
+            .into_iter()
+            .map(|(name, res)| (name, MemberKind::Term(res)));
+        let tys = sorted(self.tys.get(namespace))
+            .into_iter()
+            .map(|(name, res)| (name, MemberKind::Ty(res)));
+        terms
+            .chain(tys)
+            .map(|(name, kind)| Member {
+                name,
+                namespace: Arc::clone(namespace),
+                kind,
+            })
+            .collect()
+    }
+
+    /// Whether a later fragment redefined the item.
+    pub(super) fn is_tombstoned(&self, id: ItemId) -> bool {
+        self.tombstones.contains(&id)
+    }
+
+    /// Unbinds the item of the package being compiled that has the name in the namespace, so that a
+    /// new item can take the name, and returns the unbound item.
+    fn tombstone(&mut self, namespace: &str, name: &str) -> Option<ItemId> {
+        let mut tombstoned = None;
+        for namespaces in [&mut self.terms, &mut self.tys] {
+            let Some(items) = namespaces.get_mut(namespace) else {
+                continue;
+            };
+            if let Some(&Res::Item(id, _)) = items.get(name) {
+                if id.package.is_none() {
+                    items.remove(name);
+                    tombstoned = Some(id);
+                }
+            }
+        }
+        self.tombstones.extend(tombstoned);
+        tombstoned
+    }
+
     fn get(&self, kind: NameKind, namespace: &str, name: &str) -> Option<&Res> {
         let namespaces = match kind {
             NameKind::Ty => &self.tys,
@@ -255,7 +694,7 @@ impl GlobalScope {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum ScopeKind {
-    Namespace(Rc<str>),
+    Namespace(Arc<str>),
     Callable,
     Block,
 }
@@ -268,18 +707,37 @@ enum NameKind {
 
 #[derive(Debug, Clone)]
 struct Open {
-    namespace: Rc<str>,
+    namespace: Arc<str>,
     span: Span,
+    /// The span of the whole open statement.
+    item_span: Span,
 }
 
 pub(super) struct Resolver {
     names: Names,
     dropped_names: Vec<TrackedName>,
-    curr_params: Option<FxHashSet<Rc<str>>>,
+    curr_params: Option<FxHashSet<Arc<str>>>,
     curr_scope_chain: Vec<ScopeId>,
     globals: GlobalScope,
     locals: Locals,
     errors: Vec<Error>,
+    /// The spans of the opens that names were resolved through.
+    used_opens: FxHashSet<Span>,
+    /// The span of each local binding, for warnings that point at a shadowed binding.
+    binding_spans: FxHashMap<NodeId, Span>,
+    lints: Lints,
+    /// The most errors to report for each source, if limited.
+    max_errors: Option<usize>,
+    warnings: Vec<Warning>,
+    /// Explanations of resolved names, when enabled.
+    explanations: Option<Explanations>,
+    references: References,
+    /// What resolving the current namespace adds, when it is being cached.
+    recording: Option<cache::Recording>,
+    /// The items that fragments redefined since they were last drained.
+    redefinitions: Vec<Redefinition>,
+    /// Stops resolution before the next namespace when it is cancelled.
+    cancellation: CancellationToken,
 }
 
 impl Resolver {
@@ -292,6 +750,16 @@ impl Resolver {
             locals: Locals::default(),
             curr_scope_chain: Vec::new(),
             errors: Vec::new(),
+            used_opens: FxHashSet::default(),
+            binding_spans: FxHashMap::default(),
+            lints: Lints::default(),
+            max_errors: None,
+            warnings: Vec::new(),
+            explanations: None,
+            references: References::default(),
+            recording: None,
+            redefinitions: Vec::new(),
+            cancellation: CancellationToken::default(),
         }
     }
 
@@ -315,6 +783,34 @@ impl Resolver {
             locals,
             curr_scope_chain: vec![scope_id],
             errors: Vec::new(),
+            used_opens: FxHashSet::default(),
+            binding_spans: FxHashMap::default(),
+            lints: Lints::default(),
+            max_errors: None,
+            warnings: Vec::new(),
+            explanations: None,
+            references: References::default(),
+            recording: None,
+            redefinitions: Vec::new(),
+            cancellation: CancellationToken::default(),
+        }
+    }
+
+    /// Replaces the prelude, the enabled lints, whether names are explained, and the most errors
+    /// reported for each source, which are [`PRELUDE`], none, off, and unlimited by default.
+    pub(super) fn set_options(&mut self, options: &CompileOptions) {
+        self.globals.prelude = options
+            .prelude
+            .iter()
+            .map(|namespace| namespace.as_ref().into())
+            .collect();
+        self.lints = options.lints;
+        self.max_errors = options.max_name_errors;
+        self.cancellation = options.cancellation.clone();
+        if !options.explain_names {
+            self.explanations = None;
+        } else if self.explanations.is_none() {
+            self.explanations = Some(Explanations::new());
         }
     }
 
@@ -326,8 +822,26 @@ impl Resolver {
         &self.locals
     }
 
-    pub(super) fn drain_errors(&mut self) -> vec::Drain<Error> {
-        self.errors.drain(..)
+    pub(super) fn explanations(&self) -> Explanations {
+        self.explanations.clone().unwrap_or_default()
+    }
+
+    pub(super) fn references(&self) -> &References {
+        &self.references
+    }
+
+    pub(super) fn globals(&self) -> &GlobalScope {
+        &self.globals
+    }
+
+    /// Takes the errors found so far, consolidated for the sources like [`consolidate_errors`].
+    pub(super) fn drain_errors(&mut self, sources: &SourceMap) -> Vec<Error> {
+        consolidate_errors(self.errors.drain(..).collect(), sources, self.max_errors)
+    }
+
+    /// Takes the items that fragments redefined so far.
+    pub(super) fn drain_redefinitions(&mut self) -> Vec<Redefinition> {
+        take(&mut self.redefinitions)
     }
 
     pub(super) fn with<'a>(&'a mut self, assigner: &'a mut Assigner) -> With<'a> {
@@ -337,8 +851,40 @@ impl Resolver {
         }
     }
 
-    pub(super) fn into_result(self) -> (Names, Locals, Vec<Error>) {
-        (self.names, self.locals, self.errors)
+    /// Finishes resolution, returning warnings for the opens that no name was resolved through.
+    pub(super) fn into_result(
+        mut self,
+    ) -> (
+        Names,
+        Locals,
+        Explanations,
+        References,
+        GlobalScope,
+        Vec<Error>,
+        Vec<Warning>,
+    ) {
+        let mut unused_opens = self
+            .locals
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.opens.values().flatten())
+            .filter(|open| !self.used_opens.contains(&open.span))
+            .collect::<Vec<_>>();
+        unused_opens.sort_unstable_by_key(|open| open.span);
+        self.warnings.extend(
+            unused_opens
+                .into_iter()
+                .map(|open| Warning::UnusedOpen(open.namespace.to_string(), open.item_span)),
+        );
+        (
+            self.names,
+            self.locals,
+            self.explanations.unwrap_or_default(),
+            self.references,
+            self.globals,
+            self.errors,
+            self.warnings,
+        )
     }
 
     pub(super) fn extend_dropped_names(&mut self, dropped_names: Vec<TrackedName>) {
@@ -346,6 +892,7 @@ impl Resolver {
     }
 
     pub(super) fn bind_fragments(&mut self, ast: &ast::Package, assigner: &mut Assigner) {
+        self.tombstone_redefined(ast);
         for node in &mut ast.nodes.iter() {
             match node {
                 ast::TopLevelNode::Namespace(namespace) => {
@@ -364,11 +911,68 @@ impl Resolver {
                 }
             }
         }
+        bind_global_exports(
+            &mut self.names,
+            &mut self.globals,
+            ast.nodes.iter().filter_map(|node| match node {
+                ast::TopLevelNode::Namespace(namespace) => Some(namespace),
+                ast::TopLevelNode::Stmt(_) => None,
+            }),
+            &mut self.errors,
+        );
+    }
+
+    /// Unbinds the items of earlier fragments that the fragments declare again, so that the new
+    /// declarations replace them instead of clashing with them.
+    fn tombstone_redefined(&mut self, ast: &ast::Package) {
+        for node in &*ast.nodes {
+            match node {
+                ast::TopLevelNode::Namespace(namespace) => {
+                    let namespace_name = namespace.full_name();
+                    for name in namespace.items.iter().filter_map(|item| item_name(item)) {
+                        if let Some(old) = self.globals.tombstone(&namespace_name, &name.name) {
+                            self.redefinitions.push(Redefinition {
+                                name: format!("{namespace_name}.{}", name.name).into(),
+                                old,
+                                span: name.span,
+                            });
+                        }
+                    }
+                }
+                ast::TopLevelNode::Stmt(stmt) => {
+                    let ast::StmtKind::Item(item) = &*stmt.kind else {
+                        continue;
+                    };
+                    let Some(name) = item_name(item) else {
+                        continue;
+                    };
+                    if let Some(old) = self.current_scope_mut().tombstone(&name.name) {
+                        self.globals.tombstones.insert(old);
+                        self.redefinitions.push(Redefinition {
+                            name: Arc::clone(&name.name),
+                            old,
+                            span: name.span,
+                        });
+                    }
+                }
+            }
+        }
     }
 
     fn check_item_status(&mut self, res: Res, name: String, span: Span) {
-        if let Res::Item(_, ItemStatus::Unimplemented) = res {
-            self.errors.push(Error::Unimplemented(name, span));
+        match res {
+            Res::Item(_, ItemStatus::Unimplemented) => {
+                self.errors.push(Error::Unimplemented(name, span));
+            }
+            Res::Item(id, ItemStatus::Deprecated) => {
+                let help = self
+                    .globals
+                    .replacements
+                    .get(&id)
+                    .map(|replacement| format!("use `{replacement}` instead"));
+                self.warnings.push(Warning::Deprecated { name, help, span });
+            }
+            _ => {}
         }
     }
 
@@ -381,29 +985,83 @@ impl Resolver {
             self.locals.get_scopes(&self.curr_scope_chain),
             name,
             &namespace,
+            &mut self.used_opens,
         ) {
             Ok(res) => {
                 self.check_item_status(res, name.name.to_string(), name.span);
-                self.names.insert(name.id, res);
+                self.insert_name(name.id, res);
+                self.insert_reference(res, name.span);
+                self.explain(kind, name.id, res, None, name, None);
             }
             Err(err) => self.errors.push(err),
         }
     }
 
+    /// Records how a name resolved to `res`, if explanations are enabled.
+    fn explain(
+        &mut self,
+        kind: NameKind,
+        id: NodeId,
+        res: Res,
+        package: Option<&Ident>,
+        name: &Ident,
+        namespace: Option<&Ident>,
+    ) {
+        let Some(explanations) = &mut self.explanations else {
+            return;
+        };
+        let mut candidates = match package {
+            Some(package) => vec![(res, Origin::Package(Arc::clone(&package.name)))],
+            None => explain_candidates(
+                kind,
+                &self.globals,
+                self.locals.get_scopes(&self.curr_scope_chain),
+                name,
+                namespace,
+            ),
+        };
+        let Some(index) = candidates
+            .iter()
+            .position(|(candidate, _)| *candidate == res)
+        else {
+            return;
+        };
+        let (_, origin) = candidates.remove(index);
+        candidates.retain(|(candidate, _)| *candidate != res);
+        let shadowed = candidates;
+        explanations.insert(id, Explanation { origin, shadowed });
+    }
+
     fn resolve_path(&mut self, kind: NameKind, path: &ast::Path) {
         let name = &path.name;
         let namespace = &path.namespace;
 
-        match resolve(
-            kind,
-            &self.globals,
-            self.locals.get_scopes(&self.curr_scope_chain),
-            name,
-            namespace,
-        ) {
+        let result = match &path.package {
+            Some(package) => {
+                resolve_in_package(kind, &self.globals, package, name, namespace.as_deref())
+            }
+            None => resolve(
+                kind,
+                &self.globals,
+                self.locals.get_scopes(&self.curr_scope_chain),
+                name,
+                namespace,
+                &mut self.used_opens,
+            ),
+        };
+        match result {
             Ok(res) => {
                 self.check_item_status(res, path.name.name.to_string(), path.span);
-                self.names.insert(path.id, res);
+                self.insert_name(path.id, res);
+                self.insert_reference(res, path.name.span);
+                self.explain(
+                    kind,
+                    path.id,
+                    res,
+                    path.package.as_deref(),
+                    name,
+                    namespace.as_deref(),
+                );
             }
             Err(err) => {
                 if let Error::NotFound(name, span) = err {
@@ -437,22 +1095,39 @@ impl Resolver {
         self.bind_pat_recursive(pat, valid_at, &mut bindings);
     }
 
+    /// Binds a single local name, which is valid after `valid_at`.
+    fn bind_local(&mut self, name: &Ident, valid_at: u32) {
+        if self.lints.contains(Lints::ShadowedLocal) {
+            self.check_shadowed_local(name, valid_at);
+        }
+        self.insert_local(name, valid_at);
+    }
+
+    fn insert_local(&mut self, name: &Ident, valid_at: u32) {
+        self.binding_spans.insert(name.id, name.span);
+        self.insert_name(name.id, Res::Local(name.id));
+        self.current_scope_mut()
+            .vars
+            .entry(Arc::clone(&name.name))
+            .or_default()
+            .push((valid_at, name.id));
+    }
+
     fn bind_pat_recursive(
         &mut self,
         pat: &ast::Pat,
         valid_at: u32,
-        bindings: &mut FxHashSet<Rc<str>>,
+        bindings: &mut FxHashSet<Arc<str>>,
     ) {
         match &*pat.kind {
             ast::PatKind::Bind(name, _) => {
-                if !bindings.insert(Rc::clone(&name.name)) {
+                if bindings.insert(Arc::clone(&name.name)) {
+                    self.bind_local(name, valid_at);
+                } else {
                     self.errors
                         .push(Error::DuplicateBinding(name.name.to_string(), name.span));
+                    self.insert_local(name, valid_at);
                 }
-                self.names.insert(name.id, Res::Local(name.id));
-                self.current_scope_mut()
-                    .vars
-                    .insert(Rc::clone(&name.name), (valid_at, name.id));
             }
             ast::PatKind::Discard(_) | ast::PatKind::Elided | ast::PatKind::Err => {}
             ast::PatKind::Paren(pat) => self.bind_pat_recursive(pat, valid_at, bindings),
@@ -462,16 +1137,57 @@ impl Resolver {
         }
     }
 
-    fn bind_open(&mut self, name: &ast::Ident, alias: &Option<Box<ast::Ident>>) {
-        let alias = alias.as_ref().map_or("".into(), |a| Rc::clone(&a.name));
-        if self.globals.namespaces.contains(&name.name) {
+    /// Warns if a new binding of `name` shadows a local variable or parameter from an enclosing
+    /// scope. Bindings in the same scope and in scopes outside the current callable are ignored.
+    fn check_shadowed_local(&mut self, name: &Ident, valid_at: u32) {
+        let shadowed = self
+            .locals
+            .get_scopes(&self.curr_scope_chain)
+            .scan(false, |past_callable, scope| {
+                let visible = !*past_callable;
+                *past_callable |= scope.kind == ScopeKind::Callable;
+                visible.then_some(scope)
+            })
+            .skip(1)
+            .find_map(|scope| {
+                scope
+                    .vars
+                    .get(&name.name)?
+                    .iter()
+                    .rev()
+                    .find(|(binding_valid_at, _)| *binding_valid_at <= valid_at)
+            })
+            .and_then(|(_, id)| self.binding_spans.get(id));
+        if let Some(&shadowed_span) = shadowed {
+            self.warnings.push(Warning::ShadowedLocal {
+                name: name.name.to_string(),
+                span: name.span,
+                shadowed_span,
+            });
+        }
+    }
+
+    fn bind_open(&mut self, name: &ast::Ident, alias: &Option<Box<ast::Ident>>, item_span: Span) {
+        let alias = alias.as_ref().map_or("".into(), |a| Arc::clone(&a.name));
+        let current_namespace = self
+            .locals
+            .get_scopes(&self.curr_scope_chain)
+            .find_map(|scope| match &scope.kind {
+                ScopeKind::Namespace(namespace) => Some(Arc::clone(namespace)),
+                _ => None,
+            });
+        if let Some(namespace) = relative_namespaces(current_namespace.as_deref(), &name.name)
+            .into_iter()
+            .find(|namespace| self.globals.namespaces.contains(namespace))
+        {
             self.current_scope_mut()
                 .opens
                 .entry(alias)
                 .or_default()
                 .push(Open {
-                    namespace: Rc::clone(&name.name),
+                    namespace,
                     span: name.span,
+                    item_span,
                 });
         } else {
             self.errors
@@ -481,23 +1197,27 @@ impl Resolver {
 
     pub(super) fn bind_local_item(&mut self, assigner: &mut Assigner, item: &ast::Item) {
         match &*item.kind {
-            ast::ItemKind::Open(name, alias) => self.bind_open(name, alias),
+            ast::ItemKind::Open(name, alias) => self.bind_open(name, alias, item.span),
             ast::ItemKind::Callable(decl) => {
-                let id = intrapackage(assigner.next_item());
-                self.names.insert(
+                let id = self.next_local_item(assigner);
+                self.insert_name(
                     decl.name.id,
                     Res::Item(
                         id,
                         ItemStatus::from_attrs(&ast_attrs_as_hir_attrs(&item.attrs)),
                     ),
                 );
+                bind_defaults(decl, |param| {
+                    let default = self.next_local_item(assigner);
+                    self.insert_name(param, Res::Item(default, ItemStatus::Available));
+                });
                 self.current_scope_mut()
                     .terms
-                    .insert(Rc::clone(&decl.name.name), id);
+                    .insert(Arc::clone(&decl.name.name), id);
             }
             ast::ItemKind::Ty(name, _) => {
-                let id = intrapackage(assigner.next_item());
-                self.names.insert(
+                let id = self.next_local_item(assigner);
+                self.insert_name(
                     name.id,
                     Res::Item(
                         id,
@@ -505,22 +1225,47 @@ impl Resolver {
                     ),
                 );
                 let scope = self.current_scope_mut();
-                scope.tys.insert(Rc::clone(&name.name), id);
-                scope.terms.insert(Rc::clone(&name.name), id);
+                scope.tys.insert(Arc::clone(&name.name), id);
+                scope.terms.insert(Arc::clone(&name.name), id);
             }
+            ast::ItemKind::Export(path, _) => self
+                .errors
+                .push(Error::LocalExport(path_name(path), path.span)),
             ast::ItemKind::Err => {}
         }
     }
 
     fn bind_type_parameters(&mut self, decl: &CallableDecl) {
-        decl.generics.iter().enumerate().for_each(|(ix, ident)| {
+        decl.generics.iter().enumerate().for_each(|(ix, param)| {
             self.current_scope_mut()
                 .ty_vars
-                .insert(Rc::clone(&ident.name), ix.into());
-            self.names.insert(ident.id, Res::Param(ix.into()));
+                .insert(Arc::clone(&param.ty.name), ix.into());
+            self.insert_name(param.ty.id, Res::Param(ix.into()));
         });
     }
 
+    fn next_local_item(&mut self, assigner: &mut Assigner) -> ItemId {
+        let item = assigner.next_item();
+        if let Some(recording) = &mut self.recording {
+            recording.local_items.push(item);
+        }
+        intrapackage(item)
+    }
+
+    fn insert_name(&mut self, id: NodeId, res: Res) {
+        self.names.insert(id, res);
+        if let Some(recording) = &mut self.recording {
+            recording.names.push((id, res));
+        }
+    }
+
+    fn insert_reference(&mut self, res: Res, span: Span) {
+        self.references.insert(res, span);
+        if let Some(recording) = &mut self.recording {
+            recording.references.push((res, span));
+        }
+    }
+
     fn push_scope(&mut self, span: Span, kind: ScopeKind) {
         let scope_id = self.locals.push_scope(Scope::new(kind, span));
         self.curr_scope_chain.push(scope_id);
@@ -592,11 +1337,15 @@ impl With<'_> {
 
 impl AstVisitor<'_> for With<'_> {
     fn visit_namespace(&mut self, namespace: &ast::Namespace) {
-        let kind = ScopeKind::Namespace(Rc::clone(&namespace.name.name));
+        if self.resolver.cancellation.is_cancelled() {
+            return;
+        }
+
+        let kind = ScopeKind::Namespace(namespace.full_name());
         self.with_scope(namespace.span, kind, |visitor| {
             for item in &*namespace.items {
                 if let ast::ItemKind::Open(name, alias) = &*item.kind {
-                    visitor.resolver.bind_open(name, alias);
+                    visitor.resolver.bind_open(name, alias, item.span);
                 }
             }
 
@@ -612,10 +1361,10 @@ impl AstVisitor<'_> for With<'_> {
     }
 
     fn visit_callable_decl(&mut self, decl: &ast::CallableDecl) {
-        fn collect_param_names(pat: &ast::Pat, names: &mut FxHashSet<Rc<str>>) {
+        fn collect_param_names(pat: &ast::Pat, names: &mut FxHashSet<Arc<str>>) {
             match &*pat.kind {
                 ast::PatKind::Bind(name, _) => {
-                    names.insert(Rc::clone(&name.name));
+                    names.insert(Arc::clone(&name.name));
                 }
                 ast::PatKind::Discard(_) | ast::PatKind::Elided | ast::PatKind::Err => {}
                 ast::PatKind::Paren(pat) => collect_param_names(pat, names),
@@ -631,8 +1380,24 @@ impl AstVisitor<'_> for With<'_> {
             visitor.resolver.bind_type_parameters(decl);
             // The parameter bindings are valid after the end of the input pattern.
             // (More accurately, in the callable body, but we don't have a start offset for that).
+            // Default values are resolved before the parameters are bound, so that they can't
+            // refer to the other parameters.
+            decl.defaults
+                .iter()
+                .flatten()
+                .for_each(|value| visitor.visit_expr(value));
             visitor.resolver.bind_pat(&decl.input, decl.input.span.hi);
-            ast_visit::walk_callable_decl(visitor, decl);
+            visitor.visit_pat(&decl.input);
+            visitor.visit_ty(&decl.output);
+            decl.functors
+                .iter()
+                .for_each(|f| visitor.visit_functor_expr(f));
+            match &*decl.body {
+                CallableBody::Block(block) => visitor.visit_block(block),
+                CallableBody::Specs(specs) => {
+                    specs.iter().for_each(|spec| visitor.visit_spec_decl(spec));
+                }
+            }
         });
         self.resolver.curr_params = prev_param_names;
     }
@@ -712,7 +1477,24 @@ impl AstVisitor<'_> for With<'_> {
                     visitor.visit_expr(output);
                 });
             }
+            ast::ExprKind::Match(scrutinee, arms) => {
+                self.visit_expr(scrutinee);
+                for arm in &**arms {
+                    self.with_scope(arm.span, ScopeKind::Block, |visitor| {
+                        if let ast::MatchPatKind::Bind(name) = &*arm.pat.kind {
+                            visitor.resolver.bind_local(name, arm.span.lo);
+                        }
+                        visitor.visit_expr(&arm.body);
+                    });
+                }
+            }
             ast::ExprKind::Path(path) => self.resolver.resolve_path(NameKind::Term, path),
+            ast::ExprKind::Struct(path, fields) => {
+                self.resolver.resolve_path(NameKind::Ty, path);
+                for field in &**fields {
+                    self.visit_expr(&field.value);
+                }
+            }
             ast::ExprKind::TernOp(ast::TernOp::Update, container, index, replace)
             | ast::ExprKind::AssignUpdate(container, index, replace) => {
                 self.visit_expr(container);
@@ -739,7 +1521,7 @@ pub(super) struct GlobalTable {
 
 impl GlobalTable {
     pub(super) fn new() -> Self {
-        let builtins: [(Rc<str>, Res); 10] = [
+        let builtins: [(Arc<str>, Res); 10] = [
             ("BigInt".into(), Res::PrimTy(Prim::BigInt)),
             ("Bool".into(), Res::PrimTy(Prim::Bool)),
             ("Double".into(), Res::PrimTy(Prim::Double)),
@@ -751,20 +1533,19 @@ impl GlobalTable {
             ("String".into(), Res::PrimTy(Prim::String)),
             ("Unit".into(), Res::UnitTy),
         ];
-        let mut core: FxHashMap<Rc<str>, Res> = FxHashMap::default();
+        let mut core: FxHashMap<Arc<str>, Res> = FxHashMap::default();
         for (name, res) in builtins {
             core.insert(name, res);
         }
-        let mut tys: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Res>> = FxHashMap::default();
+        let mut tys: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Res>> = FxHashMap::default();
         tys.insert("Microsoft.Quantum.Core".into(), core);
 
         Self {
             names: IndexMap::new(),
             scope: GlobalScope {
                 tys,
-                terms: FxHashMap::default(),
-                namespaces: FxHashSet::default(),
-                intrinsics: FxHashSet::default(),
+                prelude: PRELUDE.iter().map(|&namespace| namespace.into()).collect(),
+                ..GlobalScope::default()
             },
         }
     }
@@ -791,39 +1572,107 @@ impl GlobalTable {
                 }
             }
         }
+        bind_global_exports(
+            &mut self.names,
+            &mut self.scope,
+            package.nodes.iter().filter_map(|node| match node {
+                TopLevelNode::Namespace(namespace) => Some(namespace),
+                TopLevelNode::Stmt(_) => None,
+            }),
+            &mut errors,
+        );
         errors
     }
 
-    pub(super) fn add_external_package(&mut self, id: PackageId, package: &hir::Package) {
-        for global in global::iter_package(Some(id), package).filter(|global| {
-            global.visibility == hir::Visibility::Public
-                || matches!(&global.kind, global::Kind::Term(t) if t.intrinsic)
-        }) {
-            match (global.kind, global.visibility) {
-                (global::Kind::Ty(ty), hir::Visibility::Public) => {
-                    self.scope
-                        .tys
-                        .entry(global.namespace)
+    /// Adds the public items of an external package. If the package has an alias, its items can
+    /// also be named with package-qualified paths like `Alias::Namespace.Name`.
+    pub(super) fn add_external_package(
+        &mut self,
+        id: PackageId,
+        package: &hir::Package,
+        alias: Option<&Arc<str>>,
+    ) {
+        bind_external_package(&mut self.scope, id, package, false);
+        if let Some(alias) = alias {
+            let scope = self.scope.packages.entry(Arc::from(&**alias)).or_default();
+            bind_external_package(scope, id, package, false);
+        }
+    }
+
+    /// Adds every item of a compiled package, including internal items, as the package itself
+    /// sees them.
+    pub(super) fn add_declaring_package(&mut self, id: PackageId, package: &hir::Package) {
+        bind_external_package(&mut self.scope, id, package, true);
+    }
+
+    /// Whether a term or type named `name` is declared in `namespace`.
+    pub(super) fn contains_item(&self, namespace: &str, name: &str) -> bool {
+        let declared = |items: &FxHashMap<Arc<str>, FxHashMap<Arc<str>, Res>>| {
+            items
+                .get(namespace)
+                .is_some_and(|items| items.contains_key(name))
+        };
+        declared(&self.scope.terms) || declared(&self.scope.tys)
+    }
+
+    /// Whether a namespace named `namespace` is declared.
+    pub(super) fn contains_namespace(&self, namespace: &str) -> bool {
+        self.scope.namespaces.contains(namespace)
+    }
+}
+
+fn bind_external_package(
+    scope: &mut GlobalScope,
+    id: PackageId,
+    package: &hir::Package,
+    internal: bool,
+) {
+    for item in package.items.values() {
+        if let Some(replacement) = hir_attrs_replacement(&item.attrs) {
+            let item_id = ItemId {
+                package: Some(id),
+                item: item.id,
+            };
+            scope.replacements.insert(item_id, replacement);
+        }
+    }
+
+    for global in global::iter_package(Some(id), package).filter(|global| {
+        internal
+            || global.visibility == hir::Visibility::Public
+            || matches!(&global.kind, global::Kind::Term(t) if t.intrinsic)
+    }) {
+        let visibility = if internal {
+            hir::Visibility::Public
+        } else {
+            global.visibility
+        };
+        let namespace = Arc::<str>::from(&*global.namespace);
+        let name = Arc::<str>::from(&*global.name);
+        match (global.kind, visibility) {
+            (global::Kind::Ty(ty), hir::Visibility::Public) => {
+                scope
+                    .tys
+                    .entry(namespace)
+                    .or_default()
+                    .insert(name, Res::Item(ty.id, global.status));
+            }
+            (global::Kind::Term(term), visibility) => {
+                if visibility == hir::Visibility::Public {
+                    scope
+                        .terms
+                        .entry(namespace)
                         .or_default()
-                        .insert(global.name, Res::Item(ty.id, global.status));
-                }
-                (global::Kind::Term(term), visibility) => {
-                    if visibility == hir::Visibility::Public {
-                        self.scope
-                            .terms
-                            .entry(global.namespace)
-                            .or_default()
-                            .insert(global.name.clone(), Res::Item(term.id, global.status));
-                    }
-                    if term.intrinsic {
-                        self.scope.intrinsics.insert(global.name);
-                    }
+                        .insert(Arc::clone(&name), Res::Item(term.id, global.status));
                 }
-                (global::Kind::Namespace, hir::Visibility::Public) => {
-                    self.scope.namespaces.insert(global.name);
+                if term.intrinsic {
+                    scope.intrinsics.insert(name);
                 }
-                (_, hir::Visibility::Internal) => {}
             }
+            (global::Kind::Namespace, hir::Visibility::Public) => {
+                scope.namespaces.insert(name);
+            }
+            (_, hir::Visibility::Internal) => {}
         }
     }
 }
@@ -839,13 +1688,14 @@ fn bind_global_items(
         namespace.name.id,
         Res::Item(intrapackage(assigner.next_item()), ItemStatus::Available),
     );
-    scope.namespaces.insert(Rc::clone(&namespace.name.name));
+    let namespace_name = namespace.full_name();
+    scope.namespaces.insert(Arc::clone(&namespace_name));
 
     for item in &*namespace.items {
         match bind_global_item(
             names,
             scope,
-            &namespace.name.name,
+            &namespace_name,
             || intrapackage(assigner.next_item()),
             item,
         ) {
@@ -855,10 +1705,139 @@ fn bind_global_items(
     }
 }
 
+/// Binds the `export` items of the given namespaces, making each exported item available under the
+/// exporting namespace in addition to its original one. Since an export can refer to the export of
+/// another namespace, exports are bound repeatedly until no more of them can be resolved.
+fn bind_global_exports<'a>(
+    names: &mut Names,
+    scope: &mut GlobalScope,
+    namespaces: impl Iterator<Item = &'a ast::Namespace>,
+    errors: &mut Vec<Error>,
+) {
+    let mut pending = namespaces
+        .flat_map(|namespace| {
+            namespace
+                .items
+                .iter()
+                .filter_map(move |item| match &*item.kind {
+                    ast::ItemKind::Export(path, alias) => {
+                        Some((namespace.full_name(), &**path, alias.as_deref()))
+                    }
+                    _ => None,
+                })
+        })
+        .collect::<Vec<_>>();
+
+    loop {
+        let pending_count = pending.len();
+        pending.retain(|(namespace, path, alias)| {
+            !bind_global_export(names, scope, namespace, path, *alias, errors)
+        });
+        if pending.len() == pending_count {
+            break;
+        }
+    }
+
+    errors.extend(
+        pending
+            .into_iter()
+            .map(|(_, path, _)| Error::NotFound(path_name(path), path.span)),
+    );
+}
+
+/// Binds a single `export` item, returning whether the exported path could be resolved. An
+/// unqualified path refers to an item in the exporting namespace itself, and a qualified path is
+/// resolved relative to the exporting namespace before falling back to a global namespace.
+fn bind_global_export(
+    names: &mut Names,
+    scope: &mut GlobalScope,
+    namespace: &Arc<str>,
+    path: &ast::Path,
+    alias: Option<&ast::Ident>,
+    errors: &mut Vec<Error>,
+) -> bool {
+    let (source_scope, sources) = match (&path.package, &path.namespace) {
+        (Some(package), source) => {
+            let Some(package_scope) = scope.packages.get(&package.name) else {
+                errors.push(Error::PackageNotFound(
+                    package.name.to_string(),
+                    package.span,
+                ));
+                return true;
+            };
+            let sources = source.iter().map(|source| Arc::clone(&source.name));
+            (package_scope, sources.collect())
+        }
+        (None, Some(source)) => (&*scope, relative_namespaces(Some(namespace), &source.name)),
+        (None, None) => (&*scope, vec![Arc::clone(namespace)]),
+    };
+    let Some((res, term, ty)) = sources.iter().find_map(|source| {
+        let term = source_scope
+            .get(NameKind::Term, source, &path.name.name)
+            .copied();
+        let ty = source_scope
+            .get(NameKind::Ty, source, &path.name.name)
+            .copied();
+        term.or(ty).map(|res| (res, term, ty))
+    }) else {
+        return false;
+    };
+    names.insert(path.id, res);
+
+    let (name, span) = alias.map_or((&path.name.name, path.span), |alias| {
+        (&alias.name, alias.span)
+    });
+    for (items, res) in [(&mut scope.terms, term), (&mut scope.tys, ty)] {
+        let Some(res) = res else {
+            continue;
+        };
+        match items
+            .entry(Arc::clone(namespace))
+            .or_default()
+            .entry(Arc::clone(name))
+        {
+            Entry::Occupied(entry) if *entry.get() == res => {}
+            Entry::Occupied(_) => {
+                errors.push(Error::Duplicate(
+                    name.to_string(),
+                    namespace.to_string(),
+                    span,
+                ));
+                // A newtype occupies both maps, so report the clash only once.
+                break;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(res);
+            }
+        }
+    }
+    true
+}
+
+/// The name that a callable or newtype declaration binds.
+fn item_name(item: &ast::Item) -> Option<&ast::Ident> {
+    match &*item.kind {
+        ast::ItemKind::Callable(decl) => Some(&decl.name),
+        ast::ItemKind::Ty(name, _) => Some(name),
+        ast::ItemKind::Err | ast::ItemKind::Export(..) | ast::ItemKind::Open(..) => None,
+    }
+}
+
+fn path_name(path: &ast::Path) -> String {
+    let name = match &path.namespace {
+        Some(namespace) => format!("{}.{}", namespace.name, path.name.name),
+        None => path.name.name.to_string(),
+    };
+    match &path.package {
+        Some(package) => format!("{}::{name}", package.name),
+        None => name,
+    }
+}
+
 /// Tries to extract a field name from an expression in cases where it is syntactically ambiguous
 /// whether the expression is a field name or a variable name. This applies to the index operand in
 /// a ternary update operator.
-pub(super) fn extract_field_name<'a>(names: &Names, expr: &'a ast::Expr) -> Option<&'a Rc<str>> {
+pub(super) fn extract_field_name<'a>(names: &Names, expr: &'a ast::Expr) -> Option<&'a Arc<str>> {
     // Follow the same reasoning as `is_field_update`.
     match &*expr.kind {
         ast::ExprKind::Path(path)
@@ -882,7 +1861,15 @@ fn is_field_update<'a>(
             {
                 let name = &path.name;
                 let namespace = &path.namespace;
-                resolve(NameKind::Term, globals, scopes, name, namespace)
+                // This is only a probe, so any opens it consults are not recorded as used.
+                resolve(
+                    NameKind::Term,
+                    globals,
+                    scopes,
+                    name,
+                    namespace,
+                    &mut FxHashSet::default(),
+                )
             },
             Ok(Res::Local(_))
         ),
@@ -893,29 +1880,64 @@ fn is_field_update<'a>(
 fn ast_attrs_as_hir_attrs(attrs: &[Box<ast::Attr>]) -> Vec<hir::Attr> {
     attrs
         .iter()
-        .filter_map(|attr| hir::Attr::from_str(attr.name.name.as_ref()).ok())
+        .filter_map(|attr| match hir::Attr::from_str(attr.name.name.as_ref()) {
+            Ok(hir::Attr::Deprecated(_)) => {
+                deprecated_replacement(attr).ok().map(hir::Attr::Deprecated)
+            }
+            attr => attr.ok(),
+        })
         .collect()
 }
 
+/// Gets the replacement named by a `Deprecated` attribute, which takes either no arguments or a
+/// string with the name of the replacement.
+pub(super) fn deprecated_replacement(attr: &ast::Attr) -> Result<Option<Rc<str>>, ()> {
+    match &*attr.arg.kind {
+        ast::ExprKind::Tuple(args) if args.is_empty() => Ok(None),
+        ast::ExprKind::Paren(inner) => match &*inner.kind {
+            ast::ExprKind::Lit(lit) => match &**lit {
+                ast::Lit::String(replacement) => Ok(Some(replacement.as_ref().into())),
+                _ => Err(()),
+            },
+            _ => Err(()),
+        },
+        _ => Err(()),
+    }
+}
+
+/// Gets the replacement of a deprecated item from its attributes.
+fn hir_attrs_replacement(attrs: &[hir::Attr]) -> Option<Arc<str>> {
+    attrs.iter().find_map(|attr| match attr {
+        hir::Attr::Deprecated(replacement) => replacement.as_deref().map(Arc::from),
+        _ => None,
+    })
+}
+
 fn bind_global_item(
     names: &mut Names,
     scope: &mut GlobalScope,
-    namespace: &Rc<str>,
-    next_id: impl FnOnce() -> ItemId,
+    namespace: &Arc<str>,
+    mut next_id: impl FnMut() -> ItemId,
     item: &ast::Item,
 ) -> Result<(), Vec<Error>> {
     match &*item.kind {
         ast::ItemKind::Callable(decl) => {
             let item_id = next_id();
-            let status = ItemStatus::from_attrs(&ast_attrs_as_hir_attrs(item.attrs.as_ref()));
-            let res = Res::Item(item_id, status);
+            let attrs = ast_attrs_as_hir_attrs(item.attrs.as_ref());
+            if let Some(replacement) = hir_attrs_replacement(&attrs) {
+                scope.replacements.insert(item_id, replacement);
+            }
+            let res = Res::Item(item_id, ItemStatus::from_attrs(&attrs));
             names.insert(decl.name.id, res);
+            bind_defaults(decl, |param| {
+                names.insert(param, Res::Item(next_id(), ItemStatus::Available));
+            });
             let mut errors = Vec::new();
             match scope
                 .terms
-                .entry(Rc::clone(namespace))
+                .entry(Arc::clone(namespace))
                 .or_default()
-                .entry(Rc::clone(&decl.name.name))
+                .entry(Arc::clone(&decl.name.name))
             {
                 Entry::Occupied(_) => errors.push(Error::Duplicate(
                     decl.name.name.to_string(),
@@ -927,7 +1949,7 @@ fn bind_global_item(
                 }
             }
 
-            if decl_is_intrinsic(decl) && !scope.intrinsics.insert(Rc::clone(&decl.name.name)) {
+            if decl_is_intrinsic(decl) && !scope.intrinsics.insert(Arc::clone(&decl.name.name)) {
                 errors.push(Error::DuplicateIntrinsic(
                     decl.name.name.to_string(),
                     decl.name.span,
@@ -942,20 +1964,23 @@ fn bind_global_item(
         }
         ast::ItemKind::Ty(name, _) => {
             let item_id = next_id();
-            let status = ItemStatus::from_attrs(&ast_attrs_as_hir_attrs(item.attrs.as_ref()));
-            let res = Res::Item(item_id, status);
+            let attrs = ast_attrs_as_hir_attrs(item.attrs.as_ref());
+            if let Some(replacement) = hir_attrs_replacement(&attrs) {
+                scope.replacements.insert(item_id, replacement);
+            }
+            let res = Res::Item(item_id, ItemStatus::from_attrs(&attrs));
             names.insert(name.id, res);
             match (
                 scope
                     .terms
-                    .entry(Rc::clone(namespace))
+                    .entry(Arc::clone(namespace))
                     .or_default()
-                    .entry(Rc::clone(&name.name)),
+                    .entry(Arc::clone(&name.name)),
                 scope
                     .tys
-                    .entry(Rc::clone(namespace))
+                    .entry(Arc::clone(namespace))
                     .or_default()
-                    .entry(Rc::clone(&name.name)),
+                    .entry(Arc::clone(&name.name)),
             ) {
                 (Entry::Occupied(_), _) | (_, Entry::Occupied(_)) => Err(vec![Error::Duplicate(
                     name.name.to_string(),
@@ -969,7 +1994,17 @@ fn bind_global_item(
                 }
             }
         }
-        ast::ItemKind::Err | ast::ItemKind::Open(..) => Ok(()),
+        ast::ItemKind::Err | ast::ItemKind::Export(..) | ast::ItemKind::Open(..) => Ok(()),
+    }
+}
+
+/// Calls `bind` with the node ID of each parameter that has a default value. Each of these
+/// parameters resolves to the item that computes its default value.
+fn bind_defaults(decl: &ast::CallableDecl, mut bind: impl FnMut(NodeId)) {
+    for (param, default) in decl.params().into_iter().zip(decl.defaults.iter()) {
+        if default.is_some() {
+            bind(param.id);
+        }
     }
 }
 
@@ -989,8 +2024,13 @@ fn resolve<'a>(
     scopes: impl Iterator<Item = &'a Scope>,
     name: &Ident,
     namespace: &Option<Box<Ident>>,
+    used_opens: &mut FxHashSet<Span>,
 ) -> Result<Res, Error> {
     let scopes = scopes.collect::<Vec<_>>();
+    let current_namespace = scopes.iter().find_map(|scope| match &scope.kind {
+        ScopeKind::Namespace(namespace) => Some(Arc::clone(namespace)),
+        _ => None,
+    });
     let mut candidates = FxHashMap::default();
     let mut vars = true;
     let name_str = &(*name.name);
@@ -1007,6 +2047,7 @@ fn resolve<'a>(
             candidates = resolve_explicit_opens(kind, globals, namespaces, name_str);
             if !candidates.is_empty() {
                 // Explicit opens shadow prelude and unopened globals.
+                used_opens.extend(candidates.values().map(|open| open.span));
                 break;
             }
         }
@@ -1019,7 +2060,7 @@ fn resolve<'a>(
 
     if candidates.is_empty() && namespace.is_empty() {
         // Prelude shadows unopened globals.
-        let candidates = resolve_implicit_opens(kind, globals, PRELUDE, name_str);
+        let candidates = resolve_implicit_opens(kind, globals, &globals.prelude, name_str);
         if candidates.len() > 1 {
             let mut candidates: Vec<_> = candidates.into_iter().collect();
             candidates.sort_by_key(|x| x.1);
@@ -1037,6 +2078,7 @@ fn resolve<'a>(
                 name: name.name.to_string(),
                 candidate_a,
                 candidate_b,
+                prelude: globals.prelude.join(", "),
             });
         }
         if let Some((res, _)) = single(candidates) {
@@ -1044,10 +2086,13 @@ fn resolve<'a>(
         }
     }
 
-    if candidates.is_empty() {
-        if let Some(&res) = globals.get(kind, namespace, name_str) {
-            // An unopened global is the last resort.
-            return Ok(res);
+    if candidates.is_empty() && !namespace.is_empty() {
+        // An unopened global is the last resort. A partially-qualified path is tried relative to
+        // the current namespace first, so that the innermost match wins.
+        for namespace in relative_namespaces(current_namespace.as_deref(), namespace) {
+            if let Some(&res) = globals.get(kind, &namespace, name_str) {
+                return Ok(res);
+            }
         }
     }
 
@@ -1083,6 +2128,65 @@ fn resolve<'a>(
     }
 }
 
+/// Lists every resolution that is in scope for a name, from highest to lowest precedence, following
+/// the same rules as [`resolve`].
+fn explain_candidates<'a>(
+    kind: NameKind,
+    globals: &GlobalScope,
+    scopes: impl Iterator<Item = &'a Scope>,
+    name: &Ident,
+    namespace: Option<&Ident>,
+) -> Vec<(Res, Origin)> {
+    let scopes = scopes.collect::<Vec<_>>();
+    let name = &*name.name;
+    let namespace = namespace.map_or("", |i| &i.name);
+    let mut candidates = Vec::new();
+    let mut vars = true;
+    for scope in &scopes {
+        if namespace.is_empty() {
+            if let Some(res) = resolve_scope_locals(kind, globals, scope, vars, name) {
+                candidates.push((res, Origin::Scope(scope.span)));
+            }
+        }
+
+        for open in scope.opens.get(namespace).into_iter().flatten() {
+            if let Some(&res) = globals.get(kind, &open.namespace, name) {
+                candidates.push((
+                    res,
+                    Origin::Open {
+                        namespace: Arc::clone(&open.namespace),
+                        span: open.span,
+                    },
+                ));
+            }
+        }
+
+        if scope.kind == ScopeKind::Callable {
+            vars = false;
+        }
+    }
+
+    if namespace.is_empty() {
+        for prelude in &globals.prelude {
+            if let Some(&res) = globals.get(kind, prelude, name) {
+                candidates.push((res, Origin::Prelude(Arc::clone(prelude))));
+            }
+        }
+    } else {
+        let current_namespace = scopes.iter().find_map(|scope| match &scope.kind {
+            ScopeKind::Namespace(namespace) => Some(Arc::clone(namespace)),
+            _ => None,
+        });
+        for namespace in relative_namespaces(current_namespace.as_deref(), namespace) {
+            if let Some(&res) = globals.get(kind, &namespace, name) {
+                candidates.push((res, Origin::Namespace(namespace)));
+            }
+        }
+    }
+
+    candidates
+}
+
 /// Implements shadowing rules within a single scope.
 /// A local variable always wins out against an item with the same name, even if they're declared in
 /// the same scope. It is implemented in a way that resembles Rust:
@@ -1101,7 +2205,8 @@ fn resolve_scope_locals(
     if vars {
         match kind {
             NameKind::Term => {
-                if let Some(&(_, id)) = scope.vars.get(name) {
+                // During resolution, the most recent binding is the one in effect.
+                if let Some(&(_, id)) = scope.vars.get(name).and_then(|bindings| bindings.last()) {
                     return Some(Res::Local(id));
                 }
             }
@@ -1131,18 +2236,17 @@ fn get_scope_locals(scope: &Scope, offset: u32, vars: bool) -> Vec<Local> {
 
     // variables
     if vars {
-        names.extend(scope.vars.iter().filter_map(|(name, (valid_at, id))| {
-            // Bug: Because we keep track of only one `valid_at` offset per name,
-            // when a variable is later shadowed in the same scope,
-            // it is missed in the list. https://github.com/microsoft/qsharp/issues/897
-            if offset >= *valid_at {
-                Some(Local {
+        names.extend(scope.vars.iter().filter_map(|(name, bindings)| {
+            // Later bindings shadow earlier ones, so pick the latest binding
+            // that is already available at this offset.
+            bindings
+                .iter()
+                .filter(|(valid_at, _)| offset >= *valid_at)
+                .max_by_key(|(valid_at, _)| *valid_at)
+                .map(|(_, id)| Local {
                     name: name.clone(),
                     kind: LocalKind::Var(*id),
                 })
-            } else {
-                None
-            }
         }));
 
         names.extend(scope.ty_vars.iter().map(|id| Local {
@@ -1167,18 +2271,52 @@ fn get_scope_locals(scope: &Scope, offset: u32, vars: bool) -> Vec<Local> {
 fn resolve_implicit_opens<'a, 'b>(
     kind: NameKind,
     globals: &'b GlobalScope,
-    namespaces: impl IntoIterator<Item = &'a &'a str>,
+    namespaces: impl IntoIterator<Item = &'a Arc<str>>,
     name: &'b str,
 ) -> FxHashMap<Res, &'a str> {
     let mut candidates = FxHashMap::default();
     for namespace in namespaces {
         if let Some(&res) = globals.get(kind, namespace, name) {
-            candidates.insert(res, *namespace);
+            candidates.insert(res, &**namespace);
         }
     }
     candidates
 }
 
+/// Resolves a path qualified by a package alias. Such a path always names a public item of that
+/// package by its fully-qualified namespace, so scopes, opens, and the prelude don't apply.
+fn resolve_in_package(
+    kind: NameKind,
+    globals: &GlobalScope,
+    package: &Ident,
+    name: &Ident,
+    namespace: Option<&Ident>,
+) -> Result<Res, Error> {
+    let scope = globals
+        .packages
+        .get(&package.name)
+        .ok_or_else(|| Error::PackageNotFound(package.name.to_string(), package.span))?;
+    namespace
+        .and_then(|namespace| scope.get(kind, &namespace.name, &name.name))
+        .copied()
+        .ok_or_else(|| Error::NotFound(name.name.to_string(), name.span))
+}
+
+/// Lists the fully-qualified namespaces that `namespace` may refer to from within `current`, from
+/// innermost to outermost. For example, `C` within `A.B` may refer to `A.B.C`, `A.C`, or `C`.
+fn relative_namespaces(current: Option<&str>, namespace: &str) -> Vec<Arc<str>> {
+    let mut namespaces = Vec::new();
+    let mut parent = current.unwrap_or_default();
+    while !parent.is_empty() {
+        namespaces.push(format!("{parent}.{namespace}").into());
+        parent = parent
+            .rsplit_once('.')
+            .map_or("", |(grandparent, _)| grandparent);
+    }
+    namespaces.push(namespace.into());
+    namespaces
+}
+
 fn resolve_explicit_opens<'a>(
     kind: NameKind,
     globals: &GlobalScope,
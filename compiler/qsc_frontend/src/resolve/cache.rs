@@ -0,0 +1,456 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{GlobalScope, Open, Res, Resolver, Scope, Warning};
+use crate::compile::SourceMap;
+use qsc_ast::{
+    ast::{self, NodeId, TopLevelNode},
+    visit::Visitor as AstVisitor,
+};
+use qsc_data_structures::{language_features::LanguageFeatures, lints::Lints, span::Span};
+use qsc_hir::{
+    assigner::Assigner,
+    hir::{ItemId, LocalItemId},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// The name resolution of each namespace of a package from its last compilation, which lets the
+/// next compilation of the package skip the namespaces that did not change.
+///
+/// A namespace is reused when its source is unchanged and every namespace it depends on declares
+/// the same items as before. A namespace depends on itself, on the namespaces it opens, on the
+/// prelude, and on the namespaces that declare the items it refers to. A namespace with name errors
+/// is always resolved again.
+#[derive(Debug, Default)]
+pub struct Cache {
+    context: Option<Context>,
+    globals: GlobalScope,
+    /// The resolution of each namespace, keyed by its name and the number of namespaces with the
+    /// same name before it.
+    namespaces: FxHashMap<(Arc<str>, usize), Resolution>,
+}
+
+/// The inputs to resolution besides the package itself. A change to any of them means nothing in
+/// the cache can be reused.
+#[derive(Debug, Eq, PartialEq)]
+struct Context {
+    dropped_names: Vec<(Arc<str>, Arc<str>)>,
+    language_features: LanguageFeatures,
+    lints: Lints,
+}
+
+/// What resolving one namespace added to the resolver, relative to where the namespace was.
+#[derive(Debug)]
+struct Resolution {
+    hash: u64,
+    /// The node ID of the namespace. The namespace's nodes have the IDs that follow it.
+    id: NodeId,
+    /// The offset where the namespace starts.
+    lo: u32,
+    dependencies: FxHashSet<Arc<str>>,
+    local_items: Vec<LocalItemId>,
+    names: Vec<(NodeId, Res)>,
+    references: Vec<(Res, Span)>,
+    scopes: Vec<Scope>,
+    used_opens: Vec<Span>,
+    warnings: Vec<Warning>,
+}
+
+/// What the resolver adds while resolving a namespace that will be cached.
+#[derive(Default)]
+pub(super) struct Recording {
+    pub(super) local_items: Vec<LocalItemId>,
+    pub(super) names: Vec<(NodeId, Res)>,
+    pub(super) references: Vec<(Res, Span)>,
+}
+
+impl Resolver {
+    /// Resolves the package like visiting it with [`Resolver::with`], except that each namespace
+    /// that can be reused from the cache is not visited again. The cache is then replaced with the
+    /// resolution of this package. Explanations are not cached, so every namespace is visited when
+    /// they are enabled.
+    pub(crate) fn resolve_cached(
+        &mut self,
+        assigner: &mut Assigner,
+        package: &ast::Package,
+        sources: &SourceMap,
+        language_features: LanguageFeatures,
+        cache: &mut Cache,
+    ) {
+        let context = Context {
+            dropped_names: self
+                .dropped_names
+                .iter()
+                .map(|name| (Arc::clone(&name.namespace), Arc::clone(&name.name)))
+                .collect(),
+            language_features,
+            lints: self.lints,
+        };
+        let mut previous = if self.explanations.is_none()
+            && cache.context.as_ref() == Some(&context)
+            && same_shape(&cache.globals, &self.globals)
+        {
+            std::mem::take(&mut cache.namespaces)
+        } else {
+            FxHashMap::default()
+        };
+        let changed = changed_namespaces(&cache.globals, &self.globals);
+        let declared_in = declaring_namespaces(&self.globals);
+
+        let mut namespaces = FxHashMap::default();
+        let mut counts = FxHashMap::default();
+        for node in &*package.nodes {
+            let namespace = match node {
+                TopLevelNode::Namespace(namespace) => namespace,
+                TopLevelNode::Stmt(stmt) => {
+                    self.with(assigner).visit_stmt(stmt);
+                    continue;
+                }
+            };
+
+            let namespace_name = namespace.full_name();
+            let count = counts.entry(Arc::clone(&namespace_name)).or_insert(0);
+            let key = (namespace_name, *count);
+            *count += 1;
+
+            let hash = source_hash(sources, namespace.span);
+            let resolution = match previous.remove(&key) {
+                Some(resolution)
+                    if Some(resolution.hash) == hash
+                        && resolution.dependencies.is_disjoint(&changed) =>
+                {
+                    Some(self.reuse(assigner, namespace, resolution))
+                }
+                _ => self.record(assigner, namespace, hash, &declared_in),
+            };
+            if let Some(resolution) = resolution {
+                namespaces.insert(key, resolution);
+            }
+        }
+
+        if let Some(entry) = &package.entry {
+            self.with(assigner).visit_expr(entry);
+        }
+
+        // The namespaces that a cancelled resolution skipped would be cached as resolved.
+        if self.cancellation.is_cancelled() {
+            *cache = Cache::default();
+            return;
+        }
+
+        *cache = Cache {
+            context: Some(context),
+            globals: self.globals.clone(),
+            namespaces,
+        };
+    }
+
+    /// Visits the namespace, returning what the visit added if the namespace can be cached.
+    fn record(
+        &mut self,
+        assigner: &mut Assigner,
+        namespace: &ast::Namespace,
+        hash: Option<u64>,
+        declared_in: &FxHashMap<ItemId, Vec<Arc<str>>>,
+    ) -> Option<Resolution> {
+        let errors = self.errors.len();
+        let warnings = self.warnings.len();
+        let scopes = self.locals.scopes.len();
+        self.recording = Some(Recording::default());
+        self.with(assigner).visit_namespace(namespace);
+        let recording = self
+            .recording
+            .take()
+            .expect("recording should not be taken during the visit");
+
+        let hash = hash.filter(|_| self.errors.len() == errors)?;
+        let scopes = self.locals.scopes[scopes..].to_vec();
+        let opens = || {
+            scopes
+                .iter()
+                .flat_map(|scope| scope.opens.values().flatten())
+        };
+        let used_opens = opens()
+            .map(|open| open.span)
+            .filter(|span| self.used_opens.contains(span))
+            .collect();
+
+        let mut dependencies = self
+            .globals
+            .prelude
+            .iter()
+            .cloned()
+            .collect::<FxHashSet<_>>();
+        dependencies.insert(namespace.full_name());
+        dependencies.extend(opens().map(|open| Arc::clone(&open.namespace)));
+        dependencies.extend(
+            recording
+                .names
+                .iter()
+                .filter_map(|(_, res)| match res {
+                    Res::Item(id, _) => declared_in.get(id),
+                    _ => None,
+                })
+                .flatten()
+                .cloned(),
+        );
+
+        Some(Resolution {
+            hash,
+            id: namespace.id,
+            lo: namespace.span.lo,
+            dependencies,
+            local_items: recording.local_items,
+            names: recording.names,
+            references: recording.references,
+            scopes,
+            used_opens,
+            warnings: self.warnings[warnings..].to_vec(),
+        })
+    }
+
+    /// Adds a cached resolution in place of visiting the namespace, after moving it to where the
+    /// namespace is now. Returns the moved resolution.
+    fn reuse(
+        &mut self,
+        assigner: &mut Assigner,
+        namespace: &ast::Namespace,
+        resolution: Resolution,
+    ) -> Resolution {
+        // Local items get new IDs in the same order that the visit would have assigned them.
+        let local_items = resolution
+            .local_items
+            .iter()
+            .map(|_| assigner.next_item())
+            .collect::<Vec<_>>();
+        let relocation = Relocation {
+            old_id: usize::from(resolution.id),
+            new_id: usize::from(namespace.id),
+            old_lo: resolution.lo,
+            new_lo: namespace.span.lo,
+            items: resolution
+                .local_items
+                .iter()
+                .copied()
+                .zip(local_items.iter().copied())
+                .collect(),
+        };
+
+        let resolution = Resolution {
+            hash: resolution.hash,
+            id: namespace.id,
+            lo: namespace.span.lo,
+            dependencies: resolution.dependencies,
+            local_items,
+            names: resolution
+                .names
+                .into_iter()
+                .map(|(id, name)| (relocation.node(id), relocation.res(name)))
+                .collect(),
+            references: resolution
+                .references
+                .into_iter()
+                .map(|(reference, span)| (relocation.res(reference), relocation.span(span)))
+                .collect(),
+            scopes: resolution
+                .scopes
+                .into_iter()
+                .map(|scope| relocation.scope(scope))
+                .collect(),
+            used_opens: resolution
+                .used_opens
+                .into_iter()
+                .map(|span| relocation.span(span))
+                .collect(),
+            warnings: resolution
+                .warnings
+                .into_iter()
+                .map(|warning| warning.map_spans(|span| relocation.span(span)))
+                .collect(),
+        };
+
+        for &(id, res) in &resolution.names {
+            self.names.insert(id, res);
+        }
+        for &(res, span) in &resolution.references {
+            self.references.insert(res, span);
+        }
+        self.locals.scopes.extend(resolution.scopes.iter().cloned());
+        self.used_opens
+            .extend(resolution.used_opens.iter().copied());
+        self.warnings.extend(resolution.warnings.iter().cloned());
+        resolution
+    }
+}
+
+/// Where a cached namespace was, and where it is now. The namespace's node IDs, offsets, and local
+/// items move by the same amount as the namespace itself.
+struct Relocation {
+    old_id: usize,
+    new_id: usize,
+    old_lo: u32,
+    new_lo: u32,
+    items: FxHashMap<LocalItemId, LocalItemId>,
+}
+
+impl Relocation {
+    fn node(&self, id: NodeId) -> NodeId {
+        NodeId::from(usize::from(id) - self.old_id + self.new_id)
+    }
+
+    fn offset(&self, offset: u32) -> u32 {
+        offset - self.old_lo + self.new_lo
+    }
+
+    fn span(&self, span: Span) -> Span {
+        Span {
+            lo: self.offset(span.lo),
+            hi: self.offset(span.hi),
+        }
+    }
+
+    fn item(&self, id: ItemId) -> ItemId {
+        match id {
+            ItemId {
+                package: None,
+                item,
+            } => self.items.get(&item).map_or(id, |&item| ItemId {
+                package: None,
+                item,
+            }),
+            ItemId { .. } => id,
+        }
+    }
+
+    fn res(&self, res: Res) -> Res {
+        match res {
+            Res::Item(id, status) => Res::Item(self.item(id), status),
+            Res::Local(id) => Res::Local(self.node(id)),
+            Res::Param(_) | Res::PrimTy(_) | Res::UnitTy => res,
+        }
+    }
+
+    fn scope(&self, scope: Scope) -> Scope {
+        Scope {
+            span: self.span(scope.span),
+            kind: scope.kind,
+            opens: scope
+                .opens
+                .into_iter()
+                .map(|(key, opens)| {
+                    let opens = opens.into_iter().map(|open| self.open(open)).collect();
+                    (key, opens)
+                })
+                .collect(),
+            tys: scope
+                .tys
+                .into_iter()
+                .map(|(name, id)| (name, self.item(id)))
+                .collect(),
+            terms: scope
+                .terms
+                .into_iter()
+                .map(|(name, id)| (name, self.item(id)))
+                .collect(),
+            vars: scope
+                .vars
+                .into_iter()
+                .map(|(name, bindings)| {
+                    let bindings = bindings
+                        .into_iter()
+                        .map(|(valid_at, id)| (self.offset(valid_at), self.node(id)))
+                        .collect();
+                    (name, bindings)
+                })
+                .collect(),
+            ty_vars: scope.ty_vars,
+        }
+    }
+
+    fn open(&self, open: Open) -> Open {
+        Open {
+            namespace: open.namespace,
+            span: self.span(open.span),
+            item_span: self.span(open.item_span),
+        }
+    }
+}
+
+impl Warning {
+    fn map_spans(self, f: impl Fn(Span) -> Span) -> Self {
+        match self {
+            Warning::UnusedOpen(name, span) => Warning::UnusedOpen(name, f(span)),
+            Warning::Deprecated { name, help, span } => Warning::Deprecated {
+                name,
+                help,
+                span: f(span),
+            },
+            Warning::ShadowedLocal {
+                name,
+                span,
+                shadowed_span,
+            } => Warning::ShadowedLocal {
+                name,
+                span: f(span),
+                shadowed_span: f(shadowed_span),
+            },
+        }
+    }
+}
+
+/// Hashes the source text in the span, if the span is in one of the sources.
+fn source_hash(sources: &SourceMap, span: Span) -> Option<u64> {
+    let source = sources.find_by_offset(span.lo)?;
+    let text = source
+        .contents
+        .get((span.lo - source.offset) as usize..(span.hi - source.offset) as usize)?;
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Whether everything but the items declared in each namespace is the same in both scopes.
+fn same_shape(old: &GlobalScope, new: &GlobalScope) -> bool {
+    old.namespaces == new.namespaces
+        && old.intrinsics == new.intrinsics
+        && old.packages == new.packages
+        && old.prelude == new.prelude
+        && old.replacements == new.replacements
+}
+
+/// The namespaces whose declared items are different in the two scopes.
+fn changed_namespaces(old: &GlobalScope, new: &GlobalScope) -> FxHashSet<Arc<str>> {
+    old.terms
+        .keys()
+        .chain(old.tys.keys())
+        .chain(new.terms.keys())
+        .chain(new.tys.keys())
+        .filter(|namespace| {
+            old.terms.get(*namespace) != new.terms.get(*namespace)
+                || old.tys.get(*namespace) != new.tys.get(*namespace)
+        })
+        .cloned()
+        .collect()
+}
+
+/// The namespaces that each item is declared in. An exported item is declared in both the
+/// namespace that defines it and the namespace that exports it.
+fn declaring_namespaces(globals: &GlobalScope) -> FxHashMap<ItemId, Vec<Arc<str>>> {
+    let mut declared_in = FxHashMap::<ItemId, Vec<Arc<str>>>::default();
+    for (namespace, items) in globals.terms.iter().chain(&globals.tys) {
+        for res in items.values() {
+            if let Res::Item(id, _) = res {
+                declared_in
+                    .entry(*id)
+                    .or_default()
+                    .push(Arc::clone(namespace));
+            }
+        }
+    }
+    declared_in
+}
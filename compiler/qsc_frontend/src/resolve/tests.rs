@@ -3,10 +3,10 @@
 
 #![allow(clippy::needless_raw_string_hashes)]
 
-use super::{Error, Locals, Names, Res};
+use super::{Error, Locals, Names, Res, Warning};
 use crate::{
     compile,
-    compile::RuntimeCapabilityFlags,
+    compile::{CompileOptions, RuntimeCapabilityFlags},
     resolve::{LocalKind, Resolver},
 };
 use expect_test::{expect, Expect};
@@ -17,7 +17,7 @@ use qsc_ast::{
     mut_visit::MutVisitor,
     visit::{self, Visitor},
 };
-use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use qsc_data_structures::{language_features::LanguageFeatures, lints::Lints, span::Span};
 use qsc_hir::assigner::Assigner as HirAssigner;
 use std::fmt::Write;
 
@@ -72,7 +72,7 @@ fn check(input: &str, expect: &Expect) {
 }
 
 fn resolve_names(input: &str) -> String {
-    let (package, names, _, errors) = compile(input, LanguageFeatures::default());
+    let (package, names, _, errors, _) = compile(input, LanguageFeatures::default());
     let mut renamer = Renamer::new(&names);
     renamer.visit_package(&package);
     let mut output = input.to_string();
@@ -86,10 +86,30 @@ fn resolve_names(input: &str) -> String {
     output
 }
 
+fn check_warnings(input: &str, expect: &Expect) {
+    let (_, _, _, errors, warnings) = compile(input, LanguageFeatures::default());
+    assert!(errors.is_empty(), "resolution failed: {errors:#?}");
+    expect.assert_debug_eq(&warnings);
+}
+
+fn check_lint_warnings(input: &str, lints: Lints, expect: &Expect) {
+    let (_, _, _, errors, warnings) = compile_with_lints(input, LanguageFeatures::default(), lints);
+    assert!(errors.is_empty(), "resolution failed: {errors:#?}");
+    expect.assert_debug_eq(&warnings);
+}
+
 fn compile(
     input: &str,
     language_features: LanguageFeatures,
-) -> (Package, Names, Locals, Vec<Error>) {
+) -> (Package, Names, Locals, Vec<Error>, Vec<Warning>) {
+    compile_with_lints(input, language_features, Lints::default())
+}
+
+fn compile_with_lints(
+    input: &str,
+    language_features: LanguageFeatures,
+    lints: Lints,
+) -> (Package, Names, Locals, Vec<Error>, Vec<Warning>) {
     let (namespaces, parse_errors) = qsc_parse::namespaces(input, language_features);
     assert!(parse_errors.is_empty(), "parse failed: {parse_errors:#?}");
     let mut package = Package {
@@ -104,7 +124,8 @@ fn compile(
 
     AstAssigner::new().visit_package(&mut package);
 
-    let mut cond_compile = compile::preprocess::Conditional::new(RuntimeCapabilityFlags::all());
+    let mut cond_compile =
+        compile::preprocess::Conditional::new(RuntimeCapabilityFlags::all(), &[]);
     cond_compile.visit_package(&mut package);
     let dropped_names = cond_compile.into_names();
 
@@ -112,10 +133,14 @@ fn compile(
     let mut globals = super::GlobalTable::new();
     let mut errors = globals.add_local_package(&mut assigner, &package);
     let mut resolver = Resolver::new(globals, dropped_names);
+    resolver.set_options(&CompileOptions {
+        lints,
+        ..CompileOptions::default()
+    });
     resolver.with(&mut assigner).visit_package(&package);
-    let (names, locals, mut resolve_errors) = resolver.into_result();
+    let (names, locals, _, _, _, mut resolve_errors, warnings) = resolver.into_result();
     errors.append(&mut resolve_errors);
-    (package, names, locals, errors)
+    (package, names, locals, errors, warnings)
 }
 
 #[test]
@@ -431,7 +456,7 @@ fn ambiguous_prelude() {
                 }
             }
 
-            // AmbiguousPrelude { name: "A", candidate_a: "Microsoft.Quantum.Canon", candidate_b: "Microsoft.Quantum.Core", span: Span { lo: 181, hi: 182 } }
+            // AmbiguousPrelude { name: "A", candidate_a: "Microsoft.Quantum.Canon", candidate_b: "Microsoft.Quantum.Core", prelude: "Microsoft.Quantum.Canon, Microsoft.Quantum.Core, Microsoft.Quantum.Intrinsic", span: Span { lo: 181, hi: 182 } }
         "#]],
     );
 }
@@ -1595,6 +1620,92 @@ fn local_open_shadows_parent_open() {
     );
 }
 
+#[test]
+fn nested_block_open() {
+    check(
+        indoc! {"
+            namespace A {
+                function Foo() : () {
+                    {
+                        open B;
+                        Bar();
+                    }
+                }
+            }
+
+            namespace B { function Bar() : () {} }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : () {
+                    {
+                        open B;
+                        item3();
+                    }
+                }
+            }
+
+            namespace item2 { function item3() : () {} }
+        "#]],
+    );
+}
+
+#[test]
+fn block_open_not_visible_outside_block() {
+    check(
+        indoc! {"
+            namespace A {
+                function Foo() : () {
+                    {
+                        open B;
+                    }
+                    Bar();
+                }
+            }
+
+            namespace B { function Bar() : () {} }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : () {
+                    {
+                        open B;
+                    }
+                    Bar();
+                }
+            }
+
+            namespace item2 { function item3() : () {} }
+
+            // NotFound("Bar", Span { lo: 88, hi: 91 })
+        "#]],
+    );
+}
+
+#[test]
+fn block_open_not_visible_in_sibling_callable() {
+    check(
+        indoc! {"
+            namespace A {
+                function Foo() : () { open B; }
+                function Baz() : () { Bar(); }
+            }
+
+            namespace B { function Bar() : () {} }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : () { open B; }
+                function item2() : () { Bar(); }
+            }
+
+            namespace item3 { function item4() : () {} }
+
+            // NotFound("Bar", Span { lo: 76, hi: 79 })
+        "#]],
+    );
+}
+
 #[test]
 fn update_array_index_var() {
     check(
@@ -2101,172 +2212,592 @@ fn disallow_duplicate_intrinsic_and_non_intrinsic_collision() {
     );
 }
 
-#[allow(clippy::cast_possible_truncation)]
-fn check_locals(input: &str, expect: &Expect) {
-    let parts = input.split('↘').collect::<Vec<_>>();
-    assert_eq!(
-        parts.len(),
-        2,
-        "input must contain exactly one cursor marker"
-    );
-    let cursor_offset = parts[0].len() as u32;
-    let source = parts.join("");
-
-    let (_, _, locals, _) = compile(&source, LanguageFeatures::default());
-
-    let locals = locals.get_all_at_offset(cursor_offset);
-    let actual = locals.iter().fold(String::new(), |mut output, l| {
-        let _ = writeln!(
-            output,
-            "{} ({})",
-            l.name,
-            match l.kind {
-                LocalKind::Item(item_id) => item_id.to_string(),
-                LocalKind::TyParam(param_id) => format!("ty_param {param_id}"),
-                LocalKind::Var(node_id) => format!("var {node_id}"),
-            }
-        );
-        output
-    });
-
-    expect.assert_eq(&actual);
-}
-
 #[test]
-fn get_locals_vars() {
-    check_locals(
+fn export_item() {
+    check(
         indoc! {"
-            namespace Foo {
-                function A() : Int {
-                    let x = 0;
-                    ↘
-                    let y = 0;
+            namespace Internal {
+                function Impl() : Unit {}
+            }
+            namespace Api {
+                export Internal.Impl;
+            }
+            namespace Main {
+                function Main() : Unit {
+                    Api.Impl();
                 }
             }
         "},
         &expect![[r#"
-            x (var 13)
-        "#]],
-    );
-}
-
-#[test]
-fn get_locals_vars_shadowing_same_scope() {
-    check_locals(
-        indoc! {r#"
-            namespace Foo {
-                function A() : Int {
-                    let x = 0;
-                    let x = "foo";
-                    ↘
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                export item1;
+            }
+            namespace item3 {
+                function item4() : Unit {
+                    item1();
                 }
             }
-        "#},
-        &expect![[r#"
-            x (var 17)
         "#]],
     );
 }
 
 #[test]
-fn get_locals_vars_parent_scope() {
-    check_locals(
-        indoc! {r#"
-            namespace Foo {
-                function A() : Int {
-                    let x = 0;
-                    {
-                        let y = 0;
-                        ↘
-                    }
+fn export_alias_through_open() {
+    check(
+        indoc! {"
+            namespace Internal {
+                function Impl() : Unit {}
+            }
+            namespace Api {
+                export Internal.Impl as Run;
+            }
+            namespace Main {
+                open Api;
+                function Main() : Unit {
+                    Run();
                 }
             }
-        "#},
+        "},
         &expect![[r#"
-            y (var 20)
-            x (var 13)
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                export item1 as Run;
+            }
+            namespace item3 {
+                open Api;
+                function item4() : Unit {
+                    item1();
+                }
+            }
         "#]],
     );
 }
 
 #[test]
-fn get_locals_params() {
-    check_locals(
-        indoc! {r#"
-            namespace Foo {
-                function A(x : Int) : Int {
-                    ↘
+fn export_newtype() {
+    check(
+        indoc! {"
+            namespace Internal {
+                newtype Pair = (Int, Int);
+            }
+            namespace Api {
+                export Internal.Pair;
+            }
+            namespace Main {
+                function Main() : Api.Pair {
+                    Api.Pair(1, 2)
                 }
             }
-        "#},
+        "},
         &expect![[r#"
-            x (var 8)
+            namespace item0 {
+                newtype item1 = (Int, Int);
+            }
+            namespace item2 {
+                export item1;
+            }
+            namespace item3 {
+                function item4() : item1 {
+                    item1(1, 2)
+                }
+            }
         "#]],
     );
 }
 
 #[test]
-fn get_locals_spec_params() {
-    check_locals(
+fn export_of_export_declared_later() {
+    check(
         indoc! {"
-            namespace Foo {
-                operation A(q : Qubit) : (Qubit[], Qubit) {
-                    controlled (cs, ...) {
-                        ↘
-                    }
+            namespace Outer {
+                export Api.Impl;
+            }
+            namespace Api {
+                export Internal.Impl;
+            }
+            namespace Internal {
+                function Impl() : Unit {}
+            }
+            namespace Main {
+                function Main() : Unit {
+                    Outer.Impl();
                 }
             }
         "},
         &expect![[r#"
-            cs (var 23)
-            q (var 8)
+            namespace item0 {
+                export item3;
+            }
+            namespace item1 {
+                export item3;
+            }
+            namespace item2 {
+                function item3() : Unit {}
+            }
+            namespace item4 {
+                function item5() : Unit {
+                    item3();
+                }
+            }
         "#]],
     );
 }
 
 #[test]
-fn get_locals_before_binding() {
-    check_locals(
+fn export_not_found() {
+    check(
         indoc! {"
-            namespace Foo {
-                function A() : Unit {
-                    let y = 0;
-                    let x = { ↘ };
-                }
+            namespace Api {
+                export Internal.Impl;
             }
         "},
         &expect![[r#"
-            y (var 13)
+            namespace item0 {
+                export Internal.Impl;
+            }
+
+            // NotFound("Internal.Impl", Span { lo: 27, hi: 40 })
         "#]],
     );
 }
 
 #[test]
-fn get_locals_lambda_params() {
-    check_locals(
+fn export_cycle_not_found() {
+    check(
         indoc! {"
-            namespace Foo {
-                function A() : Unit {
-                    let y = 0;
-                    let f = x -> { ↘ };
-                }
+            namespace A {
+                export B.Foo;
+            }
+            namespace B {
+                export A.Foo;
             }
         "},
         &expect![[r#"
-            x (var 20)
-            y (var 13)
+            namespace item0 {
+                export B.Foo;
+            }
+            namespace item1 {
+                export A.Foo;
+            }
+
+            // NotFound("B.Foo", Span { lo: 25, hi: 30 })
+            // NotFound("A.Foo", Span { lo: 59, hi: 64 })
         "#]],
     );
 }
 
 #[test]
-fn get_locals_for_loop() {
-    check_locals(
+fn export_duplicate() {
+    check(
         indoc! {"
-            namespace Foo {
-                function A() : Unit {
-                    for x in 0..1 {
-                        ↘
+            namespace Internal {
+                function Impl() : Unit {}
+            }
+            namespace Api {
+                function Impl() : Unit {}
+                export Internal.Impl;
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                function item3() : Unit {}
+                export item1;
+            }
+
+            // Duplicate("Impl", "Api", Span { lo: 110, hi: 123 })
+        "#]],
+    );
+}
+
+#[test]
+fn export_in_callable() {
+    check(
+        indoc! {"
+            namespace Internal {
+                function Impl() : Unit {}
+            }
+            namespace Api {
+                function Foo() : Unit {
+                    export Internal.Impl;
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                function item3() : Unit {
+                    export Internal.Impl;
+                }
+            }
+
+            // LocalExport("Internal.Impl", Span { lo: 112, hi: 125 })
+        "#]],
+    );
+}
+
+#[test]
+fn relative_path_prefers_nested_namespace() {
+    check(
+        indoc! {"
+            namespace C {
+                function Foo() : Unit {}
+            }
+            namespace A.B.C {
+                function Foo() : Unit {}
+            }
+            namespace A.B {
+                function Bar() : Unit {
+                    C.Foo();
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                function item3() : Unit {}
+            }
+            namespace item4 {
+                function item5() : Unit {
+                    item3();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn relative_path_resolves_through_parent_namespace() {
+    check(
+        indoc! {"
+            namespace A.C {
+                function Foo() : Unit {}
+            }
+            namespace A.B {
+                function Bar() : Unit {
+                    C.Foo();
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                function item3() : Unit {
+                    item1();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn relative_path_falls_back_to_global() {
+    check(
+        indoc! {"
+            namespace C {
+                function Foo() : Unit {}
+            }
+            namespace A.B {
+                function Bar() : Unit {
+                    C.Foo();
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                function item3() : Unit {
+                    item1();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn relative_open() {
+    check(
+        indoc! {"
+            namespace A.B.C {
+                function Foo() : Unit {}
+            }
+            namespace A.B {
+                open C;
+                function Bar() : Unit {
+                    Foo();
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                open C;
+                function item3() : Unit {
+                    item1();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn nested_namespace_declarations() {
+    check(
+        indoc! {"
+            namespace A {
+                function Foo() : Unit {
+                    B.Bar();
+                }
+                namespace B {
+                    function Bar() : Unit {
+                        A.Foo();
+                    }
+                }
+            }
+            namespace Main {
+                function Main() : Unit {
+                    A.B.Bar();
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {
+                    item3();
+                }
+                namespace item2 {
+                    function item3() : Unit {
+                        item1();
+                    }
+                }
+            }
+            namespace item4 {
+                function item5() : Unit {
+                    item3();
+                }
+            }
+        "#]],
+    );
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn check_locals(input: &str, expect: &Expect) {
+    let parts = input.split('↘').collect::<Vec<_>>();
+    assert_eq!(
+        parts.len(),
+        2,
+        "input must contain exactly one cursor marker"
+    );
+    let cursor_offset = parts[0].len() as u32;
+    let source = parts.join("");
+
+    let (_, _, locals, _, _) = compile(&source, LanguageFeatures::default());
+
+    let locals = locals.get_all_at_offset(cursor_offset);
+    let actual = locals.iter().fold(String::new(), |mut output, l| {
+        let _ = writeln!(
+            output,
+            "{} ({})",
+            l.name,
+            match l.kind {
+                LocalKind::Item(item_id) => item_id.to_string(),
+                LocalKind::TyParam(param_id) => format!("ty_param {param_id}"),
+                LocalKind::Var(node_id) => format!("var {node_id}"),
+            }
+        );
+        output
+    });
+
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn get_locals_vars() {
+    check_locals(
+        indoc! {"
+            namespace Foo {
+                function A() : Int {
+                    let x = 0;
+                    ↘
+                    let y = 0;
+                }
+            }
+        "},
+        &expect![[r#"
+            x (var 13)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_vars_shadowing_same_scope() {
+    check_locals(
+        indoc! {r#"
+            namespace Foo {
+                function A() : Int {
+                    let x = 0;
+                    let x = "foo";
+                    ↘
+                }
+            }
+        "#},
+        &expect![[r#"
+            x (var 17)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_vars_before_shadowing_same_scope() {
+    check_locals(
+        indoc! {r#"
+            namespace Foo {
+                function A() : Int {
+                    let x = 0;
+                    ↘
+                    let x = "foo";
+                }
+            }
+        "#},
+        &expect![[r#"
+            x (var 13)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_vars_shadowed_in_own_initializer() {
+    check_locals(
+        indoc! {r#"
+            namespace Foo {
+                function A() : Int {
+                    let x = 0;
+                    let x = x + ↘1;
+                }
+            }
+        "#},
+        &expect![[r#"
+            x (var 13)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_vars_parent_scope() {
+    check_locals(
+        indoc! {r#"
+            namespace Foo {
+                function A() : Int {
+                    let x = 0;
+                    {
+                        let y = 0;
+                        ↘
+                    }
+                }
+            }
+        "#},
+        &expect![[r#"
+            y (var 20)
+            x (var 13)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_params() {
+    check_locals(
+        indoc! {r#"
+            namespace Foo {
+                function A(x : Int) : Int {
+                    ↘
+                }
+            }
+        "#},
+        &expect![[r#"
+            x (var 8)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_spec_params() {
+    check_locals(
+        indoc! {"
+            namespace Foo {
+                operation A(q : Qubit) : (Qubit[], Qubit) {
+                    controlled (cs, ...) {
+                        ↘
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            cs (var 23)
+            q (var 8)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_before_binding() {
+    check_locals(
+        indoc! {"
+            namespace Foo {
+                function A() : Unit {
+                    let y = 0;
+                    let x = { ↘ };
+                }
+            }
+        "},
+        &expect![[r#"
+            y (var 13)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_lambda_params() {
+    check_locals(
+        indoc! {"
+            namespace Foo {
+                function A() : Unit {
+                    let y = 0;
+                    let f = x -> { ↘ };
+                }
+            }
+        "},
+        &expect![[r#"
+            x (var 20)
+            y (var 13)
+        "#]],
+    );
+}
+
+#[test]
+fn get_locals_for_loop() {
+    check_locals(
+        indoc! {"
+            namespace Foo {
+                function A() : Unit {
+                    for x in 0..1 {
+                        ↘
                     }
                 }
             }
@@ -2654,3 +3185,255 @@ fn newtype_with_tuple_destructuring() {
         "#]],
     );
 }
+
+#[test]
+fn unused_open_is_reported() {
+    check_warnings(
+        indoc! {"
+            namespace Foo {
+                function A() : Unit {}
+            }
+            namespace Bar {
+                function B() : Unit {}
+            }
+            namespace Baz {
+                open Foo;
+                open Bar;
+                function C() : Unit {
+                    A();
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                UnusedOpen(
+                    "Bar",
+                    Span {
+                        lo: 124,
+                        hi: 133,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn open_used_through_alias_or_in_type_is_not_reported() {
+    check_warnings(
+        indoc! {"
+            namespace Foo {
+                newtype Pair = (First : Int, Second : Int);
+            }
+            namespace Bar {
+                function B() : Unit {}
+            }
+            namespace Baz {
+                open Foo;
+                open Bar as B;
+                function C(pair : Pair) : Unit {
+                    B.B();
+                }
+            }
+        "},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn unused_open_in_block_is_reported() {
+    check_warnings(
+        indoc! {"
+            namespace Foo {
+                function A() : Unit {}
+            }
+            namespace Baz {
+                function C() : Unit {
+                    open Foo;
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                UnusedOpen(
+                    "Foo",
+                    Span {
+                        lo: 95,
+                        hi: 104,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn shadowed_local_in_nested_block_is_reported() {
+    check_lint_warnings(
+        indoc! {"
+            namespace Foo {
+                function A(flag : Bool) : Int {
+                    mutable result = 0;
+                    if flag {
+                        mutable result = 1;
+                    }
+                    result
+                }
+            }
+        "},
+        Lints::ShadowedLocal,
+        &expect![[r#"
+            [
+                ShadowedLocal {
+                    name: "result",
+                    span: Span {
+                        lo: 118,
+                        hi: 124,
+                    },
+                    shadowed_span: Span {
+                        lo: 68,
+                        hi: 74,
+                    },
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn shadowed_param_is_reported() {
+    check_lint_warnings(
+        indoc! {"
+            namespace Foo {
+                function A(x : Int) : Unit {
+                    for x in 0..3 {}
+                }
+            }
+        "},
+        Lints::ShadowedLocal,
+        &expect![[r#"
+            [
+                ShadowedLocal {
+                    name: "x",
+                    span: Span {
+                        lo: 61,
+                        hi: 62,
+                    },
+                    shadowed_span: Span {
+                        lo: 31,
+                        hi: 32,
+                    },
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn shadowed_local_in_same_scope_is_not_reported() {
+    check_lint_warnings(
+        indoc! {"
+            namespace Foo {
+                function A() : Int {
+                    let x = 0;
+                    let x = x + 1;
+                    x
+                }
+            }
+        "},
+        Lints::ShadowedLocal,
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn shadowed_local_in_local_callable_is_not_reported() {
+    check_lint_warnings(
+        indoc! {"
+            namespace Foo {
+                function A() : Unit {
+                    let x = 0;
+                    function B() : Unit {
+                        let x = 1;
+                    }
+                }
+            }
+        "},
+        Lints::ShadowedLocal,
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn shadowed_local_is_not_reported_when_lint_is_disabled() {
+    check_lint_warnings(
+        indoc! {"
+            namespace Foo {
+                function A(x : Int) : Unit {
+                    for x in 0..3 {}
+                }
+            }
+        "},
+        Lints::empty(),
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn deprecated_item_use_is_reported() {
+    check_warnings(
+        indoc! {r#"
+            namespace Foo {
+                @Deprecated("Foo.B")
+                function A() : Unit {}
+                function B() : Unit {}
+                @Deprecated()
+                newtype Pair = (Int, Int);
+                function C() : Unit {
+                    A();
+                    let p = Pair(1, 2);
+                }
+            }
+        "#},
+        &expect![[r#"
+            [
+                Deprecated {
+                    name: "A",
+                    help: Some(
+                        "use `Foo.B` instead",
+                    ),
+                    span: Span {
+                        lo: 178,
+                        hi: 179,
+                    },
+                },
+                Deprecated {
+                    name: "Pair",
+                    help: None,
+                    span: Span {
+                        lo: 199,
+                        hi: 203,
+                    },
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn resolver_tables_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Package>();
+    assert_send_sync::<super::GlobalScope>();
+    assert_send_sync::<Locals>();
+    assert_send_sync::<Names>();
+    assert_send_sync::<Resolver>();
+}
@@ -0,0 +1,119 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{compile::CompileUnit, typeck::Table};
+use qsc_ast::{
+    ast::{self, ExprKind, NodeId, PatKind},
+    visit::{self as ast_visit, Visitor as AstVisitor},
+};
+use qsc_data_structures::span::Span;
+use qsc_hir::ty::Ty;
+
+/// What a type hint describes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HintKind {
+    /// A name bound by a local, a callable parameter, or a loop or qubit pattern.
+    Binding,
+    /// A name bound by a lambda parameter.
+    LambdaParam,
+    /// An expression.
+    Expr,
+}
+
+/// The inferred type of a node in a compiled package.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeHint {
+    /// The AST node ID of the pattern or expression.
+    pub id: NodeId,
+    /// What the hint describes.
+    pub kind: HintKind,
+    /// The span of the node, relative to the package's sources.
+    pub span: Span,
+    /// The inferred type.
+    pub ty: Ty,
+    /// The inferred type as it is written in source.
+    pub display: String,
+}
+
+/// Finds the inferred type of each binding, lambda parameter, and expression whose span is
+/// contained in the given span of a package's sources, in source order.
+///
+/// Nodes that were not type checked, such as those in items that failed to resolve, have no hint.
+#[must_use]
+pub fn in_span(unit: &CompileUnit, span: Span) -> Vec<TypeHint> {
+    let mut collector = Collector {
+        tys: &unit.ast.tys,
+        span,
+        lambda_input: false,
+        hints: Vec::new(),
+    };
+    collector.visit_package(&unit.ast.package);
+    collector.hints
+}
+
+struct Collector<'a> {
+    tys: &'a Table,
+    span: Span,
+    lambda_input: bool,
+    hints: Vec<TypeHint>,
+}
+
+impl Collector<'_> {
+    fn push(&mut self, id: NodeId, span: Span, kind: HintKind) {
+        if self.span.lo <= span.lo && span.hi <= self.span.hi {
+            if let Some(ty) = self.tys.terms.get(id) {
+                self.hints.push(TypeHint {
+                    id,
+                    kind,
+                    span,
+                    ty: ty.clone(),
+                    display: ty.display(),
+                });
+            }
+        }
+    }
+
+    fn overlaps(&self, span: Span) -> bool {
+        span.lo < self.span.hi && self.span.lo < span.hi
+    }
+}
+
+impl<'a> AstVisitor<'a> for Collector<'_> {
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if self.overlaps(item.span) {
+            ast_visit::walk_item(self, item);
+        }
+    }
+
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if let PatKind::Bind(..) = &*pat.kind {
+            let kind = if self.lambda_input {
+                HintKind::LambdaParam
+            } else {
+                HintKind::Binding
+            };
+            self.push(pat.id, pat.span, kind);
+        }
+
+        ast_visit::walk_pat(self, pat);
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if !self.overlaps(expr.span) {
+            return;
+        }
+
+        self.push(expr.id, expr.span, HintKind::Expr);
+        if let ExprKind::Lambda(_, input, body) = &*expr.kind {
+            self.lambda_input = true;
+            self.visit_pat(input);
+            self.lambda_input = false;
+            self.visit_expr(body);
+        } else {
+            ast_visit::walk_expr(self, expr);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{in_span, HintKind};
+use crate::compile::{self, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        function Main() : Int {
+            let f = x -> x + 1;
+            f(2)
+        }
+    }
+"};
+
+fn hints(text: &str) -> Vec<(HintKind, &'static str, String)> {
+    let store = PackageStore::new(compile::core());
+    let unit = compile::compile(
+        &store,
+        &[],
+        SourceMap::new([("test".into(), SOURCE.into())], None),
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+
+    let lo = SOURCE.find(text).expect("source should contain text");
+    let span = Span {
+        lo: lo.try_into().expect("offset should fit into u32"),
+        hi: (lo + text.len())
+            .try_into()
+            .expect("offset should fit into u32"),
+    };
+    in_span(&unit, span)
+        .into_iter()
+        .map(|hint| {
+            let text = &SOURCE[hint.span.lo as usize..hint.span.hi as usize];
+            (hint.kind, text, hint.display)
+        })
+        .collect()
+}
+
+#[test]
+fn local_and_lambda() {
+    assert_eq!(
+        hints("let f = x -> x + 1;"),
+        vec![
+            (HintKind::Binding, "f", "(Int -> Int)".to_string()),
+            (HintKind::Expr, "x -> x + 1", "(Int -> Int)".to_string()),
+            (HintKind::LambdaParam, "x", "Int".to_string()),
+            (HintKind::Expr, "x + 1", "Int".to_string()),
+            (HintKind::Expr, "x", "Int".to_string()),
+            (HintKind::Expr, "1", "Int".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn call_and_arguments() {
+    assert_eq!(
+        hints("f(2)"),
+        vec![
+            (HintKind::Expr, "f(2)", "Int".to_string()),
+            (HintKind::Expr, "f", "(Int -> Int)".to_string()),
+            (HintKind::Expr, "(2)", "Int".to_string()),
+            (HintKind::Expr, "2", "Int".to_string()),
+        ]
+    );
+}
@@ -3,6 +3,7 @@
 
 mod check;
 pub(super) mod convert;
+mod functors;
 mod infer;
 mod rules;
 #[cfg(test)]
@@ -13,10 +14,10 @@ use qsc_ast::ast::NodeId;
 use qsc_data_structures::{index_map::IndexMap, span::Span};
 use qsc_hir::{
     hir::{CallableKind, ItemId},
-    ty::{FunctorSet, GenericArg, Ty, Udt},
+    ty::{ClassConstraint, FunctorSet, FunctorSetValue, GenericArg, Ty, Udt},
 };
 use rustc_hash::FxHashMap;
-use std::fmt::Debug;
+use std::{fmt::Debug, rc::Rc};
 use thiserror::Error;
 
 pub(super) use check::{Checker, GlobalTable};
@@ -24,10 +25,35 @@ pub(super) use check::{Checker, GlobalTable};
 #[derive(Debug, Default, Clone)]
 pub struct Table {
     pub udts: FxHashMap<ItemId, Udt>,
+    pub params: FxHashMap<ItemId, Vec<Param>>,
 
     // AST nodes that get mapped to types are Expr, Block, Pat, and QubitInit nodes
     pub terms: IndexMap<NodeId, Ty>,
     pub generics: IndexMap<NodeId, Vec<GenericArg>>,
+
+    /// The arguments of each call that uses named arguments or default values, in parameter order.
+    pub args: IndexMap<NodeId, Vec<Arg>>,
+
+    /// The functors inferred for each operation declared without them, by callable declaration.
+    pub functors: IndexMap<NodeId, FunctorSetValue>,
+}
+
+/// A parameter of a callable, as seen by its callers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Param {
+    /// The name of the parameter, if it binds one.
+    pub name: Option<Rc<str>>,
+    /// The item that computes the default value of the parameter, if it has one.
+    pub default: Option<ItemId>,
+}
+
+/// An argument of a call after named arguments and default values are resolved.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Arg {
+    /// The argument written at the given position in the call.
+    Given(usize),
+    /// The default value computed by the given item.
+    Default(ItemId),
 }
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -90,13 +116,60 @@ enum ErrorKind {
     #[error("type {0} cannot be converted into a string")]
     #[diagnostic(code("Qsc.TypeCk.MissingClassShow"))]
     MissingClassShow(String, #[label] Span),
+    #[error("type {0} cannot be constructed from named fields")]
+    #[diagnostic(help("only newtypes and structs whose fields are all named support `new`"))]
+    #[diagnostic(code("Qsc.TypeCk.MissingClassStruct"))]
+    MissingClassStruct(String, #[label] Span),
     #[error("type {0} cannot be unwrapped")]
     #[diagnostic(help("only newtypes support unwrap"))]
     #[diagnostic(code("Qsc.TypeCk.MissingClassUnwrap"))]
     MissingClassUnwrap(String, #[label] Span),
+    #[error("type parameter {0} is not constrained to {1}")]
+    #[diagnostic(help("add the constraint to the type parameter, like `{0}: {1}`"))]
+    #[diagnostic(code("Qsc.TypeCk.MissingParamConstraint"))]
+    MissingParamConstraint(String, ClassConstraint, #[label] Span),
+    #[error("unknown constraint `{0}`")]
+    #[diagnostic(help("the supported constraints are Add, Eq, Integral, Num, and Show"))]
+    #[diagnostic(code("Qsc.TypeCk.UnknownConstraint"))]
+    UnknownConstraint(String, #[label] Span),
     #[error("expected superset of {0}, found {1}")]
     #[diagnostic(code("Qsc.TypeCk.MissingFunctor"))]
     MissingFunctor(FunctorSet, FunctorSet, #[label] Span),
+    #[error("missing fields {1} in construction of {0}")]
+    #[diagnostic(help("every field must be given a value"))]
+    #[diagnostic(code("Qsc.TypeCk.MissingField"))]
+    MissingField(String, String, #[label] Span),
+    #[error("field `{0}` is given more than once")]
+    #[diagnostic(code("Qsc.TypeCk.DuplicateField"))]
+    DuplicateField(String, #[label] Span),
+    #[error("missing argument for parameter `{0}`")]
+    #[diagnostic(help("only parameters with default values can be left out"))]
+    #[diagnostic(code("Qsc.TypeCk.MissingArg"))]
+    MissingArg(String, #[label] Span),
+    #[error("parameter `{0}` is given more than once")]
+    #[diagnostic(code("Qsc.TypeCk.DuplicateArg"))]
+    DuplicateArg(String, #[label] Span),
+    #[error("no parameter named `{0}`")]
+    #[diagnostic(code("Qsc.TypeCk.UnknownParam"))]
+    UnknownParam(String, #[label] Span),
+    #[error("positional argument after named argument")]
+    #[diagnostic(help("named arguments must come after all positional arguments"))]
+    #[diagnostic(code("Qsc.TypeCk.PositionalAfterNamed"))]
+    PositionalAfterNamed(#[label] Span),
+    #[error("expected at most {0} arguments, found {1}")]
+    #[diagnostic(code("Qsc.TypeCk.TooManyArgs"))]
+    TooManyArgs(usize, usize, #[label] Span),
+    #[error("named arguments are only supported in calls to global callables")]
+    #[diagnostic(code("Qsc.TypeCk.UnexpectedNamedArg"))]
+    UnexpectedNamedArg(#[label] Span),
+    #[error("match is not exhaustive")]
+    #[diagnostic(help("add an arm for the missing values, or a final `_` arm"))]
+    #[diagnostic(code("Qsc.TypeCk.NonExhaustiveMatch"))]
+    NonExhaustiveMatch(#[label] Span),
+    #[error("unreachable match arm")]
+    #[diagnostic(help("the values matched by this arm are all matched by earlier arms"))]
+    #[diagnostic(code("Qsc.TypeCk.UnreachableArm"))]
+    UnreachableArm(#[label] Span),
     #[error("missing type in item signature")]
     #[diagnostic(help("types cannot be inferred for global declarations"))]
     #[diagnostic(code("Qsc.TypeCk.MissingItemTy"))]
@@ -2,11 +2,13 @@
 // Licensed under the MIT License.
 
 use super::{
+    functors,
     infer::Inferrer,
     rules::{self, SpecImpl},
-    Error, ErrorKind, Table,
+    Error, ErrorKind, Param, Table,
 };
 use crate::{
+    compile::CompileOptions,
     resolve::{Names, Res},
     typeck::convert::{self, MissingTyError},
 };
@@ -14,16 +16,17 @@ use qsc_ast::{
     ast::{self, NodeId, TopLevelNode},
     visit::{self, Visitor},
 };
-use qsc_data_structures::index_map::IndexMap;
+use qsc_data_structures::{cancellation::CancellationToken, index_map::IndexMap};
 use qsc_hir::{
     hir::{self, ItemId, PackageId},
-    ty::{FunctorSetValue, Scheme, Ty, Udt},
+    ty::{ClassConstraint, FunctorSetValue, Scheme, Ty, Udt},
 };
 use rustc_hash::FxHashMap;
-use std::vec;
+use std::{rc::Rc, vec};
 
 pub(crate) struct GlobalTable {
     udts: FxHashMap<ItemId, Udt>,
+    params: FxHashMap<ItemId, Vec<Param>>,
     terms: FxHashMap<ItemId, Scheme>,
     errors: Vec<Error>,
 }
@@ -32,6 +35,7 @@ impl GlobalTable {
     pub(crate) fn new() -> Self {
         Self {
             udts: FxHashMap::default(),
+            params: FxHashMap::default(),
             terms: FxHashMap::default(),
             errors: Vec::new(),
         }
@@ -46,6 +50,8 @@ impl GlobalTable {
 
             match &item.kind {
                 hir::ItemKind::Callable(decl) => {
+                    self.params
+                        .insert(item_id, convert::hir_callable_params(decl, id));
                     self.terms.insert(item_id, decl.scheme().with_package(id))
                 }
                 hir::ItemKind::Namespace(..) => None,
@@ -65,6 +71,9 @@ pub(crate) struct Checker {
     inferrer: Inferrer,
     new: Vec<NodeId>,
     errors: Vec<Error>,
+    infer_functors: bool,
+    /// Stops checking before the next callable when it is cancelled.
+    cancellation: CancellationToken,
 }
 
 impl Checker {
@@ -73,15 +82,25 @@ impl Checker {
             globals: globals.terms,
             table: Table {
                 udts: globals.udts,
+                params: globals.params,
                 terms: IndexMap::new(),
                 generics: IndexMap::new(),
+                args: IndexMap::new(),
+                functors: IndexMap::new(),
             },
             inferrer: Inferrer::new(),
             new: Vec::new(),
             errors: globals.errors,
+            infer_functors: false,
+            cancellation: CancellationToken::default(),
         }
     }
 
+    pub(crate) fn set_options(&mut self, options: &CompileOptions) {
+        self.infer_functors = options.infer_functors;
+        self.cancellation = options.cancellation.clone();
+    }
+
     pub(crate) fn table(&self) -> &Table {
         &self.table
     }
@@ -96,6 +115,9 @@ impl Checker {
 
     pub(crate) fn check_package(&mut self, names: &Names, package: &ast::Package) {
         ItemCollector::new(self, names).visit_package(package);
+        if self.infer_functors {
+            functors::infer(names, &mut self.globals, &mut self.table, package);
+        }
         ItemChecker::new(self, names).visit_package(package);
 
         if let Some(entry) = &package.entry {
@@ -123,6 +145,17 @@ impl Checker {
     fn check_callable_decl(&mut self, names: &Names, decl: &ast::CallableDecl) {
         self.check_callable_signature(names, decl);
         let output = convert::ty_from_ast(names, &decl.output).0;
+        let params = convert::ast_callable_generics(&decl.generics);
+        if !decl.defaults.is_empty() {
+            self.errors.append(&mut rules::defaults(
+                names,
+                &self.globals,
+                &mut self.table,
+                &params,
+                decl,
+            ));
+        }
+
         match &*decl.body {
             ast::CallableBody::Block(block) => self.check_spec(
                 names,
@@ -132,6 +165,7 @@ impl Checker {
                     spec_input: None,
                     output: &output,
                     output_span: decl.output.span,
+                    params: &params,
                     block,
                 },
             ),
@@ -146,6 +180,7 @@ impl Checker {
                                 spec_input: Some(input),
                                 output: &output,
                                 output_span: decl.output.span,
+                                params: &params,
                                 block,
                             },
                         );
@@ -215,6 +250,19 @@ impl Visitor<'_> for ItemCollector<'_> {
                         .push(Error(ErrorKind::MissingItemTy(span)));
                 }
 
+                for constraint in decl.generics.iter().flat_map(|p| p.constraints.iter()) {
+                    if constraint.name.parse::<ClassConstraint>().is_err() {
+                        self.checker.errors.push(Error(ErrorKind::UnknownConstraint(
+                            constraint.name.to_string(),
+                            constraint.span,
+                        )));
+                    }
+                }
+
+                self.checker
+                    .table
+                    .params
+                    .insert(item, convert::ast_callable_params(self.names, decl));
                 self.checker.globals.insert(item, scheme);
             }
             ast::ItemKind::Ty(name, def) => {
@@ -224,7 +272,7 @@ impl Visitor<'_> for ItemCollector<'_> {
                 };
 
                 let (cons, cons_errors) =
-                    convert::ast_ty_def_cons(self.names, &name.name, item, def);
+                    convert::ast_ty_def_cons(self.names, &Rc::from(&*name.name), item, def);
                 let (udt_def, def_errors) = convert::ast_ty_def(self.names, def);
                 self.checker.errors.extend(
                     cons_errors
@@ -236,7 +284,7 @@ impl Visitor<'_> for ItemCollector<'_> {
                 self.checker.table.udts.insert(
                     item,
                     Udt {
-                        name: name.name.clone(),
+                        name: Rc::from(&*name.name),
                         span,
                         definition: udt_def,
                     },
@@ -266,6 +314,10 @@ impl<'a> ItemChecker<'a> {
 
 impl Visitor<'_> for ItemChecker<'_> {
     fn visit_callable_decl(&mut self, decl: &ast::CallableDecl) {
+        if self.checker.cancellation.is_cancelled() {
+            return;
+        }
+
         self.checker.check_callable_decl(self.names, decl);
         visit::walk_callable_decl(self, decl);
     }
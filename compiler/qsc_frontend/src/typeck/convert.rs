@@ -3,10 +3,11 @@
 
 use std::rc::Rc;
 
+use super::Param;
 use crate::resolve::{self, Names};
 use qsc_ast::ast::{
-    self, CallableBody, CallableDecl, CallableKind, FunctorExpr, FunctorExprKind, Ident, Pat,
-    PatKind, SetOp, Spec, TyDef, TyDefKind, TyKind,
+    self, CallableBody, CallableDecl, CallableKind, FunctorExpr, FunctorExprKind, Pat, PatKind,
+    SetOp, Spec, TyDef, TyDefKind, TyKind, TypeParameter,
 };
 use qsc_data_structures::span::Span;
 use qsc_hir::{
@@ -19,6 +20,25 @@ use qsc_hir::{
 
 pub(crate) struct MissingTyError(pub(super) Span);
 
+pub(crate) fn ty_from_path(names: &Names, path: &ast::Path) -> Ty {
+    match names.get(path.id) {
+        Some(&resolve::Res::Item(item, _)) => {
+            Ty::Udt(Rc::from(&*path.name.name), hir::Res::Item(item))
+        }
+        Some(&resolve::Res::PrimTy(prim)) => Ty::Prim(prim),
+        Some(resolve::Res::UnitTy) => Ty::Tuple(Vec::new()),
+        // a path should never resolve to a parameter,
+        // as there is a syntactic difference between
+        // paths and parameters.
+        // So realistically, by construction, `Param` here is unreachable.
+        Some(resolve::Res::Local(_) | resolve::Res::Param(_)) => unreachable!(
+            "A path should never resolve \
+            to a local or a parameter, as there is syntactic differentiation."
+        ),
+        None => Ty::Err,
+    }
+}
+
 pub(crate) fn ty_from_ast(names: &Names, ty: &ast::Ty) -> (Ty, Vec<MissingTyError>) {
     match &*ty.kind {
         TyKind::Array(item) => {
@@ -42,27 +62,9 @@ pub(crate) fn ty_from_ast(names: &Names, ty: &ast::Ty) -> (Ty, Vec<MissingTyErro
         }
         TyKind::Hole => (Ty::Err, vec![MissingTyError(ty.span)]),
         TyKind::Paren(inner) => ty_from_ast(names, inner),
-        TyKind::Path(path) => {
-            let ty = match names.get(path.id) {
-                Some(&resolve::Res::Item(item, _)) => {
-                    Ty::Udt(path.name.name.clone(), hir::Res::Item(item))
-                }
-                Some(&resolve::Res::PrimTy(prim)) => Ty::Prim(prim),
-                Some(resolve::Res::UnitTy) => Ty::Tuple(Vec::new()),
-                // a path should never resolve to a parameter,
-                // as there is a syntactic difference between
-                // paths and parameters.
-                // So realistically, by construction, `Param` here is unreachable.
-                Some(resolve::Res::Local(_) | resolve::Res::Param(_)) => unreachable!(
-                    "A path should never resolve \
-                    to a local or a parameter, as there is syntactic differentiation."
-                ),
-                None => Ty::Err,
-            };
-            (ty, Vec::new())
-        }
+        TyKind::Path(path) => (ty_from_path(names, path), Vec::new()),
         TyKind::Param(name) => match names.get(name.id) {
-            Some(resolve::Res::Param(id)) => (Ty::Param(name.name.clone(), *id), Vec::new()),
+            Some(resolve::Res::Param(id)) => (Ty::Param(Rc::from(&*name.name), *id), Vec::new()),
             Some(_) => unreachable!(
                 "A parameter should never resolve to a non-parameter type, as there \
                     is syntactic differentiation"
@@ -132,7 +134,7 @@ pub(super) fn ast_ty_def(names: &Names, def: &TyDef) -> (UdtDef, Vec<MissingTyEr
                 let (ty, item_errors) = ty_from_ast(names, ty);
                 errors.extend(item_errors);
                 let (name_span, name) = match name {
-                    Some(name) => (Some(name.span), Some(name.name.clone())),
+                    Some(name) => (Some(name.span), Some(Rc::from(&*name.name))),
                     None => (None, None),
                 };
                 let field = UdtField {
@@ -187,8 +189,50 @@ pub(super) fn ast_callable_scheme(
     (Scheme::new(params, Box::new(ty)), errors)
 }
 
+/// The parameters of the callable, as seen by callers using named arguments and default values.
+pub(super) fn ast_callable_params(names: &Names, decl: &CallableDecl) -> Vec<Param> {
+    decl.params()
+        .into_iter()
+        .enumerate()
+        .map(|(ix, pat)| Param {
+            name: match &*pat.kind {
+                PatKind::Bind(name, _) => Some(Rc::from(&*name.name)),
+                _ => None,
+            },
+            default: match (decl.defaults.get(ix), names.get(pat.id)) {
+                (Some(Some(_)), Some(&resolve::Res::Item(item, _))) => Some(item),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+/// The parameters of a callable from another package, as seen by callers using named arguments
+/// and default values.
+pub(super) fn hir_callable_params(decl: &hir::CallableDecl, package: hir::PackageId) -> Vec<Param> {
+    decl.params()
+        .into_iter()
+        .enumerate()
+        .map(|(ix, pat)| Param {
+            name: match &pat.kind {
+                hir::PatKind::Bind(name) => Some(Rc::clone(&name.name)),
+                _ => None,
+            },
+            default: decl
+                .defaults
+                .get(ix)
+                .copied()
+                .flatten()
+                .map(|item| hir::ItemId {
+                    package: Some(package),
+                    item,
+                }),
+        })
+        .collect()
+}
+
 pub(crate) fn synthesize_callable_generics(
-    generics: &[Box<Ident>],
+    generics: &[Box<TypeParameter>],
     input: &mut hir::Pat,
 ) -> Vec<GenericParam> {
     let mut params = ast_callable_generics(generics);
@@ -238,14 +282,19 @@ fn synthesize_functor_params_in_pat(
     }
 }
 
-fn ast_callable_generics(generics: &[Box<Ident>]) -> Vec<GenericParam> {
+pub(super) fn ast_callable_generics(generics: &[Box<TypeParameter>]) -> Vec<GenericParam> {
     generics
         .iter()
-        .map(|param| {
-            GenericParam::Ty(TypeParamName {
-                span: param.span,
-                name: param.name.clone(),
-            })
+        .map(|param| GenericParam::Ty {
+            name: TypeParamName {
+                span: param.ty.span,
+                name: Rc::from(&*param.ty.name),
+            },
+            bounds: param
+                .constraints
+                .iter()
+                .filter_map(|constraint| constraint.name.parse().ok())
+                .collect(),
         })
         .collect()
 }
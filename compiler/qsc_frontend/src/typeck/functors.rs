@@ -0,0 +1,207 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{convert, Table};
+use crate::resolve::{Names, Res};
+use qsc_ast::{
+    ast::{self, CallableBody, CallableDecl, ExprKind, StmtKind, UnOp},
+    visit::{self, Visitor},
+};
+use qsc_hir::{
+    hir::{CallableKind, ItemId},
+    ty::{FunctorSet, FunctorSetValue, Scheme, Ty},
+};
+use rustc_hash::FxHashMap;
+
+/// Infers the functors of each operation that returns `Unit` and is declared with neither a functor
+/// set nor specializations. Every such operation starts out supporting both functors, and loses a
+/// functor when its body calls an operation without it or uses an expression that the functor
+/// cannot be generated for, until no operation changes.
+pub(super) fn infer(
+    names: &Names,
+    globals: &mut FxHashMap<ItemId, Scheme>,
+    table: &mut Table,
+    package: &ast::Package,
+) {
+    let mut candidates = Candidates {
+        names,
+        decls: Vec::new(),
+    };
+    candidates.visit_package(package);
+    let decls = candidates.decls;
+
+    let mut inferred: FxHashMap<_, _> = decls
+        .iter()
+        .map(|&(item, _)| (item, FunctorSetValue::CtlAdj))
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &(item, decl) in &decls {
+            let CallableBody::Block(block) = &*decl.body else {
+                continue;
+            };
+
+            let mut body = BodyFunctors {
+                names,
+                globals,
+                inferred: &inferred,
+                functors: FunctorSetValue::CtlAdj,
+                op_call_allowed: true,
+            };
+            body.visit_block(block);
+            let functors = body.functors;
+            if inferred.insert(item, functors) != Some(functors) {
+                changed = true;
+            }
+        }
+    }
+
+    for (item, decl) in decls {
+        let functors = inferred[&item];
+        if let Some(scheme) = globals.get_mut(&item) {
+            *scheme = scheme.with_functors(functors);
+        }
+        table.functors.insert(decl.id, functors);
+    }
+}
+
+struct Candidates<'a> {
+    names: &'a Names,
+    decls: Vec<(ItemId, &'a CallableDecl)>,
+}
+
+impl<'a> Visitor<'a> for Candidates<'a> {
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if let ast::ItemKind::Callable(decl) = &*item.kind {
+            if let Some(&Res::Item(id, _)) = self.names.get(decl.name.id) {
+                if is_candidate(self.names, decl) {
+                    self.decls.push((id, decl));
+                }
+            }
+        }
+
+        visit::walk_item(self, item);
+    }
+}
+
+fn is_candidate(names: &Names, decl: &CallableDecl) -> bool {
+    decl.kind == ast::CallableKind::Operation
+        && decl.functors.is_none()
+        && matches!(&*decl.body, CallableBody::Block(_))
+        && matches!(convert::ty_from_ast(names, &decl.output).0, Ty::Tuple(items) if items.is_empty())
+}
+
+/// Finds the functors that can be generated for a body, following the same rules as
+/// specialization generation.
+struct BodyFunctors<'a> {
+    names: &'a Names,
+    globals: &'a FxHashMap<ItemId, Scheme>,
+    inferred: &'a FxHashMap<ItemId, FunctorSetValue>,
+    functors: FunctorSetValue,
+    op_call_allowed: bool,
+}
+
+impl BodyFunctors<'_> {
+    /// The kind and functors of the callee, if it is a callable item.
+    fn callee(&self, callee: &ast::Expr) -> Option<(CallableKind, FunctorSetValue)> {
+        match &*callee.kind {
+            ExprKind::Paren(inner) | ExprKind::UnOp(UnOp::Functor(_), inner) => self.callee(inner),
+            ExprKind::Path(path) => match self.names.get(path.id) {
+                Some(&Res::Item(item, _)) => {
+                    let ty = self.globals.get(&item)?.ty();
+                    let functors = match (self.inferred.get(&item), ty.functors) {
+                        (Some(&functors), _) | (None, FunctorSet::Value(functors)) => functors,
+                        (None, _) => FunctorSetValue::Empty,
+                    };
+                    Some((ty.kind, functors))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn forbid_adj(&mut self) {
+        self.functors = self.functors.intersect(&FunctorSetValue::Ctl);
+    }
+
+    /// Visits an expression whose value is used, where the adjoint cannot separate an operation
+    /// call from the classical computation around it.
+    fn visit_nested(&mut self, f: impl FnOnce(&mut Self)) {
+        let prior = self.op_call_allowed;
+        self.op_call_allowed = false;
+        f(self);
+        self.op_call_allowed = prior;
+    }
+}
+
+impl<'a> Visitor<'a> for BodyFunctors<'_> {
+    // Items declared in the body have their own functors.
+    fn visit_item(&mut self, _: &'a ast::Item) {}
+
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        match &*stmt.kind {
+            StmtKind::Local(_, _, value) => self.visit_nested(|vis| vis.visit_expr(value)),
+            StmtKind::Qubit(_, _, init, block) => {
+                self.visit_nested(|vis| vis.visit_qubit_init(init));
+                if let Some(block) = block {
+                    self.visit_block(block);
+                }
+            }
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        match &*expr.kind {
+            ExprKind::Call(callee, arg) => {
+                match self.callee(callee) {
+                    Some((CallableKind::Function, _)) => {}
+                    Some((CallableKind::Operation, functors)) => {
+                        self.functors = self.functors.intersect(&functors);
+                        if !self.op_call_allowed {
+                            self.forbid_adj();
+                        }
+                    }
+                    None => self.functors = FunctorSetValue::Empty,
+                }
+                self.visit_nested(|vis| {
+                    vis.visit_expr(callee);
+                    vis.visit_expr(arg);
+                });
+            }
+            ExprKind::If(cond, body, otherwise) => {
+                self.visit_nested(|vis| vis.visit_expr(cond));
+                self.visit_block(body);
+                if let Some(otherwise) = otherwise {
+                    self.visit_expr(otherwise);
+                }
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                self.visit_nested(|vis| vis.visit_expr(scrutinee));
+                arms.iter().for_each(|arm| self.visit_expr(&arm.body));
+            }
+            ExprKind::For(_, iter, block) => {
+                self.visit_nested(|vis| vis.visit_expr(iter));
+                self.visit_block(block);
+            }
+            ExprKind::Block(..) | ExprKind::Conjugate(..) | ExprKind::Paren(..) => {
+                visit::walk_expr(self, expr);
+            }
+            ExprKind::Assign(..)
+            | ExprKind::AssignOp(..)
+            | ExprKind::AssignUpdate(..)
+            | ExprKind::Repeat(..)
+            | ExprKind::Return(..)
+            | ExprKind::While(..) => {
+                self.forbid_adj();
+                self.visit_nested(|vis| visit::walk_expr(vis, expr));
+            }
+            // The body of a lambda runs when the lambda is called, which already makes the
+            // functors unknown.
+            ExprKind::Lambda(..) => {}
+            _ => self.visit_nested(|vis| visit::walk_expr(vis, expr)),
+        }
+    }
+}
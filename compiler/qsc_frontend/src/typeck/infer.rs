@@ -6,8 +6,8 @@ use qsc_data_structures::{index_map::IndexMap, span::Span};
 use qsc_hir::{
     hir::{ItemId, PrimField, Res},
     ty::{
-        Arrow, FunctorSet, FunctorSetValue, GenericArg, GenericParam, InferFunctorId, InferTyId,
-        Prim, Scheme, Ty, Udt,
+        Arrow, ClassConstraint, FunctorSet, FunctorSetValue, GenericArg, GenericParam,
+        InferFunctorId, InferTyId, Prim, Scheme, Ty, Udt,
     },
 };
 use rustc_hash::FxHashMap;
@@ -59,6 +59,10 @@ pub(super) enum Class {
     },
     Num(Ty),
     Show(Ty),
+    Struct {
+        record: Ty,
+        fields: Vec<(String, Ty, Span)>,
+    },
     Unwrap {
         wrapper: Ty,
         base: Ty,
@@ -79,7 +83,7 @@ impl Class {
             Self::Call { callee, .. } => vec![callee],
             Self::Ctl { op, .. } => vec![op],
             Self::Exp { base, .. } => vec![base],
-            Self::HasField { record, .. } => vec![record],
+            Self::HasField { record, .. } | Self::Struct { record, .. } => vec![record],
             Self::HasIndex {
                 container, index, ..
             } => vec![container, index],
@@ -131,6 +135,13 @@ impl Class {
             },
             Self::Num(ty) => Self::Num(f(ty)),
             Self::Show(ty) => Self::Show(f(ty)),
+            Self::Struct { record, fields } => Self::Struct {
+                record: f(record),
+                fields: fields
+                    .into_iter()
+                    .map(|(name, ty, span)| (name, f(ty), span))
+                    .collect(),
+            },
             Self::Unwrap { wrapper, base } => Self::Unwrap {
                 wrapper: f(wrapper),
                 base: f(base),
@@ -138,7 +149,51 @@ impl Class {
         }
     }
 
-    fn check(self, udts: &FxHashMap<ItemId, Udt>, span: Span) -> (Vec<Constraint>, Vec<Error>) {
+    fn from_bound(bound: ClassConstraint, ty: Ty) -> Self {
+        match bound {
+            ClassConstraint::Add => Self::Add(ty),
+            ClassConstraint::Eq => Self::Eq(ty),
+            ClassConstraint::Integral => Self::Integral(ty),
+            ClassConstraint::Num => Self::Num(ty),
+            ClassConstraint::Show => Self::Show(ty),
+        }
+    }
+
+    /// The constraint that a type parameter must declare to belong to this class, if any.
+    fn bound(&self) -> Option<(ClassConstraint, &Ty)> {
+        match self {
+            Self::Add(ty) => Some((ClassConstraint::Add, ty)),
+            Self::Eq(ty) => Some((ClassConstraint::Eq, ty)),
+            Self::Integral(ty) => Some((ClassConstraint::Integral, ty)),
+            Self::Num(ty) => Some((ClassConstraint::Num, ty)),
+            Self::Show(ty) => Some((ClassConstraint::Show, ty)),
+            _ => None,
+        }
+    }
+
+    fn check(
+        self,
+        udts: &FxHashMap<ItemId, Udt>,
+        params: &[GenericParam],
+        span: Span,
+    ) -> (Vec<Constraint>, Vec<Error>) {
+        if let Some((bound, Ty::Param(name, id))) = self.bound() {
+            let bounded = matches!(
+                params.get(usize::from(*id)),
+                Some(GenericParam::Ty { bounds, .. }) if bounds.contains(&bound)
+            );
+            let errors = if bounded {
+                Vec::new()
+            } else {
+                vec![Error(ErrorKind::MissingParamConstraint(
+                    name.to_string(),
+                    bound,
+                    span,
+                ))]
+            };
+            return (Vec::new(), errors);
+        }
+
         match self {
             Class::Add(ty) if check_add(&ty) => (Vec::new(), Vec::new()),
             Class::Add(ty) => (
@@ -174,6 +229,7 @@ impl Class {
                 vec![Error(ErrorKind::MissingClassNum(ty.display(), span))],
             ),
             Class::Show(ty) => check_show(ty, span),
+            Class::Struct { record, fields } => check_struct(udts, &record, fields, span),
             Class::Unwrap { wrapper, base } => check_unwrap(udts, &wrapper, base, span),
         }
     }
@@ -338,7 +394,7 @@ pub(super) struct Inferrer {
 impl Inferrer {
     pub(super) fn new() -> Self {
         Self {
-            solver: Solver::new(),
+            solver: Solver::new(Vec::new()),
             constraints: VecDeque::new(),
             next_ty: InferTyId::default(),
             next_functor: InferFunctorId::default(),
@@ -346,6 +402,14 @@ impl Inferrer {
         }
     }
 
+    /// Creates an inferrer for the body of a callable with the given generic parameters.
+    pub(super) fn with_params(params: Vec<GenericParam>) -> Self {
+        Self {
+            solver: Solver::new(params),
+            ..Self::new()
+        }
+    }
+
     /// Introduces an equality constraint between the expected and actual types.
     pub(super) fn eq(&mut self, span: Span, expected: Ty, actual: Ty) {
         self.constraints.push_back(Constraint::Eq {
@@ -360,6 +424,11 @@ impl Inferrer {
         self.constraints.push_back(Constraint::Class(class, span));
     }
 
+    /// Reports an error found while collecting constraints.
+    pub(super) fn error(&mut self, error: Error) {
+        self.solver.errors.push(error);
+    }
+
     /// Returns a unique unconstrained type variable.
     pub(super) fn fresh_ty(&mut self, meta: TySource) -> Ty {
         let fresh = self.next_ty;
@@ -381,7 +450,13 @@ impl Inferrer {
             .params()
             .iter()
             .map(|param| match param {
-                GenericParam::Ty(_) => GenericArg::Ty(self.fresh_ty(TySource::not_divergent(span))),
+                GenericParam::Ty { bounds, .. } => {
+                    let ty = self.fresh_ty(TySource::not_divergent(span));
+                    for &bound in bounds {
+                        self.class(span, Class::from_bound(bound, ty.clone()));
+                    }
+                    GenericArg::Ty(ty)
+                }
                 GenericParam::Functor(expected) => {
                     let actual = self.fresh_functor();
                     self.constraints.push_back(Constraint::Superset {
@@ -451,15 +526,18 @@ impl Inferrer {
 #[derive(Debug)]
 struct Solver {
     solution: Solution,
+    /// The generic parameters of the callable being checked.
+    params: Vec<GenericParam>,
     pending_tys: FxHashMap<InferTyId, Vec<Class>>,
     pending_functors: FxHashMap<InferFunctorId, FunctorSetValue>,
     errors: Vec<Error>,
 }
 
 impl Solver {
-    fn new() -> Self {
+    fn new(params: Vec<GenericParam>) -> Self {
         Self {
             solution: Solution::default(),
+            params,
             pending_tys: FxHashMap::default(),
             pending_functors: FxHashMap::default(),
             errors: Vec::new(),
@@ -514,7 +592,7 @@ impl Solver {
         } else {
             let (constraints, mut errors) = class
                 .map(|ty| substituted_ty(&self.solution, ty))
-                .check(udts, span);
+                .check(udts, &self.params, span);
             self.errors.append(&mut errors);
             constraints
         }
@@ -1025,6 +1103,70 @@ fn check_show(ty: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>) {
     }
 }
 
+fn check_struct(
+    udts: &FxHashMap<ItemId, Udt>,
+    record: &Ty,
+    fields: Vec<(String, Ty, Span)>,
+    span: Span,
+) -> (Vec<Constraint>, Vec<Error>) {
+    let Ty::Udt(_, Res::Item(id)) = record else {
+        return (
+            Vec::new(),
+            vec![Error(ErrorKind::MissingClassStruct(record.display(), span))],
+        );
+    };
+    let Some((udt, mut missing)) = udts
+        .get(id)
+        .and_then(|udt| udt.field_names().map(|names| (udt, names)))
+    else {
+        return (
+            Vec::new(),
+            vec![Error(ErrorKind::MissingClassStruct(record.display(), span))],
+        );
+    };
+
+    let mut constraints = Vec::new();
+    let mut errors = Vec::new();
+    let mut assigned = Vec::new();
+    for (name, item, field_span) in fields {
+        if assigned.contains(&name) {
+            errors.push(Error(ErrorKind::DuplicateField(name, field_span)));
+            continue;
+        }
+        missing.retain(|missing| missing.as_ref() != name);
+        match udt.field_ty_by_name(&name) {
+            Some(ty) => constraints.push(Constraint::Eq {
+                expected: id
+                    .package
+                    .map_or_else(|| ty.clone(), |package_id| ty.with_package(package_id)),
+                actual: item,
+                span: field_span,
+            }),
+            None => errors.push(Error(ErrorKind::MissingClassHasField(
+                record.display(),
+                name.clone(),
+                field_span,
+            ))),
+        }
+        assigned.push(name);
+    }
+
+    if !missing.is_empty() {
+        let missing = missing
+            .iter()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        errors.push(Error(ErrorKind::MissingField(
+            record.display(),
+            missing,
+            span,
+        )));
+    }
+
+    (constraints, errors)
+}
+
 fn check_unwrap(
     udts: &FxHashMap<ItemId, Udt>,
     wrapper: &Ty,
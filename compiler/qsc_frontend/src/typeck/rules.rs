@@ -4,20 +4,21 @@
 use super::{
     convert,
     infer::{ArgTy, Class, Inferrer, TySource},
-    Error, Table,
+    Arg, Error, ErrorKind, Param, Table,
 };
 use crate::resolve::{self, Names, Res};
 use qsc_ast::ast::{
-    self, BinOp, Block, Expr, ExprKind, Functor, Lit, NodeId, Pat, PatKind, QubitInit,
-    QubitInitKind, Spec, Stmt, StmtKind, StringComponent, TernOp, TyKind, UnOp,
+    self, BinOp, Block, Expr, ExprKind, Functor, Lit, MatchArm, MatchPat, MatchPatKind, NodeId,
+    Pat, PatKind, QubitInit, QubitInitKind, Spec, Stmt, StmtKind, StringComponent, TernOp, TyKind,
+    UnOp,
 };
 use qsc_data_structures::span::Span;
 use qsc_hir::{
     hir::{self, ItemId},
-    ty::{Arrow, FunctorSet, FunctorSetValue, GenericArg, Prim, Scheme, Ty},
+    ty::{Arrow, FunctorSet, FunctorSetValue, GenericArg, GenericParam, Prim, Scheme, Ty},
 };
 use rustc_hash::FxHashMap;
-use std::convert::identity;
+use std::{convert::identity, mem::discriminant, rc::Rc};
 
 /// An inferred partial term has a type, but may be the result of a diverging (non-terminating)
 /// computation.
@@ -106,7 +107,9 @@ impl<'a> Context<'a> {
             TyKind::Hole => self.inferrer.fresh_ty(TySource::not_divergent(ty.span)),
             TyKind::Paren(inner) => self.infer_ty(inner),
             TyKind::Path(path) => match self.names.get(path.id) {
-                Some(&Res::Item(item, _)) => Ty::Udt(path.name.name.clone(), hir::Res::Item(item)),
+                Some(&Res::Item(item, _)) => {
+                    Ty::Udt(Rc::from(&*path.name.name), hir::Res::Item(item))
+                }
                 Some(&Res::PrimTy(prim)) => Ty::Prim(prim),
                 Some(Res::UnitTy) => Ty::Tuple(Vec::new()),
                 None => Ty::Err,
@@ -120,7 +123,7 @@ impl<'a> Context<'a> {
                 ),
             },
             TyKind::Param(name) => match self.names.get(name.id) {
-                Some(Res::Param(id)) => Ty::Param(name.name.clone(), *id),
+                Some(Res::Param(id)) => Ty::Param(Rc::from(&*name.name), *id),
                 None => Ty::Err,
                 Some(_) => unreachable!(
                     "A parameter should never resolve to a non-parameter type, as there \
@@ -228,14 +231,22 @@ impl<'a> Context<'a> {
             ExprKind::BinOp(op, lhs, rhs) => self.infer_binop(expr.span, *op, lhs, rhs),
             ExprKind::Block(block) => self.infer_block(block),
             ExprKind::Call(callee, input) => {
+                let params = self
+                    .callee_params(callee)
+                    .filter(|params| uses_params(params, input));
                 let callee = self.infer_expr(callee);
-                let input = self.infer_hole_tuple(
-                    ArgTy::Hole,
-                    ArgTy::Given,
-                    ArgTy::Tuple,
-                    ArgTy::to_ty,
-                    input,
-                );
+                let input = match params {
+                    Some(params) => self
+                        .infer_args(expr.id, expr.span, &params, input)
+                        .map(ArgTy::Given),
+                    None => self.infer_hole_tuple(
+                        ArgTy::Hole,
+                        ArgTy::Given,
+                        ArgTy::Tuple,
+                        ArgTy::to_ty,
+                        input,
+                    ),
+                };
                 let output_ty = self.inferrer.fresh_ty(TySource::not_divergent(expr.span));
                 self.inferrer.class(
                     expr.span,
@@ -365,15 +376,30 @@ impl<'a> Context<'a> {
                     functors: self.inferrer.fresh_functor(),
                 })))
             }
-            ExprKind::Lit(lit) => match lit.as_ref() {
-                Lit::BigInt(_) => converge(Ty::Prim(Prim::BigInt)),
-                Lit::Bool(_) => converge(Ty::Prim(Prim::Bool)),
-                Lit::Double(_) => converge(Ty::Prim(Prim::Double)),
-                Lit::Int(_) => converge(Ty::Prim(Prim::Int)),
-                Lit::Pauli(_) => converge(Ty::Prim(Prim::Pauli)),
-                Lit::Result(_) => converge(Ty::Prim(Prim::Result)),
-                Lit::String(_) => converge(Ty::Prim(Prim::String)),
-            },
+            ExprKind::Lit(lit) => converge(lit_ty(lit)),
+            ExprKind::Match(scrutinee, arms) => {
+                let scrutinee = self.infer_expr(scrutinee);
+                let mut ty = None;
+                let mut diverges = !arms.is_empty();
+                for arm in &**arms {
+                    self.infer_match_pat(&arm.pat, &scrutinee.ty);
+                    let body_span = arm.body.span;
+                    let body = self.infer_expr(&arm.body);
+                    diverges = diverges && body.diverges;
+                    match &ty {
+                        None => ty = Some(body.ty),
+                        Some(ty) => self.inferrer.eq(body_span, ty.clone(), body.ty),
+                    }
+                }
+                self.check_match_arms(expr.span, arms);
+                self.diverge_if(
+                    scrutinee.diverges,
+                    Partial {
+                        ty: ty.unwrap_or(Ty::Err),
+                        diverges,
+                    },
+                )
+            }
             ExprKind::Paren(expr) => self.infer_expr(expr),
             ExprKind::Path(path) => match self.names.get(path.id) {
                 None => converge(Ty::Err),
@@ -435,6 +461,24 @@ impl<'a> Context<'a> {
                 }
                 self.diverge()
             }
+            ExprKind::Struct(path, fields) => {
+                let record = convert::ty_from_path(self.names, path);
+                let mut diverges = false;
+                let mut assigns = Vec::new();
+                for field in &**fields {
+                    let value = self.infer_expr(&field.value);
+                    diverges = diverges || value.diverges;
+                    assigns.push((field.field.name.to_string(), value.ty, field.span));
+                }
+                self.inferrer.class(
+                    expr.span,
+                    Class::Struct {
+                        record: record.clone(),
+                        fields: assigns,
+                    },
+                );
+                self.diverge_if(diverges, converge(record))
+            }
             ExprKind::TernOp(TernOp::Cond, cond, if_true, if_false) => {
                 let cond_span = cond.span;
                 let cond = self.infer_expr(cond);
@@ -477,6 +521,11 @@ impl<'a> Context<'a> {
                 self.typed_holes.push((expr.id, expr.span));
                 converge(self.inferrer.fresh_ty(TySource::not_divergent(expr.span)))
             }
+            ExprKind::NamedArg(_, value) => {
+                self.inferrer
+                    .error(Error(ErrorKind::UnexpectedNamedArg(expr.span)));
+                self.infer_expr(value)
+            }
             ExprKind::Err => converge(Ty::Err),
         };
 
@@ -484,6 +533,168 @@ impl<'a> Context<'a> {
         ty
     }
 
+    /// The parameters of the callee if it is a global callable, which supports named arguments and
+    /// default values.
+    fn callee_params(&self, callee: &Expr) -> Option<Vec<Param>> {
+        match callee.kind.as_ref() {
+            ExprKind::Paren(inner) => self.callee_params(inner),
+            ExprKind::Path(path) => match self.names.get(path.id) {
+                Some(&Res::Item(item, _)) => self.table.params.get(&item).cloned(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Infers the arguments of a call that uses named arguments or default values, matching each
+    /// argument to its parameter. The resolved arguments are recorded for the call, and the type
+    /// of the input to the callee is returned.
+    fn infer_args(
+        &mut self,
+        call: NodeId,
+        span: Span,
+        params: &[Param],
+        input: &Expr,
+    ) -> Partial<Ty> {
+        let args = call_args(input);
+        let mut slots: Vec<Option<(Arg, Ty)>> = vec![None; params.len()];
+        let mut arg_tys = Vec::new();
+        let mut diverges = false;
+        let mut valid = true;
+        let mut positional = 0;
+        let mut named = false;
+
+        let positional_count = args
+            .iter()
+            .filter(|arg| !matches!(arg.kind.as_ref(), ExprKind::NamedArg(..)))
+            .count();
+        if positional_count > params.len() {
+            self.inferrer.error(Error(ErrorKind::TooManyArgs(
+                params.len(),
+                positional_count,
+                input.span,
+            )));
+            valid = false;
+        }
+
+        for (ix, arg) in args.iter().enumerate() {
+            let (name, value) = match arg.kind.as_ref() {
+                ExprKind::NamedArg(name, value) => (Some(name), value.as_ref()),
+                _ => (None, *arg),
+            };
+            let value = self.infer_expr(value);
+            diverges = diverges || value.diverges;
+            arg_tys.push(value.ty.clone());
+            if name.is_some() {
+                self.record(arg.id, value.ty.clone());
+            }
+
+            let slot = match name {
+                Some(name) => {
+                    named = true;
+                    let slot = self.named_arg_slot(params, name);
+                    valid = valid && slot.is_some();
+                    slot
+                }
+                None if named => {
+                    self.inferrer
+                        .error(Error(ErrorKind::PositionalAfterNamed(arg.span)));
+                    valid = false;
+                    None
+                }
+                None => {
+                    positional += 1;
+                    Some(positional - 1)
+                }
+            };
+
+            match slot.and_then(|slot| slots.get_mut(slot)) {
+                Some(slot @ None) => *slot = Some((Arg::Given(ix), value.ty)),
+                Some(Some(_)) => {
+                    let name = name.map_or_else(|| "_".to_string(), |name| name.name.to_string());
+                    self.inferrer
+                        .error(Error(ErrorKind::DuplicateArg(name, arg.span)));
+                    valid = false;
+                }
+                None => {}
+            }
+        }
+
+        let resolved = self.fill_defaults(span, params, slots);
+
+        if let ExprKind::Tuple(_) = input.kind.as_ref() {
+            self.record(input.id, Ty::Tuple(arg_tys));
+        } else if let Some(ty) = arg_tys.pop() {
+            self.record(input.id, ty);
+        }
+
+        let Some((resolved, tys)) = resolved.filter(|_| valid) else {
+            // The input is not unified with the callee when the arguments do not match, so a type
+            // created for a default value would never be inferred and be reported as ambiguous.
+            return self.diverge_if(diverges, converge(Ty::Err));
+        };
+
+        let mut tys: Vec<_> = tys
+            .into_iter()
+            .map(|ty| ty.unwrap_or_else(|| self.inferrer.fresh_ty(TySource::not_divergent(span))))
+            .collect();
+        self.table.args.insert(call, resolved);
+        let ty = if tys.len() == 1 {
+            tys.pop().expect("there should be one argument type")
+        } else {
+            Ty::Tuple(tys)
+        };
+        self.diverge_if(diverges, converge(ty))
+    }
+
+    /// The index of the parameter that a named argument is given for. An error is reported if the
+    /// callee has no parameter with that name.
+    fn named_arg_slot(&mut self, params: &[Param], name: &ast::Ident) -> Option<usize> {
+        let slot = params
+            .iter()
+            .position(|param| param.name.as_deref() == Some(&*name.name));
+        if slot.is_none() {
+            self.inferrer.error(Error(ErrorKind::UnknownParam(
+                name.name.to_string(),
+                name.span,
+            )));
+        }
+        slot
+    }
+
+    /// The argument for each parameter, using the default value for a parameter that is not given
+    /// an argument, along with the type of each given argument. An error is reported for each
+    /// parameter that has neither, in which case there are no arguments.
+    fn fill_defaults(
+        &mut self,
+        span: Span,
+        params: &[Param],
+        slots: Vec<Option<(Arg, Ty)>>,
+    ) -> Option<(Vec<Arg>, Vec<Option<Ty>>)> {
+        let mut resolved = Vec::new();
+        let mut tys = Vec::new();
+        let mut complete = true;
+        for (param, slot) in params.iter().zip(slots) {
+            match (slot, param.default) {
+                (Some((arg, ty)), _) => {
+                    resolved.push(arg);
+                    tys.push(Some(ty));
+                }
+                (None, Some(default)) => {
+                    resolved.push(Arg::Default(default));
+                    tys.push(None);
+                }
+                (None, None) => {
+                    let name = param.name.as_deref().unwrap_or("_").to_string();
+                    self.inferrer
+                        .error(Error(ErrorKind::MissingArg(name, span)));
+                    complete = false;
+                }
+            }
+        }
+        complete.then_some((resolved, tys))
+    }
+
     fn infer_hole_tuple<T>(
         &mut self,
         hole: fn(Ty) -> T,
@@ -662,6 +873,50 @@ impl<'a> Context<'a> {
         }
     }
 
+    fn infer_match_pat(&mut self, pat: &MatchPat, scrutinee: &Ty) {
+        match &*pat.kind {
+            MatchPatKind::Bind(name) => self.record(name.id, scrutinee.clone()),
+            MatchPatKind::Discard | MatchPatKind::Err => {}
+            MatchPatKind::Lit(lit) => self.inferrer.eq(pat.span, scrutinee.clone(), lit_ty(lit)),
+        }
+
+        self.record(pat.id, scrutinee.clone());
+    }
+
+    /// Checks that every value is matched by some arm, and that every arm matches a value that no
+    /// earlier arm does. Only literals of types with finitely many values can be exhaustive
+    /// without a final binding or `_` arm.
+    fn check_match_arms(&mut self, span: Span, arms: &[Box<MatchArm>]) {
+        let mut lits: Vec<&Lit> = Vec::new();
+        let mut exhaustive = false;
+        for arm in arms {
+            if exhaustive {
+                self.inferrer
+                    .error(Error(ErrorKind::UnreachableArm(arm.span)));
+                continue;
+            }
+
+            match &*arm.pat.kind {
+                MatchPatKind::Bind(_) | MatchPatKind::Discard | MatchPatKind::Err => {
+                    exhaustive = true;
+                }
+                MatchPatKind::Lit(lit) if lits.contains(&&**lit) => {
+                    self.inferrer
+                        .error(Error(ErrorKind::UnreachableArm(arm.span)));
+                }
+                MatchPatKind::Lit(lit) => {
+                    lits.push(lit);
+                    exhaustive = covers_all_values(&lits);
+                }
+            }
+        }
+
+        if !exhaustive {
+            self.inferrer
+                .error(Error(ErrorKind::NonExhaustiveMatch(span)));
+        }
+    }
+
     fn infer_pat(&mut self, pat: &Pat) -> Ty {
         let ty = match &*pat.kind {
             PatKind::Bind(name, None) => {
@@ -770,7 +1025,7 @@ impl<'a> Context<'a> {
 
         for (id, span) in self.typed_holes {
             let ty = self.table.terms.get_mut(id).expect("node should have type");
-            errs.push(Error(super::ErrorKind::TyHole(ty.display(), span)));
+            errs.push(Error(ErrorKind::TyHole(ty.display(), span)));
         }
 
         errs
@@ -784,6 +1039,7 @@ pub(super) struct SpecImpl<'a> {
     pub(super) spec_input: Option<&'a Pat>,
     pub(super) output: &'a Ty,
     pub(super) output_span: Span,
+    pub(super) params: &'a [GenericParam],
     pub(super) block: &'a Block,
 }
 
@@ -793,12 +1049,32 @@ pub(super) fn spec(
     table: &mut Table,
     spec: SpecImpl,
 ) -> Vec<Error> {
-    let mut inferrer = Inferrer::new();
+    let mut inferrer = Inferrer::with_params(spec.params.to_vec());
     let mut context = Context::new(names, globals, table, &mut inferrer, Vec::new());
     context.infer_spec(spec);
     context.solve()
 }
 
+/// Checks that the default value of each parameter has the type of the parameter.
+pub(super) fn defaults(
+    names: &Names,
+    globals: &FxHashMap<ItemId, Scheme>,
+    table: &mut Table,
+    params: &[GenericParam],
+    decl: &ast::CallableDecl,
+) -> Vec<Error> {
+    let mut inferrer = Inferrer::with_params(params.to_vec());
+    let mut context = Context::new(names, globals, table, &mut inferrer, Vec::new());
+    for (param, value) in decl.params().into_iter().zip(decl.defaults.iter()) {
+        if let Some(value) = value {
+            let expected = context.infer_pat(param);
+            let actual = context.infer_expr(value);
+            context.inferrer.eq(value.span, expected, actual.ty);
+        }
+    }
+    context.solve()
+}
+
 pub(super) fn expr(
     names: &Names,
     globals: &FxHashMap<ItemId, Scheme>,
@@ -834,6 +1110,53 @@ pub(super) fn solve(
     context.solve()
 }
 
+/// The arguments written in a call: the items of the argument tuple, or the whole argument if it is
+/// not a tuple.
+fn call_args(input: &Expr) -> Vec<&Expr> {
+    match input.kind.as_ref() {
+        ExprKind::Paren(inner) => vec![&**inner],
+        ExprKind::Tuple(items) => items.iter().map(AsRef::as_ref).collect(),
+        _ => vec![input],
+    }
+}
+
+/// Whether a call with the given input needs its arguments matched to the parameters by name or
+/// completed with default values.
+fn uses_params(params: &[Param], input: &Expr) -> bool {
+    let args = call_args(input);
+    args.iter()
+        .any(|arg| matches!(arg.kind.as_ref(), ExprKind::NamedArg(..)))
+        || (args.len() < params.len() && params.iter().any(|param| param.default.is_some()))
+}
+
+fn lit_ty(lit: &Lit) -> Ty {
+    match lit {
+        Lit::BigInt(_) => Ty::Prim(Prim::BigInt),
+        Lit::Bool(_) => Ty::Prim(Prim::Bool),
+        Lit::Double(_) => Ty::Prim(Prim::Double),
+        Lit::Int(_) => Ty::Prim(Prim::Int),
+        Lit::Pauli(_) => Ty::Prim(Prim::Pauli),
+        Lit::Result(_) => Ty::Prim(Prim::Result),
+        Lit::String(_) => Ty::Prim(Prim::String),
+    }
+}
+
+/// True if the distinct literals cover every value of their type.
+fn covers_all_values(lits: &[&Lit]) -> bool {
+    let Some(first) = lits.first() else {
+        return false;
+    };
+    let count = lits
+        .iter()
+        .filter(|lit| discriminant(**lit) == discriminant(*first))
+        .count();
+    match first {
+        Lit::Bool(_) | Lit::Result(_) => count == 2,
+        Lit::Pauli(_) => count == 4,
+        Lit::BigInt(_) | Lit::Double(_) | Lit::Int(_) | Lit::String(_) => false,
+    }
+}
+
 fn converge<T>(ty: T) -> Partial<T> {
     Partial {
         ty,
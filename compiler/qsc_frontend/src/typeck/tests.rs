@@ -88,7 +88,7 @@ fn compile(input: &str, entry_expr: &str) -> (Package, super::Table, Vec<compile
     let mut errors = globals.add_local_package(&mut assigner, &package);
     let mut resolver = Resolver::new(globals, Vec::new());
     resolver.with(&mut assigner).visit_package(&package);
-    let (names, _, mut resolve_errors) = resolver.into_result();
+    let (names, _, _, _, _, mut resolve_errors, _) = resolver.into_result();
     errors.append(&mut resolve_errors);
 
     let mut checker = Checker::new(super::GlobalTable::new());
@@ -274,6 +274,97 @@ fn call_generic_identity() {
     );
 }
 
+#[test]
+fn generic_eq_without_constraint_error() {
+    check(
+        indoc! {"
+            namespace A {
+                function Same<'T>(x : 'T, y : 'T) : Bool { x == y }
+            }
+        "},
+        "",
+        &expect![[r#"
+            #7 35-51 "(x : 'T, y : 'T)" : (Param<"'T": 0>, Param<"'T": 0>)
+            #8 36-42 "x : 'T" : Param<"'T": 0>
+            #12 44-50 "y : 'T" : Param<"'T": 0>
+            #19 59-69 "{ x == y }" : Bool
+            #21 61-67 "x == y" : Bool
+            #22 61-62 "x" : Param<"'T": 0>
+            #25 66-67 "y" : Param<"'T": 0>
+            Error(Type(Error(MissingParamConstraint("'T", Eq, Span { lo: 61, hi: 62 }))))
+        "#]],
+    );
+}
+
+#[test]
+fn generic_eq_with_constraint() {
+    check(
+        indoc! {"
+            namespace A {
+                function Same<'T: Eq>(x : 'T, y : 'T) : Bool { x == y }
+            }
+        "},
+        "",
+        &expect![[r#"
+            #8 39-55 "(x : 'T, y : 'T)" : (Param<"'T": 0>, Param<"'T": 0>)
+            #9 40-46 "x : 'T" : Param<"'T": 0>
+            #13 48-54 "y : 'T" : Param<"'T": 0>
+            #20 63-73 "{ x == y }" : Bool
+            #22 65-71 "x == y" : Bool
+            #23 65-66 "x" : Param<"'T": 0>
+            #26 70-71 "y" : Param<"'T": 0>
+        "#]],
+    );
+}
+
+#[test]
+fn call_constrained_generic_with_unsupported_type_error() {
+    check(
+        indoc! {"
+            namespace A {
+                function Same<'T: Eq>(x : 'T, y : 'T) : Bool { x == y }
+                function Foo() : Bool { Same(Foo, Foo) }
+            }
+        "},
+        "",
+        &expect![[r#"
+            #8 39-55 "(x : 'T, y : 'T)" : (Param<"'T": 0>, Param<"'T": 0>)
+            #9 40-46 "x : 'T" : Param<"'T": 0>
+            #13 48-54 "y : 'T" : Param<"'T": 0>
+            #20 63-73 "{ x == y }" : Bool
+            #22 65-71 "x == y" : Bool
+            #23 65-66 "x" : Param<"'T": 0>
+            #26 70-71 "y" : Param<"'T": 0>
+            #32 90-92 "()" : Unit
+            #36 100-118 "{ Same(Foo, Foo) }" : Bool
+            #38 102-116 "Same(Foo, Foo)" : Bool
+            #39 102-106 "Same" : (((Unit -> Bool), (Unit -> Bool)) -> Bool)
+            #42 106-116 "(Foo, Foo)" : ((Unit -> Bool), (Unit -> Bool))
+            #43 107-110 "Foo" : (Unit -> Bool)
+            #46 112-115 "Foo" : (Unit -> Bool)
+            Error(Type(Error(MissingClassEq("(Unit -> Bool)", Span { lo: 102, hi: 116 }))))
+        "#]],
+    );
+}
+
+#[test]
+fn generic_unknown_constraint_error() {
+    check(
+        indoc! {"
+            namespace A {
+                function Foo<'T: Ord>(x : 'T) : Unit {}
+            }
+        "},
+        "",
+        &expect![[r#"
+            #8 39-47 "(x : 'T)" : Param<"'T": 0>
+            #9 40-46 "x : 'T" : Param<"'T": 0>
+            #16 55-57 "{}" : Unit
+            Error(Type(Error(UnknownConstraint("Ord", Span { lo: 35, hi: 38 }))))
+        "#]],
+    );
+}
+
 #[test]
 fn call_generic_length() {
     check(
@@ -2307,6 +2398,177 @@ fn newtype_field_invalid() {
     );
 }
 
+#[test]
+fn struct_constructor() {
+    check(
+        indoc! {"
+            namespace A {
+                struct Pair { X : Int, Y : Int }
+                function Foo() : Pair {
+                    new Pair { Y = 2, X = 1 }
+                }
+            }
+        "},
+        "",
+        &expect![[r#"
+            #19 67-69 "()" : Unit
+            #23 77-118 "{\n        new Pair { Y = 2, X = 1 }\n    }" : UDT<"Pair": Item 1>
+            #25 87-112 "new Pair { Y = 2, X = 1 }" : UDT<"Pair": Item 1>
+            #30 102-103 "2" : Int
+            #33 109-110 "1" : Int
+        "#]],
+    );
+}
+
+#[test]
+fn struct_constructor_invalid_fields() {
+    check(
+        indoc! {"
+            namespace A {
+                struct Pair { X : Int, Y : Int }
+                function Foo() : Pair {
+                    new Pair { X = 1, X = 2, Z = 3 }
+                }
+            }
+        "},
+        "",
+        &expect![[r#"
+            #19 67-69 "()" : Unit
+            #23 77-125 "{\n        new Pair { X = 1, X = 2, Z = 3 }\n    }" : UDT<"Pair": Item 1>
+            #25 87-119 "new Pair { X = 1, X = 2, Z = 3 }" : UDT<"Pair": Item 1>
+            #30 102-103 "1" : Int
+            #33 109-110 "2" : Int
+            #36 116-117 "3" : Int
+            Error(Type(Error(DuplicateField("X", Span { lo: 105, hi: 110 }))))
+            Error(Type(Error(MissingClassHasField("Pair", "Z", Span { lo: 112, hi: 117 }))))
+            Error(Type(Error(MissingField("Pair", "`Y`", Span { lo: 87, hi: 119 }))))
+        "#]],
+    );
+}
+
+#[test]
+fn call_named_args_and_default() {
+    check(
+        indoc! {"
+            namespace A {
+                function Foo(a : Int, b : Bool = true) : Int { a }
+                function Bar() : Int { Foo(b = false, a = 1) + Foo(2) }
+            }
+        "},
+        "",
+        &expect![[r#"
+            #6 30-56 "(a : Int, b : Bool = true)" : (Int, Bool)
+            #7 31-38 "a : Int" : Int
+            #12 40-48 "b : Bool" : Bool
+            #17 51-55 "true" : Bool
+            #21 63-68 "{ a }" : Int
+            #23 65-66 "a" : Int
+            #29 85-87 "()" : Unit
+            #33 94-128 "{ Foo(b = false, a = 1) + Foo(2) }" : Int
+            #35 96-126 "Foo(b = false, a = 1) + Foo(2)" : Int
+            #36 96-117 "Foo(b = false, a = 1)" : Int
+            #37 96-99 "Foo" : ((Int, Bool) -> Int)
+            #40 99-117 "(b = false, a = 1)" : (Bool, Int)
+            #41 100-109 "b = false" : Bool
+            #43 104-109 "false" : Bool
+            #44 111-116 "a = 1" : Int
+            #46 115-116 "1" : Int
+            #47 120-126 "Foo(2)" : Int
+            #48 120-123 "Foo" : ((Int, Bool) -> Int)
+            #51 123-126 "(2)" : Int
+            #52 124-125 "2" : Int
+        "#]],
+    );
+}
+
+#[test]
+fn call_unknown_named_arg() {
+    check(
+        indoc! {"
+            namespace A {
+                function Foo(a : Int, b : Bool = true) : Int { a }
+                function Bar() : Int { Foo(c = 1) }
+            }
+        "},
+        "",
+        &expect![[r#"
+            #6 30-56 "(a : Int, b : Bool = true)" : (Int, Bool)
+            #7 31-38 "a : Int" : Int
+            #12 40-48 "b : Bool" : Bool
+            #17 51-55 "true" : Bool
+            #21 63-68 "{ a }" : Int
+            #23 65-66 "a" : Int
+            #29 85-87 "()" : Unit
+            #33 94-108 "{ Foo(c = 1) }" : Int
+            #35 96-106 "Foo(c = 1)" : Int
+            #36 96-99 "Foo" : ((Int, Bool) -> Int)
+            #39 99-106 "(c = 1)" : Int
+            #40 100-105 "c = 1" : Int
+            #42 104-105 "1" : Int
+            Error(Type(Error(UnknownParam("c", Span { lo: 100, hi: 101 }))))
+            Error(Type(Error(MissingArg("a", Span { lo: 96, hi: 106 }))))
+        "#]],
+    );
+}
+
+#[test]
+fn match_result_exhaustive() {
+    check(
+        "",
+        "match One { Zero => 1, One => 2 }",
+        &expect![[r#"
+            #1 0-33 "match One { Zero => 1, One => 2 }" : Int
+            #2 6-9 "One" : Result
+            #5 20-21 "1" : Int
+            #8 30-31 "2" : Int
+        "#]],
+    );
+}
+
+#[test]
+fn match_bool_missing_value() {
+    check(
+        "",
+        "match true { true => 1 }",
+        &expect![[r#"
+            #1 0-24 "match true { true => 1 }" : Int
+            #2 6-10 "true" : Bool
+            #5 21-22 "1" : Int
+            Error(Type(Error(NonExhaustiveMatch(Span { lo: 0, hi: 24 }))))
+        "#]],
+    );
+}
+
+#[test]
+fn match_arm_after_binding_is_unreachable() {
+    check(
+        "",
+        "match 3 { n => n, 4 => 0 }",
+        &expect![[r#"
+            #1 0-26 "match 3 { n => n, 4 => 0 }" : Int
+            #2 6-7 "3" : Int
+            #6 15-16 "n" : Int
+            #11 23-24 "0" : Int
+            Error(Type(Error(UnreachableArm(Span { lo: 18, hi: 24 }))))
+        "#]],
+    );
+}
+
+#[test]
+fn match_pattern_ty_mismatch() {
+    check(
+        "",
+        "match 1 { Zero => 0, _ => 1 }",
+        &expect![[r#"
+            #1 0-29 "match 1 { Zero => 0, _ => 1 }" : Int
+            #2 6-7 "1" : Int
+            #5 18-19 "0" : Int
+            #8 26-27 "1" : Int
+            Error(Type(Error(TyMismatch("Int", "Result", Span { lo: 10, hi: 14 }))))
+        "#]],
+    );
+}
+
 #[test]
 fn unknown_name_fits_any_ty() {
     check(
@@ -0,0 +1,124 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The structured form of the documentation of an item, which is written in Markdown in `///`
+//! comments and divided into sections by level one headers, like `# Summary` and `# Input`.
+
+#![warn(missing_docs)]
+
+#[cfg(test)]
+mod tests;
+
+use crate::hir::{Item, Package};
+
+/// The documentation of an item.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Doc {
+    /// The `# Summary` section, or the whole documentation if it has no such section.
+    pub summary: String,
+    /// The `# Description` section.
+    pub description: String,
+    /// The `# Input` section, with an entry for each parameter that has a level two header.
+    pub inputs: Vec<Entry>,
+    /// The `# Output` section.
+    pub output: String,
+    /// The `# Type Parameters` section, with an entry for each type parameter that has a level two
+    /// header.
+    pub type_params: Vec<Entry>,
+    /// Each `# Example` or `# Examples` section.
+    pub examples: Vec<String>,
+    /// The other sections, like `# Remarks` and `# References`, by their headers.
+    pub sections: Vec<Entry>,
+}
+
+/// Text with a name, which is the header that the text is under.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The name.
+    pub name: String,
+    /// The text.
+    pub text: String,
+}
+
+impl Doc {
+    /// Parses the text of `///` comments, without the leading slashes.
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let mut doc = Self::default();
+        let top_sections = sections(text, "# ");
+        if !top_sections
+            .iter()
+            .any(|section| section.name.eq_ignore_ascii_case("summary"))
+        {
+            doc.summary = text.trim().to_string();
+        }
+
+        for section in top_sections {
+            match section.name.to_ascii_lowercase().as_str() {
+                "summary" => doc.summary = section.text,
+                "description" => doc.description = section.text,
+                "input" => doc.inputs = sections(&section.text, "## "),
+                "output" => doc.output = section.text,
+                "type parameters" => doc.type_params = sections(&section.text, "## "),
+                "example" | "examples" => doc.examples.push(section.text),
+                _ => doc.sections.push(section),
+            }
+        }
+
+        doc
+    }
+
+    /// The description of the parameter with the given name.
+    #[must_use]
+    pub fn input(&self, name: &str) -> Option<&str> {
+        self.inputs
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.text.as_str())
+    }
+}
+
+impl Item {
+    /// The documentation of the item, parsed from its doc comments.
+    #[must_use]
+    pub fn parsed_doc(&self) -> Doc {
+        Doc::parse(&self.doc)
+    }
+}
+
+impl Package {
+    /// The items in the package that have doc comments, with their parsed documentation.
+    pub fn documented_items(&self) -> impl Iterator<Item = (&Item, Doc)> {
+        self.items
+            .values()
+            .filter(|item| !item.doc.trim().is_empty())
+            .map(|item| (item, item.parsed_doc()))
+    }
+}
+
+/// Splits text into sections at each line that starts with the header prefix, ignoring lines in
+/// fenced code blocks. Text before the first header is not in any section.
+fn sections(text: &str, prefix: &str) -> Vec<Entry> {
+    let mut sections: Vec<Entry> = Vec::new();
+    let mut fenced = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+        }
+
+        if let Some(name) = line.strip_prefix(prefix).filter(|_| !fenced) {
+            sections.push(Entry {
+                name: name.trim().to_string(),
+                text: String::new(),
+            });
+        } else if let Some(section) = sections.last_mut() {
+            section.text.push_str(line);
+            section.text.push('\n');
+        }
+    }
+
+    for section in &mut sections {
+        section.text = section.text.trim().to_string();
+    }
+    sections
+}
@@ -0,0 +1,125 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::Doc;
+use expect_test::{expect, Expect};
+use indoc::indoc;
+
+fn check(text: &str, expect: &Expect) {
+    expect.assert_debug_eq(&Doc::parse(text));
+}
+
+#[test]
+fn sections() {
+    check(
+        indoc! {"
+            # Summary
+            Applies a rotation.
+
+            # Description
+            Rotates about an axis.
+            # Input
+            ## angle
+            The angle.
+            ## qubit
+            The qubit
+            to rotate.
+
+            # Output
+            Nothing.
+            # Type Parameters
+            ## 'T
+            Any type.
+            # Remarks
+            See also `R`.
+        "},
+        &expect![[r#"
+            Doc {
+                summary: "Applies a rotation.",
+                description: "Rotates about an axis.",
+                inputs: [
+                    Entry {
+                        name: "angle",
+                        text: "The angle.",
+                    },
+                    Entry {
+                        name: "qubit",
+                        text: "The qubit\nto rotate.",
+                    },
+                ],
+                output: "Nothing.",
+                type_params: [
+                    Entry {
+                        name: "'T",
+                        text: "Any type.",
+                    },
+                ],
+                examples: [],
+                sections: [
+                    Entry {
+                        name: "Remarks",
+                        text: "See also `R`.",
+                    },
+                ],
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn no_headers_is_summary() {
+    check(
+        "Returns one.\n",
+        &expect![[r#"
+            Doc {
+                summary: "Returns one.",
+                description: "",
+                inputs: [],
+                output: "",
+                type_params: [],
+                examples: [],
+                sections: [],
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn headers_in_code_blocks_are_ignored() {
+    check(
+        indoc! {"
+            # Summary
+            Does nothing.
+            # Example
+            ```qsharp
+            # Not a header
+            Foo();
+            ```
+            # Examples
+            More.
+        "},
+        &expect![[r#"
+            Doc {
+                summary: "Does nothing.",
+                description: "",
+                inputs: [],
+                output: "",
+                type_params: [],
+                examples: [
+                    "```qsharp\n# Not a header\nFoo();\n```",
+                    "More.",
+                ],
+                sections: [],
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn input_by_name() {
+    let doc = Doc::parse("# Input\n## a\nFirst.\n## b\nSecond.\n");
+    assert_eq!(doc.input("b"), Some("Second."));
+    assert_eq!(doc.input("c"), None);
+}
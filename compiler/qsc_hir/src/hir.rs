@@ -9,6 +9,7 @@ use crate::ty::{Arrow, FunctorSet, FunctorSetValue, GenericArg, GenericParam, Sc
 use indenter::{indented, Indented};
 use num_bigint::BigInt;
 use qsc_data_structures::{index_map::IndexMap, span::Span};
+use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     fmt::{self, Debug, Display, Formatter, Write},
@@ -31,7 +32,7 @@ fn set_indentation<'a, 'b>(
 }
 
 /// A unique identifier for an HIR node.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct NodeId(u32);
 
 impl NodeId {
@@ -105,7 +106,7 @@ impl Hash for NodeId {
 }
 
 /// A unique identifier for a package within a package store.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct PackageId(usize);
 
 impl PackageId {
@@ -138,7 +139,9 @@ impl From<usize> for PackageId {
 }
 
 /// A unique identifier for an item within a package.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct LocalItemId(usize);
 
 impl LocalItemId {
@@ -168,7 +171,7 @@ impl From<LocalItemId> for usize {
 }
 
 /// A unique identifier for an item within a package store.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct ItemId {
     /// The package ID or `None` for the local package.
     pub package: Option<PackageId>,
@@ -186,30 +189,35 @@ impl Display for ItemId {
 }
 
 /// The status of an item.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum ItemStatus {
     /// The item is defined normally.
     Available,
     /// The item is marked as unimplemented and uses are disallowed.
     Unimplemented,
+    /// The item is marked as deprecated and uses are discouraged.
+    Deprecated,
 }
 
 impl ItemStatus {
     /// Create an item status from the given attributes list.
     #[must_use]
     pub fn from_attrs(attrs: &[Attr]) -> Self {
+        let mut status = Self::Available;
         for attr in attrs {
-            if *attr == Attr::Unimplemented {
-                return Self::Unimplemented;
+            match attr {
+                Attr::Unimplemented => return Self::Unimplemented,
+                Attr::Deprecated(_) => status = Self::Deprecated,
+                _ => {}
             }
         }
-        Self::Available
+        status
     }
 }
 
 /// A resolution. This connects a usage of a name with the declaration of that name by uniquely
 /// identifying the node that declared it.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Res {
     /// An invalid resolution.
     Err,
@@ -244,7 +252,7 @@ impl Display for Res {
 }
 
 /// The root node of the HIR.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Package {
     /// The items in the package.
     pub items: IndexMap<LocalItemId, Item>,
@@ -273,7 +281,7 @@ impl Display for Package {
 }
 
 /// An item.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Item {
     /// The ID.
     pub id: LocalItemId,
@@ -322,7 +330,7 @@ impl Display for Item {
 }
 
 /// An item kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum ItemKind {
     /// A `function` or `operation` declaration.
     Callable(CallableDecl),
@@ -355,7 +363,7 @@ impl Display for ItemKind {
 }
 
 /// A callable declaration header.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CallableDecl {
     /// The node ID.
     pub id: NodeId,
@@ -369,6 +377,9 @@ pub struct CallableDecl {
     pub generics: Vec<GenericParam>,
     /// The input to the callable.
     pub input: Pat,
+    /// The item that computes the default value of each parameter, in the same order as
+    /// [`CallableDecl::params`]. Empty if no parameter has a default value.
+    pub defaults: Vec<Option<LocalItemId>>,
     /// The return type of the callable.
     pub output: Ty,
     /// The functors supported by the callable.
@@ -397,6 +408,28 @@ impl CallableDecl {
             }),
         )
     }
+
+    /// The parameters of the callable: the items of the input tuple, or the whole input if it is
+    /// not a tuple.
+    #[must_use]
+    pub fn params(&self) -> Vec<&Pat> {
+        match &self.input.kind {
+            PatKind::Tuple(items) => items.iter().collect(),
+            _ => vec![&self.input],
+        }
+    }
+
+    /// The specializations of the callable with their kinds, starting with the body.
+    pub fn specs(&self) -> impl Iterator<Item = (SpecKind, &SpecDecl)> {
+        [
+            (SpecKind::Body, Some(&self.body)),
+            (SpecKind::Adj, self.adj.as_ref()),
+            (SpecKind::Ctl, self.ctl.as_ref()),
+            (SpecKind::CtlAdj, self.ctl_adj.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(kind, spec)| spec.map(|spec| (kind, spec)))
+    }
 }
 
 impl Display for CallableDecl {
@@ -418,6 +451,17 @@ impl Display for CallableDecl {
             indent = set_indentation(indent, 1);
         }
         write!(indent, "\ninput: {}", self.input)?;
+        if !self.defaults.is_empty() {
+            write!(indent, "\ndefaults:")?;
+            indent = set_indentation(indent, 2);
+            for (ix, default) in self.defaults.iter().enumerate() {
+                match default {
+                    Some(item) => write!(indent, "\n{ix}: Item {item}")?,
+                    None => write!(indent, "\n{ix}: <none>")?,
+                }
+            }
+            indent = set_indentation(indent, 1);
+        }
         write!(indent, "\noutput: {}", self.output)?;
         write!(indent, "\nfunctors: {}", self.functors)?;
         write!(indent, "\nbody: {}", self.body)?;
@@ -438,7 +482,7 @@ impl Display for CallableDecl {
 }
 
 /// A specialization declaration.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SpecDecl {
     /// The node ID.
     pub id: NodeId,
@@ -446,6 +490,10 @@ pub struct SpecDecl {
     pub span: Span,
     /// The body of the specialization.
     pub body: SpecBody,
+    /// The strategy that the compiler generated the body with, or `None` if the body was written by
+    /// the user or has not been generated yet.
+    #[serde(default)]
+    pub generated: Option<SpecGen>,
 }
 
 impl Display for SpecDecl {
@@ -454,8 +502,32 @@ impl Display for SpecDecl {
     }
 }
 
+/// A kind of specialization.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SpecKind {
+    /// The body specialization.
+    Body,
+    /// The adjoint specialization.
+    Adj,
+    /// The controlled specialization.
+    Ctl,
+    /// The controlled adjoint specialization.
+    CtlAdj,
+}
+
+impl Display for SpecKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SpecKind::Body => f.write_str("body"),
+            SpecKind::Adj => f.write_str("adjoint"),
+            SpecKind::Ctl => f.write_str("controlled"),
+            SpecKind::CtlAdj => f.write_str("controlled adjoint"),
+        }
+    }
+}
+
 /// The body of a specialization.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum SpecBody {
     /// The strategy to use to automatically generate the specialization.
     Gen(SpecGen),
@@ -482,7 +554,7 @@ impl Display for SpecBody {
 }
 
 /// A sequenced block of statements.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Block {
     /// The node ID.
     pub id: NodeId,
@@ -515,7 +587,7 @@ impl Display for Block {
 }
 
 /// A statement.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Stmt {
     /// The node ID.
     pub id: NodeId,
@@ -532,7 +604,7 @@ impl Display for Stmt {
 }
 
 /// A statement kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum StmtKind {
     /// An expression without a trailing semicolon.
     Expr(Expr),
@@ -574,7 +646,7 @@ impl Display for StmtKind {
 }
 
 /// An expression.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Expr {
     /// The node ID.
     pub id: NodeId,
@@ -597,7 +669,7 @@ impl Display for Expr {
 }
 
 /// An expression kind.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub enum ExprKind {
     /// An array: `[a, b, c]`.
     Array(Vec<Expr>),
@@ -997,7 +1069,7 @@ fn display_while(mut indent: Indented<Formatter>, cond: &Expr, block: &Block) ->
 }
 
 /// A string component.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum StringComponent {
     /// An expression.
     Expr(Expr),
@@ -1006,7 +1078,7 @@ pub enum StringComponent {
 }
 
 /// A pattern.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Pat {
     /// The node ID.
     pub id: NodeId,
@@ -1029,7 +1101,7 @@ impl Display for Pat {
 }
 
 /// A pattern kind.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum PatKind {
     /// A binding.
     Bind(Ident),
@@ -1067,7 +1139,7 @@ impl Display for PatKind {
 }
 
 /// A qubit initializer.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct QubitInit {
     /// The node ID.
     pub id: NodeId,
@@ -1090,7 +1162,7 @@ impl Display for QubitInit {
 }
 
 /// A qubit initializer kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum QubitInitKind {
     /// An array of qubits: `Qubit[a]`.
     Array(Box<Expr>),
@@ -1130,7 +1202,7 @@ impl Display for QubitInitKind {
 }
 
 /// An identifier.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Ident {
     /// The node ID.
     pub id: NodeId,
@@ -1147,7 +1219,7 @@ impl Display for Ident {
 }
 
 /// An attribute.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Attr {
     /// Provide pre-processing information about when an item should be included in compilation.
     Config,
@@ -1155,6 +1227,12 @@ pub enum Attr {
     EntryPoint,
     /// Indicates that an item does not have an implementation available for use.
     Unimplemented,
+    /// Indicates that an item should no longer be used, with the name of its replacement if any.
+    Deprecated(Option<Rc<str>>),
+    /// Suppresses a warning, by its code, within an item.
+    Allow,
+    /// Indicates that calls to a callable should never be inlined.
+    NoInline,
 }
 
 impl FromStr for Attr {
@@ -1165,13 +1243,16 @@ impl FromStr for Attr {
             "Config" => Ok(Self::Config),
             "EntryPoint" => Ok(Self::EntryPoint),
             "Unimplemented" => Ok(Self::Unimplemented),
+            "Deprecated" => Ok(Self::Deprecated(None)),
+            "Allow" => Ok(Self::Allow),
+            "NoInline" => Ok(Self::NoInline),
             _ => Err(()),
         }
     }
 }
 
 /// A field.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Field {
     /// A field path.
     Path(FieldPath),
@@ -1192,14 +1273,14 @@ impl Display for Field {
 }
 
 /// A path to a field in a tuple or user-defined type.
-#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct FieldPath {
     /// The tuple item indices to follow in order from top to bottom.
     pub indices: Vec<usize>,
 }
 
 /// A primitive field for a built-in type.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum PrimField {
     /// The start of a range.
     Start,
@@ -1223,7 +1304,7 @@ impl FromStr for PrimField {
 }
 
 /// The visibility of a declaration.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Visibility {
     /// Visible everywhere.
     Public,
@@ -1232,7 +1313,7 @@ pub enum Visibility {
 }
 
 /// A callable kind.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum CallableKind {
     /// A function.
     Function,
@@ -1250,7 +1331,7 @@ impl Display for CallableKind {
 }
 
 /// The mutability of a binding.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Mutability {
     /// An immutable binding.
     Immutable,
@@ -1259,7 +1340,7 @@ pub enum Mutability {
 }
 
 /// The source of an allocated qubit.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum QubitSource {
     /// A qubit initialized to the zero state.
     Fresh,
@@ -1269,7 +1350,7 @@ pub enum QubitSource {
 }
 
 /// A literal.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Lit {
     /// A big integer literal.
     BigInt(BigInt),
@@ -1300,7 +1381,7 @@ impl Display for Lit {
 }
 
 /// A measurement result.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Result {
     /// The zero eigenvalue.
     Zero,
@@ -1309,7 +1390,7 @@ pub enum Result {
 }
 
 /// A Pauli operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Pauli {
     /// The Pauli I operator.
     I,
@@ -1322,7 +1403,7 @@ pub enum Pauli {
 }
 
 /// A functor that may be applied to an operation.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Functor {
     /// The adjoint functor.
     Adj,
@@ -1340,7 +1421,7 @@ impl Display for Functor {
 }
 
 /// A strategy for generating a specialization.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum SpecGen {
     /// Choose a strategy automatically.
     Auto,
@@ -1356,7 +1437,7 @@ pub enum SpecGen {
 }
 
 /// A unary operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum UnOp {
     /// A functor application.
     Functor(Functor),
@@ -1383,7 +1464,7 @@ impl Display for UnOp {
 }
 
 /// A binary operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BinOp {
     /// Addition: `+`.
     Add,
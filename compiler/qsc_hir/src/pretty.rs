@@ -0,0 +1,266 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A pretty-printer that renders a HIR node together with all of the nodes it contains, as an
+//! indented tree with one node per line. Each line shows the kind of node, its ID, its span, and
+//! its type if it has one, so the output is stable for a given package and can be compared against
+//! in tests.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    hir::{
+        Block, CallableDecl, Expr, ExprKind, Item, ItemKind, LocalItemId, Package, Pat, PatKind,
+        QubitInit, QubitInitKind, SpecBody, Stmt, StmtKind, StringComponent,
+    },
+    visit::{self, Visitor},
+};
+use std::fmt::Write;
+
+/// Renders every item in the package, followed by its top-level statements and the entry
+/// expression if it has one.
+#[must_use]
+pub fn package_to_string(package: &Package) -> String {
+    let mut printer = Printer::default();
+    printer.visit_package(package);
+    printer.finish()
+}
+
+/// Renders the item with the given ID and all of the nodes it contains.
+///
+/// # Panics
+///
+/// Panics if the item is not in the package.
+#[must_use]
+pub fn item_to_string(package: &Package, item: LocalItemId) -> String {
+    let mut printer = Printer::default();
+    printer.visit_item(package.items.get(item).expect("item should be in package"));
+    printer.finish()
+}
+
+/// Renders the block and all of the nodes it contains.
+#[must_use]
+pub fn block_to_string(block: &Block) -> String {
+    let mut printer = Printer::default();
+    printer.visit_block(block);
+    printer.finish()
+}
+
+/// Renders the statement and all of the nodes it contains.
+#[must_use]
+pub fn stmt_to_string(stmt: &Stmt) -> String {
+    let mut printer = Printer::default();
+    printer.visit_stmt(stmt);
+    printer.finish()
+}
+
+/// Renders the expression and all of the nodes it contains.
+#[must_use]
+pub fn expr_to_string(expr: &Expr) -> String {
+    let mut printer = Printer::default();
+    printer.visit_expr(expr);
+    printer.finish()
+}
+
+#[derive(Default)]
+struct Printer {
+    output: String,
+    depth: usize,
+}
+
+impl Printer {
+    fn finish(mut self) -> String {
+        let len = self.output.trim_end().len();
+        self.output.truncate(len);
+        self.output
+    }
+
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    fn nested(&mut self, walk: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        walk(self);
+        self.depth -= 1;
+    }
+}
+
+impl<'a> Visitor<'a> for Printer {
+    fn visit_package(&mut self, package: &'a Package) {
+        for item in package.items.values() {
+            self.visit_item(item);
+        }
+        for stmt in &package.stmts {
+            self.visit_stmt(stmt);
+        }
+        if let Some(entry) = &package.entry {
+            self.line("Entry");
+            self.nested(|printer| printer.visit_expr(entry));
+        }
+    }
+
+    fn visit_item(&mut self, item: &'a Item) {
+        let kind = match &item.kind {
+            ItemKind::Callable(_) => "Callable".to_string(),
+            ItemKind::Namespace(name, items) => {
+                format!("Namespace \"{}\": [{}]", name.name, join(items))
+            }
+            ItemKind::Ty(name, _) => format!("Newtype \"{}\"", name.name),
+        };
+        let parent = item
+            .parent
+            .map_or(String::new(), |parent| format!(" [Parent {parent}]"));
+        self.line(&format!(
+            "Item {} {}{parent} ({:?}): {kind}",
+            item.id, item.span, item.visibility
+        ));
+        self.nested(|printer| visit::walk_item(printer, item));
+    }
+
+    fn visit_callable_decl(&mut self, decl: &'a CallableDecl) {
+        let generics = if decl.generics.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", join(&decl.generics))
+        };
+        self.line(&format!(
+            "CallableDecl {} {} ({}): \"{}\"{generics} [Output {}] [Functors {}]",
+            decl.id, decl.span, decl.kind, decl.name.name, decl.output, decl.functors
+        ));
+        self.nested(|printer| {
+            printer.visit_pat(&decl.input);
+            for (kind, spec) in decl.specs() {
+                let body = match &spec.body {
+                    SpecBody::Gen(gen) => format!(" (Gen {gen:?})"),
+                    SpecBody::Impl(..) => String::new(),
+                };
+                let generated = spec
+                    .generated
+                    .map_or(String::new(), |gen| format!(" [Generated {gen:?}]"));
+                printer.line(&format!(
+                    "SpecDecl {} {}: {kind}{body}{generated}",
+                    spec.id, spec.span
+                ));
+                printer.nested(|printer| visit::walk_spec_decl(printer, spec));
+            }
+        });
+    }
+
+    fn visit_block(&mut self, block: &'a Block) {
+        self.line(&format!(
+            "Block {} {} [Type {}]",
+            block.id, block.span, block.ty
+        ));
+        self.nested(|printer| visit::walk_block(printer, block));
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        let kind = match &stmt.kind {
+            StmtKind::Expr(_) => "Expr".to_string(),
+            StmtKind::Item(item) => format!("Item {item}"),
+            StmtKind::Local(mutability, _, _) => format!("Local ({mutability:?})"),
+            StmtKind::Qubit(source, _, _, _) => format!("Qubit ({source:?})"),
+            StmtKind::Semi(_) => "Semi".to_string(),
+        };
+        self.line(&format!("Stmt {} {}: {kind}", stmt.id, stmt.span));
+        self.nested(|printer| visit::walk_stmt(printer, stmt));
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        self.line(&format!(
+            "Expr {} {} [Type {}]: {}",
+            expr.id,
+            expr.span,
+            expr.ty,
+            expr_kind_label(&expr.kind)
+        ));
+        self.nested(|printer| visit::walk_expr(printer, expr));
+    }
+
+    fn visit_pat(&mut self, pat: &'a Pat) {
+        let kind = match &pat.kind {
+            PatKind::Bind(name) => format!("Bind {} \"{}\"", name.id, name.name),
+            PatKind::Discard => "Discard".to_string(),
+            PatKind::Tuple(_) => "Tuple".to_string(),
+            PatKind::Err => "Err".to_string(),
+        };
+        self.line(&format!(
+            "Pat {} {} [Type {}]: {kind}",
+            pat.id, pat.span, pat.ty
+        ));
+        self.nested(|printer| visit::walk_pat(printer, pat));
+    }
+
+    fn visit_qubit_init(&mut self, init: &'a QubitInit) {
+        let kind = match &init.kind {
+            QubitInitKind::Array(_) => "Array",
+            QubitInitKind::Single => "Single",
+            QubitInitKind::Tuple(_) => "Tuple",
+            QubitInitKind::Err => "Err",
+        };
+        self.line(&format!(
+            "QubitInit {} {} [Type {}]: {kind}",
+            init.id, init.span, init.ty
+        ));
+        self.nested(|printer| visit::walk_qubit_init(printer, init));
+    }
+}
+
+/// Describes an expression kind on a single line, leaving out the nodes it contains.
+fn expr_kind_label(kind: &ExprKind) -> String {
+    match kind {
+        ExprKind::Array(_) => "Array".to_string(),
+        ExprKind::ArrayRepeat(..) => "ArrayRepeat".to_string(),
+        ExprKind::Assign(..) => "Assign".to_string(),
+        ExprKind::AssignOp(op, ..) => format!("AssignOp ({op:?})"),
+        ExprKind::AssignField(_, field, _) => format!("AssignField ({field})"),
+        ExprKind::AssignIndex(..) => "AssignIndex".to_string(),
+        ExprKind::BinOp(op, ..) => format!("BinOp ({op:?})"),
+        ExprKind::Block(_) => "Block".to_string(),
+        ExprKind::Call(..) => "Call".to_string(),
+        ExprKind::Closure(args, callable) => format!("Closure([{}], {callable})", join(args)),
+        ExprKind::Conjugate(..) => "Conjugate".to_string(),
+        ExprKind::Err => "Err".to_string(),
+        ExprKind::Fail(_) => "Fail".to_string(),
+        ExprKind::Field(_, field) => format!("Field ({field})"),
+        ExprKind::For(..) => "For".to_string(),
+        ExprKind::Hole => "Hole".to_string(),
+        ExprKind::If(..) => "If".to_string(),
+        ExprKind::Index(..) => "Index".to_string(),
+        ExprKind::Lit(lit) => format!("Lit: {lit}"),
+        ExprKind::Range(..) => "Range".to_string(),
+        ExprKind::Repeat(..) => "Repeat".to_string(),
+        ExprKind::Return(_) => "Return".to_string(),
+        ExprKind::String(components) => {
+            let mut label = "String: ".to_string();
+            for component in components {
+                let _ = match component {
+                    StringComponent::Expr(expr) => write!(label, "{{{}}}", expr.id),
+                    StringComponent::Lit(lit) => write!(label, "{lit:?}"),
+                };
+            }
+            label
+        }
+        ExprKind::UpdateIndex(..) => "UpdateIndex".to_string(),
+        ExprKind::Tuple(_) => "Tuple".to_string(),
+        ExprKind::UnOp(op, _) => format!("UnOp ({op})"),
+        ExprKind::UpdateField(_, field, _) => format!("UpdateField ({field})"),
+        ExprKind::Var(res, args) if args.is_empty() => format!("Var: {res}"),
+        ExprKind::Var(res, args) => format!("Var: {res} <{}>", join(args)),
+        ExprKind::While(..) => "While".to_string(),
+    }
+}
+
+fn join(items: &[impl ToString]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,163 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{item_to_string, package_to_string};
+use crate::{
+    assigner::Assigner,
+    hir::{
+        Block, CallableDecl, CallableKind, Expr, ExprKind, Ident, Item, ItemKind, Lit, Mutability,
+        Package, Pat, PatKind, Res, SpecBody, SpecDecl, SpecGen, Stmt, StmtKind, Visibility,
+    },
+    ty::{FunctorSetValue, Prim, Ty},
+};
+use expect_test::expect;
+use qsc_data_structures::span::Span;
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span { lo, hi }
+}
+
+fn bind(assigner: &mut Assigner, name: &str, ty: Ty, span: Span) -> Pat {
+    let ident = Ident {
+        id: assigner.next_node(),
+        span,
+        name: name.into(),
+    };
+    Pat {
+        id: assigner.next_node(),
+        span,
+        ty,
+        kind: PatKind::Bind(ident),
+    }
+}
+
+fn empty_block(assigner: &mut Assigner, span: Span) -> Block {
+    Block {
+        id: assigner.next_node(),
+        span,
+        ty: Ty::UNIT,
+        stmts: Vec::new(),
+    }
+}
+
+#[test]
+fn entry_expr_with_nested_nodes() {
+    let mut assigner = Assigner::new();
+    let pat = bind(&mut assigner, "x", Ty::Prim(Prim::Int), span(4, 5));
+    let x = match &pat.kind {
+        PatKind::Bind(ident) => ident.id,
+        _ => unreachable!(),
+    };
+    let init = Expr {
+        id: assigner.next_node(),
+        span: span(8, 9),
+        ty: Ty::Prim(Prim::Int),
+        kind: ExprKind::Lit(Lit::Int(1)),
+    };
+    let local = Stmt {
+        id: assigner.next_node(),
+        span: span(0, 10),
+        kind: StmtKind::Local(Mutability::Immutable, pat, init),
+    };
+    let var = Expr {
+        id: assigner.next_node(),
+        span: span(11, 12),
+        ty: Ty::Prim(Prim::Int),
+        kind: ExprKind::Var(Res::Local(x), Vec::new()),
+    };
+    let result = Stmt {
+        id: assigner.next_node(),
+        span: span(11, 12),
+        kind: StmtKind::Expr(var),
+    };
+    let block = Block {
+        id: assigner.next_node(),
+        span: span(0, 13),
+        ty: Ty::Prim(Prim::Int),
+        stmts: vec![local, result],
+    };
+    let package = Package {
+        entry: Some(Expr {
+            id: assigner.next_node(),
+            span: span(0, 13),
+            ty: Ty::Prim(Prim::Int),
+            kind: ExprKind::Block(block),
+        }),
+        ..Package::default()
+    };
+
+    expect![[r#"
+        Entry
+            Expr 7 [0-13] [Type Int]: Block
+                Block 6 [0-13] [Type Int]
+                    Stmt 3 [0-10]: Local (Immutable)
+                        Pat 1 [4-5] [Type Int]: Bind 0 "x"
+                        Expr 2 [8-9] [Type Int]: Lit: Int(1)
+                    Stmt 5 [11-12]: Expr
+                        Expr 4 [11-12] [Type Int]: Var: Local 0"#]]
+    .assert_eq(&package_to_string(&package));
+}
+
+#[test]
+fn callable_with_generated_specialization() {
+    let mut assigner = Assigner::new();
+    let item = assigner.next_item();
+    let name = Ident {
+        id: assigner.next_node(),
+        span: span(10, 13),
+        name: "Foo".into(),
+    };
+    let input = bind(&mut assigner, "q", Ty::Prim(Prim::Qubit), span(14, 25));
+    let body = empty_block(&mut assigner, span(40, 42));
+    let body = SpecDecl {
+        id: assigner.next_node(),
+        span: span(40, 42),
+        body: SpecBody::Impl(None, body),
+        generated: None,
+    };
+    let adj = empty_block(&mut assigner, span(40, 42));
+    let adj = SpecDecl {
+        id: assigner.next_node(),
+        span: span(0, 60),
+        body: SpecBody::Impl(None, adj),
+        generated: Some(SpecGen::Invert),
+    };
+    let decl = CallableDecl {
+        id: assigner.next_node(),
+        span: span(0, 60),
+        kind: CallableKind::Operation,
+        name,
+        generics: Vec::new(),
+        input,
+        defaults: vec![None],
+        output: Ty::UNIT,
+        functors: FunctorSetValue::Adj,
+        body,
+        adj: Some(adj),
+        ctl: None,
+        ctl_adj: None,
+    };
+    let mut package = Package::default();
+    package.items.insert(
+        item,
+        Item {
+            id: item,
+            span: span(0, 60),
+            parent: None,
+            doc: "".into(),
+            attrs: Vec::new(),
+            visibility: Visibility::Public,
+            kind: ItemKind::Callable(decl),
+        },
+    );
+
+    expect![[r#"
+        Item 0 [0-60] (Public): Callable
+            CallableDecl 7 [0-60] (operation): "Foo" [Output Unit] [Functors Adj]
+                Pat 2 [14-25] [Type Qubit]: Bind 1 "q"
+                SpecDecl 4 [40-42]: body
+                    Block 3 [40-42] [Type Unit]
+                SpecDecl 6 [0-60]: adjoint [Generated Invert]
+                    Block 5 [40-42] [Type Unit]"#]]
+    .assert_eq(&item_to_string(&package, item));
+}
@@ -4,11 +4,14 @@
 use indenter::{indented, Indented};
 use qsc_data_structures::span::Span;
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 
 use crate::hir::{CallableKind, FieldPath, Functor, ItemId, PackageId, Res};
 use std::{
     fmt::{self, Debug, Display, Formatter, Write},
     rc::Rc,
+    result,
+    str::FromStr,
 };
 
 fn set_indentation<'a, 'b>(
@@ -24,7 +27,7 @@ fn set_indentation<'a, 'b>(
 }
 
 /// A type.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Ty {
     /// An array type.
     Array(Box<Ty>),
@@ -173,12 +176,30 @@ impl Scheme {
         }
     }
 
+    /// Replaces the functors of the type.
+    #[must_use]
+    pub fn with_functors(&self, functors: FunctorSetValue) -> Self {
+        Self {
+            params: self.params.clone(),
+            ty: Box::new(Arrow {
+                functors: FunctorSet::Value(functors),
+                ..(*self.ty).clone()
+            }),
+        }
+    }
+
     /// The generic parameters to the type.
     #[must_use]
     pub fn params(&self) -> &[GenericParam] {
         &self.params
     }
 
+    /// The type, before it is instantiated.
+    #[must_use]
+    pub fn ty(&self) -> &Arrow {
+        &self.ty
+    }
+
     /// Instantiates this type scheme with the given arguments.
     ///
     /// # Errors
@@ -258,23 +279,77 @@ fn instantiate_arrow_ty<'a>(
 impl Display for GenericParam {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
-            GenericParam::Ty(name) => write!(f, "type {name}"),
+            GenericParam::Ty { name, bounds } => {
+                write!(f, "type {name}")?;
+                for (ix, bound) in bounds.iter().enumerate() {
+                    let sep = if ix == 0 { ":" } else { " +" };
+                    write!(f, "{sep} {bound}")?;
+                }
+                Ok(())
+            }
             GenericParam::Functor(min) => write!(f, "functor ({min})"),
         }
     }
 }
 
 /// The kind of a generic parameter.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum GenericParam {
     /// A type parameter.
-    Ty(TypeParamName),
+    Ty {
+        /// The name of the parameter.
+        name: TypeParamName,
+        /// The classes that every argument to the parameter must belong to.
+        bounds: Vec<ClassConstraint>,
+    },
     /// A functor parameter with a lower bound.
     Functor(FunctorSetValue),
 }
 
+/// A class that a type parameter can be constrained to, like `'T: Eq`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ClassConstraint {
+    /// Supports the `+` operator.
+    Add,
+    /// Supports the `==` and `!=` operators.
+    Eq,
+    /// Supports the bitwise operators, `%`, and integer exponentiation.
+    Integral,
+    /// Supports the arithmetic and comparison operators.
+    Num,
+    /// Can be converted into a string by interpolation.
+    Show,
+}
+
+impl Display for ClassConstraint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ClassConstraint::Add => "Add",
+            ClassConstraint::Eq => "Eq",
+            ClassConstraint::Integral => "Integral",
+            ClassConstraint::Num => "Num",
+            ClassConstraint::Show => "Show",
+        })
+    }
+}
+
+impl FromStr for ClassConstraint {
+    type Err = ();
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        match s {
+            "Add" => Ok(Self::Add),
+            "Eq" => Ok(Self::Eq),
+            "Integral" => Ok(Self::Integral),
+            "Num" => Ok(Self::Num),
+            "Show" => Ok(Self::Show),
+            _ => Err(()),
+        }
+    }
+}
+
 /// The name of a generic type parameter.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct TypeParamName {
     /// The span.
     pub span: Span,
@@ -289,7 +364,7 @@ impl Display for TypeParamName {
 }
 
 /// A generic parameter ID.
-#[derive(Clone, Copy, Default, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ParamId(u32);
 
 impl ParamId {
@@ -323,7 +398,7 @@ impl Display for ParamId {
 }
 
 /// An argument to a generic parameter.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum GenericArg {
     /// A type argument.
     Ty(Ty),
@@ -341,7 +416,7 @@ impl Display for GenericArg {
 }
 
 /// An arrow type: `->` for a function or `=>` for an operation.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Arrow {
     /// Whether the callable is a function or an operation.
     pub kind: CallableKind,
@@ -381,7 +456,7 @@ impl Display for Arrow {
 }
 
 /// A primitive type.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Prim {
     /// The big integer type.
     BigInt,
@@ -410,7 +485,7 @@ pub enum Prim {
 }
 
 /// A set of functors.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum FunctorSet {
     /// An evaluated set.
     Value(FunctorSetValue),
@@ -446,7 +521,7 @@ impl Display for FunctorSet {
 }
 
 /// The value of a functor set.
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub enum FunctorSetValue {
     /// The empty set.
     #[default]
@@ -523,7 +598,7 @@ impl Display for FunctorSetValue {
 }
 
 /// A user-defined type.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Udt {
     /// The span.
     pub span: Span,
@@ -637,6 +712,26 @@ impl Udt {
     pub fn field_ty_by_name(&self, name: &str) -> Option<&Ty> {
         self.find_field_by_name(name).map(|field| &field.ty)
     }
+
+    /// The names of all fields in declaration order. Returns [None] if any field of this
+    /// user-defined type is unnamed.
+    #[must_use]
+    pub fn field_names(&self) -> Option<Vec<Rc<str>>> {
+        fn field_names(def: &UdtDef, names: &mut Vec<Rc<str>>) -> Option<()> {
+            match &def.kind {
+                UdtDefKind::Field(field) => names.push(field.name.clone()?),
+                UdtDefKind::Tuple(defs) => {
+                    for def in defs {
+                        field_names(def, names)?;
+                    }
+                }
+            }
+            Some(())
+        }
+
+        let mut names = Vec::new();
+        field_names(&self.definition, &mut names).map(|()| names)
+    }
 }
 
 impl Display for Udt {
@@ -650,7 +745,7 @@ impl Display for Udt {
 }
 
 /// A UDT type definition.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UdtDef {
     /// The span.
     pub span: Span,
@@ -665,7 +760,7 @@ impl Display for UdtDef {
 }
 
 /// A UDT type definition kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum UdtDefKind {
     /// A field definition with an optional name but required type.
     Field(UdtField),
@@ -699,7 +794,7 @@ impl Display for UdtDefKind {
 }
 
 /// A user-defined type.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UdtField {
     /// The span of the field name.
     pub name_span: Option<Span>,
@@ -722,7 +817,7 @@ impl Display for UdtField {
 }
 
 /// A placeholder type variable used during type inference.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct InferTyId(usize);
 
 impl InferTyId {
@@ -752,7 +847,9 @@ impl From<InferTyId> for usize {
 }
 
 /// A placeholder functor variable used during type inference.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct InferFunctorId(usize);
 
 impl InferFunctorId {
@@ -20,8 +20,8 @@ use crate::{
 use num_bigint::BigInt;
 use num_traits::Num;
 use qsc_ast::ast::{
-    self, BinOp, CallableKind, Expr, ExprKind, Functor, Lit, NodeId, Pat, PatKind, Pauli,
-    StringComponent, TernOp, UnOp,
+    self, BinOp, CallableKind, Expr, ExprKind, FieldAssign, Functor, Lit, MatchArm, MatchPat,
+    MatchPatKind, NodeId, Pat, PatKind, Path, Pauli, StringComponent, TernOp, UnOp,
 };
 use qsc_data_structures::span::Span;
 use std::{result, str::FromStr};
@@ -91,6 +91,7 @@ pub(super) fn is_stmt_final(kind: &ExprKind) -> bool {
             | ExprKind::Conjugate(..)
             | ExprKind::For(..)
             | ExprKind::If(..)
+            | ExprKind::Match(..)
             | ExprKind::Repeat(..)
             | ExprKind::While(..)
     )
@@ -177,6 +178,10 @@ fn expr_base(s: &mut ParserContext) -> Result<Box<Expr>> {
         Ok(Box::new(ExprKind::Interpolate(
             components.into_boxed_slice(),
         )))
+    } else if token(s, TokenKind::Keyword(Keyword::Match)).is_ok() {
+        expr_match(s)
+    } else if token(s, TokenKind::Keyword(Keyword::New)).is_ok() {
+        expr_struct(s)
     } else if token(s, TokenKind::Keyword(Keyword::Repeat)).is_ok() {
         let body = stmt::parse_block(s)?;
         token(s, TokenKind::Keyword(Keyword::Until))?;
@@ -205,7 +210,7 @@ fn expr_base(s: &mut ParserContext) -> Result<Box<Expr>> {
     } else if let Some(l) = lit(s)? {
         Ok(Box::new(ExprKind::Lit(Box::new(l))))
     } else if let Some(p) = opt(s, path)? {
-        Ok(Box::new(ExprKind::Path(p)))
+        Ok(Box::new(path_or_field(*p)))
     } else {
         Err(Error(ErrorKind::Rule(
             "expression",
@@ -244,6 +249,88 @@ fn expr_if(s: &mut ParserContext) -> Result<Box<ExprKind>> {
     Ok(Box::new(ExprKind::If(cond, body, otherwise)))
 }
 
+fn expr_match(s: &mut ParserContext) -> Result<Box<ExprKind>> {
+    let scrutinee = expr(s)?;
+    token(s, TokenKind::Open(Delim::Brace))?;
+    let (arms, _) = seq(s, parse_match_arm)?;
+    token(s, TokenKind::Close(Delim::Brace))?;
+    Ok(Box::new(ExprKind::Match(
+        scrutinee,
+        arms.into_boxed_slice(),
+    )))
+}
+
+fn parse_match_arm(s: &mut ParserContext) -> Result<Box<MatchArm>> {
+    let lo = s.peek().span.lo;
+    let pat = match_pat(s)?;
+    token(s, TokenKind::FatArrow)?;
+    let body = expr(s)?;
+    Ok(Box::new(MatchArm {
+        id: NodeId::default(),
+        span: s.span(lo),
+        pat,
+        body,
+    }))
+}
+
+fn match_pat(s: &mut ParserContext) -> Result<Box<MatchPat>> {
+    let lo = s.peek().span.lo;
+    let kind = if token(s, TokenKind::Keyword(Keyword::Underscore)).is_ok() {
+        MatchPatKind::Discard
+    } else if token(s, TokenKind::ClosedBinOp(ClosedBinOp::Minus)).is_ok() {
+        let lit = match lit(s)? {
+            Some(Lit::BigInt(value)) => Lit::BigInt(Box::new(-*value)),
+            Some(Lit::Double(value)) => Lit::Double(-value),
+            Some(Lit::Int(value)) => Lit::Int(value.wrapping_neg()),
+            _ => {
+                return Err(Error(ErrorKind::Rule(
+                    "numeric literal",
+                    s.peek().kind,
+                    s.peek().span,
+                )))
+            }
+        };
+        MatchPatKind::Lit(Box::new(lit))
+    } else if let Some(lit) = lit(s)? {
+        MatchPatKind::Lit(Box::new(lit))
+    } else if let Some(name) = opt(s, ident)? {
+        MatchPatKind::Bind(name)
+    } else {
+        return Err(Error(ErrorKind::Rule(
+            "pattern",
+            s.peek().kind,
+            s.peek().span,
+        )));
+    };
+
+    Ok(Box::new(MatchPat {
+        id: NodeId::default(),
+        span: s.span(lo),
+        kind: Box::new(kind),
+    }))
+}
+
+fn expr_struct(s: &mut ParserContext) -> Result<Box<ExprKind>> {
+    let name = path(s)?;
+    token(s, TokenKind::Open(Delim::Brace))?;
+    let (fields, _) = seq(s, parse_field_assign)?;
+    token(s, TokenKind::Close(Delim::Brace))?;
+    Ok(Box::new(ExprKind::Struct(name, fields.into_boxed_slice())))
+}
+
+fn parse_field_assign(s: &mut ParserContext) -> Result<Box<FieldAssign>> {
+    let lo = s.peek().span.lo;
+    let field = ident(s)?;
+    token(s, TokenKind::Eq)?;
+    let value = expr(s)?;
+    Ok(Box::new(FieldAssign {
+        id: NodeId::default(),
+        span: s.span(lo),
+        field,
+        value,
+    }))
+}
+
 fn expr_set(s: &mut ParserContext) -> Result<Box<ExprKind>> {
     let lhs = expr(s)?;
     if token(s, TokenKind::Eq).is_ok() {
@@ -631,6 +718,33 @@ fn lambda_op(s: &mut ParserContext, input: Expr, kind: CallableKind) -> Result<B
     Ok(Box::new(ExprKind::Lambda(kind, input, output)))
 }
 
+/// A package-qualified path without a namespace, like `x::Foo`, can't name a global item, so it is
+/// a field access on a local instead.
+fn path_or_field(path: Path) -> ExprKind {
+    match path {
+        Path {
+            package: Some(package),
+            namespace: None,
+            name,
+            ..
+        } => {
+            let record = Box::new(Expr {
+                id: NodeId::default(),
+                span: package.span,
+                kind: Box::new(ExprKind::Path(Box::new(Path {
+                    id: NodeId::default(),
+                    span: package.span,
+                    package: None,
+                    namespace: None,
+                    name: package,
+                }))),
+            });
+            ExprKind::Field(record, name)
+        }
+        path => ExprKind::Path(Box::new(path)),
+    }
+}
+
 fn field_op(s: &mut ParserContext, lhs: Box<Expr>) -> Result<Box<ExprKind>> {
     Ok(Box::new(ExprKind::Field(lhs, ident(s)?)))
 }
@@ -643,7 +757,7 @@ fn index_op(s: &mut ParserContext, lhs: Box<Expr>) -> Result<Box<ExprKind>> {
 
 fn call_op(s: &mut ParserContext, lhs: Box<Expr>) -> Result<Box<ExprKind>> {
     let lo = s.span(0).hi - 1;
-    let (args, final_sep) = seq(s, expr)?;
+    let (args, final_sep) = seq(s, call_arg)?;
     token(s, TokenKind::Close(Delim::Paren))?;
     let rhs = Box::new(Expr {
         id: NodeId::default(),
@@ -653,6 +767,32 @@ fn call_op(s: &mut ParserContext, lhs: Box<Expr>) -> Result<Box<ExprKind>> {
     Ok(Box::new(ExprKind::Call(lhs, rhs)))
 }
 
+/// Parses a call argument, which is either an expression or a named argument `a = b`.
+fn call_arg(s: &mut ParserContext) -> Result<Box<Expr>> {
+    let lo = s.peek().span.lo;
+    let arg = expr(s)?;
+    if token(s, TokenKind::Eq).is_err() {
+        return Ok(arg);
+    }
+
+    let name = match *arg.kind {
+        ExprKind::Path(path) if path.package.is_none() && path.namespace.is_none() => path.name,
+        _ => {
+            return Err(Error(ErrorKind::Convert(
+                "argument name",
+                "expression",
+                arg.span,
+            )))
+        }
+    };
+    let value = expr(s)?;
+    Ok(Box::new(Expr {
+        id: NodeId::default(),
+        span: s.span(lo),
+        kind: Box::new(ExprKind::NamedArg(name, value)),
+    }))
+}
+
 fn range_op(s: &mut ParserContext, start: Box<Expr>) -> Result<Box<ExprKind>> {
     let rhs = expr_op(s, OpContext::Precedence(RANGE_PRECEDENCE + 1))?;
     Ok(Box::new(if token(s, TokenKind::DotDot).is_ok() {
@@ -1653,6 +1653,32 @@ fn field_op() {
     );
 }
 
+#[test]
+fn field_op_chain() {
+    check(
+        expr,
+        "x::foo::bar",
+        &expect![[r#"
+        Expr _id_ [0-11]: Field:
+            Expr _id_ [0-6]: Field:
+                Expr _id_ [0-1]: Path: Path _id_ [0-1] (Ident _id_ [0-1] "x")
+                Ident _id_ [3-6] "foo"
+            Ident _id_ [8-11] "bar""#]],
+    );
+}
+
+#[test]
+fn package_qualified_path() {
+    check(
+        expr,
+        "Lib::Math.Add(1)",
+        &expect![[r#"
+        Expr _id_ [0-16]: Call:
+            Expr _id_ [0-13]: Path: Path _id_ [0-13] (Ident _id_ [0-3] "Lib")::(Ident _id_ [5-9] "Math") (Ident _id_ [10-13] "Add")
+            Expr _id_ [13-16]: Paren: Expr _id_ [14-15]: Lit: Int(1)"#]],
+    );
+}
+
 #[test]
 fn index_op() {
     check(
@@ -1702,6 +1728,42 @@ fn call_op_singleton_tuple() {
     );
 }
 
+#[test]
+fn call_op_named_arg() {
+    check(
+        expr,
+        "Foo(x, y = 1)",
+        &expect![[r#"
+            Expr _id_ [0-13]: Call:
+                Expr _id_ [0-3]: Path: Path _id_ [0-3] (Ident _id_ [0-3] "Foo")
+                Expr _id_ [3-13]: Tuple:
+                    Expr _id_ [4-5]: Path: Path _id_ [4-5] (Ident _id_ [4-5] "x")
+                    Expr _id_ [7-12]: NamedArg:
+                        Ident _id_ [7-8] "y"
+                        Expr _id_ [11-12]: Lit: Int(1)"#]],
+    );
+}
+
+#[test]
+fn call_op_invalid_named_arg() {
+    check(
+        expr,
+        "Foo(x.y = 1)",
+        &expect![[r#"
+            Error(
+                Convert(
+                    "argument name",
+                    "expression",
+                    Span {
+                        lo: 4,
+                        hi: 7,
+                    },
+                ),
+            )
+        "#]],
+    );
+}
+
 #[test]
 fn call_op_pair() {
     check(
@@ -2435,3 +2497,59 @@ fn invalid_initial_commas_in_pattern() {
             ]"#]],
     );
 }
+
+#[test]
+fn struct_constructor() {
+    check(
+        expr,
+        "new Foo { Bar = 1, Baz = 2 }",
+        &expect![[r#"
+            Expr _id_ [0-28]: Struct (Path _id_ [4-7] (Ident _id_ [4-7] "Foo")):
+                FieldAssign _id_ [10-17] (Ident _id_ [10-13] "Bar"): Expr _id_ [16-17]: Lit: Int(1)
+                FieldAssign _id_ [19-26] (Ident _id_ [19-22] "Baz"): Expr _id_ [25-26]: Lit: Int(2)"#]],
+    );
+}
+
+#[test]
+fn struct_constructor_empty() {
+    check(
+        expr,
+        "new Foo {}",
+        &expect![[r#"
+            Expr _id_ [0-10]: Struct (Path _id_ [4-7] (Ident _id_ [4-7] "Foo")):"#]],
+    );
+}
+
+#[test]
+fn match_lit_and_bind() {
+    check(
+        expr,
+        "match r { Zero => 0, x => 1 }",
+        &expect![[r#"
+            Expr _id_ [0-29]: Match:
+                Expr _id_ [6-7]: Path: Path _id_ [6-7] (Ident _id_ [6-7] "r")
+                MatchArm _id_ [10-19]:
+                    MatchPat _id_ [10-14]: Lit: Result(Zero)
+                    Expr _id_ [18-19]: Lit: Int(0)
+                MatchArm _id_ [21-27]:
+                    MatchPat _id_ [21-22]: Bind: Ident _id_ [21-22] "x"
+                    Expr _id_ [26-27]: Lit: Int(1)"#]],
+    );
+}
+
+#[test]
+fn match_negative_lit_and_discard() {
+    check(
+        expr,
+        "match n { -1 => a, _ => b }",
+        &expect![[r#"
+            Expr _id_ [0-27]: Match:
+                Expr _id_ [6-7]: Path: Path _id_ [6-7] (Ident _id_ [6-7] "n")
+                MatchArm _id_ [10-17]:
+                    MatchPat _id_ [10-12]: Lit: Int(-1)
+                    Expr _id_ [16-17]: Path: Path _id_ [16-17] (Ident _id_ [16-17] "a")
+                MatchArm _id_ [19-25]:
+                    MatchPat _id_ [19-20]: Discard
+                    Expr _id_ [24-25]: Path: Path _id_ [24-25] (Ident _id_ [24-25] "b")"#]],
+    );
+}
@@ -0,0 +1,363 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Formats Q# source in the standard style. The formatter works on the tokens of the source rather
+//! than its syntax tree, so it keeps every comment where it is and can format source that does not
+//! parse. It only ever changes the whitespace between tokens: it indents each line by the
+//! delimiters that enclose it, puts an opening brace at the end of the line before it, starts a new
+//! line after each statement, normalizes the spaces within a line, and puts the parameters of a
+//! callable signature that is too long on their own lines.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    keyword::Keyword,
+    lex::{Delim, InterpolatedEnding, InterpolatedStart, Lexer, StringToken, TokenKind},
+};
+use qsc_data_structures::span::Span;
+
+/// The widest a line with a callable signature can be before its parameters are put on their own
+/// lines.
+pub const MAX_LINE_WIDTH: usize = 100;
+
+const INDENT: &str = "    ";
+
+/// An edit that replaces a span of the source with new text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub new_text: String,
+}
+
+/// Formats the source, returning the edits that change it into its formatted form. The edits are
+/// in order and do not overlap. Source that does not lex, like source with an unterminated string,
+/// is not formatted.
+#[must_use]
+pub fn format(input: &str) -> Vec<TextEdit> {
+    let Some(tokens) = tokens(input) else {
+        return Vec::new();
+    };
+
+    let newline = if input.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut gaps = layout(input, &tokens, newline);
+    wrap_signatures(input, &tokens, &mut gaps, newline);
+
+    let mut edits = Vec::new();
+    let mut lo = 0;
+    for (token, gap) in tokens.iter().zip(&gaps) {
+        push_edit(&mut edits, input, lo, token.span.lo, gap);
+        lo = token.span.hi;
+    }
+    if let Some(gap) = gaps.last().filter(|_| !tokens.is_empty()) {
+        let hi = input
+            .len()
+            .try_into()
+            .expect("input length should fit into u32");
+        push_edit(&mut edits, input, lo, hi, gap);
+    }
+    edits
+}
+
+/// Applies edits, like those returned by [`format`], to the source.
+#[must_use]
+pub fn apply(input: &str, edits: &[TextEdit]) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut offset = 0;
+    for edit in edits {
+        output.push_str(&input[offset..edit.span.lo as usize]);
+        output.push_str(&edit.new_text);
+        offset = edit.span.hi as usize;
+    }
+    output.push_str(&input[offset..]);
+    output
+}
+
+fn push_edit(edits: &mut Vec<TextEdit>, input: &str, lo: u32, hi: u32, new_text: &str) {
+    if input[lo as usize..hi as usize] != *new_text {
+        edits.push(TextEdit {
+            span: Span { lo, hi },
+            new_text: new_text.to_string(),
+        });
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    Syntax(TokenKind),
+    Comment,
+}
+
+struct Token {
+    kind: Kind,
+    span: Span,
+}
+
+/// The tokens of the source, including its comments, or `None` if the source does not lex.
+fn tokens(input: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut lo = 0;
+    for token in Lexer::new(input) {
+        let token = token.ok()?;
+        push_comments(input, lo, token.span.lo, &mut tokens);
+        tokens.push(Token {
+            kind: Kind::Syntax(token.kind),
+            span: token.span,
+        });
+        lo = token.span.hi;
+    }
+
+    let hi = input
+        .len()
+        .try_into()
+        .expect("input length should fit into u32");
+    push_comments(input, lo, hi, &mut tokens);
+    Some(tokens)
+}
+
+/// Adds the comments between two tokens, where there is only whitespace and comments.
+fn push_comments(input: &str, lo: u32, hi: u32, tokens: &mut Vec<Token>) {
+    let gap = &input[lo as usize..hi as usize];
+    let mut offset = 0;
+    while let Some(start) = gap[offset..].find("//").map(|start| offset + start) {
+        let end = gap[start..].find('\n').map_or(gap.len(), |end| start + end);
+        let end = if gap[..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+        tokens.push(Token {
+            kind: Kind::Comment,
+            span: Span {
+                lo: lo + u32::try_from(start).expect("offset should fit into u32"),
+                hi: lo + u32::try_from(end).expect("offset should fit into u32"),
+            },
+        });
+        offset = end;
+    }
+}
+
+/// The whitespace before each token, and after the last token.
+fn layout(input: &str, tokens: &[Token], newline: &str) -> Vec<String> {
+    let mut gaps = Vec::with_capacity(tokens.len() + 1);
+    // Each open delimiter, and whether the tokens after it start on a new line.
+    let mut delims: Vec<bool> = Vec::new();
+    let mut interpolations = 0;
+    let mut prev: Option<&Token> = None;
+    for token in tokens {
+        let original = &input[prev.map_or(0, |prev| prev.span.hi as usize)..token.span.lo as usize];
+        if interpolations > 0 {
+            gaps.push(original.to_string());
+        } else {
+            let lines = line_breaks(prev.map(|prev| prev.kind), token.kind, original);
+            // A comment after an open delimiter ends its line, so the tokens after the delimiter
+            // are on their own lines.
+            if matches!(
+                prev.map(|prev| prev.kind),
+                Some(Kind::Syntax(TokenKind::Open(_)))
+            ) {
+                if let Some(breaks) = delims.last_mut() {
+                    *breaks = lines > 0 || token.kind == Kind::Comment;
+                }
+            }
+            if matches!(token.kind, Kind::Syntax(TokenKind::Close(_))) {
+                delims.pop();
+            }
+
+            if lines > 0 {
+                let indent = delims.iter().filter(|&&breaks| breaks).count();
+                gaps.push(newline.repeat(lines) + &INDENT.repeat(indent));
+            } else {
+                gaps.push(spacing(prev.map(|prev| prev.kind), token.kind, original).to_string());
+            }
+        }
+
+        match token.kind {
+            Kind::Syntax(TokenKind::Open(_)) => delims.push(false),
+            Kind::Syntax(TokenKind::Close(_)) if interpolations > 0 => {
+                delims.pop();
+            }
+            Kind::Syntax(TokenKind::String(StringToken::Interpolated(start, end))) => {
+                if start == InterpolatedStart::RBrace {
+                    interpolations -= 1;
+                }
+                if end == InterpolatedEnding::LBrace {
+                    interpolations += 1;
+                }
+            }
+            _ => {}
+        }
+        prev = Some(token);
+    }
+
+    gaps.push(if tokens.is_empty() {
+        String::new()
+    } else {
+        newline.to_string()
+    });
+    gaps
+}
+
+/// The number of line breaks between two tokens: none to keep them on the same line, or at most
+/// two to keep one blank line.
+fn line_breaks(prev: Option<Kind>, token: Kind, original: &str) -> usize {
+    let newlines = original.matches('\n').count();
+    match (prev, token) {
+        (None, _) => 0,
+        (
+            Some(Kind::Syntax(TokenKind::Open(Delim::Brace))),
+            Kind::Syntax(TokenKind::Close(Delim::Brace)),
+        ) => newlines.min(1),
+        (Some(Kind::Syntax(TokenKind::Open(Delim::Brace) | TokenKind::Semi)), Kind::Comment)
+            if newlines == 0 =>
+        {
+            0
+        }
+        (_, Kind::Syntax(TokenKind::Close(Delim::Brace)))
+        | (Some(Kind::Syntax(TokenKind::Open(Delim::Brace))), _) => 1,
+        (Some(Kind::Comment | Kind::Syntax(TokenKind::DocComment | TokenKind::Semi)), _) => {
+            newlines.clamp(1, 2)
+        }
+        (Some(Kind::Syntax(TokenKind::Close(Delim::Brace))), _) => newlines.min(2),
+        (_, Kind::Syntax(TokenKind::Open(Delim::Brace))) => 0,
+        _ => newlines.min(2),
+    }
+}
+
+/// The spaces between two tokens on the same line.
+fn spacing(prev: Option<Kind>, token: Kind, original: &str) -> &'static str {
+    let (Some(Kind::Syntax(prev)), Kind::Syntax(token)) = (prev, token) else {
+        return if prev.is_some() { " " } else { "" };
+    };
+
+    match (prev, token) {
+        (TokenKind::Open(Delim::Paren | Delim::Bracket), _)
+        | (
+            _,
+            TokenKind::Close(Delim::Paren | Delim::Bracket) | TokenKind::Comma | TokenKind::Semi,
+        )
+        | (TokenKind::Open(Delim::Brace), TokenKind::Close(Delim::Brace))
+        | (
+            TokenKind::Ident | TokenKind::Close(Delim::Paren | Delim::Bracket),
+            TokenKind::Open(Delim::Paren | Delim::Bracket),
+        ) => "",
+        (TokenKind::Comma, _) | (_, TokenKind::Open(Delim::Brace)) => " ",
+        (op, _) | (_, op) if is_spaced_op(op) => " ",
+        _ if original.is_empty() => "",
+        _ => " ",
+    }
+}
+
+/// Whether the token is an operator that always has a space on each side.
+fn is_spaced_op(token: TokenKind) -> bool {
+    matches!(
+        token,
+        TokenKind::BinOpEq(_)
+            | TokenKind::Colon
+            | TokenKind::Eq
+            | TokenKind::EqEq
+            | TokenKind::FatArrow
+            | TokenKind::LArrow
+            | TokenKind::Ne
+            | TokenKind::RArrow
+            | TokenKind::WSlashEq
+    )
+}
+
+/// Puts each parameter of a callable signature on its own line if the line with the signature is
+/// too wide.
+fn wrap_signatures(input: &str, tokens: &[Token], gaps: &mut [String], newline: &str) {
+    for (index, token) in tokens.iter().enumerate() {
+        if !matches!(
+            token.kind,
+            Kind::Syntax(TokenKind::Keyword(Keyword::Function | Keyword::Operation))
+        ) {
+            continue;
+        }
+
+        let Some((open, close)) = params(tokens, index) else {
+            continue;
+        };
+        if close == open + 1 || gaps[open + 1..=close].iter().any(|gap| gap.contains('\n')) {
+            continue;
+        }
+
+        let start = (0..=index)
+            .rev()
+            .find(|&i| gaps[i].contains('\n'))
+            .unwrap_or(0);
+        let end = (close + 1..tokens.len())
+            .find(|&i| gaps[i].contains('\n'))
+            .unwrap_or(tokens.len());
+        let indent = gaps[start]
+            .rsplit('\n')
+            .next()
+            .expect("split should have at least one part")
+            .to_string();
+        let width = indent.len()
+            + (start..end)
+                .map(|i| {
+                    let gap = if i == start {
+                        0
+                    } else {
+                        gaps[i].chars().count()
+                    };
+                    let span = tokens[i].span;
+                    gap + input[span.lo as usize..span.hi as usize].chars().count()
+                })
+                .sum::<usize>();
+        if width <= MAX_LINE_WIDTH {
+            continue;
+        }
+
+        let inner = format!("{newline}{indent}{INDENT}");
+        gaps[open + 1].clone_from(&inner);
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate().take(close).skip(open + 1) {
+            match token.kind {
+                Kind::Syntax(TokenKind::Open(_)) => depth += 1,
+                Kind::Syntax(TokenKind::Close(_)) => depth -= 1,
+                Kind::Syntax(TokenKind::Comma) if depth == 0 && i + 1 < close => {
+                    gaps[i + 1].clone_from(&inner);
+                }
+                _ => {}
+            }
+        }
+        gaps[close] = format!("{newline}{indent}");
+    }
+}
+
+/// The indices of the parentheses around the parameters of the callable declared by the keyword
+/// at the given index: the name, any type parameters, and then the parameters.
+fn params(tokens: &[Token], keyword: usize) -> Option<(usize, usize)> {
+    let kind = |i: usize| tokens.get(i).map(|token| token.kind);
+    if kind(keyword + 1) != Some(Kind::Syntax(TokenKind::Ident)) {
+        return None;
+    }
+
+    let mut open = keyword + 2;
+    if kind(open) == Some(Kind::Syntax(TokenKind::Lt)) {
+        while kind(open)? != Kind::Syntax(TokenKind::Gt) {
+            open += 1;
+        }
+        open += 1;
+    }
+    if kind(open) != Some(Kind::Syntax(TokenKind::Open(Delim::Paren))) {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (close, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            Kind::Syntax(TokenKind::Open(_)) => depth += 1,
+            Kind::Syntax(TokenKind::Close(_)) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((open, close));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
@@ -0,0 +1,157 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{apply, format};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+
+fn check(input: &str, expect: &Expect) {
+    let formatted = apply(input, &format(input));
+    expect.assert_eq(&formatted);
+    assert!(
+        format(&formatted).is_empty(),
+        "formatted source should not change when formatted again"
+    );
+}
+
+#[test]
+fn braces_start_indented_blocks() {
+    check(
+        "namespace Foo{operation Bar():Unit{let x=1;let y=x + 1;}}",
+        &expect![[r#"
+            namespace Foo {
+                operation Bar() : Unit {
+                    let x = 1;
+                    let y = x + 1;
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn opening_brace_moves_to_previous_line() {
+    check(
+        indoc! {"
+            namespace Foo
+            {
+                function Bar() : Int
+                {
+
+
+                    let x = 1;   
+
+
+                    x
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace Foo {
+                function Bar() : Int {
+                    let x = 1;
+
+                    x
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn comments_are_kept_and_indented() {
+    check(
+        indoc! {"
+            namespace Foo { // trailing
+            // leading
+                function Bar() : Unit {
+                      // inside
+                    let x = 1; // after
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace Foo { // trailing
+                // leading
+                function Bar() : Unit {
+                    // inside
+                    let x = 1; // after
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn spaces_within_line_are_normalized() {
+    check(
+        "function Foo ( a:Int,b : Int ) : ( Int , Int ) { return ( a,b ) ; }",
+        &expect![[r#"
+            function Foo(a : Int, b : Int) : (Int, Int) {
+                return (a, b);
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn long_signature_puts_params_on_own_lines() {
+    check(
+        indoc! {"
+            namespace Foo {
+                operation ApplyRotations(qubits : Qubit[], angles : Double[], axis : Pauli, repetitions : Int) : Unit {}
+            }
+        "},
+        &expect![[r#"
+            namespace Foo {
+                operation ApplyRotations(
+                    qubits : Qubit[],
+                    angles : Double[],
+                    axis : Pauli,
+                    repetitions : Int
+                ) : Unit {}
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn interpolated_string_is_unchanged() {
+    check(
+        r#"function Foo() : String { $"a{ 1+2 }b" }"#,
+        &expect![[r#"
+            function Foo() : String {
+                $"a{ 1+2 }b"
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn crlf_line_endings_are_kept() {
+    let input = "namespace Foo {\r\nfunction Bar() : Unit {}\r\n}";
+    let formatted = apply(input, &format(input));
+    assert_eq!(
+        formatted,
+        "namespace Foo {\r\n    function Bar() : Unit {}\r\n}\r\n"
+    );
+}
+
+#[test]
+fn formatted_source_has_no_edits() {
+    let input = indoc! {"
+        namespace Foo {
+            operation Bar() : Unit {
+                let x = 1;
+            }
+        }
+    "};
+    assert!(format(input).is_empty());
+}
+
+#[test]
+fn source_that_does_not_lex_is_not_formatted() {
+    assert!(format("namespace Foo {  let x = \"abc").is_empty());
+}
@@ -10,7 +10,7 @@ mod tests;
 use super::{
     expr::expr,
     keyword::Keyword,
-    prim::{dot_ident, ident, many, opt, pat, seq, token},
+    prim::{dot_ident, ident, many, opt, pat, path, seq, token},
     scan::ParserContext,
     stmt,
     ty::{self, ty},
@@ -24,11 +24,12 @@ use crate::{
     ErrorKind,
 };
 use qsc_ast::ast::{
-    Attr, Block, CallableBody, CallableDecl, CallableKind, Ident, Item, ItemKind, Namespace,
+    Attr, Block, CallableBody, CallableDecl, CallableKind, Expr, Ident, Item, ItemKind, Namespace,
     NodeId, Pat, PatKind, Path, Spec, SpecBody, SpecDecl, SpecGen, StmtKind, TopLevelNode, Ty,
     TyDef, TyDefKind, TyKind, Visibility, VisibilityKind,
 };
-use qsc_data_structures::span::Span;
+use qsc_data_structures::span::{Span, WithSpan};
+use std::sync::Arc;
 
 pub(super) fn parse(s: &mut ParserContext) -> Result<Box<Item>> {
     let lo = s.peek().span.lo;
@@ -37,8 +38,12 @@ pub(super) fn parse(s: &mut ParserContext) -> Result<Box<Item>> {
     let visibility = opt(s, parse_visibility)?;
     let kind = if let Some(open) = opt(s, parse_open)? {
         open
+    } else if let Some(export) = opt(s, parse_export)? {
+        export
     } else if let Some(ty) = opt(s, parse_newtype)? {
         ty
+    } else if let Some(ty) = opt(s, parse_struct)? {
+        ty
     } else if let Some(callable) = opt(s, parse_callable_decl)? {
         Box::new(ItemKind::Callable(callable))
     } else if visibility.is_some() {
@@ -74,7 +79,10 @@ fn parse_many(s: &mut ParserContext) -> Result<Vec<Box<Item>>> {
         TokenKind::At,
         TokenKind::Keyword(Keyword::Internal),
         TokenKind::Keyword(Keyword::Open),
+        TokenKind::Keyword(Keyword::Export),
+        TokenKind::Keyword(Keyword::Namespace),
         TokenKind::Keyword(Keyword::Newtype),
+        TokenKind::Keyword(Keyword::Struct),
         TokenKind::Keyword(Keyword::Operation),
         TokenKind::Keyword(Keyword::Function),
     ];
@@ -101,24 +109,27 @@ fn default(span: Span) -> Box<Item> {
 pub(super) fn parse_namespaces(s: &mut ParserContext) -> Result<Vec<Namespace>> {
     let namespaces = many(s, parse_namespace)?;
     recovering_token(s, TokenKind::Eof);
-    Ok(namespaces)
+    Ok(namespaces.into_iter().flatten().collect())
 }
 
 pub(super) fn parse_top_level_nodes(s: &mut ParserContext) -> Result<Vec<TopLevelNode>> {
     let nodes = many(s, parse_top_level_node)?;
     recovering_token(s, TokenKind::Eof);
-    Ok(nodes)
+    Ok(nodes.into_iter().flatten().collect())
 }
 
-fn parse_top_level_node(s: &mut ParserContext) -> Result<TopLevelNode> {
+fn parse_top_level_node(s: &mut ParserContext) -> Result<Vec<TopLevelNode>> {
     // Here we parse any doc comments ahead of calling `parse_namespace` or `stmt::parse` in order
     // to avoid problems with error reporting. Specifically, if `parse_namespace` consumes the
     // doc comment and then fails to find a namespace, that becomes an unrecoverable error even with
     // opt. This pattern can be dropped along with namespaces once we have a module-based design.
     let doc = parse_doc(s).unwrap_or_default();
-    if let Some(mut namespace) = opt(s, parse_namespace)? {
-        namespace.doc = doc.into();
-        Ok(TopLevelNode::Namespace(namespace))
+    if let Some(mut namespaces) = opt(s, parse_namespace)? {
+        namespaces[0].doc = doc.into();
+        Ok(namespaces
+            .into_iter()
+            .map(TopLevelNode::Namespace)
+            .collect())
     } else {
         let kind = s.peek().kind;
         let span = s.peek().span;
@@ -128,25 +139,47 @@ fn parse_top_level_node(s: &mut ParserContext) -> Result<TopLevelNode> {
         } else if !doc.is_empty() {
             return Err(Error(ErrorKind::Rule("item", kind, span)));
         }
-        Ok(TopLevelNode::Stmt(stmt))
+        Ok(vec![TopLevelNode::Stmt(stmt)])
     }
 }
 
-fn parse_namespace(s: &mut ParserContext) -> Result<Namespace> {
+/// Parses a namespace declaration along with any namespaces nested inside it. Nested namespaces
+/// are flattened into separate declarations that follow their parent and record the name of their
+/// parent, so `namespace A { namespace B {} }` declares both `A` and `A.B`.
+fn parse_namespace(s: &mut ParserContext) -> Result<Vec<Namespace>> {
     let lo = s.peek().span.lo;
     let doc = parse_doc(s).unwrap_or_default();
     token(s, TokenKind::Keyword(Keyword::Namespace))?;
     let name = dot_ident(s)?;
     token(s, TokenKind::Open(Delim::Brace))?;
-    let items = barrier(s, &[TokenKind::Close(Delim::Brace)], parse_many)?;
+    let mut nested = Vec::new();
+    let items = barrier(s, &[TokenKind::Close(Delim::Brace)], |s| {
+        let mut items = parse_many(s)?;
+        while let Some(namespaces) = opt(s, parse_namespace)? {
+            nested.extend(namespaces);
+            items.extend(parse_many(s)?);
+        }
+        Ok(items)
+    })?;
     recovering_token(s, TokenKind::Close(Delim::Brace));
-    Ok(Namespace {
+
+    let parent = Arc::clone(&name.name);
+    let mut namespaces = vec![Namespace {
         id: NodeId::default(),
         span: s.span(lo),
         doc: doc.into(),
         name,
+        parent: None,
         items: items.into_boxed_slice(),
-    })
+    }];
+    namespaces.extend(nested.into_iter().map(|namespace| Namespace {
+        parent: Some(match &namespace.parent {
+            Some(inner) => format!("{parent}.{inner}").into(),
+            None => Arc::clone(&parent),
+        }),
+        ..namespace
+    }));
+    Ok(namespaces)
 }
 
 /// See [GH Issue 941](https://github.com/microsoft/qsharp/issues/941) for context.
@@ -212,6 +245,18 @@ fn parse_open(s: &mut ParserContext) -> Result<Box<ItemKind>> {
     Ok(Box::new(ItemKind::Open(name, alias)))
 }
 
+fn parse_export(s: &mut ParserContext) -> Result<Box<ItemKind>> {
+    token(s, TokenKind::Keyword(Keyword::Export))?;
+    let path = path(s)?;
+    let alias = if token(s, TokenKind::Keyword(Keyword::As)).is_ok() {
+        Some(ident(s)?)
+    } else {
+        None
+    };
+    token(s, TokenKind::Semi)?;
+    Ok(Box::new(ItemKind::Export(path, alias)))
+}
+
 fn parse_newtype(s: &mut ParserContext) -> Result<Box<ItemKind>> {
     token(s, TokenKind::Keyword(Keyword::Newtype))?;
     let name = ident(s)?;
@@ -230,6 +275,40 @@ fn parse_newtype(s: &mut ParserContext) -> Result<Box<ItemKind>> {
     Ok(Box::new(ItemKind::Ty(name, def)))
 }
 
+fn parse_struct(s: &mut ParserContext) -> Result<Box<ItemKind>> {
+    token(s, TokenKind::Keyword(Keyword::Struct))?;
+    let name = ident(s)?;
+    let lo = s.peek().span.lo;
+    token(s, TokenKind::Open(Delim::Brace))?;
+    let (mut fields, _) = seq(s, parse_struct_field)?;
+    token(s, TokenKind::Close(Delim::Brace))?;
+
+    // A struct with a single field wraps the field's type directly, like `newtype A = (B : Int)`.
+    let def = if fields.len() == 1 {
+        fields.pop().expect("struct should have one field")
+    } else {
+        Box::new(TyDef {
+            id: NodeId::default(),
+            span: s.span(lo),
+            kind: Box::new(TyDefKind::Tuple(fields.into_boxed_slice())),
+        })
+    };
+    Ok(Box::new(ItemKind::Ty(name, def)))
+}
+
+fn parse_struct_field(s: &mut ParserContext) -> Result<Box<TyDef>> {
+    throw_away_doc(s);
+    let lo = s.peek().span.lo;
+    let name = ident(s)?;
+    token(s, TokenKind::Colon)?;
+    let field_ty = ty(s)?;
+    Ok(Box::new(TyDef {
+        id: NodeId::default(),
+        span: s.span(lo),
+        kind: Box::new(TyDefKind::Field(Some(name), Box::new(field_ty))),
+    }))
+}
+
 fn try_tydef_as_ty(tydef: &TyDef) -> Option<Ty> {
     match tydef.kind.as_ref() {
         TyDefKind::Field(Some(_), _) | TyDefKind::Err => None,
@@ -279,6 +358,7 @@ fn ty_as_ident(ty: Ty) -> Result<Box<Ident>> {
         return Err(Error(ErrorKind::Convert("identifier", "type", ty.span)));
     };
     if let Path {
+        package: None,
         namespace: None,
         name,
         ..
@@ -316,7 +396,7 @@ fn parse_callable_decl(s: &mut ParserContext) -> Result<Box<CallableDecl>> {
         Vec::new()
     };
 
-    let input = pat(s)?;
+    let (input, defaults) = parse_callable_input(s)?;
     check_input_parens(&input)?;
     token(s, TokenKind::Colon)?;
     throw_away_doc(s);
@@ -336,12 +416,76 @@ fn parse_callable_decl(s: &mut ParserContext) -> Result<Box<CallableDecl>> {
         name,
         generics: generics.into_boxed_slice(),
         input,
+        defaults,
         output: Box::new(output),
         functors,
         body: Box::new(body),
     }))
 }
 
+/// A callable parameter with an optional default value: `a : Int = 1`.
+#[derive(Default)]
+struct InputParam {
+    pat: Box<Pat>,
+    default: Option<Box<Expr>>,
+}
+
+impl WithSpan for InputParam {
+    fn with_span(self, span: Span) -> Self {
+        Self {
+            pat: self.pat.with_span(span),
+            ..self
+        }
+    }
+}
+
+/// Parses the input of a callable, returning the input pattern and the default value of each
+/// parameter. The defaults are empty if no parameter has a default value.
+#[allow(clippy::type_complexity)]
+fn parse_callable_input(s: &mut ParserContext) -> Result<(Box<Pat>, Box<[Option<Box<Expr>>]>)> {
+    throw_away_doc(s);
+    let lo = s.peek().span.lo;
+    if token(s, TokenKind::Open(Delim::Paren)).is_err() {
+        return Ok((pat(s)?, Box::default()));
+    }
+
+    let (params, final_sep) = seq(s, parse_input_param)?;
+    token(s, TokenKind::Close(Delim::Paren))?;
+    let (pats, defaults): (Vec<_>, Vec<_>) = params
+        .into_iter()
+        .map(|param| (param.pat, param.default))
+        .unzip();
+    let defaults = if defaults.iter().all(Option::is_none) {
+        Box::default()
+    } else {
+        defaults.into_boxed_slice()
+    };
+
+    let input = Box::new(Pat {
+        id: NodeId::default(),
+        span: s.span(lo),
+        kind: Box::new(final_sep.reify(pats, PatKind::Paren, PatKind::Tuple)),
+    });
+    Ok((input, defaults))
+}
+
+fn parse_input_param(s: &mut ParserContext) -> Result<InputParam> {
+    let pat = pat(s)?;
+    let default = if token(s, TokenKind::Eq).is_ok() {
+        if !matches!(*pat.kind, PatKind::Bind(..)) {
+            return Err(Error(ErrorKind::Convert(
+                "parameter name",
+                "pattern",
+                pat.span,
+            )));
+        }
+        Some(expr(s)?)
+    } else {
+        None
+    };
+    Ok(InputParam { pat, default })
+}
+
 fn parse_callable_body(s: &mut ParserContext) -> Result<CallableBody> {
     let lo = s.peek().span.lo;
     token(s, TokenKind::Open(Delim::Brace))?;
@@ -134,6 +134,48 @@ fn open_alias_dot() {
     );
 }
 
+#[test]
+fn export_path() {
+    check(
+        parse,
+        "export Foo.Bar.Baz;",
+        &expect![[r#"
+            Item _id_ [0-19]:
+                Export (Path _id_ [7-18] (Ident _id_ [7-14] "Foo.Bar") (Ident _id_ [15-18] "Baz"))"#]],
+    );
+}
+
+#[test]
+fn export_alias() {
+    check(
+        parse,
+        "export Foo.Bar as Baz;",
+        &expect![[r#"
+            Item _id_ [0-22]:
+                Export (Path _id_ [7-14] (Ident _id_ [7-10] "Foo") (Ident _id_ [11-14] "Bar")) (Ident _id_ [18-21] "Baz")"#]],
+    );
+}
+
+#[test]
+fn export_missing_semi() {
+    check(
+        parse,
+        "export Foo.Bar",
+        &expect![[r#"
+            Error(
+                Token(
+                    Semi,
+                    Eof,
+                    Span {
+                        lo: 14,
+                        hi: 14,
+                    },
+                ),
+            )
+        "#]],
+    );
+}
+
 #[test]
 fn ty_decl() {
     check(
@@ -364,6 +406,36 @@ fn ty_def_tuple_both_named() {
     );
 }
 
+#[test]
+fn struct_decl() {
+    check(
+        parse,
+        "struct Foo { X : Int, Y : Int }",
+        &expect![[r#"
+            Item _id_ [0-31]:
+                New Type (Ident _id_ [7-10] "Foo"): TyDef _id_ [11-31]: Tuple:
+                    TyDef _id_ [13-20]: Field:
+                        Ident _id_ [13-14] "X"
+                        Type _id_ [17-20]: Path: Path _id_ [17-20] (Ident _id_ [17-20] "Int")
+                    TyDef _id_ [22-29]: Field:
+                        Ident _id_ [22-23] "Y"
+                        Type _id_ [26-29]: Path: Path _id_ [26-29] (Ident _id_ [26-29] "Int")"#]],
+    );
+}
+
+#[test]
+fn struct_decl_single_field() {
+    check(
+        parse,
+        "struct Foo { X : Int }",
+        &expect![[r#"
+            Item _id_ [0-22]:
+                New Type (Ident _id_ [7-10] "Foo"): TyDef _id_ [13-20]: Field:
+                    Ident _id_ [13-14] "X"
+                    Type _id_ [17-20]: Path: Path _id_ [17-20] (Ident _id_ [17-20] "Int")"#]],
+    );
+}
+
 #[test]
 fn ty_def_nested_tuple() {
     check(
@@ -642,6 +714,31 @@ fn function_two_params() {
     );
 }
 
+#[test]
+fn function_param_with_default() {
+    check(
+        parse,
+        "function Foo(x : Int, y : Int = 1) : Unit { body intrinsic; }",
+        &expect![[r#"
+            Item _id_ [0-61]:
+                Callable _id_ [0-61] (Function):
+                    name: Ident _id_ [9-12] "Foo"
+                    input: Pat _id_ [12-34]: Tuple:
+                        Pat _id_ [13-20]: Bind:
+                            Ident _id_ [13-14] "x"
+                            Type _id_ [17-20]: Path: Path _id_ [17-20] (Ident _id_ [17-20] "Int")
+                        Pat _id_ [22-29]: Bind:
+                            Ident _id_ [22-23] "y"
+                            Type _id_ [26-29]: Path: Path _id_ [26-29] (Ident _id_ [26-29] "Int")
+                    defaults:
+                        <no default>
+                        Expr _id_ [32-33]: Lit: Int(1)
+                    output: Type _id_ [37-41]: Path: Path _id_ [37-41] (Ident _id_ [37-41] "Unit")
+                    body: Specializations:
+                        SpecDecl _id_ [44-59] (Body): Gen: Intrinsic"#]],
+    );
+}
+
 #[test]
 fn function_one_ty_param() {
     check(
@@ -679,6 +776,46 @@ fn function_two_ty_params() {
     );
 }
 
+#[test]
+fn function_ty_param_with_constraint() {
+    check(
+        parse,
+        "function Foo<'T: Eq>() : Unit { body intrinsic; }",
+        &expect![[r#"
+            Item _id_ [0-49]:
+                Callable _id_ [0-49] (Function):
+                    name: Ident _id_ [9-12] "Foo"
+                    generics:
+                        TypeParameter [13-19]: Ident _id_ [13-15] "'T"
+                            Ident _id_ [17-19] "Eq"
+                    input: Pat _id_ [20-22]: Unit
+                    output: Type _id_ [25-29]: Path: Path _id_ [25-29] (Ident _id_ [25-29] "Unit")
+                    body: Specializations:
+                        SpecDecl _id_ [32-47] (Body): Gen: Intrinsic"#]],
+    );
+}
+
+#[test]
+fn function_ty_param_with_multiple_constraints() {
+    check(
+        parse,
+        "function Foo<'T: Eq + Show, 'U>() : Unit { body intrinsic; }",
+        &expect![[r#"
+            Item _id_ [0-60]:
+                Callable _id_ [0-60] (Function):
+                    name: Ident _id_ [9-12] "Foo"
+                    generics:
+                        TypeParameter [13-26]: Ident _id_ [13-15] "'T"
+                            Ident _id_ [17-19] "Eq"
+                            Ident _id_ [22-26] "Show"
+                        Ident _id_ [28-30] "'U"
+                    input: Pat _id_ [31-33]: Unit
+                    output: Type _id_ [36-40]: Path: Path _id_ [36-40] (Ident _id_ [36-40] "Unit")
+                    body: Specializations:
+                        SpecDecl _id_ [43-58] (Body): Gen: Intrinsic"#]],
+    );
+}
+
 #[test]
 fn function_duplicate_comma_in_ty_param() {
     check(
@@ -1090,6 +1227,32 @@ fn namespace_function() {
     );
 }
 
+#[test]
+fn nested_namespace() {
+    check_vec(
+        parse_namespaces,
+        "namespace A { function Foo() : Unit {} namespace B.C { namespace D {} } function Bar() : Unit {} }",
+        &expect![[r#"
+            Namespace _id_ [0-98] (Ident _id_ [10-11] "A"):
+                Item _id_ [14-38]:
+                    Callable _id_ [14-38] (Function):
+                        name: Ident _id_ [23-26] "Foo"
+                        input: Pat _id_ [26-28]: Unit
+                        output: Type _id_ [31-35]: Path: Path _id_ [31-35] (Ident _id_ [31-35] "Unit")
+                        body: Block: Block _id_ [36-38]: <empty>
+                Item _id_ [72-96]:
+                    Callable _id_ [72-96] (Function):
+                        name: Ident _id_ [81-84] "Bar"
+                        input: Pat _id_ [84-86]: Unit
+                        output: Type _id_ [89-93]: Path: Path _id_ [89-93] (Ident _id_ [89-93] "Unit")
+                        body: Block: Block _id_ [94-96]: <empty>,
+            Namespace _id_ [39-71] (Ident _id_ [49-52] "B.C"):
+                parent: A,
+            Namespace _id_ [55-69] (Ident _id_ [65-66] "D"):
+                parent: A.B.C"#]],
+    );
+}
+
 #[test]
 fn namespace_doc() {
     check_vec(
@@ -1559,7 +1722,7 @@ fn disallow_qubit_scoped_block() {
 }
 
 #[test]
-fn reject_nested_namespace_with_items() {
+fn nested_namespace_with_items() {
     check_vec(
         parse_namespaces,
         "namespace Outer {
@@ -1569,41 +1732,23 @@ fn reject_nested_namespace_with_items() {
             }
         }",
         &expect![[r#"
-            Namespace _id_ [0-99] (Ident _id_ [10-15] "Outer"):
-
-            [
-                Error(
-                    Token(
-                        Close(
-                            Brace,
-                        ),
-                        Keyword(
-                            Namespace,
-                        ),
-                        Span {
-                            lo: 30,
-                            hi: 39,
-                        },
-                    ),
-                ),
-                Error(
-                    Token(
-                        Eof,
-                        Keyword(
-                            Newtype,
-                        ),
-                        Span {
-                            lo: 116,
-                            hi: 123,
-                        },
-                    ),
-                ),
-            ]"#]],
+            Namespace _id_ [0-165] (Ident _id_ [10-15] "Outer"):,
+            Namespace _id_ [30-155] (Ident _id_ [40-45] "Inner"):
+                parent: Outer
+                Item _id_ [64-99]:
+                    Callable _id_ [64-99] (Function):
+                        name: Ident _id_ [73-87] "NestedFunction"
+                        input: Pat _id_ [87-89]: Unit
+                        output: Type _id_ [92-96]: Path: Path _id_ [92-96] (Ident _id_ [92-96] "Unit")
+                        body: Block: Block _id_ [97-99]: <empty>
+                Item _id_ [116-141]:
+                    New Type (Ident _id_ [124-134] "NestedType"): TyDef _id_ [137-140]: Field:
+                        Type _id_ [137-140]: Path: Path _id_ [137-140] (Ident _id_ [137-140] "Int")"#]],
     );
 }
 
 #[test]
-fn reject_namespace_with_multiple_nested_levels() {
+fn namespace_with_multiple_nested_levels() {
     check_vec(
         parse_namespaces,
         "namespace LevelOne {
@@ -1614,36 +1759,17 @@ fn reject_namespace_with_multiple_nested_levels() {
             }
         }",
         &expect![[r#"
-            Namespace _id_ [0-146] (Ident _id_ [10-18] "LevelOne"):
-
-            [
-                Error(
-                    Token(
-                        Close(
-                            Brace,
-                        ),
-                        Keyword(
-                            Namespace,
-                        ),
-                        Span {
-                            lo: 33,
-                            hi: 42,
-                        },
-                    ),
-                ),
-                Error(
-                    Token(
-                        Eof,
-                        Close(
-                            Brace,
-                        ),
-                        Span {
-                            lo: 163,
-                            hi: 164,
-                        },
-                    ),
-                ),
-            ]"#]],
+            Namespace _id_ [0-188] (Ident _id_ [10-18] "LevelOne"):,
+            Namespace _id_ [33-178] (Ident _id_ [43-51] "LevelTwo"):
+                parent: LevelOne,
+            Namespace _id_ [70-164] (Ident _id_ [80-90] "LevelThree"):
+                parent: LevelOne.LevelTwo
+                Item _id_ [113-146]:
+                    Callable _id_ [113-146] (Function):
+                        name: Ident _id_ [122-134] "DeepFunction"
+                        input: Pat _id_ [134-136]: Unit
+                        output: Type _id_ [139-143]: Path: Path _id_ [139-143] (Ident _id_ [139-143] "Unit")
+                        body: Block: Block _id_ [144-146]: <empty>"#]],
     );
 }
 
@@ -24,6 +24,7 @@ pub(super) enum Keyword {
     Distribute,
     Elif,
     Else,
+    Export,
     Fail,
     False,
     Fixup,
@@ -36,8 +37,10 @@ pub(super) enum Keyword {
     Invert,
     Is,
     Let,
+    Match,
     Mutable,
     Namespace,
+    New,
     Newtype,
     Not,
     One,
@@ -52,6 +55,7 @@ pub(super) enum Keyword {
     Return,
     Slf,
     Set,
+    Struct,
     True,
     Underscore,
     Until,
@@ -79,6 +83,7 @@ impl Keyword {
             Self::Distribute => "distribute",
             Self::Elif => "elif",
             Self::Else => "else",
+            Self::Export => "export",
             Self::Fail => "fail",
             Self::False => "false",
             Self::Fixup => "fixup",
@@ -91,8 +96,10 @@ impl Keyword {
             Self::Invert => "invert",
             Self::Is => "is",
             Self::Let => "let",
+            Self::Match => "match",
             Self::Mutable => "mutable",
             Self::Namespace => "namespace",
+            Self::New => "new",
             Self::Newtype => "newtype",
             Self::Not => "not",
             Self::One => "One",
@@ -107,6 +114,7 @@ impl Keyword {
             Self::Return => "return",
             Self::Slf => "self",
             Self::Set => "set",
+            Self::Struct => "struct",
             Self::True => "true",
             Self::Underscore => "_",
             Self::Until => "until",
@@ -179,11 +187,15 @@ impl FromStr for Keyword {
             "until" => Ok(Self::Until),
             "repeat" => Ok(Self::Repeat),
             "fixup" => Ok(Self::Fixup),
-            // The next three were not found or measured
+            // The next seven were not found or measured
             // in the standard library for priority order.
             "PauliY" => Ok(Self::PauliY),
             "borrow" => Ok(Self::Borrow),
             "_" => Ok(Self::Underscore),
+            "export" => Ok(Self::Export),
+            "new" => Ok(Self::New),
+            "struct" => Ok(Self::Struct),
+            "match" => Ok(Self::Match),
             _ => Err(()),
         }
     }
@@ -6,6 +6,7 @@
 //! unique identifiers by a later stage.
 
 mod expr;
+pub mod formatter;
 mod item;
 mod keyword;
 mod lex;
@@ -80,30 +80,29 @@ pub(super) fn ident(s: &mut ParserContext) -> Result<Box<Ident>> {
 /// This function parses a [Path] from the given context
 /// and converts it into a single ident, which contains dots (`.`)
 pub(super) fn dot_ident(s: &mut ParserContext) -> Result<Box<Ident>> {
-    let p = path(s)?;
-    let mut name = String::new();
-    if let Some(namespace) = p.namespace {
-        name.push_str(&namespace.name);
-        name.push('.');
-    }
-    name.push_str(&p.name.name);
-
+    let lo = s.peek().span.lo;
+    let parts = dot_parts(s)?;
     Ok(Box::new(Ident {
-        id: p.id,
-        span: p.span,
-        name: name.into(),
+        id: NodeId::default(),
+        span: s.span(lo),
+        name: join(parts.iter().map(|i| &i.name), ".").into(),
     }))
 }
 
 /// A `path` is a dot-separated list of idents like "Foo.Bar.Baz"
 /// this can be either a namespace name (in an open statement or namespace declaration) or
-/// it can be a direct reference to something in a namespace, like `Microsoft.Quantum.Diagnostics.DumpMachine()`
+/// it can be a direct reference to something in a namespace, like `Microsoft.Quantum.Diagnostics.DumpMachine()`.
+/// The path can also name the package that declares the item, like `MyLib::Math.Add`.
 pub(super) fn path(s: &mut ParserContext) -> Result<Box<Path>> {
     let lo = s.peek().span.lo;
-    let mut parts = vec![ident(s)?];
-    while token(s, TokenKind::Dot).is_ok() {
-        parts.push(ident(s)?);
-    }
+    let mut parts = dot_parts(s)?;
+    let package = if parts.len() == 1 && token(s, TokenKind::ColonColon).is_ok() {
+        let package = parts.pop();
+        parts = dot_parts(s)?;
+        package
+    } else {
+        None
+    };
 
     let name = parts.pop().expect("path should have at least one part");
     let namespace = match (parts.first(), parts.last()) {
@@ -122,11 +121,21 @@ pub(super) fn path(s: &mut ParserContext) -> Result<Box<Path>> {
     Ok(Box::new(Path {
         id: NodeId::default(),
         span: s.span(lo),
+        package,
         namespace,
         name,
     }))
 }
 
+#[allow(clippy::vec_box)]
+fn dot_parts(s: &mut ParserContext) -> Result<Vec<Box<Ident>>> {
+    let mut parts = vec![ident(s)?];
+    while token(s, TokenKind::Dot).is_ok() {
+        parts.push(ident(s)?);
+    }
+    Ok(parts)
+}
+
 pub(super) fn pat(s: &mut ParserContext) -> Result<Box<Pat>> {
     throw_away_doc(s);
     let lo = s.peek().span.lo;
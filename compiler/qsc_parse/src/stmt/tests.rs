@@ -333,6 +333,25 @@ fn if_followed_by() {
     );
 }
 
+#[test]
+fn match_followed_by() {
+    check(
+        parse_block,
+        "{ match b { true => x, _ => y } return x; }",
+        &expect![[r#"
+            Block _id_ [0-43]:
+                Stmt _id_ [2-31]: Expr: Expr _id_ [2-31]: Match:
+                    Expr _id_ [8-9]: Path: Path _id_ [8-9] (Ident _id_ [8-9] "b")
+                    MatchArm _id_ [12-21]:
+                        MatchPat _id_ [12-16]: Lit: Bool(true)
+                        Expr _id_ [20-21]: Path: Path _id_ [20-21] (Ident _id_ [20-21] "x")
+                    MatchArm _id_ [23-29]:
+                        MatchPat _id_ [23-24]: Discard
+                        Expr _id_ [28-29]: Path: Path _id_ [28-29] (Ident _id_ [28-29] "y")
+                Stmt _id_ [32-41]: Semi: Expr _id_ [32-40]: Return: Expr _id_ [39-40]: Path: Path _id_ [39-40] (Ident _id_ [39-40] "x")"#]],
+    );
+}
+
 #[test]
 fn let_if() {
     check(
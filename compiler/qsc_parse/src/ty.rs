@@ -6,7 +6,7 @@ mod tests;
 
 use super::{
     keyword::Keyword,
-    prim::{apos_ident, opt, path, seq, token},
+    prim::{apos_ident, ident, opt, path, seq, token},
     scan::ParserContext,
     Error, Parser, Result,
 };
@@ -16,7 +16,7 @@ use crate::{
     ErrorKind,
 };
 use qsc_ast::ast::{
-    CallableKind, Functor, FunctorExpr, FunctorExprKind, Ident, NodeId, SetOp, Ty, TyKind,
+    CallableKind, Functor, FunctorExpr, FunctorExprKind, NodeId, SetOp, Ty, TyKind, TypeParameter,
 };
 
 pub(super) fn ty(s: &mut ParserContext) -> Result<Ty> {
@@ -57,9 +57,23 @@ pub(super) fn array_or_arrow(s: &mut ParserContext<'_>, mut lhs: Ty, lo: u32) ->
     }
 }
 
-pub(super) fn param(s: &mut ParserContext) -> Result<Box<Ident>> {
+pub(super) fn param(s: &mut ParserContext) -> Result<Box<TypeParameter>> {
     throw_away_doc(s);
-    apos_ident(s)
+    let lo = s.peek().span.lo;
+    let ty = apos_ident(s)?;
+    let mut constraints = Vec::new();
+    if token(s, TokenKind::Colon).is_ok() {
+        constraints.push(ident(s)?);
+        while token(s, TokenKind::ClosedBinOp(ClosedBinOp::Plus)).is_ok() {
+            constraints.push(ident(s)?);
+        }
+    }
+
+    Ok(Box::new(TypeParameter {
+        span: s.span(lo),
+        ty: *ty,
+        constraints: constraints.into_boxed_slice(),
+    }))
 }
 
 fn array(s: &mut ParserContext) -> Result<()> {
@@ -87,7 +101,7 @@ fn base(s: &mut ParserContext) -> Result<Ty> {
     let lo = s.peek().span.lo;
     let kind = if token(s, TokenKind::Keyword(Keyword::Underscore)).is_ok() {
         Ok(TyKind::Hole)
-    } else if let Some(name) = opt(s, param)? {
+    } else if let Some(name) = opt(s, apos_ident)? {
         Ok(TyKind::Param(name))
     } else if let Some(path) = opt(s, path)? {
         Ok(TyKind::Path(path))
@@ -118,6 +118,17 @@ fn ty_path() {
     );
 }
 
+#[test]
+fn ty_package_qualified_path() {
+    check(
+        ty,
+        "Lib::Math.Pair",
+        &expect![[
+            r#"Type _id_ [0-14]: Path: Path _id_ [0-14] (Ident _id_ [0-3] "Lib")::(Ident _id_ [5-9] "Math") (Ident _id_ [10-14] "Pair")"#
+        ]],
+    );
+}
+
 #[test]
 fn ty_path2() {
     check(
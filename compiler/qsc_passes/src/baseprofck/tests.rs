@@ -16,7 +16,7 @@ fn check(expr: &str, expect: &Expect) {
     let sources = SourceMap::new([("test".into(), "".into())], Some(expr.into()));
     let unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -0,0 +1,596 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Checks a package against the capabilities of the target it will run on. A value is dynamic if
+//! it depends on a measurement result, so it is only known while the program runs on the target.
+//! Each way of using a dynamic value needs a capability of the target, and each use that needs a
+//! capability the target does not have is reported at the expression where it happens.
+//!
+//! The same analysis also reports the capabilities that each callable in a package needs, so that
+//! library authors can see which targets their callables can run on.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use qsc_hir::{
+    global::Table,
+    hir::{
+        Block, CallableKind, Expr, ExprKind, ItemId, ItemKind, LocalItemId, NodeId, Package, Pat,
+        PatKind, QubitInitKind, Res, SpecBody, Stmt, StmtKind, StringComponent,
+    },
+    ty::{Prim, Ty},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::rc::Rc;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Eq, Error, PartialEq)]
+pub enum Error {
+    #[error("cannot use a dynamic bool value")]
+    #[diagnostic(help(
+        "using a bool value that depends on a measurement result requires the target to support forward branching"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.UseOfDynamicBool"))]
+    UseOfDynamicBool(#[label] Span),
+
+    #[error("cannot use a dynamic integer value")]
+    #[diagnostic(help(
+        "using an integer value that depends on a measurement result requires the target to support integer computations"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.UseOfDynamicInt"))]
+    UseOfDynamicInt(#[label] Span),
+
+    #[error("cannot use a dynamic double value")]
+    #[diagnostic(help(
+        "using a double value that depends on a measurement result requires the target to support floating-point computations"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.UseOfDynamicDouble"))]
+    UseOfDynamicDouble(#[label] Span),
+
+    #[error("cannot use a dynamic value of type `{0}`")]
+    #[diagnostic(help(
+        "using a value of this type that depends on a measurement result requires the target to support higher-level constructs"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.UseOfDynamicValue"))]
+    UseOfDynamicValue(String, #[label] Span),
+
+    #[error("cannot loop a dynamic number of times")]
+    #[diagnostic(help(
+        "a loop whose condition or range depends on a measurement result requires the target to support backwards branching"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.DynamicLoop"))]
+    DynamicLoop(#[label] Span),
+
+    #[error("cannot index with a dynamic value")]
+    #[diagnostic(help(
+        "an index that depends on a measurement result requires the target to support higher-level constructs"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.DynamicIndex"))]
+    DynamicIndex(#[label] Span),
+
+    #[error("cannot call a dynamic callable")]
+    #[diagnostic(help(
+        "calling a callable that depends on a measurement result requires the target to support higher-level constructs"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.DynamicCallee"))]
+    DynamicCallee(#[label] Span),
+
+    #[error("cannot use a dynamic size")]
+    #[diagnostic(help(
+        "creating an array or allocating qubits with a size that depends on a measurement result requires the target to support higher-level constructs"
+    ))]
+    #[diagnostic(code("Qsc.CapabilitiesCk.DynamicSize"))]
+    DynamicSize(#[label] Span),
+}
+
+impl Error {
+    /// The capability that the target needs to support what the error reports.
+    #[must_use]
+    pub fn capability(&self) -> RuntimeCapabilityFlags {
+        match self {
+            Self::UseOfDynamicBool(_) => RuntimeCapabilityFlags::ForwardBranching,
+            Self::UseOfDynamicInt(_) => RuntimeCapabilityFlags::IntegerComputations,
+            Self::UseOfDynamicDouble(_) => RuntimeCapabilityFlags::FloatingPointComputations,
+            Self::DynamicLoop(_) => RuntimeCapabilityFlags::BackwardsBranching,
+            Self::UseOfDynamicValue(..)
+            | Self::DynamicIndex(_)
+            | Self::DynamicCallee(_)
+            | Self::DynamicSize(_) => RuntimeCapabilityFlags::HigherLevelConstructs,
+        }
+    }
+}
+
+/// The capabilities that a callable needs to run on a target.
+#[derive(Clone, Debug)]
+pub struct CallableCapabilities {
+    /// The callable.
+    pub id: LocalItemId,
+    /// The name of the callable, qualified by its namespace.
+    pub name: Rc<str>,
+    /// The capabilities that the callable needs, including those of the callables in the same
+    /// package that it refers to.
+    pub capabilities: RuntimeCapabilityFlags,
+    /// Each use of a dynamic value in the body of the callable that needs a capability, at the
+    /// expression where it happens.
+    pub uses: Vec<Error>,
+}
+
+#[must_use]
+pub fn check_capabilities(
+    core: &Table,
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+) -> Vec<Error> {
+    run_checker(core, package, capabilities)
+        .errors
+        .into_iter()
+        .map(|(_, error)| error)
+        .collect()
+}
+
+/// Reports the capabilities that each callable in the package needs, in the order the callables
+/// are declared. Callables in other packages are assumed to need no capabilities.
+#[must_use]
+pub fn callable_capabilities(core: &Table, package: &Package) -> Vec<CallableCapabilities> {
+    let checker = run_checker(core, package, RuntimeCapabilityFlags::empty());
+    let mut uses: FxHashMap<_, Vec<_>> = FxHashMap::default();
+    for (callable, error) in checker.errors {
+        if let Some(callable) = callable {
+            uses.entry(callable).or_default().push(error);
+        }
+    }
+
+    let mut reports: Vec<_> = package
+        .items
+        .values()
+        .filter_map(|item| {
+            let ItemKind::Callable(decl) = &item.kind else {
+                return None;
+            };
+            let parent = item.parent.and_then(|parent| package.items.get(parent));
+            let name = match parent.map(|parent| &parent.kind) {
+                Some(ItemKind::Namespace(namespace, _)) => {
+                    format!("{}.{}", namespace.name, decl.name.name).into()
+                }
+                _ => decl.name.name.clone(),
+            };
+            let uses = uses.remove(&item.id).unwrap_or_default();
+            Some(CallableCapabilities {
+                id: item.id,
+                name,
+                capabilities: uses.iter().map(Error::capability).collect(),
+                uses,
+            })
+        })
+        .collect();
+
+    // A callable needs the capabilities of every callable it refers to, so propagate them along
+    // the references until nothing changes.
+    loop {
+        let mut changed = false;
+        for i in 0..reports.len() {
+            let referenced = checker
+                .references
+                .get(&reports[i].id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| reports.iter().find(|report| report.id == *id))
+                .fold(RuntimeCapabilityFlags::empty(), |capabilities, report| {
+                    capabilities | report.capabilities
+                });
+            if !reports[i].capabilities.contains(referenced) {
+                reports[i].capabilities |= referenced;
+                changed = true;
+            }
+        }
+        if !changed {
+            return reports;
+        }
+    }
+}
+
+fn run_checker(core: &Table, package: &Package, capabilities: RuntimeCapabilityFlags) -> Checker {
+    let mut checker = Checker {
+        capabilities,
+        length: core
+            .resolve_term("Microsoft.Quantum.Core", "Length")
+            .map(|term| term.id),
+        dynamic: FxHashSet::default(),
+        branches: 0,
+        report: false,
+        callable: None,
+        references: FxHashMap::default(),
+        errors: Vec::new(),
+    };
+
+    // A variable can become dynamic after it is used, like in a loop, so find every dynamic variable
+    // before reporting any errors.
+    loop {
+        let len = checker.dynamic.len();
+        checker.package(package);
+        if checker.dynamic.len() == len {
+            break;
+        }
+    }
+
+    checker.report = true;
+    checker.package(package);
+    checker
+}
+
+struct Checker {
+    capabilities: RuntimeCapabilityFlags,
+    /// The `Length` function, which returns a known length even for an array of dynamic values.
+    length: Option<ItemId>,
+    /// The variables that are dynamic.
+    dynamic: FxHashSet<NodeId>,
+    /// The number of enclosing branches whose condition is dynamic.
+    branches: usize,
+    report: bool,
+    /// The callable whose body is being checked.
+    callable: Option<LocalItemId>,
+    /// The callables in the package that each callable refers to.
+    references: FxHashMap<LocalItemId, FxHashSet<LocalItemId>>,
+    /// Each error with the callable it happens in.
+    errors: Vec<(Option<LocalItemId>, Error)>,
+}
+
+impl Checker {
+    fn package(&mut self, package: &Package) {
+        for item in package.items.values() {
+            if let ItemKind::Callable(decl) = &item.kind {
+                self.callable = Some(item.id);
+                self.bind_params(&decl.input);
+                for spec in [Some(&decl.body), decl.adj.as_ref(), decl.ctl.as_ref()]
+                    .into_iter()
+                    .chain([decl.ctl_adj.as_ref()])
+                    .flatten()
+                {
+                    if let SpecBody::Impl(_, block) = &spec.body {
+                        self.block(block);
+                    }
+                }
+            }
+        }
+
+        self.callable = None;
+        for stmt in &package.stmts {
+            self.stmt(stmt);
+        }
+        if let Some(entry) = &package.entry {
+            self.expr(entry);
+        }
+    }
+
+    /// Returns whether the value of the block is dynamic.
+    fn block(&mut self, block: &Block) -> bool {
+        let mut dynamic = false;
+        for stmt in &block.stmts {
+            dynamic = self.stmt(stmt);
+        }
+        dynamic
+    }
+
+    /// Returns whether the value of the statement is dynamic.
+    fn stmt(&mut self, stmt: &Stmt) -> bool {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => self.expr(expr),
+            StmtKind::Semi(expr) => {
+                self.expr(expr);
+                false
+            }
+            StmtKind::Local(_, pat, value) => {
+                let dynamic = self.expr(value);
+                self.bind(pat, dynamic);
+                false
+            }
+            StmtKind::Qubit(_, _, init, block) => {
+                self.qubit_init(&init.kind);
+                if let Some(block) = block {
+                    self.block(block);
+                }
+                false
+            }
+            StmtKind::Item(_) => false,
+        }
+    }
+
+    fn qubit_init(&mut self, init: &QubitInitKind) {
+        match init {
+            QubitInitKind::Array(size) => {
+                if self.expr(size) {
+                    self.report(Error::DynamicSize(size.span));
+                }
+            }
+            QubitInitKind::Tuple(inits) => {
+                for init in inits {
+                    self.qubit_init(&init.kind);
+                }
+            }
+            QubitInitKind::Single | QubitInitKind::Err => {}
+        }
+    }
+
+    /// Returns whether the value of the expression is dynamic.
+    #[allow(clippy::too_many_lines)]
+    fn expr(&mut self, expr: &Expr) -> bool {
+        let dynamic = match &expr.kind {
+            ExprKind::Array(exprs) | ExprKind::Tuple(exprs) => self.exprs(exprs.iter()),
+            ExprKind::ArrayRepeat(item, size) => {
+                let item = self.expr(item);
+                let dynamic_size = self.expr(size);
+                if dynamic_size {
+                    self.report(Error::DynamicSize(size.span));
+                }
+                item || dynamic_size
+            }
+            ExprKind::Assign(lhs, rhs) => {
+                let dynamic = self.expr(rhs);
+                self.assign(lhs, dynamic);
+                false
+            }
+            ExprKind::AssignOp(_, lhs, rhs) => {
+                let dynamic = self.expr(lhs) | self.expr(rhs);
+                if dynamic {
+                    self.report_value(&lhs.ty, expr.span);
+                }
+                self.assign(lhs, dynamic);
+                false
+            }
+            ExprKind::AssignField(record, _, value) => {
+                let dynamic = self.expr(value);
+                self.assign(record, dynamic);
+                false
+            }
+            ExprKind::AssignIndex(container, index, value) => {
+                if self.expr(index) {
+                    self.report(Error::DynamicIndex(index.span));
+                }
+                let dynamic = self.expr(value);
+                self.assign(container, dynamic);
+                false
+            }
+            ExprKind::BinOp(_, lhs, rhs) => self.expr(lhs) | self.expr(rhs),
+            ExprKind::Block(block) => self.block(block),
+            ExprKind::Call(callee, arg) => {
+                let dynamic_callee = self.expr(callee);
+                if dynamic_callee {
+                    self.report(Error::DynamicCallee(callee.span));
+                }
+                let operation = matches!(
+                    &callee.ty,
+                    Ty::Arrow(arrow) if arrow.kind == CallableKind::Operation
+                );
+                // Results returned by operations, like measurements, are always dynamic.
+                let measured = operation && contains_result(&expr.ty);
+                let length = matches!(
+                    &callee.kind,
+                    ExprKind::Var(Res::Item(id), _) if Some(*id) == self.length
+                );
+                dynamic_callee | (self.expr(arg) && !length) || measured
+            }
+            ExprKind::Closure(captures, _) => captures.iter().any(|id| self.dynamic.contains(id)),
+            ExprKind::Conjugate(within, apply) => {
+                self.block(within);
+                self.block(apply);
+                false
+            }
+            ExprKind::Fail(msg) | ExprKind::Return(msg) => {
+                self.expr(msg);
+                false
+            }
+            ExprKind::Field(record, _) => self.expr(record),
+            ExprKind::For(pat, iter, block) => {
+                let dynamic = self.expr(iter);
+                // Only a dynamic range changes how many times the loop runs, since an array of
+                // dynamic values still has a known length.
+                let dynamic_range = dynamic && iter.ty == Ty::Prim(Prim::Range);
+                if dynamic_range {
+                    self.report(Error::DynamicLoop(iter.span));
+                }
+                self.bind(pat, dynamic);
+                self.branch(dynamic_range, |checker| checker.block(block));
+                false
+            }
+            ExprKind::If(cond, then, otherwise) => {
+                let dynamic = self.expr(cond);
+                self.branch(dynamic, |checker| {
+                    let then = checker.expr(then);
+                    let otherwise = otherwise
+                        .as_ref()
+                        .is_some_and(|otherwise| checker.expr(otherwise));
+                    dynamic || then || otherwise
+                })
+            }
+            ExprKind::Index(container, index) => {
+                let container = self.expr(container);
+                let dynamic_index = self.expr(index);
+                if dynamic_index {
+                    self.report(Error::DynamicIndex(index.span));
+                }
+                container || dynamic_index
+            }
+            ExprKind::Range(start, step, end) => {
+                self.exprs([start, step, end].into_iter().flatten().map(AsRef::as_ref))
+            }
+            ExprKind::Repeat(body, until, fixup) => {
+                self.block(body);
+                let dynamic = self.expr(until);
+                if dynamic {
+                    self.report(Error::DynamicLoop(until.span));
+                }
+                if let Some(fixup) = fixup {
+                    self.branch(dynamic, |checker| checker.block(fixup));
+                }
+                false
+            }
+            ExprKind::String(components) => {
+                components
+                    .iter()
+                    .fold(false, |dynamic, component| match component {
+                        StringComponent::Expr(expr) => self.expr(expr) || dynamic,
+                        StringComponent::Lit(_) => dynamic,
+                    })
+            }
+            ExprKind::UnOp(_, operand) => self.expr(operand),
+            ExprKind::UpdateField(record, _, value) => self.expr(record) | self.expr(value),
+            ExprKind::UpdateIndex(container, index, value) => {
+                let container = self.expr(container);
+                let dynamic_index = self.expr(index);
+                if dynamic_index {
+                    self.report(Error::DynamicIndex(index.span));
+                }
+                container | dynamic_index | self.expr(value)
+            }
+            ExprKind::Var(Res::Local(id), _) => self.dynamic.contains(id),
+            ExprKind::Var(Res::Item(id), _) => {
+                if let (None, Some(callable)) = (id.package, self.callable) {
+                    self.references.entry(callable).or_default().insert(id.item);
+                }
+                false
+            }
+            ExprKind::While(cond, block) => {
+                let dynamic = self.expr(cond);
+                if dynamic {
+                    self.report(Error::DynamicLoop(cond.span));
+                }
+                self.branch(dynamic, |checker| checker.block(block));
+                false
+            }
+            ExprKind::Hole | ExprKind::Lit(_) | ExprKind::Var(..) | ExprKind::Err => false,
+        };
+
+        // Report where a dynamic value is computed, not everywhere that it is used.
+        if dynamic
+            && matches!(
+                expr.kind,
+                ExprKind::ArrayRepeat(..)
+                    | ExprKind::BinOp(..)
+                    | ExprKind::Call(..)
+                    | ExprKind::Field(..)
+                    | ExprKind::If(..)
+                    | ExprKind::Index(..)
+                    | ExprKind::Range(..)
+                    | ExprKind::String(_)
+                    | ExprKind::UnOp(..)
+                    | ExprKind::UpdateField(..)
+                    | ExprKind::UpdateIndex(..)
+            )
+        {
+            self.report_value(&expr.ty, expr.span);
+        }
+
+        dynamic
+    }
+
+    /// Checks each of the expressions, and returns whether any of them is dynamic. Every expression
+    /// is checked, even after a dynamic one, so that the errors in all of them are reported.
+    fn exprs<'a>(&mut self, exprs: impl IntoIterator<Item = &'a Expr>) -> bool {
+        let mut dynamic = false;
+        for expr in exprs {
+            dynamic |= self.expr(expr);
+        }
+        dynamic
+    }
+
+    /// Runs the checker on code that only runs if the condition holds, so variables that it
+    /// assigns are dynamic if the condition is.
+    fn branch<T>(&mut self, dynamic: bool, f: impl FnOnce(&mut Self) -> T) -> T {
+        if dynamic {
+            self.branches += 1;
+        }
+        let value = f(self);
+        if dynamic {
+            self.branches -= 1;
+        }
+        value
+    }
+
+    fn assign(&mut self, lhs: &Expr, dynamic: bool) {
+        if !dynamic && self.branches == 0 {
+            return;
+        }
+
+        match &lhs.kind {
+            ExprKind::Var(Res::Local(id), _) => {
+                self.dynamic.insert(*id);
+            }
+            ExprKind::Tuple(exprs) => {
+                for expr in exprs {
+                    self.assign(expr, true);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn bind(&mut self, pat: &Pat, dynamic: bool) {
+        match &pat.kind {
+            PatKind::Bind(name) if dynamic => {
+                self.dynamic.insert(name.id);
+            }
+            PatKind::Tuple(pats) => {
+                for pat in pats {
+                    self.bind(pat, dynamic);
+                }
+            }
+            PatKind::Bind(_) | PatKind::Discard | PatKind::Err => {}
+        }
+    }
+
+    /// Marks the parameters that hold results as dynamic.
+    fn bind_params(&mut self, pat: &Pat) {
+        match &pat.kind {
+            PatKind::Bind(name) if contains_result(&pat.ty) => {
+                self.dynamic.insert(name.id);
+            }
+            PatKind::Tuple(pats) => {
+                for pat in pats {
+                    self.bind_params(pat);
+                }
+            }
+            PatKind::Bind(_) | PatKind::Discard | PatKind::Err => {}
+        }
+    }
+
+    /// Reports the capabilities that a dynamic value of the type needs.
+    fn report_value(&mut self, ty: &Ty, span: Span) {
+        match ty {
+            Ty::Prim(Prim::Bool) => self.report(Error::UseOfDynamicBool(span)),
+            Ty::Prim(Prim::Int) => self.report(Error::UseOfDynamicInt(span)),
+            Ty::Prim(Prim::Double) => self.report(Error::UseOfDynamicDouble(span)),
+            Ty::Array(item) => self.report_value(item, span),
+            Ty::Tuple(items) => {
+                for item in items {
+                    self.report_value(item, span);
+                }
+            }
+            Ty::Prim(Prim::Result) | Ty::Infer(_) | Ty::Param(..) | Ty::Err => {}
+            Ty::Arrow(_) | Ty::Prim(_) | Ty::Udt(..) => {
+                self.report(Error::UseOfDynamicValue(ty.display(), span));
+            }
+        }
+    }
+
+    fn report(&mut self, error: Error) {
+        let error = (self.callable, error);
+        if self.report
+            && !self.capabilities.contains(error.1.capability())
+            && !self.errors.contains(&error)
+        {
+            self.errors.push(error);
+        }
+    }
+}
+
+fn contains_result(ty: &Ty) -> bool {
+    match ty {
+        Ty::Array(item) => contains_result(item),
+        Ty::Prim(Prim::Result) => true,
+        Ty::Tuple(items) => items.iter().any(contains_result),
+        _ => false,
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{callable_capabilities, check_capabilities, Error};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use miette::Diagnostic;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use std::fmt::Write;
+
+fn compile_unit(source: &str) -> (PackageStore, compile::CompileUnit) {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let unit = compile(
+        &store,
+        &[(std, None)],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    (store, unit)
+}
+
+fn format_error(source: &str, error: &Error) -> String {
+    let code = error.code().expect("error should have a code");
+    let label = error
+        .labels()
+        .and_then(|mut labels| labels.next())
+        .expect("error should have a label");
+    let span = &source[label.offset()..label.offset() + label.len()];
+    format!("{code}: {error} `{span}`")
+}
+
+fn check(source: &str, capabilities: RuntimeCapabilityFlags, expect: &Expect) {
+    let (store, unit) = compile_unit(source);
+    let errors: Vec<_> = check_capabilities(store.core(), &unit.package, capabilities)
+        .iter()
+        .map(|error| format_error(source, error))
+        .collect();
+    expect.assert_eq(&errors.join("\n"));
+}
+
+fn check_callables(source: &str, expect: &Expect) {
+    let (store, unit) = compile_unit(source);
+    let mut report = String::new();
+    for callable in callable_capabilities(store.core(), &unit.package) {
+        let names: Vec<_> = callable
+            .capabilities
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect();
+        let names = if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(" | ")
+        };
+        writeln!(report, "{}: {names}", callable.name).expect("writing to string should succeed");
+        for error in &callable.uses {
+            writeln!(report, "    {}", format_error(source, error))
+                .expect("writing to string should succeed");
+        }
+    }
+    expect.assert_eq(&report);
+}
+
+#[test]
+fn static_values_need_no_capabilities() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Main() : Result {
+                    use q = Qubit();
+                    for i in 0..2 {
+                        X(q);
+                    }
+                    M(q)
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::empty(),
+        &expect![""],
+    );
+}
+
+#[test]
+fn length_of_dynamic_array_is_static() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                operation Main() : Int {
+                    use qs = Qubit[2];
+                    let results = MResetEachZ(qs);
+                    Length(results) + 1
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::empty(),
+        &expect![""],
+    );
+}
+
+#[test]
+fn result_comparison_needs_forward_branching() {
+    let source = indoc! {"
+        namespace Test {
+            operation Main() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+        }
+    "};
+    check(
+        source,
+        RuntimeCapabilityFlags::empty(),
+        &expect![[
+            r#"Qsc.CapabilitiesCk.UseOfDynamicBool: cannot use a dynamic bool value `M(q) == One`"#
+        ]],
+    );
+    check(
+        source,
+        RuntimeCapabilityFlags::ForwardBranching,
+        &expect![""],
+    );
+}
+
+#[test]
+fn result_params_are_dynamic() {
+    check(
+        indoc! {"
+            namespace Test {
+                function IsOne(r : Result) : Bool {
+                    r == One
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::empty(),
+        &expect![[
+            r#"Qsc.CapabilitiesCk.UseOfDynamicBool: cannot use a dynamic bool value `r == One`"#
+        ]],
+    );
+}
+
+#[test]
+fn assignment_in_dynamic_branch_needs_integer_computations() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Main() : Int {
+                    use q = Qubit();
+                    mutable count = 0;
+                    if M(q) == One {
+                        set count += 1;
+                    }
+                    let doubled = count * 2;
+                    doubled
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::ForwardBranching,
+        &expect![[r#"
+            Qsc.CapabilitiesCk.UseOfDynamicInt: cannot use a dynamic integer value `set count += 1`
+            Qsc.CapabilitiesCk.UseOfDynamicInt: cannot use a dynamic integer value `count * 2`"#]],
+    );
+}
+
+#[test]
+fn dynamic_loop_condition_needs_backwards_branching() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    use q = Qubit();
+                    mutable done = false;
+                    while not done {
+                        set done = M(q) == One;
+                    }
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::IntegerComputations,
+        &expect![[
+            r#"Qsc.CapabilitiesCk.DynamicLoop: cannot loop a dynamic number of times `not done`"#
+        ]],
+    );
+}
+
+#[test]
+fn dynamic_index_needs_higher_level_constructs() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    use qs = Qubit[2];
+                    let i = M(qs[0]) == One ? 1 | 0;
+                    X(qs[i]);
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::all().difference(RuntimeCapabilityFlags::HigherLevelConstructs),
+        &expect![[r#"
+            Qsc.CapabilitiesCk.DynamicIndex: cannot index with a dynamic value `i`
+            Qsc.CapabilitiesCk.UseOfDynamicValue: cannot use a dynamic value of type `Qubit` `qs[i]`"#]],
+    );
+}
+
+#[test]
+fn callables_report_their_own_and_referenced_capabilities() {
+    check_callables(
+        indoc! {"
+            namespace Test {
+                operation Flip(r : Result, q : Qubit) : Unit {
+                    if r == One {
+                        X(q);
+                    }
+                }
+
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Flip(M(q), q);
+                }
+
+                function Add(a : Int, b : Int) : Int {
+                    a + b
+                }
+            }
+        "},
+        &expect![[r#"
+            Test.Flip: ForwardBranching
+                Qsc.CapabilitiesCk.UseOfDynamicBool: cannot use a dynamic bool value `r == One`
+            Test.Main: ForwardBranching
+            Test.Add: none
+        "#]],
+    );
+}
+
+#[test]
+fn callables_report_every_capability_they_need() {
+    check_callables(
+        indoc! {"
+            namespace Test {
+                operation Main() : Int {
+                    use q = Qubit();
+                    mutable count = 0;
+                    while M(q) == Zero {
+                        set count += 1;
+                    }
+                    count
+                }
+            }
+        "},
+        &expect![[r#"
+            Test.Main: ForwardBranching | IntegerComputations | BackwardsBranching
+                Qsc.CapabilitiesCk.UseOfDynamicBool: cannot use a dynamic bool value `M(q) == Zero`
+                Qsc.CapabilitiesCk.DynamicLoop: cannot loop a dynamic number of times `M(q) == Zero`
+                Qsc.CapabilitiesCk.UseOfDynamicInt: cannot use a dynamic integer value `set count += 1`
+        "#]],
+    );
+}
@@ -107,9 +107,11 @@ impl<'a> MutVisitor for ConjugateElim<'a> {
 
                 let (bind_id, apply_as_bind) = self.block_as_binding(apply, expr.ty.clone());
 
+                // The generated nodes take the span of the block they come from, or of the whole
+                // conjugate expression, so that they can be stepped through in the debugger.
                 let new_block = Block {
                     id: self.assigner.next_node(),
-                    span: Span::default(),
+                    span: expr.span,
                     ty: expr.ty.clone(),
                     stmts: vec![
                         self.block_as_stmt(within),
@@ -117,10 +119,10 @@ impl<'a> MutVisitor for ConjugateElim<'a> {
                         self.block_as_stmt(adj_within),
                         Stmt {
                             id: self.assigner.next_node(),
-                            span: Span::default(),
+                            span: expr.span,
                             kind: StmtKind::Expr(Expr {
                                 id: self.assigner.next_node(),
-                                span: Span::default(),
+                                span: expr.span,
                                 ty: expr.ty.clone(),
                                 kind: ExprKind::Var(Res::Local(bind_id), Vec::new()),
                             }),
@@ -140,7 +142,7 @@ impl ConjugateElim<'_> {
     fn block_as_expr(&mut self, block: Block, ty: Ty) -> Expr {
         Expr {
             id: self.assigner.next_node(),
-            span: Span::default(),
+            span: block.span,
             ty,
             kind: ExprKind::Block(block),
         }
@@ -149,7 +151,7 @@ impl ConjugateElim<'_> {
     fn block_as_stmt(&mut self, block: Block) -> Stmt {
         Stmt {
             id: self.assigner.next_node(),
-            span: Span::default(),
+            span: block.span,
             kind: StmtKind::Expr(self.block_as_expr(block, Ty::UNIT)),
         }
     }
@@ -159,16 +161,16 @@ impl ConjugateElim<'_> {
             bind_id,
             Stmt {
                 id: self.assigner.next_node(),
-                span: Span::default(),
+                span: block.span,
                 kind: StmtKind::Local(
                     Mutability::Immutable,
                     Pat {
                         id: self.assigner.next_node(),
-                        span: Span::default(),
+                        span: block.span,
                         ty: ty.clone(),
                         kind: PatKind::Bind(Ident {
                             id: bind_id,
-                            span: Span::default(),
+                            span: block.span,
                             name: generated_name("apply_res"),
                         }),
                     },
@@ -6,13 +6,18 @@
 
 use expect_test::{expect, Expect};
 use indoc::indoc;
-use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
 use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
-use qsc_hir::{validate::Validator, visit::Visitor};
+use qsc_hir::{
+    hir::{Package, Stmt},
+    validate::Validator,
+    visit::{self, Visitor},
+};
 
-use crate::conjugate_invert::invert_conjugate_exprs;
+use crate::conjugate_invert::{invert_conjugate_exprs, Error};
 
-fn check(file: &str, expect: &Expect) {
+/// The package compiled from the file after inverting conjugate expressions, and the errors.
+fn invert(file: &str) -> (Package, Vec<Error>) {
     let store = PackageStore::new(compile::core());
     let sources = SourceMap::new([("test".into(), file.into())], None);
     let mut unit = compile(
@@ -26,8 +31,13 @@ fn check(file: &str, expect: &Expect) {
 
     let errors = invert_conjugate_exprs(store.core(), &mut unit.package, &mut unit.assigner);
     Validator::default().visit_package(&unit.package);
+    (unit.package, errors)
+}
+
+fn check(file: &str, expect: &Expect) {
+    let (package, errors) = invert(file);
     if errors.is_empty() {
-        expect.assert_eq(&unit.package.to_string());
+        expect.assert_eq(&package.to_string());
     } else {
         expect.assert_debug_eq(&errors);
     }
@@ -76,24 +86,24 @@ fn conjugate_invert() {
                         functors: empty set
                         body: SpecDecl 9 [63-216]: Impl:
                             Block 10 [84-216] [Type Unit]:
-                                Stmt 11 [94-210]: Expr: Expr 54 [0-0] [Type Unit]: Expr Block: Block 47 [0-0] [Type Unit]:
-                                    Stmt 48 [0-0]: Expr: Expr 49 [0-0] [Type Unit]: Expr Block: Block 13 [101-148] [Type Unit]:
+                                Stmt 11 [94-210]: Expr: Expr 54 [94-210] [Type Unit]: Expr Block: Block 47 [94-210] [Type Unit]:
+                                    Stmt 48 [101-148]: Expr: Expr 49 [101-148] [Type Unit]: Expr Block: Block 13 [101-148] [Type Unit]:
                                         Stmt 14 [115-120]: Semi: Expr 15 [115-119] [Type Unit]: Call:
                                             Expr 16 [115-116] [Type (Int => Unit is Adj)]: Var: Item 1
                                             Expr 17 [117-118] [Type Int]: Lit: Int(1)
                                         Stmt 18 [133-138]: Semi: Expr 19 [133-137] [Type Unit]: Call:
                                             Expr 20 [133-134] [Type (Int => Unit is Adj)]: Var: Item 1
                                             Expr 21 [135-136] [Type Int]: Lit: Int(2)
-                                    Stmt 44 [0-0]: Local (Immutable):
-                                        Pat 45 [0-0] [Type Unit]: Bind: Ident 43 [0-0] "@apply_res"
-                                        Expr 46 [0-0] [Type Unit]: Expr Block: Block 22 [163-210] [Type Unit]:
+                                    Stmt 44 [163-210]: Local (Immutable):
+                                        Pat 45 [163-210] [Type Unit]: Bind: Ident 43 [163-210] "@apply_res"
+                                        Expr 46 [163-210] [Type Unit]: Expr Block: Block 22 [163-210] [Type Unit]:
                                             Stmt 23 [177-182]: Semi: Expr 24 [177-181] [Type Unit]: Call:
                                                 Expr 25 [177-178] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 26 [179-180] [Type Int]: Lit: Int(3)
                                             Stmt 27 [195-200]: Semi: Expr 28 [195-199] [Type Unit]: Call:
                                                 Expr 29 [195-196] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 30 [197-198] [Type Int]: Lit: Int(4)
-                                    Stmt 50 [0-0]: Expr: Expr 51 [0-0] [Type Unit]: Expr Block: Block 32 [101-148] [Type Unit]:
+                                    Stmt 50 [101-148]: Expr: Expr 51 [101-148] [Type Unit]: Expr Block: Block 32 [101-148] [Type Unit]:
                                         Stmt 33 [133-138]: Semi: Expr 34 [133-137] [Type Unit]: Call:
                                             Expr 35 [133-134] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                 Expr 36 [133-134] [Type (Int => Unit is Adj)]: Var: Item 1
@@ -102,7 +112,7 @@ fn conjugate_invert() {
                                             Expr 40 [115-116] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                 Expr 41 [115-116] [Type (Int => Unit is Adj)]: Var: Item 1
                                             Expr 42 [117-118] [Type Int]: Lit: Int(1)
-                                    Stmt 52 [0-0]: Expr: Expr 53 [0-0] [Type Unit]: Var: Local 43
+                                    Stmt 52 [94-210]: Expr: Expr 53 [94-210] [Type Unit]: Var: Local 43
                         adj: <none>
                         ctl: <none>
                         ctl-adj: <none>"#]],
@@ -156,17 +166,17 @@ fn conjugate_invert_with_output() {
                             Block 10 [83-252] [Type Int]:
                                 Stmt 11 [93-234]: Local (Immutable):
                                     Pat 12 [97-100] [Type Int]: Bind: Ident 13 [97-100] "val"
-                                    Expr 60 [0-0] [Type Int]: Expr Block: Block 53 [0-0] [Type Int]:
-                                        Stmt 54 [0-0]: Expr: Expr 55 [0-0] [Type Unit]: Expr Block: Block 15 [110-157] [Type Unit]:
+                                    Expr 60 [103-233] [Type Int]: Expr Block: Block 53 [103-233] [Type Int]:
+                                        Stmt 54 [110-157]: Expr: Expr 55 [110-157] [Type Unit]: Expr Block: Block 15 [110-157] [Type Unit]:
                                             Stmt 16 [124-129]: Semi: Expr 17 [124-128] [Type Unit]: Call:
                                                 Expr 18 [124-125] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 19 [126-127] [Type Int]: Lit: Int(1)
                                             Stmt 20 [142-147]: Semi: Expr 21 [142-146] [Type Unit]: Call:
                                                 Expr 22 [142-143] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 23 [144-145] [Type Int]: Lit: Int(2)
-                                        Stmt 50 [0-0]: Local (Immutable):
-                                            Pat 51 [0-0] [Type Int]: Bind: Ident 49 [0-0] "@apply_res"
-                                            Expr 52 [0-0] [Type Int]: Expr Block: Block 24 [172-233] [Type Int]:
+                                        Stmt 50 [172-233]: Local (Immutable):
+                                            Pat 51 [172-233] [Type Int]: Bind: Ident 49 [172-233] "@apply_res"
+                                            Expr 52 [172-233] [Type Int]: Expr Block: Block 24 [172-233] [Type Int]:
                                                 Stmt 25 [186-191]: Semi: Expr 26 [186-190] [Type Unit]: Call:
                                                     Expr 27 [186-187] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 28 [188-189] [Type Int]: Lit: Int(3)
@@ -174,7 +184,7 @@ fn conjugate_invert_with_output() {
                                                     Expr 31 [204-205] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 32 [206-207] [Type Int]: Lit: Int(4)
                                                 Stmt 33 [222-223]: Expr: Expr 34 [222-223] [Type Int]: Lit: Int(7)
-                                        Stmt 56 [0-0]: Expr: Expr 57 [0-0] [Type Unit]: Expr Block: Block 38 [110-157] [Type Unit]:
+                                        Stmt 56 [110-157]: Expr: Expr 57 [110-157] [Type Unit]: Expr Block: Block 38 [110-157] [Type Unit]:
                                             Stmt 39 [142-147]: Semi: Expr 40 [142-146] [Type Unit]: Call:
                                                 Expr 41 [142-143] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                     Expr 42 [142-143] [Type (Int => Unit is Adj)]: Var: Item 1
@@ -183,7 +193,7 @@ fn conjugate_invert_with_output() {
                                                 Expr 46 [124-125] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                     Expr 47 [124-125] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 48 [126-127] [Type Int]: Lit: Int(1)
-                                        Stmt 58 [0-0]: Expr: Expr 59 [0-0] [Type Int]: Var: Local 49
+                                        Stmt 58 [103-233]: Expr: Expr 59 [103-233] [Type Int]: Var: Local 49
                                 Stmt 35 [243-246]: Expr: Expr 36 [243-246] [Type Int]: Var: Local 13
                         adj: <none>
                         ctl: <none>
@@ -241,29 +251,29 @@ fn nested_conjugate_invert() {
                         functors: empty set
                         body: SpecDecl 9 [63-355]: Impl:
                             Block 10 [84-355] [Type Unit]:
-                                Stmt 11 [94-349]: Expr: Expr 87 [0-0] [Type Unit]: Expr Block: Block 80 [0-0] [Type Unit]:
-                                    Stmt 81 [0-0]: Expr: Expr 82 [0-0] [Type Unit]: Expr Block: Block 13 [101-287] [Type Unit]:
+                                Stmt 11 [94-349]: Expr: Expr 87 [94-349] [Type Unit]: Expr Block: Block 80 [94-349] [Type Unit]:
+                                    Stmt 81 [101-287]: Expr: Expr 82 [101-287] [Type Unit]: Expr Block: Block 13 [101-287] [Type Unit]:
                                         Stmt 14 [115-120]: Semi: Expr 15 [115-119] [Type Unit]: Call:
                                             Expr 16 [115-116] [Type (Int => Unit is Adj)]: Var: Item 1
                                             Expr 17 [117-118] [Type Int]: Lit: Int(0)
-                                        Stmt 18 [133-277]: Expr: Expr 110 [0-0] [Type Unit]: Expr Block: Block 103 [0-0] [Type Unit]:
-                                            Stmt 104 [0-0]: Expr: Expr 105 [0-0] [Type Unit]: Expr Block: Block 20 [140-199] [Type Unit]:
+                                        Stmt 18 [133-277]: Expr: Expr 110 [133-277] [Type Unit]: Expr Block: Block 103 [133-277] [Type Unit]:
+                                            Stmt 104 [140-199]: Expr: Expr 105 [140-199] [Type Unit]: Expr Block: Block 20 [140-199] [Type Unit]:
                                                 Stmt 21 [158-163]: Semi: Expr 22 [158-162] [Type Unit]: Call:
                                                     Expr 23 [158-159] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 24 [160-161] [Type Int]: Lit: Int(1)
                                                 Stmt 25 [180-185]: Semi: Expr 26 [180-184] [Type Unit]: Call:
                                                     Expr 27 [180-181] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 28 [182-183] [Type Int]: Lit: Int(2)
-                                            Stmt 100 [0-0]: Local (Immutable):
-                                                Pat 101 [0-0] [Type Unit]: Bind: Ident 99 [0-0] "@apply_res"
-                                                Expr 102 [0-0] [Type Unit]: Expr Block: Block 29 [218-277] [Type Unit]:
+                                            Stmt 100 [218-277]: Local (Immutable):
+                                                Pat 101 [218-277] [Type Unit]: Bind: Ident 99 [218-277] "@apply_res"
+                                                Expr 102 [218-277] [Type Unit]: Expr Block: Block 29 [218-277] [Type Unit]:
                                                     Stmt 30 [236-241]: Semi: Expr 31 [236-240] [Type Unit]: Call:
                                                         Expr 32 [236-237] [Type (Int => Unit is Adj)]: Var: Item 1
                                                         Expr 33 [238-239] [Type Int]: Lit: Int(3)
                                                     Stmt 34 [258-263]: Semi: Expr 35 [258-262] [Type Unit]: Call:
                                                         Expr 36 [258-259] [Type (Int => Unit is Adj)]: Var: Item 1
                                                         Expr 37 [260-261] [Type Int]: Lit: Int(4)
-                                            Stmt 106 [0-0]: Expr: Expr 107 [0-0] [Type Unit]: Expr Block: Block 88 [140-199] [Type Unit]:
+                                            Stmt 106 [140-199]: Expr: Expr 107 [140-199] [Type Unit]: Expr Block: Block 88 [140-199] [Type Unit]:
                                                 Stmt 89 [180-185]: Semi: Expr 90 [180-184] [Type Unit]: Call:
                                                     Expr 91 [180-181] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                         Expr 92 [180-181] [Type (Int => Unit is Adj)]: Var: Item 1
@@ -272,28 +282,28 @@ fn nested_conjugate_invert() {
                                                     Expr 96 [158-159] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                         Expr 97 [158-159] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 98 [160-161] [Type Int]: Lit: Int(1)
-                                            Stmt 108 [0-0]: Expr: Expr 109 [0-0] [Type Unit]: Var: Local 99
-                                    Stmt 77 [0-0]: Local (Immutable):
-                                        Pat 78 [0-0] [Type Unit]: Bind: Ident 76 [0-0] "@apply_res"
-                                        Expr 79 [0-0] [Type Unit]: Expr Block: Block 38 [302-349] [Type Unit]:
+                                            Stmt 108 [133-277]: Expr: Expr 109 [133-277] [Type Unit]: Var: Local 99
+                                    Stmt 77 [302-349]: Local (Immutable):
+                                        Pat 78 [302-349] [Type Unit]: Bind: Ident 76 [302-349] "@apply_res"
+                                        Expr 79 [302-349] [Type Unit]: Expr Block: Block 38 [302-349] [Type Unit]:
                                             Stmt 39 [316-321]: Semi: Expr 40 [316-320] [Type Unit]: Call:
                                                 Expr 41 [316-317] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 42 [318-319] [Type Int]: Lit: Int(5)
                                             Stmt 43 [334-339]: Semi: Expr 44 [334-338] [Type Unit]: Call:
                                                 Expr 45 [334-335] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 46 [336-337] [Type Int]: Lit: Int(6)
-                                    Stmt 83 [0-0]: Expr: Expr 84 [0-0] [Type Unit]: Expr Block: Block 48 [101-287] [Type Unit]:
-                                        Stmt 49 [133-277]: Expr: Expr 133 [0-0] [Type Unit]: Expr Block: Block 126 [0-0] [Type Unit]:
-                                            Stmt 127 [0-0]: Expr: Expr 128 [0-0] [Type Unit]: Expr Block: Block 51 [140-199] [Type Unit]:
+                                    Stmt 83 [101-287]: Expr: Expr 84 [101-287] [Type Unit]: Expr Block: Block 48 [101-287] [Type Unit]:
+                                        Stmt 49 [133-277]: Expr: Expr 133 [133-277] [Type Unit]: Expr Block: Block 126 [133-277] [Type Unit]:
+                                            Stmt 127 [140-199]: Expr: Expr 128 [140-199] [Type Unit]: Expr Block: Block 51 [140-199] [Type Unit]:
                                                 Stmt 52 [158-163]: Semi: Expr 53 [158-162] [Type Unit]: Call:
                                                     Expr 54 [158-159] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 55 [160-161] [Type Int]: Lit: Int(1)
                                                 Stmt 56 [180-185]: Semi: Expr 57 [180-184] [Type Unit]: Call:
                                                     Expr 58 [180-181] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 59 [182-183] [Type Int]: Lit: Int(2)
-                                            Stmt 123 [0-0]: Local (Immutable):
-                                                Pat 124 [0-0] [Type Unit]: Bind: Ident 122 [0-0] "@apply_res"
-                                                Expr 125 [0-0] [Type Unit]: Expr Block: Block 60 [218-277] [Type Unit]:
+                                            Stmt 123 [218-277]: Local (Immutable):
+                                                Pat 124 [218-277] [Type Unit]: Bind: Ident 122 [218-277] "@apply_res"
+                                                Expr 125 [218-277] [Type Unit]: Expr Block: Block 60 [218-277] [Type Unit]:
                                                     Stmt 61 [258-263]: Semi: Expr 62 [258-262] [Type Unit]: Call:
                                                         Expr 63 [258-259] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                             Expr 64 [258-259] [Type (Int => Unit is Adj)]: Var: Item 1
@@ -302,7 +312,7 @@ fn nested_conjugate_invert() {
                                                         Expr 68 [236-237] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                             Expr 69 [236-237] [Type (Int => Unit is Adj)]: Var: Item 1
                                                         Expr 70 [238-239] [Type Int]: Lit: Int(3)
-                                            Stmt 129 [0-0]: Expr: Expr 130 [0-0] [Type Unit]: Expr Block: Block 111 [140-199] [Type Unit]:
+                                            Stmt 129 [140-199]: Expr: Expr 130 [140-199] [Type Unit]: Expr Block: Block 111 [140-199] [Type Unit]:
                                                 Stmt 112 [180-185]: Semi: Expr 113 [180-184] [Type Unit]: Call:
                                                     Expr 114 [180-181] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                         Expr 115 [180-181] [Type (Int => Unit is Adj)]: Var: Item 1
@@ -311,12 +321,12 @@ fn nested_conjugate_invert() {
                                                     Expr 119 [158-159] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                         Expr 120 [158-159] [Type (Int => Unit is Adj)]: Var: Item 1
                                                     Expr 121 [160-161] [Type Int]: Lit: Int(1)
-                                            Stmt 131 [0-0]: Expr: Expr 132 [0-0] [Type Unit]: Var: Local 122
+                                            Stmt 131 [133-277]: Expr: Expr 132 [133-277] [Type Unit]: Var: Local 122
                                         Stmt 71 [115-120]: Semi: Expr 72 [115-119] [Type Unit]: Call:
                                             Expr 73 [115-116] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                 Expr 74 [115-116] [Type (Int => Unit is Adj)]: Var: Item 1
                                             Expr 75 [117-118] [Type Int]: Lit: Int(0)
-                                    Stmt 85 [0-0]: Expr: Expr 86 [0-0] [Type Unit]: Var: Local 76
+                                    Stmt 85 [94-349]: Expr: Expr 86 [94-349] [Type Unit]: Var: Local 76
                         adj: <none>
                         ctl: <none>
                         ctl-adj: <none>"#]],
@@ -513,8 +523,8 @@ fn conjugate_mutable_correct_use_succeeds() {
                                 Stmt 11 [94-108]: Local (Mutable):
                                     Pat 12 [102-103] [Type Int]: Bind: Ident 13 [102-103] "a"
                                     Expr 14 [106-107] [Type Int]: Lit: Int(1)
-                                Stmt 15 [117-329]: Expr: Expr 82 [0-0] [Type Unit]: Expr Block: Block 75 [0-0] [Type Unit]:
-                                    Stmt 76 [0-0]: Expr: Expr 77 [0-0] [Type Unit]: Expr Block: Block 17 [124-217] [Type Unit]:
+                                Stmt 15 [117-329]: Expr: Expr 82 [117-329] [Type Unit]: Expr Block: Block 75 [117-329] [Type Unit]:
+                                    Stmt 76 [124-217]: Expr: Expr 77 [124-217] [Type Unit]: Expr Block: Block 17 [124-217] [Type Unit]:
                                         Stmt 18 [138-148]: Local (Immutable):
                                             Pat 19 [142-143] [Type Int]: Bind: Ident 20 [142-143] "x"
                                             Expr 21 [146-147] [Type Int]: Var: Local 13
@@ -527,9 +537,9 @@ fn conjugate_mutable_correct_use_succeeds() {
                                         Stmt 30 [197-207]: Local (Immutable):
                                             Pat 31 [201-202] [Type Int]: Bind: Ident 32 [201-202] "y"
                                             Expr 33 [205-206] [Type Int]: Var: Local 20
-                                    Stmt 72 [0-0]: Local (Immutable):
-                                        Pat 73 [0-0] [Type Unit]: Bind: Ident 71 [0-0] "@apply_res"
-                                        Expr 74 [0-0] [Type Unit]: Expr Block: Block 34 [232-329] [Type Unit]:
+                                    Stmt 72 [232-329]: Local (Immutable):
+                                        Pat 73 [232-329] [Type Unit]: Bind: Ident 71 [232-329] "@apply_res"
+                                        Expr 74 [232-329] [Type Unit]: Expr Block: Block 34 [232-329] [Type Unit]:
                                             Stmt 35 [246-260]: Local (Mutable):
                                                 Pat 36 [254-255] [Type Int]: Bind: Ident 37 [254-255] "b"
                                                 Expr 38 [258-259] [Type Int]: Var: Local 13
@@ -542,7 +552,7 @@ fn conjugate_mutable_correct_use_succeeds() {
                                             Stmt 47 [314-319]: Semi: Expr 48 [314-318] [Type Unit]: Call:
                                                 Expr 49 [314-315] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 50 [316-317] [Type Int]: Lit: Int(4)
-                                    Stmt 78 [0-0]: Expr: Expr 79 [0-0] [Type Unit]: Expr Block: Block 52 [124-217] [Type Unit]:
+                                    Stmt 78 [124-217]: Expr: Expr 79 [124-217] [Type Unit]: Expr Block: Block 52 [124-217] [Type Unit]:
                                         Stmt 53 [138-148]: Local (Immutable):
                                             Pat 54 [142-143] [Type Int]: Bind: Ident 55 [142-143] "x"
                                             Expr 56 [146-147] [Type Int]: Var: Local 13
@@ -557,9 +567,49 @@ fn conjugate_mutable_correct_use_succeeds() {
                                             Expr 68 [161-162] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                 Expr 69 [161-162] [Type (Int => Unit is Adj)]: Var: Item 1
                                             Expr 70 [163-164] [Type Int]: Lit: Int(1)
-                                    Stmt 80 [0-0]: Expr: Expr 81 [0-0] [Type Unit]: Var: Local 71
+                                    Stmt 80 [117-329]: Expr: Expr 81 [117-329] [Type Unit]: Var: Local 71
                         adj: <none>
                         ctl: <none>
                         ctl-adj: <none>"#]],
     );
 }
+
+/// Fails on any statement without a span, which the debugger would skip as generated code.
+struct StmtSpanCheck;
+
+impl<'a> Visitor<'a> for StmtSpanCheck {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        assert_ne!(
+            stmt.span,
+            Span::default(),
+            "statement should have a span: {stmt}"
+        );
+        visit::walk_stmt(self, stmt);
+    }
+}
+
+#[test]
+fn generated_stmts_have_spans() {
+    let (package, errors) = invert(indoc! {"
+        namespace Test {
+            operation B(i : Int) : Unit is Adj {}
+            operation A() : Int {
+                let val = within {
+                    for i in 0..2 {
+                        B(i);
+                    }
+                    for j in [3, 4] {
+                        B(j);
+                    }
+                }
+                apply {
+                    B(5);
+                    6
+                };
+                val
+            }
+        }
+    "});
+    assert!(errors.is_empty(), "{errors:?}");
+    StmtSpanCheck.visit_package(&package);
+}
@@ -0,0 +1,200 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Folds expressions whose operands are constants into the constant they evaluate to, and replaces
+//! each use of an immutable variable bound to a constant with the constant. An expression is only
+//! folded when evaluating it cannot fail, so dividing by zero is still a runtime error.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_hir::{
+    hir::{
+        BinOp, Expr, ExprKind, Lit, Mutability, NodeId, PatKind, Res, Stmt, StmtKind,
+        StringComponent, UnOp,
+    },
+    mut_visit::{walk_expr, walk_stmt, MutVisitor},
+};
+use rustc_hash::FxHashMap;
+use std::mem::take;
+
+#[derive(Default)]
+pub(crate) struct ConstFold {
+    /// The constant value of each immutable variable that is bound to one.
+    values: FxHashMap<NodeId, ExprKind>,
+}
+
+impl MutVisitor for ConstFold {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt);
+
+        if let StmtKind::Local(Mutability::Immutable, pat, value) = &stmt.kind {
+            if let PatKind::Bind(name) = &pat.kind {
+                if is_const(&value.kind) {
+                    self.values.insert(name.id, value.kind.clone());
+                }
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+
+        let folded = match &mut expr.kind {
+            ExprKind::BinOp(op, lhs, rhs) => match short_circuit(*op, lhs) {
+                // The operand replaces the whole expression, so that its span is the one reported
+                // if it fails and its type is the one the evaluator sees.
+                Some(Operand::Lhs) => {
+                    *expr = take(&mut **lhs);
+                    return;
+                }
+                Some(Operand::Rhs) => {
+                    *expr = take(&mut **rhs);
+                    return;
+                }
+                None => fold_bin_op(*op, lhs, rhs),
+            },
+            ExprKind::UnOp(op, operand) => match &operand.kind {
+                ExprKind::Lit(lit) => fold_un_op(*op, lit).map(ExprKind::Lit),
+                _ => None,
+            },
+            ExprKind::If(cond, body, otherwise) => match cond.kind {
+                ExprKind::Lit(Lit::Bool(true)) => {
+                    *expr = take(&mut **body);
+                    return;
+                }
+                ExprKind::Lit(Lit::Bool(false)) => match otherwise.take() {
+                    Some(otherwise) => {
+                        *expr = *otherwise;
+                        return;
+                    }
+                    None => Some(ExprKind::Tuple(Vec::new())),
+                },
+                _ => None,
+            },
+            ExprKind::Var(Res::Local(id), _) => self.values.get(id).cloned(),
+            _ => None,
+        };
+
+        if let Some(folded) = folded {
+            expr.kind = folded;
+        }
+    }
+}
+
+fn is_const(kind: &ExprKind) -> bool {
+    match kind {
+        ExprKind::Lit(_) => true,
+        ExprKind::String(components) => lit_string(components).is_some(),
+        _ => false,
+    }
+}
+
+/// The string that the components make up if they are all literals.
+fn lit_string(components: &[StringComponent]) -> Option<String> {
+    components
+        .iter()
+        .map(|component| match component {
+            StringComponent::Expr(_) => None,
+            StringComponent::Lit(lit) => Some(lit.as_ref()),
+        })
+        .collect()
+}
+
+/// The operand of a binary operator that the whole expression evaluates to.
+enum Operand {
+    Lhs,
+    Rhs,
+}
+
+/// The operand that a short-circuiting operator evaluates to when its left operand is a constant.
+/// The right operand is not evaluated when the left operand decides the result, so it can be
+/// dropped.
+fn short_circuit(op: BinOp, lhs: &Expr) -> Option<Operand> {
+    match (op, &lhs.kind) {
+        (BinOp::AndL, ExprKind::Lit(Lit::Bool(false)))
+        | (BinOp::OrL, ExprKind::Lit(Lit::Bool(true))) => Some(Operand::Lhs),
+        (BinOp::AndL, ExprKind::Lit(Lit::Bool(true)))
+        | (BinOp::OrL, ExprKind::Lit(Lit::Bool(false))) => Some(Operand::Rhs),
+        _ => None,
+    }
+}
+
+fn fold_bin_op(op: BinOp, lhs: &Expr, rhs: &Expr) -> Option<ExprKind> {
+    match (op, &lhs.kind, &rhs.kind) {
+        (_, ExprKind::Lit(lhs), ExprKind::Lit(rhs)) => fold_lits(op, lhs, rhs).map(ExprKind::Lit),
+        (BinOp::Add, ExprKind::String(lhs), ExprKind::String(rhs)) => {
+            let value = lit_string(lhs)? + &lit_string(rhs)?;
+            Some(ExprKind::String(vec![StringComponent::Lit(value.into())]))
+        }
+        _ => None,
+    }
+}
+
+fn fold_lits(op: BinOp, lhs: &Lit, rhs: &Lit) -> Option<Lit> {
+    match (lhs, rhs) {
+        (Lit::Int(lhs), Lit::Int(rhs)) => fold_ints(op, *lhs, *rhs),
+        (Lit::Double(lhs), Lit::Double(rhs)) => fold_doubles(op, *lhs, *rhs),
+        (Lit::Bool(lhs), Lit::Bool(rhs)) => fold_eq(op, lhs, rhs),
+        (Lit::Pauli(lhs), Lit::Pauli(rhs)) => fold_eq(op, lhs, rhs),
+        (Lit::Result(lhs), Lit::Result(rhs)) => fold_eq(op, lhs, rhs),
+        _ => None,
+    }
+}
+
+fn fold_eq<T: PartialEq>(op: BinOp, lhs: &T, rhs: &T) -> Option<Lit> {
+    match op {
+        BinOp::Eq => Some(Lit::Bool(lhs == rhs)),
+        BinOp::Neq => Some(Lit::Bool(lhs != rhs)),
+        _ => None,
+    }
+}
+
+fn fold_ints(op: BinOp, lhs: i64, rhs: i64) -> Option<Lit> {
+    let value = match op {
+        BinOp::Add => lhs.wrapping_add(rhs),
+        BinOp::AndB => lhs & rhs,
+        BinOp::Div if rhs != 0 => lhs.wrapping_div(rhs),
+        BinOp::Exp => lhs.checked_pow(u32::try_from(rhs).ok()?)?,
+        BinOp::Mod if rhs != 0 => lhs.wrapping_rem(rhs),
+        BinOp::Mul => lhs.wrapping_mul(rhs),
+        BinOp::OrB => lhs | rhs,
+        BinOp::Sub => lhs.wrapping_sub(rhs),
+        BinOp::XorB => lhs ^ rhs,
+        BinOp::Gt => return Some(Lit::Bool(lhs > rhs)),
+        BinOp::Gte => return Some(Lit::Bool(lhs >= rhs)),
+        BinOp::Lt => return Some(Lit::Bool(lhs < rhs)),
+        BinOp::Lte => return Some(Lit::Bool(lhs <= rhs)),
+        _ => return fold_eq(op, &lhs, &rhs),
+    };
+    Some(Lit::Int(value))
+}
+
+#[allow(clippy::float_cmp)]
+fn fold_doubles(op: BinOp, lhs: f64, rhs: f64) -> Option<Lit> {
+    let value = match op {
+        BinOp::Add => lhs + rhs,
+        BinOp::Div => lhs / rhs,
+        BinOp::Exp => lhs.powf(rhs),
+        BinOp::Mod if rhs != 0.0 => lhs % rhs,
+        BinOp::Mul => lhs * rhs,
+        BinOp::Sub => lhs - rhs,
+        BinOp::Gt => return Some(Lit::Bool(lhs > rhs)),
+        BinOp::Gte => return Some(Lit::Bool(lhs >= rhs)),
+        BinOp::Lt => return Some(Lit::Bool(lhs < rhs)),
+        BinOp::Lte => return Some(Lit::Bool(lhs <= rhs)),
+        _ => return fold_eq(op, &lhs, &rhs),
+    };
+    Some(Lit::Double(value))
+}
+
+fn fold_un_op(op: UnOp, operand: &Lit) -> Option<Lit> {
+    match (op, operand) {
+        (UnOp::Neg, Lit::Int(value)) => Some(Lit::Int(value.wrapping_neg())),
+        (UnOp::Neg, Lit::Double(value)) => Some(Lit::Double(-value)),
+        (UnOp::NotB, Lit::Int(value)) => Some(Lit::Int(!value)),
+        (UnOp::NotL, Lit::Bool(value)) => Some(Lit::Bool(!value)),
+        (UnOp::Pos, Lit::Int(_) | Lit::Double(_)) => Some(operand.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::{
+    hir::{BinOp, Expr, ExprKind, Lit, Res, StmtKind, StringComponent},
+    mut_visit::MutVisitor,
+    ty::{Prim, Ty},
+    validate::Validator,
+    visit::Visitor,
+};
+
+use crate::const_fold::ConstFold;
+
+/// The entry expression after folding.
+fn fold(expr: &str) -> Expr {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([], Some(expr.into()));
+    let mut unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    ConstFold::default().visit_package(&mut unit.package);
+    Validator::default().visit_package(&unit.package);
+    unit.package
+        .entry
+        .expect("package should have an entry expression")
+}
+
+/// The value of a block, or the expression itself if it is not a block.
+fn value(expr: &Expr) -> &ExprKind {
+    match &expr.kind {
+        ExprKind::Block(block) => {
+            match &block.stmts.last().expect("block should not be empty").kind {
+                StmtKind::Expr(expr) => value(expr),
+                kind => panic!("block should end with an expression, found {kind}"),
+            }
+        }
+        kind => kind,
+    }
+}
+
+#[test]
+fn int_arithmetic() {
+    assert_eq!(fold("1 + 2 * 3 - 4 / 2").kind, ExprKind::Lit(Lit::Int(5)));
+}
+
+#[test]
+fn int_arithmetic_wraps() {
+    assert_eq!(
+        fold("9223372036854775807 + 1").kind,
+        ExprKind::Lit(Lit::Int(i64::MIN))
+    );
+}
+
+#[test]
+fn double_arithmetic() {
+    assert_eq!(
+        fold("1.5 * 2.0 + -0.5").kind,
+        ExprKind::Lit(Lit::Double(2.5))
+    );
+}
+
+#[test]
+fn division_by_zero_is_not_folded() {
+    assert!(matches!(
+        fold("1 / 0").kind,
+        ExprKind::BinOp(BinOp::Div, ..)
+    ));
+}
+
+#[test]
+fn int_overflow_in_exp_is_not_folded() {
+    assert!(matches!(
+        fold("2 ^ 64").kind,
+        ExprKind::BinOp(BinOp::Exp, ..)
+    ));
+}
+
+#[test]
+fn bool_expressions() {
+    assert_eq!(
+        fold("not (1 < 2) or 3 == 3").kind,
+        ExprKind::Lit(Lit::Bool(true))
+    );
+}
+
+#[test]
+fn short_circuit_drops_unevaluated_operand() {
+    assert_eq!(
+        value(&fold("{ function F() : Bool { true } false and F() }")),
+        &ExprKind::Lit(Lit::Bool(false))
+    );
+    assert!(matches!(
+        value(&fold("{ function F() : Bool { true } true and F() }")),
+        ExprKind::Call(..)
+    ));
+}
+
+#[test]
+fn short_circuit_keeps_span_and_type_of_operand() {
+    let expr = fold(r#"true and (fail "x")"#);
+    assert!(matches!(expr.kind, ExprKind::Fail(_)));
+    assert_eq!(expr.span, Span { lo: 10, hi: 18 });
+    assert_eq!(expr.ty, Ty::Prim(Prim::Bool));
+}
+
+#[test]
+fn string_concatenation() {
+    assert_eq!(
+        fold(r#""a" + "b" + "c""#).kind,
+        ExprKind::String(vec![StringComponent::Lit("abc".into())])
+    );
+}
+
+#[test]
+fn immutable_constant_is_propagated() {
+    assert_eq!(
+        value(&fold("{ let x = 2; let y = x * 3; y + 1 }")),
+        &ExprKind::Lit(Lit::Int(7))
+    );
+}
+
+#[test]
+fn mutable_variable_is_not_propagated() {
+    assert!(matches!(
+        value(&fold("{ mutable x = 2; set x = 3; x }")),
+        ExprKind::Var(Res::Local(_), _)
+    ));
+}
+
+#[test]
+fn if_with_constant_condition_is_replaced_by_branch() {
+    assert_eq!(
+        value(&fold("if 1 < 2 { 3 } else { 4 }")),
+        &ExprKind::Lit(Lit::Int(3))
+    );
+    assert_eq!(
+        value(&fold("if 1 > 2 { 3 } else { 4 }")),
+        &ExprKind::Lit(Lit::Int(4))
+    );
+}
@@ -25,10 +25,6 @@ pub enum Error {
     #[diagnostic(code("Qsc.EntryPoint.Duplicate"))]
     Duplicate(String, #[label] Span),
 
-    #[error("entry point cannot have parameters")]
-    #[diagnostic(code("Qsc.EntryPoint.Args"))]
-    Args(#[label] Span),
-
     #[error("entry point must have body implementation only")]
     #[diagnostic(code("Qsc.EntryPoint.BodyMissing"))]
     BodyMissing(#[label("cannot have specialization implementation")] Span),
@@ -40,7 +36,8 @@ pub enum Error {
 }
 
 // If no entry expression is provided, generate one from the entry point callable.
-// Only one callable should be annotated with the entry point attribute.
+// Only one callable should be annotated with the entry point attribute. An entry point with
+// parameters gets no entry expression, since its arguments are given by the host when it runs.
 pub(super) fn generate_entry_expr(
     package: &mut Package,
     assigner: &mut Assigner,
@@ -52,7 +49,7 @@ pub(super) fn generate_entry_expr(
 
     match create_entry_from_callables(assigner, callables) {
         Ok(expr) => {
-            package.entry = Some(expr);
+            package.entry = expr;
             vec![]
         }
         Err(errs) => errs,
@@ -62,7 +59,7 @@ pub(super) fn generate_entry_expr(
 fn create_entry_from_callables(
     assigner: &mut Assigner,
     callables: Vec<(&CallableDecl, LocalItemId)>,
-) -> Result<Expr, Vec<super::Error>> {
+) -> Result<Option<Expr>, Vec<super::Error>> {
     if callables.len() == 1 {
         let ep = callables[0].0;
         let arg_count = if let PatKind::Tuple(args) = &ep.input.kind {
@@ -102,12 +99,12 @@ fn create_entry_from_callables(
                             ty: block.ty.clone(),
                             kind: ExprKind::Call(Box::new(callee), Box::new(arg)),
                         };
-                        Ok(call)
+                        Ok(Some(call))
                     }
                 }
             }
         } else {
-            Err(vec![PassErr::EntryPoint(Error::Args(ep.input.span))])
+            Ok(None)
         }
     } else if callables.is_empty() {
         Err(vec![PassErr::EntryPoint(Error::NotFound)])
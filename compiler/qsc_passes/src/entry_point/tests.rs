@@ -104,3 +104,31 @@ fn test_entry_point_attr_multiple() {
         "#]],
     );
 }
+
+#[test]
+fn test_entry_point_with_params_has_no_expr() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main(n : Int) : Int { n + 1 }
+                }"}
+            .into(),
+        )],
+        None,
+    );
+    let mut unit = compile(
+        &PackageStore::new(compile::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    let errors = generate_entry_expr(&mut unit.package, &mut unit.assigner);
+    assert!(errors.is_empty(), "{errors:?}");
+    assert!(unit.package.entry.is_none());
+}
@@ -0,0 +1,160 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Inlines calls to small callables, replacing each call with a block that binds the argument to
+//! the parameters and then runs the body of the callable. Only the body specialization of a
+//! callable declared in the same package is inlined, and only if it is not generic, does not call
+//! itself, returns from nowhere but its end, and has no `@NoInline()` attribute.
+
+#[cfg(test)]
+mod tests;
+
+use crate::id_update::NodeIdRefresher;
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    assigner::Assigner,
+    hir::{
+        Attr, Block, Expr, ExprKind, ItemId, ItemKind, LocalItemId, Mutability, NodeId, Package,
+        Pat, Res, SpecBody, Stmt, StmtKind,
+    },
+    mut_visit::{walk_expr, MutVisitor},
+    ty::Ty,
+    visit::{self, Visitor},
+};
+use rustc_hash::FxHashMap;
+use std::mem::take;
+
+/// Inlines the calls to each callable whose body has at most `threshold` statements and
+/// expressions.
+pub(crate) fn inline_calls(package: &mut Package, assigner: &mut Assigner, threshold: usize) {
+    let callables: FxHashMap<_, _> = package
+        .items
+        .iter()
+        .filter_map(|(id, item)| {
+            let ItemKind::Callable(decl) = &item.kind else {
+                return None;
+            };
+            let SpecBody::Impl(None, block) = &decl.body.body else {
+                return None;
+            };
+            if !decl.generics.is_empty() || item.attrs.contains(&Attr::NoInline) {
+                return None;
+            }
+
+            let mut body = Body {
+                id,
+                size: 0,
+                inlinable: true,
+            };
+            body.visit_block(block);
+            (body.inlinable && body.size <= threshold)
+                .then(|| (id, (decl.input.clone(), block.clone())))
+        })
+        .collect();
+
+    if !callables.is_empty() {
+        Inliner {
+            assigner,
+            callables: &callables,
+        }
+        .visit_package(package);
+    }
+}
+
+/// Measures the body of a callable and finds whether it can be inlined.
+struct Body {
+    id: LocalItemId,
+    size: usize,
+    inlinable: bool,
+}
+
+impl<'a> Visitor<'a> for Body {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        self.size += 1;
+        // An item declared in the body would be declared again at each call site.
+        if matches!(stmt.kind, StmtKind::Item(_)) {
+            self.inlinable = false;
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        self.size += 1;
+        match &expr.kind {
+            // A return in the body would return from the caller once inlined.
+            ExprKind::Return(_) => self.inlinable = false,
+            ExprKind::Var(
+                Res::Item(ItemId {
+                    package: None,
+                    item,
+                }),
+                _,
+            ) if *item == self.id => self.inlinable = false,
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+struct Inliner<'a> {
+    assigner: &'a mut Assigner,
+    /// The parameters and body of each callable that can be inlined.
+    callables: &'a FxHashMap<LocalItemId, (Pat, Block)>,
+}
+
+impl Inliner<'_> {
+    fn inline(&mut self, input: &Pat, body: &Block, arg: Expr, ty: Ty, span: Span) -> Block {
+        let mut stmts = Vec::with_capacity(body.stmts.len() + 1);
+        stmts.push(Stmt {
+            id: NodeId::default(),
+            span: arg.span,
+            kind: StmtKind::Local(Mutability::Immutable, input.clone(), arg),
+        });
+        stmts.extend(body.stmts.iter().cloned());
+
+        // Each call site gets its own copy of the body, with new IDs for its nodes and variables.
+        let mut block = Block {
+            id: NodeId::default(),
+            span,
+            ty,
+            stmts,
+        };
+        NodeIdRefresher::new(self.assigner).visit_block(&mut block);
+        block
+    }
+}
+
+impl MutVisitor for Inliner<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+
+        let callables = self.callables;
+        if let ExprKind::Call(callee, arg) = &mut expr.kind {
+            if let ExprKind::Var(
+                Res::Item(ItemId {
+                    package: None,
+                    item,
+                }),
+                _,
+            ) = &callee.kind
+            {
+                if let Some((input, body)) = callables.get(item) {
+                    if !has_hole(arg) {
+                        let arg = take(&mut **arg);
+                        let block = self.inline(input, body, arg, expr.ty.clone(), expr.span);
+                        expr.kind = ExprKind::Block(block);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether the argument leaves out any parameters, which makes the call a partial application.
+fn has_hole(arg: &Expr) -> bool {
+    match &arg.kind {
+        ExprKind::Hole => true,
+        ExprKind::Tuple(items) => items.iter().any(has_hole),
+        _ => false,
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::{
+    hir::{Expr, ExprKind, Lit, StmtKind},
+    mut_visit::MutVisitor,
+    validate::Validator,
+    visit::Visitor,
+};
+
+use crate::{const_fold::ConstFold, inline::inline_calls};
+
+/// The entry expression after inlining and folding.
+fn inline(file: &str, entry: &str, threshold: usize) -> Expr {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), file.into())], Some(entry.into()));
+    let mut unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    inline_calls(&mut unit.package, &mut unit.assigner, threshold);
+    ConstFold::default().visit_package(&mut unit.package);
+    Validator::default().visit_package(&unit.package);
+    unit.package
+        .entry
+        .expect("package should have an entry expression")
+}
+
+/// The value of a block, or the expression itself if it is not a block.
+fn value(expr: &Expr) -> &ExprKind {
+    match &expr.kind {
+        ExprKind::Block(block) => {
+            match &block.stmts.last().expect("block should not be empty").kind {
+                StmtKind::Expr(expr) => value(expr),
+                kind => panic!("block should end with an expression, found {kind}"),
+            }
+        }
+        kind => kind,
+    }
+}
+
+#[test]
+fn small_callable_is_inlined() {
+    let entry = inline(
+        indoc! {"
+            namespace Test {
+                function Double(x : Int) : Int { x * 2 }
+            }
+        "},
+        "Test.Double(21)",
+        10,
+    );
+    assert_eq!(value(&entry), &ExprKind::Lit(Lit::Int(42)));
+}
+
+#[test]
+fn each_call_is_inlined() {
+    let entry = inline(
+        indoc! {"
+            namespace Test {
+                function Double(x : Int) : Int { x * 2 }
+            }
+        "},
+        "(Test.Double(1), Test.Double(2))",
+        10,
+    );
+    let ExprKind::Tuple(items) = &entry.kind else {
+        panic!("entry should be a tuple, found {}", entry.kind);
+    };
+    assert_eq!(value(&items[0]), &ExprKind::Lit(Lit::Int(2)));
+    assert_eq!(value(&items[1]), &ExprKind::Lit(Lit::Int(4)));
+}
+
+#[test]
+fn callable_larger_than_threshold_is_not_inlined() {
+    let entry = inline(
+        indoc! {"
+            namespace Test {
+                function Double(x : Int) : Int { x * 2 }
+            }
+        "},
+        "Test.Double(21)",
+        3,
+    );
+    assert!(matches!(entry.kind, ExprKind::Call(..)));
+}
+
+#[test]
+fn no_inline_attribute_prevents_inlining() {
+    let entry = inline(
+        indoc! {"
+            namespace Test {
+                @NoInline()
+                function Double(x : Int) : Int { x * 2 }
+            }
+        "},
+        "Test.Double(21)",
+        10,
+    );
+    assert!(matches!(entry.kind, ExprKind::Call(..)));
+}
+
+#[test]
+fn recursive_callable_is_not_inlined() {
+    let entry = inline(
+        indoc! {"
+            namespace Test {
+                function Factorial(n : Int) : Int {
+                    if n <= 1 { 1 } else { n * Factorial(n - 1) }
+                }
+            }
+        "},
+        "Test.Factorial(3)",
+        100,
+    );
+    assert!(matches!(entry.kind, ExprKind::Call(..)));
+}
+
+#[test]
+fn callable_with_return_is_not_inlined() {
+    let entry = inline(
+        indoc! {"
+            namespace Test {
+                function Sign(x : Int) : Int {
+                    if x < 0 {
+                        return -1;
+                    }
+                    1
+                }
+            }
+        "},
+        "Test.Sign(3)",
+        100,
+    );
+    assert!(matches!(entry.kind, ExprKind::Call(..)));
+}
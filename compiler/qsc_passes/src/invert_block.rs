@@ -68,7 +68,7 @@ impl<'a> MutVisitor for BlockInverter<'a> {
         match &mut expr.kind {
             ExprKind::For(pat, iterable, block) if self.should_reverse_loop => {
                 self.visit_block(block);
-                *expr = self.reverse_loop(pat, iterable, block);
+                *expr = self.reverse_loop(pat, iterable, block, expr.span);
             }
             ExprKind::Conjugate(_, apply) => {
                 // Only invert the apply block, within block inversion handled by a different pass.
@@ -80,15 +80,20 @@ impl<'a> MutVisitor for BlockInverter<'a> {
 }
 
 impl<'a> BlockInverter<'a> {
-    fn reverse_loop(&mut self, pat: &Pat, iterable: &Expr, block: &Block) -> Expr {
+    /// Reverses the loop with the given span. The generated statements and expressions take the span
+    /// of the loop or of its iterable, so that the reversed loop can be stepped through and reports
+    /// errors where the original loop is.
+    fn reverse_loop(&mut self, pat: &Pat, iterable: &Expr, block: &Block, span: Span) -> Expr {
         let mut wrapper = Block {
             id: NodeId::default(),
-            span: Span::default(),
+            span,
             ty: Ty::UNIT,
             stmts: Vec::new(),
         };
         match &iterable.ty {
-            Ty::Prim(Prim::Range) => self.reverse_range_loop(&mut wrapper, iterable, pat, block),
+            Ty::Prim(Prim::Range) => {
+                self.reverse_range_loop(&mut wrapper, iterable, pat, block, span);
+            }
 
             Ty::Array(arr_ty) => {
                 self.reverse_array_loop(
@@ -97,6 +102,7 @@ impl<'a> BlockInverter<'a> {
                     iterable.clone(),
                     pat.clone(),
                     block.clone(),
+                    span,
                 );
             }
 
@@ -105,7 +111,7 @@ impl<'a> BlockInverter<'a> {
 
         Expr {
             id: NodeId::default(),
-            span: Span::default(),
+            span,
             ty: Ty::UNIT,
             kind: ExprKind::Block(wrapper),
         }
@@ -118,21 +124,24 @@ impl<'a> BlockInverter<'a> {
         iterable: Expr,
         pat: Pat,
         mut block: Block,
+        span: Span,
     ) {
+        let iterable_span = iterable.span;
+
         // Create a new binding for the array expr.
         let new_arr_id = self.assigner.next_node();
         wrapper.stmts.push(Stmt {
             id: NodeId::default(),
-            span: Span::default(),
+            span: iterable_span,
             kind: StmtKind::Local(
                 Mutability::Immutable,
                 Pat {
                     id: NodeId::default(),
-                    span: Span::default(),
+                    span: iterable_span,
                     ty: Ty::Array(Box::new(arr_ty.clone())),
                     kind: PatKind::Bind(Ident {
                         id: new_arr_id,
-                        span: Span::default(),
+                        span: iterable_span,
                         name: generated_name("array"),
                     }),
                 },
@@ -144,36 +153,37 @@ impl<'a> BlockInverter<'a> {
         let index_id = self.assigner.next_node();
         let index_pat = Pat {
             id: NodeId::default(),
-            span: Span::default(),
+            span: iterable_span,
             ty: Ty::Prim(Prim::Int),
             kind: PatKind::Bind(Ident {
                 id: index_id,
-                span: Span::default(),
+                span: iterable_span,
                 name: generated_name("index"),
             }),
         };
 
-        // Create a binding from the previous loop iterator variable and the array index expr.
+        // Create a binding from the previous loop iterator variable and the array index expr. Like
+        // the binding of a forward loop, it can be stepped to at the start of each iteration.
         let iterator_bind = Stmt {
             id: NodeId::default(),
-            span: Span::default(),
+            span: pat.span,
             kind: StmtKind::Local(
                 Mutability::Immutable,
                 pat,
                 Expr {
                     id: NodeId::default(),
-                    span: Span::default(),
+                    span: iterable_span,
                     ty: arr_ty.clone(),
                     kind: ExprKind::Index(
                         Box::new(Expr {
                             id: NodeId::default(),
-                            span: Span::default(),
+                            span: iterable_span,
                             ty: Ty::Array(Box::new(arr_ty.clone())),
                             kind: ExprKind::Var(Res::Local(new_arr_id), Vec::new()),
                         }),
                         Box::new(Expr {
                             id: NodeId::default(),
-                            span: Span::default(),
+                            span: iterable_span,
                             ty: Ty::Prim(Prim::Int),
                             kind: ExprKind::Var(Res::Local(index_id), Vec::new()),
                         }),
@@ -190,15 +200,18 @@ impl<'a> BlockInverter<'a> {
         // Put in the new for-loop
         wrapper.stmts.push(Stmt {
             id: NodeId::default(),
-            span: Span::default(),
+            span,
             kind: StmtKind::Expr(Expr {
                 id: NodeId::default(),
-                span: Span::default(),
+                span,
                 ty: Ty::UNIT,
                 kind: ExprKind::For(
                     index_pat,
                     Box::new(make_array_index_range_reverse(
-                        self.core, new_arr_id, arr_ty,
+                        self.core,
+                        new_arr_id,
+                        arr_ty,
+                        iterable_span,
                     )),
                     block,
                 ),
@@ -212,21 +225,22 @@ impl<'a> BlockInverter<'a> {
         iterable: &Expr,
         pat: &Pat,
         block: &Block,
+        span: Span,
     ) {
         // Create a new binding for the range expr.
         let new_range_id = self.assigner.next_node();
         wrapper.stmts.push(Stmt {
             id: NodeId::default(),
-            span: Span::default(),
+            span: iterable.span,
             kind: StmtKind::Local(
                 Mutability::Immutable,
                 Pat {
                     id: NodeId::default(),
-                    span: Span::default(),
+                    span: iterable.span,
                     ty: Ty::Prim(Prim::Range),
                     kind: PatKind::Bind(Ident {
                         id: new_range_id,
-                        span: Span::default(),
+                        span: iterable.span,
                         name: generated_name("range"),
                     }),
                 },
@@ -237,14 +251,14 @@ impl<'a> BlockInverter<'a> {
         // Create the new for-loop that iterates over the reversed range.
         wrapper.stmts.push(Stmt {
             id: NodeId::default(),
-            span: Span::default(),
+            span,
             kind: StmtKind::Expr(Expr {
                 id: NodeId::default(),
-                span: Span::default(),
+                span,
                 ty: Ty::UNIT,
                 kind: ExprKind::For(
                     pat.clone(),
-                    Box::new(make_range_reverse_expr(new_range_id)),
+                    Box::new(make_range_reverse_expr(new_range_id, iterable.span)),
                     block.clone(),
                 ),
             }),
@@ -252,34 +266,34 @@ impl<'a> BlockInverter<'a> {
     }
 }
 
-fn make_range_reverse_expr(range_id: NodeId) -> Expr {
-    let start = make_range_field(range_id, PrimField::Start);
-    let step = make_range_field(range_id, PrimField::Step);
-    let end = make_range_field(range_id, PrimField::End);
+fn make_range_reverse_expr(range_id: NodeId, span: Span) -> Expr {
+    let start = make_range_field(range_id, PrimField::Start, span);
+    let step = make_range_field(range_id, PrimField::Step, span);
+    let end = make_range_field(range_id, PrimField::End, span);
 
     // A reversed range is `(start + (end - start) / step * step) .. -step .. start`.
     let new_start = Box::new(Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Int),
         kind: ExprKind::BinOp(
             BinOp::Add,
             Box::new(start.clone()),
             Box::new(Expr {
                 id: NodeId::default(),
-                span: Span::default(),
+                span,
                 ty: Ty::Prim(Prim::Int),
                 kind: ExprKind::BinOp(
                     BinOp::Mul,
                     Box::new(Expr {
                         id: NodeId::default(),
-                        span: Span::default(),
+                        span,
                         ty: Ty::Prim(Prim::Int),
                         kind: ExprKind::BinOp(
                             BinOp::Div,
                             Box::new(Expr {
                                 id: NodeId::default(),
-                                span: Span::default(),
+                                span,
                                 ty: Ty::Prim(Prim::Int),
                                 kind: ExprKind::BinOp(
                                     BinOp::Sub,
@@ -297,7 +311,7 @@ fn make_range_reverse_expr(range_id: NodeId) -> Expr {
     });
     let new_step = Box::new(Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Int),
         kind: ExprKind::UnOp(UnOp::Neg, Box::new(step)),
     });
@@ -305,21 +319,21 @@ fn make_range_reverse_expr(range_id: NodeId) -> Expr {
 
     Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Range),
         kind: ExprKind::Range(Some(new_start), Some(new_step), Some(new_end)),
     }
 }
 
-fn make_range_field(range_id: NodeId, field: PrimField) -> Expr {
+fn make_range_field(range_id: NodeId, field: PrimField, span: Span) -> Expr {
     Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Int),
         kind: ExprKind::Field(
             Box::new(Expr {
                 id: NodeId::default(),
-                span: Span::default(),
+                span,
                 ty: Ty::Prim(Prim::Range),
                 kind: ExprKind::Var(Res::Local(range_id), Vec::new()),
             }),
@@ -328,10 +342,10 @@ fn make_range_field(range_id: NodeId, field: PrimField) -> Expr {
     }
 }
 
-fn make_array_index_range_reverse(core: &Table, arr_id: NodeId, arr_ty: &Ty) -> Expr {
+fn make_array_index_range_reverse(core: &Table, arr_id: NodeId, arr_ty: &Ty, span: Span) -> Expr {
     let len = Box::new(Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Int),
         kind: ExprKind::Call(
             Box::new(create_gen_core_ref(
@@ -339,11 +353,11 @@ fn make_array_index_range_reverse(core: &Table, arr_id: NodeId, arr_ty: &Ty) ->
                 "Microsoft.Quantum.Core",
                 "Length",
                 vec![GenericArg::Ty(arr_ty.clone())],
-                Span::default(),
+                span,
             )),
             Box::new(Expr {
                 id: NodeId::default(),
-                span: Span::default(),
+                span,
                 ty: Ty::Array(Box::new(arr_ty.clone())),
                 kind: ExprKind::Var(Res::Local(arr_id), Vec::new()),
             }),
@@ -351,14 +365,14 @@ fn make_array_index_range_reverse(core: &Table, arr_id: NodeId, arr_ty: &Ty) ->
     });
     let start = Box::new(Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Int),
         kind: ExprKind::BinOp(
             BinOp::Sub,
             len,
             Box::new(Expr {
                 id: NodeId::default(),
-                span: Span::default(),
+                span,
                 ty: Ty::Prim(Prim::Int),
                 kind: ExprKind::Lit(Lit::Int(1)),
             }),
@@ -366,19 +380,19 @@ fn make_array_index_range_reverse(core: &Table, arr_id: NodeId, arr_ty: &Ty) ->
     });
     let step = Box::new(Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Int),
         kind: ExprKind::Lit(Lit::Int(-1)),
     });
     let end = Box::new(Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Int),
         kind: ExprKind::Lit(Lit::Int(0)),
     });
     Expr {
         id: NodeId::default(),
-        span: Span::default(),
+        span,
         ty: Ty::Prim(Prim::Range),
         kind: ExprKind::Range(Some(start), Some(step), Some(end)),
     }
@@ -4,17 +4,24 @@
 mod baseprofck;
 mod borrowck;
 mod callable_limits;
+mod capabilitiesck;
 mod common;
 mod conjugate_invert;
+mod const_fold;
 mod entry_point;
 mod id_update;
+mod inline;
 mod invert_block;
 mod logic_sep;
 mod loop_unification;
+mod loop_unroll;
 mod replace_qubit_allocation;
 mod spec_gen;
 
+pub use capabilitiesck::{callable_capabilities, CallableCapabilities};
+
 use callable_limits::CallableLimits;
+use const_fold::ConstFold;
 use entry_point::generate_entry_expr;
 use loop_unification::LoopUni;
 use miette::Diagnostic;
@@ -37,8 +44,10 @@ pub enum Error {
     BaseProfCk(baseprofck::Error),
     BorrowCk(borrowck::Error),
     CallableLimits(callable_limits::Error),
+    CapabilitiesCk(capabilitiesck::Error),
     ConjInvert(conjugate_invert::Error),
     EntryPoint(entry_point::Error),
+    LoopUnroll(loop_unroll::Error),
     SpecGen(spec_gen::Error),
 }
 
@@ -51,6 +60,9 @@ pub enum PackageType {
 pub struct PassContext {
     capabilities: RuntimeCapabilityFlags,
     borrow_check: borrowck::Checker,
+    inline_threshold: usize,
+    unroll_budget: usize,
+    const_fold: bool,
 }
 
 impl PassContext {
@@ -59,10 +71,40 @@ impl PassContext {
         Self {
             capabilities,
             borrow_check: borrowck::Checker::default(),
+            inline_threshold: 0,
+            unroll_budget: 0,
+            const_fold: false,
         }
     }
 
+    /// Inlines calls to callables whose bodies have at most `threshold` statements and expressions.
+    /// No calls are inlined by default, so each call keeps its own frame in stack traces and the
+    /// debugger.
+    #[must_use]
+    pub fn with_inline_threshold(mut self, threshold: usize) -> Self {
+        self.inline_threshold = threshold;
+        self
+    }
+
+    /// Unrolls `for` loops over constant ranges with at most `budget` iterations. No loops are
+    /// unrolled by default. When the capabilities do not include backwards branching, each loop over
+    /// a range that cannot be unrolled is reported as an error.
+    #[must_use]
+    pub fn with_unroll_budget(mut self, budget: usize) -> Self {
+        self.unroll_budget = budget;
+        self
+    }
+
+    /// Folds constant expressions and propagates immutable constants. Nothing is folded by default,
+    /// so each expression keeps its own node in stack traces and the debugger.
+    #[must_use]
+    pub fn with_const_fold(mut self, const_fold: bool) -> Self {
+        self.const_fold = const_fold;
+        self
+    }
+
     /// Run the default set of passes required for evaluation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn run_default_passes(
         &mut self,
         package: &mut Package,
@@ -74,6 +116,14 @@ impl PassContext {
         call_limits.visit_package(package);
         let callable_errors = call_limits.errors;
 
+        // Checked before any other pass changes the package, so each error points to code that the
+        // user wrote.
+        let capabilities_errors = if self.capabilities == RuntimeCapabilityFlags::all() {
+            Vec::new()
+        } else {
+            capabilitiesck::check_capabilities(core, package, self.capabilities)
+        };
+
         self.borrow_check.visit_package(package);
         let borrow_errors = &mut self.borrow_check.errors;
 
@@ -91,26 +141,65 @@ impl PassContext {
             Vec::new()
         };
 
+        let base_prof_errors = if self.capabilities == RuntimeCapabilityFlags::empty() {
+            let mut errors = baseprofck::check_base_profile_compliance(package);
+            // A comparison of dynamic results is already reported by the capabilities check, which
+            // says what capability the comparison needs.
+            errors.retain(|error| {
+                !matches!(error, baseprofck::Error::ResultComparison(span)
+                    if capabilities_errors.contains(&capabilitiesck::Error::UseOfDynamicBool(*span)))
+            });
+            errors
+        } else {
+            Vec::new()
+        };
+
+        // Unrolled once the checks above have run, and after folding so that ranges with constant
+        // bounds become literals.
+        let unroll_errors = if self.unroll_budget > 0 {
+            ConstFold::default().visit_package(package);
+            let errors = loop_unroll::unroll_loops(
+                package,
+                assigner,
+                self.unroll_budget,
+                !self
+                    .capabilities
+                    .contains(RuntimeCapabilityFlags::BackwardsBranching),
+            );
+            Validator::default().visit_package(package);
+            errors
+        } else {
+            Vec::new()
+        };
+
         LoopUni { core, assigner }.visit_package(package);
         Validator::default().visit_package(package);
 
         ReplaceQubitAllocation::new(core, assigner).visit_package(package);
         Validator::default().visit_package(package);
 
-        let base_prof_errors = if self.capabilities == RuntimeCapabilityFlags::empty() {
-            baseprofck::check_base_profile_compliance(package)
-        } else {
-            Vec::new()
-        };
+        // Inlined and folded last, so the checks above report errors for the code as the user wrote
+        // it.
+        if self.inline_threshold > 0 {
+            inline::inline_calls(package, assigner, self.inline_threshold);
+            Validator::default().visit_package(package);
+        }
+
+        if self.const_fold {
+            ConstFold::default().visit_package(package);
+            Validator::default().visit_package(package);
+        }
 
         callable_errors
             .into_iter()
             .map(Error::CallableLimits)
+            .chain(capabilities_errors.into_iter().map(Error::CapabilitiesCk))
             .chain(borrow_errors.drain(..).map(Error::BorrowCk))
             .chain(spec_errors.into_iter().map(Error::SpecGen))
             .chain(conjugate_errors.into_iter().map(Error::ConjInvert))
             .chain(entry_point_errors)
             .chain(base_prof_errors.into_iter().map(Error::BaseProfCk))
+            .chain(unroll_errors.into_iter().map(Error::LoopUnroll))
             .collect()
     }
 }
@@ -130,6 +219,7 @@ pub fn run_default_passes(
     )
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn run_core_passes(core: &mut CompileUnit) -> Vec<Error> {
     let mut borrow_check = borrowck::Checker::default();
     borrow_check.visit_package(&core.package);
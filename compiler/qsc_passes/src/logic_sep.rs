@@ -7,11 +7,11 @@ mod tests;
 use miette::Diagnostic;
 use qsc_data_structures::span::Span;
 use qsc_hir::{
-    hir::{Block, CallableKind, Expr, ExprKind, NodeId, StmtKind},
-    ty::Ty,
+    hir::{Block, CallableKind, Expr, ExprKind, NodeId, Res, StmtKind},
+    ty::{Prim, Ty},
     visit::{walk_expr, Visitor},
 };
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use thiserror::Error;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -30,13 +30,29 @@ pub enum Error {
     #[diagnostic(help("in blocks that require generated adjoint, operation calls can only appear as top-level statements or in a qubit allocation block, conjugate block, for-loop block, or conditional block"))]
     #[diagnostic(code("Qsc.LogicSeparation.OpCallForbidden"))]
     OpCallForbidden(#[label] Span),
+
+    #[error("cannot generate adjoint of a measurement")]
+    #[diagnostic(help("a measurement cannot be reversed; to use it in an operation that supports the adjoint functor, declare the `body` and `adjoint` specializations separately and write the adjoint by hand"))]
+    #[diagnostic(code("Qsc.LogicSeparation.Measurement"))]
+    Measurement(#[label] Span),
+
+    #[error("cannot generate adjoint when a variable used by an operation call is reassigned")]
+    #[diagnostic(help("the generated adjoint runs operation calls in reverse order, so they would see a different value of the variable; bind the value with `let` instead, or declare the `body` and `adjoint` specializations separately and write the adjoint by hand"))]
+    #[diagnostic(code("Qsc.LogicSeparation.MutableFlow"))]
+    MutableFlow(
+        #[label("variable is reassigned here")] Span,
+        #[label("and used by this operation call")] Span,
+    ),
 }
 
 /// Checks that the given block is separatable, meaning classical statements and quantum statements
 /// across the block and any nested expressions/blocks can be logically separated. On success, returns a `HashSet` of
 /// all quantum statement node ids, based on whether any operation calls are present in that statement.
 pub(crate) fn find_quantum_stmts(block: &Block) -> Result<FxHashSet<NodeId>, Vec<Error>> {
+    let mut op_call_vars = OpCallVars::default();
+    op_call_vars.visit_block(block);
     let mut pass = SepCheck {
+        op_call_vars: op_call_vars.vars,
         errors: Vec::new(),
         op_call_present: FxHashSet::default(),
         op_call_allowed: true,
@@ -51,6 +67,8 @@ pub(crate) fn find_quantum_stmts(block: &Block) -> Result<FxHashSet<NodeId>, Vec
 }
 
 struct SepCheck {
+    /// The span of the first operation call that uses each local variable.
+    op_call_vars: FxHashMap<NodeId, Span>,
     errors: Vec<Error>,
     op_call_present: FxHashSet<NodeId>,
     op_call_allowed: bool,
@@ -101,7 +119,10 @@ impl<'a> Visitor<'a> for SepCheck {
 
     fn visit_expr(&mut self, expr: &'a Expr) {
         if let ExprKind::Call(callee, _) = &expr.kind {
-            if matches!(&callee.ty, Ty::Arrow(arrow) if arrow.kind == CallableKind::Operation) {
+            if is_measurement(callee) {
+                self.errors.push(Error::Measurement(expr.span));
+            } else if matches!(&callee.ty, Ty::Arrow(arrow) if arrow.kind == CallableKind::Operation)
+            {
                 self.errors.push(Error::OpCallForbidden(expr.span));
             }
         }
@@ -147,7 +168,9 @@ impl SepCheck {
         self.visit_expr(args);
         self.op_call_allowed = prior;
         let mut has_inner_op_call = false;
-        if is_op_call {
+        if is_measurement(callee) {
+            self.errors.push(Error::Measurement(expr.span));
+        } else if is_op_call {
             if self.op_call_allowed {
                 has_inner_op_call = true;
             } else {
@@ -198,16 +221,67 @@ impl SepCheck {
                 false
             }
 
-            ExprKind::Assign(..)
-            | ExprKind::AssignOp(..)
-            | ExprKind::AssignField(..)
-            | ExprKind::AssignIndex(..)
-            | ExprKind::Repeat(..)
-            | ExprKind::Return(..)
-            | ExprKind::While(..) => {
+            ExprKind::Assign(lhs, _)
+            | ExprKind::AssignOp(_, lhs, _)
+            | ExprKind::AssignField(lhs, _, _)
+            | ExprKind::AssignIndex(lhs, _, _) => {
+                match &lhs.kind {
+                    ExprKind::Var(Res::Local(id), _) if self.op_call_vars.contains_key(id) => {
+                        self.errors
+                            .push(Error::MutableFlow(expr.span, self.op_call_vars[id]));
+                    }
+                    _ => self.errors.push(Error::ExprForbidden(expr.span)),
+                }
+                false
+            }
+
+            ExprKind::Repeat(..) | ExprKind::Return(..) | ExprKind::While(..) => {
                 self.errors.push(Error::ExprForbidden(expr.span));
                 false
             }
         }
     }
 }
+
+/// Whether the callee is an operation that returns a measurement result, which cannot be reversed.
+fn is_measurement(callee: &Expr) -> bool {
+    fn has_result(ty: &Ty) -> bool {
+        match ty {
+            Ty::Prim(Prim::Result) => true,
+            Ty::Array(item) => has_result(item),
+            Ty::Tuple(items) => items.iter().any(has_result),
+            _ => false,
+        }
+    }
+
+    matches!(&callee.ty, Ty::Arrow(arrow)
+        if arrow.kind == CallableKind::Operation && has_result(&arrow.output))
+}
+
+/// Finds the local variables used by operation calls.
+#[derive(Default)]
+struct OpCallVars {
+    vars: FxHashMap<NodeId, Span>,
+    call: Option<Span>,
+}
+
+impl<'a> Visitor<'a> for OpCallVars {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Call(callee, _)
+                if self.call.is_none()
+                    && matches!(&callee.ty, Ty::Arrow(arrow) if arrow.kind == CallableKind::Operation) =>
+            {
+                self.call = Some(expr.span);
+                walk_expr(self, expr);
+                self.call = None;
+            }
+            ExprKind::Var(Res::Local(id), _) => {
+                if let Some(call) = self.call {
+                    self.vars.entry(*id).or_insert(call);
+                }
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+}
@@ -31,7 +31,7 @@ fn check(block_str: &str, expect: &Expect) {
     let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
     let unit = compile(
         &store,
-        &[std],
+        &[(std, None)],
         SourceMap::new([], Some(block_str.into())),
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
@@ -472,3 +472,58 @@ fn return_forbidden() {
         "#]],
     );
 }
+
+#[test]
+fn measurement_forbidden() {
+    check(
+        "{use q = Qubit(); M(q);}",
+        &expect![[r#"
+            [
+                Measurement(
+                    Span {
+                        lo: 18,
+                        hi: 22,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn measurement_in_binding_forbidden() {
+    check(
+        "{use q = Qubit(); let r = M(q);}",
+        &expect![[r#"
+            [
+                Measurement(
+                    Span {
+                        lo: 26,
+                        hi: 30,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn assign_to_var_used_by_op_call_forbidden() {
+    check(
+        "{use q = Qubit(); mutable theta = 0.0; Rx(theta, q); set theta = 1.0;}",
+        &expect![[r#"
+            [
+                MutableFlow(
+                    Span {
+                        lo: 53,
+                        hi: 68,
+                    },
+                    Span {
+                        lo: 39,
+                        hi: 51,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
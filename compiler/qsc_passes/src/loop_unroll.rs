@@ -0,0 +1,192 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Unrolls `for` loops over ranges whose start, step, and end are constants, replacing each loop
+//! with a block that has one copy of the body for each value in the range. Each copy binds the
+//! loop variable to its value, so constant folding can then propagate it into the body.
+
+#[cfg(test)]
+mod tests;
+
+use crate::id_update::NodeIdRefresher;
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    assigner::Assigner,
+    hir::{Block, Expr, ExprKind, Lit, Mutability, NodeId, Package, Pat, Stmt, StmtKind},
+    mut_visit::{walk_expr, MutVisitor},
+    ty::{Prim, Ty},
+};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("cannot unroll a loop over a range that is not constant")]
+    #[diagnostic(help(
+        "the target does not support loops, so the start, step, and end of the range must be known at compile time"
+    ))]
+    #[diagnostic(code("Qsc.LoopUnroll.NotConstant"))]
+    NotConstant(#[label] Span),
+
+    #[error("cannot unroll a loop with {0} iterations")]
+    #[diagnostic(help(
+        "the target does not support loops, and unrolling this loop would take more than the budget of {1} iterations; raise the budget or shorten the range"
+    ))]
+    #[diagnostic(code("Qsc.LoopUnroll.OverBudget"))]
+    OverBudget(u64, usize, #[label] Span),
+}
+
+/// Unrolls each `for` loop over a constant range with at most `budget` iterations. If `report` is
+/// set, because the target does not support loops, an error is returned for each loop over a range
+/// that cannot be unrolled.
+pub(crate) fn unroll_loops(
+    package: &mut Package,
+    assigner: &mut Assigner,
+    budget: usize,
+    report: bool,
+) -> Vec<Error> {
+    let mut unroller = Unroller {
+        assigner,
+        budget,
+        report,
+        errors: Vec::new(),
+    };
+    unroller.visit_package(package);
+    unroller.errors
+}
+
+struct Unroller<'a> {
+    assigner: &'a mut Assigner,
+    budget: usize,
+    report: bool,
+    errors: Vec<Error>,
+}
+
+impl Unroller<'_> {
+    fn iteration(&mut self, pat: &Pat, value: i64, body: &Block, span: Span) -> Stmt {
+        let mut stmts = Vec::with_capacity(body.stmts.len() + 1);
+        stmts.push(Stmt {
+            id: NodeId::default(),
+            span: pat.span,
+            kind: StmtKind::Local(
+                Mutability::Immutable,
+                pat.clone(),
+                Expr {
+                    id: NodeId::default(),
+                    span,
+                    ty: Ty::Prim(Prim::Int),
+                    kind: ExprKind::Lit(Lit::Int(value)),
+                },
+            ),
+        });
+        stmts.extend(body.stmts.iter().cloned());
+
+        // Each iteration gets its own copy of the body, with new IDs for its nodes and variables.
+        let mut block = Block {
+            id: NodeId::default(),
+            span: body.span,
+            ty: body.ty.clone(),
+            stmts,
+        };
+        NodeIdRefresher::new(self.assigner).visit_block(&mut block);
+
+        Stmt {
+            id: self.assigner.next_node(),
+            span: body.span,
+            kind: StmtKind::Semi(Expr {
+                id: self.assigner.next_node(),
+                span: body.span,
+                ty: block.ty.clone(),
+                kind: ExprKind::Block(block),
+            }),
+        }
+    }
+}
+
+impl MutVisitor for Unroller<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        // Inner loops are unrolled first, so each copy of an outer body has them unrolled too.
+        walk_expr(self, expr);
+
+        let ExprKind::For(pat, iter, body) = &expr.kind else {
+            return;
+        };
+        if iter.ty != Ty::Prim(Prim::Range) {
+            return;
+        }
+        // An item declared in the body would be declared again in each iteration.
+        if body
+            .stmts
+            .iter()
+            .any(|stmt| matches!(stmt.kind, StmtKind::Item(_)))
+        {
+            return;
+        }
+
+        let Some((start, step, end)) = constant_range(iter) else {
+            if self.report {
+                self.errors.push(Error::NotConstant(iter.span));
+            }
+            return;
+        };
+        let Some(count) = count(start, step, end) else {
+            return;
+        };
+        if !usize::try_from(count).is_ok_and(|count| count <= self.budget) {
+            if self.report {
+                self.errors
+                    .push(Error::OverBudget(count, self.budget, iter.span));
+            }
+            return;
+        }
+
+        let (pat, iter_span, body) = (pat.clone(), iter.span, body.clone());
+        let stmts = (0..count)
+            .map(|k| {
+                let value = i128::from(start) + i128::from(step) * i128::from(k);
+                let value = i64::try_from(value).expect("value should be within the range");
+                self.iteration(&pat, value, &body, iter_span)
+            })
+            .collect();
+        expr.kind = ExprKind::Block(Block {
+            id: self.assigner.next_node(),
+            span: expr.span,
+            ty: expr.ty.clone(),
+            stmts,
+        });
+    }
+}
+
+/// The start, step, and end of a range expression whose parts are all integer literals.
+fn constant_range(expr: &Expr) -> Option<(i64, i64, i64)> {
+    let ExprKind::Range(Some(start), step, Some(end)) = &expr.kind else {
+        return None;
+    };
+    let step = match step {
+        Some(step) => int(step)?,
+        None => 1,
+    };
+    Some((int(start)?, step, int(end)?))
+}
+
+fn int(expr: &Expr) -> Option<i64> {
+    match expr.kind {
+        ExprKind::Lit(Lit::Int(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// The number of values in the range, or `None` if the step is zero.
+fn count(start: i64, step: i64, end: i64) -> Option<u64> {
+    let (start, step, end) = (i128::from(start), i128::from(step), i128::from(end));
+    let count = if step > 0 && end >= start {
+        (end - start) / step + 1
+    } else if step < 0 && end <= start {
+        (start - end) / -step + 1
+    } else if step == 0 {
+        return None;
+    } else {
+        0
+    };
+    u64::try_from(count).ok()
+}
@@ -0,0 +1,254 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use indoc::indoc;
+use miette::Diagnostic;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::{
+    hir::{Block, ExprKind, ItemKind, Lit, SpecBody, StmtKind},
+    mut_visit::MutVisitor,
+    validate::Validator,
+    visit::Visitor,
+};
+
+use crate::{const_fold::ConstFold, loop_unroll::unroll_loops};
+
+/// The body of `Main` after folding and unrolling, and the errors as code and source snippet.
+fn unroll(file: &str, budget: usize, report: bool) -> (Block, Vec<String>) {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let mut unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    ConstFold::default().visit_package(&mut unit.package);
+    let errors = unroll_loops(&mut unit.package, &mut unit.assigner, budget, report);
+    Validator::default().visit_package(&unit.package);
+
+    let errors = errors
+        .iter()
+        .map(|error| {
+            let code = error.code().expect("error should have a code");
+            let label = error
+                .labels()
+                .and_then(|mut labels| labels.next())
+                .expect("error should have a label");
+            let snippet = &file[label.offset()..label.offset() + label.len()];
+            format!("{code}: {snippet}")
+        })
+        .collect();
+
+    let body = unit
+        .package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            ItemKind::Callable(decl) if decl.name.name.as_ref() == "Main" => {
+                match &decl.body.body {
+                    SpecBody::Impl(_, block) => Some(block.clone()),
+                    SpecBody::Gen(_) => None,
+                }
+            }
+            _ => None,
+        })
+        .expect("package should have a Main callable");
+    (body, errors)
+}
+
+/// The value bound to the loop variable in each iteration of the loop at the given statement of
+/// the block, or `None` if the loop was not unrolled.
+fn iterations(block: &Block, index: usize) -> Option<Vec<i64>> {
+    let StmtKind::Expr(expr) = &block.stmts[index].kind else {
+        panic!("statement should be an expression");
+    };
+    match &expr.kind {
+        ExprKind::For(..) => None,
+        ExprKind::Block(unrolled) => Some(
+            unrolled
+                .stmts
+                .iter()
+                .map(|stmt| match &stmt.kind {
+                    StmtKind::Semi(expr) => match &expr.kind {
+                        ExprKind::Block(iteration) => match &iteration.stmts[0].kind {
+                            StmtKind::Local(_, _, value) => match value.kind {
+                                ExprKind::Lit(Lit::Int(value)) => value,
+                                _ => panic!("loop variable should be bound to an integer"),
+                            },
+                            _ => panic!("iteration should bind the loop variable"),
+                        },
+                        _ => panic!("iteration should be a block"),
+                    },
+                    _ => panic!("iteration should be a statement"),
+                })
+                .collect(),
+        ),
+        kind => panic!("loop should be a for loop or a block, found {kind}"),
+    }
+}
+
+#[test]
+fn constant_range_is_unrolled() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main() : Int {
+                    mutable sum = 0;
+                    for i in 1..3 {
+                        set sum += i;
+                    }
+                    sum
+                }
+            }
+        "},
+        10,
+        true,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(iterations(&body, 1), Some(vec![1, 2, 3]));
+}
+
+#[test]
+fn range_with_step_is_unrolled() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    for i in 6..-2..1 {}
+                }
+            }
+        "},
+        10,
+        true,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(iterations(&body, 0), Some(vec![6, 4, 2]));
+}
+
+#[test]
+fn range_of_constant_locals_is_unrolled() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    let n = 4;
+                    for i in 0..2..n {}
+                }
+            }
+        "},
+        10,
+        true,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(iterations(&body, 1), Some(vec![0, 2, 4]));
+}
+
+#[test]
+fn empty_range_is_unrolled_to_nothing() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    for i in 3..1 {}
+                }
+            }
+        "},
+        10,
+        true,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(iterations(&body, 0), Some(Vec::new()));
+}
+
+#[test]
+fn nested_loops_are_unrolled() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    for i in 0..1 {
+                        for j in 0..2 {}
+                    }
+                }
+            }
+        "},
+        10,
+        true,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(iterations(&body, 0), Some(vec![0, 1]));
+}
+
+#[test]
+fn loop_over_budget_is_reported() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    for i in 0..9 {}
+                }
+            }
+        "},
+        4,
+        true,
+    );
+    assert_eq!(errors, ["Qsc.LoopUnroll.OverBudget: 0..9"]);
+    assert_eq!(iterations(&body, 0), None);
+}
+
+#[test]
+fn loop_over_range_that_is_not_constant_is_reported() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main(n : Int) : Unit {
+                    for i in 0..n {}
+                }
+            }
+        "},
+        10,
+        true,
+    );
+    assert_eq!(errors, ["Qsc.LoopUnroll.NotConstant: 0..n"]);
+    assert_eq!(iterations(&body, 0), None);
+}
+
+#[test]
+fn loop_that_cannot_be_unrolled_is_not_reported_when_target_supports_loops() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main(n : Int) : Unit {
+                    for i in 0..n {}
+                    for i in 0..9 {}
+                }
+            }
+        "},
+        4,
+        false,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(iterations(&body, 0), None);
+    assert_eq!(iterations(&body, 1), None);
+}
+
+#[test]
+fn loop_over_array_is_not_unrolled() {
+    let (body, errors) = unroll(
+        indoc! {"
+            namespace Test {
+                operation Main(qs : Qubit[]) : Unit {
+                    for q in qs {}
+                }
+            }
+        "},
+        10,
+        true,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(iterations(&body, 0), None);
+}
@@ -100,6 +100,7 @@ impl MutVisitor for SpecPlacePass<'_> {
                 id: self.assigner.next_node(),
                 span: decl.span,
                 body: SpecBody::Gen(SpecGen::Invert),
+                generated: None,
             });
         }
 
@@ -108,6 +109,7 @@ impl MutVisitor for SpecPlacePass<'_> {
                 id: self.assigner.next_node(),
                 span: decl.span,
                 body: SpecBody::Gen(SpecGen::Distribute),
+                generated: None,
             });
         }
 
@@ -130,6 +132,7 @@ impl MutVisitor for SpecPlacePass<'_> {
                 id: self.assigner.next_node(),
                 span: decl.span,
                 body: SpecBody::Gen(gen),
+                generated: None,
             });
         }
     }
@@ -170,6 +173,9 @@ impl<'a> SpecImplPass<'a> {
             .extend(distrib.errors.into_iter().map(Error::CtlGen));
 
         // Update the specialization body to reflect the generated block.
+        if let SpecBody::Gen(gen) = spec_decl.body {
+            spec_decl.generated = Some(gen);
+        }
         spec_decl.body = SpecBody::Impl(
             Some(Pat {
                 id: NodeId::default(),
@@ -203,6 +209,9 @@ impl<'a> SpecImplPass<'a> {
             .extend(distrib.errors.into_iter().map(Error::AdjGen));
 
         // Update the specialization body to reflect the generated block.
+        if let SpecBody::Gen(gen) = spec_decl.body {
+            spec_decl.generated = Some(gen);
+        }
         spec_decl.body = SpecBody::Impl(ctls_pat, adj_block);
     }
 }
@@ -252,6 +261,7 @@ impl<'a> MutVisitor for SpecImplPass<'a> {
             match adj.body {
                 SpecBody::Gen(SpecGen::Slf) => {
                     adj.body = body.body.clone();
+                    adj.generated = Some(SpecGen::Slf);
                     NodeIdRefresher::new(self.assigner).visit_spec_decl(adj);
                 }
                 SpecBody::Gen(SpecGen::Invert | SpecGen::Auto) => {
@@ -273,6 +283,7 @@ impl<'a> MutVisitor for SpecImplPass<'a> {
                 }
                 SpecBody::Gen(SpecGen::Slf) => {
                     ctl_adj.body = ctl.body.clone();
+                    ctl_adj.generated = Some(SpecGen::Slf);
                     NodeIdRefresher::new(self.assigner).visit_spec_decl(ctl_adj);
                 }
                 SpecBody::Gen(SpecGen::Invert) => {
@@ -14,7 +14,7 @@ use thiserror::Error;
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub enum Error {
     #[error("operation does not support the adjoint functor")]
-    #[diagnostic(help("each operation called inside an operation with compiler-generated adjoint specializations must support the adjoint functor"))]
+    #[diagnostic(help("each operation called inside an operation with compiler-generated adjoint specializations must support the adjoint functor; otherwise, declare the `body` and `adjoint` specializations separately and write the adjoint by hand"))]
     #[diagnostic(code("Qsc.AdjGen.MissingAdjFunctor"))]
     MissingAdjFunctor(#[label] Span),
 
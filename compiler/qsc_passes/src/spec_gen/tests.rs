@@ -8,7 +8,11 @@ use expect_test::{expect, Expect};
 use indoc::indoc;
 use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
-use qsc_hir::{validate::Validator, visit::Visitor};
+use qsc_hir::{
+    hir::{ItemKind, SpecBody},
+    validate::Validator,
+    visit::Visitor,
+};
 
 use crate::spec_gen::generate_specs;
 
@@ -1177,41 +1181,41 @@ fn generate_adj_invert_with_range_loop() {
                                             Expr 27 [162-163] [Type Int]: Lit: Int(2)
                         adj: SpecDecl 34 [67-181]: Impl:
                             Block 35 [104-181] [Type Unit]:
-                                Stmt 36 [114-175]: Expr: Expr 37 [0-0] [Type Unit]: Expr Block: Block 38 [0-0] [Type Unit]:
-                                    Stmt 39 [0-0]: Local (Immutable):
-                                        Pat 40 [0-0] [Type Range]: Bind: Ident 41 [0-0] "@range"
+                                Stmt 36 [114-175]: Expr: Expr 37 [114-175] [Type Unit]: Expr Block: Block 38 [114-175] [Type Unit]:
+                                    Stmt 39 [123-127]: Local (Immutable):
+                                        Pat 40 [123-127] [Type Range]: Bind: Ident 41 [123-127] "@range"
                                         Expr 42 [123-127] [Type Range]: Range:
                                             Expr 43 [123-124] [Type Int]: Lit: Int(0)
                                             <no step>
                                             Expr 44 [126-127] [Type Int]: Lit: Int(5)
-                                    Stmt 45 [0-0]: Expr: Expr 46 [0-0] [Type Unit]: For:
+                                    Stmt 45 [114-175]: Expr: Expr 46 [114-175] [Type Unit]: For:
                                         Pat 47 [118-119] [Type Int]: Bind: Ident 48 [118-119] "i"
-                                        Expr 49 [0-0] [Type Range]: Range:
-                                            Expr 50 [0-0] [Type Int]: BinOp (Add):
-                                                Expr 51 [0-0] [Type Int]: Field:
-                                                    Expr 52 [0-0] [Type Range]: Var: Local 41
+                                        Expr 49 [123-127] [Type Range]: Range:
+                                            Expr 50 [123-127] [Type Int]: BinOp (Add):
+                                                Expr 51 [123-127] [Type Int]: Field:
+                                                    Expr 52 [123-127] [Type Range]: Var: Local 41
                                                     Prim(Start)
-                                                Expr 53 [0-0] [Type Int]: BinOp (Mul):
-                                                    Expr 54 [0-0] [Type Int]: BinOp (Div):
-                                                        Expr 55 [0-0] [Type Int]: BinOp (Sub):
-                                                            Expr 56 [0-0] [Type Int]: Field:
-                                                                Expr 57 [0-0] [Type Range]: Var: Local 41
+                                                Expr 53 [123-127] [Type Int]: BinOp (Mul):
+                                                    Expr 54 [123-127] [Type Int]: BinOp (Div):
+                                                        Expr 55 [123-127] [Type Int]: BinOp (Sub):
+                                                            Expr 56 [123-127] [Type Int]: Field:
+                                                                Expr 57 [123-127] [Type Range]: Var: Local 41
                                                                 Prim(End)
-                                                            Expr 58 [0-0] [Type Int]: Field:
-                                                                Expr 59 [0-0] [Type Range]: Var: Local 41
+                                                            Expr 58 [123-127] [Type Int]: Field:
+                                                                Expr 59 [123-127] [Type Range]: Var: Local 41
                                                                 Prim(Start)
-                                                        Expr 60 [0-0] [Type Int]: Field:
-                                                            Expr 61 [0-0] [Type Range]: Var: Local 41
+                                                        Expr 60 [123-127] [Type Int]: Field:
+                                                            Expr 61 [123-127] [Type Range]: Var: Local 41
                                                             Prim(Step)
-                                                    Expr 62 [0-0] [Type Int]: Field:
-                                                        Expr 63 [0-0] [Type Range]: Var: Local 41
+                                                    Expr 62 [123-127] [Type Int]: Field:
+                                                        Expr 63 [123-127] [Type Range]: Var: Local 41
                                                         Prim(Step)
-                                            Expr 64 [0-0] [Type Int]: UnOp (Neg):
-                                                Expr 65 [0-0] [Type Int]: Field:
-                                                    Expr 66 [0-0] [Type Range]: Var: Local 41
+                                            Expr 64 [123-127] [Type Int]: UnOp (Neg):
+                                                Expr 65 [123-127] [Type Int]: Field:
+                                                    Expr 66 [123-127] [Type Range]: Var: Local 41
                                                     Prim(Step)
-                                            Expr 67 [0-0] [Type Int]: Field:
-                                                Expr 68 [0-0] [Type Range]: Var: Local 41
+                                            Expr 67 [123-127] [Type Int]: Field:
+                                                Expr 68 [123-127] [Type Range]: Var: Local 41
                                                 Prim(Start)
                                         Block 69 [128-175] [Type Unit]:
                                             Stmt 70 [160-165]: Semi: Expr 71 [160-164] [Type Unit]: Call:
@@ -1282,32 +1286,32 @@ fn generate_adj_invert_with_array_loop() {
                                             Expr 28 [169-170] [Type Int]: Lit: Int(2)
                         adj: SpecDecl 36 [67-188]: Impl:
                             Block 37 [104-188] [Type Unit]:
-                                Stmt 38 [114-182]: Expr: Expr 39 [0-0] [Type Unit]: Expr Block: Block 40 [0-0] [Type Unit]:
-                                    Stmt 41 [0-0]: Local (Immutable):
-                                        Pat 42 [0-0] [Type Int[]]: Bind: Ident 43 [0-0] "@array"
+                                Stmt 38 [114-182]: Expr: Expr 39 [114-182] [Type Unit]: Expr Block: Block 40 [114-182] [Type Unit]:
+                                    Stmt 41 [125-134]: Local (Immutable):
+                                        Pat 42 [125-134] [Type Int[]]: Bind: Ident 43 [125-134] "@array"
                                         Expr 44 [125-134] [Type Int[]]: Array:
                                             Expr 45 [126-127] [Type Int]: Lit: Int(0)
                                             Expr 46 [129-130] [Type Int]: Lit: Int(1)
                                             Expr 47 [132-133] [Type Int]: Lit: Int(2)
-                                    Stmt 48 [0-0]: Expr: Expr 49 [0-0] [Type Unit]: For:
-                                        Pat 50 [0-0] [Type Int]: Bind: Ident 51 [0-0] "@index"
-                                        Expr 52 [0-0] [Type Range]: Range:
-                                            Expr 53 [0-0] [Type Int]: BinOp (Sub):
-                                                Expr 54 [0-0] [Type Int]: Call:
-                                                    Expr 55 [0-0] [Type (Int[] -> Int)]: Var:
+                                    Stmt 48 [114-182]: Expr: Expr 49 [114-182] [Type Unit]: For:
+                                        Pat 50 [125-134] [Type Int]: Bind: Ident 51 [125-134] "@index"
+                                        Expr 52 [125-134] [Type Range]: Range:
+                                            Expr 53 [125-134] [Type Int]: BinOp (Sub):
+                                                Expr 54 [125-134] [Type Int]: Call:
+                                                    Expr 55 [125-134] [Type (Int[] -> Int)]: Var:
                                                         res: Item 1 (Package 0)
                                                         generics:
                                                             Int
-                                                    Expr 56 [0-0] [Type Int[]]: Var: Local 43
-                                                Expr 57 [0-0] [Type Int]: Lit: Int(1)
-                                            Expr 58 [0-0] [Type Int]: Lit: Int(-1)
-                                            Expr 59 [0-0] [Type Int]: Lit: Int(0)
+                                                    Expr 56 [125-134] [Type Int[]]: Var: Local 43
+                                                Expr 57 [125-134] [Type Int]: Lit: Int(1)
+                                            Expr 58 [125-134] [Type Int]: Lit: Int(-1)
+                                            Expr 59 [125-134] [Type Int]: Lit: Int(0)
                                         Block 60 [135-182] [Type Unit]:
-                                            Stmt 61 [0-0]: Local (Immutable):
+                                            Stmt 61 [118-121]: Local (Immutable):
                                                 Pat 62 [118-121] [Type Int]: Bind: Ident 63 [118-121] "val"
-                                                Expr 64 [0-0] [Type Int]: Index:
-                                                    Expr 65 [0-0] [Type Int[]]: Var: Local 43
-                                                    Expr 66 [0-0] [Type Int]: Var: Local 51
+                                                Expr 64 [125-134] [Type Int]: Index:
+                                                    Expr 65 [125-134] [Type Int[]]: Var: Local 43
+                                                    Expr 66 [125-134] [Type Int]: Var: Local 51
                                             Stmt 67 [167-172]: Semi: Expr 68 [167-171] [Type Unit]: Call:
                                                 Expr 69 [167-168] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                     Expr 70 [167-168] [Type (Int => Unit is Adj)]: Var: Item 1
@@ -1397,32 +1401,32 @@ fn generate_adj_invert_with_nested_loops() {
                                             Expr 49 [299-300] [Type Int]: Lit: Int(4)
                         adj: SpecDecl 59 [67-318]: Impl:
                             Block 60 [104-318] [Type Unit]:
-                                Stmt 61 [114-312]: Expr: Expr 62 [0-0] [Type Unit]: Expr Block: Block 63 [0-0] [Type Unit]:
-                                    Stmt 64 [0-0]: Local (Immutable):
-                                        Pat 65 [0-0] [Type Int[]]: Bind: Ident 66 [0-0] "@array"
+                                Stmt 61 [114-312]: Expr: Expr 62 [114-312] [Type Unit]: Expr Block: Block 63 [114-312] [Type Unit]:
+                                    Stmt 64 [125-134]: Local (Immutable):
+                                        Pat 65 [125-134] [Type Int[]]: Bind: Ident 66 [125-134] "@array"
                                         Expr 67 [125-134] [Type Int[]]: Array:
                                             Expr 68 [126-127] [Type Int]: Lit: Int(0)
                                             Expr 69 [129-130] [Type Int]: Lit: Int(1)
                                             Expr 70 [132-133] [Type Int]: Lit: Int(2)
-                                    Stmt 71 [0-0]: Expr: Expr 72 [0-0] [Type Unit]: For:
-                                        Pat 73 [0-0] [Type Int]: Bind: Ident 74 [0-0] "@index"
-                                        Expr 75 [0-0] [Type Range]: Range:
-                                            Expr 76 [0-0] [Type Int]: BinOp (Sub):
-                                                Expr 77 [0-0] [Type Int]: Call:
-                                                    Expr 78 [0-0] [Type (Int[] -> Int)]: Var:
+                                    Stmt 71 [114-312]: Expr: Expr 72 [114-312] [Type Unit]: For:
+                                        Pat 73 [125-134] [Type Int]: Bind: Ident 74 [125-134] "@index"
+                                        Expr 75 [125-134] [Type Range]: Range:
+                                            Expr 76 [125-134] [Type Int]: BinOp (Sub):
+                                                Expr 77 [125-134] [Type Int]: Call:
+                                                    Expr 78 [125-134] [Type (Int[] -> Int)]: Var:
                                                         res: Item 1 (Package 0)
                                                         generics:
                                                             Int
-                                                    Expr 79 [0-0] [Type Int[]]: Var: Local 66
-                                                Expr 80 [0-0] [Type Int]: Lit: Int(1)
-                                            Expr 81 [0-0] [Type Int]: Lit: Int(-1)
-                                            Expr 82 [0-0] [Type Int]: Lit: Int(0)
+                                                    Expr 79 [125-134] [Type Int[]]: Var: Local 66
+                                                Expr 80 [125-134] [Type Int]: Lit: Int(1)
+                                            Expr 81 [125-134] [Type Int]: Lit: Int(-1)
+                                            Expr 82 [125-134] [Type Int]: Lit: Int(0)
                                         Block 83 [135-312] [Type Unit]:
-                                            Stmt 84 [0-0]: Local (Immutable):
+                                            Stmt 84 [118-121]: Local (Immutable):
                                                 Pat 85 [118-121] [Type Int]: Bind: Ident 86 [118-121] "val"
-                                                Expr 87 [0-0] [Type Int]: Index:
-                                                    Expr 88 [0-0] [Type Int[]]: Var: Local 66
-                                                    Expr 89 [0-0] [Type Int]: Var: Local 74
+                                                Expr 87 [125-134] [Type Int]: Index:
+                                                    Expr 88 [125-134] [Type Int[]]: Var: Local 66
+                                                    Expr 89 [125-134] [Type Int]: Var: Local 74
                                             Stmt 90 [167-197]: Local (Immutable):
                                                 Pat 91 [171-174] [Type Bool[]]: Bind: Ident 92 [171-174] "arr"
                                                 Expr 93 [177-196] [Type Bool[]]: Array:
@@ -1433,29 +1437,29 @@ fn generate_adj_invert_with_nested_loops() {
                                                 Expr 99 [297-298] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                     Expr 100 [297-298] [Type (Int => Unit is Adj)]: Var: Item 1
                                                 Expr 101 [299-300] [Type Int]: Lit: Int(4)
-                                            Stmt 102 [210-284]: Expr: Expr 103 [0-0] [Type Unit]: Expr Block: Block 104 [0-0] [Type Unit]:
-                                                Stmt 105 [0-0]: Local (Immutable):
-                                                    Pat 106 [0-0] [Type Bool[]]: Bind: Ident 107 [0-0] "@array"
+                                            Stmt 102 [210-284]: Expr: Expr 103 [210-284] [Type Unit]: Expr Block: Block 104 [210-284] [Type Unit]:
+                                                Stmt 105 [221-224]: Local (Immutable):
+                                                    Pat 106 [221-224] [Type Bool[]]: Bind: Ident 107 [221-224] "@array"
                                                     Expr 108 [221-224] [Type Bool[]]: Var: Local 92
-                                                Stmt 109 [0-0]: Expr: Expr 110 [0-0] [Type Unit]: For:
-                                                    Pat 111 [0-0] [Type Int]: Bind: Ident 112 [0-0] "@index"
-                                                    Expr 113 [0-0] [Type Range]: Range:
-                                                        Expr 114 [0-0] [Type Int]: BinOp (Sub):
-                                                            Expr 115 [0-0] [Type Int]: Call:
-                                                                Expr 116 [0-0] [Type (Bool[] -> Int)]: Var:
+                                                Stmt 109 [210-284]: Expr: Expr 110 [210-284] [Type Unit]: For:
+                                                    Pat 111 [221-224] [Type Int]: Bind: Ident 112 [221-224] "@index"
+                                                    Expr 113 [221-224] [Type Range]: Range:
+                                                        Expr 114 [221-224] [Type Int]: BinOp (Sub):
+                                                            Expr 115 [221-224] [Type Int]: Call:
+                                                                Expr 116 [221-224] [Type (Bool[] -> Int)]: Var:
                                                                     res: Item 1 (Package 0)
                                                                     generics:
                                                                         Bool
-                                                                Expr 117 [0-0] [Type Bool[]]: Var: Local 107
-                                                            Expr 118 [0-0] [Type Int]: Lit: Int(1)
-                                                        Expr 119 [0-0] [Type Int]: Lit: Int(-1)
-                                                        Expr 120 [0-0] [Type Int]: Lit: Int(0)
+                                                                Expr 117 [221-224] [Type Bool[]]: Var: Local 107
+                                                            Expr 118 [221-224] [Type Int]: Lit: Int(1)
+                                                        Expr 119 [221-224] [Type Int]: Lit: Int(-1)
+                                                        Expr 120 [221-224] [Type Int]: Lit: Int(0)
                                                     Block 121 [225-284] [Type Unit]:
-                                                        Stmt 122 [0-0]: Local (Immutable):
+                                                        Stmt 122 [214-217]: Local (Immutable):
                                                             Pat 123 [214-217] [Type Bool]: Bind: Ident 124 [214-217] "val"
-                                                            Expr 125 [0-0] [Type Bool]: Index:
-                                                                Expr 126 [0-0] [Type Bool[]]: Var: Local 107
-                                                                Expr 127 [0-0] [Type Int]: Var: Local 112
+                                                            Expr 125 [221-224] [Type Bool]: Index:
+                                                                Expr 126 [221-224] [Type Bool[]]: Var: Local 107
+                                                                Expr 127 [221-224] [Type Int]: Var: Local 112
                                                         Stmt 128 [265-270]: Semi: Expr 129 [265-269] [Type Unit]: Call:
                                                             Expr 130 [265-266] [Type (Int => Unit is Adj)]: UnOp (Functor Adj):
                                                                 Expr 131 [265-266] [Type (Int => Unit is Adj)]: Var: Item 1
@@ -1941,11 +1945,13 @@ fn lambda_adj_calls_non_adj() {
         &expect![[r#"
             [
                 AdjGen(
-                    MissingAdjFunctor(
-                        Span {
-                            lo: 148,
-                            hi: 149,
-                        },
+                    LogicSep(
+                        Measurement(
+                            Span {
+                                lo: 148,
+                                hi: 152,
+                            },
+                        ),
                     ),
                 ),
             ]
@@ -2124,3 +2130,60 @@ fn op_array_unsupported_functors_with_lambdas() {
         "#]],
     );
 }
+
+#[test]
+fn generated_specs_are_distinguished_from_user_specs() {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace test {
+                    operation A(q : Qubit) : Unit is Adj + Ctl {
+                        body ... {}
+                        adjoint self;
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let mut unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    let errors = generate_specs(store.core(), &mut unit.package, &mut unit.assigner);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let specs = unit
+        .package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            ItemKind::Callable(decl) => Some(
+                decl.specs()
+                    .map(|(kind, spec)| {
+                        assert!(matches!(spec.body, SpecBody::Impl(..)));
+                        match spec.generated {
+                            Some(gen) => format!("{kind}: generated ({gen:?})"),
+                            None => format!("{kind}: user"),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            _ => None,
+        })
+        .expect("package should have a callable");
+    expect![[r#"
+        body: user
+        adjoint: generated (Slf)
+        controlled: generated (Distribute)
+        controlled adjoint: generated (Slf)"#]]
+    .assert_eq(&specs);
+}
@@ -11,16 +11,10 @@ use qsc::ast::visit::{self, Visitor};
 use qsc::display::{CodeDisplay, Lookup};
 use qsc::hir::{ItemKind, Package, PackageId, Visibility};
 use qsc::line_column::{Encoding, Position, Range};
-use qsc::resolve::{Local, LocalKind};
+use qsc::resolve::{Local, LocalKind, PRELUDE};
 use rustc_hash::FxHashSet;
 use std::rc::Rc;
 
-const PRELUDE: [&str; 3] = [
-    "Microsoft.Quantum.Canon",
-    "Microsoft.Quantum.Core",
-    "Microsoft.Quantum.Intrinsic",
-];
-
 pub(crate) fn get_completions(
     compilation: &Compilation,
     source_name: &str,
@@ -71,7 +65,7 @@ pub(crate) fn get_completions(
     // The PRELUDE namespaces are always implicitly opened.
     context_finder
         .opens
-        .extend(PRELUDE.into_iter().map(|ns| (Rc::from(ns), None)));
+        .extend(PRELUDE.iter().map(|&ns| (Rc::from(ns), None)));
 
     let mut builder = CompletionListBuilder::new();
 
@@ -210,7 +204,15 @@ impl CompletionListBuilder {
     }
 
     fn push_item_decl_keywords(&mut self) {
-        static ITEM_KEYWORDS: [&str; 5] = ["operation", "open", "internal", "function", "newtype"];
+        static ITEM_KEYWORDS: [&str; 7] = [
+            "operation",
+            "open",
+            "export",
+            "internal",
+            "function",
+            "newtype",
+            "struct",
+        ];
 
         self.push_completions(
             ITEM_KEYWORDS
@@ -334,9 +336,9 @@ impl CompletionListBuilder {
     }
 
     fn push_expr_keywords(&mut self) {
-        static EXPR_KEYWORDS: [&str; 11] = [
+        static EXPR_KEYWORDS: [&str; 13] = [
             "if", "for", "in", "within", "apply", "repeat", "until", "fixup", "set", "while",
-            "fail",
+            "fail", "new", "match",
         ];
 
         self.push_completions(
@@ -617,7 +619,7 @@ enum Context {
 impl Visitor<'_> for ContextFinder {
     fn visit_namespace(&mut self, namespace: &'_ qsc::ast::Namespace) {
         if span_contains(namespace.span, self.offset) {
-            self.current_namespace_name = Some(namespace.name.name.clone());
+            self.current_namespace_name = Some(Rc::from(&*namespace.full_name()));
             self.context = Context::Namespace;
             self.opens = vec![];
             self.start_of_namespace = None;
@@ -632,8 +634,8 @@ impl Visitor<'_> for ContextFinder {
 
         if let qsc::ast::ItemKind::Open(name, alias) = &*item.kind {
             self.opens.push((
-                name.name.clone(),
-                alias.as_ref().map(|alias| alias.name.clone()),
+                Rc::from(&*name.name),
+                alias.as_ref().map(|alias| Rc::from(&*alias.name)),
             ));
         }
 
@@ -655,6 +657,9 @@ impl Visitor<'_> for ContextFinder {
     fn visit_block(&mut self, block: &'_ qsc::ast::Block) {
         if span_contains(block.span, self.offset) {
             self.context = Context::Block;
+            // Walk into the block so that any opens it contains are collected,
+            // since they are in scope at the cursor.
+            visit::walk_block(self, block);
         }
     }
 }
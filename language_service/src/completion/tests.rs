@@ -508,6 +508,39 @@ fn in_block_no_auto_open() {
     );
 }
 
+#[test]
+fn in_block_with_local_open() {
+    check(
+        indoc! {r#"
+    namespace Test {
+        operation Foo() : Unit {
+            open FakeStdLib;
+            {
+                ↘
+            }
+        }
+    }"#},
+        &["Fake"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "Fake",
+                        kind: Function,
+                        sort_text: Some(
+                            "0700Fake",
+                        ),
+                        detail: Some(
+                            "operation Fake() : Unit",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
 #[test]
 fn in_block_with_alias() {
     check(
@@ -2,7 +2,7 @@
 // Licensed under the MIT License.
 
 use std::mem::replace;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::compilation::Compilation;
 use crate::qsc_utils::{find_ident, span_contains, span_touches};
@@ -88,8 +88,8 @@ pub(crate) trait Handler<'package> {
 pub(crate) struct LocatorContext<'package> {
     pub(crate) current_callable: Option<&'package ast::CallableDecl>,
     pub(crate) lambda_params: Vec<&'package ast::Pat>,
-    pub(crate) current_item_doc: Rc<str>,
-    pub(crate) current_namespace: Rc<str>,
+    pub(crate) current_item_doc: Arc<str>,
+    pub(crate) current_namespace: Arc<str>,
     pub(crate) in_params: bool,
     pub(crate) in_lambda_params: bool,
     pub(crate) current_udt_id: Option<&'package hir::ItemId>,
@@ -113,12 +113,12 @@ impl<'inner, 'package, T> Locator<'inner, 'package, T> {
             offset,
             compilation,
             context: LocatorContext {
-                current_namespace: Rc::from(""),
+                current_namespace: Arc::from(""),
                 current_callable: None,
                 in_params: false,
                 lambda_params: vec![],
                 in_lambda_params: false,
-                current_item_doc: Rc::from(""),
+                current_item_doc: Arc::from(""),
                 current_udt_id: None,
             },
         }
@@ -128,7 +128,7 @@ impl<'inner, 'package, T> Locator<'inner, 'package, T> {
 impl<'inner, 'package, T: Handler<'package>> Visitor<'package> for Locator<'inner, 'package, T> {
     fn visit_namespace(&mut self, namespace: &'package ast::Namespace) {
         if span_contains(namespace.span, self.offset) {
-            self.context.current_namespace = namespace.name.name.clone();
+            self.context.current_namespace = namespace.full_name();
             walk_namespace(self, namespace);
         }
     }
@@ -147,11 +147,12 @@ impl<'inner, 'package, T: Handler<'package>> Visitor<'package> for Locator<'inne
 
                         // walk callable decl
                         decl.generics.iter().for_each(|p| {
-                            if span_touches(p.span, self.offset) {
+                            if span_touches(p.ty.span, self.offset) {
                                 if let Some(resolve::Res::Param(param_id)) =
-                                    self.compilation.get_res(p.id)
+                                    self.compilation.get_res(p.ty.id)
                                 {
-                                    self.inner.at_type_param_def(&self.context, p, *param_id);
+                                    self.inner
+                                        .at_type_param_def(&self.context, &p.ty, *param_id);
                                 }
                             }
                         });
@@ -236,8 +237,12 @@ impl<'inner, 'package, T: Handler<'package>> Visitor<'package> for Locator<'inne
                 if let Some(resolve::Res::Param(param_id)) = self.compilation.get_res(param.id) {
                     if let Some(curr) = self.context.current_callable {
                         if let Some(def_name) = curr.generics.get(usize::from(*param_id)) {
-                            self.inner
-                                .at_type_param_ref(&self.context, param, *param_id, def_name);
+                            self.inner.at_type_param_ref(
+                                &self.context,
+                                param,
+                                *param_id,
+                                &def_name.ty,
+                            );
                         }
                     }
                 }
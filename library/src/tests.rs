@@ -70,7 +70,7 @@ pub fn test_expression_with_lib_and_profile_and_sim(
         .eval_entry_with_sim(sim, &mut out)
         .expect("test should run successfully");
 
-    match (&expected, result) {
+    match (&expected, result.erase_udt()) {
         (&Value::Tuple(tup1), Value::Tuple(tup2)) if tup1.len() == tup2.len() => {
             // If both values are tuples of the same length, we crack them open and compare elements
             for (value1, value2) in tup1.iter().zip(tup2.iter()) {
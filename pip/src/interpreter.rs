@@ -7,20 +7,19 @@ use crate::{
 };
 use miette::Report;
 use num_bigint::BigUint;
-use num_complex::Complex64;
 use pyo3::{
     create_exception,
     exceptions::PyException,
     prelude::*,
     pyclass::CompareOp,
     types::PyList,
-    types::{PyDict, PyString, PyTuple},
+    types::{PyComplex, PyDict, PyString, PyTuple},
 };
 use qsc::{
     fir,
     interpret::{
         self,
-        output::{Error, Receiver},
+        output::{self, Error, Receiver},
         Value,
     },
     project::{FileSystem, Manifest, ManifestDescriptor},
@@ -28,7 +27,7 @@ use qsc::{
     LanguageFeatures, PackageType, SourceMap,
 };
 use resource_estimator::{self as re, estimate_expr};
-use std::fmt::Write;
+use std::{fmt::Write, rc::Rc};
 
 #[pymodule]
 fn _native(py: Python, m: &PyModule) -> PyResult<()> {
@@ -432,6 +431,7 @@ impl IntoPy<PyObject> for ValueWrapper {
             Value::BigInt(val) => val.into_py(py),
             Value::Int(val) => val.into_py(py),
             Value::Double(val) => val.into_py(py),
+            Value::Complex(val) => PyComplex::from_doubles(py, val.re, val.im).into_py(py),
             Value::Bool(val) => val.into_py(py),
             Value::String(val) => val.into_py(py),
             Value::Result(val) => if val.unwrap_bool() {
@@ -458,6 +458,7 @@ impl IntoPy<PyObject> for ValueWrapper {
             Value::Array(val) => {
                 PyList::new(py, val.iter().map(|v| ValueWrapper(v.clone()).into_py(py))).into_py(py)
             }
+            Value::Udt(_, val) => ValueWrapper(Rc::unwrap_or_clone(val)).into_py(py),
             _ => format!("<{}> {}", Value::type_name(&self.0), &self.0).into_py(py),
         }
     }
@@ -469,13 +470,14 @@ struct OptionalCallbackReceiver<'a> {
 }
 
 impl Receiver for OptionalCallbackReceiver<'_> {
-    fn state(
-        &mut self,
-        state: Vec<(BigUint, Complex64)>,
-        qubit_count: usize,
-    ) -> core::result::Result<(), Error> {
+    fn state_dump(&mut self, mut dump: output::StateDump) -> core::result::Result<(), Error> {
         if let Some(callback) = &self.callback {
-            let out = DisplayableOutput::State(DisplayableState(state, qubit_count));
+            // The callback receives the whole state as a single Python object.
+            let mut state = Vec::new();
+            while let Some(chunk) = dump.next_chunk() {
+                state.extend(chunk.into_iter().map(|entry| (entry.id, entry.amplitude)));
+            }
+            let out = DisplayableOutput::State(DisplayableState(state, dump.qubit_count()));
             callback
                 .call1(
                     self.py,
@@ -7,7 +7,11 @@ mod tests;
 use super::LogicalResources;
 use num_bigint::BigUint;
 use num_complex::Complex;
-use qsc::{interpret::Value, Backend};
+use qsc::{
+    codegen::circuit::{Circuit, OperationKind},
+    interpret::Value,
+    Backend,
+};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use rustc_hash::FxHashMap;
 use std::{
@@ -85,6 +89,48 @@ impl LogicalCounter {
         }
     }
 
+    /// Counts the operations of a circuit, with a new qubit for each of its
+    /// wires.  Operations that are not in the instruction set, such as custom
+    /// unitaries, are not counted.
+    pub fn apply_circuit(&mut self, circuit: &Circuit) {
+        let qubits: Vec<_> = circuit
+            .wires
+            .iter()
+            .map(|_| self.qubit_allocate())
+            .collect();
+        for op in &circuit.operations {
+            let controls: Vec<_> = op.controls.iter().map(|&c| qubits[c]).collect();
+            let targets: Vec<_> = op.targets.iter().map(|&t| qubits[t]).collect();
+            let theta = op.params.first().copied().unwrap_or_default();
+            match (op.kind, op.gate.as_str(), &controls[..], &targets[..]) {
+                (OperationKind::Measurement(_), _, [], &[q]) => {
+                    self.m(q);
+                }
+                (OperationKind::Reset, _, [], &[q]) => self.reset(q),
+                (_, "X", &[c0, c1], &[q]) => self.ccx(c0, c1, q),
+                (_, "X", &[c], &[q]) => self.cx(c, q),
+                (_, "Y", &[c], &[q]) => self.cy(c, q),
+                (_, "Z", &[c], &[q]) => self.cz(c, q),
+                (_, "H", [], &[q]) => self.h(q),
+                (_, "S", [], &[q]) => self.s(q),
+                (_, "S†", [], &[q]) => self.sadj(q),
+                (_, "T", [], &[q]) => self.t(q),
+                (_, "T†", [], &[q]) => self.tadj(q),
+                (_, "X", [], &[q]) => self.x(q),
+                (_, "Y", [], &[q]) => self.y(q),
+                (_, "Z", [], &[q]) => self.z(q),
+                (_, "Rx", [], &[q]) => self.rx(theta, q),
+                (_, "Ry", [], &[q]) => self.ry(theta, q),
+                (_, "Rz", [], &[q]) => self.rz(theta, q),
+                (_, "Rxx", [], &[q0, q1]) => self.rxx(theta, q0, q1),
+                (_, "Ryy", [], &[q0, q1]) => self.ryy(theta, q0, q1),
+                (_, "Rzz", [], &[q0, q1]) => self.rzz(theta, q0, q1),
+                (_, "SWAP", [], &[q0, q1]) => self.swap(q0, q1),
+                _ => {}
+            }
+        }
+    }
+
     fn schedule_r(&mut self, q: usize) {
         let level = self.level_at(q);
 
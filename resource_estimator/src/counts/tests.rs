@@ -8,9 +8,11 @@ use std::convert::Into;
 use expect_test::{expect, Expect};
 use indoc::indoc;
 use qsc::{
+    codegen::circuit,
+    compile,
     interpret::{GenericReceiver, Interpreter},
     target::Profile,
-    LanguageFeatures, PackageType, SourceMap,
+    LanguageFeatures, PackageStore, PackageType, RuntimeCapabilityFlags, SourceMap,
 };
 
 use super::LogicalCounter;
@@ -206,3 +208,59 @@ fn account_for_estimates_works() {
         "]],
     );
 }
+
+#[test]
+fn circuit_counts_both_branches_on_measurement() {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let source_map = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    open Microsoft.Quantum.Measurement;
+                    @EntryPoint()
+                    operation Main() : Result[] {
+                        use (a, b, c) = (Qubit(), Qubit(), Qubit());
+                        T(a);
+                        CCNOT(a, b, c);
+                        Rx(1.0, b);
+                        Rz(1.0, c);
+                        if M(a) == One {
+                            T(b);
+                        } else {
+                            X(b);
+                        }
+                        [MResetZ(a), MResetZ(b), MResetZ(c)]
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let (unit, errors) = compile::compile(
+        &store,
+        &[(std, None)],
+        source_map,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = store.insert(unit);
+    let circuit = circuit::from_program(&store, package, false).expect("circuit should be built");
+    let mut counter = LogicalCounter::default();
+    counter.apply_circuit(&circuit);
+    expect![["
+        LogicalResources {
+            num_qubits: 3,
+            t_count: 2,
+            rotation_count: 2,
+            rotation_depth: 1,
+            ccz_count: 1,
+            measurement_count: 4,
+        }
+    "]]
+    .assert_debug_eq(&counter.logical_resources());
+}
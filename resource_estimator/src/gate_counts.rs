@@ -0,0 +1,329 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::{counts::LogicalCounter, LogicalResources};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc::{interpret::Value, Backend};
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use std::{collections::BTreeMap, f64::consts::PI};
+
+/// Largest angle precision (in bits) that is tracked individually.  Rotations
+/// whose angles need more bits are reported as arbitrary rotations.
+const MAX_ROTATION_PRECISION: u32 = 32;
+
+/// Gate counts reported by the [`GateCounter`]
+///
+/// Serializes with the same field names as the logical resource counts
+/// consumed by the physical resource estimation, extended by the number of
+/// CNOT gates and a breakdown of rotations by angle precision.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GateCounts {
+    pub num_qubits: usize,
+    pub t_count: usize,
+    pub rotation_count: usize,
+    pub rotation_depth: usize,
+    pub ccz_count: usize,
+    pub measurement_count: usize,
+    /// Number of CNOT gates, where other two-qubit Cliffords are counted by
+    /// the number of CNOTs needed to implement them
+    pub cnot_count: usize,
+    /// Number of rotations with angle `m·2π/2^k` for odd `m`, keyed by `k`
+    pub rotations_by_precision: BTreeMap<u32, usize>,
+    /// Number of rotations whose angle cannot be represented with at most
+    /// 32 bits of precision, including rotations accounted for by estimates
+    pub arbitrary_rotation_count: usize,
+}
+
+impl From<&GateCounts> for LogicalResources {
+    fn from(counts: &GateCounts) -> Self {
+        Self {
+            num_qubits: counts.num_qubits,
+            t_count: counts.t_count,
+            rotation_count: counts.rotation_count,
+            rotation_depth: counts.rotation_depth,
+            ccz_count: counts.ccz_count,
+            measurement_count: counts.measurement_count,
+        }
+    }
+}
+
+/// Gate counter implementation
+///
+/// This counter extends the [`LogicalCounter`] by tracking CNOT gates and by
+/// binning rotations by the precision of their angle, which are the key
+/// inputs to estimate the cost of rotation synthesis in a fault-tolerant
+/// setting.  Estimate caching and repetition are honored for all counts.
+#[derive(Default)]
+pub struct GateCounter {
+    /// Counter for the logical resources
+    logical: LogicalCounter,
+    /// Counts that are not tracked by the logical counter
+    extra: ExtraCounts,
+    /// Caching stack, with the counts at the beginning of each cached block
+    caching_stack: Vec<(String, ExtraCounts)>,
+    /// Counts of completed cached blocks
+    cached_counts: FxHashMap<String, ExtraCounts>,
+    /// Repeating, with the repetition count and the counts at the beginning
+    /// of each repeated block
+    repeats: Vec<(usize, ExtraCounts)>,
+}
+
+impl GateCounter {
+    #[must_use]
+    pub fn gate_counts(&self) -> GateCounts {
+        let logical = self.logical.logical_resources();
+        let binned: usize = self.extra.rotations.values().sum();
+
+        GateCounts {
+            num_qubits: logical.num_qubits,
+            t_count: logical.t_count,
+            rotation_count: logical.rotation_count,
+            rotation_depth: logical.rotation_depth,
+            ccz_count: logical.ccz_count,
+            measurement_count: logical.measurement_count,
+            cnot_count: self.extra.cnot_count,
+            rotations_by_precision: self.extra.rotations.clone(),
+            arbitrary_rotation_count: logical.rotation_count.saturating_sub(binned),
+        }
+    }
+
+    fn count_rotation(&mut self, theta: f64) {
+        if let Some(precision) = rotation_precision(theta) {
+            *self.extra.rotations.entry(precision).or_default() += 1;
+        }
+    }
+
+    fn begin_caching(&mut self, label: String, is_new: bool) {
+        if is_new {
+            self.caching_stack.push((label, self.extra.clone()));
+        } else if let Some(cached) = self.cached_counts.get(&label) {
+            self.extra.add_scaled(cached, 1);
+        }
+    }
+
+    fn end_caching(&mut self) {
+        if let Some((label, start)) = self.caching_stack.pop() {
+            let counts = self.extra.difference(&start);
+            self.cached_counts.insert(label, counts);
+        }
+    }
+
+    fn end_repeat(&mut self) {
+        if let Some((count, start)) = self.repeats.pop() {
+            // We skip one iteration, which was already done explicitly
+            // between begin_repeat and end_repeat
+            if count > 1 {
+                let counts = self.extra.difference(&start);
+                self.extra.add_scaled(&counts, count - 1);
+            }
+        }
+    }
+}
+
+/// Returns the smallest `k` such that `theta` is an odd multiple of `2π/2^k`,
+/// if `theta` is not a Clifford or T rotation and `k` is at most
+/// [`MAX_ROTATION_PRECISION`].
+fn rotation_precision(theta: f64) -> Option<u32> {
+    (4..=MAX_ROTATION_PRECISION).find(|k| {
+        let unit = PI / f64::from(1_u32 << (k - 1));
+        let multiple = (theta / unit).round();
+        ((multiple * unit) - theta).abs() <= f64::EPSILON && (multiple as i64).rem_euclid(2) == 1
+    })
+}
+
+impl Backend for GateCounter {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.logical.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.extra.cnot_count += 1;
+        self.logical.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.extra.cnot_count += 1;
+        self.logical.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.extra.cnot_count += 1;
+        self.logical.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.logical.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.logical.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.logical.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.logical.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.count_rotation(theta);
+        self.logical.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.extra.cnot_count += 2;
+        self.count_rotation(theta);
+        self.logical.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.count_rotation(theta);
+        self.logical.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.extra.cnot_count += 2;
+        self.count_rotation(theta);
+        self.logical.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.count_rotation(theta);
+        self.logical.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.extra.cnot_count += 2;
+        self.count_rotation(theta);
+        self.logical.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.logical.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.logical.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.extra.cnot_count += 3;
+        self.logical.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.logical.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.logical.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.logical.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.logical.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.logical.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.logical.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.logical.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.logical.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.logical.qubit_is_zero(q)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        match name {
+            "BeginEstimateCaching" => {
+                let values = arg.clone().unwrap_tuple();
+                let label = format!(
+                    "{}-{}",
+                    values[0].clone().unwrap_string(),
+                    values[1].clone().unwrap_int()
+                );
+                let result = self.logical.custom_intrinsic(name, arg);
+                if let Some(Ok(Value::Bool(is_new))) = &result {
+                    self.begin_caching(label, *is_new);
+                }
+                result
+            }
+            "EndEstimateCaching" => {
+                let result = self.logical.custom_intrinsic(name, arg);
+                if let Some(Ok(_)) = &result {
+                    self.end_caching();
+                }
+                result
+            }
+            "BeginRepeatEstimatesInternal" => {
+                let count = arg.clone().unwrap_int();
+                let result = self.logical.custom_intrinsic(name, arg);
+                if let Some(Ok(_)) = &result {
+                    self.repeats.push((count as usize, self.extra.clone()));
+                }
+                result
+            }
+            "EndRepeatEstimatesInternal" => {
+                self.end_repeat();
+                self.logical.custom_intrinsic(name, arg)
+            }
+            _ => self.logical.custom_intrinsic(name, arg),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct ExtraCounts {
+    cnot_count: usize,
+    rotations: BTreeMap<u32, usize>,
+}
+
+impl ExtraCounts {
+    fn difference(&self, start: &Self) -> Self {
+        Self {
+            cnot_count: self.cnot_count - start.cnot_count,
+            rotations: self
+                .rotations
+                .iter()
+                .map(|(precision, count)| {
+                    (
+                        *precision,
+                        count - start.rotations.get(precision).copied().unwrap_or(0),
+                    )
+                })
+                .filter(|(_, count)| *count != 0)
+                .collect(),
+        }
+    }
+
+    fn add_scaled(&mut self, other: &Self, factor: usize) {
+        self.cnot_count += other.cnot_count * factor;
+        for (precision, count) in &other.rotations {
+            *self.rotations.entry(*precision).or_default() += count * factor;
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use std::convert::Into;
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc::{
+    interpret::{GenericReceiver, Interpreter},
+    target::Profile,
+    LanguageFeatures, PackageType, SourceMap,
+};
+
+use super::GateCounter;
+
+fn verify_gate_counts(source: &str, entry: Option<&str>, expect: &Expect) {
+    let source_map = SourceMap::new([("test".into(), source.into())], entry.map(Into::into));
+    let mut interpreter = Interpreter::new(
+        true,
+        source_map,
+        PackageType::Exe,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    )
+    .expect("compilation should succeed");
+    let mut counter = GateCounter::default();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut counter, &mut out)
+        .expect("evaluation should succeed");
+    expect.assert_debug_eq(&counter.gate_counts());
+}
+
+#[test]
+fn gates_are_counted() {
+    verify_gate_counts(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Math;
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    use qs = Qubit[3];
+                    T(qs[0]);
+                    Adjoint T(qs[1]);
+                    CNOT(qs[0], qs[1]);
+                    CZ(qs[1], qs[2]);
+                    SWAP(qs[0], qs[2]);
+                    Rz(PI() / 4.0, qs[0]);
+                    Rz(PI() / 2.0, qs[0]);
+                    Rz(PI() / 8.0, qs[0]);
+                    Rx(-3.0 * PI() / 8.0, qs[1]);
+                    Ry(PI() / 1024.0, qs[2]);
+                    Rzz(PI() / 16.0, qs[0], qs[1]);
+                    Rz(1.0, qs[0]);
+                }
+            }
+        "},
+        None,
+        &expect![[r#"
+            GateCounts {
+                num_qubits: 3,
+                t_count: 3,
+                rotation_count: 5,
+                rotation_depth: 4,
+                ccz_count: 0,
+                measurement_count: 0,
+                cnot_count: 7,
+                rotations_by_precision: {
+                    4: 2,
+                    5: 1,
+                    11: 1,
+                },
+                arbitrary_rotation_count: 1,
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn estimate_caching_works() {
+    verify_gate_counts(
+        indoc! {r#"
+            namespace Test {
+                open Microsoft.Quantum.Math;
+                open Microsoft.Quantum.ResourceEstimation;
+
+                operation Rotate(qs: Qubit[]) : Unit {
+                    for q in qs {
+                        Rz(PI() / 8.0, q);
+                    }
+                    CNOT(qs[0], qs[1]);
+                }
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    use qs = Qubit[2];
+                    for _ in 1..10 {
+                        if BeginEstimateCaching("Rotate", SingleVariant()) {
+                            Rotate(qs);
+                            EndEstimateCaching();
+                        }
+                    }
+                }
+            }
+        "#},
+        None,
+        &expect![[r#"
+            GateCounts {
+                num_qubits: 2,
+                t_count: 0,
+                rotation_count: 20,
+                rotation_depth: 10,
+                ccz_count: 0,
+                measurement_count: 0,
+                cnot_count: 10,
+                rotations_by_precision: {
+                    4: 20,
+                },
+                arbitrary_rotation_count: 0,
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn estimate_repeat_works() {
+    verify_gate_counts(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Math;
+                open Microsoft.Quantum.ResourceEstimation;
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    use qs = Qubit[2];
+                    within {
+                        RepeatEstimates(10);
+                    }
+                    apply {
+                        Rz(PI() / 16.0, qs[0]);
+                        CNOT(qs[0], qs[1]);
+                    }
+                }
+            }
+        "},
+        None,
+        &expect![[r#"
+            GateCounts {
+                num_qubits: 2,
+                t_count: 0,
+                rotation_count: 10,
+                rotation_depth: 10,
+                ccz_count: 0,
+                measurement_count: 0,
+                cnot_count: 10,
+                rotations_by_precision: {
+                    5: 10,
+                },
+                arbitrary_rotation_count: 0,
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn accounted_rotations_are_arbitrary() {
+    verify_gate_counts(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.ResourceEstimation;
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    use qs = Qubit[2];
+                    AccountForEstimates([TCount(2), RotationCount(3), RotationDepth(3)], PSSPCLayout(), qs);
+                }
+            }
+        "},
+        None,
+        &expect![[r#"
+            GateCounts {
+                num_qubits: 2,
+                t_count: 2,
+                rotation_count: 3,
+                rotation_depth: 3,
+                ccz_count: 0,
+                measurement_count: 0,
+                cnot_count: 0,
+                rotations_by_precision: {},
+                arbitrary_rotation_count: 3,
+            }
+        "#]],
+    );
+}
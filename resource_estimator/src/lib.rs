@@ -17,15 +17,22 @@ mod counts;
 /// Provides traits to define a fault-tolerant quantum computing architecture
 /// and functions to perform resource estimation on such architectures.
 pub mod estimates;
+mod gate_counts;
+mod schedule;
 /// Models a fault-tolerant quantum computing architecture based on
 /// customizaable gate-based and Majorana qubits, planar codes, and T-factories.
 pub mod system;
 
+pub use gate_counts::{GateCounter, GateCounts};
+pub use schedule::{GateDurations, QubitSchedule, Schedule, Scheduler};
 pub use system::estimate_physical_resources_from_json;
 
 use counts::LogicalCounter;
 use miette::Diagnostic;
-use qsc::interpret::{self, GenericReceiver, Interpreter};
+use qsc::{
+    codegen::circuit::Circuit,
+    interpret::{self, GenericReceiver, Interpreter},
+};
 use system::estimate_physical_resources;
 use thiserror::Error;
 
@@ -73,3 +80,53 @@ pub fn estimate_expr(
     estimate_physical_resources(&counter.logical_resources(), params)
         .map_err(|e| vec![Error::Estimation(e)])
 }
+
+/// Estimates the physical resources of a circuit, such as one built from the
+/// structure of a program by [`qsc::codegen::circuit::from_program`] without
+/// running it. The operations in both branches on a measurement result are
+/// counted, so the estimate covers every outcome.
+pub fn estimate_circuit(circuit: &Circuit, params: &str) -> Result<String, Vec<Error>> {
+    let mut counter = LogicalCounter::default();
+    counter.apply_circuit(circuit);
+    estimate_physical_resources(&counter.logical_resources(), params)
+        .map_err(|e| vec![Error::Estimation(e)])
+}
+
+/// Counts T gates, CNOT gates, and rotations binned by angle precision for
+/// the entry expression, and returns the counts serialized as JSON.
+pub fn count_gates_entry(interpreter: &mut Interpreter) -> Result<String, Vec<Error>> {
+    let mut counter = GateCounter::default();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut counter, &mut out)
+        .map_err(|e| e.into_iter().map(Error::Interpreter).collect::<Vec<_>>())?;
+    Ok(serde_json::to_string(&counter.gate_counts())
+        .expect("serializing to json string should succeed"))
+}
+
+/// Schedules the gates of the entry expression with the gate durations given
+/// as JSON, and returns the estimated total execution time and the busy and
+/// idle time of each qubit serialized as JSON.
+pub fn estimate_schedule_entry(
+    interpreter: &mut Interpreter,
+    durations: &str,
+) -> Result<String, Vec<Error>> {
+    let durations = if durations.is_empty() {
+        GateDurations::default()
+    } else {
+        serde_json::from_str(durations).map_err(|e| {
+            vec![Error::Estimation(system::Error::IO(
+                system::error::IO::CannotParseJSON(e),
+            ))]
+        })?
+    };
+    let mut scheduler = Scheduler::new(durations);
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut scheduler, &mut out)
+        .map_err(|e| e.into_iter().map(Error::Interpreter).collect::<Vec<_>>())?;
+    Ok(serde_json::to_string(&scheduler.schedule())
+        .expect("serializing to json string should succeed"))
+}
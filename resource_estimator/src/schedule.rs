@@ -0,0 +1,248 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc::Backend;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Durations of each gate type, in nanoseconds, used by the [`Scheduler`]
+///
+/// Deserializes from a JSON object in which missing fields take their
+/// default values.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GateDurations {
+    /// Duration of single-qubit Clifford gates (H, S, X, Y, Z)
+    pub single_qubit_clifford: f64,
+    /// Duration of T gates
+    pub t_gate: f64,
+    /// Duration of single-qubit rotations
+    pub rotation: f64,
+    /// Duration of two-qubit gates, including two-qubit rotations
+    pub two_qubit: f64,
+    /// Duration of CCNOT gates
+    pub ccx: f64,
+    /// Duration of measurements, including measurements with reset
+    pub measurement: f64,
+    /// Duration of resets
+    pub reset: f64,
+}
+
+impl Default for GateDurations {
+    fn default() -> Self {
+        Self {
+            single_qubit_clifford: 50.0,
+            t_gate: 50.0,
+            rotation: 50.0,
+            two_qubit: 100.0,
+            ccx: 300.0,
+            measurement: 1000.0,
+            reset: 1000.0,
+        }
+    }
+}
+
+/// Time-domain estimate reported by the [`Scheduler`]
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    /// Estimated total execution time, in nanoseconds
+    pub total_time: f64,
+    /// Timeline summary of each qubit, indexed by qubit id
+    pub qubits: Vec<QubitSchedule>,
+}
+
+/// Timeline summary of a single qubit
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QubitSchedule {
+    /// Time the qubit spends in gates, in nanoseconds
+    pub busy_time: f64,
+    /// Time the qubit spends waiting during the total execution time, in
+    /// nanoseconds
+    pub idle_time: f64,
+}
+
+/// Scheduling backend
+///
+/// Schedules each gate as soon as all of its qubits are available, assuming
+/// that gates on disjoint qubits run in parallel.  Qubit ids are reused after
+/// release, so the timeline of a qubit id spans all of its allocations.
+pub struct Scheduler {
+    durations: GateDurations,
+    /// Time at which each qubit finishes its last gate
+    ready: Vec<f64>,
+    /// Time each qubit spends in gates
+    busy: Vec<f64>,
+    free_list: Vec<usize>,
+    rnd: StdRng,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(GateDurations::default())
+    }
+}
+
+impl Scheduler {
+    #[must_use]
+    pub fn new(durations: GateDurations) -> Self {
+        Self {
+            durations,
+            ready: Vec::new(),
+            busy: Vec::new(),
+            free_list: Vec::new(),
+            rnd: StdRng::seed_from_u64(0),
+        }
+    }
+
+    #[must_use]
+    pub fn schedule(&self) -> Schedule {
+        let total_time = self.ready.iter().copied().fold(0.0, f64::max);
+        Schedule {
+            total_time,
+            qubits: self
+                .busy
+                .iter()
+                .map(|&busy_time| QubitSchedule {
+                    busy_time,
+                    idle_time: total_time - busy_time,
+                })
+                .collect(),
+        }
+    }
+
+    /// Schedules a gate with the given duration on `qubits` after all of
+    /// them have finished their previous gates.
+    fn schedule_gate(&mut self, duration: f64, qubits: &[usize]) {
+        let start = qubits.iter().map(|&q| self.ready[q]).fold(0.0, f64::max);
+        for &q in qubits {
+            self.ready[q] = start + duration;
+            self.busy[q] += duration;
+        }
+    }
+}
+
+impl Backend for Scheduler {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.schedule_gate(self.durations.ccx, &[ctl0, ctl1, q]);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.schedule_gate(self.durations.two_qubit, &[ctl, q]);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.schedule_gate(self.durations.two_qubit, &[ctl, q]);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.schedule_gate(self.durations.two_qubit, &[ctl, q]);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.schedule_gate(self.durations.single_qubit_clifford, &[q]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.schedule_gate(self.durations.measurement, &[q]);
+        self.rnd.gen_bool(0.5)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.m(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.schedule_gate(self.durations.reset, &[q]);
+    }
+
+    fn rx(&mut self, _theta: f64, q: usize) {
+        self.schedule_gate(self.durations.rotation, &[q]);
+    }
+
+    fn rxx(&mut self, _theta: f64, q0: usize, q1: usize) {
+        self.schedule_gate(self.durations.two_qubit, &[q0, q1]);
+    }
+
+    fn ry(&mut self, _theta: f64, q: usize) {
+        self.schedule_gate(self.durations.rotation, &[q]);
+    }
+
+    fn ryy(&mut self, _theta: f64, q0: usize, q1: usize) {
+        self.schedule_gate(self.durations.two_qubit, &[q0, q1]);
+    }
+
+    fn rz(&mut self, _theta: f64, q: usize) {
+        self.schedule_gate(self.durations.rotation, &[q]);
+    }
+
+    fn rzz(&mut self, _theta: f64, q0: usize, q1: usize) {
+        self.schedule_gate(self.durations.two_qubit, &[q0, q1]);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.schedule_gate(self.durations.single_qubit_clifford, &[q]);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.schedule_gate(self.durations.single_qubit_clifford, &[q]);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.schedule_gate(self.durations.two_qubit, &[q0, q1]);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.schedule_gate(self.durations.t_gate, &[q]);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.schedule_gate(self.durations.t_gate, &[q]);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.schedule_gate(self.durations.single_qubit_clifford, &[q]);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.schedule_gate(self.durations.single_qubit_clifford, &[q]);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.schedule_gate(self.durations.single_qubit_clifford, &[q]);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        if let Some(index) = self.free_list.pop() {
+            index
+        } else {
+            self.ready.push(0.0);
+            self.busy.push(0.0);
+            self.ready.len() - 1
+        }
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.free_list.push(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        (Vec::new(), 0)
+    }
+
+    fn qubit_is_zero(&mut self, _q: usize) -> bool {
+        true
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.rnd = StdRng::seed_from_u64(seed.unwrap_or_default());
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use std::convert::Into;
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc::{
+    interpret::{GenericReceiver, Interpreter},
+    target::Profile,
+    LanguageFeatures, PackageType, SourceMap,
+};
+
+use super::{GateDurations, Scheduler};
+
+fn verify_schedule(source: &str, durations: GateDurations, expect: &Expect) {
+    let source_map = SourceMap::new([("test".into(), source.into())], None);
+    let mut interpreter = Interpreter::new(
+        true,
+        source_map,
+        PackageType::Exe,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    )
+    .expect("compilation should succeed");
+    let mut scheduler = Scheduler::new(durations);
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .eval_entry_with_sim(&mut scheduler, &mut out)
+        .expect("evaluation should succeed");
+    expect.assert_debug_eq(&scheduler.schedule());
+}
+
+#[test]
+fn parallel_gates_are_scheduled_together() {
+    verify_schedule(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use qs = Qubit[3];
+                    H(qs[0]);
+                    T(qs[1]);
+                    CNOT(qs[0], qs[1]);
+                    X(qs[2]);
+                    ResetAll(qs);
+                }
+            }
+        "},
+        GateDurations::default(),
+        &expect![[r#"
+            Schedule {
+                total_time: 1150.0,
+                qubits: [
+                    QubitSchedule {
+                        busy_time: 1150.0,
+                        idle_time: 0.0,
+                    },
+                    QubitSchedule {
+                        busy_time: 1150.0,
+                        idle_time: 0.0,
+                    },
+                    QubitSchedule {
+                        busy_time: 1050.0,
+                        idle_time: 100.0,
+                    },
+                ],
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn durations_are_configurable() {
+    verify_schedule(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    Rz(1.0, q0);
+                    Rz(1.0, q0);
+                    CZ(q0, q1);
+                    Reset(q0);
+                    Reset(q1);
+                }
+            }
+        "},
+        GateDurations {
+            rotation: 20.0,
+            two_qubit: 200.0,
+            reset: 10.0,
+            ..GateDurations::default()
+        },
+        &expect![[r#"
+            Schedule {
+                total_time: 250.0,
+                qubits: [
+                    QubitSchedule {
+                        busy_time: 250.0,
+                        idle_time: 0.0,
+                    },
+                    QubitSchedule {
+                        busy_time: 210.0,
+                        idle_time: 40.0,
+                    },
+                ],
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn durations_deserialize_with_defaults() {
+    let durations: GateDurations =
+        serde_json::from_str(r#"{"twoQubit": 40.0, "measurement": 500.0}"#)
+            .expect("durations should deserialize");
+    assert_eq!(
+        durations,
+        GateDurations {
+            two_qubit: 40.0,
+            measurement: 500.0,
+            ..GateDurations::default()
+        }
+    );
+}
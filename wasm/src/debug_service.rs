@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use std::num::NonZeroU32;
 use std::str::FromStr;
 
 use qsc::fir::StmtId;
@@ -65,6 +66,10 @@ impl DebugService {
         QuantumStateList { entries }.into()
     }
 
+    pub fn disassemble(&self) -> Option<String> {
+        self.debugger().disassemble()
+    }
+
     pub fn get_stack_frames(&self) -> IStackFrameList {
         let frames = self.debugger().get_stack_frames();
 
@@ -72,7 +77,12 @@ impl DebugService {
             frames: frames
                 .iter()
                 .map(|s| StackFrame {
-                    name: format!("{} {}", s.name, s.functor),
+                    name: format!(
+                        "{}{} {}",
+                        s.name,
+                        s.args.as_deref().unwrap_or_default(),
+                        s.functor
+                    ),
                     path: s.path.clone(),
                     range: s.range.into(),
                 })
@@ -81,6 +91,34 @@ impl DebugService {
         .into()
     }
 
+    pub fn set_break_on_fail(&mut self, break_on_fail: bool) {
+        self.debugger_mut().set_break_on_fail(break_on_fail);
+    }
+
+    pub fn set_logpoint(&mut self, id: u32, template: &str) {
+        self.debugger_mut().set_logpoint(StmtId::from(id), template);
+    }
+
+    pub fn clear_logpoints(&mut self) {
+        self.debugger_mut().clear_logpoints();
+    }
+
+    pub fn set_hit_condition(&mut self, id: u32, condition: &str) -> Result<(), JsValue> {
+        let condition = condition.parse().map_err(|e: String| JsError::new(&e))?;
+        self.debugger_mut()
+            .set_hit_condition(StmtId::from(id), condition);
+        Ok(())
+    }
+
+    pub fn clear_hit_conditions(&mut self) {
+        self.debugger_mut().clear_hit_conditions();
+    }
+
+    pub fn set_yield_interval(&mut self, interval: u32) {
+        self.debugger_mut()
+            .set_yield_interval(NonZeroU32::new(interval));
+    }
+
     pub fn eval_next(
         &mut self,
         event_cb: &js_sys::Function,
@@ -191,6 +229,26 @@ impl DebugService {
         .into()
     }
 
+    pub fn resolve_breakpoints(&self, path: &str, line: u32, column: u32) -> IBreakpointSpanList {
+        let debugger = self.debugger();
+        let bps = debugger.resolve_breakpoints(
+            debugger.source_package_id(),
+            path,
+            qsc::line_column::Position { line, column },
+        );
+
+        BreakpointSpanList {
+            spans: bps
+                .iter()
+                .map(|s| BreakpointSpan {
+                    id: s.id,
+                    range: s.range.into(),
+                })
+                .collect(),
+        }
+        .into()
+    }
+
     pub fn get_locals(&self) -> IVariableList {
         let locals = self.debugger().get_locals();
         let variables: Vec<_> = locals
@@ -249,6 +307,14 @@ impl From<StepResult> for StructStepResult {
                 id: StepResultId::StepOut.into(),
                 value: 0,
             },
+            StepResult::ExceptionHit(_) => StructStepResult {
+                id: StepResultId::ExceptionHit.into(),
+                value: 0,
+            },
+            StepResult::Yield => StructStepResult {
+                id: StepResultId::Yield.into(),
+                value: 0,
+            },
             StepResult::Return(_) => StructStepResult {
                 id: StepResultId::Return.into(),
                 value: 0,
@@ -265,6 +331,8 @@ pub enum StepResultId {
     StepIn = 2,
     StepOut = 3,
     Return = 4,
+    ExceptionHit = 5,
+    Yield = 6,
 }
 
 impl From<StepResultId> for usize {
@@ -54,9 +54,13 @@ impl VSDiagnostic {
             interpret::Error::Compile(e) => error_labels(e),
             interpret::Error::Pass(e) => error_labels(e),
             interpret::Error::Eval(e) => error_labels(e.error()),
-            interpret::Error::NoEntryPoint | interpret::Error::UnsupportedRuntimeCapabilities => {
-                Vec::new()
-            }
+            interpret::Error::NoEntryPoint
+            | interpret::Error::EntryArgCount(..)
+            | interpret::Error::EntryArgType(..)
+            | interpret::Error::EntryArgJson
+            | interpret::Error::UnsupportedRuntimeCapabilities
+            | interpret::Error::UnitaryTooLarge(..)
+            | interpret::Error::NoStateDump => Vec::new(),
         };
 
         Self::new(labels, source_name, err)
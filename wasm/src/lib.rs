@@ -5,15 +5,14 @@
 
 use diagnostic::VSDiagnostic;
 use katas::check_solution;
-use num_bigint::BigUint;
-use num_complex::Complex64;
 use project_system::into_async_rust_fn_with;
 use qsc::{
-    compile, format_state_id, get_latex,
+    compile, get_latex,
     hir::PackageId,
     interpret::{
         self,
-        output::{self, Receiver},
+        output::{self, Receiver, StateDump},
+        ResultBits,
     },
     target::Profile,
     LanguageFeatures, PackageStore, PackageType, SourceContents, SourceMap, SourceName, SparseSim,
@@ -88,7 +87,7 @@ fn _get_qir(sources: SourceMap, language_features: LanguageFeatures) -> Result<S
 
     let (unit, errors) = qsc::compile::compile(
         &store,
-        &[std],
+        &[(std, None)],
         sources,
         PackageType::Exe,
         Profile::Base.into(),
@@ -159,7 +158,7 @@ pub fn get_hir(code: &str, language_features: Vec<String>) -> Result<String, Str
     let package = STORE_CORE_STD.with(|(store, std)| {
         let (unit, _) = compile::compile(
             store,
-            &[*std],
+            &[(*std, None)],
             sources,
             PackageType::Exe,
             Profile::Unrestricted.into(),
@@ -181,37 +180,34 @@ impl<F> Receiver for CallbackReceiver<F>
 where
     F: FnMut(&str),
 {
-    fn state(
-        &mut self,
-        state: Vec<(BigUint, Complex64)>,
-        qubit_count: usize,
-    ) -> Result<(), output::Error> {
+    fn state_dump(&mut self, mut dump: StateDump) -> Result<(), output::Error> {
         let mut dump_json = String::new();
         write!(dump_json, r#"{{"type": "DumpMachine","state": {{"#)
             .expect("writing to string should succeed");
-        let (last, most) = state
-            .split_last()
-            .expect("state should always have at least one entry");
-        for state in most {
-            write!(
-                dump_json,
-                r#""{}": [{}, {}],"#,
-                format_state_id(&state.0, qubit_count),
-                state.1.re,
-                state.1.im
-            )
-            .expect("writing to string should succeed");
+        // LaTeX is only rendered for states of at most 16 amplitudes, so no more than one past that
+        // is kept while streaming.
+        let mut latex_state = Vec::new();
+        let mut first = true;
+        while let Some(chunk) = dump.next_chunk() {
+            for entry in chunk {
+                if !first {
+                    dump_json.push(',');
+                }
+                first = false;
+                write!(
+                    dump_json,
+                    r#""{}": [{}, {}]"#,
+                    entry.label, entry.amplitude.re, entry.amplitude.im
+                )
+                .expect("writing to string should succeed");
+                if latex_state.len() <= 16 {
+                    latex_state.push((entry.id, entry.amplitude));
+                }
+            }
         }
-        write!(
-            dump_json,
-            r#""{}": [{}, {}]}}, "#,
-            format_state_id(&last.0, qubit_count),
-            last.1.re,
-            last.1.im
-        )
-        .expect("writing to string should succeed");
+        write!(dump_json, "}}, ").expect("writing to string should succeed");
 
-        let json_latex = serde_json::to_string(&get_latex(&state, qubit_count))
+        let json_latex = serde_json::to_string(&get_latex(&latex_state, dump.qubit_count()))
             .expect("serialization should succeed");
         write!(dump_json, r#" "stateLatex": {json_latex} }} "#)
             .expect("writing to string should succeed");
@@ -265,7 +261,11 @@ where
         let result = interpreter.eval_entry_with_sim(&mut SparseSim::new(), &mut out);
         let mut success = true;
         let msg: serde_json::Value = match result {
-            Ok(value) => serde_json::Value::String(value.to_string()),
+            // Arrays of results are reported as bitstrings, which keep histogram keys short.
+            Ok(value) => serde_json::Value::String(match ResultBits::try_from(&value) {
+                Ok(bits) if !bits.is_empty() => bits.to_string(),
+                _ => value.to_string(),
+            }),
             Err(errors) => {
                 // TODO: handle multiple errors
                 // https://github.com/microsoft/qsharp/issues/149